@@ -0,0 +1,124 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! A minimal client for the Key Vault keys REST API.
+
+use crate::error::{self, Result};
+use azure_core::auth::TokenCredential;
+use azure_identity::DefaultAzureCredential;
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+use std::thread;
+use url::Url;
+
+/// The Key Vault REST API version used for requests.
+const API_VERSION: &str = "7.4";
+
+/// The scope of access tokens for Key Vault.
+const KEY_VAULT_SCOPE: &str = "https://vault.azure.net/.default";
+
+/// A key as returned by Key Vault, in JSON Web Key format. Binary fields are base64url encoded.
+#[derive(Debug, Deserialize)]
+pub(crate) struct JsonWebKey {
+    pub(crate) kty: String,
+    pub(crate) n: Option<String>,
+    pub(crate) e: Option<String>,
+    pub(crate) crv: Option<String>,
+    pub(crate) x: Option<String>,
+    pub(crate) y: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KeyBundle {
+    key: JsonWebKey,
+}
+
+#[derive(Debug, Serialize)]
+struct SignRequest<'a> {
+    alg: &'a str,
+    value: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct KeyOperationResult {
+    value: String,
+}
+
+/// Sends requests for one version of a key in Key Vault.
+pub(crate) struct KeyVaultClient {
+    http: Client,
+    key_url: Url,
+    access_token: String,
+}
+
+impl KeyVaultClient {
+    /// Creates a client for the key at `<vault_url>/keys/<key_name>/<key_version>`.
+    pub(crate) fn new(
+        vault_url: &Url,
+        key_name: &str,
+        key_version: &str,
+        access_token: String,
+    ) -> Result<Self> {
+        let mut key_url = vault_url.clone();
+        key_url
+            .path_segments_mut()
+            .map_err(|()| error::Error::InvalidUrl {
+                url: vault_url.to_string(),
+            })?
+            .pop_if_empty()
+            .extend(["keys", key_name, key_version]);
+        key_url
+            .query_pairs_mut()
+            .append_pair("api-version", API_VERSION);
+        Ok(Self {
+            http: Client::builder().build().context(error::HttpClientSnafu)?,
+            key_url,
+            access_token,
+        })
+    }
+
+    /// Gets the public part of the key.
+    pub(crate) fn get_key(&self) -> reqwest::Result<JsonWebKey> {
+        self.http
+            .get(self.key_url.clone())
+            .bearer_auth(&self.access_token)
+            .send()?
+            .error_for_status()?
+            .json::<KeyBundle>()
+            .map(|bundle| bundle.key)
+    }
+
+    /// Signs a digest with the key using the JSON Web Algorithm `alg`, e.g. `PS256`. The digest
+    /// and the returned signature are base64url encoded.
+    pub(crate) fn sign(&self, alg: &str, digest: &str) -> reqwest::Result<String> {
+        let mut sign_url = self.key_url.clone();
+        if let Ok(mut segments) = sign_url.path_segments_mut() {
+            segments.push("sign");
+        }
+        self.http
+            .post(sign_url)
+            .bearer_auth(&self.access_token)
+            .json(&SignRequest { alg, value: digest })
+            .send()?
+            .error_for_status()?
+            .json::<KeyOperationResult>()
+            .map(|result| result.value)
+    }
+}
+
+/// Gets an access token for Key Vault from the default Azure credential chain.
+pub(crate) fn default_access_token() -> Result<String> {
+    // We need to spin up a new thread to deal with the async nature of the
+    // Azure SDK for Rust
+    thread::spawn(|| {
+        let runtime = tokio::runtime::Runtime::new().context(error::RuntimeCreationSnafu)?;
+        let credential = DefaultAzureCredential::default();
+        let token = runtime
+            .block_on(credential.get_token(&[KEY_VAULT_SCOPE]))
+            .context(error::AccessTokenSnafu)?;
+        Ok(token.token.secret().to_owned())
+    })
+    .join()
+    .map_err(|_| error::Error::ThreadJoin {})?
+}
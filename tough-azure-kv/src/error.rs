@@ -0,0 +1,120 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Contains the error type for this library.
+
+#![allow(clippy::default_trait_access)]
+
+use snafu::{Backtrace, Snafu};
+
+/// Alias for `Result<T, Error>`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The error type for this library.
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+#[non_exhaustive]
+#[allow(missing_docs)]
+pub enum Error {
+    /// The library failed to instantiate 'tokio Runtime'.
+    #[snafu(display("Unable to create tokio runtime: {}", source))]
+    RuntimeCreation {
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+
+    /// The library failed to join 'tokio Runtime'.
+    #[snafu(display("Unable to join tokio thread used to offload async workloads"))]
+    ThreadJoin,
+
+    /// The key source URL isn't of the form `azurekv://<vault>/<key>/<version>`
+    #[snafu(display(
+        "Invalid Key Vault key URL '{}', expected azurekv://<vault>/<key>/<version>",
+        url
+    ))]
+    InvalidUrl { url: String },
+
+    /// The Key Vault URL couldn't be built from the vault name
+    #[snafu(display("Invalid Key Vault URL '{}': {}", url, source))]
+    VaultUrl {
+        url: String,
+        source: url::ParseError,
+    },
+
+    /// No access token could be obtained from the default Azure credential chain
+    #[snafu(display("Failed to get an access token for Key Vault: {}", source))]
+    AccessToken {
+        source: azure_core::Error,
+        backtrace: Backtrace,
+    },
+
+    /// The HTTP client couldn't be built
+    #[snafu(display("Failed to create HTTP client: {}", source))]
+    HttpClient {
+        source: reqwest::Error,
+        backtrace: Backtrace,
+    },
+
+    /// The library failed to get the public key from Key Vault
+    #[snafu(display(
+        "Failed to get public key for azurekv://{}/{}/{}: {}",
+        vault,
+        key_name,
+        key_version,
+        source
+    ))]
+    GetKey {
+        vault: String,
+        key_name: String,
+        key_version: String,
+        source: reqwest::Error,
+        backtrace: Backtrace,
+    },
+
+    /// The library failed to get the message signature from Key Vault
+    #[snafu(display(
+        "Error while signing message for azurekv://{}/{}/{}: {}",
+        vault,
+        key_name,
+        key_version,
+        source
+    ))]
+    SignMessage {
+        vault: String,
+        key_name: String,
+        key_version: String,
+        source: reqwest::Error,
+        backtrace: Backtrace,
+    },
+
+    /// The key's type or curve can't sign TUF metadata
+    #[snafu(display("Unsupported Key Vault key type '{}'", key_type))]
+    UnsupportedKeyType { key_type: String },
+
+    /// A field of the key returned by Key Vault is missing
+    #[snafu(display("Found public key in Key Vault, but its '{}' field is missing", field))]
+    MissingKeyField { field: &'static str },
+
+    /// A base64url value returned by Key Vault couldn't be decoded
+    #[snafu(display("Failed to decode '{}' returned by Key Vault: {}", field, source))]
+    Base64Decode {
+        field: &'static str,
+        source: base64::DecodeError,
+    },
+
+    #[snafu(display(
+        "Signature is too long, modulus_size_bytes: {}, signature_size_bytes: {}",
+        modulus_size_bytes,
+        signature_size_bytes
+    ))]
+    SignatureTooLong {
+        modulus_size_bytes: usize,
+        signature_size_bytes: usize,
+    },
+
+    #[snafu(display(
+        "ECDSA P-256 signature should be 64 bytes, but Key Vault returned {} bytes",
+        signature_size_bytes
+    ))]
+    EcdsaSignatureLength { signature_size_bytes: usize },
+}
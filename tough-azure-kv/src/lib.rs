@@ -0,0 +1,433 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! tough-azure-kv implements the `KeySource` trait found in [tough, a Rust TUF client](https://github.com/awslabs/tough).
+//!
+//! By implementing this trait, Azure Key Vault can become a source of keys used to sign a [TUF repository](https://theupdateframework.github.io/).
+//!
+//! Keys are addressed as `azurekv://<vault name>/<key name>/<key version>`. RSA keys sign with
+//! `rsassa-pss-sha256`, and EC keys on the P-256 curve sign with `ecdsa-sha2-nistp256`. Requests
+//! are authenticated with an access token from the default Azure credential chain.
+//!
+//! # Testing
+//!
+//! Unit tests are run in the usual manner: `cargo test`.
+
+#![forbid(missing_debug_implementations, missing_copy_implementations)]
+#![deny(rust_2018_idioms)]
+// missing_docs is on its own line to make it easy to comment out when making changes.
+#![deny(missing_docs)]
+#![warn(clippy::pedantic)]
+#![allow(
+    clippy::module_name_repetitions,
+    clippy::must_use_candidate,
+    clippy::missing_errors_doc
+)]
+
+mod client;
+pub mod error;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use client::{JsonWebKey, KeyVaultClient};
+use ring::digest::{digest, SHA256};
+use ring::rand::SecureRandom;
+use snafu::{ensure, OptionExt, ResultExt};
+use std::collections::HashMap;
+use std::fmt;
+use tough::key_source::KeySource;
+use tough::schema::key::{EcdsaKey, EcdsaScheme, Key, RsaKey, RsaScheme};
+use tough::sign::Sign;
+use url::Url;
+
+/// The URL scheme of Key Vault key sources.
+pub const URL_SCHEME: &str = "azurekv";
+
+/// Implements the `KeySource` trait for keys that live in Azure Key Vault
+pub struct AzureKeyVaultKeySource {
+    /// The name of the Key Vault holding the key.
+    pub vault: String,
+    /// The name of the key.
+    pub key_name: String,
+    /// The version of the key.
+    pub key_version: String,
+    /// The base URL of the Key Vault, if not `https://<vault>.vault.azure.net`.
+    pub vault_url: Option<Url>,
+    /// The access token to send with Key Vault requests. If not provided, one is obtained from
+    /// the default Azure credential chain.
+    pub access_token: Option<String>,
+}
+
+impl AzureKeyVaultKeySource {
+    /// Creates a key source from a URL of the form `azurekv://<vault>/<key>/<version>`.
+    pub fn from_url(url: &Url) -> error::Result<Self> {
+        let invalid = || error::Error::InvalidUrl {
+            url: url.to_string(),
+        };
+        ensure!(
+            url.scheme() == URL_SCHEME,
+            error::InvalidUrlSnafu { url: url.as_str() }
+        );
+        let vault = url
+            .host_str()
+            .filter(|vault| !vault.is_empty())
+            .ok_or_else(invalid)?;
+        let segments: Vec<_> = url.path_segments().ok_or_else(invalid)?.collect();
+        match segments.as_slice() {
+            [key_name, key_version] if !key_name.is_empty() && !key_version.is_empty() => {
+                Ok(Self {
+                    vault: vault.to_owned(),
+                    key_name: (*key_name).to_owned(),
+                    key_version: (*key_version).to_owned(),
+                    vault_url: None,
+                    access_token: None,
+                })
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    fn client(&self) -> error::Result<KeyVaultClient> {
+        let vault_url = match &self.vault_url {
+            Some(vault_url) => vault_url.clone(),
+            None => {
+                let url = format!("https://{}.vault.azure.net", self.vault);
+                Url::parse(&url).context(error::VaultUrlSnafu { url })?
+            }
+        };
+        let access_token = match &self.access_token {
+            Some(access_token) => access_token.clone(),
+            None => client::default_access_token()?,
+        };
+        KeyVaultClient::new(&vault_url, &self.key_name, &self.key_version, access_token)
+    }
+}
+
+impl fmt::Debug for AzureKeyVaultKeySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AzureKeyVaultKeySource")
+            .field("vault", &self.vault)
+            .field("key_name", &self.key_name)
+            .field("key_version", &self.key_version)
+            .field("vault_url", &self.vault_url)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Implement the `KeySource` trait.
+impl KeySource for AzureKeyVaultKeySource {
+    fn as_sign(
+        &self,
+    ) -> std::result::Result<Box<dyn Sign>, Box<dyn std::error::Error + Send + Sync + 'static>>
+    {
+        let client = self.client()?;
+        // Get the public key from Key Vault
+        let jwk = client.get_key().context(error::GetKeySnafu {
+            vault: &self.vault,
+            key_name: &self.key_name,
+            key_version: &self.key_version,
+        })?;
+        let (public_key, algorithm) = parse_public_key(&jwk)?;
+        Ok(Box::new(AzureKeyVaultKey {
+            vault: self.vault.clone(),
+            key_name: self.key_name.clone(),
+            key_version: self.key_version.clone(),
+            client,
+            public_key,
+            algorithm,
+        }))
+    }
+
+    fn write(
+        &self,
+        _value: &str,
+        _key_id_hex: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        Ok(())
+    }
+}
+
+/// How a Key Vault key signs, as determined by its type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SigningAlgorithm {
+    /// `PS256`, with the size of the RSA key modulus in bytes.
+    RsassaPssSha256 { modulus_size_bytes: usize },
+    /// `ES256`
+    EcdsaP256Sha256,
+}
+
+impl SigningAlgorithm {
+    /// The JSON Web Algorithm name used by Key Vault.
+    fn name(self) -> &'static str {
+        match self {
+            SigningAlgorithm::RsassaPssSha256 { .. } => "PS256",
+            SigningAlgorithm::EcdsaP256Sha256 => "ES256",
+        }
+    }
+}
+
+/// Implements the Sign trait for a Key Vault key
+pub struct AzureKeyVaultKey {
+    /// The name of the Key Vault holding the key
+    vault: String,
+    /// The name of the key
+    key_name: String,
+    /// The version of the key
+    key_version: String,
+    /// Client for the key's Key Vault REST API
+    client: KeyVaultClient,
+    /// Public key corresponding to the Key Vault key
+    public_key: Key,
+    /// Signing algorithm to be used for the key
+    algorithm: SigningAlgorithm,
+}
+
+impl fmt::Debug for AzureKeyVaultKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AzureKeyVaultKey")
+            .field("vault", &self.vault)
+            .field("key_name", &self.key_name)
+            .field("key_version", &self.key_version)
+            .field("algorithm", &self.algorithm)
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Sign for AzureKeyVaultKey {
+    fn tuf_key(&self) -> Key {
+        self.public_key.clone()
+    }
+
+    fn sign(
+        &self,
+        msg: &[u8],
+        _rng: &dyn SecureRandom,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        let digest = URL_SAFE_NO_PAD.encode(digest(&SHA256, msg));
+        let signature =
+            self.client
+                .sign(self.algorithm.name(), &digest)
+                .context(error::SignMessageSnafu {
+                    vault: &self.vault,
+                    key_name: &self.key_name,
+                    key_version: &self.key_version,
+                })?;
+        let signature = decode_base64url("value", &signature)?;
+        let signature = match self.algorithm {
+            // Like other key stores, Key Vault may return RSA signatures shorter than the modulus,
+            // which ring won't verify unless they are padded with zeros to the modulus length.
+            SigningAlgorithm::RsassaPssSha256 { modulus_size_bytes } => {
+                pad_signature(signature, modulus_size_bytes)?
+            }
+            // Key Vault returns the raw `r || s` form, while TUF expects ASN.1 DER.
+            SigningAlgorithm::EcdsaP256Sha256 => ecdsa_signature_der(&signature)?,
+        };
+        Ok(signature)
+    }
+}
+
+/// Builds the TUF public key and the signing algorithm for a key returned by Key Vault.
+fn parse_public_key(jwk: &JsonWebKey) -> error::Result<(Key, SigningAlgorithm)> {
+    match jwk.kty.as_str() {
+        "RSA" | "RSA-HSM" => {
+            let n = decode_key_field("n", jwk.n.as_deref())?;
+            let e = decode_key_field("e", jwk.e.as_deref())?;
+            let modulus_size_bytes = strip_leading_zeros(&n).len();
+            let key = Key::Rsa {
+                keyval: RsaKey {
+                    public: rsa_public_key_der(&n, &e).into(),
+                    _extra: HashMap::new(),
+                },
+                scheme: RsaScheme::RsassaPssSha256,
+                _extra: HashMap::new(),
+            };
+            Ok((
+                key,
+                SigningAlgorithm::RsassaPssSha256 { modulus_size_bytes },
+            ))
+        }
+        "EC" | "EC-HSM" => {
+            let crv = jwk
+                .crv
+                .as_deref()
+                .context(error::MissingKeyFieldSnafu { field: "crv" })?;
+            ensure!(
+                crv == "P-256",
+                error::UnsupportedKeyTypeSnafu {
+                    key_type: format!("{} {}", jwk.kty, crv),
+                }
+            );
+            let x = decode_key_field("x", jwk.x.as_deref())?;
+            let y = decode_key_field("y", jwk.y.as_deref())?;
+            // The uncompressed point encoding: 0x04, then the coordinates
+            let mut point = vec![0x04];
+            point.extend(x);
+            point.extend(y);
+            let key = Key::Ecdsa {
+                keyval: EcdsaKey {
+                    public: point.into(),
+                    _extra: HashMap::new(),
+                },
+                scheme: EcdsaScheme::EcdsaSha2Nistp256,
+                _extra: HashMap::new(),
+            };
+            Ok((key, SigningAlgorithm::EcdsaP256Sha256))
+        }
+        other => error::UnsupportedKeyTypeSnafu { key_type: other }.fail(),
+    }
+}
+
+/// Decodes a field of a key returned by Key Vault, which must be present.
+fn decode_key_field(field: &'static str, value: Option<&str>) -> error::Result<Vec<u8>> {
+    decode_base64url(field, value.context(error::MissingKeyFieldSnafu { field })?)
+}
+
+/// Decodes a base64url value returned by Key Vault, with or without padding.
+fn decode_base64url(field: &'static str, value: &str) -> error::Result<Vec<u8>> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .context(error::Base64DecodeSnafu { field })
+}
+
+fn strip_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes
+        .iter()
+        .position(|&b| b != 0)
+        .unwrap_or(bytes.len().saturating_sub(1));
+    &bytes[start..]
+}
+
+/// Encodes the length of an ASN.1 DER value.
+#[allow(clippy::cast_possible_truncation)]
+fn der_length(len: usize) -> Vec<u8> {
+    if len < 0x80 {
+        return vec![len as u8];
+    }
+    let bytes: Vec<u8> = len
+        .to_be_bytes()
+        .iter()
+        .copied()
+        .skip_while(|&b| b == 0)
+        .collect();
+    let mut encoded = vec![0x80 | bytes.len() as u8];
+    encoded.extend(bytes);
+    encoded
+}
+
+/// Encodes an ASN.1 DER value with the given tag.
+fn der_value(tag: u8, contents: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![tag];
+    encoded.extend(der_length(contents.len()));
+    encoded.extend_from_slice(contents);
+    encoded
+}
+
+/// Encodes a big-endian unsigned integer as an ASN.1 DER `INTEGER`.
+fn der_unsigned_integer(bytes: &[u8]) -> Vec<u8> {
+    let bytes = strip_leading_zeros(bytes);
+    let mut contents = Vec::with_capacity(bytes.len() + 1);
+    // A set high bit would make the integer negative
+    if bytes.first().map_or(true, |&b| b & 0x80 != 0) {
+        contents.push(0);
+    }
+    contents.extend_from_slice(bytes);
+    der_value(0x02, &contents)
+}
+
+/// Encodes an `RSAPublicKey` (RFC 8017) from its modulus and public exponent.
+fn rsa_public_key_der(n: &[u8], e: &[u8]) -> Vec<u8> {
+    let mut contents = der_unsigned_integer(n);
+    contents.extend(der_unsigned_integer(e));
+    der_value(0x30, &contents)
+}
+
+/// Converts a raw `r || s` ECDSA P-256 signature to an ASN.1 DER `Ecdsa-Sig-Value`.
+fn ecdsa_signature_der(signature: &[u8]) -> error::Result<Vec<u8>> {
+    ensure!(
+        signature.len() == 64,
+        error::EcdsaSignatureLengthSnafu {
+            signature_size_bytes: signature.len()
+        }
+    );
+    let (r, s) = signature.split_at(32);
+    let mut contents = der_unsigned_integer(r);
+    contents.extend(der_unsigned_integer(s));
+    Ok(der_value(0x30, &contents))
+}
+
+/// * If the length of `signature` is less than `modulus_size_bytes`, this function will prepend the
+///   `signature` with zeros so that `signature.len() == modulus_size_bytes`.
+/// * If the `signature` already the same length as `modulus_size_bytes` then `signature` is
+///   returned unchanged.
+/// * If the `signature` is longer than `modulus_size_bytes`, an error is returned.
+fn pad_signature(mut signature: Vec<u8>, modulus_size_bytes: usize) -> error::Result<Vec<u8>> {
+    ensure!(
+        signature.len() <= modulus_size_bytes,
+        error::SignatureTooLongSnafu {
+            modulus_size_bytes,
+            signature_size_bytes: signature.len()
+        },
+    );
+    let padding_size: usize = modulus_size_bytes - signature.len();
+    signature.splice(..0, [0].repeat(padding_size));
+    Ok(signature)
+}
+
+// =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=
+
+#[test]
+fn from_url_parts() {
+    let source = AzureKeyVaultKeySource::from_url(
+        &Url::parse("azurekv://my-vault/tuf-root/0123abcd").unwrap(),
+    )
+    .unwrap();
+    assert_eq!(source.vault, "my-vault");
+    assert_eq!(source.key_name, "tuf-root");
+    assert_eq!(source.key_version, "0123abcd");
+}
+
+#[test]
+fn from_url_missing_version() {
+    for url in [
+        "azurekv://my-vault/tuf-root",
+        "azurekv://my-vault/tuf-root/",
+        "azurekv:///tuf-root/0123abcd",
+        "azurekv://my-vault/a/b/c",
+        "aws-kms://my-vault/tuf-root/0123abcd",
+    ] {
+        assert!(AzureKeyVaultKeySource::from_url(&Url::parse(url).unwrap()).is_err());
+    }
+}
+
+#[test]
+fn der_length_long_form() {
+    assert_eq!(der_length(0x7f), [0x7f]);
+    assert_eq!(der_length(0x80), [0x81, 0x80]);
+    assert_eq!(der_length(0x0101), [0x82, 0x01, 0x01]);
+}
+
+#[test]
+fn der_unsigned_integer_sign_bit() {
+    assert_eq!(der_unsigned_integer(&[0x00, 0x7f]), [0x02, 0x01, 0x7f]);
+    assert_eq!(der_unsigned_integer(&[0x80]), [0x02, 0x02, 0x00, 0x80]);
+    assert_eq!(der_unsigned_integer(&[0x00, 0x00]), [0x02, 0x01, 0x00]);
+}
+
+#[test]
+fn ecdsa_signature_wrong_length() {
+    assert!(ecdsa_signature_der(&[1; 63]).is_err());
+}
+
+#[test]
+fn pad_signature_short_by_two() {
+    let signature: Vec<u8> = vec![1, 2, 3, 4];
+    let expected: Vec<u8> = vec![0, 0, 1, 2, 3, 4];
+    let actual = pad_signature(signature, 6).unwrap();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn pad_signature_too_long() {
+    assert!(pad_signature(vec![1, 2, 3, 4, 5], 4).is_err());
+}
@@ -0,0 +1,183 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use httptest::{all_of, matchers::*, responders::*, Expectation, Server};
+use ring::digest::{digest, SHA256};
+use ring::rand::SystemRandom;
+use ring::signature::{
+    EcdsaKeyPair, KeyPair, RsaKeyPair, UnparsedPublicKey, ECDSA_P256_SHA256_ASN1,
+    ECDSA_P256_SHA256_FIXED_SIGNING, RSA_PSS_2048_8192_SHA256, RSA_PSS_SHA256,
+};
+use serde_json::json;
+use std::path::PathBuf;
+use tough::key_source::{KeySource, LocalKeySource};
+use tough::schema::key::Key;
+use tough_azure_kv::AzureKeyVaultKeySource;
+use url::Url;
+
+const MESSAGE: &[u8] = b"Hello, Key Vault";
+const KEY_PATH: &str = "/keys/tuf-root/0123abcd";
+
+fn snakeoil() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join("tough")
+        .join("tests")
+        .join("data")
+        .join("snakeoil.pem")
+}
+
+fn key_source(server: &Server) -> AzureKeyVaultKeySource {
+    let mut source = AzureKeyVaultKeySource::from_url(
+        &Url::parse("azurekv://my-vault/tuf-root/0123abcd").unwrap(),
+    )
+    .unwrap();
+    source.vault_url = Some(Url::parse(&server.url_str("/")).unwrap());
+    source.access_token = Some("test-token".to_owned());
+    source
+}
+
+/// Expect one authenticated request for the key, returning `key` as its JSON Web Key.
+fn expect_get_key(server: &Server, key: serde_json::Value) {
+    server.expect(
+        Expectation::matching(all_of![
+            request::method_path("GET", KEY_PATH),
+            request::query(url_decoded(contains(("api-version", "7.4")))),
+            request::headers(contains(("authorization", "Bearer test-token"))),
+        ])
+        .times(1)
+        .respond_with(json_encoded(json!({ "key": key }))),
+    );
+}
+
+/// Expect one authenticated request to sign the digest of `MESSAGE` with `alg`.
+fn expect_sign(server: &Server, alg: &str, signature: &[u8]) {
+    let value = URL_SAFE_NO_PAD.encode(digest(&SHA256, MESSAGE));
+    server.expect(
+        Expectation::matching(all_of![
+            request::method_path("POST", format!("{}/sign", KEY_PATH)),
+            request::query(url_decoded(contains(("api-version", "7.4")))),
+            request::headers(contains(("authorization", "Bearer test-token"))),
+            request::body(json_decoded(eq(json!({ "alg": alg, "value": value })))),
+        ])
+        .times(1)
+        .respond_with(json_encoded(json!({
+            "kid": "https://my-vault.vault.azure.net/keys/tuf-root/0123abcd",
+            "value": URL_SAFE_NO_PAD.encode(signature),
+        }))),
+    );
+}
+
+fn public_key_bytes(key: &Key) -> Vec<u8> {
+    match key {
+        Key::Rsa { keyval, .. } => keyval.public.to_vec(),
+        Key::Ecdsa { keyval, .. } => keyval.public.to_vec(),
+        Key::Ed25519 { .. } => panic!("unexpected ed25519 key"),
+    }
+}
+
+#[test]
+// Ensure an RSA key matches the local copy of the same key, and signs with RSASSA-PSS
+fn rsa_sign() {
+    let pem = pem::parse(std::fs::read(snakeoil()).unwrap()).unwrap();
+    let key_pair = RsaKeyPair::from_der(pem.contents()).unwrap();
+    let public_key = key_pair.public_key();
+    let mut signature = vec![0; key_pair.public_modulus_len()];
+    key_pair
+        .sign(
+            &RSA_PSS_SHA256,
+            &SystemRandom::new(),
+            MESSAGE,
+            &mut signature,
+        )
+        .unwrap();
+
+    let server = Server::run();
+    expect_get_key(
+        &server,
+        json!({
+            "kid": "https://my-vault.vault.azure.net/keys/tuf-root/0123abcd",
+            "kty": "RSA-HSM",
+            "n": URL_SAFE_NO_PAD.encode(public_key.modulus().big_endian_without_leading_zero()),
+            "e": URL_SAFE_NO_PAD.encode(public_key.exponent().big_endian_without_leading_zero()),
+        }),
+    );
+    expect_sign(&server, "PS256", &signature);
+
+    let sign = key_source(&server).as_sign().unwrap();
+    let local = LocalKeySource { path: snakeoil() }.as_sign().unwrap();
+    let tuf_key = sign.tuf_key();
+    assert_eq!(tuf_key, local.tuf_key());
+    assert_eq!(tuf_key.key_id().unwrap(), local.tuf_key().key_id().unwrap());
+
+    let signature = sign.sign(MESSAGE, &SystemRandom::new()).unwrap();
+    UnparsedPublicKey::new(&RSA_PSS_2048_8192_SHA256, public_key_bytes(&tuf_key))
+        .verify(MESSAGE, &signature)
+        .unwrap();
+}
+
+#[test]
+// Ensure an EC key signs with ECDSA, converting Key Vault's raw signature to ASN.1 DER
+fn ecdsa_sign() {
+    let rng = SystemRandom::new();
+    let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).unwrap();
+    let key_pair =
+        EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref()).unwrap();
+    let point = key_pair.public_key().as_ref();
+    let signature = key_pair.sign(&rng, MESSAGE).unwrap();
+
+    let server = Server::run();
+    expect_get_key(
+        &server,
+        json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": URL_SAFE_NO_PAD.encode(&point[1..33]),
+            "y": URL_SAFE_NO_PAD.encode(&point[33..]),
+        }),
+    );
+    expect_sign(&server, "ES256", signature.as_ref());
+
+    let sign = key_source(&server).as_sign().unwrap();
+    let tuf_key = sign.tuf_key();
+    assert!(matches!(tuf_key, Key::Ecdsa { .. }));
+    assert_eq!(public_key_bytes(&tuf_key), point);
+
+    let signature = sign.sign(MESSAGE, &rng).unwrap();
+    UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, public_key_bytes(&tuf_key))
+        .verify(MESSAGE, &signature)
+        .unwrap();
+}
+
+#[test]
+// Ensure keys that can't sign TUF metadata are rejected
+fn unsupported_key_type() {
+    let server = Server::run();
+    expect_get_key(
+        &server,
+        json!({ "kty": "EC", "crv": "P-384", "x": "AA", "y": "AA" }),
+    );
+    let error = key_source(&server).as_sign().unwrap_err();
+    assert!(error.to_string().contains("P-384"), "{}", error);
+}
+
+#[test]
+// Ensure a Key Vault error status is surfaced
+fn get_key_forbidden() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(request::method_path("GET", KEY_PATH))
+            .times(1)
+            .respond_with(status_code(403)),
+    );
+    let error = key_source(&server).as_sign().unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("azurekv://my-vault/tuf-root/0123abcd"),
+        "{}",
+        error
+    );
+}
@@ -0,0 +1,55 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Compares serial and pipelined hashing of a large target. Run with `cargo bench --bench
+//! target_hash`; set `TARGET_HASH_BENCH_MIB` to change the size of the target, which defaults to
+//! 1024 MiB.
+
+use std::io::Write;
+use std::num::NonZeroUsize;
+use std::time::{Duration, Instant};
+use tempfile::NamedTempFile;
+use tough::schema::Target;
+
+const MIB: usize = 1024 * 1024;
+
+fn time<F: FnMut() -> Target>(label: &str, size: usize, mut hash: F) -> Target {
+    let start = Instant::now();
+    let target = hash();
+    let elapsed = start.elapsed();
+    println!(
+        "{:<32} {:>8.3}s {:>10.1} MiB/s",
+        label,
+        elapsed.as_secs_f64(),
+        size as f64 / MIB as f64 / elapsed.max(Duration::from_nanos(1)).as_secs_f64()
+    );
+    target
+}
+
+fn main() {
+    let mib = std::env::var("TARGET_HASH_BENCH_MIB")
+        .ok()
+        .and_then(|mib| mib.parse().ok())
+        .unwrap_or(1024);
+    let size = mib * MIB;
+
+    let mut file = NamedTempFile::new().unwrap();
+    let block = (0..MIB).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    for _ in 0..mib {
+        file.write_all(&block).unwrap();
+    }
+    file.flush().unwrap();
+
+    let serial = time("serial", size, || Target::from_path(file.path()).unwrap());
+    for chunk_mib in [1, 4, 16] {
+        let target = time(&format!("pipelined, {chunk_mib} MiB chunks"), size, || {
+            Target::from_path_pipelined(
+                file.path(),
+                NonZeroUsize::new(chunk_mib * MIB).unwrap(),
+                NonZeroUsize::new(4).unwrap(),
+            )
+            .unwrap()
+        });
+        assert_eq!(target, serial);
+    }
+}
@@ -1,10 +1,37 @@
 use crate::error::{self, Result};
-use crate::fetch::{fetch_max_size, fetch_sha256};
-use crate::schema::{RoleType, Target};
-use crate::{encode_filename, Prefix, Repository, TargetName};
+use crate::fetch::{fetch_length_sha256, fetch_max_size};
+use crate::io::{DigestAdapter, LengthAdapter};
+use crate::schema::hash::Hasher;
+use crate::schema::{HashAlgorithm, RoleType, Target};
+use crate::urlpath::encode_url_path;
+use crate::{encode_filename, HashPolicy, Prefix, Repository, TargetName};
+use ring::digest::{SHA256, SHA384, SHA512};
+use serde::Serialize;
 use snafu::{OptionExt, ResultExt};
+use std::collections::HashSet;
 use std::io::{Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use url::Url;
+use walkdir::WalkDir;
+
+/// The changes that caching a repository's targets would make to a local mirror, as computed by
+/// [`Repository::cache_plan`]. Each entry is a file path relative to the targets directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CachePlan {
+    /// Targets that are missing from the mirror.
+    pub add: Vec<String>,
+    /// Targets in the mirror whose contents differ from the repository's.
+    pub update: Vec<String>,
+    /// Files in the mirror that are not among the cached targets. Only listed when pruning.
+    pub delete: Vec<String>,
+}
+
+impl CachePlan {
+    /// Returns `true` if caching would leave the mirror unchanged.
+    pub fn is_empty(&self) -> bool {
+        self.add.is_empty() && self.update.is_empty() && self.delete.is_empty()
+    }
+}
 
 impl Repository {
     /// Cache an entire or partial repository to disk, including all required metadata.
@@ -61,6 +88,89 @@ impl Repository {
         Ok(())
     }
 
+    /// Computes the changes that [`Repository::cache`] would make to the targets in
+    /// `targets_outdir`, without downloading any targets. Files already in `targets_outdir` are
+    /// compared to the repository by length and sha256 digest.
+    ///
+    /// * `targets_outdir` is the directory of the mirror's targets. It need not exist yet.
+    /// * `targets_subset` is the list of targets to include, as for [`Repository::cache`].
+    /// * `prune` specifies whether files in `targets_outdir` that are not among the included
+    /// targets are listed for deletion.
+    pub fn cache_plan<P, S>(
+        &self,
+        targets_outdir: P,
+        targets_subset: Option<&[S]>,
+        prune: bool,
+    ) -> Result<CachePlan>
+    where
+        P: AsRef<Path>,
+        S: AsRef<str>,
+    {
+        let targets_outdir = targets_outdir.as_ref();
+        let mut included = Vec::new();
+        if let Some(target_list) = targets_subset {
+            for raw_name in target_list {
+                let target_name = TargetName::new(raw_name.as_ref())?;
+                let target = self.targets.signed.find_target(&target_name).context(
+                    error::CacheTargetMissingSnafu {
+                        target_name: target_name.clone(),
+                    },
+                )?;
+                included.push((target_name, target));
+            }
+        } else {
+            included.extend(self.targets.signed.targets_map());
+        }
+
+        let mut plan = CachePlan::default();
+        let mut expected = HashSet::new();
+        for (target_name, target) in included {
+            let (_, filename) = self.target_digest_and_filename(target, &target_name);
+            let path = targets_outdir.join(&filename);
+            if !path.is_file() {
+                plan.add.push(filename.clone());
+            } else if !cached_target_matches(&path, target)? {
+                plan.update.push(filename.clone());
+            }
+            expected.insert(PathBuf::from(filename));
+        }
+
+        if prune && targets_outdir.is_dir() {
+            for entry in WalkDir::new(targets_outdir) {
+                let entry = entry.context(error::WalkDirSnafu {
+                    directory: targets_outdir,
+                })?;
+                if !entry.file_type().is_file() {
+                    continue;
+                }
+                // Entries are always below `targets_outdir`
+                let relative = entry
+                    .path()
+                    .strip_prefix(targets_outdir)
+                    .unwrap_or(entry.path());
+                if !expected.contains(relative) {
+                    plan.delete.push(relative.to_string_lossy().into_owned());
+                }
+            }
+        }
+
+        plan.add.sort();
+        plan.update.sort();
+        plan.delete.sort();
+        Ok(plan)
+    }
+
+    /// Returns the path, relative to the targets directory, at which [`Repository::cache`] saves
+    /// the named target.
+    pub fn cached_target_filename(&self, target_name: &TargetName) -> Result<String> {
+        let target = self.targets.signed.find_target(target_name).context(
+            error::CacheTargetMissingSnafu {
+                target_name: target_name.clone(),
+            },
+        )?;
+        Ok(self.target_digest_and_filename(target, target_name).1)
+    }
+
     /// Cache only a repository's metadata files (snapshot, targets, timestamp), including any
     /// delegated targets metadata.  The cached files will be saved to the local filesystem.
     ///
@@ -186,7 +296,7 @@ impl Repository {
         let mut read = fetch_max_size(
             self.transport.as_ref(),
             self.metadata_base_url
-                .join(filename)
+                .join(&encode_url_path(filename))
                 .context(error::JoinUrlSnafu {
                     path: filename,
                     url: self.metadata_base_url.clone(),
@@ -242,15 +352,10 @@ impl Repository {
         target: &Target,
         name: &TargetName,
     ) -> (Vec<u8>, String) {
-        let sha256 = &target.hashes.sha256.clone().into_vec();
-        if self.consistent_snapshot {
-            (
-                sha256.clone(),
-                format!("{}.{}", hex::encode(sha256), name.resolved()),
-            )
-        } else {
-            (sha256.clone(), name.resolved().to_owned())
-        }
+        (
+            target.hashes.sha256.clone().into_vec(),
+            target_filename(target, name, self.consistent_snapshot),
+        )
     }
 
     /// Fetches the signed target using `Transport`. Aborts with error if the fetched target is
@@ -260,18 +365,127 @@ impl Repository {
         target: &Target,
         digest: &[u8],
         filename: &str,
-    ) -> Result<impl Read + '_> {
-        fetch_sha256(
-            self.transport.as_ref(),
-            self.targets_base_url
-                .join(filename)
-                .context(error::JoinUrlSnafu {
-                    path: filename,
-                    url: self.targets_base_url.clone(),
-                })?,
+    ) -> Result<Box<dyn Read + Send + '_>> {
+        let url = self.target_url(filename)?;
+        let reader = fetch_length_sha256(
+            self.targets_transport.as_ref(),
+            url.clone(),
             target.length,
-            "targets.json",
             digest,
-        )
+        )?;
+        self.verify_target_hashes(target, filename, Box::new(reader), &url)
     }
+
+    /// Wraps `reader`, whose bytes come from somewhere other than the repository, so that it fails
+    /// unless they have the length and hashes of `target`. `filename` and its URL in the
+    /// repository are only used to describe the target in errors.
+    pub(crate) fn verify_target<'a>(
+        &self,
+        target: &Target,
+        filename: &str,
+        reader: Box<dyn Read + Send + 'a>,
+    ) -> Result<Box<dyn Read + Send + 'a>> {
+        let url = self.target_url(filename)?;
+        let reader = DigestAdapter::sha256(
+            Box::new(LengthAdapter::new(reader, target.length, url.clone())),
+            &target.hashes.sha256,
+            url.clone(),
+        );
+        self.verify_target_hashes(target, filename, Box::new(reader), &url)
+    }
+
+    /// Returns the URL of the target file `filename` in the repository.
+    pub(crate) fn target_url(&self, filename: &str) -> Result<Url> {
+        join_target_filename(&self.targets_base_url, filename)
+    }
+
+    /// The sha256 hash is always checked. When every declared hash must match, stacks a verifying
+    /// reader on `reader` for each of the other hashes of `target` as well.
+    fn verify_target_hashes<'a>(
+        &self,
+        target: &Target,
+        filename: &str,
+        mut reader: Box<dyn Read + Send + 'a>,
+        url: &Url,
+    ) -> Result<Box<dyn Read + Send + 'a>> {
+        if self.hash_policy == HashPolicy::All {
+            for (algorithm, value) in &target.hashes._extra {
+                let hasher = match algorithm.as_str() {
+                    "sha384" => Hasher::ring(&SHA384),
+                    "sha512" => Hasher::ring(&SHA512),
+                    _ => match HashAlgorithm::from_key(algorithm) {
+                        Some(algorithm) => algorithm.hasher(),
+                        None => {
+                            return error::UnverifiableHashSnafu {
+                                name: filename,
+                                algorithm,
+                            }
+                            .fail()
+                        }
+                    },
+                };
+                let hash = value
+                    .as_str()
+                    .and_then(|hash| hex::decode(hash).ok())
+                    .context(error::UnverifiableHashSnafu {
+                        name: filename,
+                        algorithm,
+                    })?;
+                reader = Box::new(DigestAdapter::with_hasher(
+                    reader,
+                    hasher,
+                    &hash,
+                    url.clone(),
+                ));
+            }
+        }
+        Ok(reader)
+    }
+}
+
+/// Checks whether the file at `path` has the length and sha256 digest of `target`.
+fn cached_target_matches(path: &Path, target: &Target) -> Result<bool> {
+    let metadata = std::fs::metadata(path).context(error::FileReadSnafu { path })?;
+    if metadata.len() != target.length {
+        return Ok(false);
+    }
+    let mut file = std::fs::File::open(path).context(error::FileOpenSnafu { path })?;
+    let mut hasher = Hasher::ring(&SHA256);
+    std::io::copy(&mut file, &mut hasher).context(error::FileReadSnafu { path })?;
+    Ok(hasher.finish() == target.hashes.sha256.as_ref())
+}
+
+/// Returns the URL from which a client fetches `target`, listed as `name`, in a repository whose
+/// targets are at `targets_base_url`. As when a [`Repository`] fetches it, the file is named with
+/// its sha256 digest prepended if the repository uses consistent snapshots, and the name is
+/// percent-encoded, so that names containing e.g. spaces, `#` or `?` are fetched by their own URL.
+pub fn target_fetch_url(
+    targets_base_url: &Url,
+    name: &TargetName,
+    target: &Target,
+    consistent_snapshot: bool,
+) -> Result<Url> {
+    join_target_filename(
+        targets_base_url,
+        &target_filename(target, name, consistent_snapshot),
+    )
+}
+
+/// Returns the name of the file that `target`, listed as `name`, is stored as.
+fn target_filename(target: &Target, name: &TargetName, consistent_snapshot: bool) -> String {
+    if consistent_snapshot {
+        format!("{}.{}", hex::encode(&target.hashes.sha256), name.resolved())
+    } else {
+        name.resolved().to_owned()
+    }
+}
+
+/// Joins the target file `filename` onto `targets_base_url`.
+fn join_target_filename(targets_base_url: &Url, filename: &str) -> Result<Url> {
+    targets_base_url
+        .join(&encode_url_path(filename))
+        .context(error::JoinUrlSnafu {
+            path: filename,
+            url: targets_base_url.clone(),
+        })
 }
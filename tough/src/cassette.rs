@@ -0,0 +1,259 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Provides transports that record a repository's responses to a cassette file and replay them,
+//! so that tests can run against recorded repository interactions without network access.
+
+use crate::error::{self, Result};
+use crate::{Transport, TransportError, TransportErrorKind};
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Cursor, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use url::Url;
+
+/// The responses recorded by a [`RecordingTransport`], in the order they were fetched.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+}
+
+/// A URL and the response fetching it produced.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    url: String,
+    #[serde(flatten)]
+    response: Response,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Response {
+    /// The hex-encoded bytes of the response body.
+    Body(String),
+    /// The fetch failed.
+    Error { kind: ErrorKind, message: String },
+}
+
+/// A serializable copy of [`TransportErrorKind`].
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ErrorKind {
+    UnsupportedUrlScheme,
+    FileNotFound,
+    Other,
+}
+
+impl From<TransportErrorKind> for ErrorKind {
+    fn from(kind: TransportErrorKind) -> Self {
+        match kind {
+            TransportErrorKind::UnsupportedUrlScheme => ErrorKind::UnsupportedUrlScheme,
+            TransportErrorKind::FileNotFound => ErrorKind::FileNotFound,
+            TransportErrorKind::Other => ErrorKind::Other,
+        }
+    }
+}
+
+impl From<ErrorKind> for TransportErrorKind {
+    fn from(kind: ErrorKind) -> Self {
+        match kind {
+            ErrorKind::UnsupportedUrlScheme => TransportErrorKind::UnsupportedUrlScheme,
+            ErrorKind::FileNotFound => TransportErrorKind::FileNotFound,
+            ErrorKind::Other => TransportErrorKind::Other,
+        }
+    }
+}
+
+// =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=
+
+/// A [`Transport`] that fetches with another transport, and records each URL and its response,
+/// including failed fetches, so they can be saved to a cassette file and served by a
+/// [`ReplayTransport`].
+///
+/// Each response body is read in full before it is returned, so that it can be recorded. Clones
+/// share their recording, so keep a clone of the transport given to a
+/// [`RepositoryLoader`](crate::RepositoryLoader) to call [`save`](Self::save) on.
+///
+/// ```rust,no_run
+/// # use std::fs::File;
+/// # use tough::{FilesystemTransport, RecordingTransport, RepositoryLoader};
+/// # use url::Url;
+/// let recorder = RecordingTransport::new(FilesystemTransport);
+/// let repository = RepositoryLoader::new(
+///     File::open("root.json").unwrap(),
+///     Url::parse("file:///repo/metadata/").unwrap(),
+///     Url::parse("file:///repo/targets/").unwrap(),
+/// )
+/// .transport(recorder.clone())
+/// .load()
+/// .unwrap();
+/// recorder.save("repo.cassette.json").unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RecordingTransport<T: Transport> {
+    inner: T,
+    cassette: Arc<Mutex<Cassette>>,
+}
+
+impl<T: Transport> RecordingTransport<T> {
+    /// Creates a `RecordingTransport` that fetches with `inner`.
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            cassette: Arc::default(),
+        }
+    }
+
+    /// Writes everything recorded so far to a cassette file at `path`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let file = File::create(path).context(error::FileWriteSnafu { path })?;
+        serde_json::to_writer_pretty(file, &*self.cassette())
+            .map_err(std::io::Error::from)
+            .context(error::FileWriteSnafu { path })
+    }
+
+    // The recording is valid even if another thread panicked while holding the lock: interactions
+    // are only ever pushed whole.
+    fn cassette(&self) -> MutexGuard<'_, Cassette> {
+        self.cassette.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn record(&self, url: &Url, response: Response) {
+        self.cassette().interactions.push(Interaction {
+            url: url.to_string(),
+            response,
+        });
+    }
+
+    /// Reads the body of a fetch of `url` to its end and records it, or records the error.
+    fn record_fetch(
+        &self,
+        url: Url,
+        result: std::result::Result<Box<dyn Read + Send + '_>, TransportError>,
+    ) -> std::result::Result<Box<dyn Read + Send + '_>, TransportError> {
+        let result = result.and_then(|mut reader| {
+            let mut body = Vec::new();
+            reader
+                .read_to_end(&mut body)
+                .map_err(|e| TransportError::new_with_cause(TransportErrorKind::Other, &url, e))?;
+            Ok(body)
+        });
+        match result {
+            Ok(body) => {
+                self.record(&url, Response::Body(hex::encode(&body)));
+                Ok(Box::new(Cursor::new(body)))
+            }
+            Err(err) => {
+                self.record(
+                    &url,
+                    Response::Error {
+                        kind: err.kind().into(),
+                        message: err.to_string(),
+                    },
+                );
+                Err(err)
+            }
+        }
+    }
+}
+
+impl<T: Transport + Clone> Transport for RecordingTransport<T> {
+    fn fetch(&self, url: Url) -> std::result::Result<Box<dyn Read + Send + '_>, TransportError> {
+        let result = self.inner.fetch(url.clone());
+        self.record_fetch(url, result)
+    }
+
+    fn fetch_sha256(
+        &self,
+        url: Url,
+        sha256: &[u8],
+    ) -> std::result::Result<Box<dyn Read + Send + '_>, TransportError> {
+        let result = self.inner.fetch_sha256(url.clone(), sha256);
+        self.record_fetch(url, result)
+    }
+}
+
+// =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=
+
+/// A [`Transport`] that serves the responses in a cassette file saved by a
+/// [`RecordingTransport`], without any network or filesystem access.
+///
+/// The recorded bytes are served exactly, so they are verified just as they were when recorded.
+/// A URL that was fetched more than once is served its responses in the order they were
+/// recorded, and then its last response again. Fetching a URL that was never recorded fails with
+/// [`TransportErrorKind::Other`].
+#[derive(Debug, Clone)]
+pub struct ReplayTransport {
+    responses: Arc<HashMap<String, Vec<Response>>>,
+    served: Arc<Mutex<HashMap<String, usize>>>,
+    unrecorded: Arc<AtomicUsize>,
+}
+
+impl ReplayTransport {
+    /// Reads the cassette file at `path`.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).context(error::FileOpenSnafu { path })?;
+        let cassette: Cassette =
+            serde_json::from_reader(file).context(error::FileParseJsonSnafu { path })?;
+        let mut responses: HashMap<String, Vec<Response>> = HashMap::new();
+        for interaction in cassette.interactions {
+            responses
+                .entry(interaction.url)
+                .or_default()
+                .push(interaction.response);
+        }
+        Ok(Self {
+            responses: Arc::new(responses),
+            served: Arc::default(),
+            unrecorded: Arc::default(),
+        })
+    }
+
+    /// Returns how many fetches were for URLs that aren't in the cassette. A test can check this
+    /// is zero to ensure it didn't stray from the recorded interactions.
+    pub fn unrecorded_fetches(&self) -> usize {
+        self.unrecorded.load(Ordering::SeqCst)
+    }
+
+    /// Returns the next response for `url`, if it was recorded.
+    fn next_response(&self, url: &str) -> Option<&Response> {
+        let responses = self.responses.get(url)?;
+        let mut served = self.served.lock().unwrap_or_else(PoisonError::into_inner);
+        let count = served.entry(url.to_owned()).or_default();
+        let response = responses.get(*count).or_else(|| responses.last());
+        *count += 1;
+        response
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn fetch(&self, url: Url) -> std::result::Result<Box<dyn Read + Send + '_>, TransportError> {
+        match self.next_response(url.as_str()) {
+            Some(Response::Body(body)) => {
+                let body = hex::decode(body).map_err(|e| {
+                    TransportError::new_with_cause(TransportErrorKind::Other, &url, e)
+                })?;
+                Ok(Box::new(Cursor::new(body)))
+            }
+            Some(Response::Error { kind, message }) => Err(TransportError::new_with_cause(
+                (*kind).into(),
+                &url,
+                message.clone(),
+            )),
+            None => {
+                self.unrecorded.fetch_add(1, Ordering::SeqCst);
+                Err(TransportError::new_with_cause(
+                    TransportErrorKind::Other,
+                    &url,
+                    "URL is not in the cassette",
+                ))
+            }
+        }
+    }
+}
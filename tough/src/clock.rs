@@ -0,0 +1,35 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Provides the [`Clock`] trait, the source of the current time used for expiration checks.
+
+use chrono::{DateTime, Utc};
+use std::fmt::Debug;
+
+/// A source of the current time. [`RepositoryLoader`](crate::RepositoryLoader) uses a `Clock` to
+/// decide whether metadata has expired. Providing a clock other than [`SystemClock`] makes loads
+/// deterministic, which is useful for tests and reproducible builds.
+pub trait Clock: Debug + Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// A [`Clock`] that reads the system clock. This is the default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A [`Clock`] that always returns the same time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
@@ -0,0 +1,214 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Provides a way to load a repository from a content-addressed store of objects.
+
+use crate::schema::{Signed, Snapshot, Targets, Timestamp};
+use crate::{encode_filename, RepositoryLoader, Transport, TransportError, TransportErrorKind};
+use percent_encoding::percent_decode_str;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Cursor, ErrorKind, Read};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use url::Url;
+
+/// The metadata base URL handed to the loader; it is never resolved.
+const METADATA_BASE_URL: &str = "content-store:///metadata/";
+/// The targets base URL handed to the loader; it is never resolved.
+const TARGETS_BASE_URL: &str = "content-store:///targets/";
+
+/// A directory of content-addressed objects, each named by the lowercase hex sha256 digest of its
+/// contents, holding a repository's metadata and targets.
+///
+/// `ContentStore` is a [`Transport`] that resolves each file by the hash declared for it, rather
+/// than by its path: snapshot.json by the hash timestamp.json declares, each targets metadata file
+/// by the hash snapshot.json declares, and each target by the hash its targets metadata declares.
+/// Only timestamp.json, whose hash is declared nowhere in the repository, must be given. Files are
+/// verified as usual when they are loaded, so a corrupt store is detected like a corrupt mirror.
+///
+/// snapshot.json must list the hashes of the targets metadata files. Root updates are not
+/// available; the given root is the trusted root.
+///
+/// ```rust,no_run
+/// # use std::fs::File;
+/// # use tough::ContentStore;
+/// # let timestamp_sha256 = vec![0; 32];
+/// let repository = ContentStore::new("/var/lib/store/objects", &timestamp_sha256)
+///     .loader(File::open("/var/lib/store/root.json").unwrap())
+///     .load()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ContentStore {
+    objects: PathBuf,
+    timestamp_sha256: Vec<u8>,
+    /// The sha256 of each file whose hash has been declared by metadata fetched so far, keyed by
+    /// its metadata filename or target name
+    declared: Arc<Mutex<Declared>>,
+}
+
+#[derive(Debug, Default)]
+struct Declared {
+    metadata: HashMap<String, Vec<u8>>,
+    targets: HashMap<String, Vec<u8>>,
+}
+
+impl ContentStore {
+    /// Create a `ContentStore` for the objects in `objects`, where the repository's current
+    /// timestamp.json has the sha256 digest `timestamp_sha256`.
+    pub fn new<P>(objects: P, timestamp_sha256: &[u8]) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            objects: objects.into(),
+            timestamp_sha256: timestamp_sha256.to_owned(),
+            declared: Arc::default(),
+        }
+    }
+
+    /// Create a [`RepositoryLoader`] that loads the repository from this store, trusting `root`.
+    /// Any other loader settings, such as limits or expiration enforcement, can be set on the
+    /// result before calling `load`.
+    pub fn loader<R: Read>(self, root: R) -> RepositoryLoader<R> {
+        RepositoryLoader::new(
+            root,
+            // These are constants, so parsing them cannot fail
+            Url::parse(METADATA_BASE_URL).unwrap(),
+            Url::parse(TARGETS_BASE_URL).unwrap(),
+        )
+        .transport(self)
+    }
+
+    /// Opens the object with the given sha256 digest.
+    fn open(&self, sha256: &[u8], url: &Url) -> Result<File, TransportError> {
+        File::open(self.objects.join(hex::encode(sha256))).map_err(|e| {
+            let kind = match e.kind() {
+                ErrorKind::NotFound => TransportErrorKind::FileNotFound,
+                _ => TransportErrorKind::Other,
+            };
+            TransportError::new_with_cause(kind, url, e)
+        })
+    }
+
+    fn fetch_metadata(&self, filename: &str, url: &Url) -> Result<Vec<u8>, TransportError> {
+        let unversioned = match filename.split_once('.') {
+            Some((version, rest)) if version.bytes().all(|b| b.is_ascii_digit()) => rest,
+            _ => filename,
+        };
+        let sha256 = if filename == "timestamp.json" {
+            self.timestamp_sha256.clone()
+        } else {
+            self.declared()
+                .metadata
+                .get(filename)
+                .cloned()
+                .ok_or_else(|| {
+                    TransportError::new_with_cause(
+                        TransportErrorKind::FileNotFound,
+                        url,
+                        "no hash has been declared for this file",
+                    )
+                })?
+        };
+
+        let mut buf = Vec::new();
+        self.open(&sha256, url)?
+            .read_to_end(&mut buf)
+            .map_err(|e| TransportError::new_with_cause(TransportErrorKind::Other, url, e))?;
+
+        // Note the hashes this file declares for the files fetched after it. A file that doesn't
+        // parse declares nothing; the loader reports it when it parses the file itself.
+        if filename == "timestamp.json" {
+            if let Ok(timestamp) = serde_json::from_slice::<Signed<Timestamp>>(&buf) {
+                let meta = timestamp.signed.meta.iter().map(|(name, meta)| {
+                    (name, meta.version.get(), Some(meta.hashes.sha256.to_vec()))
+                });
+                self.declare_metadata(meta);
+            }
+        } else if unversioned == "snapshot.json" {
+            if let Ok(snapshot) = serde_json::from_slice::<Signed<Snapshot>>(&buf) {
+                let meta = snapshot.signed.meta.iter().map(|(name, meta)| {
+                    let sha256 = meta.hashes.as_ref().map(|hashes| hashes.sha256.to_vec());
+                    (name, meta.version.get(), sha256)
+                });
+                self.declare_metadata(meta);
+            }
+        } else if let Ok(targets) = serde_json::from_slice::<Signed<Targets>>(&buf) {
+            let mut declared = self.declared();
+            for (name, target) in &targets.signed.targets {
+                declared
+                    .targets
+                    .insert(name.resolved().to_owned(), target.hashes.sha256.to_vec());
+            }
+        }
+        Ok(buf)
+    }
+
+    /// Records the hash declared for each metadata file in `meta`, under the filenames it is
+    /// fetched by with and without consistent snapshots.
+    fn declare_metadata<'a, I>(&self, meta: I)
+    where
+        I: Iterator<Item = (&'a String, u64, Option<Vec<u8>>)>,
+    {
+        let mut declared = self.declared();
+        for (name, version, sha256) in meta {
+            let (sha256, role) = match (sha256, name.strip_suffix(".json")) {
+                (Some(sha256), Some(role)) => (sha256, role),
+                _ => continue,
+            };
+            let filename = format!("{}.json", encode_filename(role));
+            declared
+                .metadata
+                .insert(format!("{version}.{filename}"), sha256.clone());
+            declared.metadata.insert(filename, sha256);
+        }
+    }
+
+    fn fetch_target(&self, filename: &str, url: &Url) -> Result<File, TransportError> {
+        // With consistent snapshots, the target's filename starts with the hash the client wants
+        if let Some((prefix, _)) = filename.split_once('.') {
+            if let Ok(sha256) = hex::decode(prefix) {
+                if sha256.len() == 32 {
+                    return self.open(&sha256, url);
+                }
+            }
+        }
+        let sha256 = self.declared().targets.get(filename).cloned();
+        match sha256 {
+            Some(sha256) => self.open(&sha256, url),
+            None => Err(TransportError::new_with_cause(
+                TransportErrorKind::FileNotFound,
+                url,
+                "no hash has been declared for this target",
+            )),
+        }
+    }
+
+    fn declared(&self) -> std::sync::MutexGuard<'_, Declared> {
+        // A panic while holding the lock can't leave the maps half-updated, so keep using them
+        self.declared
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl Transport for ContentStore {
+    fn fetch(&self, url: Url) -> Result<Box<dyn Read + Send>, TransportError> {
+        let decoded = |base: &str| {
+            url.as_str()
+                .strip_prefix(base)
+                .and_then(|filename| percent_decode_str(filename).decode_utf8().ok())
+                .map(|filename| filename.into_owned())
+        };
+        if let Some(filename) = decoded(METADATA_BASE_URL) {
+            let buf = self.fetch_metadata(&filename, &url)?;
+            Ok(Box::new(Cursor::new(buf)))
+        } else if let Some(filename) = decoded(TARGETS_BASE_URL) {
+            Ok(Box::new(self.fetch_target(&filename, &url)?))
+        } else {
+            Err(TransportError::new(TransportErrorKind::FileNotFound, url))
+        }
+    }
+}
@@ -1,6 +1,7 @@
 // Copyright 2019 Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+use crate::clock::Clock;
 use crate::error::{self, Result};
 use chrono::{DateTime, Utc};
 use log::debug;
@@ -19,16 +20,19 @@ pub(crate) struct Datastore {
     path_lock: Arc<RwLock<DatastorePath>>,
     /// A lock to treat the system_time function as a critical section.
     time_lock: Arc<RwLock<()>>,
+    /// The source of the current time.
+    clock: Arc<dyn Clock>,
 }
 
 impl Datastore {
-    pub(crate) fn new(path: Option<PathBuf>) -> Result<Self> {
+    pub(crate) fn new(path: Option<PathBuf>, clock: Arc<dyn Clock>) -> Result<Self> {
         Ok(Self {
             path_lock: Arc::new(RwLock::new(match path {
                 None => DatastorePath::TempDir(TempDir::new().context(error::DatastoreInitSnafu)?),
                 Some(p) => DatastorePath::Path(p),
             })),
             time_lock: Arc::new(RwLock::new(())),
+            clock,
         })
     }
 
@@ -109,7 +113,7 @@ impl Datastore {
             .map(serde_json::from_reader::<_, DateTime<Utc>>);
 
         // Get 'current' system time
-        let sys_time = Utc::now();
+        let sys_time = self.clock.now();
 
         if let Some(Ok(latest_known_time)) = poss_latest_known_time {
             // Make sure the sampled system time did not go back in time
@@ -9,18 +9,22 @@ pub mod signed;
 pub mod targets;
 mod test;
 
-use crate::editor::signed::{SignedDelegatedTargets, SignedRepository, SignedRole};
-use crate::editor::targets::TargetsEditor;
+use crate::editor::signed::{
+    SignedDelegatedTargets, SignedRepository, SignedRole, UnsignedRepository,
+};
+use crate::editor::targets::{CustomValidator, TargetsEditor};
 use crate::error::{self, Result};
 use crate::fetch::fetch_max_size;
 use crate::key_source::KeySource;
 use crate::schema::decoded::{Decoded, Hex};
 use crate::schema::key::Key;
 use crate::schema::{
-    Hashes, KeyHolder, PathSet, Role, RoleType, Root, Signed, Snapshot, SnapshotMeta, Target,
-    Targets, Timestamp, TimestampMeta,
+    spec_version_compatible, DelegatedTargets, Delegations, HashAlgorithm, Hashes, KeyHolder,
+    PathSet, Role, RoleType, Root, Signed, Snapshot, SnapshotMeta, Target, Targets, Timestamp,
+    TimestampMeta, SPEC_VERSION,
 };
 use crate::transport::Transport;
+use crate::urlpath::encode_url_path;
 use crate::{encode_filename, Limits};
 use crate::{Repository, TargetName};
 use chrono::{DateTime, Utc};
@@ -29,14 +33,21 @@ use ring::rand::SystemRandom;
 use serde_json::Value;
 use snafu::{ensure, OptionExt, ResultExt};
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 use std::fmt::Display;
 use std::num::NonZeroU64;
 use std::path::Path;
 use url::Url;
 
-const SPEC_VERSION: &str = "1.0.0";
+/// The most bits of the target name hash that `delegate_to_hash_bins()` can split targets by. Every
+/// bin is signed metadata, so more bins than this would be impractical.
+const MAX_HASH_BIN_BITS: u8 = 16;
+
+/// The longest delegated role name, in bytes, that [`validate_role_name`] accepts. Role names
+/// become metadata file names, which are longer once percent-encoded and given a version prefix,
+/// and most filesystems limit file names to 255 bytes.
+pub const MAX_ROLE_NAME_LENGTH: usize = 128;
 
 /// `RepositoryEditor` contains the various bits of data needed to construct
 /// or edit a TUF repository.
@@ -46,7 +57,10 @@ const SPEC_VERSION: &str = "1.0.0";
 /// An existing `tough::Repository` may be loaded and edited using the
 /// `from_repo()` method. When a repo is loaded in this way, versions and
 /// expirations are discarded. It is good practice to update these whenever
-/// a repo is changed.
+/// a repo is changed. The `spec_version` of the loaded metadata is preserved
+/// unless it is changed with `spec_version()`. To re-issue a role without
+/// changing its content, use `bump_version()`, which starts from the loaded
+/// versions and expirations instead.
 ///
 /// Targets, versions, and expirations may be added to their respective roles
 /// via the provided "setter" methods. The final step in the process is the
@@ -68,13 +82,22 @@ const SPEC_VERSION: &str = "1.0.0";
 pub struct RepositoryEditor {
     signed_root: SignedRole<Root>,
 
+    /// The `spec_version` written to snapshot and timestamp; `SPEC_VERSION` if None
+    spec_version: Option<String>,
+
     snapshot_version: Option<NonZeroU64>,
     snapshot_expires: Option<DateTime<Utc>>,
     snapshot_extra: Option<HashMap<String, Value>>,
+    /// Whether snapshot lists the length and hashes of each targets metadata file
+    snapshot_meta_hashes: bool,
+    /// The version and expiration of the snapshot added with `snapshot()`
+    current_snapshot: Option<(NonZeroU64, DateTime<Utc>)>,
 
     timestamp_version: Option<NonZeroU64>,
     timestamp_expires: Option<DateTime<Utc>>,
     timestamp_extra: Option<HashMap<String, Value>>,
+    /// The version and expiration of the timestamp added with `timestamp()`
+    current_timestamp: Option<(NonZeroU64, DateTime<Utc>)>,
 
     targets_editor: Option<TargetsEditor>,
 
@@ -126,13 +149,17 @@ impl RepositoryEditor {
 
         Ok(RepositoryEditor {
             signed_root,
+            spec_version: None,
             targets_editor: Some(editor),
             snapshot_version: None,
             snapshot_expires: None,
             snapshot_extra: None,
+            snapshot_meta_hashes: true,
+            current_snapshot: None,
             timestamp_version: None,
             timestamp_expires: None,
             timestamp_extra: None,
+            current_timestamp: None,
             signed_targets: None,
             transport: None,
             limits: None,
@@ -213,10 +240,68 @@ impl RepositoryEditor {
         })
     }
 
+    /// Builds the top-level targets and snapshot roles without signing them, for repositories
+    /// whose metadata is signed by an external system.
+    ///
+    /// The roles are returned with empty `signatures`, along with the canonical bytes each must be
+    /// signed over; see [`UnsignedRepository`]. Delegated roles are kept as they were signed.
+    /// Because the hash of targets.json isn't known until it is signed, snapshot only lists the
+    /// version of each targets metadata file, as with `snapshot_meta_hashes(false)`. No timestamp
+    /// is built, since it records the hash of the signed snapshot.json; issue it once snapshot is
+    /// signed.
+    ///
+    /// The targets editor must be editing the top-level targets role, or have been cleared.
+    pub fn unsigned(mut self) -> Result<UnsignedRepository> {
+        let targets = match self.targets_editor.take() {
+            Some(targets_editor) => {
+                let targets = targets_editor.build_targets()?;
+                ensure!(targets.name == "targets", error::TargetsEditorSomeSnafu);
+                Signed {
+                    signed: targets.targets,
+                    signatures: Vec::new(),
+                }
+            }
+            None => {
+                let mut targets = self.signed_targets.clone().context(error::NoTargetsSnafu)?;
+                targets.signatures.clear();
+                targets
+            }
+        };
+        targets.signed.validate().context(error::InvalidPathSnafu)?;
+        let delegated_targets = targets.signed.signed_delegated_targets();
+        let unsigned_targets = SignedRole::from_signed(targets)?;
+
+        let unsigned_delegated_targets = if delegated_targets.is_empty() {
+            None
+        } else {
+            Some(SignedDelegatedTargets {
+                roles: delegated_targets
+                    .into_iter()
+                    .map(SignedRole::from_signed)
+                    .collect::<Result<_>>()?,
+                consistent_snapshot: self.signed_root.signed.signed.consistent_snapshot,
+            })
+        };
+
+        self.snapshot_meta_hashes = false;
+        let snapshot = self.build_snapshot(&unsigned_targets, &unsigned_delegated_targets)?;
+        let unsigned_snapshot = SignedRole::from_signed(Signed {
+            signed: snapshot,
+            signatures: Vec::new(),
+        })?;
+
+        Ok(UnsignedRepository {
+            root: self.signed_root,
+            targets: unsigned_targets,
+            snapshot: unsigned_snapshot,
+            delegated_targets: unsigned_delegated_targets,
+        })
+    }
+
     /// Add an existing `Targets` struct to the repository.
     pub fn targets(&mut self, targets: Signed<Targets>) -> Result<&mut Self> {
         ensure!(
-            targets.signed.spec_version == SPEC_VERSION,
+            spec_version_compatible(&targets.signed.spec_version),
             error::SpecVersionSnafu {
                 given: targets.signed.spec_version,
                 supported: SPEC_VERSION
@@ -234,33 +319,52 @@ impl RepositoryEditor {
     }
 
     /// Add an existing `Snapshot` to the repository. Only the `_extra` data
-    /// is preserved
+    /// and `spec_version` are preserved
     pub fn snapshot(&mut self, snapshot: Snapshot) -> Result<&mut Self> {
         ensure!(
-            snapshot.spec_version == SPEC_VERSION,
+            spec_version_compatible(&snapshot.spec_version),
             error::SpecVersionSnafu {
                 given: snapshot.spec_version,
                 supported: SPEC_VERSION
             }
         );
+        self.current_snapshot = Some((snapshot.version, snapshot.expires));
+        self.spec_version = Some(snapshot.spec_version);
         self.snapshot_extra = Some(snapshot._extra);
         Ok(self)
     }
 
     /// Add an existing `Timestamp` to the repository. Only the `_extra` data
-    /// is preserved
+    /// and `spec_version` are preserved
     pub fn timestamp(&mut self, timestamp: Timestamp) -> Result<&mut Self> {
         ensure!(
-            timestamp.spec_version == SPEC_VERSION,
+            spec_version_compatible(&timestamp.spec_version),
             error::SpecVersionSnafu {
                 given: timestamp.spec_version,
                 supported: SPEC_VERSION
             }
         );
+        self.current_timestamp = Some((timestamp.version, timestamp.expires));
+        self.spec_version = Some(timestamp.spec_version);
         self.timestamp_extra = Some(timestamp._extra);
         Ok(self)
     }
 
+    /// Set the `spec_version` written to the snapshot, the timestamp, and the targets role
+    /// currently being edited. Other roles keep the `spec_version` they were loaded with.
+    pub fn spec_version(&mut self, spec_version: &str) -> Result<&mut Self> {
+        ensure!(
+            spec_version_compatible(spec_version),
+            error::SpecVersionSnafu {
+                given: spec_version,
+                supported: SPEC_VERSION
+            }
+        );
+        self.targets_editor_mut()?.spec_version(spec_version);
+        self.spec_version = Some(spec_version.to_string());
+        Ok(self)
+    }
+
     /// Returns a mutable reference to the targets editor if it exists
     fn targets_editor_mut(&mut self) -> Result<&mut TargetsEditor> {
         self.targets_editor.as_mut().ok_or(error::Error::NoTargets)
@@ -276,6 +380,24 @@ impl RepositoryEditor {
         Ok(self)
     }
 
+    /// Check the `custom` metadata of each target added to the `Targets` role in
+    /// `targets_editor` from now on. See `TargetsEditor::custom_validator()`.
+    pub fn custom_validator<V: CustomValidator + 'static>(
+        &mut self,
+        validator: V,
+    ) -> Result<&mut Self> {
+        self.targets_editor_mut()?.custom_validator(validator);
+        Ok(self)
+    }
+
+    /// Record the hash of each of `algorithms`, in addition to `sha256`, for targets added by path
+    /// to the `Targets` role in `targets_editor` from now on. See
+    /// `TargetsEditor::target_hashes()`.
+    pub fn target_hashes(&mut self, algorithms: &[HashAlgorithm]) -> Result<&mut Self> {
+        self.targets_editor_mut()?.target_hashes(algorithms);
+        Ok(self)
+    }
+
     /// Remove a `Target` from the repository
     pub fn remove_target(&mut self, name: &TargetName) -> Result<&mut Self> {
         self.targets_editor_mut()?.remove_target(name);
@@ -283,6 +405,16 @@ impl RepositoryEditor {
         Ok(self)
     }
 
+    /// Renames every target in the `Targets` role in `targets_editor`. See
+    /// `TargetsEditor::rename_targets()`.
+    pub fn rename_targets<F>(&mut self, rename: F) -> Result<&mut Self>
+    where
+        F: FnMut(&TargetName) -> String,
+    {
+        self.targets_editor_mut()?.rename_targets(rename)?;
+        Ok(self)
+    }
+
     /// Add a target to the repository using its path
     ///
     /// Note: This function builds a `Target` synchronously;
@@ -293,8 +425,7 @@ impl RepositoryEditor {
     where
         P: AsRef<Path>,
     {
-        let (target_name, target) = RepositoryEditor::build_target(target_path)?;
-        self.add_target(target_name, target)?;
+        self.targets_editor_mut()?.add_target_path(target_path)?;
         Ok(self)
     }
 
@@ -305,11 +436,7 @@ impl RepositoryEditor {
     where
         P: AsRef<Path>,
     {
-        for target in targets {
-            let (target_name, target) = RepositoryEditor::build_target(target)?;
-            self.add_target(target_name, target)?;
-        }
-
+        self.targets_editor_mut()?.add_target_paths(targets)?;
         Ok(self)
     }
 
@@ -355,44 +482,116 @@ impl RepositoryEditor {
         expiration: DateTime<Utc>,
         version: NonZeroU64,
     ) -> Result<&mut Self> {
-        // Create the new targets using targets editor
-        let mut new_targets_editor = TargetsEditor::new(name);
-        // Set the version and expiration
-        new_targets_editor.version(version).expires(expiration);
-        // Sign the new targets
-        let new_targets = new_targets_editor.create_signed(key_source)?;
-        // Find the keyids for key_source
-        let mut keyids = Vec::new();
-        let mut key_pairs = HashMap::new();
-        for source in key_source {
-            let key_pair = source
-                .as_sign()
-                .context(error::KeyPairFromKeySourceSnafu)?
-                .tuf_key();
-            keyids.push(
-                key_pair
-                    .key_id()
-                    .context(error::JsonSerializationSnafu {})?,
-            );
-            key_pairs.insert(
-                key_pair
-                    .key_id()
-                    .context(error::JsonSerializationSnafu {})?,
-                key_pair,
-            );
-        }
+        let new_role = NewRole::sign(name, Vec::new(), key_source, expiration, version)?;
         // Add the new role to targets_editor
         self.targets_editor_mut()?.delegate_role(
-            new_targets,
+            new_role.targets,
             paths,
-            key_pairs,
-            keyids,
+            new_role.key_pairs,
+            new_role.keyids,
             threshold,
         )?;
 
         Ok(self)
     }
 
+    /// Replaces the roles that the `Targets` role in `targets_editor` delegates by explicit paths
+    /// with `2^bit_length` hashed bins. Each bin is delegated the target names whose sha256 hash
+    /// starts with its bin number's `bit_length` bits, and is named `{name_prefix}-{bin}`, with the
+    /// bin number in hex. The bins are delegated after any roles that are kept.
+    ///
+    /// Each target listed by a replaced role is moved to its bin; a target listed by several of
+    /// them is taken from the first, which is the one clients trusted. The bins are signed with
+    /// `key_source` at `version`, expiring at `expiration`; the delegating role is signed as usual.
+    /// Roles that delegate to other roles can't be replaced.
+    #[allow(clippy::too_many_arguments)]
+    pub fn delegate_to_hash_bins(
+        &mut self,
+        bit_length: u8,
+        name_prefix: &str,
+        key_source: &[Box<dyn KeySource>],
+        threshold: NonZeroU64,
+        expiration: DateTime<Utc>,
+        version: NonZeroU64,
+    ) -> Result<&mut Self> {
+        ensure!(
+            (1..=MAX_HASH_BIN_BITS).contains(&bit_length),
+            error::HashBinBitLengthSnafu {
+                bit_length,
+                max: MAX_HASH_BIN_BITS,
+            }
+        );
+        let targets_editor = self.targets_editor_mut()?;
+
+        // Collect the targets of the replaced roles, in the order clients search them
+        let mut replaced = 0;
+        let mut kept = HashSet::new();
+        let mut targets = Vec::new();
+        let mut seen = HashSet::new();
+        for role in targets_editor.delegated_roles() {
+            if !matches!(role.paths, PathSet::Paths(_)) {
+                kept.insert(role.name.as_str());
+                continue;
+            }
+            let role_targets = &role.targets.as_ref().context(error::NoTargetsSnafu)?.signed;
+            ensure!(
+                role_targets
+                    .delegations
+                    .as_ref()
+                    .map_or(true, |delegations| delegations.roles.is_empty()),
+                error::HashBinNestedDelegationSnafu { role: &role.name }
+            );
+            for (target_name, target) in &role_targets.targets {
+                if seen.insert(target_name) {
+                    targets.push((target_name.clone(), target.clone()));
+                }
+            }
+            replaced += 1;
+        }
+        ensure!(
+            replaced > 0,
+            error::NoExplicitDelegationsSnafu {
+                role: targets_editor.name(),
+            }
+        );
+
+        let bins = hash_bins(targets, bit_length, name_prefix)?;
+        if let Some(bin) = bins.iter().find(|bin| kept.contains(bin.name.as_str())) {
+            return error::HashBinNameTakenSnafu { name: &bin.name }.fail();
+        }
+
+        // Sign every bin before changing the delegations, so a failure leaves them as they were
+        let new_roles = bins
+            .into_iter()
+            .map(|bin| {
+                let new_role =
+                    NewRole::sign(&bin.name, bin.targets, key_source, expiration, version)?;
+                ensure!(
+                    threshold.get() <= new_role.keyids.len() as u64,
+                    error::UnstableDelegationSnafu {
+                        role: bin.name,
+                        actual: new_role.keyids.len(),
+                        threshold: threshold.get(),
+                    }
+                );
+                Ok((bin.paths, new_role))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let targets_editor = self.targets_editor_mut()?;
+        targets_editor.remove_explicit_roles();
+        for (paths, new_role) in new_roles {
+            targets_editor.delegate_role(
+                new_role.targets,
+                paths,
+                new_role.key_pairs,
+                new_role.keyids,
+                threshold,
+            )?;
+        }
+
+        Ok(self)
+    }
+
     /// Set the `Snapshot` version
     pub fn snapshot_version(&mut self, snapshot_version: NonZeroU64) -> &mut Self {
         self.snapshot_version = Some(snapshot_version);
@@ -405,6 +604,15 @@ impl RepositoryEditor {
         self
     }
 
+    /// Set whether `Snapshot` lists the length and sha256 hash of each targets metadata file, or
+    /// only its version. They are listed by default. The TUF specification allows leaving them
+    /// out to keep `Snapshot` small, in which case clients verify targets metadata by version
+    /// alone.
+    pub fn snapshot_meta_hashes(&mut self, include: bool) -> &mut Self {
+        self.snapshot_meta_hashes = include;
+        self
+    }
+
     /// Set the `Targets` version
     pub fn targets_version(&mut self, targets_version: NonZeroU64) -> Result<&mut Self> {
         self.targets_editor_mut()?.version(targets_version);
@@ -429,6 +637,59 @@ impl RepositoryEditor {
         self
     }
 
+    /// Increments the version of the top-level role `role`, keeping its content and expiration as
+    /// they were loaded with `from_repo()`; set a new expiration afterward to change it. The roles
+    /// that list it are bumped as well, since each must list the new version: bumping `targets`
+    /// bumps `snapshot`, and bumping either bumps `timestamp`.
+    ///
+    /// When `snapshot` or `timestamp` is bumped, `targets.json` and delegated roles are kept
+    /// exactly as they were loaded, and the targets role can no longer be edited. When only
+    /// `timestamp` is bumped, the snapshot is signed again at its current version.
+    ///
+    /// `root` isn't signed by the editor, and delegated roles are bumped with
+    /// `stamp_delegated_role()`, so both are rejected.
+    pub fn bump_version(&mut self, role: RoleType) -> Result<&mut Self> {
+        let (snapshot_version, snapshot_expires) =
+            self.current_snapshot.context(error::MissingSnafu {
+                field: "current snapshot",
+            })?;
+        let (timestamp_version, timestamp_expires) =
+            self.current_timestamp.context(error::MissingSnafu {
+                field: "current timestamp",
+            })?;
+        let snapshot_version = match role {
+            RoleType::Targets => {
+                let targets = &self
+                    .signed_targets
+                    .as_ref()
+                    .context(error::MissingSnafu {
+                        field: "current targets",
+                    })?
+                    .signed;
+                let version = increment_version(targets.version, "targets")?;
+                let expires = targets.expires;
+                self.targets_editor_mut()?.version(version).expires(expires);
+                increment_version(snapshot_version, "snapshot")?
+            }
+            RoleType::Snapshot => {
+                self.targets_editor = None;
+                increment_version(snapshot_version, "snapshot")?
+            }
+            RoleType::Timestamp => {
+                self.targets_editor = None;
+                snapshot_version
+            }
+            RoleType::Root | RoleType::DelegatedTargets => {
+                return error::BumpVersionRoleSnafu { role }.fail();
+            }
+        };
+        self.snapshot_version(snapshot_version)
+            .snapshot_expires(snapshot_expires)
+            .timestamp_version(increment_version(timestamp_version, "timestamp")?)
+            .timestamp_expires(timestamp_expires);
+        Ok(self)
+    }
+
     /// Takes the current Targets from `targets_editor` and inserts the role to its proper place in `signed_targets`
     /// Sets `targets_editor` to None
     /// Must be called before `change_delegated_targets()`
@@ -464,6 +725,7 @@ impl RepositoryEditor {
             .as_mut()
             .context(error::NoTargetsSnafu)?
             .signed;
+        let delegators = delegators_of(targets, role);
         let (key_holder, targets) = if role == "targets" {
             (
                 KeyHolder::Root(self.signed_root.signed.signed.clone()),
@@ -484,7 +746,9 @@ impl RepositoryEditor {
                 .clone();
             (KeyHolder::Delegations(parent), targets.signed)
         };
-        self.targets_editor = Some(TargetsEditor::from_targets(role, targets, key_holder));
+        let mut targets_editor = TargetsEditor::from_targets(role, targets, key_holder);
+        targets_editor.delegators(delegators);
+        self.targets_editor = Some(targets_editor);
 
         Ok(self)
     }
@@ -515,7 +779,7 @@ impl RepositoryEditor {
         let encoded_name = encode_filename(name);
         let encoded_filename = format!("{encoded_name}.json");
         let role_url = metadata_base_url
-            .join(&encoded_filename)
+            .join(&encode_url_path(&encoded_filename))
             .with_context(|_| error::JoinUrlEncodedSnafu {
                 original: name,
                 encoded: encoded_name,
@@ -580,7 +844,7 @@ impl RepositoryEditor {
             let encoded_name = encode_filename(&name);
             let encoded_filename = format!("{encoded_name}.json");
             let role_url = metadata_base_url
-                .join(&encoded_filename)
+                .join(&encode_url_path(&encoded_filename))
                 .with_context(|_| error::JoinUrlEncodedSnafu {
                     original: &name,
                     encoded: encoded_name,
@@ -651,6 +915,168 @@ impl RepositoryEditor {
         Ok(self)
     }
 
+    /// Moves the delegated role `role` so that it is delegated by `new_parent` instead of its
+    /// current parent. The role's metadata, paths, threshold, and keys are carried over unchanged.
+    /// Both the old and the new parent have their versions incremented and are re-signed with
+    /// `keys`, so `keys` must contain signing keys for both parents.
+    /// Fails without modifying the repository if any target of `role` (or of roles it delegates)
+    /// would no longer be reachable through the delegation paths under `new_parent`.
+    /// Throws error if the `targets_editor` was not cleared using `sign_targets_editor()`
+    pub fn move_role(
+        &mut self,
+        role: &str,
+        new_parent: &str,
+        keys: &[Box<dyn KeySource>],
+    ) -> Result<&mut Self> {
+        if self.targets_editor.is_some() {
+            return Err(error::Error::TargetsEditorSome);
+        }
+        // Work on a copy so a failed move leaves the repository untouched
+        let mut targets = self
+            .signed_targets
+            .as_ref()
+            .context(error::NoTargetsSnafu)?
+            .signed
+            .clone();
+        let old_parent = delegator_of(&targets, role).context(error::DelegateNotFoundSnafu {
+            name: role.to_string(),
+        })?;
+        if old_parent == new_parent {
+            return Ok(self);
+        }
+        // `role` can't be moved under itself or any role it delegates to
+        ensure!(
+            new_parent != role
+                && targets
+                    .delegated_targets(role)
+                    .map_or(true, |role_targets| {
+                        role_targets.signed.delegated_role(new_parent).is_err()
+                    }),
+            error::DelegationCycleSnafu {
+                role,
+                parent: new_parent,
+            }
+        );
+
+        // Detach the role from its current parent, taking along the keys it needs
+        let Delegations {
+            keys: old_keys,
+            roles: old_roles,
+        } = targets_of_mut(&mut targets, &old_parent)?
+            .delegations
+            .as_mut()
+            .context(error::NoDelegationsSnafu)?;
+        let index = old_roles
+            .iter()
+            .position(|delegated_role| delegated_role.name == role)
+            .context(error::DelegateNotFoundSnafu {
+                name: role.to_string(),
+            })?;
+        let delegated_role = old_roles.remove(index);
+        let mut role_keys = HashMap::new();
+        for keyid in &delegated_role.keyids {
+            if let Some(key) = old_keys.get(keyid) {
+                role_keys.insert(keyid.clone(), key.clone());
+            }
+        }
+        // Drop keys the old parent no longer uses for any of its remaining roles
+        old_keys.retain(|keyid, _| {
+            old_roles
+                .iter()
+                .any(|remaining| remaining.keyids.contains(keyid))
+        });
+
+        // Attach the role to its new parent
+        let new_delegations = targets_of_mut(&mut targets, new_parent)?
+            .delegations
+            .get_or_insert_with(Delegations::new);
+        new_delegations.keys.extend(role_keys);
+        new_delegations.roles.push(delegated_role);
+
+        // Every target below `role` must still be delegated to it through the new chain of paths
+        let moved = targets
+            .delegated_targets(role)
+            .context(error::DelegateMissingSnafu {
+                name: role.to_string(),
+            })?;
+        for (target_name, _) in moved.signed.targets_iter() {
+            targets
+                .find_target(target_name)
+                .context(error::MovedRolePathsSnafu {
+                    role,
+                    parent: new_parent,
+                })?;
+        }
+
+        self.signed_targets
+            .as_mut()
+            .context(error::NoTargetsSnafu)?
+            .signed = targets;
+
+        // Re-sign both parents since their delegations changed
+        self.resign_targets(&old_parent, None, keys)?;
+        self.resign_targets(new_parent, None, keys)?;
+        Ok(self)
+    }
+
+    /// Increments the version of the delegated role `name`, sets its expiration to `expires`, and
+    /// signs it with `keys`. Fails with `SigningKeysNotFound` if `keys` can't meet the role's
+    /// threshold, in which case the role is left unchanged.
+    /// Throws error if the `targets_editor` was not cleared using `sign_targets_editor()`
+    pub fn stamp_delegated_role(
+        &mut self,
+        name: &str,
+        expires: DateTime<Utc>,
+        keys: &[Box<dyn KeySource>],
+    ) -> Result<&mut Self> {
+        if self.targets_editor.is_some() {
+            return Err(error::Error::TargetsEditorSome);
+        }
+        ensure!(
+            name != "targets",
+            error::DelegateNotFoundSnafu {
+                name: name.to_string(),
+            }
+        );
+        let result = self.resign_targets(name, Some(expires), keys);
+        // A role that failed to sign is left in the targets editor; drop it
+        self.targets_editor = None;
+        result.map(|()| self)
+    }
+
+    /// Increments the version of the `Targets` role named `name` and signs it with `keys`,
+    /// setting its expiration to `expires` or keeping its current expiration
+    fn resign_targets(
+        &mut self,
+        name: &str,
+        expires: Option<DateTime<Utc>>,
+        keys: &[Box<dyn KeySource>],
+    ) -> Result<()> {
+        let targets = &self
+            .signed_targets
+            .as_ref()
+            .context(error::NoTargetsSnafu)?
+            .signed;
+        let (version, current_expires) = if name == "targets" {
+            (targets.version, targets.expires)
+        } else {
+            let delegated_targets = &targets
+                .delegated_targets(name)
+                .context(error::DelegateMissingSnafu {
+                    name: name.to_string(),
+                })?
+                .signed;
+            (delegated_targets.version, delegated_targets.expires)
+        };
+        let version = increment_version(version, name)?;
+        self.change_delegated_targets(name)?;
+        self.targets_editor_mut()?
+            .version(version)
+            .expires(expires.unwrap_or(current_expires));
+        self.sign_targets_editor(keys)?;
+        Ok(())
+    }
+
     // =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=
 
     /// Build the `Snapshot` struct
@@ -667,17 +1093,23 @@ impl RepositoryEditor {
         })?;
         let _extra = self.snapshot_extra.clone().unwrap_or_default();
 
-        let mut snapshot = Snapshot::new(SPEC_VERSION.to_string(), version, expires);
+        let mut snapshot = Snapshot::new(
+            self.spec_version
+                .clone()
+                .unwrap_or_else(|| SPEC_VERSION.to_string()),
+            version,
+            expires,
+        );
 
         // Snapshot stores metadata about targets and root
-        let targets_meta = Self::snapshot_meta(signed_targets);
+        let targets_meta = Self::snapshot_meta(signed_targets, self.snapshot_meta_hashes);
         snapshot
             .meta
             .insert("targets.json".to_owned(), targets_meta);
 
         if let Some(signed_delegated_targets) = signed_delegated_targets.as_ref() {
             for delegated_targets in &signed_delegated_targets.roles {
-                let meta = Self::snapshot_meta(delegated_targets);
+                let meta = Self::snapshot_meta(delegated_targets, self.snapshot_meta_hashes);
                 snapshot.meta.insert(
                     format!("{}.json", delegated_targets.signed.signed.name),
                     meta,
@@ -689,17 +1121,17 @@ impl RepositoryEditor {
     }
 
     /// Build a `SnapshotMeta` struct from a given `SignedRole<R>`. This metadata
-    /// includes the sha256 and length of the signed role.
-    fn snapshot_meta<R>(role: &SignedRole<R>) -> SnapshotMeta
+    /// includes the sha256 and length of the signed role if `include_hashes` is set.
+    fn snapshot_meta<R>(role: &SignedRole<R>, include_hashes: bool) -> SnapshotMeta
     where
         R: Role,
     {
         SnapshotMeta {
-            hashes: Some(Hashes {
+            hashes: include_hashes.then(|| Hashes {
                 sha256: role.sha256.to_vec().into(),
                 _extra: HashMap::new(),
             }),
-            length: Some(role.length),
+            length: include_hashes.then(|| role.length),
             version: role.signed.signed.version(),
             _extra: HashMap::new(),
         }
@@ -714,7 +1146,13 @@ impl RepositoryEditor {
             field: "timestamp expiration",
         })?;
         let _extra = self.timestamp_extra.clone().unwrap_or_default();
-        let mut timestamp = Timestamp::new(SPEC_VERSION.to_string(), version, expires);
+        let mut timestamp = Timestamp::new(
+            self.spec_version
+                .clone()
+                .unwrap_or_else(|| SPEC_VERSION.to_string()),
+            version,
+            expires,
+        );
 
         // Timestamp stores metadata about snapshot
         let snapshot_meta = Self::timestamp_meta(signed_snapshot);
@@ -744,6 +1182,203 @@ impl RepositoryEditor {
     }
 }
 
+/// Checks that `name` can be given to a new delegated role. Role names become the names of
+/// metadata files and parts of URLs, so a name must not be empty or longer than
+/// [`MAX_ROLE_NAME_LENGTH`], contain a path separator or `..`, or be the name of a top-level role.
+///
+/// Roles that are created or added with the editors are checked; roles in existing metadata are
+/// not, and are still percent-encoded wherever they are written.
+pub fn validate_role_name(name: &str) -> Result<()> {
+    let reason = if name.is_empty() {
+        "it is empty".to_string()
+    } else if name.len() > MAX_ROLE_NAME_LENGTH {
+        format!("it is longer than {MAX_ROLE_NAME_LENGTH} bytes")
+    } else if name.contains(&['/', '\\'][..]) {
+        "it contains a path separator".to_string()
+    } else if name == "." || name.contains("..") {
+        "it is '.' or contains '..'".to_string()
+    } else if ["root", "snapshot", "targets", "timestamp"].contains(&name) {
+        "it is the name of a top-level role".to_string()
+    } else {
+        return Ok(());
+    };
+    error::InvalidRoleNameSnafu { name, reason }.fail()
+}
+
+/// Signs `root` with the given keys and returns the signed root.json, ready to be written to disk
+/// or passed to [`RepositoryLoader::new`](crate::RepositoryLoader::new). The keys must make a
+/// threshold of signatures for the root role listed in `root`.
+pub fn sign_root(root: Root, keys: &[Box<dyn KeySource>]) -> Result<Vec<u8>> {
+    let threshold = root
+        .roles
+        .get(&RoleType::Root)
+        .context(error::NoRoleKeysinRootSnafu {
+            role: RoleType::Root.to_string(),
+        })?
+        .threshold;
+    let key_holder = KeyHolder::Root(root.clone());
+    let signed_root = SignedRole::new(root, &key_holder, keys, &SystemRandom::new())?;
+    ensure!(
+        signed_root.signed.signatures.len() as u64 >= threshold.get(),
+        error::SigningKeysNotFoundSnafu {
+            role: RoleType::Root.to_string(),
+        }
+    );
+    Ok(signed_root.buffer)
+}
+
+/// A newly created delegated role, signed, with the keys it is delegated
+struct NewRole {
+    targets: Signed<DelegatedTargets>,
+    keyids: Vec<Decoded<Hex>>,
+    key_pairs: HashMap<Decoded<Hex>, Key>,
+}
+
+impl NewRole {
+    /// Creates the role `name` listing `targets`, and signs it with `key_source`
+    fn sign(
+        name: &str,
+        targets: Vec<(TargetName, Target)>,
+        key_source: &[Box<dyn KeySource>],
+        expiration: DateTime<Utc>,
+        version: NonZeroU64,
+    ) -> Result<Self> {
+        // Create the new targets using targets editor
+        let mut new_targets_editor = TargetsEditor::new(name);
+        // Set the version and expiration
+        new_targets_editor.version(version).expires(expiration);
+        for (target_name, target) in targets {
+            new_targets_editor.add_target(target_name, target)?;
+        }
+        // Sign the new targets
+        let targets = new_targets_editor.create_signed(key_source)?;
+        // Find the keyids for key_source
+        let mut keyids = Vec::new();
+        let mut key_pairs = HashMap::new();
+        for source in key_source {
+            let key_pair = source
+                .as_sign()
+                .context(error::KeyPairFromKeySourceSnafu)?
+                .tuf_key();
+            keyids.push(
+                key_pair
+                    .key_id()
+                    .context(error::JsonSerializationSnafu {})?,
+            );
+            key_pairs.insert(
+                key_pair
+                    .key_id()
+                    .context(error::JsonSerializationSnafu {})?,
+                key_pair,
+            );
+        }
+        Ok(Self {
+            targets,
+            keyids,
+            key_pairs,
+        })
+    }
+}
+
+/// A hashed bin created by `delegate_to_hash_bins()`
+struct HashBin {
+    name: String,
+    paths: PathSet,
+    targets: Vec<(TargetName, Target)>,
+}
+
+/// Sorts `targets` into `2^bit_length` hashed bins named `{name_prefix}-{bin}`, in bin order.
+fn hash_bins(
+    targets: Vec<(TargetName, Target)>,
+    bit_length: u8,
+    name_prefix: &str,
+) -> Result<Vec<HashBin>> {
+    let width = usize::from((bit_length + 3) / 4);
+    let mut bins = (0..1_u32 << bit_length)
+        .map(|bin| HashBin {
+            name: format!("{name_prefix}-{bin:0width$x}"),
+            paths: PathSet::hash_bin(bin, bit_length),
+            targets: Vec::new(),
+        })
+        .collect::<Vec<_>>();
+    for (target_name, target) in targets {
+        let bin = &mut bins[PathSet::hash_bin_of(&target_name, bit_length) as usize];
+        // Make sure clients will find the target in its bin
+        ensure!(
+            bin.paths.matches_target_name(&target_name),
+            error::HashBinUnresolvableSnafu {
+                target: target_name.raw(),
+                bin: &bin.name,
+            }
+        );
+        bin.targets.push((target_name, target));
+    }
+    Ok(bins)
+}
+
+/// Returns the version that follows `version` of the role `role`
+fn increment_version(version: NonZeroU64, role: &str) -> Result<NonZeroU64> {
+    version
+        .get()
+        .checked_add(1)
+        .and_then(NonZeroU64::new)
+        .context(error::VersionOverflowSnafu { role })
+}
+
+/// Returns the name of the role that directly delegates `name`, if any
+fn delegator_of(targets: &Targets, name: &str) -> Option<String> {
+    let delegates = |targets: &Targets| {
+        targets.delegations.as_ref().map_or(false, |delegations| {
+            delegations.roles.iter().any(|role| role.name == name)
+        })
+    };
+    if delegates(targets) {
+        return Some("targets".to_string());
+    }
+    targets
+        .role_names()
+        .into_iter()
+        .find(|role| {
+            targets
+                .delegated_targets(role)
+                .map_or(false, |role_targets| delegates(&role_targets.signed))
+        })
+        .cloned()
+}
+
+/// Returns the roles that delegate to `name`, directly or through other roles, starting with
+/// "targets" and ending with the role that directly delegates `name`
+fn delegators_of(targets: &Targets, name: &str) -> Vec<String> {
+    let mut delegators: Vec<String> = Vec::new();
+    let mut current = name.to_string();
+    while current != "targets" {
+        match delegator_of(targets, &current) {
+            // Stop at a cycle that's already in the metadata rather than follow it forever
+            Some(delegator) if !delegators.contains(&delegator) => {
+                current = delegator.clone();
+                delegators.push(delegator);
+            }
+            _ => break,
+        }
+    }
+    delegators.reverse();
+    delegators
+}
+
+/// Returns a mutable reference to the `Targets` of the role named `name`, where "targets" refers
+/// to the top level `targets` itself
+fn targets_of_mut<'a>(targets: &'a mut Targets, name: &str) -> Result<&'a mut Targets> {
+    if name == "targets" {
+        return Ok(targets);
+    }
+    Ok(&mut targets
+        .delegated_targets_mut(name)
+        .context(error::DelegateMissingSnafu {
+            name: name.to_string(),
+        })?
+        .signed)
+}
+
 fn parse_url(url: &str) -> Result<Url> {
     let mut url = Cow::from(url);
     if !url.ends_with('/') {
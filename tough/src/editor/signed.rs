@@ -5,13 +5,15 @@
 //! signing, ready to be written to disk.
 //! Provides the `SignedDelegatedTargets` object which represents the output of `TargetsEditor` after
 //! signing, ready to be written to disk.
+//! Provides the `UnsignedRepository` object which represents the output of `RepositoryEditor`
+//! without signing, ready to be handed to an external signer.
 
 use crate::error::{self, Result};
 use crate::io::DigestAdapter;
 use crate::key_source::KeySource;
 use crate::schema::{
-    DelegatedTargets, KeyHolder, Role, RoleType, Root, Signature, Signed, Snapshot, Target,
-    Targets, Timestamp,
+    to_stable_json_pretty, DelegatedTargets, KeyHolder, Role, RoleType, Root, Signature, Signed,
+    Snapshot, Target, Targets, Timestamp,
 };
 use olpc_cjson::CanonicalFormatter;
 use ring::digest::{digest, SHA256, SHA256_OUTPUT_LEN};
@@ -27,7 +29,7 @@ use std::os::unix::fs::symlink;
 #[cfg(target_os = "windows")]
 use std::os::windows::fs::symlink_file as symlink;
 
-use crate::TargetName;
+use crate::{encode_filename, TargetName};
 use std::borrow::Cow;
 use std::path::{Path, PathBuf};
 use url::Url;
@@ -107,12 +109,11 @@ where
     /// Creates a `SignedRole<Role>` from a `Signed<Role>`.
     /// This is used to create signed roles for any signed metadata
     pub(crate) fn from_signed(role: Signed<T>) -> Result<SignedRole<T>> {
-        // Serialize the role, and calculate its length and
-        // sha256.
-        let mut buffer =
-            serde_json::to_vec_pretty(&role).context(error::SerializeSignedRoleSnafu {
-                role: T::TYPE.to_string(),
-            })?;
+        // Serialize the role with sorted keys, so the same metadata is always written the same
+        // way, and calculate its length and sha256.
+        let mut buffer = to_stable_json_pretty(&role).context(error::SerializeSignedRoleSnafu {
+            role: T::TYPE.to_string(),
+        })?;
         buffer.push(b'\n');
         let length = buffer.len() as u64;
 
@@ -138,6 +139,9 @@ where
 
     /// Provides access to the internal buffer containing the serialized form of the signed role.
     /// This buffer should be used anywhere this role is written to file.
+    ///
+    /// The role is serialized with [`to_stable_json_pretty`], so object keys are sorted and the
+    /// same metadata always gives the same bytes.
     pub fn buffer(&self) -> &Vec<u8> {
         &self.buffer
     }
@@ -203,6 +207,55 @@ pub enum PathExists {
 }
 derive_fromstr_from_deserialize!(PathExists);
 
+/// The file mode given to targets that are copied into a repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetMode {
+    /// Keep the permission bits of the source file, including any executable bits.
+    Preserve,
+    /// Set the given mode, e.g. `0o755`. Only applies on Unix; elsewhere the source file's
+    /// permissions are kept.
+    Set(u32),
+}
+
+/// How targets are laid out in the directory they are linked or copied into, or downloaded into
+/// with [`Repository::save_target_with_layout`](crate::Repository::save_target_with_layout).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetLayout {
+    /// Name each linked or copied target in the directory after its file name. Downloaded targets
+    /// are saved as by [`Repository::save_target`](crate::Repository::save_target).
+    Flat,
+    /// Name each linked or copied target after its path below the input directory, and mirror the
+    /// `/`-separated directories of its target name in the output directory, e.g. `a/b/c.bin`.
+    /// Target names that are absolute or contain a `..` component are rejected.
+    Nested,
+}
+
+/// What to do with metadata files in an output directory that belong to roles no longer listed
+/// in the snapshot being written, e.g. those of a removed delegated role.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StaleMetadata {
+    /// Leave stale files in place; they are still reported.
+    Keep,
+    /// Remove stale files.
+    Remove,
+}
+
+/// Applies `mode` to a target that was just copied to `path`. `fs::copy` already keeps the source
+/// file's permission bits, so only an explicit mode needs any work.
+fn set_target_mode(path: &Path, mode: TargetMode) -> Result<()> {
+    match mode {
+        TargetMode::Preserve => Ok(()),
+        #[cfg(not(target_os = "windows"))]
+        TargetMode::Set(mode) => {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, fs::Permissions::from_mode(mode))
+                .context(error::SetTargetModeSnafu { path })
+        }
+        #[cfg(target_os = "windows")]
+        TargetMode::Set(_) => Ok(()),
+    }
+}
+
 /// `TargetPath` represents an existing file at the path generated by `target_path`, if any, and
 /// the type of the file.  (Other file types will return an error instead.)  This can be used to
 /// determine whether you want to continue or fail.
@@ -234,6 +287,27 @@ pub struct SignedRepository {
 }
 
 impl SignedRepository {
+    /// Provides access to the signed root role.
+    pub fn root(&self) -> &SignedRole<Root> {
+        &self.root
+    }
+
+    /// Provides access to the signed top-level targets role. Its delegations hold the signed
+    /// metadata of the delegated roles.
+    pub fn targets(&self) -> &SignedRole<Targets> {
+        &self.targets
+    }
+
+    /// Provides access to the signed snapshot role.
+    pub fn snapshot(&self) -> &SignedRole<Snapshot> {
+        &self.snapshot
+    }
+
+    /// Provides access to the signed timestamp role.
+    pub fn timestamp(&self) -> &SignedRole<Timestamp> {
+        &self.timestamp
+    }
+
     /// Writes the metadata to the given directory. If consistent snapshots
     /// are used, the appropriate files are prefixed with their version.
     pub fn write<P>(&self, outdir: P) -> Result<()>
@@ -251,6 +325,85 @@ impl SignedRepository {
         Ok(())
     }
 
+    /// Like [`write`](Self::write), but also finds the metadata files in `outdir` that belong to
+    /// roles no longer listed in this repository's snapshot, e.g. a delegated role that was
+    /// removed, and keeps or removes them as `stale` says. Returns the stale files found.
+    pub fn write_with_stale_metadata<P>(
+        &self,
+        outdir: P,
+        stale: StaleMetadata,
+    ) -> Result<Vec<PathBuf>>
+    where
+        P: AsRef<Path>,
+    {
+        self.write(&outdir)?;
+        let stale_files = self.stale_metadata(&outdir)?;
+        if stale == StaleMetadata::Remove {
+            for path in &stale_files {
+                fs::remove_file(path).context(error::RemoveStaleMetadataSnafu { path })?;
+            }
+        }
+        Ok(stale_files)
+    }
+
+    /// Lists the metadata files in `outdir` that belong to roles no longer listed in this
+    /// repository's snapshot, without writing or removing anything; a dry run of
+    /// [`write_with_stale_metadata`](Self::write_with_stale_metadata).
+    ///
+    /// Only `.json` files directly in `outdir` are considered. The files of root, snapshot,
+    /// timestamp, and every role listed in snapshot are kept, including older versions written
+    /// for consistent snapshots.
+    pub fn stale_metadata<P>(&self, outdir: P) -> Result<Vec<PathBuf>>
+    where
+        P: AsRef<Path>,
+    {
+        let outdir = outdir.as_ref();
+        if !outdir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut current = vec![
+            "root".to_string(),
+            "snapshot".to_string(),
+            "timestamp".to_string(),
+        ];
+        current.extend(
+            self.snapshot
+                .signed
+                .signed
+                .meta
+                .keys()
+                .filter_map(|file| file.strip_suffix(".json"))
+                .map(encode_filename),
+        );
+
+        let mut stale_files = Vec::new();
+        for entry in WalkDir::new(outdir).min_depth(1).max_depth(1) {
+            let entry = entry.context(error::WalkDirSnafu { directory: outdir })?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let stem = match entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_suffix(".json"))
+            {
+                Some(stem) => stem,
+                None => continue,
+            };
+            // A consistent snapshot file name is prefixed with the role's version
+            let unversioned = stem
+                .split_once('.')
+                .filter(|(version, _)| version.parse::<u64>().is_ok())
+                .map(|(_, name)| name);
+            let is_current = |name: &str| current.iter().any(|current| current == name);
+            if !is_current(stem) && !unversioned.map_or(false, is_current) {
+                stale_files.push(entry.into_path());
+            }
+        }
+        stale_files.sort();
+        Ok(stale_files)
+    }
+
     /// Crawls a given directory and symlinks any targets found to the given
     /// "out" directory. If consistent snapshots are used, the target files
     /// are prefixed with their `sha256`.
@@ -265,6 +418,22 @@ impl SignedRepository {
         outdir: P2,
         replace_behavior: PathExists,
     ) -> Result<()>
+    where
+        P1: AsRef<Path>,
+        P2: AsRef<Path>,
+    {
+        self.link_targets_with_layout(indir, outdir, replace_behavior, TargetLayout::Flat)
+    }
+
+    /// Like [`link_targets`](Self::link_targets), but `layout` decides how the targets are laid
+    /// out in `outdir`.
+    pub fn link_targets_with_layout<P1, P2>(
+        &self,
+        indir: P1,
+        outdir: P2,
+        replace_behavior: PathExists,
+        layout: TargetLayout,
+    ) -> Result<()>
     where
         P1: AsRef<Path>,
         P2: AsRef<Path>,
@@ -272,8 +441,17 @@ impl SignedRepository {
         self.walk_targets(
             indir.as_ref(),
             outdir.as_ref(),
-            Self::link_target,
+            |this, input_path, outdir, replace_behavior, target_filename| {
+                this.link_target_with_layout(
+                    input_path,
+                    outdir,
+                    replace_behavior,
+                    target_filename,
+                    layout,
+                )
+            },
             replace_behavior,
+            layout,
         )
     }
 
@@ -291,6 +469,22 @@ impl SignedRepository {
         outdir: P2,
         replace_behavior: PathExists,
     ) -> Result<()>
+    where
+        P1: AsRef<Path>,
+        P2: AsRef<Path>,
+    {
+        self.copy_targets_with_mode(indir, outdir, replace_behavior, TargetMode::Preserve)
+    }
+
+    /// Like [`copy_targets`](Self::copy_targets), but `mode` decides the file mode of the copied
+    /// targets. Symlinked targets have no mode of their own, so `link_targets` has no equivalent.
+    pub fn copy_targets_with_mode<P1, P2>(
+        &self,
+        indir: P1,
+        outdir: P2,
+        replace_behavior: PathExists,
+        mode: TargetMode,
+    ) -> Result<()>
     where
         P1: AsRef<Path>,
         P2: AsRef<Path>,
@@ -298,8 +492,17 @@ impl SignedRepository {
         self.walk_targets(
             indir.as_ref(),
             outdir.as_ref(),
-            Self::copy_target,
+            |this, input_path, outdir, replace_behavior, target_filename| {
+                this.copy_target_with_mode(
+                    input_path,
+                    outdir,
+                    replace_behavior,
+                    target_filename,
+                    mode,
+                )
+            },
             replace_behavior,
+            TargetLayout::Flat,
         )
     }
 
@@ -315,12 +518,31 @@ impl SignedRepository {
         outdir: &Path,
         replace_behavior: PathExists,
         target_filename: Option<&TargetName>,
+    ) -> Result<()> {
+        self.link_target_with_layout(
+            input_path,
+            outdir,
+            replace_behavior,
+            target_filename,
+            TargetLayout::Flat,
+        )
+    }
+
+    /// Like [`link_target`](Self::link_target), but `layout` decides where in `outdir` the target
+    /// is linked.
+    pub fn link_target_with_layout(
+        &self,
+        input_path: &Path,
+        outdir: &Path,
+        replace_behavior: PathExists,
+        target_filename: Option<&TargetName>,
+        layout: TargetLayout,
     ) -> Result<()> {
         ensure!(
             input_path.is_file(),
             error::PathIsNotFileSnafu { path: input_path }
         );
-        match self.target_path(input_path, outdir, target_filename)? {
+        match self.target_path(input_path, outdir, target_filename, layout)? {
             TargetPath::New { path } => {
                 symlink(input_path, &path).context(error::LinkCreateSnafu { path })?;
             }
@@ -357,21 +579,42 @@ impl SignedRepository {
         outdir: &Path,
         replace_behavior: PathExists,
         target_filename: Option<&TargetName>,
+    ) -> Result<()> {
+        self.copy_target_with_mode(
+            input_path,
+            outdir,
+            replace_behavior,
+            target_filename,
+            TargetMode::Preserve,
+        )
+    }
+
+    /// Like [`copy_target`](Self::copy_target), but `mode` decides the file mode of the copied
+    /// target. A target that is skipped because it already exists is left as it is.
+    pub fn copy_target_with_mode(
+        &self,
+        input_path: &Path,
+        outdir: &Path,
+        replace_behavior: PathExists,
+        target_filename: Option<&TargetName>,
+        mode: TargetMode,
     ) -> Result<()> {
         ensure!(
             input_path.is_file(),
             error::PathIsNotFileSnafu { path: input_path }
         );
-        match self.target_path(input_path, outdir, target_filename)? {
+        match self.target_path(input_path, outdir, target_filename, TargetLayout::Flat)? {
             TargetPath::New { path } => {
-                fs::copy(input_path, &path).context(error::FileWriteSnafu { path })?;
+                fs::copy(input_path, &path).context(error::FileWriteSnafu { path: &path })?;
+                set_target_mode(&path, mode)?;
             }
             TargetPath::File { path } => match replace_behavior {
                 PathExists::Skip => {}
                 PathExists::Fail => error::PathExistsFailSnafu { path }.fail()?,
                 PathExists::Replace => {
                     fs::remove_file(&path).context(error::RemoveTargetSnafu { path: &path })?;
-                    fs::copy(input_path, &path).context(error::FileWriteSnafu { path })?;
+                    fs::copy(input_path, &path).context(error::FileWriteSnafu { path: &path })?;
+                    set_target_mode(&path, mode)?;
                 }
             },
             TargetPath::Symlink { path } => {
@@ -400,6 +643,80 @@ impl TargetsWalker for SignedRepository {
     }
 }
 
+/// Metadata built by [`RepositoryEditor::unsigned`](crate::editor::RepositoryEditor::unsigned)
+/// for an external signer: the top-level targets and snapshot roles, with empty `signatures`.
+///
+/// The bytes to sign for each role are its canonical form, given by [`Self::targets_to_sign`] and
+/// [`Self::snapshot_to_sign`]. Once a role is signed, its serialized form (and so its hash)
+/// changes, so the `buffer` of these roles must not be listed in other metadata.
+#[derive(Debug)]
+pub struct UnsignedRepository {
+    pub(crate) root: SignedRole<Root>,
+    pub(crate) targets: SignedRole<Targets>,
+    pub(crate) snapshot: SignedRole<Snapshot>,
+    pub(crate) delegated_targets: Option<SignedDelegatedTargets>,
+}
+
+impl UnsignedRepository {
+    /// Provides access to the unsigned top-level targets role.
+    pub fn targets(&self) -> &SignedRole<Targets> {
+        &self.targets
+    }
+
+    /// Provides access to the unsigned snapshot role.
+    pub fn snapshot(&self) -> &SignedRole<Snapshot> {
+        &self.snapshot
+    }
+
+    /// Returns the bytes the targets role must be signed over.
+    pub fn targets_to_sign(&self) -> Result<Vec<u8>> {
+        to_sign(&self.targets)
+    }
+
+    /// Returns the bytes the snapshot role must be signed over.
+    pub fn snapshot_to_sign(&self) -> Result<Vec<u8>> {
+        to_sign(&self.snapshot)
+    }
+
+    /// Writes the metadata to the given directory, named as [`SignedRepository::write`] names
+    /// it, along with `targets.to-sign` and `snapshot.to-sign` files holding the bytes each role
+    /// must be signed over. root.json and any delegated roles are written as they were signed.
+    pub fn write<P>(&self, outdir: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let outdir = outdir.as_ref();
+        let consistent_snapshot = self.root.signed.signed.consistent_snapshot;
+        self.root.write(outdir, consistent_snapshot)?;
+        self.targets.write(outdir, consistent_snapshot)?;
+        self.snapshot.write(outdir, consistent_snapshot)?;
+        if let Some(delegated_targets) = &self.delegated_targets {
+            delegated_targets.write(outdir, consistent_snapshot)?;
+        }
+        for (role, data) in [
+            (RoleType::Targets, self.targets_to_sign()?),
+            (RoleType::Snapshot, self.snapshot_to_sign()?),
+        ] {
+            let path = outdir.join(format!("{}.to-sign", role));
+            fs::write(&path, data).context(error::FileWriteSnafu { path })?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the canonical form of a role, which is what its signatures are made over.
+fn to_sign<T: Role + Serialize>(role: &SignedRole<T>) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    let mut ser = serde_json::Serializer::with_formatter(&mut data, CanonicalFormatter::new());
+    role.signed
+        .signed
+        .serialize(&mut ser)
+        .context(error::SerializeRoleSnafu {
+            role: T::TYPE.to_string(),
+        })?;
+    Ok(data)
+}
+
 /// A set of signed targets role metadata.
 #[derive(Debug)]
 pub struct SignedDelegatedTargets {
@@ -439,6 +756,22 @@ impl SignedDelegatedTargets {
         outdir: P2,
         replace_behavior: PathExists,
     ) -> Result<()>
+    where
+        P1: AsRef<Path>,
+        P2: AsRef<Path>,
+    {
+        self.link_targets_with_layout(indir, outdir, replace_behavior, TargetLayout::Flat)
+    }
+
+    /// Like [`link_targets`](Self::link_targets), but `layout` decides how the targets are laid
+    /// out in `outdir`.
+    pub fn link_targets_with_layout<P1, P2>(
+        &self,
+        indir: P1,
+        outdir: P2,
+        replace_behavior: PathExists,
+        layout: TargetLayout,
+    ) -> Result<()>
     where
         P1: AsRef<Path>,
         P2: AsRef<Path>,
@@ -446,8 +779,17 @@ impl SignedDelegatedTargets {
         self.walk_targets(
             indir.as_ref(),
             outdir.as_ref(),
-            Self::link_target,
+            |this, input_path, outdir, replace_behavior, target_filename| {
+                this.link_target_with_layout(
+                    input_path,
+                    outdir,
+                    replace_behavior,
+                    target_filename,
+                    layout,
+                )
+            },
             replace_behavior,
+            layout,
         )
     }
 
@@ -465,6 +807,22 @@ impl SignedDelegatedTargets {
         outdir: P2,
         replace_behavior: PathExists,
     ) -> Result<()>
+    where
+        P1: AsRef<Path>,
+        P2: AsRef<Path>,
+    {
+        self.copy_targets_with_mode(indir, outdir, replace_behavior, TargetMode::Preserve)
+    }
+
+    /// Like [`copy_targets`](Self::copy_targets), but `mode` decides the file mode of the copied
+    /// targets. Symlinked targets have no mode of their own, so `link_targets` has no equivalent.
+    pub fn copy_targets_with_mode<P1, P2>(
+        &self,
+        indir: P1,
+        outdir: P2,
+        replace_behavior: PathExists,
+        mode: TargetMode,
+    ) -> Result<()>
     where
         P1: AsRef<Path>,
         P2: AsRef<Path>,
@@ -472,8 +830,17 @@ impl SignedDelegatedTargets {
         self.walk_targets(
             indir.as_ref(),
             outdir.as_ref(),
-            Self::copy_target,
+            |this, input_path, outdir, replace_behavior, target_filename| {
+                this.copy_target_with_mode(
+                    input_path,
+                    outdir,
+                    replace_behavior,
+                    target_filename,
+                    mode,
+                )
+            },
             replace_behavior,
+            TargetLayout::Flat,
         )
     }
 
@@ -489,12 +856,31 @@ impl SignedDelegatedTargets {
         outdir: &Path,
         replace_behavior: PathExists,
         target_filename: Option<&TargetName>,
+    ) -> Result<()> {
+        self.link_target_with_layout(
+            input_path,
+            outdir,
+            replace_behavior,
+            target_filename,
+            TargetLayout::Flat,
+        )
+    }
+
+    /// Like [`link_target`](Self::link_target), but `layout` decides where in `outdir` the target
+    /// is linked.
+    pub fn link_target_with_layout(
+        &self,
+        input_path: &Path,
+        outdir: &Path,
+        replace_behavior: PathExists,
+        target_filename: Option<&TargetName>,
+        layout: TargetLayout,
     ) -> Result<()> {
         ensure!(
             input_path.is_file(),
             error::PathIsNotFileSnafu { path: input_path }
         );
-        match self.target_path(input_path, outdir, target_filename)? {
+        match self.target_path(input_path, outdir, target_filename, layout)? {
             TargetPath::New { path } => {
                 symlink(input_path, &path).context(error::LinkCreateSnafu { path })?;
             }
@@ -531,21 +917,42 @@ impl SignedDelegatedTargets {
         outdir: &Path,
         replace_behavior: PathExists,
         target_filename: Option<&TargetName>,
+    ) -> Result<()> {
+        self.copy_target_with_mode(
+            input_path,
+            outdir,
+            replace_behavior,
+            target_filename,
+            TargetMode::Preserve,
+        )
+    }
+
+    /// Like [`copy_target`](Self::copy_target), but `mode` decides the file mode of the copied
+    /// target. A target that is skipped because it already exists is left as it is.
+    pub fn copy_target_with_mode(
+        &self,
+        input_path: &Path,
+        outdir: &Path,
+        replace_behavior: PathExists,
+        target_filename: Option<&TargetName>,
+        mode: TargetMode,
     ) -> Result<()> {
         ensure!(
             input_path.is_file(),
             error::PathIsNotFileSnafu { path: input_path }
         );
-        match self.target_path(input_path, outdir, target_filename)? {
+        match self.target_path(input_path, outdir, target_filename, TargetLayout::Flat)? {
             TargetPath::New { path } => {
-                fs::copy(input_path, &path).context(error::FileWriteSnafu { path })?;
+                fs::copy(input_path, &path).context(error::FileWriteSnafu { path: &path })?;
+                set_target_mode(&path, mode)?;
             }
             TargetPath::File { path } => match replace_behavior {
                 PathExists::Skip => {}
                 PathExists::Fail => error::PathExistsFailSnafu { path }.fail()?,
                 PathExists::Replace => {
                     fs::remove_file(&path).context(error::RemoveTargetSnafu { path: &path })?;
-                    fs::copy(input_path, &path).context(error::FileWriteSnafu { path })?;
+                    fs::copy(input_path, &path).context(error::FileWriteSnafu { path: &path })?;
+                    set_target_mode(&path, mode)?;
                 }
             },
             TargetPath::Symlink { path } => {
@@ -578,6 +985,55 @@ impl TargetsWalker for SignedDelegatedTargets {
     }
 }
 
+/// Names the file at `path` after its path below `indir`, with `/` separating its components.
+fn nested_target_name(indir: &Path, path: &Path) -> Result<TargetName> {
+    let relative = path
+        .strip_prefix(indir)
+        .ok()
+        .context(error::PathIsNotTargetSnafu { path })?;
+    let components = relative
+        .components()
+        .map(|component| {
+            component
+                .as_os_str()
+                .to_str()
+                .context(error::PathUtf8Snafu { path })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    TargetName::new(components.join("/"))
+}
+
+/// Returns the path below `outdir` that mirrors the `/`-separated directories of `name`, creating
+/// its directories. The file name is prefixed with the hex-encoded `sha256`, if given, as for
+/// consistent snapshots. Names that are absolute or contain a `..` component are rejected.
+pub(crate) fn nested_target_path(
+    outdir: &Path,
+    name: &TargetName,
+    sha256: Option<&[u8]>,
+) -> Result<PathBuf> {
+    // The raw name is checked, since resolving it would hide any `..` components
+    let raw = name.raw();
+    let mut components = raw.split('/').collect::<Vec<_>>();
+    ensure!(
+        !raw.starts_with('/')
+            && components
+                .iter()
+                .all(|component| *component != ".." && !component.contains('\\')),
+        error::TargetPathTraversalSnafu { name: raw }
+    );
+    // A target name is never empty, so there is always a last component
+    let file_name = components.pop().unwrap_or_default();
+    let dir = components
+        .into_iter()
+        .filter(|component| !component.is_empty() && *component != ".")
+        .fold(outdir.to_owned(), |dir, component| dir.join(component));
+    fs::create_dir_all(&dir).context(error::DirCreateSnafu { path: &dir })?;
+    Ok(match sha256 {
+        Some(sha256) => dir.join(format!("{}.{}", hex::encode(sha256), file_name)),
+        None => dir.join(file_name),
+    })
+}
+
 /// `TargetsWalker` is used to unify the logic related to copying and linking targets.
 /// `TargetsWalker`'s default implementation of `walk_targets()` and `target_path()` use
 /// the trait's `targets()` and `consistent_snapshot()` methods to get a map of targets and
@@ -590,13 +1046,15 @@ trait TargetsWalker {
 
     /// Walks a given directory and calls the provided function with every file found.
     /// The function is given the file path, the output directory where the user expects
-    /// it to go, and optionally a desired filename.
+    /// it to go, and optionally a desired filename. With a nested `layout`, the filename is the
+    /// file's path below `indir`.
     fn walk_targets<F>(
         &self,
         indir: &Path,
         outdir: &Path,
         f: F,
         replace_behavior: PathExists,
+        layout: TargetLayout,
     ) -> Result<()>
     where
         F: Fn(&Self, &Path, &Path, PathExists, Option<&TargetName>) -> Result<()>,
@@ -620,8 +1078,19 @@ trait TargetsWalker {
                 continue;
             };
 
+            let target_name = match layout {
+                TargetLayout::Flat => None,
+                TargetLayout::Nested => Some(nested_target_name(&abs_indir, entry.path())?),
+            };
+
             // Call the requested function to manipulate the path we found
-            if let Err(e) = f(self, entry.path(), outdir, replace_behavior, None) {
+            if let Err(e) = f(
+                self,
+                entry.path(),
+                outdir,
+                replace_behavior,
+                target_name.as_ref(),
+            ) {
                 match e {
                     // If we found a path that isn't a known target in the repo, skip it.
                     error::Error::PathIsNotTarget { .. } => continue,
@@ -641,6 +1110,7 @@ trait TargetsWalker {
         input: &Path,
         outdir: &Path,
         target_filename: Option<&TargetName>,
+        layout: TargetLayout,
     ) -> Result<TargetPath> {
         let outdir =
             std::fs::canonicalize(outdir).context(error::AbsolutePathSnafu { path: outdir })?;
@@ -681,14 +1151,24 @@ trait TargetsWalker {
             }
         );
 
-        let dest = if self.consistent_snapshot() {
-            outdir.join(format!(
-                "{}.{}",
-                hex::encode(&target_from_path.hashes.sha256),
-                target_name.resolved()
-            ))
-        } else {
-            outdir.join(target_name.resolved())
+        let dest = match layout {
+            TargetLayout::Flat => {
+                if self.consistent_snapshot() {
+                    outdir.join(format!(
+                        "{}.{}",
+                        hex::encode(&target_from_path.hashes.sha256),
+                        target_name.resolved()
+                    ))
+                } else {
+                    outdir.join(target_name.resolved())
+                }
+            }
+            TargetLayout::Nested => nested_target_path(
+                &outdir,
+                &target_name,
+                self.consistent_snapshot()
+                    .then(|| target_from_path.hashes.sha256.as_ref()),
+            )?,
         };
 
         // Return the target path, using the `TargetPath` enum that represents the type of file
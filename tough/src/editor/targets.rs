@@ -5,31 +5,47 @@
 //! Provides a `TargetsEditor` object for building and editing targets roles.
 
 use crate::editor::signed::{SignedDelegatedTargets, SignedRole};
+use crate::editor::{delegators_of, validate_role_name};
 use crate::error::{self, Result};
 use crate::fetch::fetch_max_size;
 use crate::key_source::KeySource;
 use crate::schema::decoded::{Decoded, Hex};
 use crate::schema::key::Key;
 use crate::schema::{
-    DelegatedRole, DelegatedTargets, Delegations, KeyHolder, PathSet, RoleType, Signed, Target,
-    Targets,
+    DelegatedRole, DelegatedTargets, Delegations, HashAlgorithm, KeyHolder, PathSet, RoleType,
+    Signed, Target, Targets, SPEC_VERSION,
 };
 use crate::transport::Transport;
+use crate::urlpath::encode_url_path;
 use crate::{encode_filename, Limits};
 use crate::{Repository, TargetName};
 use chrono::{DateTime, Utc};
 use ring::rand::SystemRandom;
 use serde_json::Value;
-use snafu::{OptionExt, ResultExt};
+use snafu::{ensure, OptionExt, ResultExt};
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::convert::TryInto;
-use std::fmt::Display;
+use std::fmt::{Debug, Display};
 use std::num::NonZeroU64;
 use std::path::Path;
+use std::sync::Arc;
 use url::Url;
 
-const SPEC_VERSION: &str = "1.0.0";
+/// A hook that checks a target's `custom` metadata before the target is added to a role, e.g.
+/// against a JSON schema, so that malformed custom metadata is never signed.
+///
+/// See [`TargetsEditor::custom_validator`] and
+/// [`RepositoryEditor::custom_validator`](crate::editor::RepositoryEditor::custom_validator).
+pub trait CustomValidator: Debug + Send + Sync {
+    /// Returns an error describing why `custom`, the custom metadata of the target `name`, is
+    /// invalid.
+    fn validate(
+        &self,
+        name: &TargetName,
+        custom: &HashMap<String, Value>,
+    ) -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync + 'static>>;
+}
 
 /// If you are not working with a repository that utilizes delegated targets, use the `RepositoryEditor`.
 ///
@@ -74,6 +90,8 @@ pub struct TargetsEditor {
     version: Option<NonZeroU64>,
     /// Expiration of the `Targets`
     expires: Option<DateTime<Utc>>,
+    /// The `spec_version` written to the role; `SPEC_VERSION` if None
+    spec_version: Option<String>,
     /// New roles that were created with the editor
     new_roles: Option<Vec<DelegatedRole>>,
 
@@ -82,6 +100,19 @@ pub struct TargetsEditor {
     limits: Option<Limits>,
 
     transport: Option<Box<dyn Transport>>,
+
+    /// Skips checking that targets are covered by the role's delegated paths when signing
+    allow_undelegated_targets: bool,
+
+    /// Checks the custom metadata of each added target
+    custom_validator: Option<Arc<dyn CustomValidator>>,
+
+    /// The roles that delegate to `name`, starting with "targets", used to reject delegations
+    /// that would create a cycle; empty if they aren't known
+    delegators: Vec<String>,
+
+    /// Hashes recorded, in addition to `sha256`, for targets added by path
+    target_hashes: Vec<HashAlgorithm>,
 }
 
 impl TargetsEditor {
@@ -94,11 +125,16 @@ impl TargetsEditor {
             existing_targets: None,
             version: None,
             expires: None,
+            spec_version: None,
             name: name.to_string(),
             new_roles: None,
             _extra: None,
             limits: None,
             transport: None,
+            allow_undelegated_targets: false,
+            custom_validator: None,
+            delegators: Vec::new(),
+            target_hashes: Vec::new(),
         }
     }
 
@@ -112,11 +148,16 @@ impl TargetsEditor {
             existing_targets: Some(targets.targets),
             version: None,
             expires: None,
+            spec_version: Some(targets.spec_version),
             name: name.to_string(),
             new_roles: None,
             _extra: Some(targets._extra),
             limits: None,
             transport: None,
+            allow_undelegated_targets: false,
+            custom_validator: None,
+            delegators: Vec::new(),
+            target_hashes: Vec::new(),
         }
     }
 
@@ -124,6 +165,7 @@ impl TargetsEditor {
     /// `version` and `expires` are thrown out to encourage updating the version and expiration
     /// If a `Repository` has been loaded, use `from_repo()` to preserve the `Transport` and `Limits`.
     pub fn from_repo(repo: Repository, name: &str) -> Result<Self> {
+        let delegators = delegators_of(&repo.targets.signed, name);
         let (targets, key_holder) = if name == "targets" {
             (
                 repo.targets.signed.clone(),
@@ -158,11 +200,16 @@ impl TargetsEditor {
             existing_targets: Some(targets.targets),
             version: None,
             expires: None,
+            spec_version: Some(targets.spec_version),
             name: name.to_string(),
             new_roles: None,
             _extra: Some(targets._extra),
             limits: Some(repo.limits),
             transport: Some(repo.transport),
+            allow_undelegated_targets: false,
+            custom_validator: None,
+            delegators,
+            target_hashes: Vec::new(),
         })
     }
 
@@ -171,11 +218,39 @@ impl TargetsEditor {
         self.limits = Some(limits);
     }
 
+    /// Sets the roles that delegate to this one, starting with "targets", so that delegations
+    /// that would create a cycle through them can be rejected
+    pub(crate) fn delegators(&mut self, delegators: Vec<String>) {
+        self.delegators = delegators;
+    }
+
     /// Add a transport to the `TargetsEditor`, only necessary if loading a role
     pub fn transport(&mut self, transport: Box<dyn Transport>) {
         self.transport = Some(transport);
     }
 
+    /// Allow signing targets that aren't covered by the paths this role was delegated by its
+    /// parent. Such metadata violates the TUF specification and clients will not trust those
+    /// targets, but it can be useful while staging changes to the delegation.
+    pub fn allow_undelegated_targets(&mut self, allow: bool) -> &mut Self {
+        self.allow_undelegated_targets = allow;
+        self
+    }
+
+    /// Check the `custom` metadata of each target added from now on with `validator`; a target
+    /// whose custom metadata is invalid is not added.
+    pub fn custom_validator<V: CustomValidator + 'static>(&mut self, validator: V) -> &mut Self {
+        self.custom_validator = Some(Arc::new(validator));
+        self
+    }
+
+    /// Record the hash of each of `algorithms`, in addition to `sha256`, for targets added by path
+    /// from now on.
+    pub fn target_hashes(&mut self, algorithms: &[HashAlgorithm]) -> &mut Self {
+        self.target_hashes = algorithms.to_vec();
+        self
+    }
+
     /// Add a `Target` to the `Targets` role
     pub fn add_target<T, E>(&mut self, name: T, target: Target) -> Result<&mut Self>
     where
@@ -188,6 +263,13 @@ impl TargetsEditor {
             }
             .build()
         })?;
+        if let Some(validator) = &self.custom_validator {
+            validator.validate(&target_name, &target.custom).context(
+                error::TargetCustomInvalidSnafu {
+                    name: target_name.raw(),
+                },
+            )?;
+        }
         self.new_targets
             .get_or_insert_with(HashMap::new)
             .insert(target_name, target);
@@ -216,7 +298,7 @@ impl TargetsEditor {
         )?;
 
         // Build a Target from the path given. If it is not a file, this will fail
-        let target = Target::from_path(target_path)
+        let target = Target::from_path_with_hashes(target_path, &self.target_hashes)
             .context(error::TargetFromPathSnafu { path: target_path })?;
 
         self.add_target(target_name, target)?;
@@ -248,6 +330,47 @@ impl TargetsEditor {
         self
     }
 
+    /// Renames every target in this role, e.g. to add or strip a prefix when targets move to a
+    /// new storage layout. `rename` is given each target's current name and returns its new name;
+    /// the targets themselves are unchanged. Fails without renaming anything if a new name is
+    /// invalid or two targets would be given the same name.
+    pub fn rename_targets<F>(&mut self, mut rename: F) -> Result<&mut Self>
+    where
+        F: FnMut(&TargetName) -> String,
+    {
+        let mut targets = self.existing_targets.clone().unwrap_or_default();
+        targets.extend(self.new_targets.clone().unwrap_or_default());
+
+        // Map each new name to the target and the name it was renamed from
+        let mut renamed: HashMap<TargetName, (TargetName, Target)> = HashMap::new();
+        for (name, target) in targets {
+            let new_name = TargetName::new(rename(&name)).map_err(|e| {
+                error::InvalidTargetNameSnafu {
+                    inner: e.to_string(),
+                }
+                .build()
+            })?;
+            if let Some((other, _)) = renamed.get(&new_name) {
+                return error::RenameTargetCollisionSnafu {
+                    first: other.raw(),
+                    second: name.raw(),
+                    name: new_name.raw(),
+                }
+                .fail();
+            }
+            renamed.insert(new_name, (name, target));
+        }
+
+        self.existing_targets = Some(
+            renamed
+                .into_iter()
+                .map(|(new_name, (_, target))| (new_name, target))
+                .collect(),
+        );
+        self.new_targets = None;
+        Ok(self)
+    }
+
     /// Remove all targets from this role
     pub fn clear_targets(&mut self) -> &mut Self {
         self.existing_targets
@@ -269,6 +392,13 @@ impl TargetsEditor {
         self
     }
 
+    /// Set the `spec_version`. Roles loaded from metadata keep their existing `spec_version`
+    /// unless it is set here.
+    pub fn spec_version(&mut self, spec_version: &str) -> &mut Self {
+        self.spec_version = Some(spec_version.to_string());
+        self
+    }
+
     /// Adds a key to delegations keyids, adds the key to `role` if it is provided
     pub fn add_key(
         &mut self,
@@ -339,6 +469,34 @@ impl TargetsEditor {
         keyids: Vec<Decoded<Hex>>,
         threshold: NonZeroU64,
     ) -> Result<&mut Self> {
+        validate_role_name(&targets.signed.name)?;
+        // Make sure the delegated role doesn't lead back to this role or the roles delegating it
+        let mut chain = self
+            .delegators
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(self.name.as_str()))
+            .collect::<Vec<_>>();
+        if let Some(cycle) = find_cycle(
+            &mut chain,
+            &targets.signed.name,
+            Some(&targets.signed.targets),
+        ) {
+            return error::DelegateCycleSnafu {
+                role: targets.signed.name,
+                cycle,
+            }
+            .fail();
+        }
+        // Make sure the delegated role can actually be loaded with these keys
+        ensure!(
+            threshold.get() <= keyids.len() as u64,
+            error::UnstableDelegationSnafu {
+                role: &targets.signed.name,
+                actual: keyids.len(),
+                threshold: threshold.get(),
+            }
+        );
         self.add_key(key_pairs, None)?;
         self.new_roles
             .get_or_insert(Vec::new())
@@ -356,6 +514,32 @@ impl TargetsEditor {
         Ok(self)
     }
 
+    /// The name of the `Targets` role being edited
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The roles this role delegates, including those added with `delegate_role()`, in the order
+    /// clients search them
+    pub(crate) fn delegated_roles(&self) -> impl Iterator<Item = &DelegatedRole> {
+        self.delegations
+            .iter()
+            .flat_map(|delegations| delegations.roles.iter())
+            .chain(self.new_roles.iter().flatten())
+    }
+
+    /// Removes the roles this role delegates by explicit paths, including those added with
+    /// `delegate_role()`
+    pub(crate) fn remove_explicit_roles(&mut self) {
+        let explicit = |role: &DelegatedRole| matches!(role.paths, PathSet::Paths(_));
+        if let Some(delegations) = self.delegations.as_mut() {
+            delegations.roles.retain(|role| !explicit(role));
+        }
+        if let Some(new_roles) = self.new_roles.as_mut() {
+            new_roles.retain(|role| !explicit(role));
+        }
+    }
+
     /// Removes a role from delegations
     /// If `recursive` is `false`, `role` is only removed if it is directly delegated by this role
     /// If `true` removes whichever role eventually delegates 'role'
@@ -380,6 +564,34 @@ impl TargetsEditor {
         Ok(self)
     }
 
+    /// Replaces the paths that `role` is delegated by this role. If this role was itself delegated
+    /// by its parent, the new paths must fall within the paths it was delegated.
+    pub fn set_role_paths(&mut self, role: &str, paths: PathSet) -> Result<&mut Self> {
+        if let Some(KeyHolder::Delegations(parent)) = &self.key_holder {
+            if let Some(own_role) = parent.roles.iter().find(|own| own.name == self.name) {
+                ensure!(
+                    own_role.paths.contains_path_set(&paths),
+                    error::PathsNotDelegatedSnafu {
+                        role,
+                        parent: &self.name,
+                    }
+                );
+            }
+        }
+        let delegations = self
+            .delegations
+            .as_mut()
+            .context(error::NoDelegationsSnafu)?;
+        let delegated_role = delegations
+            .roles
+            .iter_mut()
+            .chain(self.new_roles.iter_mut().flatten())
+            .find(|delegated_role| delegated_role.name == role)
+            .context(error::DelegateNotFoundSnafu { name: role })?;
+        delegated_role.paths = paths;
+        Ok(self)
+    }
+
     /// Adds a role to `new_roles` using a metadata file located at `metadata_url`/`name`.json
     /// `add_role()` uses `delegate_role()` to add a role from an existing metadata file.
     pub fn add_role(
@@ -390,6 +602,7 @@ impl TargetsEditor {
         threshold: NonZeroU64,
         keys: Option<HashMap<Decoded<Hex>, Key>>,
     ) -> Result<&mut Self> {
+        validate_role_name(name)?;
         let limits = self.limits.context(error::MissingLimitsSnafu)?;
         let transport: &dyn Transport = self
             .transport
@@ -402,7 +615,7 @@ impl TargetsEditor {
         let encoded_name = encode_filename(name);
         let encoded_filename = format!("{encoded_name}.json");
         let role_url = metadata_base_url
-            .join(&encoded_filename)
+            .join(&encode_url_path(&encoded_filename))
             .with_context(|_| error::JoinUrlEncodedSnafu {
                 original: name,
                 encoded: encoded_name,
@@ -478,7 +691,10 @@ impl TargetsEditor {
         Ok(DelegatedTargets {
             name: self.name.clone(),
             targets: Targets {
-                spec_version: SPEC_VERSION.to_string(),
+                spec_version: self
+                    .spec_version
+                    .clone()
+                    .unwrap_or_else(|| SPEC_VERSION.to_string()),
                 version,
                 expires,
                 targets,
@@ -488,8 +704,38 @@ impl TargetsEditor {
         })
     }
 
+    /// Ensures every target is covered by the paths this role was delegated by its parent. The
+    /// top-level targets role and roles that haven't been delegated yet aren't checked.
+    fn validate_target_paths(&self, targets: &DelegatedTargets) -> Result<()> {
+        if self.allow_undelegated_targets {
+            return Ok(());
+        }
+        if let Some(KeyHolder::Delegations(delegations)) = &self.key_holder {
+            if let Some(role) = delegations.roles.iter().find(|role| role.name == self.name) {
+                let mut undelegated = targets
+                    .targets
+                    .targets
+                    .keys()
+                    .filter(|target_name| !role.paths.matches_target_name(target_name))
+                    .map(|target_name| target_name.raw().to_string())
+                    .collect::<Vec<_>>();
+                undelegated.sort();
+                ensure!(
+                    undelegated.is_empty(),
+                    error::UndelegatedTargetsSnafu {
+                        role: &self.name,
+                        targets: undelegated,
+                    }
+                );
+            }
+        }
+        Ok(())
+    }
+
     /// Creates a `KeyHolder` to sign the `Targets` role with the signing keys provided
     fn create_key_holder(&self, keys: &[Box<dyn KeySource>]) -> Result<KeyHolder> {
+        // Without a key holder, this is a new role, so make sure its name is safe to write
+        validate_role_name(&self.name)?;
         // There isn't a KeyHolder, so create one based on the provided keys
         let mut delegations = Delegations::new();
         // First create the tuf key pairs and keyids
@@ -541,9 +787,9 @@ impl TargetsEditor {
             self.create_key_holder(keys)?
         };
         // create a signed role for the targets being edited
-        let targets = self
-            .build_targets()
-            .and_then(|targets| SignedRole::new(targets, &key_holder, keys, &rng))?;
+        let targets = self.build_targets()?;
+        self.validate_target_paths(&targets)?;
+        let targets = SignedRole::new(targets, &key_holder, keys, &rng)?;
         Ok(targets.signed)
     }
 
@@ -560,10 +806,9 @@ impl TargetsEditor {
         };
 
         // create a signed role for the targets we are editing
-        let signed_targets = self
-            .build_targets()
-            .and_then(|targets| SignedRole::new(targets, &key_holder, keys, &rng))?;
-        roles.push(signed_targets);
+        let targets = self.build_targets()?;
+        self.validate_target_paths(&targets)?;
+        roles.push(SignedRole::new(targets, &key_holder, keys, &rng)?);
         // create signed roles for any role metadata we added to this targets
         if let Some(new_roles) = &self.new_roles {
             for role in new_roles {
@@ -583,6 +828,34 @@ impl TargetsEditor {
     }
 }
 
+/// Looks for a delegation cycle created by delegating `name`, whose metadata is `targets` if it's
+/// known, from the last role in `chain`, the chain of delegations that leads to it. Returns the
+/// cycle, e.g. "A -> B -> A", if there is one.
+fn find_cycle<'a>(
+    chain: &mut Vec<&'a str>,
+    name: &'a str,
+    targets: Option<&'a Targets>,
+) -> Option<String> {
+    if let Some(start) = chain.iter().position(|role| *role == name) {
+        let mut cycle = chain[start..].to_vec();
+        cycle.push(name);
+        return Some(cycle.join(" -> "));
+    }
+    let delegations = targets?.delegations.as_ref()?;
+    chain.push(name);
+    for role in &delegations.roles {
+        let role_targets = role
+            .targets
+            .as_ref()
+            .map(|role_targets| &role_targets.signed);
+        if let Some(cycle) = find_cycle(chain, &role.name, role_targets) {
+            return Some(cycle);
+        }
+    }
+    chain.pop();
+    None
+}
+
 fn parse_url(url: &str) -> Result<Url> {
     let mut url = Cow::from(url);
     if !url.ends_with('/') {
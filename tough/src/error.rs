@@ -81,6 +81,14 @@ pub enum Error {
         backtrace: Backtrace,
     },
 
+    /// With strict parsing, a metadata file has a JSON object with the same key more than once.
+    #[snafu(display("Failed to strictly parse {} metadata: {}", role, source))]
+    DuplicateJsonKey {
+        role: RoleType,
+        source: crate::schema::Error,
+        backtrace: Backtrace,
+    },
+
     /// A metadata file has expired.
     #[snafu(display("{} metadata is expired", role))]
     ExpiredMetadata {
@@ -119,6 +127,19 @@ pub enum Error {
     #[snafu(display("Can't build URL from relative path '{}'", path.display()))]
     FileUrl { path: PathBuf, backtrace: Backtrace },
 
+    #[snafu(display("Unrecognized key source URL scheme \"{}\"", scheme))]
+    UnrecognizedScheme {
+        scheme: String,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Failed to build key source for scheme \"{}\": {}", scheme, source))]
+    KeySourceConstruct {
+        scheme: String,
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Failed to write to {}: {}", path.display(), source))]
     FileWrite {
         path: PathBuf,
@@ -141,6 +162,27 @@ pub enum Error {
         backtrace: Backtrace,
     },
 
+    #[snafu(display(
+        "Targets not covered by the paths delegated to role '{}': {}",
+        role,
+        targets.join(", ")
+    ))]
+    UndelegatedTargets { role: String, targets: Vec<String> },
+
+    #[snafu(display(
+        "Paths for role '{}' are not within the paths delegated to its parent '{}'",
+        role,
+        parent
+    ))]
+    PathsNotDelegated { role: String, parent: String },
+
+    #[snafu(display(
+        "Unable to verify the {} hash declared for target '{}'",
+        algorithm,
+        name
+    ))]
+    UnverifiableHash { name: String, algorithm: String },
+
     #[snafu(display("Source path for target must be file or symlink - '{}'", path.display()))]
     InvalidFileType { path: PathBuf, backtrace: Backtrace },
 
@@ -191,6 +233,89 @@ pub enum Error {
     #[snafu(display("Unrecognized private key format"))]
     KeyUnrecognized { backtrace: Backtrace },
 
+    /// A key source doesn't hold a key with the key ID that was asked for.
+    #[snafu(display("Key source does not hold a key with key ID {}", key_id))]
+    KeyIdNotInSource {
+        key_id: String,
+        backtrace: Backtrace,
+    },
+
+    /// A credential key source names a credential that isn't a valid file name.
+    #[snafu(display("Invalid credential name '{}'", name))]
+    CredentialName { name: String, backtrace: Backtrace },
+
+    /// A credential key source's credential isn't in the credentials directory.
+    #[snafu(display(
+        "Credential '{}' not found in credentials directory {}",
+        name,
+        directory.display()
+    ))]
+    CredentialNotFound {
+        name: String,
+        directory: PathBuf,
+        backtrace: Backtrace,
+    },
+
+    /// A credential key source was asked to write a key; credentials are read-only.
+    #[snafu(display("Can't write key to credential '{}': credentials are read-only", name))]
+    CredentialReadOnly { name: String, backtrace: Backtrace },
+
+    /// A credential key source was used without a credentials directory to read from.
+    #[snafu(display(
+        "Can't read credential '{}': {} is not set",
+        name,
+        crate::key_source::CREDENTIALS_DIRECTORY
+    ))]
+    CredentialsDirectoryUnset { name: String, backtrace: Backtrace },
+
+    /// A role uses a key whose signature scheme the loader's [`KeyPolicy`] doesn't allow.
+    ///
+    /// [`KeyPolicy`]: crate::KeyPolicy
+    #[snafu(display(
+        "Key {} of role '{}' uses signature scheme '{}', which the key policy does not allow",
+        key_id,
+        role,
+        scheme
+    ))]
+    KeyPolicyScheme {
+        role: String,
+        key_id: String,
+        scheme: String,
+        backtrace: Backtrace,
+    },
+
+    /// A role uses an RSA key smaller than the loader's [`KeyPolicy`] allows.
+    ///
+    /// [`KeyPolicy`]: crate::KeyPolicy
+    #[snafu(display(
+        "Key {} of role '{}' is a {}-bit RSA key, but the key policy requires at least {} bits",
+        key_id,
+        role,
+        bits,
+        min_bits
+    ))]
+    KeyPolicyRsaSize {
+        role: String,
+        key_id: String,
+        bits: usize,
+        min_bits: usize,
+        backtrace: Backtrace,
+    },
+
+    /// A target's body was shorter or longer than the length declared in its metadata.
+    #[snafu(display(
+        "Length mismatch for {}: expected {} bytes, read {}",
+        context,
+        expected,
+        read
+    ))]
+    LengthMismatch {
+        context: String,
+        expected: u64,
+        read: u64,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Failed to create symlink at '{}': {}", path.display(), source))]
     LinkCreate {
         path: PathBuf,
@@ -259,6 +384,14 @@ pub enum Error {
         backtrace: Backtrace,
     },
 
+    /// An older snapshot was requested from a repository that doesn't keep old metadata.
+    #[snafu(display(
+        "Snapshot version {} can't be fetched; old metadata is only available from repositories \
+         using consistent snapshots",
+        version
+    ))]
+    OldSnapshotUnavailable { version: u64, backtrace: Backtrace },
+
     /// The library failed to parse a metadata file, either because it was not valid JSON or it did
     /// not conform to the expected schema.
     ///
@@ -275,6 +408,14 @@ pub enum Error {
         backtrace: Backtrace,
     },
 
+    #[snafu(display("Failed to read {} metadata from '{}': {}", role, url, source))]
+    ReadMetadata {
+        role: RoleType,
+        url: Url,
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+
     /// The library failed to parse the trusted root metadata file, either because it was not valid
     /// JSON or it did not conform to the expected schema. The *trusted* root metadata file is the
     /// file is either the `root` argument passed to `Repository::load`, or the most recently
@@ -285,6 +426,20 @@ pub enum Error {
         backtrace: Backtrace,
     },
 
+    /// The library failed to read the trusted root metadata file passed to
+    /// [`Repository::load`][crate::Repository::load].
+    #[snafu(display("Failed to read trusted root metadata: {}", source))]
+    ReadTrustedMetadata {
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+
+    /// A base URL doesn't use HTTPS, but [`RepositoryLoader::require_https`] was set.
+    ///
+    /// [`RepositoryLoader::require_https`]: crate::RepositoryLoader::require_https
+    #[snafu(display("Refusing to use '{}': HTTPS is required", url))]
+    InsecureUrl { url: Url, backtrace: Backtrace },
+
     /// Failed to parse a URL provided to [`Repository::load`][crate::Repository::load].
     #[snafu(display("Failed to parse URL {:?}: {}", url, source))]
     ParseUrl {
@@ -312,6 +467,20 @@ pub enum Error {
         backtrace: Backtrace,
     },
 
+    /// Renaming targets would give two targets the same name.
+    #[snafu(display(
+        "Renaming targets '{}' and '{}' would give both the name '{}'",
+        first,
+        second,
+        name
+    ))]
+    RenameTargetCollision {
+        first: String,
+        second: String,
+        name: String,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Failed to remove existing target path '{}': {}", path.display(), source))]
     RemoveTarget {
         path: PathBuf,
@@ -319,6 +488,13 @@ pub enum Error {
         backtrace: Backtrace,
     },
 
+    #[snafu(display("Failed to remove stale metadata file '{}': {}", path.display(), source))]
+    RemoveStaleMetadata {
+        path: PathBuf,
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Unable to get info about the outdir '{}': {}", path.display(), source))]
     SaveTargetDirInfo {
         path: PathBuf,
@@ -365,6 +541,13 @@ pub enum Error {
         filepath: PathBuf,
     },
 
+    #[snafu(display("Failed to set the mode of target '{}': {}", path.display(), source))]
+    SetTargetMode {
+        path: PathBuf,
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Failed to serialize role '{}' for signing: {}", role, source))]
     SerializeRole {
         role: String,
@@ -416,6 +599,13 @@ pub enum Error {
         latest_known_time: DateTime<Utc>,
     },
 
+    #[snafu(display("Invalid custom metadata for target '{}': {}", name, source))]
+    TargetCustomInvalid {
+        name: String,
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Refusing to replace {} with requested {} for target {}", found, expected, path.display()))]
     TargetFileTypeMismatch {
         expected: String,
@@ -446,6 +636,12 @@ pub enum Error {
     #[snafu(display("Unable to resolve target name '{}', expected a rooted path", name))]
     TargetNameRootMissing { name: String },
 
+    #[snafu(display(
+        "Refusing to mirror target '{}' into a directory, it is absolute or contains '..'",
+        name
+    ))]
+    TargetPathTraversal { name: String, backtrace: Backtrace },
+
     /// A transport error occurred while fetching a URL.
     #[snafu(display("Failed to fetch {}: {}", url, source))]
     Transport {
@@ -494,6 +690,39 @@ pub enum Error {
         backtrace: Backtrace,
     },
 
+    /// The snapshot metadata did not match the version or hash recorded for it in the timestamp
+    /// metadata, which may indicate a replay or mix-and-match attack.
+    #[snafu(display(
+        "Snapshot metadata {} mismatch: fetched '{}', timestamp.json expected '{}'",
+        property,
+        fetched,
+        expected
+    ))]
+    SnapshotMismatch {
+        property: &'static str,
+        expected: String,
+        fetched: String,
+        backtrace: Backtrace,
+    },
+
+    /// A fetched metadata file did not have the length recorded for it in the snapshot metadata.
+    /// A longer file may indicate an attack, and is rejected as soon as it runs past the declared
+    /// length; a shorter one was truncated.
+    #[snafu(display(
+        "{} metadata at '{}' does not have the length declared by snapshot.json: expected {} bytes, read {}",
+        role,
+        url,
+        expected,
+        read
+    ))]
+    SnapshotLengthMismatch {
+        role: RoleType,
+        url: Url,
+        expected: u64,
+        read: u64,
+        backtrace: Backtrace,
+    },
+
     /// A fetched metadata file did not have the version we expected it to have.
     #[snafu(display(
         "{} metadata version mismatch: fetched {}, expected {}",
@@ -508,6 +737,21 @@ pub enum Error {
         backtrace: Backtrace,
     },
 
+    /// A loaded role's version differs from the one pinned with
+    /// [`RepositoryLoader::pin_versions`](crate::RepositoryLoader::pin_versions).
+    #[snafu(display(
+        "{} metadata version {} doesn't match the pinned version {}",
+        role,
+        loaded,
+        pinned
+    ))]
+    PinnedVersion {
+        role: RoleType,
+        loaded: u64,
+        pinned: u64,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Error reading data from '{}': {}", url, source))]
     CacheFileRead {
         url: Url,
@@ -543,6 +787,12 @@ pub enum Error {
         backtrace: Backtrace,
     },
 
+    #[snafu(display("The target '{}' was not found", target_name.raw()))]
+    FetchPlanTargetNotFound {
+        target_name: TargetName,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Failed to walk directory tree '{}': {}", directory.display(), source))]
     WalkDir {
         directory: PathBuf,
@@ -581,6 +831,52 @@ pub enum Error {
     #[snafu(display("Role missing from snapshot meta: {}", name))]
     RoleNotInMeta { name: String },
 
+    /// Snapshot metadata lists a delegated role whose metadata can't be found
+    #[snafu(display(
+        "Snapshot metadata lists delegated role '{}', but its metadata at {} {}",
+        role,
+        url,
+        reason
+    ))]
+    MissingDelegatedMetadata {
+        role: String,
+        url: Url,
+        reason: String,
+        backtrace: Backtrace,
+    },
+
+    /// Delegated role metadata doesn't match the hash snapshot.json lists for it
+    #[snafu(display(
+        "Delegated role '{}' metadata at {} has sha256 {}, but snapshot.json expects {}",
+        role,
+        url,
+        calculated,
+        expected
+    ))]
+    DelegatedHashMismatch {
+        role: String,
+        url: Url,
+        calculated: String,
+        expected: String,
+        backtrace: Backtrace,
+    },
+
+    /// Delegated role metadata doesn't have the length snapshot.json lists for it
+    #[snafu(display(
+        "Delegated role '{}' metadata at {} does not have the length declared by snapshot.json: expected {} bytes, read {}",
+        role,
+        url,
+        expected,
+        read
+    ))]
+    DelegatedLengthMismatch {
+        role: String,
+        url: Url,
+        expected: u64,
+        read: u64,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("The key for {} was not included", role))]
     KeyNotFound {
         role: String,
@@ -646,8 +942,87 @@ pub enum Error {
         threshold: u64,
     },
 
+    /// A delegation's threshold can't be met by the keys it lists
+    #[snafu(display(
+        "Unstable delegation; found {} keys for role {}, threshold is {}",
+        actual,
+        role,
+        threshold
+    ))]
+    UnstableDelegation {
+        role: String,
+        actual: usize,
+        threshold: u64,
+    },
+
     #[snafu(display("The targets editor was not cleared"))]
     TargetsEditorSome,
+
+    /// A delegated role was moved under itself or one of its own delegatees
+    #[snafu(display(
+        "Moving role '{}' under '{}' would create a delegation cycle",
+        role,
+        parent
+    ))]
+    DelegationCycle { role: String, parent: String },
+
+    /// Delegating a role would create a delegation cycle
+    #[snafu(display(
+        "Delegating role '{}' would create a delegation cycle: {}",
+        role,
+        cycle
+    ))]
+    DelegateCycle { role: String, cycle: String },
+
+    /// A delegated role was given a name that can't safely be used as a file name
+    #[snafu(display("Invalid delegated role name '{}': {}", name, reason))]
+    InvalidRoleName { name: String, reason: String },
+
+    /// Hashed bins were requested with an unsupported number of bits
+    #[snafu(display(
+        "Hashed bins must use between 1 and {} bits of the target name hash, not {}",
+        max,
+        bit_length
+    ))]
+    HashBinBitLength { bit_length: u8, max: u8 },
+
+    /// A role delegated by explicit paths can't be replaced by hashed bins
+    #[snafu(display(
+        "Role '{}' delegates to other roles, so it can't be replaced by hashed bins",
+        role
+    ))]
+    HashBinNestedDelegation { role: String },
+
+    /// A hashed bin would have the name of a role that is kept
+    #[snafu(display("Hashed bin '{}' has the name of an existing role", name))]
+    HashBinNameTaken { name: String },
+
+    /// A target would not be found in the hashed bin it was moved to
+    #[snafu(display("Target '{}' does not resolve to hashed bin '{}'", target, bin))]
+    HashBinUnresolvable { target: String, bin: String },
+
+    /// There are no roles delegated by explicit paths to replace with hashed bins
+    #[snafu(display("Role '{}' delegates no roles by explicit paths", role))]
+    NoExplicitDelegations { role: String },
+
+    /// A moved role's targets are not delegated to it under its new parent
+    #[snafu(display(
+        "Targets of role '{}' are not delegated to it under '{}': {}",
+        role,
+        parent,
+        source
+    ))]
+    MovedRolePaths {
+        role: String,
+        parent: String,
+        source: schema::Error,
+    },
+
+    #[snafu(display("The editor can't bump the version of the '{}' role", role))]
+    BumpVersionRole { role: RoleType },
+
+    #[snafu(display("Version number overflow for role '{}'", role))]
+    VersionOverflow { role: String },
 }
 
 // used in `std::io::Read` implementations
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use crate::error::{self, Result};
-use crate::io::{DigestAdapter, MaxSizeAdapter};
+use crate::io::{DigestAdapter, LengthAdapter, MaxSizeAdapter};
 use crate::transport::Transport;
 use snafu::ResultExt;
 use std::io::Read;
@@ -23,6 +23,25 @@ pub(crate) fn fetch_max_size<'a>(
     ))
 }
 
+/// Fetches a body of at most `max_size` bytes whose sha256 digest the metadata says is `sha256`,
+/// passing the digest on to the transport so that it can fail early. The digest isn't checked as
+/// the body is read; the caller checks it, so that it can report a mismatch its own way.
+pub(crate) fn fetch_max_size_sha256<'a>(
+    transport: &'a dyn Transport,
+    url: Url,
+    max_size: u64,
+    specifier: &'static str,
+    sha256: &[u8],
+) -> Result<impl Read + Send + 'a> {
+    Ok(MaxSizeAdapter::new(
+        transport
+            .fetch_sha256(url.clone(), sha256)
+            .context(error::TransportSnafu { url })?,
+        specifier,
+        max_size,
+    ))
+}
+
 pub(crate) fn fetch_sha256<'a>(
     transport: &'a dyn Transport,
     url: Url,
@@ -33,7 +52,7 @@ pub(crate) fn fetch_sha256<'a>(
     Ok(DigestAdapter::sha256(
         Box::new(MaxSizeAdapter::new(
             transport
-                .fetch(url.clone())
+                .fetch_sha256(url.clone(), sha256)
                 .context(error::TransportSnafu { url: url.clone() })?,
             specifier,
             size,
@@ -42,3 +61,41 @@ pub(crate) fn fetch_sha256<'a>(
         url,
     ))
 }
+
+/// Fetches a body that must be exactly `length` bytes long. The length is checked as the body
+/// streams, so an oversized body fails as soon as it runs past `length`.
+pub(crate) fn fetch_length<'a>(
+    transport: &'a dyn Transport,
+    url: Url,
+    length: u64,
+) -> Result<impl Read + Send + 'a> {
+    Ok(LengthAdapter::new(
+        transport
+            .fetch(url.clone())
+            .context(error::TransportSnafu { url: url.clone() })?,
+        length,
+        url,
+    ))
+}
+
+/// Fetches a body that must be exactly `length` bytes long and have the given sha256 digest. The
+/// length is checked as the body streams, so a truncated or oversized body fails before, and
+/// regardless of, the hash check.
+pub(crate) fn fetch_length_sha256<'a>(
+    transport: &'a dyn Transport,
+    url: Url,
+    length: u64,
+    sha256: &[u8],
+) -> Result<impl Read + Send + 'a> {
+    Ok(DigestAdapter::sha256(
+        Box::new(LengthAdapter::new(
+            transport
+                .fetch_sha256(url.clone(), sha256)
+                .context(error::TransportSnafu { url: url.clone() })?,
+            length,
+            url.clone(),
+        )),
+        sha256,
+        url,
+    ))
+}
@@ -0,0 +1,102 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Computes the files a minimal client fetches to update and install a target.
+
+use crate::error::{self, Result};
+use crate::schema::{Target, Targets};
+use crate::urlpath::encode_url_path;
+use crate::{encode_filename, Repository, TargetName};
+use snafu::{OptionExt, ResultExt};
+use std::num::NonZeroU64;
+use url::Url;
+
+impl Repository {
+    /// Returns, in order, the URL of each file a minimal client fetches to update from a trusted
+    /// root.json at `trusted_root_version` to this repository's metadata and install the target
+    /// `name`:
+    ///
+    /// 1. each newer version of root.json, up to this repository's;
+    /// 2. timestamp.json, then snapshot.json and the top-level targets.json;
+    /// 3. the metadata of each delegated role consulted while searching for the target, in the
+    ///    order [`find_target`](crate::schema::Targets::find_target) consults them;
+    /// 4. the target itself.
+    ///
+    /// The root.json that a client requests after the newest one, to learn that it is the newest,
+    /// isn't listed, since it isn't found. Files are named as the client names them, e.g. with
+    /// version or hash prefixes when consistent snapshots are used.
+    ///
+    /// Returns an error if the repository doesn't list the target.
+    pub fn fetch_plan(
+        &self,
+        trusted_root_version: NonZeroU64,
+        name: &TargetName,
+    ) -> Result<Vec<Url>> {
+        let mut urls = Vec::new();
+        for version in trusted_root_version.get() + 1..=self.root.signed.version.get() {
+            urls.push(self.metadata_url(&format!("{}.root.json", version))?);
+        }
+        urls.push(self.metadata_url("timestamp.json")?);
+        urls.push(self.role_metadata_url("snapshot", self.snapshot.signed.version)?);
+        urls.push(self.role_metadata_url("targets", self.targets.signed.version)?);
+
+        let target = self.plan_target_search(&self.targets.signed, name, &mut urls)?;
+        let target = target.context(error::FetchPlanTargetNotFoundSnafu {
+            target_name: name.clone(),
+        })?;
+        let (_, filename) = self.target_digest_and_filename(target, name);
+        urls.push(self.target_url(&filename)?);
+        Ok(urls)
+    }
+
+    /// Searches `targets` and the roles it delegates to for the target `name`, as `find_target`
+    /// does, adding the metadata URL of each delegated role consulted to `urls`.
+    fn plan_target_search<'a>(
+        &self,
+        targets: &'a Targets,
+        name: &TargetName,
+        urls: &mut Vec<Url>,
+    ) -> Result<Option<&'a Target>> {
+        if let Some(target) = targets.targets.get(name) {
+            return Ok(Some(target));
+        }
+        for role in targets.delegations.iter().flat_map(|d| &d.roles) {
+            if !role.paths.matches_target_name(name) {
+                continue;
+            }
+            let role_meta = self
+                .snapshot
+                .signed
+                .meta
+                .get(&format!("{}.json", role.name))
+                .context(error::RoleNotInMetaSnafu { name: &role.name })?;
+            urls.push(self.role_metadata_url(&encode_filename(&role.name), role_meta.version)?);
+            if let Some(role_targets) = &role.targets {
+                if let Some(target) = self.plan_target_search(&role_targets.signed, name, urls)? {
+                    return Ok(Some(target));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the URL of the metadata of the role whose file name, without extension, is `stem`,
+    /// prefixed with `version` when consistent snapshots are used.
+    fn role_metadata_url(&self, stem: &str, version: NonZeroU64) -> Result<Url> {
+        if self.consistent_snapshot {
+            self.metadata_url(&format!("{}.{}.json", version, stem))
+        } else {
+            self.metadata_url(&format!("{}.json", stem))
+        }
+    }
+
+    /// Returns the URL of the metadata file `path`.
+    fn metadata_url(&self, path: &str) -> Result<Url> {
+        self.metadata_base_url
+            .join(&encode_url_path(path))
+            .context(error::JoinUrlSnafu {
+                path,
+                url: self.metadata_base_url.clone(),
+            })
+    }
+}
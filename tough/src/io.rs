@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use crate::error;
-use ring::digest::{Context, SHA256};
+use crate::schema::hash::Hasher;
+use ring::digest::{Algorithm, SHA256};
 use std::io::{self, Read};
 use url::Url;
 
@@ -10,16 +11,34 @@ pub(crate) struct DigestAdapter<'a> {
     url: Url,
     reader: Box<dyn Read + Send + 'a>,
     hash: Vec<u8>,
-    digest: Option<Context>,
+    digest: Option<Hasher>,
 }
 
 impl<'a> DigestAdapter<'a> {
     pub(crate) fn sha256(reader: Box<dyn Read + Send + 'a>, hash: &[u8], url: Url) -> Self {
+        Self::new(reader, &SHA256, hash, url)
+    }
+
+    pub(crate) fn new(
+        reader: Box<dyn Read + Send + 'a>,
+        algorithm: &'static Algorithm,
+        hash: &[u8],
+        url: Url,
+    ) -> Self {
+        Self::with_hasher(reader, Hasher::ring(algorithm), hash, url)
+    }
+
+    pub(crate) fn with_hasher(
+        reader: Box<dyn Read + Send + 'a>,
+        hasher: Hasher,
+        hash: &[u8],
+        url: Url,
+    ) -> Self {
         Self {
             url,
             reader,
             hash: hash.to_owned(),
-            digest: Some(Context::new(&SHA256)),
+            digest: Some(hasher),
         }
     }
 }
@@ -34,7 +53,7 @@ impl<'a> Read for DigestAdapter<'a> {
         let size = self.reader.read(buf)?;
         if size == 0 {
             let result = self.digest.take().unwrap().finish();
-            if result.as_ref() != self.hash.as_slice() {
+            if result != self.hash {
                 error::HashMismatchSnafu {
                     context: self.url.to_string(),
                     calculated: hex::encode(result),
@@ -92,9 +111,47 @@ impl<'a> Read for MaxSizeAdapter<'a> {
     }
 }
 
+/// Fails a read as soon as the body is known to differ from its expected length: when more bytes
+/// than expected have been read, or the end of the body is reached early.
+pub(crate) struct LengthAdapter<'a> {
+    url: Url,
+    reader: Box<dyn Read + Send + 'a>,
+    length: u64,
+    counter: u64,
+}
+
+impl<'a> LengthAdapter<'a> {
+    pub(crate) fn new(reader: Box<dyn Read + Send + 'a>, length: u64, url: Url) -> Self {
+        Self {
+            url,
+            reader,
+            length,
+            counter: 0,
+        }
+    }
+}
+
+impl<'a> Read for LengthAdapter<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let size = self.reader.read(buf)?;
+        self.counter += size as u64;
+        if self.counter > self.length
+            || (size == 0 && !buf.is_empty() && self.counter < self.length)
+        {
+            error::LengthMismatchSnafu {
+                context: self.url.to_string(),
+                expected: self.length,
+                read: self.counter,
+            }
+            .fail()?;
+        }
+        Ok(size)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::io::{DigestAdapter, MaxSizeAdapter};
+    use crate::io::{DigestAdapter, LengthAdapter, MaxSizeAdapter};
     use hex_literal::hex;
     use std::io::{Cursor, Read};
     use url::Url;
@@ -111,6 +168,27 @@ mod tests {
         assert!(reader.read_to_end(&mut buf).is_err());
     }
 
+    #[test]
+    fn test_length_adapter() {
+        let url = Url::parse("file:///").unwrap();
+        let mut reader =
+            LengthAdapter::new(Box::new(Cursor::new(b"hello".to_vec())), 5, url.clone());
+        let mut buf = Vec::new();
+        assert!(reader.read_to_end(&mut buf).is_ok());
+        assert_eq!(buf, b"hello");
+
+        // Too long
+        let mut reader =
+            LengthAdapter::new(Box::new(Cursor::new(b"hello".to_vec())), 4, url.clone());
+        let mut buf = Vec::new();
+        assert!(reader.read_to_end(&mut buf).is_err());
+
+        // Too short
+        let mut reader = LengthAdapter::new(Box::new(Cursor::new(b"hello".to_vec())), 6, url);
+        let mut buf = Vec::new();
+        assert!(reader.read_to_end(&mut buf).is_err());
+    }
+
     #[test]
     fn test_digest_adapter() {
         let mut reader = DigestAdapter::sha256(
@@ -4,11 +4,16 @@
 //! Provides an abstraction over the source of a signing key. This allows signing keys to be
 //! obtained, for example, from local files or from cloud provider key stores.
 use crate::error;
+use crate::schema::decoded::{Decoded, Hex};
 use crate::sign::{parse_keypair, Sign};
-use snafu::ResultExt;
+use crate::SafeUrlPath;
+use percent_encoding::percent_decode_str;
+use snafu::{ensure, OptionExt, ResultExt};
+use std::collections::HashMap;
 use std::fmt::Debug;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::result::Result;
+use url::Url;
 
 /// This trait should be implemented for each source of signing keys. Examples
 /// of sources include: files, AWS SSM, etc.
@@ -16,6 +21,14 @@ pub trait KeySource: Debug + Send + Sync {
     /// Returns an object that implements the `Sign` trait
     fn as_sign(&self) -> Result<Box<dyn Sign>, Box<dyn std::error::Error + Send + Sync + 'static>>;
 
+    /// Returns every key held by the source. Sources that can hold more than one key, such as an
+    /// HSM slot, should override this; by default it returns the key from `as_sign`.
+    fn as_signs(
+        &self,
+    ) -> Result<Vec<Box<dyn Sign>>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        Ok(vec![self.as_sign()?])
+    }
+
     /// Writes a key back to the `KeySource`
     fn write(
         &self,
@@ -27,7 +40,8 @@ pub trait KeySource: Debug + Send + Sync {
 /// Points to a local key using a filesystem path.
 #[derive(Debug)]
 pub struct LocalKeySource {
-    /// The path to a local key file in PEM pkcs8 or RSA format.
+    /// The path to a local key file in PEM pkcs8 or RSA format. A PEM file may hold several
+    /// keys, one per block; `as_sign` returns the first.
     pub path: PathBuf,
 }
 
@@ -38,6 +52,21 @@ impl KeySource for LocalKeySource {
         Ok(Box::new(parse_keypair(&data)?))
     }
 
+    fn as_signs(
+        &self,
+    ) -> Result<Vec<Box<dyn Sign>>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        let data = std::fs::read(&self.path).context(error::FileReadSnafu { path: &self.path })?;
+        let blocks = match pem::parse_many(&data) {
+            Ok(blocks) if blocks.len() > 1 => blocks,
+            _ => return Ok(vec![Box::new(parse_keypair(&data)?)]),
+        };
+        let mut keys: Vec<Box<dyn Sign>> = Vec::with_capacity(blocks.len());
+        for block in &blocks {
+            keys.push(Box::new(parse_keypair(pem::encode(block).as_bytes())?));
+        }
+        Ok(keys)
+    }
+
     fn write(
         &self,
         value: &str,
@@ -47,3 +76,212 @@ impl KeySource for LocalKeySource {
             .context(error::FileWriteSnafu { path: &self.path })?)
     }
 }
+
+/// The environment variable through which systemd passes the directory holding a service's
+/// credentials.
+pub const CREDENTIALS_DIRECTORY: &str = "CREDENTIALS_DIRECTORY";
+
+/// Points to a key passed to a systemd service as a credential, e.g. with `LoadCredential=` or
+/// `LoadCredentialEncrypted=`, so that the service can sign without a plaintext key file of its
+/// own. The key is read, in the same formats as [`LocalKeySource`], from the file named `name` in
+/// the directory given by the `CREDENTIALS_DIRECTORY` environment variable.
+///
+/// A [`KeySourceRegistry`] created with `new` builds a `CredentialKeySource` for URLs such as
+/// `cred:signing-key`.
+#[derive(Debug)]
+pub struct CredentialKeySource {
+    /// The name of the credential.
+    pub name: String,
+}
+
+impl CredentialKeySource {
+    /// Returns the path of the credential's file, failing if there's no credentials directory or
+    /// the credential isn't in it.
+    fn path(&self) -> crate::error::Result<PathBuf> {
+        // Credential names are file names within the directory
+        ensure!(
+            !self.name.is_empty()
+                && !self.name.contains('/')
+                && self.name != ".."
+                && self.name != ".",
+            error::CredentialNameSnafu { name: &self.name }
+        );
+        let directory = std::env::var_os(CREDENTIALS_DIRECTORY)
+            .filter(|directory| !directory.is_empty())
+            .context(error::CredentialsDirectoryUnsetSnafu { name: &self.name })?;
+        let directory = PathBuf::from(directory);
+        let path = directory.join(&self.name);
+        ensure!(
+            path.is_file(),
+            error::CredentialNotFoundSnafu {
+                name: &self.name,
+                directory
+            }
+        );
+        Ok(path)
+    }
+}
+
+/// Implements the `KeySource` trait for a `CredentialKeySource`
+impl KeySource for CredentialKeySource {
+    fn as_sign(&self) -> Result<Box<dyn Sign>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        LocalKeySource { path: self.path()? }.as_sign()
+    }
+
+    fn as_signs(
+        &self,
+    ) -> Result<Vec<Box<dyn Sign>>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        LocalKeySource { path: self.path()? }.as_signs()
+    }
+
+    fn write(
+        &self,
+        _value: &str,
+        _key_id_hex: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        Err(error::CredentialReadOnlySnafu { name: &self.name }
+            .build()
+            .into())
+    }
+}
+
+/// Selects the key with a given key ID from a `KeySource` that holds several keys, such as an HSM
+/// slot or a file of several PEM blocks.
+#[derive(Debug)]
+pub struct KeyIdKeySource {
+    /// The source holding the key.
+    pub source: Box<dyn KeySource>,
+    /// The ID of the key to sign with.
+    pub key_id: Decoded<Hex>,
+}
+
+/// Implements the `KeySource` trait for a `KeyIdKeySource`, failing if the key isn't held by the
+/// underlying source
+impl KeySource for KeyIdKeySource {
+    fn as_sign(&self) -> Result<Box<dyn Sign>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        for sign in self.source.as_signs()? {
+            if sign.tuf_key().key_id()? == self.key_id {
+                return Ok(sign);
+            }
+        }
+        Err(error::KeyIdNotInSourceSnafu {
+            key_id: hex::encode(&self.key_id),
+        }
+        .build()
+        .into())
+    }
+
+    fn write(
+        &self,
+        value: &str,
+        key_id_hex: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.source.write(value, key_id_hex)
+    }
+}
+
+/// A function that builds a `KeySource` from a URL whose scheme it was registered for.
+pub type KeySourceConstructor = Box<
+    dyn Fn(&Url) -> Result<Box<dyn KeySource>, Box<dyn std::error::Error + Send + Sync + 'static>>
+        + Send
+        + Sync,
+>;
+
+/// Maps URL schemes to constructors for the `KeySource` they refer to, so that signing keys can
+/// be named with strings such as "file:///path/to/key" or "./path/to/key".
+///
+/// A registry created with `new` understands the `file` scheme, which builds a [`LocalKeySource`],
+/// and the `cred` scheme, which builds a [`CredentialKeySource`]. Other sources of keys, such as a
+/// custom HSM, can be supported by calling `register` with their own scheme.
+pub struct KeySourceRegistry {
+    constructors: HashMap<String, KeySourceConstructor>,
+}
+
+impl KeySourceRegistry {
+    /// Creates a registry with the built-in `file` and `cred` schemes registered.
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        registry.register("file", |url| {
+            Ok(Box::new(LocalKeySource {
+                path: url.safe_url_filepath(),
+            }))
+        });
+        registry.register("cred", |url| {
+            Ok(Box::new(CredentialKeySource {
+                name: percent_decode_str(url.path()).decode_utf8()?.into_owned(),
+            }))
+        });
+        registry
+    }
+
+    /// Creates a registry with no schemes registered.
+    pub fn empty() -> Self {
+        Self {
+            constructors: HashMap::new(),
+        }
+    }
+
+    /// Registers `constructor` to build a `KeySource` for URLs with the given `scheme`, replacing
+    /// any constructor previously registered for it.
+    pub fn register<S, F>(&mut self, scheme: S, constructor: F) -> &mut Self
+    where
+        S: Into<String>,
+        F: Fn(
+                &Url,
+            )
+                -> Result<Box<dyn KeySource>, Box<dyn std::error::Error + Send + Sync + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.constructors
+            .insert(scheme.into(), Box::new(constructor));
+        self
+    }
+
+    /// Returns `true` if a constructor is registered for `scheme`.
+    pub fn contains(&self, scheme: &str) -> bool {
+        self.constructors.contains_key(scheme)
+    }
+
+    /// Builds the `KeySource` named by `input`. If `input` is an existing path it is treated as a
+    /// `file` URL, otherwise it is parsed as a URL and passed to the constructor registered for
+    /// its scheme.
+    pub fn resolve(&self, input: &str) -> error::Result<Box<dyn KeySource>> {
+        let input_as_path = Path::new(input);
+        let url = if input_as_path.exists() {
+            Url::from_file_path(input)
+                .ok() // dump unhelpful `()` error
+                .context(error::FileUrlSnafu {
+                    path: input_as_path,
+                })?
+        } else {
+            Url::parse(input).context(error::ParseUrlSnafu { url: input })?
+        };
+        let constructor =
+            self.constructors
+                .get(url.scheme())
+                .context(error::UnrecognizedSchemeSnafu {
+                    scheme: url.scheme(),
+                })?;
+        constructor(&url).context(error::KeySourceConstructSnafu {
+            scheme: url.scheme(),
+        })
+    }
+}
+
+impl Default for KeySourceRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for KeySourceRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut schemes = self.constructors.keys().collect::<Vec<_>>();
+        schemes.sort();
+        f.debug_struct("KeySourceRegistry")
+            .field("schemes", &schemes)
+            .finish()
+    }
+}
@@ -17,8 +17,14 @@
 //! Integration tests require `noxious-server` and are disabled by default behind a feature named `integ`.
 //! To run all tests, including integration tests: `cargo test --all-features` or
 //! `cargo test --features 'http,integ'`.
-
-#![forbid(missing_debug_implementations, missing_copy_implementations)]
+//! The SFTP integration tests also need the `sftp` feature and an SFTP server that serves the
+//! test data, set by the `TOUGH_TEST_SFTP_URL` and `TOUGH_TEST_SFTP_KEY` environment variables;
+//! they are skipped when those aren't set.
+
+#![forbid(missing_debug_implementations)]
+// Denied rather than forbidden so that a type can opt out where being `Copy` would depend on
+// features.
+#![deny(missing_copy_implementations)]
 #![deny(rust_2018_idioms)]
 // missing_docs is on its own line to make it easy to comment out when making changes.
 #![deny(missing_docs)]
@@ -31,42 +37,77 @@
 )]
 
 mod cache;
+mod cassette;
+mod clock;
+mod content_store;
 mod datastore;
 pub mod editor;
 pub mod error;
 mod fetch;
+mod fetch_plan;
 #[cfg(feature = "http")]
 pub mod http;
 mod io;
 pub mod key_source;
+mod parts;
+mod prefetched;
+#[cfg(feature = "s3")]
+pub mod s3;
 pub mod schema;
+#[cfg(feature = "sftp")]
+pub mod sftp;
 pub mod sign;
 mod target_name;
 mod transport;
 mod urlpath;
 
+pub use crate::cache::{target_fetch_url, CachePlan};
+pub use crate::cassette::{RecordingTransport, ReplayTransport};
+pub use crate::clock::{Clock, FixedClock, SystemClock};
+pub use crate::content_store::ContentStore;
 use crate::datastore::Datastore;
+use crate::editor::signed::{nested_target_path, PathExists, TargetLayout};
 use crate::error::Result;
-use crate::fetch::{fetch_max_size, fetch_sha256};
+use crate::fetch::{
+    fetch_length, fetch_length_sha256, fetch_max_size, fetch_max_size_sha256, fetch_sha256,
+};
 /// An HTTP transport that includes retries.
 #[cfg(feature = "http")]
 pub use crate::http::{HttpTransport, HttpTransportBuilder, RetryRead};
+use crate::parts::PartsTransport;
+pub use crate::parts::{NumberedParts, PartNames};
+pub use crate::prefetched::PrefetchedMetadata;
+/// An S3 transport that can check objects' declared digests before downloading them.
+#[cfg(feature = "s3")]
+pub use crate::s3::{S3Transport, S3TransportBuilder};
+use crate::schema::decoded::{Decoded, Hex};
 use crate::schema::{
-    DelegatedRole, Delegations, Role, RoleType, Root, Signed, Snapshot, Timestamp,
+    spec_version_known, DelegatedRole, Delegations, Key, Role, RoleType, Root, Signed, Snapshot,
+    Timestamp, SPEC_VERSION,
 };
+/// An SFTP transport that streams files over SSH.
+#[cfg(feature = "sftp")]
+pub use crate::sftp::{SftpAuth, SftpHostKey, SftpTransport, SftpTransportBuilder};
 pub use crate::target_name::TargetName;
 pub use crate::transport::{
-    DefaultTransport, FilesystemTransport, Transport, TransportError, TransportErrorKind,
+    DefaultTransport, FilesystemTransport, RetryBudget, Transport, TransportError,
+    TransportErrorKind, UrlSigner,
 };
+use crate::transport::{LoggingTransport, RetryBudgetTransport, UrlSigningTransport};
+use crate::urlpath::encode_url_path;
 pub use crate::urlpath::SafeUrlPath;
 use chrono::{DateTime, Utc};
 use log::warn;
 use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use ring::digest::{digest, SHA256};
 use snafu::{ensure, OptionExt, ResultExt};
 use std::collections::HashMap;
 use std::fs::create_dir_all;
 use std::io::Read;
+use std::num::{NonZeroU64, NonZeroUsize};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use tempfile::NamedTempFile;
 use url::Url;
 
@@ -105,6 +146,30 @@ impl From<ExpirationEnforcement> for bool {
     }
 }
 
+/// Represents which of a target's declared hashes must match when the target is fetched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashPolicy {
+    /// The target's `sha256` hash must match. Any other hashes declared for the target are
+    /// ignored.
+    Any,
+
+    /// Every hash declared for the target must match. Fetching a target fails if it declares a
+    /// hash with an algorithm that cannot be verified (only `sha256`, `sha384`, `sha512`, and
+    /// `blake2b` are supported).
+    All,
+}
+
+/// `HashPolicy` defaults to `Any`.
+impl Default for HashPolicy {
+    fn default() -> Self {
+        HashPolicy::Any
+    }
+}
+
+/// The number of delegated role metadata files fetched at the same time if no limit has been set
+/// with [`RepositoryLoader::delegation_fetch_concurrency`].
+pub const DEFAULT_DELEGATION_FETCH_CONCURRENCY: usize = 8;
+
 /// A builder for settings with which to load a [`Repository`]. Required settings are provided in
 /// the [`RepositoryLoader::new`] function. Optional parameters can be added after calling new.
 /// Finally, call [`RepositoryLoader::load`] to load the [`Repository`].
@@ -168,6 +233,22 @@ where
     limits: Option<Limits>,
     datastore: Option<PathBuf>,
     expiration_enforcement: Option<ExpirationEnforcement>,
+    hash_policy: Option<HashPolicy>,
+    key_policy: Option<KeyPolicy>,
+    delegation_fetch_concurrency: Option<NonZeroUsize>,
+    metadata_url_signer: Option<Arc<dyn UrlSigner>>,
+    target_url_signer: Option<Arc<dyn UrlSigner>>,
+    target_part_names: Option<Arc<dyn PartNames>>,
+    clock: Option<Arc<dyn Clock>>,
+    retry_budget: Option<RetryBudget>,
+    require_https: bool,
+    allow_file_urls: bool,
+    strict_parsing: bool,
+    pinned_versions: Option<PinnedVersions>,
+    #[cfg(feature = "http")]
+    proxy: Option<Url>,
+    #[cfg(feature = "http")]
+    no_proxy: Option<String>,
 }
 
 impl<R: Read> RepositoryLoader<R> {
@@ -189,6 +270,22 @@ impl<R: Read> RepositoryLoader<R> {
             limits: None,
             datastore: None,
             expiration_enforcement: None,
+            hash_policy: None,
+            key_policy: None,
+            delegation_fetch_concurrency: None,
+            metadata_url_signer: None,
+            target_url_signer: None,
+            target_part_names: None,
+            clock: None,
+            retry_budget: None,
+            require_https: false,
+            allow_file_urls: false,
+            strict_parsing: false,
+            pinned_versions: None,
+            #[cfg(feature = "http")]
+            proxy: None,
+            #[cfg(feature = "http")]
+            no_proxy: None,
         }
     }
 
@@ -234,6 +331,169 @@ impl<R: Read> RepositoryLoader<R> {
         self.expiration_enforcement = Some(exp);
         self
     }
+
+    /// Set the [`HashPolicy`] used to verify targets. If no policy has been set, `Any` will be
+    /// used.
+    #[must_use]
+    pub fn hash_policy(mut self, hash_policy: HashPolicy) -> Self {
+        self.hash_policy = Some(hash_policy);
+        self
+    }
+
+    /// Set the [`KeyPolicy`] that the keys of every role must satisfy. If no policy has been set,
+    /// the default policy will be used.
+    #[must_use]
+    pub fn key_policy(mut self, key_policy: KeyPolicy) -> Self {
+        self.key_policy = Some(key_policy);
+        self
+    }
+
+    /// Set the maximum number of delegated role metadata files that are fetched at the same time.
+    /// Each fetched role is still verified before it is trusted. If no limit has been set,
+    /// [`DEFAULT_DELEGATION_FETCH_CONCURRENCY`] will be used.
+    #[must_use]
+    pub fn delegation_fetch_concurrency(mut self, concurrency: NonZeroUsize) -> Self {
+        self.delegation_fetch_concurrency = Some(concurrency);
+        self
+    }
+
+    /// Set a [`UrlSigner`] that rewrites the URL of each metadata file right before it is fetched,
+    /// both while loading and by the resulting [`Repository`].
+    #[must_use]
+    pub fn metadata_url_signer<S: UrlSigner + 'static>(mut self, signer: S) -> Self {
+        self.metadata_url_signer = Some(Arc::new(signer));
+        self
+    }
+
+    /// Set a [`UrlSigner`] that rewrites the URL of each target right before it is fetched, e.g.
+    /// to produce the time-limited signed URLs some CDNs require.
+    #[must_use]
+    pub fn target_url_signer<S: UrlSigner + 'static>(mut self, signer: S) -> Self {
+        self.target_url_signer = Some(Arc::new(signer));
+        self
+    }
+
+    /// Set the [`PartNames`] of the part files that targets may be stored as. Each target is then
+    /// fetched as the concatenation of its parts, in order, and verified against its declared
+    /// length and hashes as a single stream. A target whose first part isn't found is fetched
+    /// whole, so targets stored in parts and whole can be mixed. Metadata is always fetched whole.
+    #[must_use]
+    pub fn target_parts<P: PartNames + 'static>(mut self, names: P) -> Self {
+        self.target_part_names = Some(Arc::new(names));
+        self
+    }
+
+    /// Set the [`Clock`] used to decide whether metadata has expired, both while loading and by
+    /// the resulting [`Repository`]. If no clock has been set, [`SystemClock`] will be used.
+    #[must_use]
+    pub fn clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Set a [`RetryBudget`] capping the retries of failed metadata fetches across the whole
+    /// [`RepositoryLoader::load`]. If no budget has been set, failed fetches are not retried
+    /// beyond what the transport does itself.
+    #[must_use]
+    pub fn retry_budget(mut self, budget: RetryBudget) -> Self {
+        self.retry_budget = Some(budget);
+        self
+    }
+
+    /// Require the metadata and targets base URLs to use HTTPS, so that a misconfigured
+    /// plaintext `http://` URL fails [`RepositoryLoader::load`] before anything is fetched. If
+    /// `allow_file` is true, `file://` URLs are accepted too, e.g. for a repository on local
+    /// disk.
+    #[must_use]
+    pub fn require_https(mut self, allow_file: bool) -> Self {
+        self.require_https = true;
+        self.allow_file_urls = allow_file;
+        self
+    }
+
+    /// Reject metadata in which any JSON object has the same key more than once, such as a
+    /// targets role listing the same target name twice. By default, as with most JSON parsers, the
+    /// last value of a repeated key is silently kept. Applies to every metadata file the
+    /// `Repository` loads, including on [`Repository::refresh`].
+    #[must_use]
+    pub fn strict_parsing(mut self) -> Self {
+        self.strict_parsing = true;
+        self
+    }
+
+    /// Require the repository to be at exactly `snapshot` and `timestamp` versions, e.g. for a
+    /// deployment that pins them in its configuration, so that [`RepositoryLoader::load`] fails
+    /// with [`error::Error::PinnedVersion`] if the mirror has moved past them, or hasn't reached
+    /// them yet. The versions are checked as soon as timestamp.json is verified, before
+    /// snapshot.json is fetched. Applies to [`Repository::refresh`] as well.
+    #[must_use]
+    pub fn pin_versions(mut self, snapshot: NonZeroU64, timestamp: NonZeroU64) -> Self {
+        self.pinned_versions = Some(PinnedVersions {
+            snapshot,
+            timestamp,
+        });
+        self
+    }
+
+    /// Fetch metadata and targets over HTTP and HTTPS through the proxy at `url`, which takes
+    /// precedence over the `HTTP_PROXY` and `HTTPS_PROXY` environment variables. If no proxy has
+    /// been set, those variables are used. See [`HttpTransport`](HttpTransport#proxy-support) for
+    /// details.
+    ///
+    /// This configures the [`DefaultTransport`], so it has no effect if a transport has been set
+    /// with [`RepositoryLoader::transport`]; use [`HttpTransportBuilder::proxy`] for that
+    /// transport instead.
+    #[cfg(feature = "http")]
+    #[must_use]
+    pub fn proxy(mut self, url: Url) -> Self {
+        self.proxy = Some(url);
+        self
+    }
+
+    /// Set the comma-separated `hosts` that are fetched from directly rather than through the
+    /// [`proxy`](Self::proxy), which take precedence over the `NO_PROXY` environment variable.
+    /// See [`HttpTransportBuilder::no_proxy`] for the format.
+    ///
+    /// Like [`RepositoryLoader::proxy`], this has no effect if a transport has been set.
+    #[cfg(feature = "http")]
+    #[must_use]
+    pub fn no_proxy<S: Into<String>>(mut self, hosts: S) -> Self {
+        self.no_proxy = Some(hosts.into());
+        self
+    }
+}
+
+/// The snapshot and timestamp versions set with [`RepositoryLoader::pin_versions`].
+#[derive(Debug, Clone, Copy)]
+struct PinnedVersions {
+    snapshot: NonZeroU64,
+    timestamp: NonZeroU64,
+}
+
+impl PinnedVersions {
+    /// Checks the version of a verified timestamp, and the snapshot version it lists, against the
+    /// pinned versions. The snapshot that is then loaded must have the version timestamp lists.
+    fn check(self, timestamp: &Signed<Timestamp>) -> Result<()> {
+        ensure!(
+            timestamp.signed.version == self.timestamp,
+            error::PinnedVersionSnafu {
+                role: RoleType::Timestamp,
+                loaded: timestamp.signed.version.get(),
+                pinned: self.timestamp.get(),
+            }
+        );
+        if let Some(snapshot_meta) = timestamp.signed.meta.get("snapshot.json") {
+            ensure!(
+                snapshot_meta.version == self.snapshot,
+                error::PinnedVersionSnafu {
+                    role: RoleType::Snapshot,
+                    loaded: snapshot_meta.version.get(),
+                    pinned: self.snapshot.get(),
+                }
+            );
+        }
+        Ok(())
+    }
 }
 
 /// Limits used when fetching repository metadata.
@@ -282,6 +542,101 @@ impl Default for Limits {
     }
 }
 
+/// Limits the keys that roles may use, as set by [`RepositoryLoader::key_policy`]. When the
+/// repository is loaded, every key listed for a top-level role in root.json, and for a delegated
+/// role by its delegating role, must satisfy the policy.
+///
+/// Signature schemes are named as written in metadata: `rsassa-pss-sha256`, `ed25519`, and
+/// `ecdsa-sha2-nistp256`.
+///
+/// The [`Default`] policy allows every supported scheme, and RSA keys of at least 2048 bits (the
+/// smallest that can be verified anyway).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyPolicy {
+    /// The signature schemes that keys may use. If `None`, any scheme not in `denied_schemes` is
+    /// allowed.
+    pub allowed_schemes: Option<Vec<String>>,
+
+    /// The signature schemes that keys may not use, even if they are in `allowed_schemes`.
+    pub denied_schemes: Vec<String>,
+
+    /// The minimum size in bits of an RSA key's modulus.
+    pub min_rsa_bits: usize,
+}
+
+impl Default for KeyPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_schemes: None,
+            denied_schemes: Vec::new(),
+            min_rsa_bits: 2048,
+        }
+    }
+}
+
+impl KeyPolicy {
+    /// Checks that the key `key_id`, listed for `role`, satisfies the policy.
+    fn check(&self, role: &str, key_id: &Decoded<Hex>, key: &Key) -> Result<()> {
+        let scheme = key.scheme_name();
+        ensure!(
+            !self.denied_schemes.iter().any(|denied| denied == scheme)
+                && self
+                    .allowed_schemes
+                    .as_ref()
+                    .map_or(true, |allowed| allowed.iter().any(|a| a == scheme)),
+            error::KeyPolicySchemeSnafu {
+                role,
+                key_id: hex::encode(key_id),
+                scheme,
+            }
+        );
+        if let Key::Rsa { .. } = key {
+            let bits = key.rsa_modulus_bits().unwrap_or(0);
+            ensure!(
+                bits >= self.min_rsa_bits,
+                error::KeyPolicyRsaSizeSnafu {
+                    role,
+                    key_id: hex::encode(key_id),
+                    bits,
+                    min_bits: self.min_rsa_bits,
+                }
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks the keys of every role listed in `root`.
+    fn check_root(&self, root: &Root) -> Result<()> {
+        let mut roles = root.roles.iter().collect::<Vec<_>>();
+        roles.sort_by_key(|(role, _)| role.to_string());
+        for (role, role_keys) in roles {
+            for key_id in &role_keys.keyids {
+                if let Some(key) = root.keys.get(key_id) {
+                    self.check(&role.to_string(), key_id, key)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the keys of every role delegated by `targets`, and by the roles it delegates to.
+    fn check_delegations(&self, targets: &crate::schema::Targets) -> Result<()> {
+        if let Some(delegations) = &targets.delegations {
+            for role in &delegations.roles {
+                for key_id in &role.keyids {
+                    if let Some(key) = delegations.keys.get(key_id) {
+                        self.check(&role.name, key_id, key)?;
+                    }
+                }
+                if let Some(targets) = &role.targets {
+                    self.check_delegations(&targets.signed)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Use this enum to specify whether or not we should include a prefix in the target name when
 /// saving a target.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -292,12 +647,35 @@ pub enum Prefix {
     Digest,
 }
 
+/// The version and expiration of one role's metadata, as listed by
+/// [`Repository::metadata_summary`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoleSummary {
+    /// The role's name: `root`, `timestamp`, `snapshot`, `targets`, or a delegated role's name.
+    pub name: String,
+    /// The version of the role's metadata.
+    pub version: NonZeroU64,
+    /// When the role's metadata expires.
+    pub expires: DateTime<Utc>,
+}
+
+impl RoleSummary {
+    fn new<T: Role>(name: impl Into<String>, role: &T) -> Self {
+        Self {
+            name: name.into(),
+            version: role.version(),
+            expires: role.expires(),
+        }
+    }
+}
+
 /// A TUF repository.
 ///
 /// You can create a `Repository` using a [`RepositoryLoader`].
 #[derive(Debug, Clone)]
 pub struct Repository {
     transport: Box<dyn Transport + Send + Sync>,
+    targets_transport: Box<dyn Transport + Send + Sync>,
     consistent_snapshot: bool,
     datastore: Datastore,
     earliest_expiration: DateTime<Utc>,
@@ -310,77 +688,134 @@ pub struct Repository {
     metadata_base_url: Url,
     targets_base_url: Url,
     expiration_enforcement: ExpirationEnforcement,
+    hash_policy: HashPolicy,
+    key_policy: KeyPolicy,
+    delegation_fetch_concurrency: usize,
+    strict_parsing: bool,
+    pinned_versions: Option<PinnedVersions>,
+    raw_metadata: HashMap<RoleType, Vec<u8>>,
 }
 
 impl Repository {
     /// Load and verify TUF repository metadata using a [`RepositoryLoader`] for the settings.
     fn load<R: Read>(loader: RepositoryLoader<R>) -> Result<Self> {
-        let datastore = Datastore::new(loader.datastore)?;
-        let transport = loader
-            .transport
-            .unwrap_or_else(|| Box::new(DefaultTransport::new()));
+        let datastore = Datastore::new(
+            loader.datastore,
+            loader.clock.unwrap_or_else(|| Arc::new(SystemClock)),
+        )?;
+        let transport: Box<dyn Transport + Send + Sync> = match loader.transport {
+            Some(transport) => transport,
+            #[cfg(feature = "http")]
+            None => {
+                let mut http = HttpTransportBuilder::new();
+                if let Some(proxy) = loader.proxy {
+                    http = http.proxy(proxy);
+                }
+                if let Some(no_proxy) = loader.no_proxy {
+                    http = http.no_proxy(no_proxy);
+                }
+                Box::new(DefaultTransport::new_with_http_settings(http))
+            }
+            #[cfg(not(feature = "http"))]
+            None => Box::new(DefaultTransport::new()),
+        };
+        // Log fetches below the URL signers, so the signed URLs are logged (redacted)
+        let transport: Box<dyn Transport + Send + Sync> =
+            Box::new(LoggingTransport { inner: transport });
+        let targets_transport = sign_urls(transport.clone(), loader.target_url_signer);
+        let targets_transport: Box<dyn Transport + Send + Sync> = match loader.target_part_names {
+            Some(names) => Box::new(PartsTransport {
+                inner: targets_transport,
+                names,
+            }),
+            None => targets_transport,
+        };
+        let transport = sign_urls(transport, loader.metadata_url_signer);
+        // The retry budget only applies to this load, not to later fetches by the `Repository`
+        let load_transport: Box<dyn Transport + Send + Sync> = match loader.retry_budget {
+            Some(budget) => Box::new(RetryBudgetTransport::new(transport.clone(), budget)),
+            None => transport.clone(),
+        };
         let limits = loader.limits.unwrap_or_default();
         let expiration_enforcement = loader.expiration_enforcement.unwrap_or_default();
+        let hash_policy = loader.hash_policy.unwrap_or_default();
+        let key_policy = loader.key_policy.unwrap_or_default();
+        let delegation_fetch_concurrency = loader
+            .delegation_fetch_concurrency
+            .map_or(DEFAULT_DELEGATION_FETCH_CONCURRENCY, NonZeroUsize::get);
         let metadata_base_url = parse_url(loader.metadata_base_url)?;
         let targets_base_url = parse_url(loader.targets_base_url)?;
+        if loader.require_https {
+            for url in [&metadata_base_url, &targets_base_url] {
+                ensure!(
+                    url.scheme() == "https" || (loader.allow_file_urls && url.scheme() == "file"),
+                    error::InsecureUrlSnafu { url: url.clone() }
+                );
+            }
+        }
 
         // 0. Load the trusted root metadata file + 1. Update the root metadata file
-        let root = load_root(
-            transport.as_ref(),
+        let (root, raw_root) = load_root(
+            load_transport.as_ref(),
             loader.root,
             &datastore,
             limits.max_root_size,
             limits.max_root_updates,
             &metadata_base_url,
             expiration_enforcement,
+            loader.strict_parsing,
         )?;
+        key_policy.check_root(&root.signed)?;
 
         // 2. Download the timestamp metadata file
-        let timestamp = load_timestamp(
-            transport.as_ref(),
+        let (timestamp, raw_timestamp) = load_timestamp(
+            load_transport.as_ref(),
             &root,
             &datastore,
             limits.max_timestamp_size,
             &metadata_base_url,
             expiration_enforcement,
+            loader.strict_parsing,
         )?;
+        if let Some(pinned_versions) = loader.pinned_versions {
+            pinned_versions.check(&timestamp)?;
+        }
 
         // 3. Download the snapshot metadata file
-        let snapshot = load_snapshot(
-            transport.as_ref(),
+        let (snapshot, raw_snapshot) = load_snapshot(
+            load_transport.as_ref(),
             &root,
             &timestamp,
             &datastore,
             &metadata_base_url,
             expiration_enforcement,
+            loader.strict_parsing,
         )?;
 
         // 4. Download the targets metadata file
-        let targets = load_targets(
-            transport.as_ref(),
+        let (targets, raw_targets) = load_targets(
+            load_transport.as_ref(),
             &root,
             &snapshot,
             &datastore,
             limits.max_targets_size,
+            delegation_fetch_concurrency,
             &metadata_base_url,
             expiration_enforcement,
+            loader.strict_parsing,
         )?;
+        key_policy.check_delegations(&targets.signed)?;
 
-        let expires_iter = [
-            (root.signed.expires, RoleType::Root),
-            (timestamp.signed.expires, RoleType::Timestamp),
-            (snapshot.signed.expires, RoleType::Snapshot),
-            (targets.signed.expires, RoleType::Targets),
-        ];
         let (earliest_expiration, earliest_expiration_role) =
-            expires_iter.iter().min_by_key(|tup| tup.0).unwrap();
+            earliest_expiration(&root, &timestamp, &snapshot, &targets);
 
         Ok(Self {
             transport,
+            targets_transport,
             consistent_snapshot: root.signed.consistent_snapshot,
             datastore,
-            earliest_expiration: *earliest_expiration,
-            earliest_expiration_role: *earliest_expiration_role,
+            earliest_expiration,
+            earliest_expiration_role,
             root,
             snapshot,
             timestamp,
@@ -389,6 +824,17 @@ impl Repository {
             metadata_base_url,
             targets_base_url,
             expiration_enforcement,
+            hash_policy,
+            key_policy,
+            delegation_fetch_concurrency,
+            strict_parsing: loader.strict_parsing,
+            pinned_versions: loader.pinned_versions,
+            raw_metadata: HashMap::from([
+                (RoleType::Root, raw_root),
+                (RoleType::Timestamp, raw_timestamp),
+                (RoleType::Snapshot, raw_snapshot),
+                (RoleType::Targets, raw_targets),
+            ]),
         })
     }
 
@@ -412,11 +858,217 @@ impl Repository {
         &self.timestamp
     }
 
+    /// Returns the root, timestamp, snapshot, or targets metadata exactly as it was fetched and
+    /// verified while loading, rather than a re-serialization of the parsed role. For root, these
+    /// are the bytes of the trusted root if no newer root was found. Returns `None` for delegated
+    /// targets.
+    pub fn raw_metadata(&self, role: RoleType) -> Option<&[u8]> {
+        self.raw_metadata.get(&role).map(Vec::as_slice)
+    }
+
+    /// Fetches the latest `timestamp.json` from the repository and verifies it against the trusted
+    /// root, without fetching snapshot or targets metadata. This is a cheap way to check that a
+    /// repository is alive and fresh, e.g. for monitoring.
+    ///
+    /// The same signature, rollback and expiration checks are applied as when loading the
+    /// repository. The fetched timestamp is returned, and its version and expiration can be read
+    /// from it; it does not replace the timestamp held by this `Repository`, nor the trusted
+    /// timestamp in the datastore.
+    pub fn refresh_timestamp(&self) -> Result<Signed<Timestamp>> {
+        fetch_timestamp(
+            self.transport.as_ref(),
+            &self.root,
+            &self.datastore,
+            self.limits.max_timestamp_size,
+            &self.metadata_base_url,
+            self.expiration_enforcement,
+            self.strict_parsing,
+        )
+        .map(|(timestamp, _)| timestamp)
+    }
+
+    /// Checks whether the repository has published an update, by fetching only the latest
+    /// `timestamp.json` through `remote_transport` and comparing the snapshot version it lists to
+    /// the version of the trusted snapshot. Snapshot and targets metadata aren't fetched, so this
+    /// is cheap enough to call on a schedule; call [`refresh`](Self::refresh) to load the update.
+    ///
+    /// The fetched timestamp is verified against the trusted root as in
+    /// [`refresh_timestamp`](Self::refresh_timestamp), and likewise isn't saved to the datastore.
+    /// Returns whether the remote snapshot version is newer than the trusted one, along with the
+    /// remote snapshot version.
+    pub fn is_update_available(
+        &self,
+        remote_transport: &dyn Transport,
+    ) -> Result<(bool, NonZeroU64)> {
+        let (timestamp, _) = fetch_timestamp(
+            remote_transport,
+            &self.root,
+            &self.datastore,
+            self.limits.max_timestamp_size,
+            &self.metadata_base_url,
+            self.expiration_enforcement,
+            self.strict_parsing,
+        )?;
+        let remote_version = timestamp
+            .signed
+            .meta
+            .get("snapshot.json")
+            .context(error::MetaMissingSnafu {
+                file: "snapshot.json",
+                role: RoleType::Timestamp,
+            })?
+            .version;
+        Ok((
+            remote_version > self.snapshot.signed.version,
+            remote_version,
+        ))
+    }
+
+    /// Checks the repository for an update and, if there is one, loads it in place: root is
+    /// updated if a newer one was published, then timestamp, snapshot and targets are fetched and
+    /// verified as when loading, using the same transport and settings. Returns whether anything
+    /// changed. If the timestamp and root are unchanged, snapshot and targets aren't fetched again.
+    ///
+    /// The new metadata only replaces the old once all of it has been verified; if any of it
+    /// fails, an error is returned and the metadata held by this `Repository` is left as it was.
+    /// Each role is saved to the datastore as soon as it is verified, though, so the datastore may
+    /// already hold a newer timestamp, snapshot or targets that was verified before the failure.
+    /// The datastore only serves as the trusted versions for rollback checks on later loads.
+    pub fn refresh(&mut self) -> Result<bool> {
+        // The top-level roles are always present in `raw_metadata`
+        let old_raw_root = &self.raw_metadata[&RoleType::Root];
+        let (root, raw_root) = load_root(
+            self.transport.as_ref(),
+            old_raw_root.as_slice(),
+            &self.datastore,
+            self.limits.max_root_size,
+            self.limits.max_root_updates,
+            &self.metadata_base_url,
+            self.expiration_enforcement,
+            self.strict_parsing,
+        )?;
+        self.key_policy.check_root(&root.signed)?;
+        let (timestamp, raw_timestamp) = load_timestamp(
+            self.transport.as_ref(),
+            &root,
+            &self.datastore,
+            self.limits.max_timestamp_size,
+            &self.metadata_base_url,
+            self.expiration_enforcement,
+            self.strict_parsing,
+        )?;
+        if let Some(pinned_versions) = self.pinned_versions {
+            pinned_versions.check(&timestamp)?;
+        }
+        if raw_root == *old_raw_root
+            && self.raw_metadata(RoleType::Timestamp) == Some(raw_timestamp.as_slice())
+        {
+            return Ok(false);
+        }
+
+        let (snapshot, raw_snapshot) = load_snapshot(
+            self.transport.as_ref(),
+            &root,
+            &timestamp,
+            &self.datastore,
+            &self.metadata_base_url,
+            self.expiration_enforcement,
+            self.strict_parsing,
+        )?;
+        let (targets, raw_targets) = load_targets(
+            self.transport.as_ref(),
+            &root,
+            &snapshot,
+            &self.datastore,
+            self.limits.max_targets_size,
+            self.delegation_fetch_concurrency,
+            &self.metadata_base_url,
+            self.expiration_enforcement,
+            self.strict_parsing,
+        )?;
+        self.key_policy.check_delegations(&targets.signed)?;
+
+        let (earliest_expiration, earliest_expiration_role) =
+            earliest_expiration(&root, &timestamp, &snapshot, &targets);
+        self.consistent_snapshot = root.signed.consistent_snapshot;
+        self.earliest_expiration = earliest_expiration;
+        self.earliest_expiration_role = earliest_expiration_role;
+        self.root = root;
+        self.timestamp = timestamp;
+        self.snapshot = snapshot;
+        self.targets = targets;
+        self.raw_metadata = HashMap::from([
+            (RoleType::Root, raw_root),
+            (RoleType::Timestamp, raw_timestamp),
+            (RoleType::Snapshot, raw_snapshot),
+            (RoleType::Targets, raw_targets),
+        ]);
+        Ok(true)
+    }
+
+    /// Returns the name, version and expiration of every loaded role: root, timestamp, snapshot
+    /// and targets, followed by each delegated role reachable from targets, depth first. This is
+    /// meant for monitoring, e.g. to alert before any role expires.
+    pub fn metadata_summary(&self) -> Vec<RoleSummary> {
+        let mut summary = vec![
+            RoleSummary::new(RoleType::Root.to_string(), &self.root.signed),
+            RoleSummary::new(RoleType::Timestamp.to_string(), &self.timestamp.signed),
+            RoleSummary::new(RoleType::Snapshot.to_string(), &self.snapshot.signed),
+            RoleSummary::new(RoleType::Targets.to_string(), &self.targets.signed),
+        ];
+        summarize_delegations(&self.targets.signed, &mut summary);
+        summary
+    }
+
     ///return a vec of all targets including all target files delegated by targets
     pub fn all_targets(&self) -> impl Iterator<Item = (&TargetName, &schema::Target)> + '_ {
         self.targets.signed.targets_iter()
     }
 
+    /// Returns the names of the targets that changed between snapshot version
+    /// `old_snapshot_version` and the loaded snapshot: targets that were added or removed since
+    /// then, and targets whose sha256 hash differs. Delegated targets are included. A client that
+    /// already holds the targets from the old snapshot only needs to fetch these.
+    ///
+    /// The old snapshot and the targets metadata it lists are fetched from the repository and
+    /// verified against the trusted root, but their expiration is not checked. Old metadata can
+    /// only be fetched from repositories using consistent snapshots.
+    pub fn targets_changed_since(
+        &self,
+        old_snapshot_version: NonZeroU64,
+    ) -> Result<Vec<TargetName>> {
+        if old_snapshot_version == self.snapshot.signed.version {
+            return Ok(Vec::new());
+        }
+        ensure!(
+            self.consistent_snapshot,
+            error::OldSnapshotUnavailableSnafu {
+                version: old_snapshot_version.get()
+            }
+        );
+        let old_targets = load_old_targets(
+            self.transport.as_ref(),
+            &self.root,
+            old_snapshot_version,
+            &self.datastore,
+            self.limits.max_targets_size,
+            &self.metadata_base_url,
+            self.strict_parsing,
+        )?;
+
+        let mut old_hashes = target_hashes(&old_targets.signed);
+        let mut changed = Vec::new();
+        for (name, sha256) in target_hashes(&self.targets.signed) {
+            if old_hashes.remove(name) != Some(sha256) {
+                changed.push(name.clone());
+            }
+        }
+        // Whatever is left was removed since the old snapshot
+        changed.extend(old_hashes.into_keys().cloned());
+        changed.sort();
+        Ok(changed)
+    }
+
     /// Fetches a target from the repository.
     ///
     /// If the repository metadata is expired or there is an issue making the request, `Err` is
@@ -429,15 +1081,7 @@ impl Repository {
     /// mismatch, the reader returns a [`std::io::Error`]. **Consumers of this library must not use
     /// data from the reader if it returns an error.**
     pub fn read_target(&self, name: &TargetName) -> Result<Option<impl Read + Send + '_>> {
-        // Check for repository metadata expiration.
-        if self.expiration_enforcement == ExpirationEnforcement::Safe {
-            ensure!(
-                self.datastore.system_time()? < self.earliest_expiration,
-                error::ExpiredMetadataSnafu {
-                    role: self.earliest_expiration_role
-                }
-            );
-        }
+        self.check_metadata_expiration()?;
 
         // 5. Verify the desired target against its targets metadata.
         //
@@ -464,6 +1108,75 @@ impl Repository {
         })
     }
 
+    /// Verifies target bytes obtained from somewhere other than the repository, such as an
+    /// external cache, against the target `name` as listed by the delegated role `role`. Only the
+    /// targets listed directly in `role`'s metadata are considered, not those of the roles it
+    /// delegates to.
+    ///
+    /// If the repository metadata is expired or `role` isn't a delegated role of the repository,
+    /// `Err` is returned. If `role` doesn't list the target, `Ok(None)` is returned.
+    ///
+    /// Otherwise, `reader` is wrapped in a reader that checks its length and hashes as it is read,
+    /// in the same way as the reader returned by [`read_target`](Self::read_target). **Consumers
+    /// of this library must not use data from the reader if it returns an error.**
+    pub fn verify_delegated_target<'a, R>(
+        &self,
+        role: &str,
+        name: &TargetName,
+        reader: R,
+    ) -> Result<Option<impl Read + Send + 'a>>
+    where
+        R: Read + Send + 'a,
+    {
+        self.check_metadata_expiration()?;
+        let target = self
+            .targets
+            .signed
+            .delegated_role(role)
+            .context(error::DelegateMissingSnafu { name: role })?
+            .targets
+            .as_ref()
+            .and_then(|targets| targets.signed.targets.get(name));
+        Ok(match target {
+            Some(target) => {
+                let (_, file) = self.target_digest_and_filename(target, name);
+                Some(self.verify_target(target, file.as_str(), Box::new(reader))?)
+            }
+            None => None,
+        })
+    }
+
+    /// Returns every target in the repository, including delegated targets, each with a reader
+    /// that streams its contents. Targets are only fetched as the iterator advances, so a whole
+    /// repository can be copied elsewhere without staging it on disk or holding it in memory.
+    ///
+    /// As with [`read_target`](Self::read_target), each reader fails if the target is longer than
+    /// its signed length, and returns an error instead of the final bytes if its hash doesn't
+    /// match, so the target must be read to the end to be verified.
+    pub fn stream_all_targets(
+        &self,
+    ) -> impl Iterator<Item = Result<(TargetName, impl Read + Send + '_)>> + '_ {
+        self.all_targets().map(move |(name, target)| -> Result<_> {
+            self.check_metadata_expiration()?;
+            let (sha256, file) = self.target_digest_and_filename(target, name);
+            let reader = self.fetch_target(target, &sha256, file.as_str())?;
+            Ok((name.clone(), reader))
+        })
+    }
+
+    /// Fails if any of the repository's metadata has expired, unless expiration isn't enforced.
+    fn check_metadata_expiration(&self) -> Result<()> {
+        if self.expiration_enforcement == ExpirationEnforcement::Safe {
+            ensure!(
+                self.datastore.system_time()? < self.earliest_expiration,
+                error::ExpiredMetadataSnafu {
+                    role: self.earliest_expiration_role
+                }
+            );
+        }
+        Ok(())
+    }
+
     /// Fetches a target from the repository and saves it to `outdir`. Attempts to do this as safely
     /// as possible by using `path_clean` to eliminate `../` path traversals from the the target's
     /// name. Ensures that the resulting filepath is in `outdir` or a child of `outdir`.
@@ -540,23 +1253,115 @@ impl Repository {
             }
         );
 
+        self.save_target_to(name, resolved_filepath, PathExists::Replace)
+    }
+
+    /// Fetches a target from the repository and saves it to `outdir`, laid out as `layout`
+    /// describes. With [`TargetLayout::Flat`], the target is saved as by
+    /// [`save_target`](Self::save_target).
+    ///
+    /// With [`TargetLayout::Nested`], the directories of the target's `/`-separated name are
+    /// mirrored in `outdir`, and names that are absolute or contain a `..` component are rejected
+    /// rather than resolved. [`Prefix::Digest`] prepends the sha digest to the file name rather
+    /// than to the whole name, e.g. `a/b/{digest}.c.bin`, as targets are named in a repository
+    /// that uses consistent snapshots.
+    ///
+    /// `outdir` must exist.
+    pub fn save_target_with_layout<P>(
+        &self,
+        name: &TargetName,
+        outdir: P,
+        prepend: Prefix,
+        layout: TargetLayout,
+    ) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let outdir = outdir.as_ref();
+        if layout == TargetLayout::Flat {
+            return self.save_target(name, outdir, prepend);
+        }
+        let outdir = outdir
+            .canonicalize()
+            .context(error::SaveTargetOutdirCanonicalizeSnafu { path: outdir })?;
+        ensure!(
+            outdir.is_dir(),
+            error::SaveTargetOutdirSnafu { path: outdir }
+        );
+        // Look the target up before any directories are created for it
+        let target = self
+            .targets
+            .signed
+            .find_target(name)
+            .ok()
+            .with_context(|| error::SaveTargetNotFoundSnafu { name: name.clone() })?;
+        let sha256 = match prepend {
+            Prefix::Digest => Some(target.hashes.sha256.as_ref()),
+            Prefix::None => None,
+        };
+        let path = nested_target_path(&outdir, name, sha256)?;
+        self.save_target_to(name, path, PathExists::Replace)
+    }
+
+    /// Fetches and verifies a target from the repository and saves it at `path`. The target is
+    /// written to a temporary file in the same directory, which is renamed to `path` only once the
+    /// whole target has been verified, so a partially written or unverified file never appears at
+    /// `path`. Intermediate directories are created with `create_dir_all`.
+    ///
+    /// Unlike [`save_target`](Self::save_target), `path` is used as given; it isn't derived from
+    /// the target's name.
+    ///
+    /// If a file already exists at `path`, `replace_behavior` decides what happens: it is left
+    /// alone without fetching the target, replaced, or a `PathExistsFail` error is returned.
+    pub fn save_target_to<P>(
+        &self,
+        name: &TargetName,
+        path: P,
+        replace_behavior: PathExists,
+    ) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        if path.symlink_metadata().is_ok() {
+            match replace_behavior {
+                PathExists::Skip => return Ok(()),
+                PathExists::Fail => return error::PathExistsFailSnafu { path }.fail(),
+                PathExists::Replace => {}
+            }
+        }
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+
         // Fetch and write the target using NamedTempFile for an atomic file creation.
         let mut reader = self
             .read_target(name)?
             .with_context(|| error::SaveTargetNotFoundSnafu { name: name.clone() })?;
-        create_dir_all(filepath_dir).context(error::DirCreateSnafu {
-            path: &filepath_dir,
-        })?;
+        create_dir_all(dir).context(error::DirCreateSnafu { path: dir })?;
         let mut f =
-            NamedTempFile::new_in(filepath_dir).context(error::NamedTempFileCreateSnafu {
-                path: &filepath_dir,
-            })?;
+            NamedTempFile::new_in(dir).context(error::NamedTempFileCreateSnafu { path: dir })?;
+        // The reader fails once it finds the target doesn't match its hashes, and the temporary
+        // file is removed when it's dropped
         std::io::copy(&mut reader, &mut f).context(error::FileWriteSnafu { path: &f.path() })?;
-        f.persist(&resolved_filepath)
-            .context(error::NamedTempFilePersistSnafu {
-                path: resolved_filepath,
-            })?;
-
+        let persisted = match replace_behavior {
+            PathExists::Replace => f.persist(path),
+            // Don't overwrite a file that appeared while the target was being fetched
+            PathExists::Skip | PathExists::Fail => f.persist_noclobber(path),
+        };
+        match persisted {
+            Ok(_) => {}
+            Err(err)
+                if err.error.kind() == std::io::ErrorKind::AlreadyExists
+                    && !matches!(replace_behavior, PathExists::Replace) =>
+            {
+                if let PathExists::Fail = replace_behavior {
+                    return error::PathExistsFailSnafu { path }.fail();
+                }
+            }
+            Err(err) => return Err(err).context(error::NamedTempFilePersistSnafu { path }),
+        }
         Ok(())
     }
 
@@ -589,6 +1394,18 @@ pub(crate) fn encode_filename<S: AsRef<str>>(name: S) -> String {
     utf8_percent_encode(name.as_ref(), &CHARACTERS_TO_ESCAPE).to_string()
 }
 
+/// Appends a summary of each role delegated by `targets`, and of the roles they delegate in turn.
+fn summarize_delegations(targets: &schema::Targets, summary: &mut Vec<RoleSummary>) {
+    if let Some(delegations) = &targets.delegations {
+        for role in &delegations.roles {
+            if let Some(role_targets) = &role.targets {
+                summary.push(RoleSummary::new(&role.name, &role_targets.signed));
+                summarize_delegations(&role_targets.signed, summary);
+            }
+        }
+    }
+}
+
 /// TUF v1.0.16, 5.2.9, 5.3.3, 5.4.5, 5.5.4, The expiration timestamp in the `[metadata]` file MUST
 /// be higher than the fixed update start time.
 fn check_expired<T: Role>(datastore: &Datastore, role: &T) -> Result<()> {
@@ -599,6 +1416,32 @@ fn check_expired<T: Role>(datastore: &Datastore, role: &T) -> Result<()> {
     Ok(())
 }
 
+/// Warns when a role declares a `spec_version` newer than the one this library implements. The
+/// metadata is still used; newer minor and patch versions of the spec are meant to be compatible.
+fn warn_unknown_spec_version(role: &str, spec_version: &str) {
+    if !spec_version_known(spec_version) {
+        warn!(
+            "The '{}' metadata declares spec_version '{}', which is newer than the supported \
+            version {}",
+            role, spec_version, SPEC_VERSION
+        );
+    }
+}
+
+/// Wraps `transport` so that each URL passes through `signer` before it is fetched.
+fn sign_urls(
+    transport: Box<dyn Transport + Send + Sync>,
+    signer: Option<Arc<dyn UrlSigner>>,
+) -> Box<dyn Transport + Send + Sync> {
+    match signer {
+        Some(signer) => Box::new(UrlSigningTransport {
+            inner: transport,
+            signer,
+        }),
+        None => transport,
+    }
+}
+
 /// Checks to see if the `Url` has a trailing slash and adds one if not. Without a trailing slash,
 /// the last component of a `Url` is considered to be a file. `metadata_url` and `targets_url`
 /// must refer to a base (i.e. directory), so we need them to end with a slash.
@@ -612,23 +1455,87 @@ fn parse_url(url: Url) -> Result<Url> {
     }
 }
 
+/// Parses a fetched metadata file. With `strict_parsing`, it is also rejected if any of its JSON
+/// objects has the same key more than once.
+fn parse_metadata<T>(buf: &[u8], role: RoleType, strict_parsing: bool) -> Result<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let metadata = serde_json::from_slice(buf).context(error::ParseMetadataSnafu { role })?;
+    if strict_parsing {
+        schema::check_unique_keys(buf).context(error::DuplicateJsonKeySnafu { role })?;
+    }
+    Ok(metadata)
+}
+
+/// Reads a fetched metadata file to the end, keeping its bytes as they were served.
+fn read_metadata(mut reader: impl Read, role: RoleType, url: Url) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    if let Err(source) = reader.read_to_end(&mut buf) {
+        // Only lengths declared by snapshot.json are enforced with a `LengthAdapter`
+        if let Some(error::Error::LengthMismatch { expected, read, .. }) = source
+            .get_ref()
+            .and_then(|err| err.downcast_ref::<error::Error>())
+        {
+            return error::SnapshotLengthMismatchSnafu {
+                role,
+                url,
+                expected: *expected,
+                read: *read,
+            }
+            .fail();
+        }
+        return Err(source).context(error::ReadMetadataSnafu { role, url });
+    }
+    Ok(buf)
+}
+
+/// Returns the earliest expiration of the top-level roles, and the role that expires then.
+fn earliest_expiration(
+    root: &Signed<Root>,
+    timestamp: &Signed<Timestamp>,
+    snapshot: &Signed<Snapshot>,
+    targets: &Signed<crate::schema::Targets>,
+) -> (DateTime<Utc>, RoleType) {
+    [
+        (root.signed.expires, RoleType::Root),
+        (timestamp.signed.expires, RoleType::Timestamp),
+        (snapshot.signed.expires, RoleType::Snapshot),
+        (targets.signed.expires, RoleType::Targets),
+    ]
+    .iter()
+    .min_by_key(|tup| tup.0)
+    .copied()
+    .unwrap()
+}
+
 /// Steps 0 and 1 of the client application, which load the current root metadata file based on a
 /// trusted root metadata file.
+#[allow(clippy::too_many_arguments)]
 fn load_root<R: Read>(
     transport: &dyn Transport,
-    root: R,
+    mut root: R,
     datastore: &Datastore,
     max_root_size: u64,
     max_root_updates: u64,
     metadata_base_url: &Url,
     expiration_enforcement: ExpirationEnforcement,
-) -> Result<Signed<Root>> {
+    strict_parsing: bool,
+) -> Result<(Signed<Root>, Vec<u8>)> {
     // 0. Load the trusted root metadata file. We assume that a good, trusted copy of this file was
     //    shipped with the package manager or software updater using an out-of-band process. Note
     //    that the expiration of the trusted root metadata file does not matter, because we will
     //    attempt to update it in the next step.
+    let mut root_buf = Vec::new();
+    root.read_to_end(&mut root_buf)
+        .context(error::ReadTrustedMetadataSnafu)?;
     let mut root: Signed<Root> =
-        serde_json::from_reader(root).context(error::ParseTrustedMetadataSnafu)?;
+        serde_json::from_slice(&root_buf).context(error::ParseTrustedMetadataSnafu)?;
+    if strict_parsing {
+        schema::check_unique_keys(&root_buf).context(error::DuplicateJsonKeySnafu {
+            role: RoleType::Root,
+        })?;
+    }
     root.signed
         .verify_role(&root)
         .context(error::VerifyTrustedMetadataSnafu)?;
@@ -669,21 +1576,21 @@ fn load_root<R: Read>(
             error::MaxUpdatesExceededSnafu { max_root_updates }
         );
         let path = format!("{}.root.json", root.signed.version.get() + 1);
+        let root_url = metadata_base_url.join(&path).context(error::JoinUrlSnafu {
+            path,
+            url: metadata_base_url.clone(),
+        })?;
         match fetch_max_size(
             transport,
-            metadata_base_url.join(&path).context(error::JoinUrlSnafu {
-                path,
-                url: metadata_base_url.clone(),
-            })?,
+            root_url.clone(),
             max_root_size,
             "max_root_size argument",
         ) {
             Err(_) => break, // If this file is not available, then go to step 1.8.
             Ok(reader) => {
+                let new_root_buf = read_metadata(reader, RoleType::Root, root_url)?;
                 let new_root: Signed<Root> =
-                    serde_json::from_reader(reader).context(error::ParseMetadataSnafu {
-                        role: RoleType::Root,
-                    })?;
+                    parse_metadata(&new_root_buf, RoleType::Root, strict_parsing)?;
 
                 // 1.3. Check signatures. Version N+1 of the root metadata file MUST have been
                 //   signed by: (1) a threshold of keys specified in the trusted root metadata file
@@ -737,6 +1644,7 @@ fn load_root<R: Read>(
                 //
                 // (This is where version N+1 becomes version N.)
                 root = new_root;
+                root_buf = new_root_buf;
 
                 // 1.7. Repeat steps 1.1 to 1.7.
                 continue;
@@ -744,6 +1652,8 @@ fn load_root<R: Read>(
         }
     }
 
+    warn_unknown_spec_version("root", &root.signed.spec_version);
+
     // TUF v1.0.16, 5.2.9. Check for a freeze attack. The expiration timestamp in the trusted root
     // metadata file MUST be higher than the fixed update start time. If the trusted root metadata
     // file has expired, abort the update cycle, report the potential freeze attack. On the next
@@ -776,10 +1686,11 @@ fn load_root<R: Read>(
     // (This is done by checking the value of root.signed.consistent_snapshot throughout this
     // library.)
 
-    Ok(root)
+    Ok((root, root_buf))
 }
 
-/// Step 2 of the client application, which loads the timestamp metadata file.
+/// Step 2 of the client application, which loads the timestamp metadata file and, once it is
+/// verified, saves it to the datastore as the trusted timestamp.
 fn load_timestamp(
     transport: &dyn Transport,
     root: &Signed<Root>,
@@ -787,25 +1698,53 @@ fn load_timestamp(
     max_timestamp_size: u64,
     metadata_base_url: &Url,
     expiration_enforcement: ExpirationEnforcement,
-) -> Result<Signed<Timestamp>> {
+    strict_parsing: bool,
+) -> Result<(Signed<Timestamp>, Vec<u8>)> {
+    let (timestamp, timestamp_buf) = fetch_timestamp(
+        transport,
+        root,
+        datastore,
+        max_timestamp_size,
+        metadata_base_url,
+        expiration_enforcement,
+        strict_parsing,
+    )?;
+
+    // Now that everything seems okay, write the timestamp file to the datastore.
+    datastore.create("timestamp.json", &timestamp)?;
+
+    Ok((timestamp, timestamp_buf))
+}
+
+/// Fetches the timestamp metadata file and verifies it as in step 2 of the client application,
+/// against the trusted root and the trusted timestamp in the datastore, without saving it.
+fn fetch_timestamp(
+    transport: &dyn Transport,
+    root: &Signed<Root>,
+    datastore: &Datastore,
+    max_timestamp_size: u64,
+    metadata_base_url: &Url,
+    expiration_enforcement: ExpirationEnforcement,
+    strict_parsing: bool,
+) -> Result<(Signed<Timestamp>, Vec<u8>)> {
     // 2. Download the timestamp metadata file, up to Y number of bytes (because the size is
     //    unknown.) The value for Y is set by the authors of the application using TUF. For
     //    example, Y may be tens of kilobytes. The filename used to download the timestamp metadata
     //    file is of the fixed form FILENAME.EXT (e.g., timestamp.json).
     let path = "timestamp.json";
+    let timestamp_url = metadata_base_url.join(path).context(error::JoinUrlSnafu {
+        path,
+        url: metadata_base_url.clone(),
+    })?;
     let reader = fetch_max_size(
         transport,
-        metadata_base_url.join(path).context(error::JoinUrlSnafu {
-            path,
-            url: metadata_base_url.clone(),
-        })?,
+        timestamp_url.clone(),
         max_timestamp_size,
         "max_timestamp_size argument",
     )?;
+    let timestamp_buf = read_metadata(reader, RoleType::Timestamp, timestamp_url)?;
     let timestamp: Signed<Timestamp> =
-        serde_json::from_reader(reader).context(error::ParseMetadataSnafu {
-            role: RoleType::Timestamp,
-        })?;
+        parse_metadata(&timestamp_buf, RoleType::Timestamp, strict_parsing)?;
 
     // 2.1. Check signatures. The new timestamp metadata file must have been signed by a threshold
     //   of keys specified in the trusted root metadata file. If the new timestamp metadata file is
@@ -836,6 +1775,8 @@ fn load_timestamp(
         }
     }
 
+    warn_unknown_spec_version("timestamp", &timestamp.signed.spec_version);
+
     // TUF v1.0.16, 5.3.3. Check for a freeze attack. The expiration timestamp in the new timestamp
     // metadata file MUST be higher than the fixed update start time. If so, the new timestamp
     // metadata file becomes the trusted timestamp metadata file. If the new timestamp metadata file
@@ -844,10 +1785,28 @@ fn load_timestamp(
         check_expired(datastore, &timestamp.signed)?;
     }
 
-    // Now that everything seems okay, write the timestamp file to the datastore.
-    datastore.create("timestamp.json", &timestamp)?;
+    Ok((timestamp, timestamp_buf))
+}
 
-    Ok(timestamp)
+/// Fails with `SnapshotMismatch`, logging both values, if a property of the fetched snapshot
+/// doesn't match what timestamp.json recorded for it. This is what stops a replayed or
+/// mixed-and-matched snapshot from being trusted.
+fn check_snapshot_match(property: &'static str, expected: &str, fetched: &str) -> Result<()> {
+    if expected != fetched {
+        log::error!(
+            "Snapshot {} mismatch: timestamp.json expects '{}', fetched snapshot has '{}'",
+            property,
+            expected,
+            fetched
+        );
+        return error::SnapshotMismatchSnafu {
+            property,
+            expected,
+            fetched,
+        }
+        .fail();
+    }
+    Ok(())
 }
 
 /// Step 3 of the client application, which loads the snapshot metadata file.
@@ -858,7 +1817,8 @@ fn load_snapshot(
     datastore: &Datastore,
     metadata_base_url: &Url,
     expiration_enforcement: ExpirationEnforcement,
-) -> Result<Signed<Snapshot>> {
+    strict_parsing: bool,
+) -> Result<(Signed<Snapshot>, Vec<u8>)> {
     // 3. Download snapshot metadata file, up to the number of bytes specified in the timestamp
     //    metadata file. If consistent snapshots are not used (see Section 7), then the filename
     //    used to download the snapshot metadata file is of the fixed form FILENAME.EXT (e.g.,
@@ -880,35 +1840,41 @@ fn load_snapshot(
     } else {
         "snapshot.json".to_owned()
     };
-    let reader = fetch_sha256(
+    let snapshot_url = metadata_base_url.join(&path).context(error::JoinUrlSnafu {
+        path,
+        url: metadata_base_url.clone(),
+    })?;
+    let mut snapshot_buf = Vec::new();
+    fetch_max_size_sha256(
         transport,
-        metadata_base_url.join(&path).context(error::JoinUrlSnafu {
-            path,
-            url: metadata_base_url.clone(),
-        })?,
+        snapshot_url.clone(),
         snapshot_meta.length,
         "timestamp.json",
         &snapshot_meta.hashes.sha256,
-    )?;
-    let snapshot: Signed<Snapshot> =
-        serde_json::from_reader(reader).context(error::ParseMetadataSnafu {
-            role: RoleType::Snapshot,
-        })?;
+    )?
+    .read_to_end(&mut snapshot_buf)
+    .context(error::ReadMetadataSnafu {
+        role: RoleType::Snapshot,
+        url: snapshot_url,
+    })?;
 
     // 3.1. Check against timestamp metadata. The hashes and version number of the new snapshot
     //   metadata file MUST match the hashes and version number listed in timestamp metadata. If
     //   hashes and version do not match, discard the new snapshot metadata, abort the update
     //   cycle, and report the failure.
-    //
-    // (We already checked the hash in `fetch_sha256` above.)
-    ensure!(
-        snapshot.signed.version == snapshot_meta.version,
-        error::VersionMismatchSnafu {
-            role: RoleType::Snapshot,
-            fetched: snapshot.signed.version,
-            expected: snapshot_meta.version
-        }
-    );
+    let sha256 = digest(&SHA256, &snapshot_buf);
+    check_snapshot_match(
+        "sha256",
+        &hex::encode(&snapshot_meta.hashes.sha256),
+        &hex::encode(sha256),
+    )?;
+    let snapshot: Signed<Snapshot> =
+        parse_metadata(&snapshot_buf, RoleType::Snapshot, strict_parsing)?;
+    check_snapshot_match(
+        "version",
+        &snapshot_meta.version.to_string(),
+        &snapshot.signed.version.to_string(),
+    )?;
 
     // 3.2. Check signatures. The new snapshot metadata file MUST have been signed by a threshold
     //   of keys specified in the trusted root metadata file. If the new snapshot metadata file is
@@ -971,6 +1937,8 @@ fn load_snapshot(
         }
     }
 
+    warn_unknown_spec_version("snapshot", &snapshot.signed.spec_version);
+
     // TUF v1.0.16, 5.4.5. Check for a freeze attack. The expiration timestamp in the new snapshot
     // metadata file MUST be higher than the fixed update start time. If so, the new snapshot
     // metadata file becomes the trusted snapshot metadata file. If the new snapshot metadata file
@@ -982,19 +1950,22 @@ fn load_snapshot(
     // Now that everything seems okay, write the snapshot file to the datastore.
     datastore.create("snapshot.json", &snapshot)?;
 
-    Ok(snapshot)
+    Ok((snapshot, snapshot_buf))
 }
 
 /// Step 4 of the client application, which loads the targets metadata file.
+#[allow(clippy::too_many_arguments)]
 fn load_targets(
-    transport: &dyn Transport,
+    transport: &(dyn Transport + Send + Sync),
     root: &Signed<Root>,
     snapshot: &Signed<Snapshot>,
     datastore: &Datastore,
     max_targets_size: u64,
+    delegation_fetch_concurrency: usize,
     metadata_base_url: &Url,
     expiration_enforcement: ExpirationEnforcement,
-) -> Result<Signed<crate::schema::Targets>> {
+    strict_parsing: bool,
+) -> Result<(Signed<crate::schema::Targets>, Vec<u8>)> {
     // 4. Download the top-level targets metadata file, up to either the number of bytes specified
     //    in the snapshot metadata file, or some Z number of bytes. The value for Z is set by the
     //    authors of the application using TUF. For example, Z may be tens of kilobytes. If
@@ -1022,30 +1993,34 @@ fn load_targets(
         path,
         url: metadata_base_url.clone(),
     })?;
-    let (max_targets_size, specifier) = match targets_meta.length {
-        Some(length) => (length, "snapshot.json"),
-        None => (max_targets_size, "max_targets_size parameter"),
-    };
-    let reader = if let Some(hashes) = &targets_meta.hashes {
-        Box::new(fetch_sha256(
+    // A length declared by snapshot.json is enforced exactly as the file streams, so an oversized
+    // file is rejected as soon as it runs past the length, and a truncated one at its end.
+    let specifier = "max_targets_size parameter";
+    let reader = match (targets_meta.length, &targets_meta.hashes) {
+        (Some(length), Some(hashes)) => Box::new(fetch_length_sha256(
+            transport,
+            targets_url.clone(),
+            length,
+            &hashes.sha256,
+        )?) as Box<dyn Read>,
+        (Some(length), None) => Box::new(fetch_length(transport, targets_url.clone(), length)?),
+        (None, Some(hashes)) => Box::new(fetch_sha256(
             transport,
-            targets_url,
+            targets_url.clone(),
             max_targets_size,
             specifier,
             &hashes.sha256,
-        )?) as Box<dyn Read>
-    } else {
-        Box::new(fetch_max_size(
+        )?),
+        (None, None) => Box::new(fetch_max_size(
             transport,
-            targets_url,
+            targets_url.clone(),
             max_targets_size,
             specifier,
-        )?)
+        )?),
     };
+    let targets_buf = read_metadata(reader, RoleType::Targets, targets_url)?;
     let mut targets: Signed<crate::schema::Targets> =
-        serde_json::from_reader(reader).context(error::ParseMetadataSnafu {
-            role: RoleType::Targets,
-        })?;
+        parse_metadata(&targets_buf, RoleType::Targets, strict_parsing)?;
 
     // 4.1. Check against snapshot metadata. The hashes (if any), and version number of the new
     //   targets metadata file MUST match the trusted snapshot metadata. This is done, in part, to
@@ -1092,6 +2067,8 @@ fn load_targets(
         }
     }
 
+    warn_unknown_spec_version("targets", &targets.signed.spec_version);
+
     // TUF v1.0.16, 5.5.4. Check for a freeze attack. The expiration timestamp in the new targets
     // metadata file MUST be higher than the fixed update start time. If so, the new targets
     // metadata file becomes the trusted targets metadata file. If the new targets metadata file is
@@ -1112,83 +2089,76 @@ fn load_targets(
             root.signed.consistent_snapshot,
             metadata_base_url,
             max_targets_size,
+            delegation_fetch_concurrency,
             delegations,
             datastore,
+            strict_parsing,
         )?;
     }
 
     // This validation can only be done from the top level targets.json role. This check verifies
     // that each target's delegate hierarchy is a match (i.e. it's delegate ownership is valid).
     targets.signed.validate().context(error::InvalidPathSnafu)?;
-    Ok(targets)
+    Ok((targets, targets_buf))
 }
 
 // Follow the paths of delegations starting with the top level targets.json delegation
+#[allow(clippy::too_many_arguments)]
 fn load_delegations(
-    transport: &dyn Transport,
+    transport: &(dyn Transport + Send + Sync),
     snapshot: &Signed<Snapshot>,
     consistent_snapshot: bool,
     metadata_base_url: &Url,
     max_targets_size: u64,
+    delegation_fetch_concurrency: usize,
     delegation: &mut Delegations,
     datastore: &Datastore,
+    strict_parsing: bool,
 ) -> Result<()> {
+    // fetch the delegated roles, with at most `delegation_fetch_concurrency` fetches in flight
+    let delegations = &*delegation;
+    let next_role = &AtomicUsize::new(0);
+    let workers = delegation_fetch_concurrency.min(delegations.roles.len());
     let mut delegated_roles: HashMap<String, Option<Signed<crate::schema::Targets>>> =
         HashMap::new();
-    for delegated_role in &delegation.roles {
-        // find the role file metadata
-        let role_meta = snapshot
-            .signed
-            .meta
-            .get(&format!("{}.json", &delegated_role.name))
-            .context(error::RoleNotInMetaSnafu {
-                name: delegated_role.name.clone(),
-            })?;
-
-        let path = if consistent_snapshot {
-            format!(
-                "{}.{}.json",
-                &role_meta.version,
-                encode_filename(&delegated_role.name)
-            )
-        } else {
-            format!("{}.json", encode_filename(&delegated_role.name))
-        };
-        let role_url = metadata_base_url.join(&path).context(error::JoinUrlSnafu {
-            path: path.clone(),
-            url: metadata_base_url.clone(),
-        })?;
-        let specifier = "max_targets_size parameter";
-        // load the role json file
-        let reader = Box::new(fetch_max_size(
-            transport,
-            role_url,
-            max_targets_size,
-            specifier,
-        )?);
-        // since each role is a targets, we load them as such
-        let role: Signed<crate::schema::Targets> =
-            serde_json::from_reader(reader).context(error::ParseMetadataSnafu {
-                role: RoleType::Targets,
-            })?;
-        // verify each role with the delegation
-        delegation
-            .verify_role(&role, &delegated_role.name)
-            .context(error::VerifyMetadataSnafu {
-                role: RoleType::Targets,
-            })?;
-        ensure!(
-            role.signed.version == role_meta.version,
-            error::VersionMismatchSnafu {
-                role: RoleType::Targets,
-                fetched: role.signed.version,
-                expected: role_meta.version
+    std::thread::scope(|scope| {
+        let handles = (0..workers)
+            .map(|_| {
+                scope.spawn(
+                    move || -> Result<Vec<(String, Signed<crate::schema::Targets>)>> {
+                        let mut fetched = Vec::new();
+                        while let Some(delegated_role) = delegations
+                            .roles
+                            .get(next_role.fetch_add(1, Ordering::Relaxed))
+                        {
+                            let role = load_delegated_role(
+                                transport,
+                                snapshot,
+                                consistent_snapshot,
+                                metadata_base_url,
+                                max_targets_size,
+                                delegations,
+                                &delegated_role.name,
+                                datastore,
+                                strict_parsing,
+                            )?;
+                            fetched.push((delegated_role.name.clone(), role));
+                        }
+                        Ok(fetched)
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            let fetched = handle
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+            for (name, role) in fetched? {
+                delegated_roles.insert(name, Some(role));
             }
-        );
-
-        datastore.create(&path, &role)?;
-        delegated_roles.insert(delegated_role.name.clone(), Some(role));
-    }
+        }
+        Ok::<(), error::Error>(())
+    })?;
     // load all roles delegated by this role
     for delegated_role in &mut delegation.roles {
         delegated_role.targets = delegated_roles.remove(&delegated_role.name).context(
@@ -1204,8 +2174,10 @@ fn load_delegations(
                     consistent_snapshot,
                     metadata_base_url,
                     max_targets_size,
+                    delegation_fetch_concurrency,
                     delegations,
                     datastore,
+                    strict_parsing,
                 )?;
             }
         }
@@ -1213,6 +2185,233 @@ fn load_delegations(
     Ok(())
 }
 
+// Fetches the metadata of a single delegated role and verifies it against the delegation
+#[allow(clippy::too_many_arguments)]
+fn load_delegated_role(
+    transport: &dyn Transport,
+    snapshot: &Signed<Snapshot>,
+    consistent_snapshot: bool,
+    metadata_base_url: &Url,
+    max_targets_size: u64,
+    delegation: &Delegations,
+    name: &str,
+    datastore: &Datastore,
+    strict_parsing: bool,
+) -> Result<Signed<crate::schema::Targets>> {
+    // find the role file metadata
+    let role_meta = snapshot
+        .signed
+        .meta
+        .get(&format!("{}.json", name))
+        .context(error::RoleNotInMetaSnafu { name })?;
+
+    let path = if consistent_snapshot {
+        format!("{}.{}.json", &role_meta.version, encode_filename(name))
+    } else {
+        format!("{}.json", encode_filename(name))
+    };
+    let role_url =
+        metadata_base_url
+            .join(&encode_url_path(&path))
+            .context(error::JoinUrlSnafu {
+                path: path.clone(),
+                url: metadata_base_url.clone(),
+            })?;
+    // load the role json file, which must exist since snapshot.json lists it. A length declared
+    // by snapshot.json is enforced exactly as the file streams, as for the top-level targets.
+    let reader = match role_meta.length {
+        Some(length) => fetch_length(transport, role_url.clone(), length)
+            .map(|reader| Box::new(reader) as Box<dyn Read>),
+        None => fetch_max_size(
+            transport,
+            role_url.clone(),
+            max_targets_size,
+            "max_targets_size parameter",
+        )
+        .map(|reader| Box::new(reader) as Box<dyn Read>),
+    };
+    let reader = match reader {
+        Ok(reader) => reader,
+        Err(error::Error::Transport { source, .. })
+            if matches!(source.kind(), TransportErrorKind::FileNotFound) =>
+        {
+            return error::MissingDelegatedMetadataSnafu {
+                role: name,
+                url: role_url,
+                reason: "was not found",
+            }
+            .fail();
+        }
+        Err(err) => return Err(err),
+    };
+    let role_buf = match read_metadata(reader, RoleType::Targets, role_url.clone()) {
+        Err(error::Error::SnapshotLengthMismatch { expected, read, .. }) => {
+            return error::DelegatedLengthMismatchSnafu {
+                role: name,
+                url: role_url,
+                expected,
+                read,
+            }
+            .fail();
+        }
+        result => result?,
+    };
+    // 4.1. applies to delegated targets metadata too: it MUST match the hashes (if any) listed
+    //   for it in the trusted snapshot metadata, at every level of delegation.
+    if let Some(hashes) = &role_meta.hashes {
+        let sha256 = digest(&SHA256, &role_buf);
+        ensure!(
+            sha256.as_ref() == hashes.sha256.as_ref(),
+            error::DelegatedHashMismatchSnafu {
+                role: name,
+                url: role_url,
+                calculated: hex::encode(sha256),
+                expected: hex::encode(&hashes.sha256),
+            }
+        );
+    }
+    // since each role is a targets, we load them as such
+    let role: Signed<crate::schema::Targets> =
+        parse_metadata(&role_buf, RoleType::Targets, strict_parsing)?;
+    // verify each role with the delegation
+    delegation
+        .verify_role(&role, name)
+        .context(error::VerifyMetadataSnafu {
+            role: RoleType::Targets,
+        })?;
+    ensure!(
+        role.signed.version == role_meta.version,
+        error::VersionMismatchSnafu {
+            role: RoleType::Targets,
+            fetched: role.signed.version,
+            expected: role_meta.version
+        }
+    );
+    warn_unknown_spec_version(name, &role.signed.spec_version);
+
+    datastore.create(&path, &role)?;
+    Ok(role)
+}
+
+/// Maps each target name to its sha256 hash. A target listed by more than one role resolves to
+/// the first role that lists it, as in `find_target`.
+fn target_hashes(targets: &crate::schema::Targets) -> HashMap<&TargetName, &Decoded<Hex>> {
+    let mut hashes = HashMap::new();
+    for (name, target) in targets.targets_iter() {
+        hashes.entry(name).or_insert(&target.hashes.sha256);
+    }
+    hashes
+}
+
+/// Fetches snapshot version `version` of a consistent snapshot repository, and the targets and
+/// delegated targets metadata it lists, verifying them against `root` without checking for
+/// expiration or rollback.
+fn load_old_targets(
+    transport: &(dyn Transport + Send + Sync),
+    root: &Signed<Root>,
+    version: NonZeroU64,
+    datastore: &Datastore,
+    max_targets_size: u64,
+    metadata_base_url: &Url,
+    strict_parsing: bool,
+) -> Result<Signed<crate::schema::Targets>> {
+    let path = format!("{version}.snapshot.json");
+    let snapshot_url = metadata_base_url.join(&path).context(error::JoinUrlSnafu {
+        path,
+        url: metadata_base_url.clone(),
+    })?;
+    let reader = fetch_max_size(
+        transport,
+        snapshot_url.clone(),
+        max_targets_size,
+        "max_targets_size parameter",
+    )?;
+    let snapshot_buf = read_metadata(reader, RoleType::Snapshot, snapshot_url)?;
+    let snapshot: Signed<Snapshot> =
+        parse_metadata(&snapshot_buf, RoleType::Snapshot, strict_parsing)?;
+    ensure!(
+        snapshot.signed.version == version,
+        error::VersionMismatchSnafu {
+            role: RoleType::Snapshot,
+            fetched: snapshot.signed.version.get(),
+            expected: version.get(),
+        }
+    );
+    root.signed
+        .verify_role(&snapshot)
+        .context(error::VerifyMetadataSnafu {
+            role: RoleType::Snapshot,
+        })?;
+
+    let targets_meta =
+        snapshot
+            .signed
+            .meta
+            .get("targets.json")
+            .context(error::MetaMissingSnafu {
+                file: "targets.json",
+                role: RoleType::Snapshot,
+            })?;
+    let path = format!("{}.targets.json", targets_meta.version);
+    let targets_url = metadata_base_url.join(&path).context(error::JoinUrlSnafu {
+        path,
+        url: metadata_base_url.clone(),
+    })?;
+    let (max_size, specifier) = match targets_meta.length {
+        Some(length) => (length, "snapshot.json"),
+        None => (max_targets_size, "max_targets_size parameter"),
+    };
+    let reader = if let Some(hashes) = &targets_meta.hashes {
+        Box::new(fetch_sha256(
+            transport,
+            targets_url.clone(),
+            max_size,
+            specifier,
+            &hashes.sha256,
+        )?) as Box<dyn Read>
+    } else {
+        Box::new(fetch_max_size(
+            transport,
+            targets_url.clone(),
+            max_size,
+            specifier,
+        )?)
+    };
+    let targets_buf = read_metadata(reader, RoleType::Targets, targets_url)?;
+    let mut targets: Signed<crate::schema::Targets> =
+        parse_metadata(&targets_buf, RoleType::Targets, strict_parsing)?;
+    ensure!(
+        targets.signed.version == targets_meta.version,
+        error::VersionMismatchSnafu {
+            role: RoleType::Targets,
+            fetched: targets.signed.version,
+            expected: targets_meta.version
+        }
+    );
+    root.signed
+        .verify_role(&targets)
+        .context(error::VerifyMetadataSnafu {
+            role: RoleType::Targets,
+        })?;
+
+    // Delegated roles are stored under versioned names, so loading old ones doesn't disturb the
+    // current ones in the datastore
+    if let Some(delegations) = &mut targets.signed.delegations {
+        load_delegations(
+            transport,
+            &snapshot,
+            true,
+            metadata_base_url,
+            max_targets_size,
+            DEFAULT_DELEGATION_FETCH_CONCURRENCY,
+            delegations,
+            datastore,
+            strict_parsing,
+        )?;
+    }
+    Ok(targets)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,171 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Provides support for targets that are stored as several part files, which are fetched in order
+//! and concatenated into a single stream.
+
+use crate::{Transport, TransportError, TransportErrorKind};
+use std::fmt::Debug;
+use std::io::{self, Read};
+use std::sync::Arc;
+use url::Url;
+
+/// Names the part files that a target stored in parts is split into.
+///
+/// See [`RepositoryLoader::target_parts`](crate::RepositoryLoader::target_parts).
+pub trait PartNames: Debug + Send + Sync {
+    /// Returns the URL of part `index`, counting from 0, of the file at `url`.
+    fn part_url(&self, url: &Url, index: usize) -> Result<Url, TransportError>;
+}
+
+/// Names each part by appending a separator and the part's number to the file name, e.g.
+/// `file.bin.part0`, `file.bin.part1`, and so on by default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberedParts {
+    separator: String,
+    first: usize,
+    width: usize,
+}
+
+impl Default for NumberedParts {
+    fn default() -> Self {
+        Self::new(".part")
+    }
+}
+
+impl NumberedParts {
+    /// Create a `NumberedParts` that puts `separator` between the file name and the part number.
+    /// Parts are numbered from 0, without padding.
+    pub fn new<S: Into<String>>(separator: S) -> Self {
+        Self {
+            separator: separator.into(),
+            first: 0,
+            width: 0,
+        }
+    }
+
+    /// Set the number of the first part, e.g. 1 for `file.bin.001`, `file.bin.002`, ...
+    #[must_use]
+    pub fn first(mut self, first: usize) -> Self {
+        self.first = first;
+        self
+    }
+
+    /// Set the number of digits part numbers are zero-padded to, e.g. 3 for `file.bin.001`.
+    #[must_use]
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+}
+
+impl PartNames for NumberedParts {
+    fn part_url(&self, url: &Url, index: usize) -> Result<Url, TransportError> {
+        let number = self.first.checked_add(index).ok_or_else(|| {
+            TransportError::new_with_cause(TransportErrorKind::Other, url, "part number overflowed")
+        })?;
+        let mut part_url = url.clone();
+        part_url.set_path(&format!(
+            "{}{}{:0width$}",
+            url.path(),
+            self.separator,
+            number,
+            width = self.width
+        ));
+        Ok(part_url)
+    }
+}
+
+/// A [`Transport`] that fetches each file as the concatenation of its parts, as named by
+/// [`PartNames`]. Parts are fetched one at a time, as the previous part is read to its end, until
+/// a part isn't found. A file whose first part isn't found is fetched whole instead.
+///
+/// A missing part can't be told apart from the end of the file, so this must only be used where
+/// the assembled file is verified, like a target with a declared length and hash.
+#[derive(Debug, Clone)]
+pub(crate) struct PartsTransport {
+    pub(crate) inner: Box<dyn Transport + Send + Sync>,
+    pub(crate) names: Arc<dyn PartNames>,
+}
+
+impl PartsTransport {
+    /// Fetches part `index` of the file at `url`, or returns `None` if the part isn't found.
+    fn fetch_part(
+        &self,
+        url: &Url,
+        index: usize,
+    ) -> Result<Option<Box<dyn Read + Send + '_>>, TransportError> {
+        match self.inner.fetch(self.names.part_url(url, index)?) {
+            Ok(read) => Ok(Some(read)),
+            Err(err) if matches!(err.kind(), TransportErrorKind::FileNotFound) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl Transport for PartsTransport {
+    fn fetch(&self, url: Url) -> Result<Box<dyn Read + Send + '_>, TransportError> {
+        self.fetch_parts_or_whole(url, |url| self.inner.fetch(url))
+    }
+
+    /// A file stored in parts has no digest of its own to check before it's fetched, so the
+    /// digest is only passed on when the file is fetched whole.
+    fn fetch_sha256(
+        &self,
+        url: Url,
+        sha256: &[u8],
+    ) -> Result<Box<dyn Read + Send + '_>, TransportError> {
+        self.fetch_parts_or_whole(url, |url| self.inner.fetch_sha256(url, sha256))
+    }
+}
+
+impl PartsTransport {
+    /// Fetches the file at `url` in parts, or with `whole` if its first part isn't found.
+    fn fetch_parts_or_whole<'a, F>(
+        &'a self,
+        url: Url,
+        whole: F,
+    ) -> Result<Box<dyn Read + Send + 'a>, TransportError>
+    where
+        F: FnOnce(Url) -> Result<Box<dyn Read + Send + 'a>, TransportError>,
+    {
+        match self.fetch_part(&url, 0)? {
+            Some(current) => Ok(Box::new(PartsReader {
+                transport: self,
+                url,
+                next: 1,
+                current: Some(current),
+            })),
+            None => whole(url),
+        }
+    }
+}
+
+/// Reads the parts of a file in order, fetching each part once the previous one is exhausted.
+struct PartsReader<'a> {
+    transport: &'a PartsTransport,
+    url: Url,
+    next: usize,
+    /// The part being read, or `None` once every part has been read.
+    current: Option<Box<dyn Read + Send + 'a>>,
+}
+
+impl Read for PartsReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let current = match &mut self.current {
+                Some(current) => current,
+                None => return Ok(0),
+            };
+            let read = current.read(buf)?;
+            if read > 0 || buf.is_empty() {
+                return Ok(read);
+            }
+            self.current = self
+                .transport
+                .fetch_part(&self.url, self.next)
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+            self.next += 1;
+        }
+    }
+}
@@ -0,0 +1,157 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Provides a way to load a repository from metadata that has already been fetched.
+
+use crate::{RepositoryLoader, Transport, TransportError, TransportErrorKind};
+use percent_encoding::percent_decode_str;
+use std::fmt::{self, Debug, Formatter};
+use std::io::{Cursor, Read};
+use std::sync::Arc;
+use url::Url;
+
+/// The metadata base URL handed to the loader; it is never resolved.
+const METADATA_BASE_URL: &str = "prefetched:///metadata/";
+/// The targets base URL handed to the loader; it is never resolved.
+const TARGETS_BASE_URL: &str = "prefetched:///targets/";
+
+type DelegatedRoleFn = dyn Fn(&str) -> Option<Vec<u8>> + Send + Sync;
+
+/// The raw bytes of a repository's metadata, fetched by some means other than a [`Transport`]
+/// (for example, embedded in a binary or received over another channel).
+///
+/// `PrefetchedMetadata` produces a [`RepositoryLoader`] that verifies the metadata exactly as it
+/// would verify metadata fetched from a remote repository, but without any network or filesystem
+/// access. Root updates are not available; the given root is the trusted root.
+///
+/// ```rust
+/// # use std::path::PathBuf;
+/// # use tough::{ExpirationEnforcement, PrefetchedMetadata};
+/// # let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("data").join("tuf-reference-impl").join("metadata");
+/// # let read = |name: &str| std::fs::read(dir.join(name)).unwrap();
+/// let repository = PrefetchedMetadata::new(
+///     read("1.root.json"),
+///     read("timestamp.json"),
+///     read("snapshot.json"),
+///     read("targets.json"),
+/// )
+/// .delegated_roles(move |name| std::fs::read(dir.join(format!("{name}.json"))).ok())
+/// .loader()
+/// .expiration_enforcement(ExpirationEnforcement::Unsafe)
+/// .load()
+/// .unwrap();
+/// ```
+#[derive(Clone)]
+pub struct PrefetchedMetadata {
+    root: Vec<u8>,
+    timestamp: Arc<Vec<u8>>,
+    snapshot: Arc<Vec<u8>>,
+    targets: Arc<Vec<u8>>,
+    delegated_roles: Option<Arc<DelegatedRoleFn>>,
+}
+
+impl Debug for PrefetchedMetadata {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrefetchedMetadata")
+            .field("root", &self.root.len())
+            .field("timestamp", &self.timestamp.len())
+            .field("snapshot", &self.snapshot.len())
+            .field("targets", &self.targets.len())
+            .field("delegated_roles", &self.delegated_roles.is_some())
+            .finish()
+    }
+}
+
+impl PrefetchedMetadata {
+    /// Create `PrefetchedMetadata` from the raw bytes of the top-level role files.
+    ///
+    /// `root` must be a trusted root.json, obtained out-of-band as for [`RepositoryLoader::new`].
+    pub fn new(root: Vec<u8>, timestamp: Vec<u8>, snapshot: Vec<u8>, targets: Vec<u8>) -> Self {
+        Self {
+            root,
+            timestamp: Arc::new(timestamp),
+            snapshot: Arc::new(snapshot),
+            targets: Arc::new(targets),
+            delegated_roles: None,
+        }
+    }
+
+    /// Supply the bytes of delegated roles on demand. The function is called with the name of
+    /// each delegated role as it is needed, and returns `None` if the role is unavailable.
+    ///
+    /// With consistent snapshots, it may first be called with the versioned name of a role, such
+    /// as `1.role1`, since a role's name may itself start with digits and a dot; it should return
+    /// `None` for names that aren't roles.
+    #[must_use]
+    pub fn delegated_roles<F>(mut self, delegated_roles: F) -> Self
+    where
+        F: Fn(&str) -> Option<Vec<u8>> + Send + Sync + 'static,
+    {
+        self.delegated_roles = Some(Arc::new(delegated_roles));
+        self
+    }
+
+    /// Create a [`RepositoryLoader`] that verifies this metadata. Any other loader settings, such
+    /// as limits or expiration enforcement, can be set on the result before calling `load`.
+    ///
+    /// The resulting repository has no targets base URL, so targets cannot be read through it.
+    pub fn loader(self) -> RepositoryLoader<Cursor<Vec<u8>>> {
+        let root = Cursor::new(self.root.clone());
+        RepositoryLoader::new(
+            root,
+            // These are constants, so parsing them cannot fail
+            Url::parse(METADATA_BASE_URL).unwrap(),
+            Url::parse(TARGETS_BASE_URL).unwrap(),
+        )
+        .transport(self)
+    }
+
+    /// Finds the bytes for a metadata filename, which may carry a consistent snapshot version
+    /// prefix.
+    fn lookup(&self, filename: &str) -> Option<Vec<u8>> {
+        if filename == "timestamp.json" {
+            return Some(self.timestamp.as_ref().clone());
+        }
+        // A delegated role's own name may look like a version prefix, e.g. "1.foo", so the name is
+        // taken as it is first, and a prefix is only stripped if the rest names a known role
+        self.lookup_unversioned(filename).or_else(|| {
+            let (version, rest) = filename.split_once('.')?;
+            if version.is_empty() || !version.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            self.lookup_unversioned(rest)
+        })
+    }
+
+    /// Finds the bytes for a metadata filename without a version prefix.
+    fn lookup_unversioned(&self, filename: &str) -> Option<Vec<u8>> {
+        match filename {
+            "snapshot.json" => Some(self.snapshot.as_ref().clone()),
+            "targets.json" => Some(self.targets.as_ref().clone()),
+            // Root updates are not supported; the given root is the latest
+            "root.json" => None,
+            _ => {
+                let name = filename.strip_suffix(".json")?;
+                let name = percent_decode_str(name).decode_utf8().ok()?;
+                let delegated_roles = self.delegated_roles.as_ref()?;
+                delegated_roles(&*name)
+            }
+        }
+    }
+}
+
+impl Transport for PrefetchedMetadata {
+    fn fetch(&self, url: Url) -> Result<Box<dyn Read + Send>, TransportError> {
+        // The filename is percent-encoded in the URL, on top of the encoding of a delegated role's
+        // name in its filename
+        let found = url
+            .as_str()
+            .strip_prefix(METADATA_BASE_URL)
+            .and_then(|filename| percent_decode_str(filename).decode_utf8().ok())
+            .and_then(|filename| self.lookup(&filename));
+        match found {
+            Some(bytes) => Ok(Box::new(Cursor::new(bytes))),
+            None => Err(TransportError::new(TransportErrorKind::FileNotFound, url)),
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! The `s3` module provides `S3Transport` which enables `Repository` objects to be loaded from
+//! an S3 bucket with `s3://bucket/key` URLs.
+use crate::http::HttpError;
+use crate::{HttpTransport, HttpTransportBuilder, Transport, TransportError, TransportErrorKind};
+use log::debug;
+use reqwest::StatusCode;
+use snafu::{ensure, OptionExt, ResultExt, Snafu};
+use std::io::Read;
+use url::Url;
+
+/// A builder for [`S3Transport`] which allows settings customization.
+///
+/// # Example
+///
+/// ```
+/// # use tough::{HttpTransportBuilder, S3TransportBuilder};
+/// # use url::Url;
+/// let s3_transport = S3TransportBuilder::new()
+///     .endpoint(Url::parse("https://s3.us-west-2.amazonaws.com/").unwrap())
+///     .http_settings(HttpTransportBuilder::new().tries(3))
+///     .verify_digest_metadata("sha256")
+///     .build();
+/// ```
+#[derive(Clone, Debug)]
+pub struct S3TransportBuilder {
+    endpoint: Url,
+    http: HttpTransportBuilder,
+    digest_metadata_key: Option<String>,
+}
+
+impl Default for S3TransportBuilder {
+    fn default() -> Self {
+        Self {
+            endpoint: Url::parse("https://s3.amazonaws.com/").expect("endpoint URL is valid"),
+            http: HttpTransportBuilder::default(),
+            digest_metadata_key: None,
+        }
+    }
+}
+
+impl S3TransportBuilder {
+    /// Create a new `S3TransportBuilder` with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the S3 endpoint that objects are fetched from, e.g. the endpoint of the bucket's
+    /// region or of an S3-compatible store. Objects are addressed path-style, as
+    /// `{endpoint}/{bucket}/{key}`. Defaults to `https://s3.amazonaws.com/`.
+    #[must_use]
+    pub fn endpoint(mut self, mut value: Url) -> Self {
+        if !value.path().ends_with('/') {
+            value.set_path(&format!("{}/", value.path()));
+        }
+        self.endpoint = value;
+        self
+    }
+
+    /// Set the timeouts, retries, proxy and TLS settings of the HTTP requests made to the
+    /// endpoint.
+    #[must_use]
+    pub fn http_settings(mut self, value: HttpTransportBuilder) -> Self {
+        self.http = value;
+        self
+    }
+
+    /// Before downloading a file whose sha256 digest is known from the TUF metadata, check it
+    /// against the hex-encoded digest that the object declares in its user-defined metadata under
+    /// `key`, i.e. in its `x-amz-meta-{key}` header. A mismatch fails the fetch before the
+    /// download starts, which catches objects corrupted or replaced in storage early. Objects that
+    /// don't declare a digest are downloaded as usual.
+    ///
+    /// The check costs a `HEAD` request per file; the downloaded bytes are verified against the
+    /// metadata either way.
+    #[must_use]
+    pub fn verify_digest_metadata<S: Into<String>>(mut self, key: S) -> Self {
+        self.digest_metadata_key = Some(key.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Construct an [`S3Transport`] transport from this builder's settings.
+    pub fn build(self) -> S3Transport {
+        S3Transport {
+            http: self.http.clone().build(),
+            settings: self,
+        }
+    }
+}
+
+/// A [`Transport`] for objects in S3, for `s3://bucket/key` URLs. Objects are fetched over HTTP
+/// from the endpoint set by the [`S3TransportBuilder`], with the retries of [`HttpTransport`].
+///
+/// Requests are not signed, so the bucket must allow anonymous reads of the repository, e.g.
+/// through its bucket policy, or be reached through an endpoint that signs requests for it.
+#[derive(Clone, Debug)]
+pub struct S3Transport {
+    settings: S3TransportBuilder,
+    http: HttpTransport,
+}
+
+impl Default for S3Transport {
+    fn default() -> Self {
+        S3TransportBuilder::default().build()
+    }
+}
+
+impl Transport for S3Transport {
+    fn fetch(&self, url: Url) -> Result<Box<dyn Read + Send>, TransportError> {
+        let object_url = self.object_url(&url)?;
+        self.http.fetch(object_url)
+    }
+
+    /// Checks the object's declared digest, if set up to, before fetching it.
+    fn fetch_sha256(
+        &self,
+        url: Url,
+        sha256: &[u8],
+    ) -> Result<Box<dyn Read + Send>, TransportError> {
+        let object_url = self.object_url(&url)?;
+        if let Some(key) = &self.settings.digest_metadata_key {
+            self.check_digest_metadata(&object_url, key, sha256)
+                .map_err(|e| TransportError::from((url, e)))?;
+        }
+        self.http.fetch(object_url)
+    }
+}
+
+impl S3Transport {
+    /// Returns the URL of the object at the `s3://bucket/key` URL `url` on the endpoint.
+    fn object_url(&self, url: &Url) -> Result<Url, TransportError> {
+        if url.scheme() != "s3" {
+            return Err(TransportError::new(
+                TransportErrorKind::UnsupportedUrlScheme,
+                url,
+            ));
+        }
+        let bucket = url
+            .host_str()
+            .filter(|bucket| !bucket.is_empty())
+            .ok_or_else(|| {
+                TransportError::new_with_cause(
+                    TransportErrorKind::Other,
+                    url,
+                    "S3 URLs must name a bucket, e.g. 's3://bucket/key'",
+                )
+            })?;
+        self.settings
+            .endpoint
+            .join(&format!("{}{}", bucket, url.path()))
+            .map_err(|e| TransportError::new_with_cause(TransportErrorKind::Other, url, e))
+    }
+
+    /// Fails if the object at `object_url` declares a digest in the user-defined metadata `key`
+    /// that isn't `sha256`.
+    fn check_digest_metadata(
+        &self,
+        object_url: &Url,
+        key: &str,
+        sha256: &[u8],
+    ) -> Result<(), S3Error> {
+        let client = self.settings.http.client().context(HttpClientSnafu)?;
+        let response = client.head(object_url.clone()).send().context(HeadSnafu)?;
+        let status = response.status();
+        ensure!(status.is_success(), HeadStatusSnafu { status });
+
+        let header = format!("x-amz-meta-{key}");
+        let value = match response.headers().get(&header) {
+            Some(value) => value,
+            None => {
+                debug!("'{}' declares no digest in '{}'", object_url, header);
+                return Ok(());
+            }
+        };
+        let declared = value
+            .to_str()
+            .ok()
+            .and_then(|value| hex::decode(value.trim()).ok())
+            .context(InvalidDigestSnafu {
+                header: &header,
+                value: String::from_utf8_lossy(value.as_bytes()),
+            })?;
+        ensure!(
+            declared == sha256,
+            DigestMismatchSnafu {
+                header,
+                declared: hex::encode(declared),
+                expected: hex::encode(sha256),
+            }
+        );
+        Ok(())
+    }
+}
+
+/// The error type for the S3 transport module.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+#[allow(missing_docs)]
+pub enum S3Error {
+    #[snafu(display(
+        "Object declares sha256 '{}' in '{}', but the metadata expects '{}'",
+        declared,
+        header,
+        expected
+    ))]
+    DigestMismatch {
+        header: String,
+        declared: String,
+        expected: String,
+    },
+
+    #[snafu(display("Failed to fetch object metadata: {}", source))]
+    Head { source: reqwest::Error },
+
+    #[snafu(display("Object metadata request failed with status {}", status))]
+    HeadStatus { status: StatusCode },
+
+    #[snafu(display("The HTTP client could not be built: {}", source))]
+    HttpClient { source: HttpError },
+
+    #[snafu(display("Object declares a digest in '{}' that isn't hex: '{}'", header, value))]
+    InvalidDigest { header: String, value: String },
+}
+
+/// Convert a URL `Url` and an `S3Error` into a `TransportError`
+impl From<(Url, S3Error)> for TransportError {
+    fn from((url, e): (Url, S3Error)) -> Self {
+        match e {
+            // As with `HttpTransport`, S3 answers 403 for objects that don't exist
+            S3Error::HeadStatus {
+                status: StatusCode::FORBIDDEN | StatusCode::NOT_FOUND | StatusCode::GONE,
+            } => TransportError::new_with_cause(TransportErrorKind::FileNotFound, url, e),
+            _ => TransportError::new_with_cause(TransportErrorKind::Other, url, e),
+        }
+    }
+}
@@ -1,9 +1,10 @@
 use crate::schema::decoded::{Decoded, Hex};
 use crate::schema::error;
 use crate::schema::key::Key;
-use serde::{de::Error as _, Deserialize, Deserializer};
-use snafu::ensure;
-use std::collections::HashMap;
+use serde::de::{DeserializeSeed, Error as _, MapAccess, SeqAccess};
+use serde::{Deserialize, Deserializer};
+use snafu::{ensure, ResultExt};
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 
 /// Validates the key ID for each key during deserialization and fails if any don't match.
@@ -75,6 +76,122 @@ where
     Ok(map)
 }
 
+/// Checks that no object in the JSON document `json` has the same key more than once.
+///
+/// Deserializing metadata doesn't catch this for maps such as a role's `targets`, which silently
+/// keep the last value of a repeated key. Fails with [`Error::DuplicateJsonKey`] for the first
+/// repeated key, giving the JSON pointer of the object it's in.
+///
+/// [`Error::DuplicateJsonKey`]: crate::schema::Error::DuplicateJsonKey
+pub fn check_unique_keys(json: &[u8]) -> Result<(), error::Error> {
+    let mut duplicate = None;
+    let result = UniqueKeys {
+        pointer: String::new(),
+        duplicate: &mut duplicate,
+    }
+    .deserialize(&mut serde_json::Deserializer::from_slice(json));
+    if let Some((key, pointer)) = duplicate {
+        return error::DuplicateJsonKeySnafu { key, pointer }.fail();
+    }
+    result.context(error::JsonParseSnafu)
+}
+
+/// Walks a JSON value, recording the first repeated key of any object in `duplicate`, along with
+/// the JSON pointer of the object, and failing there.
+struct UniqueKeys<'a> {
+    pointer: String,
+    duplicate: &'a mut Option<(String, String)>,
+}
+
+impl<'de> DeserializeSeed<'de> for UniqueKeys<'_> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> serde::de::Visitor<'de> for UniqueKeys<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("any JSON value")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<(), A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut index = 0;
+        while seq
+            .next_element_seed(UniqueKeys {
+                pointer: format!("{}/{}", self.pointer, index),
+                duplicate: &mut *self.duplicate,
+            })?
+            .is_some()
+        {
+            index += 1;
+        }
+        Ok(())
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<(), A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut keys = HashSet::new();
+        while let Some(key) = map.next_key::<String>()? {
+            // JSON pointers escape '~' and '/' in keys
+            let pointer = format!(
+                "{}/{}",
+                self.pointer,
+                key.replace('~', "~0").replace('/', "~1")
+            );
+            if !keys.insert(key.clone()) {
+                let pointer = if self.pointer.is_empty() {
+                    "/".to_string()
+                } else {
+                    self.pointer
+                };
+                *self.duplicate = Some((key, pointer));
+                return Err(A::Error::custom("duplicate key"));
+            }
+            map.next_value_seed(UniqueKeys {
+                pointer,
+                duplicate: &mut *self.duplicate,
+            })?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::schema::{Root, Signed};
@@ -87,6 +204,24 @@ mod tests {
         .is_err());
     }
 
+    #[test]
+    fn duplicate_json_key() {
+        use crate::schema::{check_unique_keys, Error};
+
+        assert!(check_unique_keys(br#"{"a": [{"b": 1, "c": {"b": 2}}], "b": null}"#).is_ok());
+        match check_unique_keys(br#"{"a": [1, {"x/y": {"b": 1, "b": 2}}]}"#) {
+            Err(Error::DuplicateJsonKey { key, pointer }) => {
+                assert_eq!(key, "b");
+                assert_eq!(pointer, "/a/1/x~1y");
+            }
+            other => panic!("Expected a duplicate key error but received {other:?}"),
+        }
+        assert!(matches!(
+            check_unique_keys(br#"{"a": 1, "a": 1}"#),
+            Err(Error::DuplicateJsonKey { pointer, .. }) if pointer == "/"
+        ));
+    }
+
     /// Ensure that we can deserialize a root.json file that has hex-encoded ECDSA keys. This uses
     /// sigstore's root.json file taken from here:
     /// `<https://sigstore-tuf-root.storage.googleapis.com/2.root.json>`
@@ -0,0 +1,145 @@
+use super::decoded::{Decoded, Hex};
+use super::{RoleType, Root};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::num::NonZeroU64;
+
+/// A value that differs between two versions of metadata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Change<T> {
+    /// The value in the older metadata.
+    pub old: T,
+    /// The value in the newer metadata.
+    pub new: T,
+}
+
+impl<T: PartialEq> Change<T> {
+    fn of(old: T, new: T) -> Option<Self> {
+        (old != new).then_some(Change { old, new })
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Change<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.old, self.new)
+    }
+}
+
+/// The changes to one role's keys between two versions of root.json.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoleDiff {
+    /// The role that changed.
+    pub role: RoleType,
+    /// The key IDs the newer root.json lists for the role that the older one doesn't, sorted.
+    pub added_keys: Vec<Decoded<Hex>>,
+    /// The key IDs the older root.json lists for the role that the newer one doesn't, sorted.
+    pub removed_keys: Vec<Decoded<Hex>>,
+    /// The change to the role's threshold, if the role is listed in both versions.
+    pub threshold: Option<Change<NonZeroU64>>,
+}
+
+/// The differences between two versions of root.json, as returned by [`Root::diff`]. Only what
+/// matters when reviewing a root rotation is compared: the version, expiration, consistent
+/// snapshot setting, and the keys and threshold of each role.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootDiff {
+    /// The change to the version.
+    pub version: Option<Change<NonZeroU64>>,
+    /// The change to the expiration.
+    pub expires: Option<Change<DateTime<Utc>>>,
+    /// The change to whether consistent snapshots are used.
+    pub consistent_snapshot: Option<Change<bool>>,
+    /// The roles whose keys or threshold changed, sorted by name.
+    pub roles: Vec<RoleDiff>,
+}
+
+impl RootDiff {
+    /// Returns true if nothing compared by [`Root::diff`] changed.
+    pub fn is_empty(&self) -> bool {
+        self.version.is_none()
+            && self.expires.is_none()
+            && self.consistent_snapshot.is_none()
+            && self.roles.is_empty()
+    }
+}
+
+/// Describes each change on its own line.
+impl fmt::Display for RootDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(version) = &self.version {
+            writeln!(f, "version: {}", version)?;
+        }
+        if let Some(expires) = &self.expires {
+            writeln!(
+                f,
+                "expires: {} -> {}",
+                expires.old.to_rfc3339(),
+                expires.new.to_rfc3339()
+            )?;
+        }
+        if let Some(consistent_snapshot) = &self.consistent_snapshot {
+            writeln!(f, "consistent_snapshot: {}", consistent_snapshot)?;
+        }
+        for role in &self.roles {
+            for key_id in &role.added_keys {
+                writeln!(f, "{}: added key {}", role.role, hex::encode(key_id))?;
+            }
+            for key_id in &role.removed_keys {
+                writeln!(f, "{}: removed key {}", role.role, hex::encode(key_id))?;
+            }
+            if let Some(threshold) = &role.threshold {
+                writeln!(f, "{}: threshold {}", role.role, threshold)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Root {
+    /// Compares this root with `other`, a later version of it, and describes what changed: keys
+    /// added to or removed from each role, threshold changes, and version and expiration bumps.
+    /// A role listed in only one of the roots is reported as having all of its keys added or
+    /// removed.
+    pub fn diff(&self, other: &Root) -> RootDiff {
+        let role_types = self
+            .roles
+            .keys()
+            .chain(other.roles.keys())
+            .map(|role| (role.to_string(), *role))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut roles = Vec::new();
+        for role in role_types.into_values() {
+            let old = self.roles.get(&role);
+            let new = other.roles.get(&role);
+            let old_keys = old
+                .map(|keys| keys.keyids.iter().cloned().collect::<BTreeSet<_>>())
+                .unwrap_or_default();
+            let new_keys = new
+                .map(|keys| keys.keyids.iter().cloned().collect::<BTreeSet<_>>())
+                .unwrap_or_default();
+            let role_diff = RoleDiff {
+                role,
+                added_keys: new_keys.difference(&old_keys).cloned().collect(),
+                removed_keys: old_keys.difference(&new_keys).cloned().collect(),
+                threshold: old
+                    .zip(new)
+                    .and_then(|(old, new)| Change::of(old.threshold, new.threshold)),
+            };
+            if !role_diff.added_keys.is_empty()
+                || !role_diff.removed_keys.is_empty()
+                || role_diff.threshold.is_some()
+            {
+                roles.push(role_diff);
+            }
+        }
+
+        RootDiff {
+            version: Change::of(self.version, other.version),
+            expires: Change::of(self.expires, other.expires),
+            consistent_snapshot: Change::of(self.consistent_snapshot, other.consistent_snapshot),
+            roles,
+        }
+    }
+}
@@ -17,10 +17,31 @@ pub type Result<T> = std::result::Result<T, Error>;
 #[non_exhaustive]
 #[allow(missing_docs)]
 pub enum Error {
+    /// A signature that failed to verify was made with another key listed in the metadata,
+    /// whose scheme differs from that of the key the signature claims.
+    #[snafu(display(
+        "Signature on '{}' role by key {} was made with a {} key but the key's scheme is {}",
+        role,
+        key_id,
+        found,
+        expected
+    ))]
+    AlgorithmMismatch {
+        role: String,
+        key_id: String,
+        expected: &'static str,
+        found: &'static str,
+        backtrace: Backtrace,
+    },
+
     /// A duplicate key ID was present in the root metadata.
     #[snafu(display("Duplicate key ID: {}", keyid))]
     DuplicateKeyId { keyid: String },
 
+    /// A JSON object has the same key more than once.
+    #[snafu(display("Duplicate key '{}' in JSON object at '{}'", key, pointer))]
+    DuplicateJsonKey { key: String, pointer: String },
+
     /// A duplicate role was present in the delegations metadata.
     #[snafu(display("Duplicate role name: {}", name))]
     DuplicateRoleName { name: String },
@@ -64,6 +85,13 @@ pub enum Error {
         backtrace: Backtrace,
     },
 
+    /// Failed to parse a JSON document.
+    #[snafu(display("Failed to parse JSON: {}", source))]
+    JsonParse {
+        source: serde_json::Error,
+        backtrace: Backtrace,
+    },
+
     /// The library failed to serialize an object to JSON.
     #[snafu(display("Failed to serialize {} to JSON: {}", what, source))]
     JsonSerialization {
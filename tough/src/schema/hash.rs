@@ -0,0 +1,84 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Hash algorithms that can be recorded for a target in addition to `sha256`.
+
+use blake2::{Blake2b512, Digest as _};
+use ring::digest::{Algorithm, Context};
+use std::fmt;
+use std::io::{self, Write};
+
+/// A hash algorithm that can be recorded in a target's `hashes`, in addition to the `sha256`
+/// hash every target has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HashAlgorithm {
+    /// BLAKE2b with a 512-bit digest, recorded under the `blake2b` key.
+    Blake2b,
+}
+
+impl HashAlgorithm {
+    /// The key under which this algorithm's hash is recorded in a target's `hashes`.
+    pub fn key(self) -> &'static str {
+        match self {
+            HashAlgorithm::Blake2b => "blake2b",
+        }
+    }
+
+    /// Returns the algorithm recorded under `key` in a target's `hashes`, if it is one of these.
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "blake2b" => Some(HashAlgorithm::Blake2b),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn hasher(self) -> Hasher {
+        match self {
+            HashAlgorithm::Blake2b => Hasher::Blake2b(Blake2b512::new()),
+        }
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.key())
+    }
+}
+
+/// Computes a digest incrementally with either a `ring` algorithm or one `ring` doesn't provide.
+pub(crate) enum Hasher {
+    Ring(Context),
+    Blake2b(Blake2b512),
+}
+
+impl Hasher {
+    pub(crate) fn ring(algorithm: &'static Algorithm) -> Self {
+        Hasher::Ring(Context::new(algorithm))
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Ring(context) => context.update(data),
+            Hasher::Blake2b(hasher) => hasher.update(data),
+        }
+    }
+
+    pub(crate) fn finish(self) -> Vec<u8> {
+        match self {
+            Hasher::Ring(context) => context.finish().as_ref().to_vec(),
+            Hasher::Blake2b(hasher) => hasher.finalize().to_vec(),
+        }
+    }
+}
+
+/// Hashes everything written, so that a reader can be hashed with `io::copy`.
+impl Write for Hasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -4,6 +4,7 @@
 
 use crate::schema::decoded::{Decoded, EcdsaFlex, Hex, RsaPem};
 use crate::schema::error::{self, Result};
+use crate::schema::spki;
 use olpc_cjson::CanonicalFormatter;
 use ring::digest::{digest, SHA256};
 use ring::signature::VerificationAlgorithm;
@@ -140,6 +141,33 @@ impl Key {
         Ok(digest(&SHA256, &buf).as_ref().to_vec().into())
     }
 
+    /// Returns the name of this key's signature scheme, as written in metadata.
+    pub fn scheme_name(&self) -> &'static str {
+        match self {
+            Key::Rsa {
+                scheme: RsaScheme::RsassaPssSha256,
+                ..
+            } => "rsassa-pss-sha256",
+            Key::Ed25519 {
+                scheme: Ed25519Scheme::Ed25519,
+                ..
+            } => "ed25519",
+            Key::Ecdsa {
+                scheme: EcdsaScheme::EcdsaSha2Nistp256,
+                ..
+            } => "ecdsa-sha2-nistp256",
+        }
+    }
+
+    /// Returns the size in bits of this key's modulus if it's an RSA key, or `None` for other keys
+    /// and RSA keys that can't be parsed.
+    pub fn rsa_modulus_bits(&self) -> Option<usize> {
+        match self {
+            Key::Rsa { keyval, .. } => spki::rsa_modulus_bits(&keyval.public),
+            Key::Ed25519 { .. } | Key::Ecdsa { .. } => None,
+        }
+    }
+
     /// Verify a signature of an object made with this key.
     pub(super) fn verify(&self, msg: &[u8], signature: &[u8]) -> bool {
         let (alg, public_key): (&dyn VerificationAlgorithm, untrusted::Input<'_>) = match self {
@@ -4,16 +4,23 @@
 
 mod de;
 pub mod decoded;
+mod diff;
 mod error;
+pub(crate) mod hash;
 mod iter;
 pub mod key;
 mod spki;
+mod validate;
 mod verify;
 
+pub use crate::schema::de::check_unique_keys;
 use crate::schema::decoded::{Decoded, Hex};
+pub use crate::schema::diff::{Change, RoleDiff, RootDiff};
 pub use crate::schema::error::{Error, Result};
+pub use crate::schema::hash::HashAlgorithm;
 use crate::schema::iter::KeysIter;
 use crate::schema::key::Key;
+pub use crate::schema::validate::RootProblem;
 use crate::sign::Sign;
 pub use crate::transport::{FilesystemTransport, Transport};
 use crate::{encode_filename, TargetName};
@@ -21,7 +28,7 @@ use chrono::{DateTime, Utc};
 use globset::{Glob, GlobMatcher};
 use hex::ToHex;
 use olpc_cjson::CanonicalFormatter;
-use ring::digest::{digest, Context, SHA256};
+use ring::digest::{digest, Context, Digest, SHA256};
 use serde::de::Error as SerdeDeError;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
@@ -30,10 +37,75 @@ use snafu::ResultExt;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::Read;
-use std::num::NonZeroU64;
+use std::num::{NonZeroU64, NonZeroUsize};
 use std::ops::{Deref, DerefMut};
 use std::path::Path;
 use std::str::FromStr;
+use std::sync::mpsc::{sync_channel, SyncSender};
+
+/// The version of the TUF specification implemented by this library. New metadata is written with
+/// this `spec_version` unless another version is given.
+pub const SPEC_VERSION: &str = "1.0.0";
+
+/// Parses a `spec_version` of the form `major.minor.patch`.
+fn parse_spec_version(spec_version: &str) -> Option<(u64, u64, u64)> {
+    let mut parts = spec_version.split('.').map(str::parse::<u64>);
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(major)), Some(Ok(minor)), Some(Ok(patch)), None) => Some((major, minor, patch)),
+        _ => None,
+    }
+}
+
+/// Returns `true` if `spec_version` is a version of the TUF specification this library knows
+/// about, i.e. it parses and is not newer than [`SPEC_VERSION`].
+pub fn spec_version_known(spec_version: &str) -> bool {
+    match (
+        parse_spec_version(spec_version),
+        parse_spec_version(SPEC_VERSION),
+    ) {
+        (Some(given), Some(supported)) => given <= supported,
+        _ => false,
+    }
+}
+
+/// Returns `true` if `spec_version` shares its major version with [`SPEC_VERSION`], meaning
+/// metadata declaring it can be edited and re-signed without changing its format.
+pub(crate) fn spec_version_compatible(spec_version: &str) -> bool {
+    match (
+        parse_spec_version(spec_version),
+        parse_spec_version(SPEC_VERSION),
+    ) {
+        (Some((given, _, _)), Some((supported, _, _))) => given == supported,
+        _ => false,
+    }
+}
+
+/// Serializes `value` as pretty-printed JSON in which the keys of every object are sorted, as they
+/// are in the canonical form that is signed. Metadata is written this way so that the same
+/// metadata always serializes to the same bytes, and diffs between versions only show real
+/// changes. Array elements keep their order.
+pub fn to_stable_json_pretty<T: Serialize>(value: &T) -> serde_json::Result<Vec<u8>> {
+    fn sort_keys(value: Value) -> Value {
+        match value {
+            Value::Object(map) => {
+                let mut entries = map.into_iter().collect::<Vec<_>>();
+                entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                // Inserting in sorted order keeps the keys sorted even if `serde_json` was built
+                // to preserve insertion order
+                Value::Object(
+                    entries
+                        .into_iter()
+                        .map(|(key, value)| (key, sort_keys(value)))
+                        .collect(),
+                )
+            }
+            Value::Array(values) => Value::Array(values.into_iter().map(sort_keys).collect()),
+            value => value,
+        }
+    }
+
+    serde_json::to_vec_pretty(&sort_keys(serde_json::to_value(value)?))
+}
 
 /// The type of metadata role.
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Hash)]
@@ -416,6 +488,20 @@ pub struct Targets {
     pub _extra: HashMap<String, Value>,
 }
 
+/// A role consulted while resolving a target name, as listed by [`Targets::resolution_order`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolutionStep {
+    /// The role's name; the top-level role is named `targets`.
+    pub role: String,
+    /// How many delegations separate the role from the top-level role, which is at depth 0.
+    pub depth: usize,
+    /// Whether the role lists the target name.
+    pub lists_target: bool,
+    /// Whether the delegation to the role is terminating, which ends the search once the role
+    /// and its own delegations are consulted. Always `false` for the top-level role.
+    pub terminating: bool,
+}
+
 /// TUF 4.5: TARGETS is an object whose format is the following:
 /// ```text
 /// { TARGETPATH : {
@@ -456,6 +542,15 @@ pub struct Target {
 impl Target {
     /// Given a path, returns a Target struct
     pub fn from_path<P>(path: P) -> Result<Target>
+    where
+        P: AsRef<Path>,
+    {
+        Target::from_path_with_hashes(path, &[])
+    }
+
+    /// Given a path, returns a Target struct whose `hashes` include the hash of each of
+    /// `algorithms` as well as `sha256`. The file is read once for all of the hashes.
+    pub fn from_path_with_hashes<P>(path: P, algorithms: &[HashAlgorithm]) -> Result<Target>
     where
         P: AsRef<Path>,
     {
@@ -468,6 +563,10 @@ impl Target {
         // Get the sha256 and length of the target
         let mut file = File::open(path).context(error::FileOpenSnafu { path })?;
         let mut digest = Context::new(&SHA256);
+        let mut hashers: Vec<_> = algorithms
+            .iter()
+            .map(|algorithm| (*algorithm, algorithm.hasher()))
+            .collect();
         let mut buf = [0; 8 * 1024];
         let mut length = 0;
         loop {
@@ -475,20 +574,95 @@ impl Target {
                 0 => break,
                 n => {
                     digest.update(&buf[..n]);
+                    for (_, hasher) in &mut hashers {
+                        hasher.update(&buf[..n]);
+                    }
                     length += n as u64;
                 }
             }
         }
 
-        Ok(Target {
+        let mut target = Target::from_digest(length, &digest.finish());
+        for (algorithm, hasher) in hashers {
+            target.hashes._extra.insert(
+                algorithm.key().to_string(),
+                Value::String(hex::encode(hasher.finish())),
+            );
+        }
+        Ok(target)
+    }
+
+    /// Given a path, returns a Target struct, reading the file on one thread while hashing it on
+    /// another. This speeds up hashing very large files, whose reads would otherwise stall the
+    /// hashing. The result is the same as from [`from_path`](Self::from_path).
+    ///
+    /// The file is read in chunks of `chunk_size` bytes, and at most `read_ahead` chunks are read
+    /// ahead of the hashing, so memory use is bounded by about `chunk_size * (read_ahead + 2)`.
+    pub fn from_path_pipelined<P>(
+        path: P,
+        chunk_size: NonZeroUsize,
+        read_ahead: NonZeroUsize,
+    ) -> Result<Target>
+    where
+        P: AsRef<Path>,
+    {
+        // Ensure the given path is a file
+        let path = path.as_ref();
+        if !path.is_file() {
+            return error::TargetNotAFileSnafu { path }.fail();
+        }
+
+        let file = File::open(path).context(error::FileOpenSnafu { path })?;
+        let (sender, receiver) = sync_channel(read_ahead.get());
+        let mut digest = Context::new(&SHA256);
+        let mut length = 0;
+        std::thread::scope(|scope| -> Result<()> {
+            scope.spawn(move || read_chunks(file, chunk_size.get(), &sender));
+            // The reader stops after sending an error or when the file ends, which closes the
+            // channel
+            for chunk in receiver {
+                let chunk = chunk.context(error::FileReadSnafu { path })?;
+                digest.update(&chunk);
+                length += chunk.len() as u64;
+            }
+            Ok(())
+        })?;
+
+        Ok(Target::from_digest(length, &digest.finish()))
+    }
+
+    fn from_digest(length: u64, sha256: &Digest) -> Target {
+        Target {
             length,
             hashes: Hashes {
-                sha256: Decoded::from(digest.finish().as_ref().to_vec()),
+                sha256: Decoded::from(sha256.as_ref().to_vec()),
                 _extra: HashMap::new(),
             },
             custom: HashMap::new(),
             _extra: HashMap::new(),
-        })
+        }
+    }
+}
+
+/// Reads `file` in chunks of `chunk_size` bytes, sending each to `sender`, until the end of the
+/// file or the first error. Every chunk but the last is full, so the chunks don't depend on how
+/// the file's reads happen to be split.
+fn read_chunks(mut file: File, chunk_size: usize, sender: &SyncSender<std::io::Result<Vec<u8>>>) {
+    loop {
+        let mut chunk = Vec::with_capacity(chunk_size);
+        match (&mut file).take(chunk_size as u64).read_to_end(&mut chunk) {
+            Ok(0) => return,
+            Ok(n) => {
+                // The hashing side only goes away after an error, so there's nothing left to do
+                if sender.send(Ok(chunk)).is_err() || n < chunk_size {
+                    return;
+                }
+            }
+            Err(err) => {
+                let _ = sender.send(Err(err));
+                return;
+            }
+        }
     }
 }
 
@@ -534,6 +708,90 @@ impl Targets {
         .fail()
     }
 
+    /// Returns each target name that is listed by more than one role in the delegation tree,
+    /// along with the names of those roles in the order they are consulted. This role is reported
+    /// as `targets`. Roles that are only reachable past a matching terminating delegation are
+    /// never consulted for the name, so they are not reported.
+    ///
+    /// Such duplicates usually point to misconfigured delegations, since which copy of the target
+    /// is used depends on the order of the delegations.
+    pub fn duplicate_targets(&self) -> Vec<(TargetName, Vec<String>)> {
+        let mut target_names = self
+            .targets_iter()
+            .map(|(target_name, _)| target_name)
+            .collect::<Vec<_>>();
+        target_names.sort();
+        target_names.dedup();
+        target_names
+            .into_iter()
+            .filter_map(|target_name| {
+                let roles = self
+                    .resolution_order(target_name)
+                    .into_iter()
+                    .filter(|step| step.lists_target)
+                    .map(|step| step.role)
+                    .collect::<Vec<_>>();
+                (roles.len() > 1).then(|| (target_name.clone(), roles))
+            })
+            .collect()
+    }
+
+    /// Returns the roles consulted to resolve `target_name`, in the order the TUF specification
+    /// consults them: this role, named `targets`, then each delegated role whose paths match the
+    /// name, depth first and in listed order. The search ends after a matching terminating
+    /// delegation, so roles past it are not returned. The first role that lists the name is the
+    /// one whose target is trusted.
+    ///
+    /// This is meant for diagnosing overlapping delegations; it doesn't change how targets are
+    /// resolved. Roles whose metadata isn't loaded are returned as not listing the name.
+    pub fn resolution_order(&self, target_name: &TargetName) -> Vec<ResolutionStep> {
+        let mut steps = vec![ResolutionStep {
+            role: "targets".to_string(),
+            depth: 0,
+            lists_target: self.targets.contains_key(target_name),
+            terminating: false,
+        }];
+        self.delegation_order(target_name, 1, &mut steps);
+        steps
+    }
+
+    /// Appends to `steps` the delegated roles of this role that are consulted for `target_name`,
+    /// which are at `depth`. Returns `true` once a matching terminating delegation ends the search.
+    fn delegation_order(
+        &self,
+        target_name: &TargetName,
+        depth: usize,
+        steps: &mut Vec<ResolutionStep>,
+    ) -> bool {
+        if let Some(delegations) = &self.delegations {
+            for role in &delegations.roles {
+                if !role.paths.matches_target_name(target_name) {
+                    continue;
+                }
+                steps.push(ResolutionStep {
+                    role: role.name.clone(),
+                    depth,
+                    lists_target: role.targets.as_ref().map_or(false, |targets| {
+                        targets.signed.targets.contains_key(target_name)
+                    }),
+                    terminating: role.terminating,
+                });
+                if let Some(targets) = &role.targets {
+                    if targets
+                        .signed
+                        .delegation_order(target_name, depth + 1, steps)
+                    {
+                        return true;
+                    }
+                }
+                if role.terminating {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
     /// Returns a hashmap of all targets and all delegated targets recursively
     pub fn targets_map(&self) -> HashMap<TargetName, &Target> {
         self.targets_iter()
@@ -947,6 +1205,91 @@ impl PathPattern {
     fn matches_target_name(&self, target_name: &TargetName) -> bool {
         self.glob.is_match(target_name.resolved())
     }
+
+    /// Returns whether every name matched by `other` is also matched by this pattern. Only
+    /// containment that can be proven is reported: `other` must be equal to this pattern, a
+    /// literal name it matches, or made up of literals, `?` and `*` that this pattern's literals,
+    /// `?` and `*` cover, e.g. `foo/bar*` falls within `foo/*` but `foo/*` doesn't fall within
+    /// `foo/?`. Patterns using classes, alternates or escapes are only contained if equal.
+    fn contains(&self, other: &PathPattern) -> bool {
+        if self == other {
+            return true;
+        }
+        let other_tokens = match GlobToken::parse(other.value(), false) {
+            Some(tokens) => tokens,
+            None => return false,
+        };
+        if other_tokens
+            .iter()
+            .all(|token| matches!(token, GlobToken::Literal(_)))
+        {
+            return self.glob.is_match(other.value());
+        }
+        match GlobToken::parse(self.value(), true) {
+            Some(tokens) => GlobToken::covers(&tokens, &other_tokens),
+            None => false,
+        }
+    }
+}
+
+/// The parts of a glob that [`PathPattern::contains`] can reason about.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum GlobToken {
+    Literal(char),
+    /// `?`, which matches any one character.
+    Any,
+    /// `*`, which matches any run of characters, including `/`.
+    Star,
+}
+
+impl GlobToken {
+    /// Splits `pattern` into tokens, or returns `None` if it uses other glob syntax. A `**` is
+    /// only accepted if `recursive` is set, and is then read as `*`, which matches no more than
+    /// it does; so only parse the containing pattern this way.
+    fn parse(pattern: &str, recursive: bool) -> Option<Vec<GlobToken>> {
+        let mut tokens = Vec::new();
+        for c in pattern.chars() {
+            let token = match c {
+                '?' => GlobToken::Any,
+                '*' => GlobToken::Star,
+                '[' | ']' | '{' | '}' | '\\' => return None,
+                c => GlobToken::Literal(c),
+            };
+            if token == GlobToken::Star && tokens.last() == Some(&GlobToken::Star) {
+                if !recursive {
+                    return None;
+                }
+                continue;
+            }
+            tokens.push(token);
+        }
+        Some(tokens)
+    }
+
+    /// Returns whether the tokens of `outer` can consume the tokens of `inner`, each `*` of
+    /// `outer` taking any run of them and each `?` a single `?` or literal, which proves that
+    /// `outer` matches every name `inner` does.
+    fn covers(outer: &[GlobToken], inner: &[GlobToken]) -> bool {
+        // covered[i][j] is whether outer[i..] covers inner[j..]
+        let mut covered = vec![vec![false; inner.len() + 1]; outer.len() + 1];
+        covered[outer.len()][inner.len()] = true;
+        for i in (0..outer.len()).rev() {
+            for j in (0..=inner.len()).rev() {
+                let next = inner.get(j);
+                covered[i][j] = match outer[i] {
+                    GlobToken::Star => covered[i + 1][j] || (next.is_some() && covered[i][j + 1]),
+                    GlobToken::Any => {
+                        matches!(next, Some(GlobToken::Any | GlobToken::Literal(_)))
+                            && covered[i + 1][j + 1]
+                    }
+                    GlobToken::Literal(c) => {
+                        next == Some(&GlobToken::Literal(c)) && covered[i + 1][j + 1]
+                    }
+                };
+            }
+        }
+        covered[0][0]
+    }
 }
 
 impl FromStr for PathPattern {
@@ -1017,9 +1360,31 @@ impl FromStr for PathHashPrefix {
 }
 
 impl PathSet {
+    /// Returns the hash prefixes of hashed bin `bin` of `2^bit_length` bins: the target names
+    /// whose sha256 hash starts with the `bit_length` bits of `bin`. When `bit_length` isn't a
+    /// multiple of 4, the bin is listed as each hex prefix that starts with those bits.
+    pub(crate) fn hash_bin(bin: u32, bit_length: u8) -> PathSet {
+        let digits = (bit_length + 3) / 4;
+        let width = usize::from(digits);
+        let shift = u32::from(4 * digits - bit_length);
+        PathSet::PathHashPrefixes(
+            ((bin << shift)..((bin + 1) << shift))
+                .map(|prefix| PathHashPrefix(format!("{prefix:0width$x}")))
+                .collect(),
+        )
+    }
+
+    /// Returns the hashed bin of `2^bit_length` bins that `target_name` falls in.
+    pub(crate) fn hash_bin_of(target_name: &TargetName, bit_length: u8) -> u32 {
+        let target_name_digest = digest(&SHA256, target_name.resolved().as_bytes());
+        let mut first = [0; 4];
+        first.copy_from_slice(&target_name_digest.as_ref()[..4]);
+        u32::from_be_bytes(first) >> (32 - u32::from(bit_length))
+    }
+
     /// Given a `target_name`, returns whether or not this `PathSet` contains a pattern or hash
     /// prefix that matches.
-    fn matches_target_name(&self, target_name: &TargetName) -> bool {
+    pub(crate) fn matches_target_name(&self, target_name: &TargetName) -> bool {
         match self {
             Self::Paths(paths) => {
                 for path in paths {
@@ -1039,6 +1404,27 @@ impl PathSet {
         }
         false
     }
+
+    /// Returns whether every path in `other` falls within this `PathSet`. A pattern falls within
+    /// this `PathSet` if one of its patterns can be shown to match every name it matches, e.g.
+    /// `foo/bar*` falls within `foo/*`; see [`PathPattern::contains`]. A hash prefix falls within a
+    /// hash prefix that begins it. Patterns and hash prefixes can't be compared, so never fall
+    /// within each other.
+    pub(crate) fn contains_path_set(&self, other: &PathSet) -> bool {
+        match (self, other) {
+            (Self::Paths(paths), Self::Paths(other_paths)) => other_paths
+                .iter()
+                .all(|other| paths.iter().any(|path| path.contains(other))),
+            (Self::PathHashPrefixes(prefixes), Self::PathHashPrefixes(other_prefixes)) => {
+                other_prefixes.iter().all(|other| {
+                    prefixes
+                        .iter()
+                        .any(|prefix| other.value().starts_with(prefix.value()))
+                })
+            }
+            _ => false,
+        }
+    }
 }
 
 impl Delegations {
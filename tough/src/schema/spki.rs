@@ -95,6 +95,27 @@ pub(super) fn decode(
         .to_owned())
 }
 
+/// Returns the size in bits of the modulus of an `RSAPublicKey` document, or `None` if it can't be
+/// parsed.
+pub(super) fn rsa_modulus_bits(rsa_public_key: &[u8]) -> Option<usize> {
+    untrusted::Input::from(rsa_public_key)
+        .read_all(ring::error::Unspecified, |input| {
+            der::nested(
+                input,
+                der::Tag::Sequence,
+                ring::error::Unspecified,
+                |input| {
+                    let modulus = der::positive_integer(input)?;
+                    let _exponent = der::positive_integer(input)?;
+                    let modulus = modulus.big_endian_without_leading_zero();
+                    // `positive_integer` rejects empty input, so there is a first byte
+                    Ok(modulus.len() * 8 - modulus[0].leading_zeros() as usize)
+                },
+            )
+        })
+        .ok()
+}
+
 fn asn1_tag(tag: der::Tag, data: Vec<u8>) -> Vec<u8> {
     let mut v = vec![tag as u8];
     v.extend(asn1_encode_len(data.len()));
@@ -0,0 +1,186 @@
+use super::decoded::{Decoded, Hex};
+use super::{spec_version_known, RoleType, Root};
+use std::collections::HashSet;
+use std::fmt;
+
+/// The roles that every root.json must list, in the order they're checked.
+const REQUIRED_ROLES: [RoleType; 4] = [
+    RoleType::Root,
+    RoleType::Snapshot,
+    RoleType::Targets,
+    RoleType::Timestamp,
+];
+
+/// A problem found by [`Root::validate`] that makes a root.json unusable or contrary to the TUF
+/// specification, even though it deserializes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RootProblem {
+    /// The `spec_version` isn't a version of the specification this library knows about.
+    UnknownSpecVersion {
+        /// The `spec_version` given in root.json.
+        spec_version: String,
+    },
+    /// A top-level role isn't listed.
+    MissingRole {
+        /// The role that is missing.
+        role: RoleType,
+    },
+    /// A role is listed that root.json can't delegate to.
+    UnexpectedRole {
+        /// The role that is listed.
+        role: RoleType,
+    },
+    /// A role lists no key IDs.
+    NoKeys {
+        /// The role with no key IDs.
+        role: RoleType,
+    },
+    /// A role lists the same key ID more than once.
+    DuplicateKeyId {
+        /// The role listing the key ID.
+        role: RoleType,
+        /// The repeated key ID.
+        key_id: Decoded<Hex>,
+    },
+    /// A role lists a key ID that isn't in the `keys` map.
+    MissingKey {
+        /// The role listing the key ID.
+        role: RoleType,
+        /// The key ID with no key.
+        key_id: Decoded<Hex>,
+    },
+    /// A role's threshold is higher than the number of distinct keys it can use.
+    ThresholdUnsatisfiable {
+        /// The role whose threshold can't be met.
+        role: RoleType,
+        /// The role's threshold.
+        threshold: u64,
+        /// The number of distinct keys in the `keys` map that the role lists.
+        keys: usize,
+    },
+    /// A key in the `keys` map is listed under an ID that doesn't match its key material.
+    InvalidKeyId {
+        /// The key ID in the `keys` map.
+        key_id: Decoded<Hex>,
+        /// The key ID calculated from the key.
+        calculated: Decoded<Hex>,
+    },
+}
+
+impl fmt::Display for RootProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RootProblem::UnknownSpecVersion { spec_version } => {
+                write!(f, "Unknown spec_version '{}'", spec_version)
+            }
+            RootProblem::MissingRole { role } => write!(f, "Role '{}' is not listed", role),
+            RootProblem::UnexpectedRole { role } => {
+                write!(f, "Role '{}' cannot be listed in root.json", role)
+            }
+            RootProblem::NoKeys { role } => write!(f, "Role '{}' lists no key IDs", role),
+            RootProblem::DuplicateKeyId { role, key_id } => write!(
+                f,
+                "Role '{}' lists key ID {} more than once",
+                role,
+                hex::encode(key_id)
+            ),
+            RootProblem::MissingKey { role, key_id } => write!(
+                f,
+                "Role '{}' lists key ID {}, which is not in the keys map",
+                role,
+                hex::encode(key_id)
+            ),
+            RootProblem::ThresholdUnsatisfiable {
+                role,
+                threshold,
+                keys,
+            } => write!(
+                f,
+                "Role '{}' has threshold {} but only {} usable keys",
+                role, threshold, keys
+            ),
+            RootProblem::InvalidKeyId { key_id, calculated } => write!(
+                f,
+                "Key ID {} does not match its key, whose ID is {}",
+                hex::encode(key_id),
+                hex::encode(calculated)
+            ),
+        }
+    }
+}
+
+impl Root {
+    /// Checks this root for problems that deserialization doesn't catch: every top-level role must
+    /// be listed with at least one key ID, no duplicates, only key IDs present in `keys`, and a
+    /// threshold its keys can meet. Every key in `keys` must be listed under its own key ID, which
+    /// deserialization already ensures but a `Root` built in code may not.
+    ///
+    /// Returns every problem found, in a stable order; an empty list means the root is valid.
+    pub fn validate(&self) -> Vec<RootProblem> {
+        let mut problems = Vec::new();
+        if !spec_version_known(&self.spec_version) {
+            problems.push(RootProblem::UnknownSpecVersion {
+                spec_version: self.spec_version.clone(),
+            });
+        }
+
+        for role in REQUIRED_ROLES {
+            let role_keys = match self.roles.get(&role) {
+                Some(role_keys) => role_keys,
+                None => {
+                    problems.push(RootProblem::MissingRole { role });
+                    continue;
+                }
+            };
+            if role_keys.keyids.is_empty() {
+                problems.push(RootProblem::NoKeys { role });
+                continue;
+            }
+            let mut seen = HashSet::new();
+            for key_id in &role_keys.keyids {
+                if !seen.insert(key_id) {
+                    problems.push(RootProblem::DuplicateKeyId {
+                        role,
+                        key_id: key_id.clone(),
+                    });
+                } else if !self.keys.contains_key(key_id) {
+                    problems.push(RootProblem::MissingKey {
+                        role,
+                        key_id: key_id.clone(),
+                    });
+                }
+            }
+            let keys = seen
+                .into_iter()
+                .filter(|key_id| self.keys.contains_key(*key_id))
+                .count();
+            if (keys as u64) < role_keys.threshold.get() {
+                problems.push(RootProblem::ThresholdUnsatisfiable {
+                    role,
+                    threshold: role_keys.threshold.get(),
+                    keys,
+                });
+            }
+        }
+        if self.roles.contains_key(&RoleType::DelegatedTargets) {
+            problems.push(RootProblem::UnexpectedRole {
+                role: RoleType::DelegatedTargets,
+            });
+        }
+
+        let mut keys = self.keys.iter().collect::<Vec<_>>();
+        keys.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (key_id, key) in keys {
+            if let Ok(calculated) = key.key_id() {
+                if &calculated != key_id {
+                    problems.push(RootProblem::InvalidKeyId {
+                        key_id: key_id.clone(),
+                        calculated,
+                    });
+                }
+            }
+        }
+        problems
+    }
+}
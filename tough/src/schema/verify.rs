@@ -1,5 +1,7 @@
+use super::decoded::{Decoded, Hex};
 use super::error::{self, Result};
-use super::{Delegations, Role, RoleType, Root, Signed, Targets};
+use super::key::Key;
+use super::{Delegations, Role, RoleType, Root, Signature, Signed, Targets};
 use olpc_cjson::CanonicalFormatter;
 use serde::Serialize;
 use snafu::{ensure, OptionExt, ResultExt};
@@ -23,6 +25,7 @@ impl Root {
             })?;
 
         let mut valid_keyids = HashSet::new();
+        let mut mismatch = None;
 
         for signature in &role.signatures {
             if role_keys.keyids.contains(&signature.keyid) {
@@ -32,11 +35,20 @@ impl Root {
                         if valid_keyids.insert(&signature.keyid) {
                             valid += 1;
                         }
+                    } else if mismatch.is_none() {
+                        mismatch = signing_scheme(self.keys.values(), key, &data, &signature.sig)
+                            .map(|found| (&signature.keyid, key.scheme_name(), found));
                     }
                 }
             }
         }
 
+        check_algorithm_mismatch(
+            &T::TYPE.to_string(),
+            valid,
+            u64::from(role_keys.threshold),
+            mismatch,
+        )?;
         ensure!(
             valid >= u64::from(role_keys.threshold),
             error::SignatureThresholdSnafu {
@@ -47,6 +59,26 @@ impl Root {
         );
         Ok(())
     }
+
+    /// Checks a single signature of `role`, such as a detached signature collected from one of
+    /// several signers. Returns `false` if the signature wasn't made by a key trusted for the role
+    /// or doesn't match. Unlike [`Root::verify_role`], no threshold is applied.
+    pub fn signature_is_valid<T: Role + Serialize>(
+        &self,
+        role: &T,
+        signature: &Signature,
+    ) -> Result<bool> {
+        let trusted = self
+            .roles
+            .get(&T::TYPE)
+            .context(error::MissingRoleSnafu { role: T::TYPE })?
+            .keyids
+            .contains(&signature.keyid);
+        Ok(match self.keys.get(&signature.keyid) {
+            Some(key) if trusted => key.verify(&role.canonical_form()?, &signature.sig),
+            _ => false,
+        })
+    }
 }
 
 impl Delegations {
@@ -69,16 +101,21 @@ impl Delegations {
             .context(error::JsonSerializationSnafu {
                 what: format!("{name} role"),
             })?;
+        let mut mismatch = None;
         for signature in &role.signatures {
             if role_keys.keyids.contains(&signature.keyid) {
                 if let Some(key) = self.keys.get(&signature.keyid) {
                     if key.verify(&data, &signature.sig) {
                         valid += 1;
+                    } else if mismatch.is_none() {
+                        mismatch = signing_scheme(self.keys.values(), key, &data, &signature.sig)
+                            .map(|found| (&signature.keyid, key.scheme_name(), found));
                     }
                 }
             }
         }
 
+        check_algorithm_mismatch(name, valid, u64::from(role_keys.threshold), mismatch)?;
         ensure!(
             valid >= u64::from(role_keys.threshold),
             error::SignatureThresholdSnafu {
@@ -91,6 +128,41 @@ impl Delegations {
     }
 }
 
+/// Returns the scheme that a signature which failed to verify with `key` was made with, if one of
+/// the other `keys`, of a different scheme than `key`'s, verifies it. This catches signatures
+/// made with the wrong key for the key ID they give, e.g. an Ed25519 key's for an RSA key ID.
+fn signing_scheme<'a>(
+    keys: impl Iterator<Item = &'a Key>,
+    key: &Key,
+    data: &[u8],
+    signature: &[u8],
+) -> Option<&'static str> {
+    keys.filter(|other| other.scheme_name() != key.scheme_name())
+        .find(|other| other.verify(data, signature))
+        .map(Key::scheme_name)
+}
+
+/// When a role falls short of its signature threshold and one of its failed signatures was made
+/// with a key of a different scheme than the key it names, reports that rather than the
+/// threshold.
+fn check_algorithm_mismatch(
+    role: &str,
+    valid: u64,
+    threshold: u64,
+    mismatch: Option<(&Decoded<Hex>, &'static str, &'static str)>,
+) -> Result<()> {
+    match mismatch {
+        Some((key_id, expected, found)) if valid < threshold => error::AlgorithmMismatchSnafu {
+            role,
+            key_id: hex::encode(key_id),
+            expected,
+            found,
+        }
+        .fail(),
+        _ => Ok(()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{Root, Signed};
@@ -0,0 +1,308 @@
+//! The `sftp` module provides `SftpTransport` which enables `Repository` objects to be loaded
+//! over SSH, from a server that offers the SFTP subsystem.
+use crate::{Transport, TransportError, TransportErrorKind};
+use log::debug;
+use percent_encoding::percent_decode_str;
+use ssh2::{CheckResult, ErrorCode, HashType, KnownHostFileKind, Session, Sftp};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::io::Read;
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+use url::Url;
+
+/// The SFTP status code for a file that doesn't exist, `SSH_FX_NO_SUCH_FILE`.
+const SFTP_NO_SUCH_FILE: i32 = 2;
+
+/// How an [`SftpTransport`] authenticates to the SSH server.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum SftpAuth {
+    /// Authenticate with the keys held by the running SSH agent, found through `SSH_AUTH_SOCK`.
+    Agent,
+    /// Authenticate with the private key in the file at `path`, decrypting it with `passphrase` if
+    /// it is encrypted.
+    PrivateKey {
+        /// The path to the private key file.
+        path: PathBuf,
+        /// The passphrase of an encrypted private key.
+        passphrase: Option<String>,
+    },
+}
+
+/// How an [`SftpTransport`] checks the identity of the SSH server before authenticating to it.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum SftpHostKey {
+    /// Accept any host key. Like HTTP without TLS, this still lets the repository verify what it
+    /// fetches against the signed metadata, but the credentials used to log in may be sent to an
+    /// impostor.
+    Any,
+    /// Accept only a host key listed for the host, and port if it isn't 22, in the OpenSSH
+    /// `known_hosts` file at this path, e.g. `~/.ssh/known_hosts`.
+    KnownHosts(PathBuf),
+    /// Accept only the host key with this SHA-256 fingerprint, i.e. the decoded digest that
+    /// `ssh-keygen -l -E sha256` prints in base64.
+    Sha256(Vec<u8>),
+}
+
+/// A builder for [`SftpTransport`] which allows settings customization.
+///
+/// # Example
+///
+/// ```
+/// # use tough::{SftpAuth, SftpHostKey, SftpTransportBuilder};
+/// let sftp_transport = SftpTransportBuilder::new("tuf")
+///     .auth(SftpAuth::PrivateKey {
+///         path: "/home/tuf/.ssh/id_ed25519".into(),
+///         passphrase: None,
+///     })
+///     .host_key(SftpHostKey::KnownHosts("/home/tuf/.ssh/known_hosts".into()))
+///     .build();
+/// ```
+#[derive(Clone, Debug)]
+pub struct SftpTransportBuilder {
+    username: String,
+    auth: SftpAuth,
+    host_key: SftpHostKey,
+    timeout: Duration,
+}
+
+impl SftpTransportBuilder {
+    /// Create a new `SftpTransportBuilder` that logs in as `username`, using the SSH agent, unless
+    /// a URL names its own user, e.g. `sftp://tuf@example.com/repo/metadata/`.
+    pub fn new<S: Into<String>>(username: S) -> Self {
+        Self {
+            username: username.into(),
+            auth: SftpAuth::Agent,
+            host_key: SftpHostKey::Any,
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Set how to authenticate to the server.
+    #[must_use]
+    pub fn auth(mut self, value: SftpAuth) -> Self {
+        self.auth = value;
+        self
+    }
+
+    /// Set how to check the server's host key. Defaults to [`SftpHostKey::Any`].
+    #[must_use]
+    pub fn host_key(mut self, value: SftpHostKey) -> Self {
+        self.host_key = value;
+        self
+    }
+
+    /// Set a timeout for each blocking operation of the SSH session, e.g. the handshake or a read.
+    #[must_use]
+    pub fn timeout(mut self, value: Duration) -> Self {
+        self.timeout = value;
+        self
+    }
+
+    /// Construct an [`SftpTransport`] transport from this builder's settings.
+    pub fn build(self) -> SftpTransport {
+        SftpTransport {
+            settings: self,
+            sessions: SessionCache::default(),
+        }
+    }
+}
+
+/// A [`Transport`] over SFTP, for `sftp://host[:port]/path` URLs. The first fetch from a host
+/// opens an SSH session to it, checked and authenticated as set by the [`SftpTransportBuilder`],
+/// which later fetches from the host reuse; clones of the transport share the sessions. The
+/// returned reader streams the file from the server rather than reading it all up front.
+#[derive(Clone, Debug)]
+pub struct SftpTransport {
+    settings: SftpTransportBuilder,
+    sessions: SessionCache,
+}
+
+/// Identifies the sessions an [`SftpTransport`] can reuse: those to the same host and port, logged
+/// in as the same user.
+type SessionKey = (String, u16, String);
+
+/// The SFTP sessions opened by an [`SftpTransport`].
+#[derive(Clone, Default)]
+struct SessionCache(Arc<Mutex<HashMap<SessionKey, Sftp>>>);
+
+impl SessionCache {
+    fn lock(&self) -> MutexGuard<'_, HashMap<SessionKey, Sftp>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl fmt::Debug for SessionCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.lock().keys()).finish()
+    }
+}
+
+impl Transport for SftpTransport {
+    fn fetch(&self, url: Url) -> Result<Box<dyn Read + Send>, TransportError> {
+        if url.scheme() != "sftp" {
+            return Err(TransportError::new(
+                TransportErrorKind::UnsupportedUrlScheme,
+                url,
+            ));
+        }
+        let path = percent_decode_str(url.path())
+            .decode_utf8()
+            .map_err(|e| other(&url, e))?;
+        let path = Path::new(&*path);
+        let key = self.session_key(&url)?;
+
+        let mut sessions = self.sessions.lock();
+        let file = sessions.get(&key).and_then(|sftp| {
+            debug!(
+                "opening '{}' over the SFTP session to '{}'",
+                path.display(),
+                key.0
+            );
+            match sftp.open(path) {
+                // The session may have been closed, e.g. by the server timing it out, in which
+                // case a new one is opened below
+                Err(e) if !matches!(e.code(), ErrorCode::SFTP(_)) => {
+                    debug!("discarding the SFTP session to '{}': {}", key.0, e);
+                    None
+                }
+                result => Some(result),
+            }
+        });
+        let file = match file {
+            Some(file) => file,
+            None => {
+                let sftp = self.connect(&url, &key)?;
+                debug!("opening '{}' over a new SFTP session", path.display());
+                let file = sftp.open(path);
+                sessions.insert(key, sftp);
+                file
+            }
+        };
+        // The file keeps the session it was opened with alive until it's dropped.
+        Ok(Box::new(file.map_err(|e| sftp_error(&url, e))?))
+    }
+}
+
+impl SftpTransport {
+    /// Returns the host, port and user of the session to fetch `url` with.
+    fn session_key(&self, url: &Url) -> Result<SessionKey, TransportError> {
+        let host = url.host_str().ok_or_else(|| {
+            TransportError::new_with_cause(
+                TransportErrorKind::Other,
+                url,
+                "SFTP URLs must name a host, e.g. 'sftp://example.com/repo/'",
+            )
+        })?;
+        let username = if url.username().is_empty() {
+            self.settings.username.as_str()
+        } else {
+            url.username()
+        };
+        Ok((
+            host.to_owned(),
+            url.port().unwrap_or(22),
+            username.to_owned(),
+        ))
+    }
+
+    /// Opens an SFTP session to the host of `url`, over an SSH session that has been checked and
+    /// authenticated.
+    fn connect(&self, url: &Url, key: &SessionKey) -> Result<Sftp, TransportError> {
+        let (host, port, username) = key;
+        let addrs = url.socket_addrs(|| Some(22)).map_err(|e| other(url, e))?;
+        let tcp = TcpStream::connect(&*addrs).map_err(|e| other(url, e))?;
+
+        let mut session = Session::new().map_err(|e| other(url, e))?;
+        session.set_timeout(u32::try_from(self.settings.timeout.as_millis()).unwrap_or(u32::MAX));
+        session.set_tcp_stream(tcp);
+        session.handshake().map_err(|e| other(url, e))?;
+        self.check_host_key(url, &session, host, *port)?;
+
+        match &self.settings.auth {
+            SftpAuth::Agent => session.userauth_agent(username),
+            SftpAuth::PrivateKey { path, passphrase } => {
+                session.userauth_pubkey_file(username, None, path, passphrase.as_deref())
+            }
+        }
+        .map_err(|e| other(url, e))?;
+        session.sftp().map_err(|e| sftp_error(url, e))
+    }
+
+    /// Fails unless the host key that `session` was offered is accepted by the settings.
+    fn check_host_key(
+        &self,
+        url: &Url,
+        session: &Session,
+        host: &str,
+        port: u16,
+    ) -> Result<(), TransportError> {
+        match &self.settings.host_key {
+            SftpHostKey::Any => Ok(()),
+            SftpHostKey::KnownHosts(path) => {
+                let (host_key, _) = session
+                    .host_key()
+                    .ok_or_else(|| other(url, "the server offered no host key"))?;
+                let mut known_hosts = session.known_hosts().map_err(|e| other(url, e))?;
+                known_hosts
+                    .read_file(path, KnownHostFileKind::OpenSSH)
+                    .map_err(|e| other(url, e))?;
+                match known_hosts.check_port(host, port, host_key) {
+                    CheckResult::Match => Ok(()),
+                    CheckResult::Mismatch => Err(other(
+                        url,
+                        format!("the host key doesn't match the one in '{}'", path.display()),
+                    )),
+                    CheckResult::NotFound => Err(other(
+                        url,
+                        format!("the host isn't listed in '{}'", path.display()),
+                    )),
+                    CheckResult::Failure => Err(other(
+                        url,
+                        format!(
+                            "the host key could not be checked against '{}'",
+                            path.display()
+                        ),
+                    )),
+                }
+            }
+            SftpHostKey::Sha256(expected) => {
+                let fingerprint = session
+                    .host_key_hash(HashType::Sha256)
+                    .ok_or_else(|| other(url, "the server offered no host key"))?;
+                if fingerprint == expected.as_slice() {
+                    Ok(())
+                } else {
+                    Err(other(
+                        url,
+                        "the host key doesn't match the expected SHA-256 fingerprint",
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// Wraps an error that isn't about the file itself, e.g. a failure to connect or authenticate.
+fn other<E>(url: &Url, e: E) -> TransportError
+where
+    E: Into<Box<dyn Error + Send + Sync>>,
+{
+    TransportError::new_with_cause(TransportErrorKind::Other, url, e)
+}
+
+/// Converts an SFTP error into a [`TransportError`], which is `FileNotFound` if the server says
+/// the file doesn't exist.
+fn sftp_error(url: &Url, e: ssh2::Error) -> TransportError {
+    let kind = match e.code() {
+        ErrorCode::SFTP(SFTP_NO_SUCH_FILE) => TransportErrorKind::FileNotFound,
+        _ => TransportErrorKind::Other,
+    };
+    TransportError::new_with_cause(kind, url, e)
+}
@@ -1,10 +1,15 @@
+use crate::urlpath::redact_url;
 use crate::SafeUrlPath;
 #[cfg(feature = "http")]
 use crate::{HttpTransport, HttpTransportBuilder};
 use dyn_clone::DynClone;
+use log::{debug, log_enabled, Level};
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
 use std::io::{ErrorKind, Read};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use url::Url;
 
 /// A trait to abstract over the method/protocol by which files are obtained.
@@ -17,6 +22,20 @@ use url::Url;
 pub trait Transport: Debug + DynClone {
     /// Opens a `Read` object for the file specified by `url`.
     fn fetch(&self, url: Url) -> Result<Box<dyn Read + Send + '_>, TransportError>;
+
+    /// Opens a `Read` object for the file specified by `url`, whose sha256 digest the metadata
+    /// says is `sha256`. The caller verifies the digest as the file is read whatever the transport
+    /// does, but a transport that can learn a file's digest before downloading it, such as the
+    /// `S3Transport` of the `s3` feature, may use this to fail early. By default, the digest is
+    /// ignored and the file is fetched with [`fetch`](Self::fetch).
+    fn fetch_sha256(
+        &self,
+        url: Url,
+        sha256: &[u8],
+    ) -> Result<Box<dyn Read + Send + '_>, TransportError> {
+        let _ = sha256;
+        self.fetch(url)
+    }
 }
 
 // Implements `Clone` for `Transport` trait objects (i.e. on `Box::<dyn Clone>`). To facilitate
@@ -165,9 +184,307 @@ impl Transport for FilesystemTransport {
 
 // =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=
 
+/// A hook that rewrites a URL right before it is fetched, e.g. to append a short-lived signature
+/// required by a CDN. Because the URL is signed for each fetch, signatures stay fresh even when
+/// many files are fetched over a long time.
+///
+/// See [`RepositoryLoader::target_url_signer`](crate::RepositoryLoader::target_url_signer) and
+/// [`RepositoryLoader::metadata_url_signer`](crate::RepositoryLoader::metadata_url_signer).
+pub trait UrlSigner: Debug + Send + Sync {
+    /// Returns the URL to fetch in place of `url`.
+    fn sign_url(&self, url: Url) -> Result<Url, TransportError>;
+}
+
+/// A [`Transport`] that passes each URL through a [`UrlSigner`] before fetching it.
+#[derive(Debug, Clone)]
+pub(crate) struct UrlSigningTransport {
+    pub(crate) inner: Box<dyn Transport + Send + Sync>,
+    pub(crate) signer: Arc<dyn UrlSigner>,
+}
+
+impl Transport for UrlSigningTransport {
+    fn fetch(&self, url: Url) -> Result<Box<dyn Read + Send + '_>, TransportError> {
+        let url = self.signer.sign_url(url)?;
+        self.inner.fetch(url)
+    }
+
+    fn fetch_sha256(
+        &self,
+        url: Url,
+        sha256: &[u8],
+    ) -> Result<Box<dyn Read + Send + '_>, TransportError> {
+        let url = self.signer.sign_url(url)?;
+        self.inner.fetch_sha256(url, sha256)
+    }
+}
+
+// =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=
+
+/// A [`Transport`] that logs each fetch at debug level: the URL fetched, whether it succeeded, the
+/// number of bytes read, and how long it took. The values of query parameters and any password
+/// in the URL are redacted, since signed URLs carry their credentials there.
+#[derive(Debug, Clone)]
+pub(crate) struct LoggingTransport {
+    pub(crate) inner: Box<dyn Transport + Send + Sync>,
+}
+
+impl Transport for LoggingTransport {
+    fn fetch(&self, url: Url) -> Result<Box<dyn Read + Send + '_>, TransportError> {
+        self.logged_fetch(url, |url| self.inner.fetch(url))
+    }
+
+    fn fetch_sha256(
+        &self,
+        url: Url,
+        sha256: &[u8],
+    ) -> Result<Box<dyn Read + Send + '_>, TransportError> {
+        self.logged_fetch(url, |url| self.inner.fetch_sha256(url, sha256))
+    }
+}
+
+impl LoggingTransport {
+    /// Fetches `url` with `fetch`, logging the outcome.
+    fn logged_fetch<'a, F>(
+        &'a self,
+        url: Url,
+        fetch: F,
+    ) -> Result<Box<dyn Read + Send + 'a>, TransportError>
+    where
+        F: FnOnce(Url) -> Result<Box<dyn Read + Send + 'a>, TransportError>,
+    {
+        if !log_enabled!(Level::Debug) {
+            return fetch(url);
+        }
+        let start = Instant::now();
+        let redacted = redact_url(&url);
+        match fetch(url) {
+            Ok(read) => Ok(Box::new(LoggingRead {
+                inner: read,
+                url: redacted,
+                start,
+                bytes: 0,
+                logged: false,
+            })),
+            Err(err) => {
+                // The error itself isn't logged, as it holds the unredacted URL
+                debug!(
+                    "fetch of '{}' failed after {:?}: {}",
+                    redacted,
+                    start.elapsed(),
+                    err.kind()
+                );
+                Err(err)
+            }
+        }
+    }
+}
+
+/// Counts the bytes read from a fetched file, and logs the outcome once the file has been read to
+/// its end, has failed to read, or is dropped early.
+struct LoggingRead<'a> {
+    inner: Box<dyn Read + Send + 'a>,
+    url: String,
+    start: Instant,
+    bytes: u64,
+    logged: bool,
+}
+
+impl Read for LoggingRead<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let result = self.inner.read(buf);
+        if !self.logged {
+            match &result {
+                Ok(0) if !buf.is_empty() => {
+                    debug!(
+                        "fetched '{}': {} bytes in {:?}",
+                        self.url,
+                        self.bytes,
+                        self.start.elapsed()
+                    );
+                    self.logged = true;
+                }
+                Ok(n) => self.bytes += *n as u64,
+                Err(err) => {
+                    debug!(
+                        "reading '{}' failed after {} bytes in {:?}: {}",
+                        self.url,
+                        self.bytes,
+                        self.start.elapsed(),
+                        err.kind()
+                    );
+                    self.logged = true;
+                }
+            }
+        }
+        result
+    }
+}
+
+impl Drop for LoggingRead<'_> {
+    fn drop(&mut self) {
+        if !self.logged {
+            debug!(
+                "closed '{}' before its end: {} bytes in {:?}",
+                self.url,
+                self.bytes,
+                self.start.elapsed()
+            );
+        }
+    }
+}
+
+// =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=
+
+/// Caps the retries made for failed fetches across an entire
+/// [`RepositoryLoader::load`](crate::RepositoryLoader::load), so that a mirror failing for every
+/// file fails the load quickly instead of retrying each file in turn.
+///
+/// A fetch that fails with [`TransportErrorKind::Other`] is retried up to `tries` times in total,
+/// and each retry spends from the budget shared by every fetch of the load. Once `max_retries`
+/// retries have been spent, or `max_duration` has passed since the load started, failed fetches
+/// are no longer retried.
+///
+/// Retries made inside a [`Transport`] are not counted. To have the budget cap all retries, turn
+/// off the transport's own retries, e.g. with `HttpTransportBuilder::tries(1)`.
+///
+/// # Example
+///
+/// ```
+/// # use std::time::Duration;
+/// # use tough::RetryBudget;
+/// let budget = RetryBudget::new(10)
+///     .tries(3)
+///     .max_duration(Duration::from_secs(30));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct RetryBudget {
+    max_retries: u32,
+    max_duration: Option<Duration>,
+    tries: u32,
+    backoff: Duration,
+}
+
+impl RetryBudget {
+    /// Create a `RetryBudget` allowing `max_retries` retries in total. By default each fetch is
+    /// tried up to 4 times, with a 100ms pause between tries, and there is no time limit.
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            max_duration: None,
+            tries: 4,
+            backoff: Duration::from_millis(100),
+        }
+    }
+
+    /// Set the time after the load starts past which failed fetches are no longer retried.
+    #[must_use]
+    pub fn max_duration(mut self, value: Duration) -> Self {
+        self.max_duration = Some(value);
+        self
+    }
+
+    /// Set the total number of times a single fetch is tried, budget permitting.
+    #[must_use]
+    pub fn tries(mut self, value: u32) -> Self {
+        self.tries = value;
+        self
+    }
+
+    /// Set the pause duration between tries of a fetch.
+    #[must_use]
+    pub fn backoff(mut self, value: Duration) -> Self {
+        self.backoff = value;
+        self
+    }
+}
+
+/// A [`Transport`] that retries failed fetches while its [`RetryBudget`] allows. Clones share the
+/// spent budget.
+#[derive(Debug, Clone)]
+pub(crate) struct RetryBudgetTransport {
+    inner: Box<dyn Transport + Send + Sync>,
+    budget: RetryBudget,
+    start: Instant,
+    retries: Arc<AtomicU32>,
+}
+
+impl RetryBudgetTransport {
+    /// Starts spending `budget` on the fetches of `inner`.
+    pub(crate) fn new(inner: Box<dyn Transport + Send + Sync>, budget: RetryBudget) -> Self {
+        Self {
+            inner,
+            budget,
+            start: Instant::now(),
+            retries: Arc::default(),
+        }
+    }
+
+    /// Spends one retry from the budget, returning `false` if the budget is exhausted.
+    fn spend(&self) -> bool {
+        if let Some(max_duration) = self.budget.max_duration {
+            if self.start.elapsed() >= max_duration {
+                return false;
+            }
+        }
+        self.retries
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |retries| {
+                (retries < self.budget.max_retries).then_some(retries + 1)
+            })
+            .is_ok()
+    }
+}
+
+impl Transport for RetryBudgetTransport {
+    fn fetch(&self, url: Url) -> Result<Box<dyn Read + Send + '_>, TransportError> {
+        self.retried_fetch(&url, |url| self.inner.fetch(url))
+    }
+
+    fn fetch_sha256(
+        &self,
+        url: Url,
+        sha256: &[u8],
+    ) -> Result<Box<dyn Read + Send + '_>, TransportError> {
+        self.retried_fetch(&url, |url| self.inner.fetch_sha256(url, sha256))
+    }
+}
+
+impl RetryBudgetTransport {
+    /// Fetches `url` with `fetch`, retrying as the budget allows.
+    fn retried_fetch<'a, F>(
+        &'a self,
+        url: &Url,
+        fetch: F,
+    ) -> Result<Box<dyn Read + Send + 'a>, TransportError>
+    where
+        F: Fn(Url) -> Result<Box<dyn Read + Send + 'a>, TransportError>,
+    {
+        let mut tries = 1;
+        loop {
+            let err = match fetch(url.clone()) {
+                Ok(read) => return Ok(read),
+                Err(err) => err,
+            };
+            if !matches!(err.kind(), TransportErrorKind::Other) || tries >= self.budget.tries {
+                return Err(err);
+            }
+            if !self.spend() {
+                debug!("retry budget exhausted, not retrying '{}': {}", url, err);
+                return Err(err);
+            }
+            tries += 1;
+            std::thread::sleep(self.budget.backoff);
+        }
+    }
+}
+
+// =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=   =^..^=
+
 /// A Transport that provides support for both local files and, if the `http` feature is enabled,
 /// HTTP-transported files.
-#[derive(Debug, Clone, Copy)]
+// Not `Copy` even without the `http` feature, since enabling a feature must not remove a trait
+// implementation that other crates may rely on.
+#[allow(missing_copy_implementations)]
+#[derive(Debug, Clone)]
 pub struct DefaultTransport {
     file: FilesystemTransport,
     #[cfg(feature = "http")]
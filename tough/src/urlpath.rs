@@ -1,10 +1,75 @@
-//! This module contains utilities for mapping URL paths to local Paths.
+//! This module contains utilities for mapping URL paths to local Paths, and for logging URLs.
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
 use std::path::PathBuf;
 use url::Url;
 
+/// The set of characters that are escaped in each segment of a file path when it is joined onto a
+/// base URL. This is the URL path segment set, plus `%` so that a file name which is itself
+/// percent-encoded (such as a delegated role's metadata file) is fetched by its literal name, and
+/// `:` and `\` so that a segment is never read as a URL scheme or a path separator.
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b':')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'\\')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// Percent-encodes each `/`-separated segment of a relative file path so that it can be joined
+/// onto a base URL, and the server (or [`SafeUrlPath`]) decodes it back to the same file path.
+pub(crate) fn encode_url_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| utf8_percent_encode(segment, PATH_SEGMENT).to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Returns `url` with the value of each query parameter, and any password, replaced, so that it
+/// can be logged without the credentials of a signed URL.
+pub(crate) fn redact_url(url: &Url) -> String {
+    let mut url = url.clone();
+    if url.query().is_some() {
+        let names = url
+            .query_pairs()
+            .map(|(name, _)| name.into_owned())
+            .collect::<Vec<_>>();
+        url.query_pairs_mut()
+            .clear()
+            .extend_pairs(names.iter().map(|name| (name, "REDACTED")));
+    }
+    if url.password().is_some() {
+        // Only URLs that can't have a password fail to take one
+        let _ = url.set_password(Some("REDACTED"));
+    }
+    url.into()
+}
+
+/// Decodes the percent-encoding of each segment of a URL path. A segment that would decode to a
+/// path separator or a `.`/`..` traversal, or that isn't UTF-8, is left encoded.
+fn decode_url_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| match percent_decode_str(segment).decode_utf8() {
+            Ok(decoded) if !decoded.contains(['/', '\\']) && decoded != "." && decoded != ".." => {
+                decoded.into_owned()
+            }
+            _ => segment.to_owned(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
 /// Converts a file URL into a file path.
 /// Needed because `url.to_file_path()` will decode any percent encoding, which could restore path
-/// traversal characters, and `url.path()` roots paths to '/' on Windows.
+/// traversal characters, and `url.path()` roots paths to '/' on Windows. Percent-encoding is
+/// decoded one path segment at a time, leaving any segment encoded that would decode to a path
+/// traversal.
 pub trait SafeUrlPath {
     /// Returns the path component of a URL as a filesystem path.
     fn safe_url_filepath(&self) -> PathBuf;
@@ -16,18 +81,20 @@ impl SafeUrlPath for Url {
         let url_path = self.path();
 
         // Windows filepaths when written as `file://` URLs have path components prefixed with a /.
-        PathBuf::from(if let Some(stripped) = url_path.strip_prefix('/') {
-            stripped
-        } else {
-            url_path
-        })
+        PathBuf::from(decode_url_path(
+            if let Some(stripped) = url_path.strip_prefix('/') {
+                stripped
+            } else {
+                url_path
+            },
+        ))
     }
 }
 
 #[cfg(unix)]
 impl SafeUrlPath for Url {
     fn safe_url_filepath(&self) -> PathBuf {
-        PathBuf::from(self.path())
+        PathBuf::from(decode_url_path(self.path()))
     }
 }
 
@@ -53,6 +120,32 @@ mod test {
         assert!(safe_url_path.is_absolute());
     }
 
+    #[test]
+    fn test_encode_url_path() {
+        assert_eq!(encode_url_path("a b/ö.txt"), "a%20b/%C3%B6.txt");
+        assert_eq!(encode_url_path("50%#1?.txt"), "50%25%231%3F.txt");
+        assert_eq!(encode_url_path("file:x.txt"), "file%3Ax.txt");
+        assert_eq!(encode_url_path("..%2Frole.json"), "..%252Frole.json");
+    }
+
+    #[test]
+    fn test_safe_decoded() {
+        let url_base = Url::from_directory_path(manifest_dir())
+            .expect("Could not create URL from CARGO_MANIFEST_DIR");
+
+        for path in ["dir/a b.txt", "ᚩ os/🍺.txt", "50%#1?.txt", "..%2Frole.json"] {
+            let url = url_base.join(&encode_url_path(path)).unwrap();
+            assert_eq!(manifest_dir().join(path), url.safe_url_filepath());
+        }
+
+        // Encoded separators are not decoded
+        let url = url_base.join("a%2F..%2Fb/c").unwrap();
+        assert_eq!(
+            manifest_dir().join("a%2F..%2Fb").join("c"),
+            url.safe_url_filepath()
+        );
+    }
+
     #[test]
     fn test_safe_traversals() {
         let url_base = Url::from_directory_path(manifest_dir())
@@ -0,0 +1,124 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use chrono::{Duration, Utc};
+use olpc_cjson::CanonicalFormatter;
+use ring::rand::SystemRandom;
+use serde::Serialize;
+use std::fs::File;
+use std::num::NonZeroU64;
+use tempfile::TempDir;
+use test_utils::{dir_url, test_data, RepoBuilder};
+use tough::error::Error::VerifyMetadata;
+use tough::key_source::{KeySource, LocalKeySource};
+use tough::schema::Error::AlgorithmMismatch;
+use tough::schema::{PathPattern, PathSet};
+use tough::RepositoryLoader;
+
+mod test_utils;
+
+/// Test that a signature made with another listed key, of a different scheme than the key it
+/// names, is reported as such.
+#[test]
+fn delegated_role_signed_with_wrong_algorithm() {
+    // An RSA key
+    let rsa_key: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource {
+        path: test_data().join("snakeoil_2.pem"),
+    })];
+    // An Ed25519 key
+    let ed25519_key: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource {
+        path: test_data().join("targetskey"),
+    })];
+    let root_path = test_data().join("simple-rsa").join("root.json");
+    let expires = Utc::now().checked_add_signed(Duration::days(7)).unwrap();
+    let one = NonZeroU64::new(1).unwrap();
+    let paths = |pattern: &str| PathSet::Paths(vec![PathPattern::new(pattern).unwrap()]);
+    let mut builder = RepoBuilder::new().expires(expires);
+    builder
+        .editor()
+        .delegate_role("rsa-role", rsa_key, paths("a/*"), one, expires, one)
+        .unwrap()
+        .delegate_role("ed25519-role", ed25519_key, paths("b/*"), one, expires, one)
+        .unwrap();
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_dir = repo_dir.path().join("metadata");
+    builder.write(&metadata_dir);
+
+    // Replace the RSA signature on rsa-role with the Ed25519 key's, keeping the RSA key's ID
+    let role_path = metadata_dir.join("1.rsa-role.json");
+    let mut role: serde_json::Value =
+        serde_json::from_reader(File::open(&role_path).unwrap()).unwrap();
+    let mut data = Vec::new();
+    role["signed"]
+        .serialize(&mut serde_json::Serializer::with_formatter(
+            &mut data,
+            CanonicalFormatter::new(),
+        ))
+        .unwrap();
+    let sig = ed25519_key[0]
+        .as_sign()
+        .unwrap()
+        .sign(&data, &SystemRandom::new())
+        .unwrap();
+    role["signatures"][0]["sig"] = serde_json::Value::from(hex::encode(sig));
+    std::fs::write(&role_path, serde_json::to_vec(&role).unwrap()).unwrap();
+
+    let result = RepositoryLoader::new(
+        File::open(&root_path).unwrap(),
+        dir_url(&metadata_dir),
+        dir_url(repo_dir.path().join("targets")),
+    )
+    .load();
+    match result {
+        Err(VerifyMetadata {
+            source:
+                AlgorithmMismatch {
+                    role,
+                    expected,
+                    found,
+                    ..
+                },
+            ..
+        }) => {
+            assert_eq!(role, "rsa-role");
+            assert_eq!(expected, "rsassa-pss-sha256");
+            assert_eq!(found, "ed25519");
+        }
+        other => panic!("Expected an 'AlgorithmMismatch' error but received {other:?}"),
+    }
+}
+
+/// Test that a signature that no listed key made is reported as falling short of the threshold,
+/// since the algorithm it was made with can't be told.
+#[test]
+fn unknown_signature_is_not_algorithm_mismatch() {
+    let root_path = test_data().join("simple-rsa").join("root.json");
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_dir = repo_dir.path().join("metadata");
+    RepoBuilder::new().write(&metadata_dir);
+
+    // Replace the RSA signature on timestamp.json with one shaped like an Ed25519 signature
+    let timestamp_path = metadata_dir.join("timestamp.json");
+    let mut timestamp: serde_json::Value =
+        serde_json::from_reader(File::open(&timestamp_path).unwrap()).unwrap();
+    timestamp["signatures"][0]["sig"] = serde_json::Value::from("ab".repeat(64));
+    std::fs::write(&timestamp_path, serde_json::to_vec(&timestamp).unwrap()).unwrap();
+
+    let result = RepositoryLoader::new(
+        File::open(&root_path).unwrap(),
+        dir_url(&metadata_dir),
+        dir_url(repo_dir.path().join("targets")),
+    )
+    .load();
+    assert!(
+        matches!(
+            result,
+            Err(VerifyMetadata {
+                source: tough::schema::Error::SignatureThreshold { .. },
+                ..
+            })
+        ),
+        "unexpected result: {:?}",
+        result.map(|_| ())
+    );
+}
@@ -0,0 +1,105 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use test_utils::{dir_url, read_to_end, test_data, RepoBuilder};
+use tough::editor::signed::PathExists;
+use tough::schema::{HashAlgorithm, Target};
+use tough::{HashPolicy, Repository, RepositoryLoader, TargetName};
+
+mod test_utils;
+
+fn targets_path() -> PathBuf {
+    test_data().join("tuf-reference-impl").join("targets")
+}
+
+fn write(builder: RepoBuilder, outdir: &Path) {
+    builder
+        .write(outdir.join("metadata"))
+        .link_targets(targets_path(), outdir.join("targets"), PathExists::Skip)
+        .unwrap();
+}
+
+fn load(dir: &Path, hash_policy: HashPolicy) -> Repository {
+    RepositoryLoader::new(
+        File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        dir_url(dir.join("metadata")),
+        dir_url(dir.join("targets")),
+    )
+    .hash_policy(hash_policy)
+    .load()
+    .unwrap()
+}
+
+/// Test that targets added with BLAKE2b hashes record them, and that the hashes are verified
+/// when every declared hash must match.
+#[test]
+fn create_and_verify_blake2b_targets() {
+    let repo_dir = TempDir::new().unwrap();
+    let mut builder = RepoBuilder::new();
+    builder
+        .editor()
+        .target_hashes(&[HashAlgorithm::Blake2b])
+        .unwrap();
+    let builder = builder
+        .target_path(targets_path().join("file1.txt"))
+        .target_path(targets_path().join("file2.txt"));
+    write(builder, repo_dir.path());
+
+    let repo = load(repo_dir.path(), HashPolicy::All);
+    for name in ["file1.txt", "file2.txt"] {
+        let name = TargetName::new(name).unwrap();
+        let blake2b = repo.targets().signed.targets[&name].hashes._extra["blake2b"]
+            .as_str()
+            .unwrap();
+        // A BLAKE2b-512 digest, hex encoded
+        assert_eq!(hex::decode(blake2b).unwrap().len(), 64);
+        assert_eq!(
+            read_to_end(repo.read_target(&name).unwrap().unwrap()),
+            fs::read(targets_path().join(name.raw())).unwrap()
+        );
+    }
+}
+
+/// Test that a target whose BLAKE2b hash doesn't match fails to read when every declared hash
+/// must match, but reads when only sha256 is checked.
+#[test]
+fn blake2b_mismatch() {
+    let repo_dir = TempDir::new().unwrap();
+    let mut target =
+        Target::from_path_with_hashes(targets_path().join("file1.txt"), &[HashAlgorithm::Blake2b])
+            .unwrap();
+    let wrong = "0".repeat(128);
+    assert_ne!(target.hashes._extra["blake2b"], wrong.as_str());
+    target
+        .hashes
+        ._extra
+        .insert("blake2b".to_string(), serde_json::Value::from(wrong));
+    write(
+        RepoBuilder::new().target("file1.txt", target),
+        repo_dir.path(),
+    );
+
+    let file1 = TargetName::new("file1.txt").unwrap();
+    let repo = load(repo_dir.path(), HashPolicy::All);
+    let mut buf = Vec::new();
+    assert!(repo
+        .read_target(&file1)
+        .unwrap()
+        .unwrap()
+        .read_to_end(&mut buf)
+        .is_err());
+
+    let repo = load(repo_dir.path(), HashPolicy::Any);
+    assert!(!read_to_end(repo.read_target(&file1).unwrap().unwrap()).is_empty());
+}
+
+/// Test that `from_path` records only sha256.
+#[test]
+fn from_path_records_only_sha256() {
+    let target = Target::from_path(targets_path().join("file1.txt")).unwrap();
+    assert!(target.hashes._extra.is_empty());
+}
@@ -0,0 +1,105 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::File;
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::{dir_url, read_to_end, test_data};
+use tough::{
+    FilesystemTransport, RecordingTransport, ReplayTransport, Repository, RepositoryLoader,
+    TargetName,
+};
+
+mod test_utils;
+
+/// Copies the files in `from` to the directory `to`.
+fn copy_files(from: &Path, to: &Path) {
+    std::fs::create_dir_all(to).unwrap();
+    for entry in std::fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        std::fs::copy(entry.path(), to.join(entry.file_name())).unwrap();
+    }
+}
+
+fn load(repo_dir: &Path, transport: impl tough::Transport + Send + Sync + 'static) -> Repository {
+    RepositoryLoader::new(
+        File::open(test_data().join("tuf-reference-impl/metadata/1.root.json")).unwrap(),
+        dir_url(repo_dir.join("metadata")),
+        dir_url(repo_dir.join("targets")),
+    )
+    .transport(transport)
+    .load()
+    .unwrap()
+}
+
+/// Loads a copy of the reference implementation's repository and reads a delegated target through
+/// a `RecordingTransport`, then deletes the copy. Returns the copy's path and the cassette.
+fn record(cassette_dir: &Path) -> (std::path::PathBuf, std::path::PathBuf) {
+    let repo = TempDir::new().unwrap();
+    let base = test_data().join("tuf-reference-impl");
+    copy_files(&base.join("metadata"), &repo.path().join("metadata"));
+    copy_files(&base.join("targets"), &repo.path().join("targets"));
+
+    let recorder = RecordingTransport::new(FilesystemTransport);
+    let repository = load(repo.path(), recorder.clone());
+    let file3 = TargetName::new("file3.txt").unwrap();
+    assert_eq!(
+        read_to_end(repository.read_target(&file3).unwrap().unwrap()),
+        b"This is role1's target file."
+    );
+    let cassette = cassette_dir.join("cassette.json");
+    recorder.save(&cassette).unwrap();
+    (repo.path().to_owned(), cassette)
+}
+
+/// Test that a recorded load, including a delegated role and a target, replays without the
+/// repository's files.
+#[test]
+fn record_then_replay() {
+    let cassette_dir = TempDir::new().unwrap();
+    let (repo_dir, cassette) = record(cassette_dir.path());
+    assert!(!repo_dir.exists());
+
+    let replay = ReplayTransport::from_path(&cassette).unwrap();
+    let repository = load(&repo_dir, replay.clone());
+    assert!(repository.delegated_role("role2").is_some());
+    let file3 = TargetName::new("file3.txt").unwrap();
+    assert_eq!(
+        read_to_end(repository.read_target(&file3).unwrap().unwrap()),
+        b"This is role1's target file."
+    );
+    assert_eq!(replay.unrecorded_fetches(), 0);
+}
+
+/// Test that replayed bytes are verified as they were when recorded, so a cassette that was
+/// changed afterward fails to load.
+#[test]
+fn replay_tampered_cassette() {
+    let cassette_dir = TempDir::new().unwrap();
+    let (repo_dir, cassette) = record(cassette_dir.path());
+
+    // Change one byte of each recorded targets.json body
+    let mut recorded: serde_json::Value =
+        serde_json::from_reader(File::open(&cassette).unwrap()).unwrap();
+    for interaction in recorded["interactions"].as_array_mut().unwrap() {
+        if interaction["url"]
+            .as_str()
+            .unwrap()
+            .ends_with("/targets.json")
+        {
+            let body = interaction["body"].as_str().unwrap();
+            let flipped = if body.starts_with("7b") { "5b" } else { "7b" };
+            interaction["body"] = format!("{}{}", flipped, &body[2..]).into();
+        }
+    }
+    serde_json::to_writer(File::create(&cassette).unwrap(), &recorded).unwrap();
+
+    let result = RepositoryLoader::new(
+        File::open(test_data().join("tuf-reference-impl/metadata/1.root.json")).unwrap(),
+        dir_url(repo_dir.join("metadata")),
+        dir_url(repo_dir.join("targets")),
+    )
+    .transport(ReplayTransport::from_path(&cassette).unwrap())
+    .load();
+    assert!(result.is_err());
+}
@@ -0,0 +1,42 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use chrono::{TimeZone, Utc};
+use std::fs::File;
+use test_utils::{dir_url, read_to_end, test_data};
+use tough::error::Error::ExpiredMetadata;
+use tough::{FixedClock, RepositoryLoader, TargetName};
+
+mod test_utils;
+
+/// Loads the reference implementation repository, whose metadata expires at the start of 2030, as
+/// of the given year.
+fn load_in_year(year: i32) -> tough::error::Result<tough::Repository> {
+    let base = test_data().join("tuf-reference-impl");
+    RepositoryLoader::new(
+        File::open(base.join("metadata").join("1.root.json")).unwrap(),
+        dir_url(base.join("metadata")),
+        dir_url(base.join("targets")),
+    )
+    .clock(FixedClock(
+        Utc.with_ymd_and_hms(year, 6, 1, 0, 0, 0).unwrap(),
+    ))
+    .load()
+}
+
+/// Test that expiration is checked against the injected clock rather than the system clock.
+#[test]
+fn fixed_clock_expiration() {
+    // Reading a target checks expiration again, also against the injected clock
+    let repo = load_in_year(2029).unwrap();
+    let file1 = TargetName::new("file1.txt").unwrap();
+    assert_eq!(
+        read_to_end(repo.read_target(&file1).unwrap().unwrap()),
+        &b"This is an example target file."[..]
+    );
+
+    match load_in_year(2031) {
+        Err(ExpiredMetadata { .. }) => {}
+        other => panic!("Expected an 'ExpiredMetadata' error but received {other:?}"),
+    }
+}
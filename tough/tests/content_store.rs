@@ -0,0 +1,97 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use ring::digest::{digest, SHA256};
+use std::fs::{self, File};
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::{read_to_end, test_data, RepoBuilder};
+use tough::schema::{PathPattern, PathSet};
+use tough::{ContentStore, TargetName};
+
+mod test_utils;
+
+/// Writes a repository in which targets lists file1.txt and delegates role1, which lists
+/// file2.txt.
+fn write_repository(metadata_dir: &Path) {
+    let targets_dir = test_data().join("tuf-reference-impl").join("targets");
+    RepoBuilder::new()
+        .target_path(targets_dir.join("file1.txt"))
+        .delegate(
+            "role1",
+            PathSet::Paths(vec![PathPattern::new("file2.txt").unwrap()]),
+        )
+        .role_target_path("role1", targets_dir.join("file2.txt"))
+        .write(metadata_dir);
+}
+
+/// Stores each file in `dir` in `objects` under its sha256 digest, returning the digest of the
+/// file named `name`.
+fn store_files(dir: &Path, objects: &Path, name: &str) -> Option<Vec<u8>> {
+    let mut found = None;
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let contents = fs::read(&path).unwrap();
+        let sha256 = digest(&SHA256, &contents).as_ref().to_vec();
+        fs::write(objects.join(hex::encode(&sha256)), contents).unwrap();
+        if path.file_name().unwrap() == name {
+            found = Some(sha256);
+        }
+    }
+    found
+}
+
+/// Test that a repository, including a delegated role and its targets, loads from a store of
+/// objects named by their hashes.
+#[test]
+fn load_from_content_store() {
+    let metadata_dir = TempDir::new().unwrap();
+    write_repository(metadata_dir.path());
+    let store = TempDir::new().unwrap();
+    let objects = store.path();
+    let timestamp_sha256 = store_files(metadata_dir.path(), objects, "timestamp.json").unwrap();
+    let targets_dir = test_data().join("tuf-reference-impl").join("targets");
+    store_files(&targets_dir, objects, "");
+
+    let repo = ContentStore::new(objects, &timestamp_sha256)
+        .loader(File::open(test_data().join("simple-rsa").join("root.json")).unwrap())
+        .load()
+        .unwrap();
+    assert!(repo.delegated_role("role1").is_some());
+    for name in ["file1.txt", "file2.txt"] {
+        assert_eq!(
+            read_to_end(
+                repo.read_target(&TargetName::new(name).unwrap())
+                    .unwrap()
+                    .unwrap()
+            ),
+            fs::read(targets_dir.join(name)).unwrap()
+        );
+    }
+}
+
+/// Test that an object whose contents don't match its hash is rejected as usual.
+#[test]
+fn content_store_corrupt_object() {
+    let metadata_dir = TempDir::new().unwrap();
+    write_repository(metadata_dir.path());
+    let store = TempDir::new().unwrap();
+    let objects = store.path();
+    let timestamp_sha256 = store_files(metadata_dir.path(), objects, "timestamp.json").unwrap();
+    let targets_dir = test_data().join("tuf-reference-impl").join("targets");
+    let file1_sha256 = store_files(&targets_dir, objects, "file1.txt").unwrap();
+    let file1_path = objects.join(hex::encode(file1_sha256));
+    let mut contents = fs::read(&file1_path).unwrap();
+    contents[0] ^= 1;
+    fs::write(&file1_path, contents).unwrap();
+
+    let repo = ContentStore::new(objects, &timestamp_sha256)
+        .loader(File::open(test_data().join("simple-rsa").join("root.json")).unwrap())
+        .load()
+        .unwrap();
+    let mut reader = repo
+        .read_target(&TargetName::new("file1.txt").unwrap())
+        .unwrap()
+        .unwrap();
+    assert!(std::io::Read::read_to_end(&mut reader, &mut Vec::new()).is_err());
+}
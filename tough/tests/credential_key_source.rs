@@ -0,0 +1,61 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use test_utils::test_data;
+use tough::error::Error;
+use tough::key_source::{KeySource, KeySourceRegistry, LocalKeySource, CREDENTIALS_DIRECTORY};
+
+mod test_utils;
+
+/// Returns the `tough` error behind a key source error.
+fn tough_error(err: Box<dyn std::error::Error + Send + Sync>) -> Error {
+    *err.downcast::<Error>().unwrap()
+}
+
+/// Test that a `cred:` key source reads its key from the credentials directory, and fails clearly
+/// when the credential or the directory is missing. The environment is shared by every test in
+/// this file, so these checks run in one test.
+#[test]
+fn credential_key_source() {
+    let registry = KeySourceRegistry::new();
+    let expected = LocalKeySource {
+        path: test_data().join("snakeoil.pem"),
+    }
+    .as_sign()
+    .unwrap()
+    .tuf_key()
+    .key_id()
+    .unwrap();
+
+    std::env::set_var(CREDENTIALS_DIRECTORY, test_data());
+    let source = registry.resolve("cred:snakeoil.pem").unwrap();
+    assert_eq!(
+        source.as_sign().unwrap().tuf_key().key_id().unwrap(),
+        expected
+    );
+    assert!(matches!(
+        tough_error(source.write("key", "").unwrap_err()),
+        Error::CredentialReadOnly { .. }
+    ));
+
+    let missing = registry.resolve("cred:missing-key").unwrap();
+    match tough_error(missing.as_sign().unwrap_err()) {
+        Error::CredentialNotFound {
+            name, directory, ..
+        } => {
+            assert_eq!(name, "missing-key");
+            assert_eq!(directory, test_data());
+        }
+        other => panic!("Expected a 'CredentialNotFound' error but received {other:?}"),
+    }
+    assert!(matches!(
+        tough_error(registry.resolve("cred:..").unwrap().as_sign().unwrap_err()),
+        Error::CredentialName { .. }
+    ));
+
+    std::env::remove_var(CREDENTIALS_DIRECTORY);
+    assert!(matches!(
+        tough_error(source.as_sign().unwrap_err()),
+        Error::CredentialsDirectoryUnset { .. }
+    ));
+}
@@ -0,0 +1,82 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::{self, File};
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::{dir_url, test_data, RepoBuilder};
+use tough::error::Error;
+use tough::schema::{PathPattern, PathSet};
+use tough::{Repository, RepositoryLoader};
+
+mod test_utils;
+
+/// Writes a repository in which targets delegates role1, which delegates role2, and snapshot
+/// lists the length and hash of each targets metadata file.
+fn write_nested_repository(metadata_dir: &Path) {
+    let paths = || PathSet::Paths(vec![PathPattern::new("file?.txt").unwrap()]);
+    RepoBuilder::new()
+        .delegate("role1", paths())
+        .delegate_from("role1", "role2", paths())
+        .role_target_path(
+            "role2",
+            test_data()
+                .join("tuf-reference-impl")
+                .join("targets")
+                .join("file1.txt"),
+        )
+        .write(metadata_dir);
+}
+
+fn load(metadata_dir: &Path) -> Result<Repository, Error> {
+    RepositoryLoader::new(
+        File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        dir_url(metadata_dir),
+        dir_url(test_data().join("tuf-reference-impl").join("targets")),
+    )
+    .load()
+}
+
+/// Test that a repository with nested delegations loads when every role matches snapshot.
+#[test]
+fn nested_delegations_match_snapshot() {
+    let metadata_dir = TempDir::new().unwrap();
+    write_nested_repository(metadata_dir.path());
+    let repo = load(metadata_dir.path()).unwrap();
+    assert!(repo.delegated_role("role2").is_some());
+}
+
+/// Test that a role delegated by another delegated role is checked against the hash snapshot
+/// lists for it, even when its signatures are still valid.
+#[test]
+fn nested_delegated_hash_mismatch() {
+    let metadata_dir = TempDir::new().unwrap();
+    write_nested_repository(metadata_dir.path());
+
+    // Swap one byte of indentation for a tab, which changes the file's hash but not its length
+    let role2_path = metadata_dir.path().join("1.role2.json");
+    let role2 = fs::read_to_string(&role2_path).unwrap();
+    fs::write(&role2_path, role2.replacen("  ", " \t", 1)).unwrap();
+
+    match load(metadata_dir.path()) {
+        Err(Error::DelegatedHashMismatch { role, .. }) => assert_eq!(role, "role2"),
+        other => panic!("Expected a 'DelegatedHashMismatch' error but received {other:?}"),
+    }
+}
+
+/// Test that delegated role metadata longer than the length snapshot lists for it is rejected.
+#[test]
+fn delegated_length_mismatch() {
+    let metadata_dir = TempDir::new().unwrap();
+    write_nested_repository(metadata_dir.path());
+
+    let role1_path = metadata_dir.path().join("1.role1.json");
+    let mut role1 = fs::read(&role1_path).unwrap();
+    role1.push(b'\n');
+    fs::write(&role1_path, role1).unwrap();
+
+    match load(metadata_dir.path()) {
+        Err(Error::DelegatedLengthMismatch { role, .. }) => assert_eq!(role, "role1"),
+        other => panic!("Expected a 'DelegatedLengthMismatch' error but received {other:?}"),
+    }
+}
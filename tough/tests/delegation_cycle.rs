@@ -0,0 +1,114 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use chrono::{Duration, Utc};
+use std::collections::HashMap;
+use std::fs::File;
+use std::num::NonZeroU64;
+use tempfile::TempDir;
+use test_utils::{dir_url, test_data, RepoBuilder};
+use tough::editor::targets::TargetsEditor;
+use tough::editor::RepositoryEditor;
+use tough::key_source::{KeySource, LocalKeySource};
+use tough::schema::{PathPattern, PathSet};
+use tough::RepositoryLoader;
+
+mod test_utils;
+
+/// Test that delegating a role back to itself, or to a role that delegates it, is rejected and
+/// the error names the cycle.
+#[test]
+fn reject_delegation_cycle() {
+    let expires = Utc::now().checked_add_signed(Duration::days(7)).unwrap();
+    let one = NonZeroU64::new(1).unwrap();
+    let root = test_data().join("simple-rsa").join("root.json");
+    let key: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource {
+        path: test_data().join("snakeoil.pem"),
+    })];
+    let paths = || PathSet::Paths(vec![PathPattern::new("*").unwrap()]);
+
+    // targets -> A -> B
+    let mut editor = RepositoryEditor::new(&root).unwrap();
+    editor
+        .targets_version(one)
+        .unwrap()
+        .targets_expires(expires)
+        .unwrap()
+        .delegate_role("A", key, paths(), one, expires, one)
+        .unwrap()
+        .sign_targets_editor(key)
+        .unwrap()
+        .change_delegated_targets("A")
+        .unwrap()
+        .delegate_role("B", key, paths(), one, expires, one)
+        .unwrap()
+        .sign_targets_editor(key)
+        .unwrap()
+        .change_delegated_targets("B")
+        .unwrap();
+
+    for (role, cycle) in [
+        ("A", "A -> B -> A"),
+        ("B", "B -> B"),
+        ("targets", "targets -> A -> B -> targets"),
+    ] {
+        let error = editor
+            .delegate_role(role, key, paths(), one, expires, one)
+            .unwrap_err();
+        assert!(
+            error.to_string().ends_with(cycle),
+            "unexpected error: {}",
+            error
+        );
+    }
+
+    // A role that isn't already in the chain can still be delegated
+    editor
+        .delegate_role("C", key, paths(), one, expires, one)
+        .unwrap();
+}
+
+/// Test that an editor built with `TargetsEditor::from_repo` knows the roles that delegate to the
+/// role it edits, so delegating one of them back is rejected.
+#[test]
+fn reject_delegation_cycle_from_repo() {
+    let expires = Utc::now().checked_add_signed(Duration::days(7)).unwrap();
+    let one = NonZeroU64::new(1).unwrap();
+    let root = test_data().join("simple-rsa").join("root.json");
+    let key: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource {
+        path: test_data().join("snakeoil.pem"),
+    })];
+    let paths = || PathSet::Paths(vec![PathPattern::new("*").unwrap()]);
+
+    // targets -> A -> B
+    let repo_dir = TempDir::new().unwrap();
+    RepoBuilder::new()
+        .expires(expires)
+        .delegate("A", paths())
+        .delegate_from("A", "B", paths())
+        .write(repo_dir.path());
+    let repo = RepositoryLoader::new(
+        File::open(&root).unwrap(),
+        dir_url(repo_dir.path()),
+        dir_url(repo_dir.path().join("targets")),
+    )
+    .load()
+    .unwrap();
+
+    let role_a = TargetsEditor::new("A")
+        .version(one)
+        .expires(expires)
+        .create_signed(key)
+        .unwrap();
+    let keyid = key[0].as_sign().unwrap().tuf_key().key_id().unwrap();
+    let key_pairs = HashMap::from([(keyid.clone(), key[0].as_sign().unwrap().tuf_key())]);
+    let error = TargetsEditor::from_repo(repo, "B")
+        .unwrap()
+        .delegate_role(role_a, paths(), key_pairs, vec![keyid], one)
+        .unwrap_err();
+    assert!(
+        error.to_string().ends_with("A -> B -> A"),
+        "unexpected error: {}",
+        error
+    );
+}
@@ -0,0 +1,91 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::File;
+use std::io::Read;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tempfile::TempDir;
+use test_utils::{dir_url, test_data, RepoBuilder};
+use tough::schema::{PathPattern, PathSet};
+use tough::{FilesystemTransport, RepositoryLoader, Transport, TransportError};
+use url::Url;
+
+mod test_utils;
+
+const ROLE_COUNT: usize = 20;
+
+/// A `Transport` that records the largest number of fetches that were in flight at once.
+#[derive(Debug, Clone, Default)]
+struct CountingTransport {
+    in_flight: Arc<AtomicUsize>,
+    max_in_flight: Arc<AtomicUsize>,
+}
+
+impl Transport for CountingTransport {
+    fn fetch(&self, url: Url) -> Result<Box<dyn Read + Send>, TransportError> {
+        let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+        // Hold the fetch open long enough for the other workers to start theirs
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let result = FilesystemTransport.fetch(url);
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        result
+    }
+}
+
+/// Creates a repository where the top-level targets role delegates to `ROLE_COUNT` roles.
+fn create_repo(outdir: &Path) {
+    (0..ROLE_COUNT)
+        .fold(RepoBuilder::new(), |builder, i| {
+            builder.delegate(
+                &format!("role{i}"),
+                PathSet::Paths(vec![PathPattern::new(format!("role{i}/*")).unwrap()]),
+            )
+        })
+        .write(outdir.join("metadata"));
+}
+
+/// Loads the repository in `dir` and returns the largest number of concurrent fetches.
+fn max_in_flight(dir: &Path, concurrency: usize) -> usize {
+    let transport = CountingTransport::default();
+    let repo = RepositoryLoader::new(
+        File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        dir_url(dir.join("metadata")),
+        dir_url(dir.join("targets")),
+    )
+    .transport(transport.clone())
+    .delegation_fetch_concurrency(NonZeroUsize::new(concurrency).unwrap())
+    .load()
+    .unwrap();
+
+    // Every delegated role was still fetched and verified
+    for i in 0..ROLE_COUNT {
+        assert!(repo
+            .delegated_role(&format!("role{i}"))
+            .unwrap()
+            .targets
+            .is_some());
+    }
+    transport.max_in_flight.load(Ordering::SeqCst)
+}
+
+/// Test that delegated role fetches never exceed the configured concurrency.
+#[test]
+fn delegation_fetches_bounded() {
+    let repo_dir = TempDir::new().unwrap();
+    create_repo(repo_dir.path());
+
+    assert!(max_in_flight(repo_dir.path(), 4) <= 4);
+}
+
+/// Test that a concurrency of one fetches delegated roles serially.
+#[test]
+fn delegation_fetches_serial() {
+    let repo_dir = TempDir::new().unwrap();
+    create_repo(repo_dir.path());
+
+    assert_eq!(max_in_flight(repo_dir.path(), 1), 1);
+}
@@ -0,0 +1,91 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use chrono::{Duration, Utc};
+use std::num::NonZeroU64;
+use test_utils::test_data;
+use tough::schema::{DelegatedRole, PathPattern, PathSet, Signed, Target, Targets};
+use tough::TargetName;
+
+mod test_utils;
+
+fn targets(names: &[&str]) -> Targets {
+    let mut targets = Targets::new(
+        "1.0.0".to_string(),
+        NonZeroU64::new(1).unwrap(),
+        Utc::now().checked_add_signed(Duration::days(7)).unwrap(),
+    );
+    let target = Target::from_path(
+        test_data()
+            .join("tuf-reference-impl")
+            .join("targets")
+            .join("file1.txt"),
+    )
+    .unwrap();
+    for name in names {
+        targets
+            .targets
+            .insert(TargetName::new(*name).unwrap(), target.clone());
+    }
+    targets
+}
+
+fn delegated_role(name: &str, path: &str, terminating: bool, listed: &[&str]) -> DelegatedRole {
+    DelegatedRole {
+        name: name.to_string(),
+        keyids: Vec::new(),
+        threshold: NonZeroU64::new(1).unwrap(),
+        paths: PathSet::Paths(vec![PathPattern::new(path).unwrap()]),
+        terminating,
+        targets: Some(Signed {
+            signed: targets(listed),
+            signatures: Vec::new(),
+        }),
+    }
+}
+
+/// Test that a target name listed by more than one consulted role is reported with those roles.
+#[test]
+fn duplicate_targets_reported() {
+    let mut top = targets(&["file1.txt", "file2.txt"]);
+    let delegations = top.delegations.as_mut().unwrap();
+    delegations
+        .roles
+        .push(delegated_role("role1", "*", false, &["file1.txt", "a.txt"]));
+    delegations
+        .roles
+        .push(delegated_role("role2", "a.txt", false, &["a.txt"]));
+    // role3 is not delegated "file2.txt", so it is never consulted for it
+    delegations
+        .roles
+        .push(delegated_role("role3", "b.txt", false, &["file2.txt"]));
+
+    assert_eq!(
+        top.duplicate_targets(),
+        vec![
+            (
+                TargetName::new("a.txt").unwrap(),
+                vec!["role1".to_string(), "role2".to_string()]
+            ),
+            (
+                TargetName::new("file1.txt").unwrap(),
+                vec!["targets".to_string(), "role1".to_string()]
+            ),
+        ]
+    );
+}
+
+/// Test that roles past a matching terminating delegation are not reported.
+#[test]
+fn duplicate_targets_terminating() {
+    let mut top = targets(&[]);
+    let delegations = top.delegations.as_mut().unwrap();
+    delegations
+        .roles
+        .push(delegated_role("role1", "*", true, &["a.txt"]));
+    delegations
+        .roles
+        .push(delegated_role("role2", "*", false, &["a.txt"]));
+
+    assert!(top.duplicate_targets().is_empty());
+}
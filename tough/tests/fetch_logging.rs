@@ -0,0 +1,154 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::fs::File;
+use std::sync::Mutex;
+use test_utils::{dir_url, read_to_end, test_data};
+use tough::{ExpirationEnforcement, RepositoryLoader, TargetName, TransportError, UrlSigner};
+use url::Url;
+
+mod test_utils;
+
+/// A logger that keeps every debug message logged by tough.
+struct CaptureLogger {
+    messages: Mutex<Vec<String>>,
+}
+
+impl Log for CaptureLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= Level::Debug && metadata.target().starts_with("tough")
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if self.enabled(record.metadata()) {
+            self.messages
+                .lock()
+                .unwrap()
+                .push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CaptureLogger = CaptureLogger {
+    messages: Mutex::new(Vec::new()),
+};
+
+/// A `UrlSigner` that appends a secret to each URL.
+#[derive(Debug)]
+struct SecretSigner;
+
+impl UrlSigner for SecretSigner {
+    fn sign_url(&self, mut url: Url) -> Result<Url, TransportError> {
+        url.query_pairs_mut().append_pair("signature", "hunter2");
+        Ok(url)
+    }
+}
+
+/// Test that each fetch of a load is logged with its URL and byte count, and that secrets in
+/// signed URLs are redacted, including from the debug messages of the HTTP transport.
+#[test]
+fn fetches_are_logged() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(LevelFilter::Debug);
+
+    let base = test_data().join("tuf-reference-impl");
+    let repo = RepositoryLoader::new(
+        File::open(base.join("metadata").join("1.root.json")).unwrap(),
+        dir_url(base.join("metadata")),
+        dir_url(base.join("targets")),
+    )
+    .expiration_enforcement(ExpirationEnforcement::Unsafe)
+    .target_url_signer(SecretSigner)
+    .load()
+    .unwrap();
+    read_to_end(
+        repo.read_target(&TargetName::new("file1.txt").unwrap())
+            .unwrap()
+            .unwrap(),
+    );
+
+    let messages = LOGGER.messages.lock().unwrap();
+    let timestamp_len = std::fs::metadata(base.join("metadata").join("timestamp.json"))
+        .unwrap()
+        .len();
+    let timestamp_url = dir_url(base.join("metadata"))
+        .join("timestamp.json")
+        .unwrap();
+    assert!(
+        messages.iter().any(|message| message.starts_with(&format!(
+            "fetched '{timestamp_url}': {timestamp_len} bytes in "
+        ))),
+        "{messages:#?}"
+    );
+    // Probing for a newer root fails, as there is none
+    assert!(
+        messages
+            .iter()
+            .any(|message| message.contains("2.root.json' failed after")
+                && message.ends_with("file not found")),
+        "{messages:#?}"
+    );
+    let file1_url = dir_url(base.join("targets")).join("file1.txt").unwrap();
+    assert!(
+        messages.iter().any(|message| message.starts_with(&format!(
+            "fetched '{file1_url}?signature=REDACTED': 31 bytes in "
+        ))),
+        "{messages:#?}"
+    );
+    drop(messages);
+
+    #[cfg(feature = "http")]
+    fetch_signed_over_http();
+    let messages = LOGGER.messages.lock().unwrap();
+    assert!(messages.iter().all(|message| !message.contains("hunter2")));
+}
+
+/// Fetches signed URLs with an `HttpTransport` from a server that answers a repeat fetch of
+/// timestamp.json with `304 Not Modified`, and fails to serve a target, so that the transport
+/// logs both at debug level.
+#[cfg(feature = "http")]
+fn fetch_signed_over_http() {
+    use httptest::{cycle, matchers::*, responders::*, Expectation, Server};
+    use tough::{HttpTransportBuilder, Transport};
+
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(request::method_path("GET", "/metadata/timestamp.json"))
+            .times(2)
+            .respond_with(cycle![
+                status_code(200).append_header("ETag", "\"1\"").body("{}"),
+                status_code(304),
+            ]),
+    );
+    server.expect(
+        Expectation::matching(request::method_path("GET", "/targets/file1.txt"))
+            .times(1)
+            .respond_with(status_code(500)),
+    );
+    let signed = |path: &str| {
+        SecretSigner
+            .sign_url(Url::parse(&server.url_str(path)).unwrap())
+            .unwrap()
+    };
+    let transport = HttpTransportBuilder::new().tries(1).build();
+    for _ in 0..2 {
+        read_to_end(transport.fetch(signed("/metadata/timestamp.json")).unwrap());
+    }
+    assert!(transport.fetch(signed("/targets/file1.txt")).is_err());
+
+    let messages = LOGGER.messages.lock().unwrap();
+    assert!(
+        messages.iter().any(|message| message
+            .contains("/metadata/timestamp.json?signature=REDACTED' is not modified")),
+        "{messages:#?}"
+    );
+    assert!(
+        messages
+            .iter()
+            .any(|message| message.contains("/targets/file1.txt?signature=REDACTED', no more")),
+        "{messages:#?}"
+    );
+}
@@ -0,0 +1,64 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::File;
+use std::num::NonZeroU64;
+use test_utils::{dir_url, test_data};
+use tough::{Repository, RepositoryLoader, TargetName};
+use url::Url;
+
+mod test_utils;
+
+fn load() -> (Repository, Url, Url) {
+    let base = test_data().join("tuf-reference-impl");
+    let metadata_url = dir_url(base.join("metadata"));
+    let targets_url = dir_url(base.join("targets"));
+    let repo = RepositoryLoader::new(
+        File::open(base.join("metadata").join("1.root.json")).unwrap(),
+        metadata_url.clone(),
+        targets_url.clone(),
+    )
+    .load()
+    .unwrap();
+    (repo, metadata_url, targets_url)
+}
+
+/// Test that installing a target listed by a delegated role fetches the top-level metadata, the
+/// delegated role's metadata, and the target, but not the roles that role delegates to.
+#[test]
+fn fetch_plan_delegated_target() {
+    let (repo, metadata_url, targets_url) = load();
+    let one = NonZeroU64::new(1).unwrap();
+    let plan = repo
+        .fetch_plan(one, &TargetName::new("file3.txt").unwrap())
+        .unwrap();
+    assert_eq!(
+        plan,
+        vec![
+            metadata_url.join("timestamp.json").unwrap(),
+            metadata_url.join("snapshot.json").unwrap(),
+            metadata_url.join("targets.json").unwrap(),
+            metadata_url.join("role1.json").unwrap(),
+            targets_url.join("file3.txt").unwrap(),
+        ]
+    );
+
+    // A target listed by the top-level targets role needs no delegated metadata
+    let plan = repo
+        .fetch_plan(one, &TargetName::new("file1.txt").unwrap())
+        .unwrap();
+    assert_eq!(plan.len(), 4);
+    assert_eq!(plan[3], targets_url.join("file1.txt").unwrap());
+}
+
+/// Test that a target the repository doesn't list has no fetch plan.
+#[test]
+fn fetch_plan_missing_target() {
+    let (repo, _, _) = load();
+    assert!(repo
+        .fetch_plan(
+            NonZeroU64::new(1).unwrap(),
+            &TargetName::new("missing.txt").unwrap()
+        )
+        .is_err());
+}
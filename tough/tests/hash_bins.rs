@@ -0,0 +1,100 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use chrono::{DateTime, Duration, Utc};
+use std::fs::{self, File};
+use std::num::NonZeroU64;
+use std::path::PathBuf;
+use tempfile::TempDir;
+use test_utils::{dir_url, read_to_end, role_key, test_data, RepoBuilder};
+use tough::editor::signed::PathExists;
+use tough::schema::{PathPattern, PathSet};
+use tough::{RepositoryLoader, TargetName};
+
+mod test_utils;
+
+fn targets_path() -> PathBuf {
+    test_data().join("tuf-reference-impl").join("targets")
+}
+
+fn paths(patterns: &[&str]) -> PathSet {
+    PathSet::Paths(
+        patterns
+            .iter()
+            .map(|pattern| PathPattern::new(*pattern).unwrap())
+            .collect(),
+    )
+}
+
+/// Starts a repository in which targets delegates role1, which lists file1.txt and file2.txt,
+/// and role2, which lists file3.txt, by explicit paths.
+fn repo_builder(expires: DateTime<Utc>) -> RepoBuilder {
+    RepoBuilder::new()
+        .expires(expires)
+        .delegate("role1", paths(&["file1.txt", "file2.txt"]))
+        .delegate("role2", paths(&["file3.txt"]))
+        .role_target_path("role1", targets_path().join("file1.txt"))
+        .role_target_path("role1", targets_path().join("file2.txt"))
+        .role_target_path("role2", targets_path().join("file3.txt"))
+}
+
+/// Test that explicit delegations migrated to hashed bins are replaced by the bins, and that every
+/// target still resolves and reads.
+#[test]
+fn migrate_to_hash_bins() {
+    let expires = Utc::now().checked_add_signed(Duration::days(7)).unwrap();
+    let one = NonZeroU64::new(1).unwrap();
+    let mut builder = repo_builder(expires);
+    builder
+        .editor()
+        .delegate_to_hash_bins(2, "bin", &role_key(), one, expires, one)
+        .unwrap();
+
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_dir = repo_dir.path().join("metadata");
+    let targets_dir = repo_dir.path().join("targets");
+    builder
+        .write(&metadata_dir)
+        .link_targets(targets_path(), &targets_dir, PathExists::Skip)
+        .unwrap();
+
+    let repo = RepositoryLoader::new(
+        File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        dir_url(&metadata_dir),
+        dir_url(&targets_dir),
+    )
+    .load()
+    .unwrap();
+    assert!(repo.delegated_role("role1").is_none());
+    assert!(repo.delegated_role("role2").is_none());
+    for bin in ["bin-0", "bin-1", "bin-2", "bin-3"] {
+        assert!(repo.delegated_role(bin).is_some(), "missing {bin}");
+    }
+    for name in ["file1.txt", "file2.txt", "file3.txt"] {
+        assert_eq!(
+            read_to_end(
+                repo.read_target(&TargetName::new(name).unwrap())
+                    .unwrap()
+                    .unwrap()
+            ),
+            fs::read(targets_path().join(name)).unwrap()
+        );
+    }
+}
+
+/// Test that an unusable bit length is rejected.
+#[test]
+fn hash_bins_bad_bit_length() {
+    let expires = Utc::now().checked_add_signed(Duration::days(7)).unwrap();
+    let one = NonZeroU64::new(1).unwrap();
+    let mut builder = repo_builder(expires);
+    let editor = builder.editor();
+    assert!(editor
+        .delegate_to_hash_bins(0, "bin", &role_key(), one, expires, one)
+        .is_err());
+    assert!(editor
+        .delegate_to_hash_bins(17, "bin", &role_key(), one, expires, one)
+        .is_err());
+    // The explicit delegations are intact, so the repository still signs
+    builder.sign();
+}
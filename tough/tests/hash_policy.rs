@@ -0,0 +1,95 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use ring::digest::{digest, SHA512};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use test_utils::{dir_url, read_to_end, test_data, RepoBuilder};
+use tough::editor::signed::PathExists;
+use tough::schema::Target;
+use tough::{HashPolicy, Repository, RepositoryLoader, TargetName};
+
+mod test_utils;
+
+fn targets_path() -> PathBuf {
+    test_data().join("tuf-reference-impl").join("targets")
+}
+
+/// Creates a repository whose only target, file1.txt, declares the given sha512 hash in addition
+/// to its (correct) sha256 hash.
+fn create_repo(outdir: &Path, sha512: &str) {
+    let mut target = Target::from_path(targets_path().join("file1.txt")).unwrap();
+    target
+        .hashes
+        ._extra
+        .insert("sha512".to_string(), serde_json::Value::from(sha512));
+
+    RepoBuilder::new()
+        .target("file1.txt", target)
+        .write(outdir.join("metadata"))
+        .link_targets(targets_path(), outdir.join("targets"), PathExists::Skip)
+        .unwrap();
+}
+
+fn load_repo(dir: &Path, hash_policy: HashPolicy) -> Repository {
+    RepositoryLoader::new(
+        File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        dir_url(dir.join("metadata")),
+        dir_url(dir.join("targets")),
+    )
+    .hash_policy(hash_policy)
+    .load()
+    .unwrap()
+}
+
+fn file1_sha512() -> String {
+    let contents = std::fs::read(targets_path().join("file1.txt")).unwrap();
+    hex::encode(digest(&SHA512, &contents))
+}
+
+/// A mismatched sha512 is ignored when any hash may match.
+#[test]
+fn sha512_mismatch_policy_any() {
+    let repo_dir = TempDir::new().unwrap();
+    create_repo(repo_dir.path(), &"0".repeat(128));
+    let repo = load_repo(repo_dir.path(), HashPolicy::Any);
+
+    let file1 = TargetName::new("file1.txt").unwrap();
+    assert_eq!(
+        read_to_end(repo.read_target(&file1).unwrap().unwrap()),
+        &b"This is an example target file."[..]
+    );
+}
+
+/// A mismatched sha512 fails the read when every hash must match.
+#[test]
+fn sha512_mismatch_policy_all() {
+    let repo_dir = TempDir::new().unwrap();
+    create_repo(repo_dir.path(), &"0".repeat(128));
+    let repo = load_repo(repo_dir.path(), HashPolicy::All);
+
+    let file1 = TargetName::new("file1.txt").unwrap();
+    let mut buf = Vec::new();
+    assert!(repo
+        .read_target(&file1)
+        .unwrap()
+        .unwrap()
+        .read_to_end(&mut buf)
+        .is_err());
+}
+
+/// A matching sha512 passes when every hash must match.
+#[test]
+fn sha512_match_policy_all() {
+    let repo_dir = TempDir::new().unwrap();
+    create_repo(repo_dir.path(), &file1_sha512());
+    let repo = load_repo(repo_dir.path(), HashPolicy::All);
+
+    let file1 = TargetName::new("file1.txt").unwrap();
+    assert_eq!(
+        read_to_end(repo.read_target(&file1).unwrap().unwrap()),
+        &b"This is an example target file."[..]
+    );
+}
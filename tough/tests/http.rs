@@ -4,10 +4,16 @@ mod test_utils;
 #[cfg(feature = "http")]
 mod http_happy {
     use crate::test_utils::{read_to_end, test_data};
-    use httptest::{matchers::*, responders::*, Expectation, Server};
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use httptest::{all_of, matchers::*, responders::*, Expectation, Server};
     use std::fs::File;
+    use std::io::Write;
     use std::str::FromStr;
-    use tough::{DefaultTransport, HttpTransport, RepositoryLoader, TargetName, Transport};
+    use tough::{
+        DefaultTransport, HttpTransport, HttpTransportBuilder, RepositoryLoader, TargetName,
+        Transport,
+    };
     use url::Url;
 
     /// Set an expectation in a test HTTP server which serves a file from `tuf-reference-impl`.
@@ -23,6 +29,24 @@ mod http_happy {
             )
     }
 
+    /// Set an expectation in a test HTTP server which serves a gzip-compressed file from
+    /// `tuf-reference-impl` with `Content-Encoding: gzip`.
+    fn create_gzip_get(relative_path: &str) -> httptest::Expectation {
+        let repo_dir = test_data().join("tuf-reference-impl");
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&std::fs::read(repo_dir.join(relative_path)).unwrap())
+            .unwrap();
+        Expectation::matching(request::method_path("GET", format!("/{}", relative_path)))
+            .times(1)
+            .respond_with(
+                status_code(200)
+                    .append_header("content-type", "application/json")
+                    .append_header("content-encoding", "gzip")
+                    .body(encoder.finish().unwrap()),
+            )
+    }
+
     /// Set an expectation in a test HTTP server to return a `403 Forbidden` status code.
     /// This is necessary for objects like `x.root.json` as tough will continue to increment
     /// `x.root.json` until it receives either `403 Forbidden` or `404 NotFound`.
@@ -89,6 +113,155 @@ mod http_happy {
             "0644"
         );
     }
+
+    /// Test that gzip-encoded metadata and targets are decompressed before they are verified.
+    #[test]
+    fn test_http_gzip_content_encoding() {
+        let server = Server::run();
+        let repo_dir = test_data().join("tuf-reference-impl");
+        server.expect(create_gzip_get("metadata/timestamp.json"));
+        server.expect(create_gzip_get("metadata/snapshot.json"));
+        server.expect(create_gzip_get("metadata/targets.json"));
+        server.expect(create_gzip_get("metadata/role1.json"));
+        server.expect(create_successful_get("metadata/role2.json"));
+        server.expect(create_gzip_get("targets/file1.txt"));
+        server.expect(create_unsuccessful_get("metadata/2.root.json"));
+        let metadata_base_url = Url::from_str(server.url_str("/metadata").as_str()).unwrap();
+        let targets_base_url = Url::from_str(server.url_str("/targets").as_str()).unwrap();
+        let repo = RepositoryLoader::new(
+            File::open(repo_dir.join("metadata").join("1.root.json")).unwrap(),
+            metadata_base_url,
+            targets_base_url,
+        )
+        .transport(HttpTransport::default())
+        .load()
+        .unwrap();
+
+        assert!(repo.delegated_role("role2").is_some());
+        let file1 = TargetName::new("file1.txt").unwrap();
+        assert_eq!(
+            read_to_end(repo.read_target(&file1).unwrap().unwrap()),
+            &b"This is an example target file."[..]
+        );
+    }
+
+    /// Test that a repeat fetch of `timestamp.json` is conditional on the `ETag` of the first,
+    /// and that a `304 Not Modified` response is served from the previously fetched bytes.
+    #[test]
+    fn test_http_timestamp_not_modified() {
+        let server = Server::run();
+        let repo_dir = test_data().join("tuf-reference-impl");
+        let timestamp_bytes = std::fs::read(repo_dir.join("metadata/timestamp.json")).unwrap();
+        server.expect(
+            Expectation::matching(all_of![
+                request::method_path("GET", "/metadata/timestamp.json"),
+                not(request::headers(contains(key("if-none-match")))),
+            ])
+            .times(1)
+            .respond_with(
+                status_code(200)
+                    .append_header("etag", "\"timestamp-1\"")
+                    .body(timestamp_bytes),
+            ),
+        );
+        server.expect(
+            Expectation::matching(all_of![
+                request::method_path("GET", "/metadata/timestamp.json"),
+                request::headers(contains(("if-none-match", "\"timestamp-1\""))),
+            ])
+            .times(1)
+            .respond_with(status_code(304)),
+        );
+        server.expect(create_successful_get("metadata/snapshot.json"));
+        server.expect(create_successful_get("metadata/targets.json"));
+        server.expect(create_successful_get("metadata/role1.json"));
+        server.expect(create_successful_get("metadata/role2.json"));
+        server.expect(create_unsuccessful_get("metadata/2.root.json"));
+        let metadata_base_url = Url::from_str(server.url_str("/metadata").as_str()).unwrap();
+        let targets_base_url = Url::from_str(server.url_str("/targets").as_str()).unwrap();
+        let repo = RepositoryLoader::new(
+            File::open(repo_dir.join("metadata").join("1.root.json")).unwrap(),
+            metadata_base_url,
+            targets_base_url,
+        )
+        .transport(HttpTransport::default())
+        .load()
+        .unwrap();
+
+        let timestamp = repo.refresh_timestamp().unwrap();
+        assert_eq!(timestamp.signed.version, repo.timestamp().signed.version);
+        assert_eq!(timestamp.signed.expires, repo.timestamp().signed.expires);
+    }
+
+    /// Test that the default transport sends every fetch through the proxy given to the loader.
+    /// The repository's host doesn't resolve, so the load only succeeds through the proxy, which
+    /// receives each request with the repository's host.
+    #[test]
+    fn test_http_proxy() {
+        let proxy = Server::run();
+        let repo_dir = test_data().join("tuf-reference-impl");
+        for path in [
+            "metadata/timestamp.json",
+            "metadata/snapshot.json",
+            "metadata/targets.json",
+            "metadata/role1.json",
+            "metadata/role2.json",
+            "targets/file1.txt",
+        ] {
+            proxy.expect(
+                Expectation::matching(all_of![
+                    request::method_path("GET", format!("/{}", path)),
+                    request::headers(contains(("host", "tuf.example.invalid"))),
+                ])
+                .times(1)
+                .respond_with(status_code(200).body(std::fs::read(repo_dir.join(path)).unwrap())),
+            );
+        }
+        proxy.expect(create_unsuccessful_get("metadata/2.root.json"));
+
+        let repo = RepositoryLoader::new(
+            File::open(repo_dir.join("metadata").join("1.root.json")).unwrap(),
+            Url::parse("http://tuf.example.invalid/metadata/").unwrap(),
+            Url::parse("http://tuf.example.invalid/targets/").unwrap(),
+        )
+        .proxy(Url::from_str(&proxy.url_str("/")).unwrap())
+        .load()
+        .unwrap();
+        let file1 = TargetName::new("file1.txt").unwrap();
+        assert_eq!(
+            read_to_end(repo.read_target(&file1).unwrap().unwrap()),
+            &b"This is an example target file."[..]
+        );
+    }
+
+    /// Test that hosts listed in `no_proxy` are fetched from directly. The proxy isn't listening,
+    /// so the load only succeeds if it is bypassed.
+    #[test]
+    fn test_http_no_proxy() {
+        let server = Server::run();
+        server.expect(create_successful_get("metadata/timestamp.json"));
+        server.expect(create_successful_get("metadata/snapshot.json"));
+        server.expect(create_successful_get("metadata/targets.json"));
+        server.expect(create_successful_get("metadata/role1.json"));
+        server.expect(create_successful_get("metadata/role2.json"));
+        server.expect(create_unsuccessful_get("metadata/2.root.json"));
+        let repo_dir = test_data().join("tuf-reference-impl");
+        let metadata_base_url = Url::from_str(server.url_str("/metadata").as_str()).unwrap();
+        let targets_base_url = Url::from_str(server.url_str("/targets").as_str()).unwrap();
+        let transport = HttpTransportBuilder::new()
+            .proxy(Url::parse("http://127.0.0.1:1/").unwrap())
+            .no_proxy(server.addr().ip().to_string())
+            .tries(1)
+            .build();
+        RepositoryLoader::new(
+            File::open(repo_dir.join("metadata").join("1.root.json")).unwrap(),
+            metadata_base_url,
+            targets_base_url,
+        )
+        .transport(transport)
+        .load()
+        .unwrap();
+    }
 }
 
 #[cfg(feature = "http")]
@@ -0,0 +1,114 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+mod test_utils;
+
+#[cfg(feature = "http-tls")]
+mod http_tls {
+    use crate::test_utils::{read_to_end, test_data};
+    use rustls::server::AllowAnyAuthenticatedClient;
+    use rustls::{
+        Certificate, PrivateKey, RootCertStore, ServerConfig, ServerConnection, StreamOwned,
+    };
+    use std::fs;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::thread;
+    use tough::{HttpTransportBuilder, Transport};
+    use url::Url;
+
+    const BODY: &[u8] = b"served over mutual TLS\n";
+
+    fn read_pem(name: &str) -> Vec<u8> {
+        fs::read(test_data().join("mtls").join(name)).unwrap()
+    }
+
+    fn certificates(name: &str) -> Vec<Certificate> {
+        rustls_pemfile::certs(&mut read_pem(name).as_slice())
+            .unwrap()
+            .into_iter()
+            .map(Certificate)
+            .collect()
+    }
+
+    /// Starts an HTTPS server on localhost that requires a client certificate issued by the test
+    /// CA, and answers every request with `BODY`. Returns the server's URL.
+    fn start_server() -> Url {
+        let mut roots = RootCertStore::empty();
+        for certificate in certificates("ca.pem") {
+            roots.add(&certificate).unwrap();
+        }
+        let key = rustls_pemfile::pkcs8_private_keys(&mut read_pem("server.key").as_slice())
+            .unwrap()
+            .remove(0);
+        let config = Arc::new(
+            ServerConfig::builder()
+                .with_safe_defaults()
+                .with_client_cert_verifier(AllowAnyAuthenticatedClient::new(roots))
+                .with_single_cert(certificates("server.pem"), PrivateKey(key))
+                .unwrap(),
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let connection = ServerConnection::new(Arc::clone(&config)).unwrap();
+                let mut stream = BufReader::new(StreamOwned::new(connection, stream.unwrap()));
+                // Read the request headers; a client without a certificate fails the handshake here
+                let mut line = String::new();
+                while matches!(stream.read_line(&mut line), Ok(n) if n > 0) && line != "\r\n" {
+                    line.clear();
+                }
+                if line != "\r\n" {
+                    continue;
+                }
+                let stream = stream.get_mut();
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    BODY.len()
+                );
+                let _ = stream.write_all(BODY);
+                let _ = stream.flush();
+                stream.conn.send_close_notify();
+                let _ = stream.flush();
+            }
+        });
+        Url::parse(&format!("https://localhost:{}/file.txt", port)).unwrap()
+    }
+
+    /// Test that a file is fetched from a server that requires a client certificate.
+    #[test]
+    fn fetch_with_client_certificate() {
+        let url = start_server();
+        let transport = HttpTransportBuilder::new()
+            .add_root_certificate_pem(&read_pem("ca.pem"))
+            .unwrap()
+            .client_identity_pem(&read_pem("client.pem"), &read_pem("client.key"))
+            .unwrap()
+            .build();
+        assert_eq!(read_to_end(transport.fetch(url).unwrap()), BODY);
+    }
+
+    /// Test that the server rejects a client that doesn't present a certificate.
+    #[test]
+    fn fetch_without_client_certificate() {
+        let url = start_server();
+        let transport = HttpTransportBuilder::new()
+            .tries(1)
+            .add_root_certificate_pem(&read_pem("ca.pem"))
+            .unwrap()
+            .build();
+        assert!(transport.fetch(url).is_err());
+    }
+
+    /// Test that an invalid client key is reported when the transport is configured.
+    #[test]
+    fn invalid_client_identity() {
+        assert!(HttpTransportBuilder::new()
+            .client_identity_pem(&read_pem("client.pem"), b"not a key")
+            .is_err());
+    }
+}
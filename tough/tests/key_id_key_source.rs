@@ -0,0 +1,82 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::File;
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::{dir_url, test_data, RepoBuilder};
+use tough::key_source::{KeyIdKeySource, KeySource, LocalKeySource};
+use tough::schema::decoded::{Decoded, Hex};
+use tough::RepositoryLoader;
+
+mod test_utils;
+
+fn key_id(path: &Path) -> Decoded<Hex> {
+    LocalKeySource {
+        path: path.to_owned(),
+    }
+    .as_sign()
+    .unwrap()
+    .tuf_key()
+    .key_id()
+    .unwrap()
+}
+
+/// Writes a key bundle holding both snakeoil keys, `snakeoil_2.pem` first.
+fn write_bundle(dir: &Path) -> LocalKeySource {
+    let mut bundle = std::fs::read(test_data().join("snakeoil_2.pem")).unwrap();
+    bundle.extend(std::fs::read(test_data().join("snakeoil.pem")).unwrap());
+    let path = dir.join("bundle.pem");
+    std::fs::write(&path, bundle).unwrap();
+    LocalKeySource { path }
+}
+
+/// Test that a key can be picked out of a source holding several keys, and used to sign a
+/// repository whose root only trusts that key.
+#[test]
+fn sign_with_key_id() {
+    let bundle_dir = TempDir::new().unwrap();
+    let bundle = write_bundle(bundle_dir.path());
+    assert_eq!(bundle.as_signs().unwrap().len(), 2);
+    let snakeoil_id = key_id(&test_data().join("snakeoil.pem"));
+    let keys: Vec<Box<dyn KeySource>> = vec![Box::new(KeyIdKeySource {
+        source: Box::new(bundle),
+        key_id: snakeoil_id.clone(),
+    })];
+    assert_eq!(
+        keys[0].as_sign().unwrap().tuf_key().key_id().unwrap(),
+        snakeoil_id
+    );
+
+    let root_path = test_data().join("simple-rsa").join("root.json");
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_dir = repo_dir.path().join("metadata");
+    RepoBuilder::new()
+        .sign_with(&keys)
+        .write(&metadata_dir)
+        .unwrap();
+
+    RepositoryLoader::new(
+        File::open(&root_path).unwrap(),
+        dir_url(&metadata_dir),
+        dir_url(repo_dir.path().join("targets")),
+    )
+    .load()
+    .unwrap();
+}
+
+/// Test that asking for a key the source doesn't hold is an error.
+#[test]
+fn key_id_not_in_source() {
+    let bundle_dir = TempDir::new().unwrap();
+    let source = KeyIdKeySource {
+        source: Box::new(write_bundle(bundle_dir.path())),
+        key_id: vec![0; 32].into(),
+    };
+    let error = source.as_sign().unwrap_err();
+    assert!(
+        error.to_string().contains(&"00".repeat(32)),
+        "unexpected error: {}",
+        error
+    );
+}
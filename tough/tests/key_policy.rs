@@ -0,0 +1,77 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::File;
+use test_utils::{dir_url, test_data};
+use tough::error::Error;
+use tough::{KeyPolicy, Repository, RepositoryLoader};
+
+mod test_utils;
+
+/// Loads the reference implementation repository, whose root role uses a 3072-bit RSA key and whose
+/// other roles, including the delegated ones, use Ed25519 keys.
+fn load(key_policy: KeyPolicy) -> tough::error::Result<Repository> {
+    let base = test_data().join("tuf-reference-impl");
+    RepositoryLoader::new(
+        File::open(base.join("metadata").join("1.root.json")).unwrap(),
+        dir_url(base.join("metadata")),
+        dir_url(base.join("targets")),
+    )
+    .key_policy(key_policy)
+    .load()
+}
+
+#[test]
+fn default_policy_allows_supported_keys() {
+    load(KeyPolicy::default()).unwrap();
+}
+
+#[test]
+fn allowed_schemes_and_rsa_size() {
+    load(KeyPolicy {
+        allowed_schemes: Some(vec!["rsassa-pss-sha256".into(), "ed25519".into()]),
+        denied_schemes: vec!["ecdsa-sha2-nistp256".into()],
+        min_rsa_bits: 3072,
+    })
+    .unwrap();
+}
+
+#[test]
+fn denied_scheme() {
+    let err = load(KeyPolicy {
+        denied_schemes: vec!["ed25519".into()],
+        ..KeyPolicy::default()
+    })
+    .unwrap_err();
+    assert!(
+        matches!(&err, Error::KeyPolicyScheme { role, scheme, .. } if role == "snapshot" && scheme == "ed25519"),
+        "unexpected error: {err}"
+    );
+    assert!(err.to_string().starts_with("Key 59a4df8af818e9ed"));
+}
+
+#[test]
+fn scheme_not_allowed() {
+    let err = load(KeyPolicy {
+        allowed_schemes: Some(vec!["ed25519".into()]),
+        ..KeyPolicy::default()
+    })
+    .unwrap_err();
+    assert!(
+        matches!(&err, Error::KeyPolicyScheme { role, scheme, .. } if role == "root" && scheme == "rsassa-pss-sha256"),
+        "unexpected error: {err}"
+    );
+}
+
+#[test]
+fn rsa_key_too_small() {
+    let err = load(KeyPolicy {
+        min_rsa_bits: 4096,
+        ..KeyPolicy::default()
+    })
+    .unwrap_err();
+    assert!(
+        matches!(&err, Error::KeyPolicyRsaSize { role, bits: 3072, min_bits: 4096, .. } if role == "root"),
+        "unexpected error: {err}"
+    );
+}
@@ -0,0 +1,69 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::File;
+use tempfile::TempDir;
+use test_utils::{dir_url, test_data, RepoBuilder};
+use tough::error::Error::UnrecognizedScheme;
+use tough::key_source::{KeySource, KeySourceRegistry, LocalKeySource};
+use tough::RepositoryLoader;
+
+mod test_utils;
+
+/// A registry with a custom `test-data` scheme that names keys in the test data directory.
+fn registry() -> KeySourceRegistry {
+    let mut registry = KeySourceRegistry::new();
+    registry.register("test-data", |url| {
+        Ok(Box::new(LocalKeySource {
+            path: test_data().join(url.path().trim_start_matches('/')),
+        }))
+    });
+    registry
+}
+
+/// Test that a key from a custom scheme can be used to sign a repository.
+#[test]
+fn sign_with_custom_scheme() {
+    let registry = registry();
+    assert!(registry.contains("file"));
+    assert!(registry.contains("test-data"));
+    let keys: Vec<Box<dyn KeySource>> =
+        vec![registry.resolve("test-data:///snakeoil.pem").unwrap()];
+
+    let root_path = test_data().join("simple-rsa").join("root.json");
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_dir = repo_dir.path().join("metadata");
+    RepoBuilder::new()
+        .sign_with(&keys)
+        .write(&metadata_dir)
+        .unwrap();
+
+    // The repository verifies against the root the key belongs to
+    RepositoryLoader::new(
+        File::open(&root_path).unwrap(),
+        dir_url(&metadata_dir),
+        dir_url(repo_dir.path().join("targets")),
+    )
+    .load()
+    .unwrap();
+}
+
+/// Test that existing paths resolve to local keys and unknown schemes are rejected.
+#[test]
+fn resolve_builtin_and_unknown_schemes() {
+    let registry = registry();
+    let key_path = test_data().join("snakeoil.pem");
+    assert!(registry
+        .resolve(key_path.to_str().unwrap())
+        .unwrap()
+        .as_sign()
+        .is_ok());
+
+    match registry.resolve("hsm://slot/1") {
+        Err(UnrecognizedScheme { scheme, .. }) => assert_eq!(scheme, "hsm"),
+        other => panic!("Expected an 'UnrecognizedScheme' error but received {other:?}"),
+    }
+    assert!(KeySourceRegistry::empty()
+        .resolve(key_path.to_str().unwrap())
+        .is_err());
+}
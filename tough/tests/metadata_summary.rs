@@ -0,0 +1,50 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use chrono::{TimeZone, Utc};
+use std::fs::File;
+use test_utils::{dir_url, test_data};
+use tough::{ExpirationEnforcement, RepositoryLoader};
+
+mod test_utils;
+
+/// Test that the summary lists the top-level roles and each delegated role, with their versions
+/// and expirations.
+#[test]
+fn metadata_summary() {
+    let base = test_data().join("tuf-reference-impl");
+    let repo = RepositoryLoader::new(
+        File::open(base.join("metadata").join("1.root.json")).unwrap(),
+        dir_url(base.join("metadata")),
+        dir_url(base.join("targets")),
+    )
+    .expiration_enforcement(ExpirationEnforcement::Unsafe)
+    .load()
+    .unwrap();
+
+    let summary = repo.metadata_summary();
+    assert_eq!(summary.len(), 6);
+    assert_eq!(
+        summary
+            .iter()
+            .map(|role| role.name.as_str())
+            .collect::<Vec<_>>(),
+        ["root", "timestamp", "snapshot", "targets", "role1", "role2"]
+    );
+    let expires = Utc.with_ymd_and_hms(2030, 1, 1, 0, 0, 0).unwrap();
+    for role in &summary {
+        assert_eq!(role.version.get(), 1);
+        assert_eq!(role.expires, expires);
+    }
+    assert_eq!(summary[3].version, repo.targets().signed.version);
+    assert_eq!(
+        summary[4].expires,
+        repo.delegated_role("role1")
+            .unwrap()
+            .targets
+            .as_ref()
+            .unwrap()
+            .signed
+            .expires
+    );
+}
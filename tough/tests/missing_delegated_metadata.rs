@@ -0,0 +1,66 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::File;
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::{dir_url, test_data, RepoBuilder};
+use tough::error::Error::{DelegatedHashMismatch, MissingDelegatedMetadata};
+use tough::schema::{PathPattern, PathSet};
+use tough::{ExpirationEnforcement, RepositoryLoader};
+
+mod test_utils;
+
+fn load_missing_role(root: &Path, metadata_dir: &Path) -> String {
+    let result = RepositoryLoader::new(
+        File::open(root).unwrap(),
+        dir_url(metadata_dir),
+        dir_url(test_data().join("tuf-reference-impl").join("targets")),
+    )
+    .expiration_enforcement(ExpirationEnforcement::Unsafe)
+    .load();
+    match result {
+        Err(MissingDelegatedMetadata { role, .. }) => role,
+        other => panic!("Expected a 'MissingDelegatedMetadata' error but received {other:?}"),
+    }
+}
+
+/// Test that a delegated role listed in snapshot.json, but whose metadata doesn't exist, is
+/// reported by name.
+#[test]
+fn dangling_snapshot_reference() {
+    let base = test_data()
+        .join("dangling-snapshot-reference")
+        .join("metadata");
+    assert_eq!(load_missing_role(&base.join("1.root.json"), &base), "role2");
+}
+
+/// Test that delegated role metadata that doesn't match the hash in snapshot.json is reported by
+/// name, even when its signatures are still valid, and isn't mistaken for missing metadata.
+#[test]
+fn delegated_metadata_hash_mismatch() {
+    let root = test_data().join("simple-rsa").join("root.json");
+    let metadata_dir = TempDir::new().unwrap();
+    RepoBuilder::new()
+        .delegate(
+            "role1",
+            PathSet::Paths(vec![PathPattern::new("file?.txt").unwrap()]),
+        )
+        .write(metadata_dir.path());
+
+    // Swap one byte of indentation for a tab, which changes the file's hash but not its meaning
+    let role1_path = metadata_dir.path().join("1.role1.json");
+    let role1 = std::fs::read_to_string(&role1_path).unwrap();
+    std::fs::write(&role1_path, role1.replacen("  ", " \t", 1)).unwrap();
+
+    let result = RepositoryLoader::new(
+        File::open(&root).unwrap(),
+        dir_url(metadata_dir.path()),
+        dir_url(test_data().join("tuf-reference-impl").join("targets")),
+    )
+    .load();
+    match result {
+        Err(DelegatedHashMismatch { role, .. }) => assert_eq!(role, "role1"),
+        other => panic!("Expected a 'DelegatedHashMismatch' error but received {other:?}"),
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::File;
+use std::num::NonZeroU64;
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::{dir_url, test_data, RepoBuilder};
+use tough::error::Error;
+use tough::schema::RoleType;
+use tough::{ExpirationEnforcement, RepositoryLoader};
+
+mod test_utils;
+
+fn version(version: u64) -> NonZeroU64 {
+    NonZeroU64::new(version).unwrap()
+}
+
+fn load_pinned(metadata_dir: &Path, snapshot: u64, timestamp: u64) -> Result<(), Error> {
+    RepositoryLoader::new(
+        File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        dir_url(metadata_dir),
+        dir_url(metadata_dir.join("targets")),
+    )
+    .pin_versions(version(snapshot), version(timestamp))
+    .load()
+    .map(|_| ())
+}
+
+/// Test that a repository at the pinned versions loads.
+#[test]
+fn pinned_versions_match() {
+    let base = test_data().join("tuf-reference-impl");
+    let repo = RepositoryLoader::new(
+        File::open(base.join("metadata").join("1.root.json")).unwrap(),
+        dir_url(base.join("metadata")),
+        dir_url(base.join("targets")),
+    )
+    .expiration_enforcement(ExpirationEnforcement::Unsafe)
+    .pin_versions(version(1), version(1))
+    .load()
+    .unwrap();
+    assert_eq!(repo.snapshot().signed.version, version(1));
+    assert_eq!(repo.timestamp().signed.version, version(1));
+}
+
+/// Test that a repository that has moved past either pinned version fails to load.
+#[test]
+fn pinned_versions_advanced() {
+    let repo_dir = TempDir::new().unwrap();
+    RepoBuilder::new()
+        .snapshot_version(3)
+        .timestamp_version(5)
+        .write(repo_dir.path());
+    load_pinned(repo_dir.path(), 3, 5).unwrap();
+
+    match load_pinned(repo_dir.path(), 3, 4) {
+        Err(Error::PinnedVersion {
+            role,
+            loaded,
+            pinned,
+            ..
+        }) => {
+            assert_eq!(role, RoleType::Timestamp);
+            assert_eq!(loaded, 5);
+            assert_eq!(pinned, 4);
+        }
+        other => panic!("Expected a 'PinnedVersion' error but received {other:?}"),
+    }
+    match load_pinned(repo_dir.path(), 2, 5) {
+        Err(Error::PinnedVersion {
+            role,
+            loaded,
+            pinned,
+            ..
+        }) => {
+            assert_eq!(role, RoleType::Snapshot);
+            assert_eq!(loaded, 3);
+            assert_eq!(pinned, 2);
+        }
+        other => panic!("Expected a 'PinnedVersion' error but received {other:?}"),
+    }
+}
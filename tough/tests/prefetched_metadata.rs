@@ -0,0 +1,122 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::{read_to_end, test_data, RepoBuilder};
+use tough::schema::{PathPattern, PathSet};
+use tough::{PrefetchedMetadata, TargetName, Transport, TransportErrorKind};
+use url::Url;
+
+mod test_utils;
+
+/// Creates a repository with one target whose top-level targets role delegates to `role1`.
+fn create_repo(metadata_dir: &Path) {
+    RepoBuilder::new()
+        .delegate(
+            "role1",
+            PathSet::Paths(vec![PathPattern::new("role1/*").unwrap()]),
+        )
+        .target_path(
+            test_data()
+                .join("tuf-reference-impl")
+                .join("targets")
+                .join("file1.txt"),
+        )
+        .write(metadata_dir);
+}
+
+/// Test that metadata passed in as bytes loads into a verified repository without a transport.
+#[test]
+fn load_prefetched_metadata() {
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_dir = repo_dir.path().join("metadata");
+    create_repo(&metadata_dir);
+    let read = |name: &str| std::fs::read(metadata_dir.join(name)).unwrap();
+
+    // simple-rsa uses consistent snapshots, so each role file carries a version prefix
+    let role1 = read("1.role1.json");
+    let repo = PrefetchedMetadata::new(
+        std::fs::read(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        read("timestamp.json"),
+        read("1.snapshot.json"),
+        read("1.targets.json"),
+    )
+    .delegated_roles(move |name| (name == "role1").then(|| role1.clone()))
+    .loader()
+    .load()
+    .unwrap();
+
+    assert!(repo
+        .targets()
+        .signed
+        .targets
+        .contains_key(&TargetName::new("file1.txt").unwrap()));
+    assert!(repo.delegated_role("role1").unwrap().targets.is_some());
+
+    // The metadata is loaded and verified, but targets are not reachable through it
+    assert!(repo
+        .read_target(&TargetName::new("file1.txt").unwrap())
+        .is_err());
+}
+
+/// Test that tampered metadata bytes fail verification.
+#[test]
+fn load_prefetched_metadata_tampered() {
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_dir = repo_dir.path().join("metadata");
+    create_repo(&metadata_dir);
+    let read = |name: &str| std::fs::read(metadata_dir.join(name)).unwrap();
+
+    // A snapshot whose bytes don't match the hash in the timestamp is rejected
+    let mut snapshot = read("1.snapshot.json");
+    snapshot.push(b'\n');
+    assert!(PrefetchedMetadata::new(
+        std::fs::read(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        read("timestamp.json"),
+        snapshot,
+        read("1.targets.json"),
+    )
+    .loader()
+    .load()
+    .is_err());
+}
+
+/// Test that a delegated role whose name looks like a version prefix, as requested from a
+/// repository without consistent snapshots, is looked up by its whole name, and that a prefix is
+/// only stripped from the names of known roles.
+#[test]
+fn prefetched_metadata_versioned_names() {
+    let metadata = PrefetchedMetadata::new(
+        b"root".to_vec(),
+        b"timestamp".to_vec(),
+        b"snapshot".to_vec(),
+        b"targets".to_vec(),
+    )
+    .delegated_roles(|name| match name {
+        "1.foo" => Some(b"1.foo".to_vec()),
+        "bar" => Some(b"bar".to_vec()),
+        _ => None,
+    });
+    let fetch = |filename: &str| {
+        let url = Url::parse("prefetched:///metadata/")
+            .unwrap()
+            .join(filename)
+            .unwrap();
+        metadata.fetch(url).map(read_to_end)
+    };
+
+    assert_eq!(fetch("1.foo.json").unwrap(), b"1.foo");
+    assert_eq!(fetch("2.1.foo.json").unwrap(), b"1.foo");
+    assert_eq!(fetch("1.bar.json").unwrap(), b"bar");
+    assert_eq!(fetch("1.snapshot.json").unwrap(), b"snapshot");
+    for missing in ["foo.json", "1.baz.json", ".bar.json", "1.root.json"] {
+        assert!(
+            matches!(
+                fetch(missing).unwrap_err().kind(),
+                TransportErrorKind::FileNotFound
+            ),
+            "{missing} was found"
+        );
+    }
+}
@@ -0,0 +1,85 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use chrono::{Duration, Utc};
+use std::fs::File;
+use std::num::NonZeroU64;
+use tempfile::TempDir;
+use test_utils::{dir_url, test_data, RepoBuilder};
+use tough::{RepositoryLoader, TargetName};
+
+mod test_utils;
+
+/// Test that refreshing picks up a published update, and reports when there is none.
+#[test]
+fn refresh_across_update() {
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_dir = repo_dir.path().join("metadata");
+    let targets_dir = test_data().join("tuf-reference-impl").join("targets");
+    RepoBuilder::new()
+        .target_path(targets_dir.join("file1.txt"))
+        .write(&metadata_dir);
+
+    let mut repo = RepositoryLoader::new(
+        File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        dir_url(&metadata_dir),
+        dir_url(repo_dir.path().join("targets")),
+    )
+    .load()
+    .unwrap();
+    assert!(!repo.refresh().unwrap());
+
+    RepoBuilder::new()
+        .version(2)
+        .target_path(targets_dir.join("file1.txt"))
+        .target_path(targets_dir.join("file2.txt"))
+        .write(&metadata_dir);
+    assert!(repo.refresh().unwrap());
+    let two = NonZeroU64::new(2).unwrap();
+    assert_eq!(repo.timestamp().signed.version, two);
+    assert_eq!(repo.snapshot().signed.version, two);
+    assert_eq!(repo.targets().signed.version, two);
+    assert!(repo
+        .targets()
+        .signed
+        .targets
+        .contains_key(&TargetName::new("file2.txt").unwrap()));
+    assert!(!repo.refresh().unwrap());
+}
+
+/// Test that a refresh that fails verification leaves the repository as it was.
+#[test]
+fn refresh_failure_keeps_state() {
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_dir = repo_dir.path().join("metadata");
+    let targets_dir = test_data().join("tuf-reference-impl").join("targets");
+    RepoBuilder::new()
+        .target_path(targets_dir.join("file1.txt"))
+        .write(&metadata_dir);
+
+    let mut repo = RepositoryLoader::new(
+        File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        dir_url(&metadata_dir),
+        dir_url(repo_dir.path().join("targets")),
+    )
+    .load()
+    .unwrap();
+
+    // Publish an update whose timestamp has already expired
+    let expired = Utc::now().checked_sub_signed(Duration::days(1)).unwrap();
+    RepoBuilder::new()
+        .version(2)
+        .timestamp_expires(expired)
+        .target_path(targets_dir.join("file1.txt"))
+        .target_path(targets_dir.join("file2.txt"))
+        .write(&metadata_dir);
+    assert!(repo.refresh().is_err());
+    let one = NonZeroU64::new(1).unwrap();
+    assert_eq!(repo.timestamp().signed.version, one);
+    assert_eq!(repo.targets().signed.version, one);
+    assert!(!repo
+        .targets()
+        .signed
+        .targets
+        .contains_key(&TargetName::new("file2.txt").unwrap()));
+}
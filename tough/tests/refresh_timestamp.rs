@@ -0,0 +1,107 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use chrono::{Duration, Utc};
+use std::fs::File;
+use std::num::NonZeroU64;
+use tempfile::TempDir;
+use test_utils::{dir_url, test_data, RepoBuilder};
+use tough::error::Error::ExpiredMetadata;
+use tough::schema::RoleType;
+use tough::RepositoryLoader;
+
+mod test_utils;
+
+/// Starts a small repository on the simple-rsa root, listing `file1.txt`.
+fn repo_builder() -> RepoBuilder {
+    RepoBuilder::new().target_path(
+        test_data()
+            .join("tuf-reference-impl")
+            .join("targets")
+            .join("file1.txt"),
+    )
+}
+
+/// Test that the latest timestamp can be fetched and verified on its own.
+#[test]
+fn refresh_timestamp_valid() {
+    let base = test_data().join("tuf-reference-impl");
+    let repo = RepositoryLoader::new(
+        File::open(base.join("metadata").join("1.root.json")).unwrap(),
+        dir_url(base.join("metadata")),
+        dir_url(base.join("targets")),
+    )
+    .load()
+    .unwrap();
+
+    let timestamp = repo.refresh_timestamp().unwrap();
+    assert_eq!(timestamp.signed.version, repo.timestamp().signed.version);
+    assert_eq!(timestamp.signed.expires, repo.timestamp().signed.expires);
+}
+
+/// Test that refreshing fails when the repository has since published an expired timestamp.
+#[test]
+fn refresh_timestamp_expired() {
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_dir = repo_dir.path().join("metadata");
+    repo_builder()
+        .timestamp_expires(Utc::now().checked_add_signed(Duration::days(1)).unwrap())
+        .write(&metadata_dir);
+
+    let repo = RepositoryLoader::new(
+        File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        dir_url(&metadata_dir),
+        dir_url(repo_dir.path().join("targets")),
+    )
+    .load()
+    .unwrap();
+    assert_eq!(
+        repo.refresh_timestamp().unwrap().signed.version,
+        NonZeroU64::new(1).unwrap()
+    );
+
+    // Publish a newer timestamp that has already expired
+    repo_builder()
+        .timestamp_version(2)
+        .timestamp_expires(Utc::now().checked_sub_signed(Duration::days(1)).unwrap())
+        .write(&metadata_dir);
+    match repo.refresh_timestamp() {
+        Err(ExpiredMetadata { role, .. }) => assert_eq!(role, RoleType::Timestamp),
+        other => panic!("Expected an 'ExpiredMetadata' error but received {other:?}"),
+    }
+}
+
+/// Test that refreshing the timestamp doesn't save it to the datastore, which keeps the timestamp
+/// the repository was loaded with as the trusted one.
+#[test]
+fn refresh_timestamp_not_saved() {
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_dir = repo_dir.path().join("metadata");
+    let datastore = TempDir::new().unwrap();
+    let expires = Utc::now().checked_add_signed(Duration::days(1)).unwrap();
+    repo_builder()
+        .timestamp_expires(expires)
+        .write(&metadata_dir);
+
+    let repo = RepositoryLoader::new(
+        File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        dir_url(&metadata_dir),
+        dir_url(repo_dir.path().join("targets")),
+    )
+    .datastore(datastore.path())
+    .load()
+    .unwrap();
+
+    repo_builder()
+        .timestamp_version(2)
+        .timestamp_expires(expires)
+        .write(&metadata_dir);
+    assert_eq!(
+        repo.refresh_timestamp().unwrap().signed.version,
+        NonZeroU64::new(2).unwrap()
+    );
+    let saved: tough::schema::Signed<tough::schema::Timestamp> =
+        serde_json::from_reader(File::open(datastore.path().join("timestamp.json")).unwrap())
+            .unwrap();
+    assert_eq!(saved.signed.version, NonZeroU64::new(1).unwrap());
+}
@@ -299,3 +299,79 @@ fn test_repo_cache_consistent_snapshots() {
         .join("5aa1d2b3bea034a0f9d0b27a1bc72919b3145a2b092b72ac0415a05e07e2bdd1.data1.txt");
     assert!(expected_filepath.is_file())
 }
+
+/// Test that the plan for caching into a stale mirror lists exactly the changes that caching
+/// (and pruning) then makes.
+#[test]
+fn test_repo_cache_plan_matches_cache() {
+    let repo_paths = RepoPaths::new();
+    let repo = load_tuf_reference_impl(&repo_paths);
+
+    // A mirror with an outdated file1.txt, an up-to-date file2.txt, no file3.txt and a file that is
+    // not a target at all
+    let destination = TempDir::new().unwrap();
+    let metadata_destination = destination.as_ref().join("metadata");
+    let targets_destination = destination.as_ref().join("targets");
+    std::fs::create_dir_all(targets_destination.join("extra")).unwrap();
+    std::fs::write(targets_destination.join("file1.txt"), "outdated").unwrap();
+    std::fs::copy(
+        test_data()
+            .join("tuf-reference-impl")
+            .join("targets")
+            .join("file2.txt"),
+        targets_destination.join("file2.txt"),
+    )
+    .unwrap();
+    std::fs::write(targets_destination.join("extra").join("old.txt"), "old").unwrap();
+
+    let plan = repo
+        .cache_plan(&targets_destination, None::<&[&str]>, true)
+        .unwrap();
+    assert_eq!(plan.add, vec!["file3.txt"]);
+    assert_eq!(plan.update, vec!["file1.txt"]);
+    assert_eq!(plan.delete, vec!["extra/old.txt"]);
+
+    let files = ["file1.txt", "file2.txt", "file3.txt", "extra/old.txt"];
+    let read_files = || {
+        files
+            .iter()
+            .map(|file| std::fs::read(targets_destination.join(file)).ok())
+            .collect::<Vec<_>>()
+    };
+    let before = read_files();
+    repo.cache(
+        &metadata_destination,
+        &targets_destination,
+        None::<&[&str]>,
+        true,
+    )
+    .unwrap();
+    for file in &plan.delete {
+        std::fs::remove_file(targets_destination.join(file)).unwrap();
+    }
+    let after = read_files();
+
+    // Every file the plan names changed in the way it said, and no other file changed
+    for ((file, before), after) in files.iter().zip(before).zip(after) {
+        let file = file.to_string();
+        match (before, after) {
+            (None, Some(_)) => assert!(plan.add.contains(&file), "{file} was added"),
+            (Some(_), None) => assert!(plan.delete.contains(&file), "{file} was deleted"),
+            (Some(before), Some(after)) if before != after => {
+                assert!(plan.update.contains(&file), "{file} was updated")
+            }
+            _ => assert!(
+                !plan.add.contains(&file)
+                    && !plan.update.contains(&file)
+                    && !plan.delete.contains(&file),
+                "{file} was unchanged"
+            ),
+        }
+    }
+
+    // Once synced, there is nothing left to do
+    assert!(repo
+        .cache_plan(&targets_destination, None::<&[&str]>, true)
+        .unwrap()
+        .is_empty());
+}
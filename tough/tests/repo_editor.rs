@@ -7,16 +7,21 @@ use std::collections::HashMap;
 use std::fs::File;
 use std::io::prelude::Write;
 use std::num::NonZeroU64;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 use tough::editor::signed::PathExists;
-use tough::editor::{targets::TargetsEditor, RepositoryEditor};
+use tough::editor::targets::{CustomValidator, TargetsEditor};
+use tough::editor::RepositoryEditor;
+use tough::error::Error::{
+    BumpVersionRole, InvalidTargetName, PathsNotDelegated, RenameTargetCollision,
+    TargetCustomInvalid, UndelegatedTargets,
+};
 use tough::key_source::KeySource;
 use tough::key_source::LocalKeySource;
 use tough::schema::decoded::Decoded;
 use tough::schema::decoded::Hex;
 use tough::schema::key::Key;
-use tough::schema::{PathPattern, PathSet};
+use tough::schema::{PathPattern, PathSet, RoleType, Target};
 use tough::{Repository, RepositoryLoader, TargetName};
 use url::Url;
 
@@ -806,3 +811,459 @@ fn update_targets_flow() {
         &b"Updated file1.txt"[..]
     );
 }
+
+#[test]
+/// Moves a delegated role from one parent to another and reloads the repository
+fn move_role_between_parents() {
+    let mut editor = test_repo_editor();
+    let targets_expiration = Utc::now().checked_add_signed(Duration::days(13)).unwrap();
+    let targets_version = NonZeroU64::new(789).unwrap();
+
+    let targets_key: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource { path: key_path() })];
+    let role1_key: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource {
+        path: targets_key_path(),
+    })];
+    let role2_key: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource {
+        path: targets_key_path1(),
+    })];
+    let parent_keys: &[Box<dyn KeySource>] = &[
+        Box::new(LocalKeySource {
+            path: targets_key_path(),
+        }),
+        Box::new(LocalKeySource {
+            path: targets_key_path1(),
+        }),
+    ];
+
+    // targets delegates role1, role4, and role5
+    editor
+        .delegate_role(
+            "role1",
+            role1_key,
+            PathSet::Paths(vec![PathPattern::new("file?.txt").unwrap()]),
+            NonZeroU64::new(1).unwrap(),
+            Utc::now().checked_add_signed(Duration::days(21)).unwrap(),
+            NonZeroU64::new(1).unwrap(),
+        )
+        .unwrap()
+        .delegate_role(
+            "role4",
+            role2_key,
+            PathSet::Paths(vec![PathPattern::new("file1.txt").unwrap()]),
+            NonZeroU64::new(1).unwrap(),
+            Utc::now().checked_add_signed(Duration::days(21)).unwrap(),
+            NonZeroU64::new(1).unwrap(),
+        )
+        .unwrap()
+        .delegate_role(
+            "role5",
+            role1_key,
+            PathSet::Paths(vec![PathPattern::new("file2.txt").unwrap()]),
+            NonZeroU64::new(1).unwrap(),
+            Utc::now().checked_add_signed(Duration::days(21)).unwrap(),
+            NonZeroU64::new(1).unwrap(),
+        )
+        .unwrap();
+    // role1 delegates role2, which signs file1.txt
+    editor
+        .sign_targets_editor(targets_key)
+        .unwrap()
+        .change_delegated_targets("role1")
+        .unwrap()
+        .delegate_role(
+            "role2",
+            role2_key,
+            PathSet::Paths(vec![PathPattern::new("file1.txt").unwrap()]),
+            NonZeroU64::new(1).unwrap(),
+            Utc::now().checked_add_signed(Duration::days(21)).unwrap(),
+            NonZeroU64::new(1).unwrap(),
+        )
+        .unwrap()
+        .targets_version(targets_version)
+        .unwrap()
+        .targets_expires(targets_expiration)
+        .unwrap()
+        .sign_targets_editor(role1_key)
+        .unwrap()
+        .change_delegated_targets("role2")
+        .unwrap()
+        .add_target_paths([targets_path().join("file1.txt").to_str().unwrap()].to_vec())
+        .unwrap()
+        .targets_version(targets_version)
+        .unwrap()
+        .targets_expires(targets_expiration)
+        .unwrap()
+        .sign_targets_editor(role2_key)
+        .unwrap();
+
+    // role5 isn't delegated file1.txt, so role2 can't be moved under it
+    assert!(editor.move_role("role2", "role5", parent_keys).is_err());
+    // role2 can't be moved under itself
+    assert!(editor.move_role("role2", "role2", parent_keys).is_err());
+    editor.move_role("role2", "role4", parent_keys).unwrap();
+
+    let signed_repo = editor.sign(targets_key).unwrap();
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_destination = repo_dir.path().join("metadata");
+    let targets_destination = repo_dir.path().join("targets");
+    signed_repo.write(&metadata_destination).unwrap();
+    signed_repo
+        .link_targets(targets_path(), &targets_destination, PathExists::Skip)
+        .unwrap();
+
+    // Load the repo we just created
+    let new_repo = RepositoryLoader::new(
+        File::open(root_path()).unwrap(),
+        dir_url(&metadata_destination),
+        dir_url(&targets_destination),
+    )
+    .load()
+    .unwrap();
+
+    // role2 is now delegated by role4 and no longer by role1
+    let role4_targets = &new_repo
+        .delegated_role("role4")
+        .unwrap()
+        .targets
+        .as_ref()
+        .unwrap()
+        .signed;
+    assert!(role4_targets.delegated_role("role2").is_ok());
+    assert_eq!(role4_targets.version, NonZeroU64::new(2).unwrap());
+    assert!(new_repo
+        .delegated_role("role1")
+        .unwrap()
+        .targets
+        .as_ref()
+        .unwrap()
+        .signed
+        .delegated_role("role2")
+        .is_err());
+
+    let file1 = TargetName::new("file1.txt").unwrap();
+    assert_eq!(
+        read_to_end(new_repo.read_target(&file1).unwrap().unwrap()),
+        &b"This is an example target file."[..]
+    );
+}
+
+#[test]
+// Make sure targets outside of a delegated role's paths are rejected when signing the role, unless
+// explicitly allowed
+fn delegated_target_paths_validated() {
+    let mut editor = test_repo_editor();
+    let targets_expiration = Utc::now().checked_add_signed(Duration::days(13)).unwrap();
+    let targets_version = NonZeroU64::new(789).unwrap();
+
+    let targets_key: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource { path: key_path() })];
+    let role1_key: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource {
+        path: targets_key_path(),
+    })];
+
+    // role1 is only delegated file1.txt, which it can sign for
+    editor
+        .delegate_role(
+            "role1",
+            role1_key,
+            PathSet::Paths(vec![PathPattern::new("file1.txt").unwrap()]),
+            NonZeroU64::new(1).unwrap(),
+            Utc::now().checked_add_signed(Duration::days(21)).unwrap(),
+            NonZeroU64::new(1).unwrap(),
+        )
+        .unwrap()
+        .sign_targets_editor(targets_key)
+        .unwrap()
+        .change_delegated_targets("role1")
+        .unwrap()
+        .add_target_paths([targets_path().join("file1.txt").to_str().unwrap()].to_vec())
+        .unwrap()
+        .targets_version(targets_version)
+        .unwrap()
+        .targets_expires(targets_expiration)
+        .unwrap()
+        .sign_targets_editor(role1_key)
+        .unwrap();
+
+    let signed_repo = editor.sign(targets_key).unwrap();
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_destination = repo_dir.path().join("metadata");
+    signed_repo.write(&metadata_destination).unwrap();
+    let new_repo = RepositoryLoader::new(
+        File::open(root_path()).unwrap(),
+        dir_url(&metadata_destination),
+        dir_url(repo_dir.path().join("targets")),
+    )
+    .load()
+    .unwrap();
+
+    // file2.txt isn't delegated to role1
+    let mut editor = TargetsEditor::from_repo(new_repo, "role1").unwrap();
+    editor
+        .add_target_paths([targets_path().join("file2.txt")].to_vec())
+        .unwrap()
+        .version(NonZeroU64::new(2).unwrap())
+        .expires(targets_expiration);
+    match editor.sign(role1_key) {
+        Err(UndelegatedTargets { role, targets }) => {
+            assert_eq!(role, "role1");
+            assert_eq!(targets, vec!["file2.txt".to_string()]);
+        }
+        Err(e) => panic!("Expected an 'UndelegatedTargets' error but received {e}"),
+        Ok(_) => panic!("Expected an 'UndelegatedTargets' error"),
+    }
+
+    // Signing succeeds when undelegated targets are explicitly allowed
+    editor
+        .allow_undelegated_targets(true)
+        .sign(role1_key)
+        .unwrap();
+}
+
+#[test]
+// Make sure a role's delegated paths can only be changed to paths within those delegated to its
+// parent
+fn set_role_paths_within_parent() {
+    let mut editor = test_repo_editor();
+    let targets_expiration = Utc::now().checked_add_signed(Duration::days(13)).unwrap();
+    let targets_version = NonZeroU64::new(789).unwrap();
+    let role_expiration = Utc::now().checked_add_signed(Duration::days(21)).unwrap();
+    let one = NonZeroU64::new(1).unwrap();
+
+    let targets_key: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource { path: key_path() })];
+    let role1_key: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource {
+        path: targets_key_path(),
+    })];
+    let role2_key: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource {
+        path: targets_key_path1(),
+    })];
+
+    // targets delegates file?.txt to role1, which delegates file1.txt to role2
+    editor
+        .delegate_role(
+            "role1",
+            role1_key,
+            PathSet::Paths(vec![PathPattern::new("file?.txt").unwrap()]),
+            one,
+            role_expiration,
+            one,
+        )
+        .unwrap()
+        .sign_targets_editor(targets_key)
+        .unwrap()
+        .change_delegated_targets("role1")
+        .unwrap()
+        .delegate_role(
+            "role2",
+            role2_key,
+            PathSet::Paths(vec![PathPattern::new("file1.txt").unwrap()]),
+            one,
+            role_expiration,
+            one,
+        )
+        .unwrap()
+        .targets_version(targets_version)
+        .unwrap()
+        .targets_expires(targets_expiration)
+        .unwrap()
+        .sign_targets_editor(role1_key)
+        .unwrap();
+
+    let signed_repo = editor.sign(targets_key).unwrap();
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_destination = repo_dir.path().join("metadata");
+    signed_repo.write(&metadata_destination).unwrap();
+    let new_repo = RepositoryLoader::new(
+        File::open(root_path()).unwrap(),
+        dir_url(&metadata_destination),
+        dir_url(repo_dir.path().join("targets")),
+    )
+    .load()
+    .unwrap();
+
+    let mut editor = TargetsEditor::from_repo(new_repo, "role1").unwrap();
+    // other.txt isn't delegated to role1, so role1 can't delegate it
+    assert!(matches!(
+        editor.set_role_paths(
+            "role2",
+            PathSet::Paths(vec![PathPattern::new("other.txt").unwrap()]),
+        ),
+        Err(PathsNotDelegated { .. })
+    ));
+    // file*.txt matches file10.txt, which file?.txt doesn't, even though file?.txt matches the
+    // name "file*.txt" itself
+    assert!(matches!(
+        editor.set_role_paths(
+            "role2",
+            PathSet::Paths(vec![PathPattern::new("file*.txt").unwrap()]),
+        ),
+        Err(PathsNotDelegated { .. })
+    ));
+    let signed_role1 = editor
+        .set_role_paths(
+            "role2",
+            PathSet::Paths(vec![PathPattern::new("file2.txt").unwrap()]),
+        )
+        .unwrap()
+        .version(NonZeroU64::new(2).unwrap())
+        .expires(targets_expiration)
+        .create_signed(role1_key)
+        .unwrap();
+    let delegations = signed_role1.signed.targets.delegations.unwrap();
+    assert!(delegations.target_is_delegated(&TargetName::new("file2.txt").unwrap()));
+    assert!(!delegations.target_is_delegated(&TargetName::new("file1.txt").unwrap()));
+}
+
+#[test]
+// Make sure all targets of a role can be renamed with a prefix, and that renames producing invalid
+// or clashing names are rejected
+fn rename_targets_with_prefix() {
+    let mut editor = test_repo_editor();
+    editor
+        .add_target_path(targets_path().join("file1.txt"))
+        .unwrap();
+
+    // Two targets can't be given the same name, nor can a target be given an invalid one
+    assert!(matches!(
+        editor.rename_targets(|_| "same.txt".to_string()),
+        Err(RenameTargetCollision { .. })
+    ));
+    assert!(matches!(
+        editor.rename_targets(|_| String::new()),
+        Err(InvalidTargetName { .. })
+    ));
+
+    editor
+        .rename_targets(|name| format!("migrated/{}", name.raw()))
+        .unwrap();
+    let targets_key: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource { path: key_path() })];
+    let signed_repo = editor.sign(targets_key).unwrap();
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_destination = repo_dir.path().join("metadata");
+    signed_repo.write(&metadata_destination).unwrap();
+    let new_repo = RepositoryLoader::new(
+        File::open(root_path()).unwrap(),
+        dir_url(&metadata_destination),
+        dir_url(repo_dir.path().join("targets")),
+    )
+    .load()
+    .unwrap();
+
+    let mut names = new_repo
+        .targets()
+        .signed
+        .targets
+        .keys()
+        .map(|name| name.raw().to_string())
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, ["migrated/file1.txt", "migrated/file3.txt"]);
+}
+
+/// A minimal schema for custom metadata: each listed field must be present and a string.
+#[derive(Debug)]
+struct RequiredStrings(&'static [&'static str]);
+
+impl CustomValidator for RequiredStrings {
+    fn validate(
+        &self,
+        _name: &TargetName,
+        custom: &HashMap<String, serde_json::Value>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        for field in self.0 {
+            if !custom
+                .get(*field)
+                .map_or(false, serde_json::Value::is_string)
+            {
+                return Err(format!("'{field}' must be a string").into());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+// Make sure targets whose custom metadata fails validation are rejected, and valid ones are added
+fn custom_metadata_validated() {
+    let mut editor = test_repo_editor();
+    editor.custom_validator(RequiredStrings(&["kind"])).unwrap();
+
+    let mut valid = Target::from_path(targets_path().join("file1.txt")).unwrap();
+    valid
+        .custom
+        .insert("kind".to_string(), serde_json::json!("config"));
+    editor.add_target("file1.txt", valid).unwrap();
+
+    let mut invalid = Target::from_path(targets_path().join("file2.txt")).unwrap();
+    invalid
+        .custom
+        .insert("kind".to_string(), serde_json::json!(3));
+    let err = editor.add_target("file2.txt", invalid).unwrap_err();
+    assert!(matches!(err, TargetCustomInvalid { .. }));
+    assert!(err.to_string().contains("'kind' must be a string"));
+
+    let targets_key: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource { path: key_path() })];
+    let signed_repo = editor.sign(targets_key).unwrap();
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_destination = repo_dir.path().join("metadata");
+    signed_repo.write(&metadata_destination).unwrap();
+    let new_repo = RepositoryLoader::new(
+        File::open(root_path()).unwrap(),
+        dir_url(&metadata_destination),
+        dir_url(repo_dir.path().join("targets")),
+    )
+    .load()
+    .unwrap();
+    let targets = &new_repo.targets().signed.targets;
+    assert_eq!(
+        targets[&TargetName::new("file1.txt").unwrap()].custom["kind"],
+        "config"
+    );
+    assert!(!targets.contains_key(&TargetName::new("file2.txt").unwrap()));
+}
+
+/// Signs `editor` with snakeoil.pem, writes its metadata to `metadata_dir` and loads it back.
+fn sign_write_load(editor: RepositoryEditor, metadata_dir: &Path) -> Repository {
+    let key: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource { path: key_path() })];
+    editor.sign(key).unwrap().write(metadata_dir).unwrap();
+    RepositoryLoader::new(
+        File::open(root_path()).unwrap(),
+        dir_url(metadata_dir),
+        dir_url(targets_path()),
+    )
+    .load()
+    .unwrap()
+}
+
+#[test]
+// Make sure bumping the targets version keeps its content and expiration, and bumps snapshot and
+// timestamp along with it
+fn bump_targets_version() {
+    let repo_dir = TempDir::new().unwrap();
+    let repo = sign_write_load(test_repo_editor(), &repo_dir.path().join("v1"));
+    let targets = repo.targets().signed.clone();
+    let snapshot = repo.snapshot().signed.clone();
+    let timestamp = repo.timestamp().signed.clone();
+
+    let mut editor = RepositoryEditor::from_repo(root_path(), repo).unwrap();
+    assert!(matches!(
+        editor.bump_version(RoleType::Root).unwrap_err(),
+        BumpVersionRole { .. }
+    ));
+    editor.bump_version(RoleType::Targets).unwrap();
+    let bumped = sign_write_load(editor, &repo_dir.path().join("v2"));
+
+    let bumped_targets = &bumped.targets().signed;
+    assert_eq!(bumped_targets.version.get(), targets.version.get() + 1);
+    assert_eq!(bumped_targets.expires, targets.expires);
+    assert_eq!(bumped_targets.targets, targets.targets);
+    assert_eq!(
+        bumped.snapshot().signed.version.get(),
+        snapshot.version.get() + 1
+    );
+    assert_eq!(bumped.snapshot().signed.expires, snapshot.expires);
+    assert_eq!(
+        bumped.timestamp().signed.version.get(),
+        timestamp.version.get() + 1
+    );
+    assert_eq!(bumped.timestamp().signed.expires, timestamp.expires);
+}
@@ -0,0 +1,57 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::File;
+use test_utils::{dir_url, test_data};
+use tough::error::Error;
+use tough::{ExpirationEnforcement, RepositoryLoader};
+use url::Url;
+
+mod test_utils;
+
+fn loader(metadata_base_url: Url, targets_base_url: Url) -> RepositoryLoader<File> {
+    let base = test_data().join("tuf-reference-impl");
+    RepositoryLoader::new(
+        File::open(base.join("metadata").join("1.root.json")).unwrap(),
+        metadata_base_url,
+        targets_base_url,
+    )
+    .expiration_enforcement(ExpirationEnforcement::Unsafe)
+}
+
+/// Test that a plaintext HTTP base URL is rejected when HTTPS is required.
+#[test]
+fn require_https_rejects_http() {
+    let base = test_data().join("tuf-reference-impl");
+    let result = loader(
+        dir_url(base.join("metadata")),
+        Url::parse("http://example.com/targets/").unwrap(),
+    )
+    .require_https(true)
+    .load();
+    match result {
+        Err(Error::InsecureUrl { url, .. }) => {
+            assert_eq!(url.as_str(), "http://example.com/targets/");
+        }
+        other => panic!("expected an insecure URL error, got {other:?}"),
+    }
+}
+
+/// Test that `file://` URLs need an explicit opt-in when HTTPS is required.
+#[test]
+fn require_https_file_opt_in() {
+    let base = test_data().join("tuf-reference-impl");
+    let metadata_base_url = dir_url(base.join("metadata"));
+    let targets_base_url = dir_url(base.join("targets"));
+
+    assert!(matches!(
+        loader(metadata_base_url.clone(), targets_base_url.clone())
+            .require_https(false)
+            .load(),
+        Err(Error::InsecureUrl { .. })
+    ));
+    loader(metadata_base_url, targets_base_url)
+        .require_https(true)
+        .load()
+        .unwrap();
+}
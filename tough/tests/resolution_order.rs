@@ -0,0 +1,105 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use chrono::{Duration, Utc};
+use std::num::NonZeroU64;
+use test_utils::test_data;
+use tough::schema::{DelegatedRole, PathPattern, PathSet, ResolutionStep, Signed, Target, Targets};
+use tough::TargetName;
+
+mod test_utils;
+
+fn targets(names: &[&str]) -> Targets {
+    let mut targets = Targets::new(
+        "1.0.0".to_string(),
+        NonZeroU64::new(1).unwrap(),
+        Utc::now().checked_add_signed(Duration::days(7)).unwrap(),
+    );
+    let target = Target::from_path(
+        test_data()
+            .join("tuf-reference-impl")
+            .join("targets")
+            .join("file1.txt"),
+    )
+    .unwrap();
+    for name in names {
+        targets
+            .targets
+            .insert(TargetName::new(*name).unwrap(), target.clone());
+    }
+    targets
+}
+
+fn delegated_role(name: &str, path: &str, terminating: bool, targets: Targets) -> DelegatedRole {
+    DelegatedRole {
+        name: name.to_string(),
+        keyids: Vec::new(),
+        threshold: NonZeroU64::new(1).unwrap(),
+        paths: PathSet::Paths(vec![PathPattern::new(path).unwrap()]),
+        terminating,
+        targets: Some(Signed {
+            signed: targets,
+            signatures: Vec::new(),
+        }),
+    }
+}
+
+fn step(role: &str, depth: usize, lists_target: bool, terminating: bool) -> ResolutionStep {
+    ResolutionStep {
+        role: role.to_string(),
+        depth,
+        lists_target,
+        terminating,
+    }
+}
+
+/// Test that overlapping roles are listed depth first in delegation order, and that the search
+/// ends after a matching terminating delegation.
+#[test]
+fn resolution_order_overlapping_roles() {
+    let mut role1 = targets(&[]);
+    role1
+        .delegations
+        .as_mut()
+        .unwrap()
+        .roles
+        .push(delegated_role(
+            "role1-child",
+            "a.*",
+            false,
+            targets(&["a.txt"]),
+        ));
+    let mut top = targets(&[]);
+    let delegations = top.delegations.as_mut().unwrap();
+    delegations
+        .roles
+        .push(delegated_role("role1", "*.txt", false, role1));
+    // role2 isn't delegated "a.txt", so it isn't consulted
+    delegations
+        .roles
+        .push(delegated_role("role2", "b.txt", false, targets(&["a.txt"])));
+    delegations
+        .roles
+        .push(delegated_role("role3", "a.txt", true, targets(&["a.txt"])));
+    // role4 is past a matching terminating delegation, so it isn't consulted
+    delegations
+        .roles
+        .push(delegated_role("role4", "*", false, targets(&["a.txt"])));
+
+    assert_eq!(
+        top.resolution_order(&TargetName::new("a.txt").unwrap()),
+        vec![
+            step("targets", 0, false, false),
+            step("role1", 1, false, false),
+            step("role1-child", 2, true, false),
+            step("role3", 1, true, true),
+        ]
+    );
+    assert_eq!(
+        top.resolution_order(&TargetName::new("c.bin").unwrap()),
+        vec![
+            step("targets", 0, false, false),
+            step("role4", 1, false, false),
+        ]
+    );
+}
@@ -0,0 +1,95 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use test_utils::{dir_url, test_data};
+use tough::{
+    ExpirationEnforcement, FilesystemTransport, RepositoryLoader, RetryBudget, Transport,
+    TransportError, TransportErrorKind,
+};
+use url::Url;
+
+mod test_utils;
+
+/// A `Transport` that fails the first `failures` fetches of every file.
+#[derive(Debug, Clone)]
+struct FlakyTransport {
+    failures: u32,
+    fetches: Arc<Mutex<HashMap<Url, u32>>>,
+}
+
+impl FlakyTransport {
+    fn new(failures: u32) -> Self {
+        Self {
+            failures,
+            fetches: Arc::default(),
+        }
+    }
+
+    fn total_fetches(&self) -> u32 {
+        self.fetches.lock().unwrap().values().sum()
+    }
+}
+
+impl Transport for FlakyTransport {
+    fn fetch(&self, url: Url) -> Result<Box<dyn Read + Send>, TransportError> {
+        let mut fetches = self.fetches.lock().unwrap();
+        let count = fetches.entry(url.clone()).or_default();
+        *count += 1;
+        if *count <= self.failures {
+            return Err(TransportError::new(TransportErrorKind::Other, url));
+        }
+        FilesystemTransport.fetch(url)
+    }
+}
+
+fn loader(transport: &FlakyTransport, budget: RetryBudget) -> RepositoryLoader<File> {
+    let base = test_data().join("tuf-reference-impl");
+    RepositoryLoader::new(
+        File::open(base.join("metadata").join("1.root.json")).unwrap(),
+        dir_url(base.join("metadata")),
+        dir_url(base.join("targets")),
+    )
+    .transport(transport.clone())
+    .expiration_enforcement(ExpirationEnforcement::Unsafe)
+    .retry_budget(budget)
+}
+
+/// Test that retries are capped across the whole load when every file fails at first.
+#[test]
+fn retry_budget_exhausted() {
+    let transport = FlakyTransport::new(2);
+    let start = Instant::now();
+    let budget = RetryBudget::new(3).backoff(Duration::from_millis(10));
+    assert!(loader(&transport, budget).load().is_err());
+    assert!(start.elapsed() < Duration::from_secs(5));
+    // `2.root.json` spends two retries before it is found to be missing, then `timestamp.json`
+    // spends the last retry and fails
+    assert_eq!(transport.total_fetches(), 5);
+}
+
+/// Test that no retries are made once the budget's time has run out.
+#[test]
+fn retry_budget_max_duration() {
+    let transport = FlakyTransport::new(u32::MAX);
+    let start = Instant::now();
+    let budget = RetryBudget::new(u32::MAX)
+        .tries(u32::MAX)
+        .backoff(Duration::from_millis(10))
+        .max_duration(Duration::from_millis(100));
+    assert!(loader(&transport, budget).load().is_err());
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+/// Test that a load succeeds when the budget covers every retry.
+#[test]
+fn retry_budget_sufficient() {
+    let transport = FlakyTransport::new(2);
+    let budget = RetryBudget::new(100).backoff(Duration::from_millis(1));
+    let repo = loader(&transport, budget).load().unwrap();
+    assert_eq!(repo.delegated_role("role2").unwrap().name, "role2");
+}
@@ -0,0 +1,112 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use chrono::{Duration, Utc};
+use std::fs::File;
+use std::num::NonZeroU64;
+use test_utils::{dir_url, test_data};
+use tough::editor::targets::TargetsEditor;
+use tough::editor::{validate_role_name, RepositoryEditor, MAX_ROLE_NAME_LENGTH};
+use tough::error::Error;
+use tough::key_source::{KeySource, LocalKeySource};
+use tough::schema::{PathPattern, PathSet};
+use tough::RepositoryLoader;
+
+mod test_utils;
+
+/// Names that can't be given to a delegated role.
+fn invalid_names() -> Vec<String> {
+    vec![
+        String::new(),
+        "a".repeat(MAX_ROLE_NAME_LENGTH + 1),
+        "nested/role".to_string(),
+        "nested\\role".to_string(),
+        "../../escaped".to_string(),
+        "..".to_string(),
+        ".".to_string(),
+        "snapshot".to_string(),
+    ]
+}
+
+fn assert_invalid_role_name(result: Result<(), Error>, name: &str) {
+    match result {
+        Err(Error::InvalidRoleName { name: rejected, .. }) => assert_eq!(rejected, name),
+        other => panic!("Expected an 'InvalidRoleName' error for '{name}' but received {other:?}"),
+    }
+}
+
+/// Test that the names of roles that can be safely written are accepted.
+#[test]
+fn valid_role_names() {
+    let longest = "a".repeat(MAX_ROLE_NAME_LENGTH);
+    for name in ["role1", "bins-0f", "a.b", "( ͡° ͜ʖ ͡°)", longest.as_str()] {
+        validate_role_name(name).unwrap();
+    }
+}
+
+/// Test that creating a role with an invalid name fails, whether it is signed on its own or
+/// delegated by the repository editor.
+#[test]
+fn create_role_invalid_name() {
+    let expires = Utc::now().checked_add_signed(Duration::days(7)).unwrap();
+    let one = NonZeroU64::new(1).unwrap();
+    let key: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource {
+        path: test_data().join("snakeoil.pem"),
+    })];
+    let paths = || PathSet::Paths(vec![PathPattern::new("*").unwrap()]);
+    let mut editor =
+        RepositoryEditor::new(test_data().join("simple-rsa").join("root.json")).unwrap();
+    editor
+        .targets_version(one)
+        .unwrap()
+        .targets_expires(expires)
+        .unwrap();
+
+    for name in invalid_names() {
+        assert_invalid_role_name(
+            TargetsEditor::new(&name)
+                .version(one)
+                .expires(expires)
+                .sign(key)
+                .map(|_| ()),
+            &name,
+        );
+        assert_invalid_role_name(
+            editor
+                .delegate_role(&name, key, paths(), one, expires, one)
+                .map(|_| ()),
+            &name,
+        );
+    }
+}
+
+/// Test that adding a role with an invalid name fails before its metadata is looked for.
+#[test]
+fn add_role_invalid_name() {
+    let base = test_data().join("tuf-reference-impl");
+    let root = base.join("metadata").join("1.root.json");
+    let repo = RepositoryLoader::new(
+        File::open(&root).unwrap(),
+        dir_url(base.join("metadata")),
+        dir_url(base.join("targets")),
+    )
+    .load()
+    .unwrap();
+    let mut editor = RepositoryEditor::from_repo(&root, repo).unwrap();
+    let metadata_url = dir_url(base.join("metadata"));
+
+    for name in invalid_names() {
+        assert_invalid_role_name(
+            editor
+                .add_role(
+                    &name,
+                    metadata_url.as_str(),
+                    PathSet::Paths(vec![PathPattern::new("*").unwrap()]),
+                    NonZeroU64::new(1).unwrap(),
+                    None,
+                )
+                .map(|_| ()),
+            &name,
+        );
+    }
+}
@@ -0,0 +1,78 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use chrono::Duration;
+use std::fs::File;
+use std::num::NonZeroU64;
+use test_utils::test_data;
+use tough::schema::decoded::{Decoded, Hex};
+use tough::schema::{Change, RoleDiff, RoleType, Root, RootDiff, Signed};
+
+mod test_utils;
+
+/// Loads a root that uses one key for every role.
+fn root() -> Root {
+    let path = test_data().join("simple-rsa").join("root.json");
+    let root: Signed<Root> = serde_json::from_reader(File::open(path).unwrap()).unwrap();
+    root.signed
+}
+
+/// Test that comparing a root with itself finds no changes.
+#[test]
+fn diff_unchanged() {
+    let diff = root().diff(&root());
+    assert!(diff.is_empty());
+    assert_eq!(diff.to_string(), "");
+}
+
+/// Test the diff of a root that rotated the snapshot key and raised the timestamp threshold.
+#[test]
+fn diff_rotated_snapshot_key() {
+    let old = root();
+    let old_key: Decoded<Hex> = old.roles[&RoleType::Snapshot].keyids[0].clone();
+    let new_key: Decoded<Hex> = vec![0xab; 32].into();
+
+    let mut new = old.clone();
+    new.version = NonZeroU64::new(old.version.get() + 1).unwrap();
+    new.expires = old.expires + Duration::days(30);
+    new.roles.get_mut(&RoleType::Snapshot).unwrap().keyids = vec![new_key.clone()];
+    new.roles.get_mut(&RoleType::Timestamp).unwrap().threshold = NonZeroU64::new(2).unwrap();
+
+    let diff = old.diff(&new);
+    assert_eq!(
+        diff,
+        RootDiff {
+            version: Some(Change {
+                old: old.version,
+                new: new.version
+            }),
+            expires: Some(Change {
+                old: old.expires,
+                new: new.expires
+            }),
+            consistent_snapshot: None,
+            roles: vec![
+                RoleDiff {
+                    role: RoleType::Snapshot,
+                    added_keys: vec![new_key.clone()],
+                    removed_keys: vec![old_key.clone()],
+                    threshold: None,
+                },
+                RoleDiff {
+                    role: RoleType::Timestamp,
+                    added_keys: Vec::new(),
+                    removed_keys: Vec::new(),
+                    threshold: Some(Change {
+                        old: NonZeroU64::new(1).unwrap(),
+                        new: NonZeroU64::new(2).unwrap()
+                    }),
+                },
+            ],
+        }
+    );
+    assert!(diff.to_string().contains(&format!(
+        "snapshot: added key {}\nsnapshot: removed key {}\ntimestamp: threshold 1 -> 2\n",
+        hex::encode(&new_key),
+        hex::encode(&old_key)
+    )));
+}
@@ -0,0 +1,140 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::File;
+use std::num::NonZeroU64;
+use test_utils::test_data;
+use tough::schema::decoded::{Decoded, Hex};
+use tough::schema::{RoleType, Root, RootProblem, Signed};
+
+mod test_utils;
+
+/// Loads a valid root that uses one key for every role.
+fn valid_root() -> Root {
+    let path = test_data().join("simple-rsa").join("root.json");
+    let root: Signed<Root> = serde_json::from_reader(File::open(path).unwrap()).unwrap();
+    root.signed
+}
+
+fn key_id(root: &Root) -> Decoded<Hex> {
+    root.keys.keys().next().unwrap().clone()
+}
+
+/// Test that a valid root has no problems.
+#[test]
+fn validate_valid_root() {
+    assert_eq!(valid_root().validate(), Vec::new());
+}
+
+/// Test that roles that are missing, empty, or listed but not allowed are reported.
+#[test]
+fn validate_roles() {
+    let mut root = valid_root();
+    root.roles.remove(&RoleType::Snapshot);
+    root.roles
+        .get_mut(&RoleType::Timestamp)
+        .unwrap()
+        .keyids
+        .clear();
+    let delegated = root.roles[&RoleType::Targets].clone();
+    root.roles.insert(RoleType::DelegatedTargets, delegated);
+    assert_eq!(
+        root.validate(),
+        vec![
+            RootProblem::MissingRole {
+                role: RoleType::Snapshot
+            },
+            RootProblem::NoKeys {
+                role: RoleType::Timestamp
+            },
+            RootProblem::UnexpectedRole {
+                role: RoleType::DelegatedTargets
+            },
+        ]
+    );
+}
+
+/// Test that duplicated key IDs don't count twice toward a threshold.
+#[test]
+fn validate_duplicate_key_id() {
+    let mut root = valid_root();
+    let key_id = key_id(&root);
+    let role_keys = root.roles.get_mut(&RoleType::Root).unwrap();
+    role_keys.keyids.push(key_id.clone());
+    role_keys.threshold = NonZeroU64::new(2).unwrap();
+    assert_eq!(
+        root.validate(),
+        vec![
+            RootProblem::DuplicateKeyId {
+                role: RoleType::Root,
+                key_id,
+            },
+            RootProblem::ThresholdUnsatisfiable {
+                role: RoleType::Root,
+                threshold: 2,
+                keys: 1,
+            },
+        ]
+    );
+}
+
+/// Test that a key ID with no key in the `keys` map is reported, and doesn't count toward the
+/// role's threshold.
+#[test]
+fn validate_missing_key() {
+    let mut root = valid_root();
+    let missing: Decoded<Hex> = vec![0; 32].into();
+    root.roles
+        .get_mut(&RoleType::Targets)
+        .unwrap()
+        .keyids
+        .push(missing.clone());
+    root.roles.get_mut(&RoleType::Targets).unwrap().threshold = NonZeroU64::new(2).unwrap();
+    assert_eq!(
+        root.validate(),
+        vec![
+            RootProblem::MissingKey {
+                role: RoleType::Targets,
+                key_id: missing,
+            },
+            RootProblem::ThresholdUnsatisfiable {
+                role: RoleType::Targets,
+                threshold: 2,
+                keys: 1,
+            },
+        ]
+    );
+}
+
+/// Test that a key listed under an ID that doesn't match it is reported.
+#[test]
+fn validate_invalid_key_id() {
+    let mut root = valid_root();
+    let key_id = key_id(&root);
+    let key = root.keys.remove(&key_id).unwrap();
+    let wrong: Decoded<Hex> = vec![0; 32].into();
+    root.keys.insert(wrong.clone(), key);
+    for role_keys in root.roles.values_mut() {
+        role_keys.keyids = vec![wrong.clone()];
+    }
+    assert_eq!(
+        root.validate(),
+        vec![RootProblem::InvalidKeyId {
+            key_id: wrong,
+            calculated: key_id,
+        }]
+    );
+}
+
+/// Test that a spec version newer than this library's is reported.
+#[test]
+fn validate_spec_version() {
+    let mut root = valid_root();
+    root.spec_version = "2.0.0".to_owned();
+    assert_eq!(
+        root.validate(),
+        vec![RootProblem::UnknownSpecVersion {
+            spec_version: "2.0.0".to_owned()
+        }]
+    );
+}
@@ -0,0 +1,170 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+mod test_utils;
+
+/// Instead of guarding every individual thing with `#[cfg(feature = "s3")]`, use a module.
+#[cfg(feature = "s3")]
+mod s3 {
+    use crate::test_utils::{read_to_end, test_data, RepoBuilder};
+    use httptest::{matchers::*, responders::*, Expectation, Server};
+    use ring::digest::{digest, SHA256};
+    use std::fs::File;
+    use tempfile::TempDir;
+    use tough::error::Error;
+    use tough::{HttpTransportBuilder, RepositoryLoader, S3Transport, S3TransportBuilder};
+    use tough::{Transport, TransportError, TransportErrorKind};
+    use url::Url;
+
+    const OBJECT_PATH: &str = "/bucket/targets/file1.txt";
+
+    fn file1() -> Vec<u8> {
+        std::fs::read(
+            test_data()
+                .join("tuf-reference-impl")
+                .join("targets")
+                .join("file1.txt"),
+        )
+        .unwrap()
+    }
+
+    fn transport(server: &Server) -> S3Transport {
+        S3TransportBuilder::new()
+            .endpoint(Url::parse(&server.url_str("/")).unwrap())
+            .http_settings(HttpTransportBuilder::new().tries(1))
+            .verify_digest_metadata("sha256")
+            .build()
+    }
+
+    /// Serves the metadata of file1.txt at `OBJECT_PATH`, declaring `declared` as its sha256 if
+    /// given, and the object itself if it's expected to be downloaded. The server fails the test
+    /// if it receives any other request.
+    fn serve_object(server: &Server, declared: Option<&str>, download: bool) {
+        let mut head = status_code(200);
+        if let Some(declared) = declared {
+            head = head.append_header("x-amz-meta-sha256", declared.to_owned());
+        }
+        server.expect(
+            Expectation::matching(request::method_path("HEAD", OBJECT_PATH))
+                .times(1)
+                .respond_with(head),
+        );
+        if download {
+            server.expect(
+                Expectation::matching(request::method_path("GET", OBJECT_PATH))
+                    .times(1)
+                    .respond_with(status_code(200).body(file1())),
+            );
+        }
+    }
+
+    fn fetch_file1(server: &Server) -> Result<Vec<u8>, TransportError> {
+        let url = Url::parse("s3://bucket/targets/file1.txt").unwrap();
+        let sha256 = digest(&SHA256, &file1());
+        transport(server)
+            .fetch_sha256(url, sha256.as_ref())
+            .map(read_to_end)
+    }
+
+    /// Test that an object whose declared digest matches the metadata is downloaded.
+    #[test]
+    fn s3_declared_digest_matches() {
+        let server = Server::run();
+        let declared = hex::encode(digest(&SHA256, &file1()));
+        serve_object(&server, Some(&declared), true);
+        assert_eq!(fetch_file1(&server).unwrap(), file1());
+    }
+
+    /// Test that an object whose declared digest doesn't match the metadata fails before it is
+    /// downloaded.
+    #[test]
+    fn s3_declared_digest_mismatch() {
+        let server = Server::run();
+        serve_object(&server, Some(&"00".repeat(32)), false);
+        let err = fetch_file1(&server).unwrap_err();
+        assert!(matches!(err.kind(), TransportErrorKind::Other));
+        assert!(
+            err.to_string().contains("x-amz-meta-sha256"),
+            "unexpected error: {}",
+            err
+        );
+    }
+
+    /// Test that an object that declares no digest is downloaded as usual.
+    #[test]
+    fn s3_no_declared_digest() {
+        let server = Server::run();
+        serve_object(&server, None, true);
+        assert_eq!(fetch_file1(&server).unwrap(), file1());
+    }
+
+    /// Test that snapshot.json, whose digest timestamp.json records, is checked against the
+    /// digest its object declares before it is downloaded.
+    #[test]
+    fn s3_snapshot_declared_digest_mismatch() {
+        let repo_dir = TempDir::new().unwrap();
+        let metadata_dir = repo_dir.path().join("metadata");
+        RepoBuilder::new().write(&metadata_dir);
+
+        let server = Server::run();
+        server.expect(
+            Expectation::matching(request::method_path("GET", "/bucket/metadata/2.root.json"))
+                .times(1)
+                .respond_with(status_code(404)),
+        );
+        server.expect(
+            Expectation::matching(request::method_path(
+                "GET",
+                "/bucket/metadata/timestamp.json",
+            ))
+            .times(1)
+            .respond_with(
+                status_code(200).body(std::fs::read(metadata_dir.join("timestamp.json")).unwrap()),
+            ),
+        );
+        server.expect(
+            Expectation::matching(request::method_path(
+                "HEAD",
+                "/bucket/metadata/1.snapshot.json",
+            ))
+            .times(1)
+            .respond_with(status_code(200).append_header("x-amz-meta-sha256", "00".repeat(32))),
+        );
+
+        let result = RepositoryLoader::new(
+            File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+            Url::parse("s3://bucket/metadata/").unwrap(),
+            Url::parse("s3://bucket/targets/").unwrap(),
+        )
+        .transport(transport(&server))
+        .load();
+        match result {
+            Err(Error::Transport { url, source, .. }) => {
+                assert_eq!(url.as_str(), "s3://bucket/metadata/1.snapshot.json");
+                assert!(
+                    source.to_string().contains("x-amz-meta-sha256"),
+                    "unexpected error: {}",
+                    source
+                );
+            }
+            other => panic!(
+                "Expected a 'Transport' error but received {:?}",
+                other.map(|_| ())
+            ),
+        }
+    }
+
+    /// Test that a plain fetch, with no digest to compare, doesn't check the object's metadata.
+    #[test]
+    fn s3_fetch_without_digest() {
+        let server = Server::run();
+        server.expect(
+            Expectation::matching(request::method_path("GET", OBJECT_PATH))
+                .times(1)
+                .respond_with(status_code(200).body(file1())),
+        );
+        let url = Url::parse("s3://bucket/targets/file1.txt").unwrap();
+        let body = read_to_end(transport(&server).fetch(url).unwrap());
+        assert_eq!(body, file1());
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::File;
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::{dir_url, test_data};
+use tough::editor::signed::PathExists;
+use tough::{Repository, RepositoryLoader, TargetName};
+
+mod test_utils;
+
+/// Loads the reference implementation's metadata, serving targets from `targets_dir`.
+fn load(targets_dir: &Path) -> Repository {
+    let base = test_data().join("tuf-reference-impl");
+    RepositoryLoader::new(
+        File::open(base.join("metadata").join("1.root.json")).unwrap(),
+        dir_url(base.join("metadata")),
+        dir_url(targets_dir),
+    )
+    .load()
+    .unwrap()
+}
+
+/// Test that a target is saved at the exact path given, and that an existing file is handled as
+/// `PathExists` asks.
+#[test]
+fn save_target_to_path() {
+    let repo = load(&test_data().join("tuf-reference-impl").join("targets"));
+    let file1 = TargetName::new("file1.txt").unwrap();
+    let outdir = TempDir::new().unwrap();
+    let dest = outdir.path().join("nested").join("saved.txt");
+
+    repo.save_target_to(&file1, &dest, PathExists::Fail)
+        .unwrap();
+    let expected = std::fs::read(
+        test_data()
+            .join("tuf-reference-impl")
+            .join("targets")
+            .join("file1.txt"),
+    )
+    .unwrap();
+    assert_eq!(std::fs::read(&dest).unwrap(), expected);
+
+    std::fs::write(&dest, b"existing").unwrap();
+    assert!(repo
+        .save_target_to(&file1, &dest, PathExists::Fail)
+        .is_err());
+    repo.save_target_to(&file1, &dest, PathExists::Skip)
+        .unwrap();
+    assert_eq!(std::fs::read(&dest).unwrap(), b"existing");
+    repo.save_target_to(&file1, &dest, PathExists::Replace)
+        .unwrap();
+    assert_eq!(std::fs::read(&dest).unwrap(), expected);
+}
+
+/// Test that a target that fails verification leaves nothing behind, neither at the destination
+/// nor as a temporary file, and doesn't touch a file it would have replaced.
+#[test]
+fn save_target_to_verification_failure() {
+    let targets_dir = TempDir::new().unwrap();
+    std::fs::write(
+        targets_dir.path().join("file1.txt"),
+        b"This is not the target file that was signed for.",
+    )
+    .unwrap();
+    let repo = load(targets_dir.path());
+    let file1 = TargetName::new("file1.txt").unwrap();
+
+    let outdir = TempDir::new().unwrap();
+    let dest = outdir.path().join("file1.txt");
+    assert!(repo
+        .save_target_to(&file1, &dest, PathExists::Replace)
+        .is_err());
+    assert!(!dest.exists());
+    assert_eq!(std::fs::read_dir(outdir.path()).unwrap().count(), 0);
+
+    std::fs::write(&dest, b"existing").unwrap();
+    assert!(repo
+        .save_target_to(&file1, &dest, PathExists::Replace)
+        .is_err());
+    assert_eq!(std::fs::read(&dest).unwrap(), b"existing");
+    assert_eq!(std::fs::read_dir(outdir.path()).unwrap().count(), 1);
+}
@@ -0,0 +1,114 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#![cfg(feature = "sftp")]
+
+mod test_utils;
+
+use std::env;
+use std::fs::{self, File};
+use test_utils::{read_to_end, test_data};
+use tough::{
+    RepositoryLoader, SftpAuth, SftpHostKey, SftpTransportBuilder, TargetName, Transport,
+    TransportErrorKind,
+};
+use url::Url;
+
+/// Returns a builder for a transport that authenticates with the private key in
+/// `TOUGH_TEST_SFTP_KEY`, and the `sftp://` URL at which the test server serves the
+/// tuf-reference-impl test data, from `TOUGH_TEST_SFTP_URL`, e.g.
+/// `sftp://tough@localhost:2222/data/tuf-reference-impl/`. Returns `None` if either isn't set, so
+/// that tests needing the server are skipped where there is none.
+fn test_server() -> Option<(SftpTransportBuilder, Url)> {
+    let (key, url) = match (
+        env::var_os("TOUGH_TEST_SFTP_KEY"),
+        env::var("TOUGH_TEST_SFTP_URL"),
+    ) {
+        (Some(key), Ok(url)) => (key, url),
+        _ => {
+            eprintln!("Skipping: TOUGH_TEST_SFTP_KEY and TOUGH_TEST_SFTP_URL name no SFTP server");
+            return None;
+        }
+    };
+    let builder = SftpTransportBuilder::new("tough").auth(SftpAuth::PrivateKey {
+        path: key.into(),
+        passphrase: None,
+    });
+    Some((builder, Url::parse(&url).unwrap()))
+}
+
+/// Test that a repository loads over SFTP and its targets match the local test data.
+#[test]
+#[cfg_attr(not(feature = "integ"), ignore)]
+fn sftp_load_repository() {
+    let (builder, base_url) = match test_server() {
+        Some(server) => server,
+        None => return,
+    };
+    let base = test_data().join("tuf-reference-impl");
+    let repo = RepositoryLoader::new(
+        File::open(base.join("metadata").join("1.root.json")).unwrap(),
+        base_url.join("metadata/").unwrap(),
+        base_url.join("targets/").unwrap(),
+    )
+    .transport(builder.build())
+    .load()
+    .unwrap();
+
+    let file1 = TargetName::new("file1.txt").unwrap();
+    assert_eq!(
+        read_to_end(repo.read_target(&file1).unwrap().unwrap()),
+        fs::read(base.join("targets").join("file1.txt")).unwrap()
+    );
+}
+
+/// Test that fetching a file the server doesn't have is a `FileNotFound` error, and that the
+/// session stays usable for later fetches.
+#[test]
+#[cfg_attr(not(feature = "integ"), ignore)]
+fn sftp_file_not_found() {
+    let (builder, base_url) = match test_server() {
+        Some(server) => server,
+        None => return,
+    };
+    let transport = builder.build();
+    let url = base_url.join("metadata/2.root.json").unwrap();
+    let err = transport.fetch(url).err().unwrap();
+    assert!(matches!(err.kind(), TransportErrorKind::FileNotFound));
+    let url = base_url.join("metadata/1.root.json").unwrap();
+    transport.fetch(url).unwrap();
+}
+
+/// Test that a server whose host key doesn't have the pinned fingerprint is refused.
+#[test]
+#[cfg_attr(not(feature = "integ"), ignore)]
+fn sftp_host_key_mismatch() {
+    let (builder, base_url) = match test_server() {
+        Some(server) => server,
+        None => return,
+    };
+    let url = base_url.join("metadata/1.root.json").unwrap();
+    let err = builder
+        .host_key(SftpHostKey::Sha256(vec![0; 32]))
+        .build()
+        .fetch(url)
+        .err()
+        .unwrap();
+    assert!(matches!(err.kind(), TransportErrorKind::Other));
+    assert!(err.to_string().contains("fingerprint"), "{}", err);
+}
+
+/// Test that URLs of other schemes are refused without connecting anywhere.
+#[test]
+fn sftp_unsupported_scheme() {
+    let url = Url::parse("https://example.com/metadata/timestamp.json").unwrap();
+    let err = SftpTransportBuilder::new("tough")
+        .build()
+        .fetch(url)
+        .err()
+        .unwrap();
+    assert!(matches!(
+        err.kind(),
+        TransportErrorKind::UnsupportedUrlScheme
+    ));
+}
@@ -0,0 +1,84 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use chrono::{Duration, Utc};
+use std::collections::HashMap;
+use std::num::NonZeroU64;
+use tempfile::TempDir;
+use test_utils::{dir_url, test_data, RepoBuilder};
+use tough::editor::sign_root;
+use tough::key_source::{KeySource, LocalKeySource};
+use tough::schema::{RoleKeys, RoleType, Root};
+use tough::RepositoryLoader;
+
+mod test_utils;
+
+/// Builds a root that trusts `key_source` for every role, with the given root role threshold.
+fn build_root(key_source: &dyn KeySource, root_threshold: u64) -> Root {
+    let one = NonZeroU64::new(1).unwrap();
+    let key = key_source.as_sign().unwrap().tuf_key();
+    let key_id = key.key_id().unwrap();
+    let roles = [
+        RoleType::Root,
+        RoleType::Snapshot,
+        RoleType::Targets,
+        RoleType::Timestamp,
+    ]
+    .iter()
+    .map(|&role| {
+        let threshold = if role == RoleType::Root {
+            NonZeroU64::new(root_threshold).unwrap()
+        } else {
+            one
+        };
+        let role_keys = RoleKeys {
+            keyids: vec![key_id.clone()],
+            threshold,
+            _extra: HashMap::new(),
+        };
+        (role, role_keys)
+    })
+    .collect();
+    Root {
+        spec_version: "1.0.0".to_owned(),
+        consistent_snapshot: true,
+        version: one,
+        expires: Utc::now().checked_add_signed(Duration::days(7)).unwrap(),
+        keys: HashMap::from([(key_id, key)]),
+        roles,
+        _extra: HashMap::new(),
+    }
+}
+
+/// Test that a root built and signed in code can be used to create and load a repository.
+#[test]
+fn sign_root_in_code() {
+    let keys: Vec<Box<dyn KeySource>> = vec![Box::new(LocalKeySource {
+        path: test_data().join("snakeoil.pem"),
+    })];
+    let root_json = sign_root(build_root(keys[0].as_ref(), 1), &keys).unwrap();
+
+    let repo_dir = TempDir::new().unwrap();
+    let root_path = repo_dir.path().join("root.json");
+    std::fs::write(&root_path, &root_json).unwrap();
+    let metadata_dir = repo_dir.path().join("metadata");
+    RepoBuilder::with_root(&root_path, test_data().join("snakeoil.pem")).write(&metadata_dir);
+
+    let repo = RepositoryLoader::new(
+        root_json.as_slice(),
+        dir_url(&metadata_dir),
+        dir_url(repo_dir.path().join("targets")),
+    )
+    .load()
+    .unwrap();
+    assert_eq!(repo.root().signatures.len(), 1);
+}
+
+/// Test that signing fails when the keys can't meet the root role's threshold.
+#[test]
+fn sign_root_below_threshold() {
+    let keys: Vec<Box<dyn KeySource>> = vec![Box::new(LocalKeySource {
+        path: test_data().join("snakeoil.pem"),
+    })];
+    assert!(sign_root(build_root(keys[0].as_ref(), 2), &keys).is_err());
+}
@@ -0,0 +1,51 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::File;
+use std::num::NonZeroU64;
+use tempfile::TempDir;
+use test_utils::{dir_url, test_data, RepoBuilder};
+use tough::schema::{PathPattern, PathSet, Signed, Snapshot};
+use tough::{RepositoryLoader, TargetName};
+
+mod test_utils;
+
+/// Test that a snapshot listing only the versions of targets metadata can be written and loaded,
+/// including for delegated roles.
+#[test]
+fn hashless_snapshot_meta() {
+    let one = NonZeroU64::new(1).unwrap();
+    let root = test_data().join("simple-rsa").join("root.json");
+    let targets_dir = test_data().join("tuf-reference-impl").join("targets");
+
+    let mut builder = RepoBuilder::new()
+        .delegate(
+            "role1",
+            PathSet::Paths(vec![PathPattern::new("file?.txt").unwrap()]),
+        )
+        .role_target_path("role1", targets_dir.join("file1.txt"));
+    builder.editor().snapshot_meta_hashes(false);
+    let metadata_dir = TempDir::new().unwrap();
+    builder.write(metadata_dir.path());
+
+    let snapshot: Signed<Snapshot> =
+        serde_json::from_reader(File::open(metadata_dir.path().join("1.snapshot.json")).unwrap())
+            .unwrap();
+    for name in ["targets.json", "role1.json"] {
+        let meta = &snapshot.signed.meta[name];
+        assert_eq!(meta.version, one);
+        assert!(meta.hashes.is_none());
+        assert!(meta.length.is_none());
+    }
+
+    let repo = RepositoryLoader::new(
+        File::open(&root).unwrap(),
+        dir_url(metadata_dir.path()),
+        dir_url(&targets_dir),
+    )
+    .load()
+    .unwrap();
+    assert!(repo.delegated_role("role1").is_some());
+    let file1 = TargetName::new("file1.txt").unwrap();
+    assert!(repo.targets().signed.find_target(&file1).is_ok());
+}
@@ -0,0 +1,93 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use chrono::{Duration, Utc};
+use ring::digest::{digest, SHA256};
+use ring::rand::SystemRandom;
+use std::collections::HashMap;
+use std::fs::File;
+use std::num::NonZeroU64;
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::{dir_url, root_key, test_data, RepoBuilder};
+use tough::editor::signed::SignedRole;
+use tough::error::Error::SnapshotMismatch;
+use tough::schema::{Hashes, KeyHolder, Root, Signed, Timestamp, TimestampMeta};
+use tough::RepositoryLoader;
+
+mod test_utils;
+
+/// Writes a small repository signed with the simple-rsa root, then replaces its timestamp with
+/// one that records the given snapshot version and sha256 for `1.snapshot.json`. The snapshot is
+/// also published under the recorded version so it can be fetched.
+fn create_repo(metadata_dir: &Path, snapshot_version: u64, sha256: Option<Vec<u8>>) {
+    let expires = Utc::now().checked_add_signed(Duration::days(7)).unwrap();
+    let one = NonZeroU64::new(1).unwrap();
+    let root_path = test_data().join("simple-rsa").join("root.json");
+    RepoBuilder::new().expires(expires).write(metadata_dir);
+
+    let snapshot = std::fs::read(metadata_dir.join("1.snapshot.json")).unwrap();
+    std::fs::write(
+        metadata_dir.join(format!("{snapshot_version}.snapshot.json")),
+        &snapshot,
+    )
+    .unwrap();
+
+    let mut timestamp = Timestamp::new("1.0.0".to_string(), one, expires);
+    timestamp.meta.insert(
+        "snapshot.json".to_string(),
+        TimestampMeta {
+            length: snapshot.len() as u64,
+            hashes: Hashes {
+                sha256: sha256
+                    .unwrap_or_else(|| digest(&SHA256, &snapshot).as_ref().to_vec())
+                    .into(),
+                _extra: HashMap::new(),
+            },
+            version: NonZeroU64::new(snapshot_version).unwrap(),
+            _extra: HashMap::new(),
+        },
+    );
+    let root: Signed<Root> = serde_json::from_reader(File::open(&root_path).unwrap()).unwrap();
+    let signed_timestamp = SignedRole::new(
+        timestamp,
+        &KeyHolder::Root(root.signed),
+        &root_key(),
+        &SystemRandom::new(),
+    )
+    .unwrap();
+    std::fs::write(
+        metadata_dir.join("timestamp.json"),
+        signed_timestamp.buffer(),
+    )
+    .unwrap();
+}
+
+fn load_property_mismatch(metadata_dir: &Path) -> &'static str {
+    let result = RepositoryLoader::new(
+        File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        dir_url(metadata_dir),
+        dir_url(metadata_dir.join("targets")),
+    )
+    .load();
+    match result {
+        Err(SnapshotMismatch { property, .. }) => property,
+        other => panic!("Expected a 'SnapshotMismatch' error but received {other:?}"),
+    }
+}
+
+/// Test that a timestamp recording the wrong snapshot version is rejected.
+#[test]
+fn snapshot_version_mismatch() {
+    let repo_dir = TempDir::new().unwrap();
+    create_repo(repo_dir.path(), 2, None);
+    assert_eq!(load_property_mismatch(repo_dir.path()), "version");
+}
+
+/// Test that a timestamp recording the wrong snapshot hash is rejected.
+#[test]
+fn snapshot_hash_mismatch() {
+    let repo_dir = TempDir::new().unwrap();
+    create_repo(repo_dir.path(), 1, Some(vec![0; 32]));
+    assert_eq!(load_property_mismatch(repo_dir.path()), "sha256");
+}
@@ -0,0 +1,102 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use log::{Level, Log, Metadata, Record};
+use std::fs::File;
+use std::path::Path;
+use std::sync::Mutex;
+use tempfile::TempDir;
+use test_utils::{dir_url, test_data, RepoBuilder};
+use tough::schema::spec_version_known;
+use tough::{Repository, RepositoryLoader};
+
+mod test_utils;
+
+/// A logger that keeps every warning it receives.
+struct WarningLogger {
+    warnings: Mutex<Vec<String>>,
+}
+
+impl Log for WarningLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= Level::Warn
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if self.enabled(record.metadata()) {
+            self.warnings
+                .lock()
+                .unwrap()
+                .push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: WarningLogger = WarningLogger {
+    warnings: Mutex::new(Vec::new()),
+};
+
+fn load(metadata_dir: &Path) -> Repository {
+    RepositoryLoader::new(
+        File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        dir_url(metadata_dir),
+        dir_url(metadata_dir.join("targets")),
+    )
+    .load()
+    .unwrap()
+}
+
+/// Creates a repository declaring `spec_version` in each of its roles.
+fn create_repo(spec_version: &str, metadata_dir: &Path) {
+    let mut builder = RepoBuilder::new();
+    builder.editor().spec_version(spec_version).unwrap();
+    builder.write(metadata_dir);
+}
+
+/// Test that editing a loaded repository keeps its `spec_version`.
+#[test]
+fn spec_version_preserved() {
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_dir = repo_dir.path().join("metadata");
+    create_repo("1.0.31", &metadata_dir);
+
+    let edited_dir = repo_dir.path().join("edited");
+    RepoBuilder::from_repo(load(&metadata_dir))
+        .version(2)
+        .write(&edited_dir);
+
+    let repo = load(&edited_dir);
+    assert_eq!(repo.targets().signed.spec_version, "1.0.31");
+    assert_eq!(repo.snapshot().signed.spec_version, "1.0.31");
+    assert_eq!(repo.timestamp().signed.spec_version, "1.0.31");
+
+    // A spec version with a different major version can't be written
+    let mut builder = RepoBuilder::from_repo(load(&metadata_dir));
+    assert!(builder.editor().spec_version("2.0.0").is_err());
+}
+
+/// Test that loading metadata with a spec version newer than the supported one succeeds with a
+/// warning.
+#[test]
+fn spec_version_newer_warns() {
+    assert!(spec_version_known("1.0.0"));
+    assert!(!spec_version_known("1.0.31"));
+    assert!(!spec_version_known("not-a-version"));
+
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Warn);
+
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_dir = repo_dir.path().join("metadata");
+    create_repo("1.0.31", &metadata_dir);
+    load(&metadata_dir);
+
+    let warnings = LOGGER.warnings.lock().unwrap();
+    for role in ["timestamp", "snapshot", "targets"] {
+        assert!(warnings.iter().any(|warning| warning.starts_with(&format!(
+            "The '{role}' metadata declares spec_version '1.0.31'"
+        ))));
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::collections::HashMap;
+use std::num::NonZeroU64;
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::{test_data, RepoBuilder};
+use tough::editor::sign_root;
+use tough::key_source::{KeySource, LocalKeySource};
+use tough::schema::{
+    to_stable_json_pretty, PathPattern, PathSet, RoleKeys, RoleType, Root, Signed, Targets,
+};
+
+mod test_utils;
+
+/// Builds and signs a repository with an ed25519 key, whose signatures are deterministic, and
+/// writes its metadata to `outdir`.
+fn write_repo(root_path: &Path, outdir: &Path) {
+    let targets_dir = test_data().join("tuf-reference-impl").join("targets");
+    RepoBuilder::with_root(root_path, test_data().join("targetskey"))
+        .expires("2100-01-01T00:00:00Z".parse().unwrap())
+        .target_path(targets_dir.join("file1.txt"))
+        .target_path(targets_dir.join("file2.txt"))
+        .delegate(
+            "role1",
+            PathSet::Paths(vec![PathPattern::new("file3.txt").unwrap()]),
+        )
+        .write(outdir);
+}
+
+/// Test that signing the same repository again writes byte-for-byte the same metadata, even
+/// though the maps it's built from iterate in a different order each time, and that the metadata
+/// is written with sorted keys.
+#[test]
+fn stable_metadata_bytes() {
+    let keys: Vec<Box<dyn KeySource>> = vec![Box::new(LocalKeySource {
+        path: test_data().join("targetskey"),
+    })];
+    let key = keys[0].as_sign().unwrap().tuf_key();
+    let key_id = key.key_id().unwrap();
+    let one = NonZeroU64::new(1).unwrap();
+    let roles = [
+        RoleType::Root,
+        RoleType::Snapshot,
+        RoleType::Targets,
+        RoleType::Timestamp,
+    ]
+    .iter()
+    .map(|&role| {
+        let role_keys = RoleKeys {
+            keyids: vec![key_id.clone()],
+            threshold: one,
+            _extra: HashMap::new(),
+        };
+        (role, role_keys)
+    })
+    .collect();
+    let root = Root {
+        spec_version: "1.0.0".to_owned(),
+        consistent_snapshot: false,
+        version: one,
+        expires: "2100-01-01T00:00:00Z".parse().unwrap(),
+        keys: HashMap::from([(key_id, key)]),
+        roles,
+        _extra: HashMap::new(),
+    };
+    let root_dir = TempDir::new().unwrap();
+    let root_path = root_dir.path().join("root.json");
+    std::fs::write(&root_path, sign_root(root, &keys).unwrap()).unwrap();
+
+    let first = TempDir::new().unwrap();
+    write_repo(&root_path, first.path());
+    for _ in 0..3 {
+        let again = TempDir::new().unwrap();
+        write_repo(&root_path, again.path());
+        for file in [
+            "1.root.json",
+            "targets.json",
+            "role1.json",
+            "snapshot.json",
+            "timestamp.json",
+        ] {
+            assert_eq!(
+                std::fs::read(first.path().join(file)).unwrap(),
+                std::fs::read(again.path().join(file)).unwrap(),
+                "{} differs",
+                file
+            );
+        }
+    }
+
+    // Re-serializing the written metadata gives the same bytes, so its keys are already sorted
+    let written = std::fs::read(first.path().join("targets.json")).unwrap();
+    let targets: Signed<Targets> = serde_json::from_slice(&written).unwrap();
+    let mut expected = to_stable_json_pretty(&targets).unwrap();
+    expected.push(b'\n');
+    assert_eq!(written, expected);
+    let text = String::from_utf8(written).unwrap();
+    assert!(text.find("\"file1.txt\"").unwrap() < text.find("\"file2.txt\"").unwrap());
+}
@@ -0,0 +1,70 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::File;
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::{dir_url, test_data, RepoBuilder};
+use tough::editor::signed::StaleMetadata;
+use tough::schema::{PathPattern, PathSet};
+use tough::RepositoryLoader;
+
+mod test_utils;
+
+fn file_names(paths: &[std::path::PathBuf]) -> Vec<&str> {
+    paths
+        .iter()
+        .map(|path| path.file_name().unwrap().to_str().unwrap())
+        .collect()
+}
+
+fn load(metadata_dir: &Path) {
+    RepositoryLoader::new(
+        File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        dir_url(metadata_dir),
+        dir_url(test_data().join("tuf-reference-impl").join("targets")),
+    )
+    .load()
+    .unwrap();
+}
+
+/// Test that the metadata of a removed delegated role is listed as stale, kept or removed as
+/// asked, and that older versions of current roles are left alone.
+#[test]
+fn remove_stale_role_metadata() {
+    let metadata_dir = TempDir::new().unwrap();
+    let dir = metadata_dir.path();
+    let first = RepoBuilder::new()
+        .delegate(
+            "role1",
+            PathSet::Paths(vec![PathPattern::new("file?.txt").unwrap()]),
+        )
+        .sign();
+    first.write(dir).unwrap();
+    assert!(first.stale_metadata(dir).unwrap().is_empty());
+    assert!(dir.join("1.role1.json").exists());
+
+    // role1 is no longer delegated
+    let second = RepoBuilder::new().version(2).sign();
+    assert_eq!(
+        file_names(&second.stale_metadata(dir).unwrap()),
+        ["1.role1.json"]
+    );
+    // A dry run doesn't write anything
+    assert!(!dir.join("2.targets.json").exists());
+
+    let stale = second
+        .write_with_stale_metadata(dir, StaleMetadata::Keep)
+        .unwrap();
+    assert_eq!(file_names(&stale), ["1.role1.json"]);
+    assert!(dir.join("1.role1.json").exists());
+
+    let stale = second
+        .write_with_stale_metadata(dir, StaleMetadata::Remove)
+        .unwrap();
+    assert_eq!(file_names(&stale), ["1.role1.json"]);
+    assert!(!dir.join("1.role1.json").exists());
+    assert!(dir.join("1.targets.json").exists());
+    assert!(second.stale_metadata(dir).unwrap().is_empty());
+    load(dir);
+}
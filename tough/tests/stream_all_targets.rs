@@ -0,0 +1,73 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::{self, File};
+use std::io::{Cursor, Read};
+use test_utils::{dir_url, test_data};
+use tough::{
+    ExpirationEnforcement, FilesystemTransport, Repository, RepositoryLoader, Transport,
+    TransportError,
+};
+use url::Url;
+
+mod test_utils;
+
+/// A `Transport` that serves `file2.txt` with its first byte changed.
+#[derive(Debug, Clone, Copy)]
+struct TamperingTransport;
+
+impl Transport for TamperingTransport {
+    fn fetch(&self, url: Url) -> Result<Box<dyn Read + Send>, TransportError> {
+        let mut reader = FilesystemTransport.fetch(url.clone())?;
+        if !url.path().ends_with("/file2.txt") {
+            return Ok(reader);
+        }
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).unwrap();
+        data[0] ^= 1;
+        Ok(Box::new(Cursor::new(data)))
+    }
+}
+
+fn load<T: Transport + Send + Sync + 'static>(transport: T) -> Repository {
+    let base = test_data().join("tuf-reference-impl");
+    RepositoryLoader::new(
+        File::open(base.join("metadata").join("1.root.json")).unwrap(),
+        dir_url(base.join("metadata")),
+        dir_url(base.join("targets")),
+    )
+    .transport(transport)
+    .expiration_enforcement(ExpirationEnforcement::Unsafe)
+    .load()
+    .unwrap()
+}
+
+/// Test that every target, including delegated targets, is streamed with its contents.
+#[test]
+fn stream_all_targets() {
+    let repo = load(FilesystemTransport);
+    let targets_dir = test_data().join("tuf-reference-impl").join("targets");
+
+    let mut names = Vec::new();
+    for item in repo.stream_all_targets() {
+        let (name, mut reader) = item.unwrap();
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).unwrap();
+        assert_eq!(data, fs::read(targets_dir.join(name.raw())).unwrap());
+        names.push(name.raw().to_owned());
+    }
+    names.sort();
+    assert_eq!(names, ["file1.txt", "file2.txt", "file3.txt"]);
+}
+
+/// Test that a target whose contents don't match its hash fails when its stream is read.
+#[test]
+fn stream_all_targets_verified() {
+    let repo = load(TamperingTransport);
+
+    for item in repo.stream_all_targets() {
+        let (name, mut reader) = item.unwrap();
+        let result = reader.read_to_end(&mut Vec::new());
+        assert_eq!(result.is_err(), name.raw() == "file2.txt");
+    }
+}
@@ -0,0 +1,87 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::{self, File};
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::{dir_url, test_data, RepoBuilder};
+use tough::error::Error;
+use tough::schema::RoleType;
+use tough::{RepositoryLoader, TargetName};
+
+mod test_utils;
+
+/// Writes a repository listing file1.txt, then lists file1.txt a second time, with a different
+/// hash, ahead of the original entry in targets.json. Parsers that keep the last value of a
+/// repeated key see the signed, original entry, so the signature still verifies. Snapshot doesn't
+/// list the hash of targets.json, so the edit goes unnoticed there too.
+fn write_repo_with_duplicate_target(metadata_dir: &Path) {
+    let mut builder = RepoBuilder::new().target_path(
+        test_data()
+            .join("tuf-reference-impl")
+            .join("targets")
+            .join("file1.txt"),
+    );
+    builder.editor().snapshot_meta_hashes(false);
+    builder.write(metadata_dir);
+
+    let targets_path = metadata_dir.join("1.targets.json");
+    let targets = fs::read_to_string(&targets_path).unwrap();
+    let duplicated = targets.replacen(
+        r#""file1.txt": {"#,
+        &format!(
+            r#""file1.txt": {{"length": 31, "hashes": {{"sha256": "{}"}}}}, "file1.txt": {{"#,
+            "0".repeat(64)
+        ),
+        1,
+    );
+    assert_ne!(duplicated, targets);
+    fs::write(&targets_path, duplicated).unwrap();
+}
+
+fn loader(metadata_dir: &Path) -> RepositoryLoader<File> {
+    RepositoryLoader::new(
+        File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        dir_url(metadata_dir),
+        dir_url(metadata_dir),
+    )
+}
+
+/// Test that a duplicated target name is silently collapsed by default, and rejected with strict
+/// parsing.
+#[test]
+fn duplicate_target_name() {
+    let repo_dir = TempDir::new().unwrap();
+    write_repo_with_duplicate_target(repo_dir.path());
+
+    let repo = loader(repo_dir.path()).load().unwrap();
+    let target = &repo.targets().signed.targets[&TargetName::new("file1.txt").unwrap()];
+    assert_ne!(target.hashes.sha256.to_vec(), vec![0; 32]);
+
+    match loader(repo_dir.path()).strict_parsing().load() {
+        Err(Error::DuplicateJsonKey { role, source, .. }) => {
+            assert_eq!(role, RoleType::Targets);
+            assert!(matches!(
+                source,
+                tough::schema::Error::DuplicateJsonKey { key, pointer }
+                    if key == "file1.txt" && pointer == "/signed/targets"
+            ));
+        }
+        other => panic!("Expected a 'DuplicateJsonKey' error but received {other:?}"),
+    }
+}
+
+/// Test that a repository without repeated keys loads with strict parsing.
+#[test]
+fn strict_parsing_valid() {
+    let base = test_data().join("tuf-reference-impl");
+    RepositoryLoader::new(
+        File::open(base.join("metadata").join("1.root.json")).unwrap(),
+        dir_url(base.join("metadata")),
+        dir_url(base.join("targets")),
+    )
+    .expiration_enforcement(tough::ExpirationEnforcement::Unsafe)
+    .strict_parsing()
+    .load()
+    .unwrap();
+}
@@ -0,0 +1,76 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::io::Write;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use tempfile::NamedTempFile;
+use tough::schema::Target;
+
+/// Writes `len` bytes of deterministic, non-repeating data to a temporary file.
+fn write_file(len: usize) -> NamedTempFile {
+    let mut file = NamedTempFile::new().unwrap();
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut data = Vec::with_capacity(len);
+    while data.len() < len {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        data.extend_from_slice(&state.to_le_bytes());
+    }
+    data.truncate(len);
+    file.write_all(&data).unwrap();
+    file
+}
+
+fn pipelined(path: &Path, chunk_size: usize, read_ahead: usize) -> Target {
+    Target::from_path_pipelined(
+        path,
+        NonZeroUsize::new(chunk_size).unwrap(),
+        NonZeroUsize::new(read_ahead).unwrap(),
+    )
+    .unwrap()
+}
+
+/// Test that pipelined hashing of a large file matches serial hashing, whether or not the chunk
+/// size divides the file's length, and when a single chunk holds the whole file.
+#[test]
+fn pipelined_hash_matches_serial() {
+    // A little over 24 MiB, so that no power-of-two chunk size divides it
+    let file = write_file(24 * 1024 * 1024 + 12_345);
+    let serial = Target::from_path(file.path()).unwrap();
+    assert_eq!(serial.length, 24 * 1024 * 1024 + 12_345);
+
+    for (chunk_size, read_ahead) in [
+        (4093, 1),
+        (64 * 1024, 4),
+        (1024 * 1024, 2),
+        (32 * 1024 * 1024, 1),
+    ] {
+        let target = pipelined(file.path(), chunk_size, read_ahead);
+        assert_eq!(target, serial, "chunk size {chunk_size}");
+    }
+}
+
+/// Test that pipelined hashing of an empty file matches serial hashing.
+#[test]
+fn pipelined_hash_empty_file() {
+    let file = write_file(0);
+    assert_eq!(
+        pipelined(file.path(), 1024, 1),
+        Target::from_path(file.path()).unwrap()
+    );
+}
+
+/// Test that pipelined hashing of a directory fails like serial hashing does.
+#[test]
+fn pipelined_hash_not_a_file() {
+    let dir = tempfile::TempDir::new().unwrap();
+    assert!(Target::from_path_pipelined(
+        dir.path(),
+        NonZeroUsize::new(1024).unwrap(),
+        NonZeroUsize::new(1).unwrap(),
+    )
+    .is_err());
+}
@@ -0,0 +1,90 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::RepoBuilder;
+use tough::editor::signed::{PathExists, SignedRepository, TargetLayout};
+use tough::error::Error;
+use tough::schema::Target;
+use tough::TargetName;
+
+mod test_utils;
+
+/// Signs a repository listing `a/b/c.bin`, `top.txt` and `../evil.txt`, found in `indir`.
+fn signed_repo(indir: &Path) -> SignedRepository {
+    let mut builder = RepoBuilder::new();
+    for (name, path) in [
+        ("a/b/c.bin", "a/b/c.bin"),
+        ("top.txt", "top.txt"),
+        ("../evil.txt", "evil.txt"),
+    ] {
+        builder = builder.target(name, Target::from_path(indir.join(path)).unwrap());
+    }
+    builder.sign()
+}
+
+/// Returns how many files in `dir` end with `.{name}`, as the consistent snapshot name of the
+/// target file `name` does.
+fn linked(dir: &Path, name: &str) -> usize {
+    let suffix = format!(".{name}");
+    fs::read_dir(dir)
+        .unwrap()
+        .filter(|entry| {
+            entry
+                .as_ref()
+                .unwrap()
+                .file_name()
+                .to_str()
+                .unwrap()
+                .ends_with(&suffix)
+        })
+        .count()
+}
+
+/// Test that a nested layout mirrors the directories of target names, and that a target name
+/// with a `..` component is not linked outside of the output directory.
+#[test]
+fn nested_target_layout() {
+    let indir = TempDir::new().unwrap();
+    fs::create_dir_all(indir.path().join("a").join("b")).unwrap();
+    fs::write(indir.path().join("a").join("b").join("c.bin"), "nested").unwrap();
+    fs::write(indir.path().join("top.txt"), "top").unwrap();
+    fs::write(indir.path().join("evil.txt"), "evil").unwrap();
+    let repo = signed_repo(indir.path());
+
+    let repo_dir = TempDir::new().unwrap();
+    let outdir = repo_dir.path().join("targets");
+    repo.link_targets_with_layout(
+        indir.path(),
+        &outdir,
+        PathExists::Fail,
+        TargetLayout::Nested,
+    )
+    .unwrap();
+    assert_eq!(linked(&outdir.join("a").join("b"), "c.bin"), 1);
+    assert_eq!(linked(&outdir, "top.txt"), 1);
+    // `evil.txt` isn't the name of a target, so it's skipped
+    assert_eq!(linked(&outdir, "evil.txt"), 0);
+
+    let err = repo
+        .link_target_with_layout(
+            &indir.path().join("evil.txt"),
+            &outdir,
+            PathExists::Fail,
+            Some(&TargetName::new("../evil.txt").unwrap()),
+            TargetLayout::Nested,
+        )
+        .unwrap_err();
+    assert!(matches!(err, Error::TargetPathTraversal { .. }));
+    assert_eq!(linked(repo_dir.path(), "evil.txt"), 0);
+    assert_eq!(linked(&outdir, "evil.txt"), 0);
+
+    // The flat layout names targets after their file names only
+    let flat = TempDir::new().unwrap();
+    repo.link_targets(indir.path(), flat.path(), PathExists::Fail)
+        .unwrap();
+    assert_eq!(linked(flat.path(), "top.txt"), 1);
+    assert_eq!(linked(flat.path(), "c.bin"), 0);
+}
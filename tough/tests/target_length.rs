@@ -0,0 +1,75 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::{dir_url, test_data, RepoBuilder};
+use tough::error::Error;
+use tough::schema::Target;
+use tough::{RepositoryLoader, TargetName};
+
+mod test_utils;
+
+/// Creates a repository whose only target, file1.txt, is served with the given body instead of
+/// the contents its metadata was created from.
+fn create_repo(outdir: &Path, body: &[u8]) {
+    let target = Target::from_path(
+        test_data()
+            .join("tuf-reference-impl")
+            .join("targets")
+            .join("file1.txt"),
+    )
+    .unwrap();
+    let filename = format!("{}.file1.txt", hex::encode(&target.hashes.sha256));
+
+    RepoBuilder::new()
+        .target("file1.txt", target)
+        .write(outdir.join("metadata"));
+    std::fs::create_dir_all(outdir.join("targets")).unwrap();
+    std::fs::write(outdir.join("targets").join(filename), body).unwrap();
+}
+
+/// Reads file1.txt from the repository in `dir`, returning the error that failed the read.
+fn read_error(dir: &Path) -> std::io::Error {
+    let repo = RepositoryLoader::new(
+        File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        dir_url(dir.join("metadata")),
+        dir_url(dir.join("targets")),
+    )
+    .load()
+    .unwrap();
+    let mut reader = repo
+        .read_target(&TargetName::new("file1.txt").unwrap())
+        .unwrap()
+        .unwrap();
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf).unwrap_err()
+}
+
+fn assert_length_mismatch(err: &std::io::Error) {
+    match err.get_ref().and_then(|e| e.downcast_ref::<Error>()) {
+        Some(Error::LengthMismatch { expected, .. }) => assert_eq!(*expected, 31),
+        other => panic!("Expected a 'LengthMismatch' error but received {other:?}"),
+    }
+}
+
+/// A body that ends before the declared length is rejected for its length.
+#[test]
+fn target_body_too_short() {
+    let repo_dir = TempDir::new().unwrap();
+    create_repo(repo_dir.path(), b"This is an example");
+    assert_length_mismatch(&read_error(repo_dir.path()));
+}
+
+/// A body that runs past the declared length is rejected for its length.
+#[test]
+fn target_body_too_long() {
+    let repo_dir = TempDir::new().unwrap();
+    create_repo(
+        repo_dir.path(),
+        b"This is an example target file. And then some more.",
+    );
+    assert_length_mismatch(&read_error(repo_dir.path()));
+}
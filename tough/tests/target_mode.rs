@@ -0,0 +1,54 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#![cfg(unix)]
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::RepoBuilder;
+use tough::editor::signed::{PathExists, SignedRepository, TargetMode};
+
+mod test_utils;
+
+/// Signs a repository listing the single target `run.sh`, found in `indir`.
+fn signed_repo(indir: &Path) -> SignedRepository {
+    RepoBuilder::new().target_path(indir.join("run.sh")).sign()
+}
+
+/// Returns the permission bits of the single file in `dir`, following symlinks.
+fn mode_in(dir: &Path) -> u32 {
+    let entry = fs::read_dir(dir).unwrap().next().unwrap().unwrap();
+    fs::metadata(entry.path()).unwrap().permissions().mode() & 0o777
+}
+
+/// Test that linked and copied targets keep the source file's executable bits, and that copied
+/// targets can be given a configured mode instead.
+#[test]
+fn target_mode_preserved_or_set() {
+    let indir = TempDir::new().unwrap();
+    let script = indir.path().join("run.sh");
+    fs::write(&script, "#!/bin/sh\necho hello\n").unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    let repo = signed_repo(indir.path());
+
+    let linked = TempDir::new().unwrap();
+    repo.link_targets(indir.path(), linked.path(), PathExists::Skip)
+        .unwrap();
+    assert_eq!(mode_in(linked.path()), 0o755);
+
+    let copied = TempDir::new().unwrap();
+    repo.copy_targets(indir.path(), copied.path(), PathExists::Skip)
+        .unwrap();
+    assert_eq!(mode_in(copied.path()), 0o755);
+
+    repo.copy_targets_with_mode(
+        indir.path(),
+        copied.path(),
+        PathExists::Replace,
+        TargetMode::Set(0o700),
+    )
+    .unwrap();
+    assert_eq!(mode_in(copied.path()), 0o700);
+}
@@ -0,0 +1,97 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+use test_utils::{dir_url, read_to_end, test_data, RepoBuilder};
+use tough::editor::signed::PathExists;
+use tough::{FilesystemTransport, Prefix, RepositoryLoader, TargetName, Transport, TransportError};
+use url::Url;
+
+mod test_utils;
+
+/// Target names that must be percent-encoded when they are fetched.
+const NAMES: [&str; 3] = ["file with spaces.txt", "ünïcödé 🍺.txt", "50% #1.txt"];
+
+/// A `Transport` that records every URL it fetches.
+#[derive(Debug, Clone, Default)]
+struct RecordingTransport {
+    urls: Arc<Mutex<Vec<Url>>>,
+}
+
+impl Transport for RecordingTransport {
+    fn fetch(&self, url: Url) -> Result<Box<dyn Read + Send>, TransportError> {
+        self.urls.lock().unwrap().push(url.clone());
+        FilesystemTransport.fetch(url)
+    }
+}
+
+/// Creates a repository listing a target, written to `indir`, for each of `NAMES`, and links the
+/// targets into `repo_dir/targets`.
+fn create_repo(indir: &Path, repo_dir: &Path) {
+    let mut builder = RepoBuilder::new();
+    for name in NAMES {
+        let path = indir.join(name);
+        fs::write(&path, name).unwrap();
+        builder = builder.target_path(path);
+    }
+    let signed = builder.write(repo_dir.join("metadata"));
+
+    let targets_dir = repo_dir.join("targets");
+    fs::create_dir_all(&targets_dir).unwrap();
+    signed
+        .link_targets(indir, &targets_dir, PathExists::Fail)
+        .unwrap();
+    // Targets are linked by their unencoded names
+    for name in NAMES {
+        assert!(fs::read_dir(&targets_dir).unwrap().any(|entry| entry
+            .unwrap()
+            .file_name()
+            .to_str()
+            .unwrap()
+            .ends_with(&format!(".{name}"))));
+    }
+}
+
+/// Test that targets whose names contain spaces, unicode and `%` or `#` are fetched by
+/// percent-encoded URLs and saved under their own names.
+#[test]
+fn target_names_percent_encoded() {
+    let indir = TempDir::new().unwrap();
+    let repo_dir = TempDir::new().unwrap();
+    create_repo(indir.path(), repo_dir.path());
+
+    let transport = RecordingTransport::default();
+    let repo = RepositoryLoader::new(
+        File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        dir_url(repo_dir.path().join("metadata")),
+        dir_url(repo_dir.path().join("targets")),
+    )
+    .transport(transport.clone())
+    .load()
+    .unwrap();
+
+    let outdir = TempDir::new().unwrap();
+    for name in NAMES {
+        let target_name = TargetName::new(name).unwrap();
+        let contents = read_to_end(repo.read_target(&target_name).unwrap().unwrap());
+        assert_eq!(contents, name.as_bytes());
+
+        repo.save_target(&target_name, outdir.path(), Prefix::None)
+            .unwrap();
+        assert_eq!(fs::read(outdir.path().join(name)).unwrap(), name.as_bytes());
+    }
+
+    let urls = transport.urls.lock().unwrap();
+    for encoded in [
+        "file%20with%20spaces.txt",
+        "%C3%BCn%C3%AFc%C3%B6d%C3%A9%20%F0%9F%8D%BA.txt",
+        "50%25%20%231.txt",
+    ] {
+        let suffix = format!(".{encoded}");
+        assert!(urls.iter().any(|url| url.path().ends_with(&suffix)));
+    }
+}
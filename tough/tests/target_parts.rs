@@ -0,0 +1,110 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::{dir_url, read_to_end, test_data};
+use tough::{NumberedParts, Repository, RepositoryLoader, TargetName};
+
+mod test_utils;
+
+/// Creates a targets directory in which `file1.txt` of the reference implementation repository is
+/// split into three parts named by `names`, and `file2.txt` is stored whole.
+fn split_targets(dir: &Path, names: &[&str]) -> Vec<u8> {
+    let targets = test_data().join("tuf-reference-impl").join("targets");
+    let file1 = fs::read(targets.join("file1.txt")).unwrap();
+    let third = file1.len() / 3;
+    let parts = [
+        &file1[..third],
+        &file1[third..2 * third],
+        &file1[2 * third..],
+    ];
+    for (name, part) in names.iter().zip(parts) {
+        fs::write(dir.join(name), part).unwrap();
+    }
+    fs::copy(targets.join("file2.txt"), dir.join("file2.txt")).unwrap();
+    file1
+}
+
+fn load(targets_dir: &Path, names: NumberedParts) -> Repository {
+    let metadata = test_data().join("tuf-reference-impl").join("metadata");
+    RepositoryLoader::new(
+        File::open(metadata.join("1.root.json")).unwrap(),
+        dir_url(&metadata),
+        dir_url(targets_dir),
+    )
+    .target_parts(names)
+    .load()
+    .unwrap()
+}
+
+/// Test that a target stored in three parts is assembled and verified, and that a target stored
+/// whole is still fetched.
+#[test]
+fn assemble_target_from_parts() {
+    let targets_dir = TempDir::new().unwrap();
+    let file1 = split_targets(
+        targets_dir.path(),
+        &["file1.txt.part0", "file1.txt.part1", "file1.txt.part2"],
+    );
+    let repo = load(targets_dir.path(), NumberedParts::default());
+
+    let target = repo
+        .read_target(&TargetName::new("file1.txt").unwrap())
+        .unwrap()
+        .unwrap();
+    assert_eq!(read_to_end(target), file1);
+    let target = repo
+        .read_target(&TargetName::new("file2.txt").unwrap())
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        read_to_end(target),
+        fs::read(
+            test_data()
+                .join("tuf-reference-impl")
+                .join("targets")
+                .join("file2.txt")
+        )
+        .unwrap()
+    );
+}
+
+/// Test that parts can be named with another separator, numbered from 1 and zero-padded.
+#[test]
+fn custom_part_names() {
+    let targets_dir = TempDir::new().unwrap();
+    let file1 = split_targets(
+        targets_dir.path(),
+        &["file1.txt.001", "file1.txt.002", "file1.txt.003"],
+    );
+    let repo = load(
+        targets_dir.path(),
+        NumberedParts::new(".").first(1).width(3),
+    );
+
+    let target = repo
+        .read_target(&TargetName::new("file1.txt").unwrap())
+        .unwrap()
+        .unwrap();
+    assert_eq!(read_to_end(target), file1);
+}
+
+/// Test that a target missing its last part fails verification.
+#[test]
+fn missing_part() {
+    let targets_dir = TempDir::new().unwrap();
+    split_targets(
+        targets_dir.path(),
+        &["file1.txt.part0", "file1.txt.part1", "file1.txt.part3"],
+    );
+    let repo = load(targets_dir.path(), NumberedParts::default());
+
+    let mut target = repo
+        .read_target(&TargetName::new("file1.txt").unwrap())
+        .unwrap()
+        .unwrap();
+    assert!(target.read_to_end(&mut Vec::new()).is_err());
+}
@@ -9,7 +9,7 @@ use std::num::NonZeroU64;
 use std::path::Path;
 use tempfile::TempDir;
 use test_utils::{dir_url, test_data, DATA_1, DATA_2, DATA_3};
-use tough::editor::signed::SignedRole;
+use tough::editor::signed::{SignedRole, TargetLayout};
 use tough::editor::RepositoryEditor;
 use tough::key_source::{KeySource, LocalKeySource};
 use tough::schema::{KeyHolder, PathPattern, PathSet, RoleKeys, RoleType, Root, Signed, Target};
@@ -175,3 +175,71 @@ fn safe_target_paths() {
         DATA_3
     );
 }
+
+/// Test that saving targets with the nested layout mirrors the directories of their names, puts
+/// the digest prefix on the file name, and refuses names with `..` components.
+#[test]
+fn save_target_nested_layout() {
+    let tempdir = TempDir::new().unwrap();
+    let root_path = tempdir.path().join("root.json");
+    let keys = create_root(&root_path, false);
+    let one = NonZeroU64::new(1).unwrap();
+
+    let repo_dir = tempdir.path().join("repo");
+    let targets_dir = repo_dir.join("targets");
+    fs::create_dir_all(targets_dir.join("a/b")).unwrap();
+    fs::write(targets_dir.join("a/b/data1.txt"), DATA_1).unwrap();
+    fs::write(targets_dir.join("data2.txt"), DATA_2).unwrap();
+    let nested_name = TargetName::new("a/b/data1.txt").unwrap();
+    let nested_target = Target::from_path(targets_dir.join("a/b/data1.txt")).unwrap();
+    let evil_name = TargetName::new("../data2.txt").unwrap();
+
+    let mut editor = RepositoryEditor::new(&root_path).unwrap();
+    editor
+        .snapshot_version(one)
+        .snapshot_expires(later())
+        .timestamp_version(one)
+        .timestamp_expires(later())
+        .targets_version(one)
+        .unwrap()
+        .targets_expires(later())
+        .unwrap()
+        .add_target(nested_name.clone(), nested_target.clone())
+        .unwrap()
+        .add_target(
+            evil_name.clone(),
+            Target::from_path(targets_dir.join("data2.txt")).unwrap(),
+        )
+        .unwrap();
+    let metadata_dir = repo_dir.join("metadata");
+    editor.sign(&keys).unwrap().write(&metadata_dir).unwrap();
+
+    let loaded_repo = RepositoryLoader::new(
+        File::open(&root_path).unwrap(),
+        dir_url(&metadata_dir),
+        dir_url(&targets_dir),
+    )
+    .load()
+    .unwrap();
+
+    let outdir = tempdir.path().join("outdir");
+    create_dir_all(&outdir).unwrap();
+    loaded_repo
+        .save_target_with_layout(&nested_name, &outdir, Prefix::Digest, TargetLayout::Nested)
+        .unwrap();
+    let digest = hex::encode(&nested_target.hashes.sha256);
+    assert_eq!(
+        fs::read_to_string(outdir.join(format!("a/b/{digest}.data1.txt"))).unwrap(),
+        DATA_1
+    );
+
+    let err = loaded_repo
+        .save_target_with_layout(&evil_name, &outdir, Prefix::None, TargetLayout::Nested)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        tough::error::Error::TargetPathTraversal { .. }
+    ));
+    assert!(!tempdir.path().join("data2.txt").exists());
+    assert!(!outdir.join("data2.txt").exists());
+}
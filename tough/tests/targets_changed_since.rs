@@ -0,0 +1,76 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::File;
+use std::num::NonZeroU64;
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::{dir_url, test_data, RepoBuilder};
+use tough::schema::Target;
+use tough::{Repository, RepositoryLoader, TargetName};
+
+mod test_utils;
+
+fn target(file: &str) -> Target {
+    Target::from_path(
+        test_data()
+            .join("tuf-reference-impl")
+            .join("targets")
+            .join(file),
+    )
+    .unwrap()
+}
+
+fn load(metadata_dir: &Path) -> Repository {
+    RepositoryLoader::new(
+        File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        dir_url(metadata_dir),
+        dir_url(metadata_dir.join("targets")),
+    )
+    .load()
+    .unwrap()
+}
+
+/// Test that the targets added, removed or changed between two snapshots are listed.
+#[test]
+fn targets_changed_between_snapshots() {
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_dir = repo_dir.path().join("metadata");
+
+    // Snapshot 1 lists file1.txt, file2.txt and file3.txt
+    RepoBuilder::new()
+        .target("file1.txt", target("file1.txt"))
+        .target("file2.txt", target("file2.txt"))
+        .target("file3.txt", target("file3.txt"))
+        .write(&metadata_dir);
+
+    // Snapshot 2 removes file1.txt, changes file2.txt, keeps file3.txt and adds file4.txt
+    let mut builder = RepoBuilder::from_repo(load(&metadata_dir)).version(2);
+    builder
+        .editor()
+        .remove_target(&TargetName::new("file1.txt").unwrap())
+        .unwrap();
+    builder
+        .target("file2.txt", target("file3.txt"))
+        .target("file4.txt", target("file1.txt"))
+        .write(&metadata_dir);
+
+    let repo = load(&metadata_dir);
+    assert_eq!(
+        repo.targets_changed_since(NonZeroU64::new(1).unwrap())
+            .unwrap(),
+        vec![
+            TargetName::new("file1.txt").unwrap(),
+            TargetName::new("file2.txt").unwrap(),
+            TargetName::new("file4.txt").unwrap(),
+        ]
+    );
+    assert!(repo
+        .targets_changed_since(NonZeroU64::new(2).unwrap())
+        .unwrap()
+        .is_empty());
+    // Snapshot 3 was never published
+    assert!(repo
+        .targets_changed_since(NonZeroU64::new(3).unwrap())
+        .is_err());
+}
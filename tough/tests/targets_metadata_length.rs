@@ -0,0 +1,72 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::File;
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::{dir_url, test_data, RepoBuilder};
+use tough::error::Error;
+use tough::schema::RoleType;
+use tough::RepositoryLoader;
+
+mod test_utils;
+
+/// Creates a repository, then replaces its targets metadata with the result of `serve`, which is
+/// given the metadata as written. Returns the length of the metadata as written.
+fn create_repo(outdir: &Path, serve: impl Fn(Vec<u8>) -> Vec<u8>) -> u64 {
+    RepoBuilder::new().write(outdir);
+
+    let targets_path = outdir.join("1.targets.json");
+    let targets = std::fs::read(&targets_path).unwrap();
+    let length = targets.len() as u64;
+    std::fs::write(&targets_path, serve(targets)).unwrap();
+    length
+}
+
+/// Loads the repository in `dir`, returning the length declared and read in the error that
+/// failed the load.
+fn load_length_mismatch(dir: &Path) -> (u64, u64) {
+    let result = RepositoryLoader::new(
+        File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        dir_url(dir),
+        dir_url(dir.join("targets")),
+    )
+    .load();
+    match result {
+        Err(Error::SnapshotLengthMismatch {
+            role: RoleType::Targets,
+            expected,
+            read,
+            ..
+        }) => (expected, read),
+        other => panic!("Expected a 'SnapshotLengthMismatch' error but received {other:?}"),
+    }
+}
+
+/// Targets metadata served past the length declared in snapshot.json is rejected for its length,
+/// even though its trailing bytes are only whitespace.
+#[test]
+fn targets_metadata_too_long() {
+    let repo_dir = TempDir::new().unwrap();
+    let length = create_repo(repo_dir.path(), |mut targets| {
+        targets.extend(vec![b' '; 64 * 1024]);
+        targets
+    });
+    let (expected, read) = load_length_mismatch(repo_dir.path());
+    assert_eq!(expected, length);
+    assert!(read > length);
+}
+
+/// Targets metadata that ends before the length declared in snapshot.json is rejected for its
+/// length.
+#[test]
+fn targets_metadata_truncated() {
+    let repo_dir = TempDir::new().unwrap();
+    let length = create_repo(repo_dir.path(), |mut targets| {
+        targets.truncate(targets.len() / 2);
+        targets
+    });
+    let (expected, read) = load_length_mismatch(repo_dir.path());
+    assert_eq!(expected, length);
+    assert_eq!(read, length / 2);
+}
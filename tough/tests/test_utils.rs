@@ -5,8 +5,15 @@
 // cause compiler warnings for unused code, so we suppress them.
 #![allow(unused)]
 
+use chrono::{DateTime, Duration, Utc};
 use std::io::Read;
+use std::num::NonZeroU64;
 use std::path::{Path, PathBuf};
+use tough::editor::signed::SignedRepository;
+use tough::editor::RepositoryEditor;
+use tough::key_source::{KeySource, LocalKeySource};
+use tough::schema::{PathSet, Target};
+use tough::{Repository, TargetName};
 use url::Url;
 
 /// Utilities for tests. Not every test module uses every function, so we suppress unused warnings.
@@ -33,3 +40,224 @@ pub fn read_to_end<R: Read>(mut reader: R) -> Vec<u8> {
     reader.read_to_end(&mut v).unwrap();
     v
 }
+
+/// Returns the key that signs the top-level roles of the simple-rsa root
+pub fn root_key() -> Vec<Box<dyn KeySource>> {
+    key_source(&test_data().join("snakeoil.pem"))
+}
+
+/// Returns the key that signs the roles delegated by a `RepoBuilder`
+pub fn role_key() -> Vec<Box<dyn KeySource>> {
+    key_source(&test_data().join("targetskey"))
+}
+
+/// Builds a repository on the simple-rsa root in which every role has the same version and
+/// expiration, by default 1 and a week from now. Top-level roles are signed with `root_key()` and
+/// delegated roles with `role_key()`, unless the builder was started `with_root`.
+pub struct RepoBuilder {
+    editor: RepositoryEditor,
+    root_key: PathBuf,
+    role_key: PathBuf,
+    version: NonZeroU64,
+    snapshot_version: Option<NonZeroU64>,
+    timestamp_version: Option<NonZeroU64>,
+    expires: DateTime<Utc>,
+    timestamp_expires: Option<DateTime<Utc>>,
+}
+
+impl Default for RepoBuilder {
+    fn default() -> Self {
+        Self::from_editor(
+            RepositoryEditor::new(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        )
+    }
+}
+
+impl RepoBuilder {
+    /// Starts a repository with no targets or delegated roles
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts from the roles of `repo`, which must be signed with the simple-rsa root
+    pub fn from_repo(repo: Repository) -> Self {
+        Self::from_editor(
+            RepositoryEditor::from_repo(test_data().join("simple-rsa").join("root.json"), repo)
+                .unwrap(),
+        )
+    }
+
+    /// Starts a repository on the root.json at `root_path` instead, whose roles, top-level and
+    /// delegated, are all signed with the key at `key_path`
+    pub fn with_root<P1, P2>(root_path: P1, key_path: P2) -> Self
+    where
+        P1: AsRef<Path>,
+        P2: AsRef<Path>,
+    {
+        Self {
+            root_key: key_path.as_ref().to_owned(),
+            role_key: key_path.as_ref().to_owned(),
+            ..Self::from_editor(RepositoryEditor::new(root_path).unwrap())
+        }
+    }
+
+    fn from_editor(editor: RepositoryEditor) -> Self {
+        Self {
+            editor,
+            root_key: test_data().join("snakeoil.pem"),
+            role_key: test_data().join("targetskey"),
+            version: NonZeroU64::new(1).unwrap(),
+            snapshot_version: None,
+            timestamp_version: None,
+            expires: Utc::now().checked_add_signed(Duration::days(7)).unwrap(),
+            timestamp_expires: None,
+        }
+    }
+
+    /// Sets the version of every role, including roles delegated after this call
+    pub fn version(mut self, version: u64) -> Self {
+        self.version = NonZeroU64::new(version).unwrap();
+        self
+    }
+
+    /// Sets the version of snapshot.json alone
+    pub fn snapshot_version(mut self, version: u64) -> Self {
+        self.snapshot_version = Some(NonZeroU64::new(version).unwrap());
+        self
+    }
+
+    /// Sets the version of timestamp.json alone
+    pub fn timestamp_version(mut self, version: u64) -> Self {
+        self.timestamp_version = Some(NonZeroU64::new(version).unwrap());
+        self
+    }
+
+    /// Sets the expiration of every role, including roles delegated after this call
+    pub fn expires(mut self, expires: DateTime<Utc>) -> Self {
+        self.expires = expires;
+        self
+    }
+
+    /// Sets the expiration of timestamp.json alone
+    pub fn timestamp_expires(mut self, expires: DateTime<Utc>) -> Self {
+        self.timestamp_expires = Some(expires);
+        self
+    }
+
+    /// Lists `target` under `name` in the top-level targets role
+    pub fn target(mut self, name: &str, target: Target) -> Self {
+        self.editor
+            .add_target(TargetName::new(name).unwrap(), target)
+            .unwrap();
+        self
+    }
+
+    /// Lists the file at `path` under its file name in the top-level targets role
+    pub fn target_path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.editor.add_target_path(path).unwrap();
+        self
+    }
+
+    /// Delegates `paths` from the top-level targets role to a new, empty role `name`
+    pub fn delegate(mut self, name: &str, paths: PathSet) -> Self {
+        self.editor
+            .delegate_role(
+                name,
+                &key_source(&self.role_key),
+                paths,
+                NonZeroU64::new(1).unwrap(),
+                self.expires,
+                self.version,
+            )
+            .unwrap();
+        self
+    }
+
+    /// Delegates `paths` from the delegated role `delegator` to a new, empty role `name`
+    pub fn delegate_from(self, delegator: &str, name: &str, paths: PathSet) -> Self {
+        let key = key_source(&self.role_key);
+        let (expires, version) = (self.expires, self.version);
+        self.edit_role(delegator, |editor| {
+            editor
+                .delegate_role(
+                    name,
+                    &key,
+                    paths,
+                    NonZeroU64::new(1).unwrap(),
+                    expires,
+                    version,
+                )
+                .unwrap();
+        })
+    }
+
+    /// Lists the file at `path` under its file name in the delegated role `role`
+    pub fn role_target_path<P: AsRef<Path>>(self, role: &str, path: P) -> Self {
+        self.edit_role(role, |editor| {
+            editor.add_target_path(path).unwrap();
+        })
+    }
+
+    /// Gives access to the editor, for changes the builder doesn't cover
+    pub fn editor(&mut self) -> &mut RepositoryEditor {
+        &mut self.editor
+    }
+
+    /// Sets the versions and expirations of the top-level roles and signs the repository
+    pub fn sign(self) -> SignedRepository {
+        let keys = key_source(&self.root_key);
+        self.sign_with(&keys)
+    }
+
+    /// Like `sign`, but signs the top-level roles with `keys`
+    pub fn sign_with(mut self, keys: &[Box<dyn KeySource>]) -> SignedRepository {
+        self.editor
+            .targets_version(self.version)
+            .unwrap()
+            .targets_expires(self.expires)
+            .unwrap()
+            .snapshot_version(self.snapshot_version.unwrap_or(self.version))
+            .snapshot_expires(self.expires)
+            .timestamp_version(self.timestamp_version.unwrap_or(self.version))
+            .timestamp_expires(self.timestamp_expires.unwrap_or(self.expires));
+        self.editor.sign(keys).unwrap()
+    }
+
+    /// Signs the repository and writes its metadata to `metadata_dir`
+    pub fn write<P: AsRef<Path>>(self, metadata_dir: P) -> SignedRepository {
+        let signed = self.sign();
+        signed.write(metadata_dir).unwrap();
+        signed
+    }
+
+    /// Signs the top-level targets role, makes `edit` to the delegated role `role`, then signs
+    /// that role and goes back to editing the top-level targets role
+    fn edit_role<F: FnOnce(&mut RepositoryEditor)>(mut self, role: &str, edit: F) -> Self {
+        self.editor
+            .targets_version(self.version)
+            .unwrap()
+            .targets_expires(self.expires)
+            .unwrap()
+            .sign_targets_editor(&key_source(&self.root_key))
+            .unwrap()
+            .change_delegated_targets(role)
+            .unwrap();
+        edit(&mut self.editor);
+        self.editor
+            .targets_version(self.version)
+            .unwrap()
+            .targets_expires(self.expires)
+            .unwrap()
+            .sign_targets_editor(&key_source(&self.role_key))
+            .unwrap()
+            .change_delegated_targets("targets")
+            .unwrap();
+        self
+    }
+}
+
+fn key_source(path: &Path) -> Vec<Box<dyn KeySource>> {
+    vec![Box::new(LocalKeySource {
+        path: path.to_owned(),
+    })]
+}
@@ -0,0 +1,155 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use chrono::{Duration, Utc};
+use ring::digest::{digest, SHA256};
+use ring::rand::SystemRandom;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::num::NonZeroU64;
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::{dir_url, read_to_end, test_data};
+use tough::editor::signed::SignedRole;
+use tough::editor::RepositoryEditor;
+use tough::key_source::{KeySource, LocalKeySource};
+use tough::schema::{
+    Hashes, KeyHolder, Root, Signature, Signed, Snapshot, Targets, Timestamp, TimestampMeta,
+};
+use tough::sign::Sign;
+use tough::{RepositoryLoader, TargetName};
+
+mod test_utils;
+
+/// Signs the `.to-sign` file of `role` with `key`, as an external signer would, and adds the
+/// signature to the unsigned metadata file `file`.
+fn sign_externally<T>(dir: &Path, role: &str, file: &str, key: &dyn KeySource)
+where
+    T: Serialize + DeserializeOwned,
+{
+    let to_sign = fs::read(dir.join(format!("{}.to-sign", role))).unwrap();
+    let key = key.as_sign().unwrap();
+    let sig = key.sign(&to_sign, &SystemRandom::new()).unwrap();
+
+    let path = dir.join(file);
+    let mut metadata: Signed<T> = serde_json::from_reader(File::open(&path).unwrap()).unwrap();
+    assert!(metadata.signatures.is_empty());
+    metadata.signatures.push(Signature {
+        keyid: key.tuf_key().key_id().unwrap(),
+        sig: sig.into(),
+    });
+    fs::write(&path, serde_json::to_vec_pretty(&metadata).unwrap()).unwrap();
+}
+
+/// Test that unsigned metadata can be signed by an external signer from the `.to-sign` files, and
+/// that the result loads once a timestamp is issued for the signed snapshot.
+#[test]
+fn sign_unsigned_metadata() {
+    let expires = Utc::now().checked_add_signed(Duration::days(7)).unwrap();
+    let one = NonZeroU64::new(1).unwrap();
+    let root_path = test_data().join("simple-rsa").join("root.json");
+    let key = LocalKeySource {
+        path: test_data().join("snakeoil.pem"),
+    };
+    let targets_dir = test_data().join("tuf-reference-impl").join("targets");
+
+    let mut editor = RepositoryEditor::new(&root_path).unwrap();
+    editor
+        .add_target_paths(vec![targets_dir.join("file1.txt")])
+        .unwrap()
+        .targets_version(one)
+        .unwrap()
+        .targets_expires(expires)
+        .unwrap()
+        .snapshot_version(one)
+        .snapshot_expires(expires);
+    let unsigned = editor.unsigned().unwrap();
+    assert!(unsigned.targets().signed().signatures.is_empty());
+    assert!(unsigned.snapshot().signed().signatures.is_empty());
+    assert!(unsigned.snapshot().signed().signed.meta["targets.json"]
+        .hashes
+        .is_none());
+
+    let metadata_dir = TempDir::new().unwrap();
+    let dir = metadata_dir.path();
+    unsigned.write(dir).unwrap();
+    assert_eq!(
+        fs::read(dir.join("targets.to-sign")).unwrap(),
+        unsigned.targets_to_sign().unwrap()
+    );
+
+    // The external signer signs targets and snapshot, in any order
+    sign_externally::<Snapshot>(dir, "snapshot", "1.snapshot.json", &key);
+    sign_externally::<Targets>(dir, "targets", "1.targets.json", &key);
+
+    // The timestamp is issued once the signed snapshot is known
+    let snapshot = fs::read(dir.join("1.snapshot.json")).unwrap();
+    let mut timestamp = Timestamp::new("1.0.0".to_string(), one, expires);
+    timestamp.meta.insert(
+        "snapshot.json".to_string(),
+        TimestampMeta {
+            length: snapshot.len() as u64,
+            hashes: Hashes {
+                sha256: digest(&SHA256, &snapshot).as_ref().to_vec().into(),
+                _extra: HashMap::new(),
+            },
+            version: one,
+            _extra: HashMap::new(),
+        },
+    );
+    let root: Signed<Root> = serde_json::from_reader(File::open(&root_path).unwrap()).unwrap();
+    let keys: &[Box<dyn KeySource>] = &[Box::new(key)];
+    SignedRole::new(
+        timestamp,
+        &KeyHolder::Root(root.signed),
+        keys,
+        &SystemRandom::new(),
+    )
+    .unwrap()
+    .write(dir, true)
+    .unwrap();
+
+    let repo = RepositoryLoader::new(
+        File::open(&root_path).unwrap(),
+        dir_url(dir),
+        dir_url(&targets_dir),
+    )
+    .load()
+    .unwrap();
+    let file1 = TargetName::new("file1.txt").unwrap();
+    assert_eq!(
+        read_to_end(repo.read_target(&file1).unwrap().unwrap()),
+        fs::read(targets_dir.join("file1.txt")).unwrap()
+    );
+}
+
+/// Test that targets metadata that hasn't been signed externally doesn't verify.
+#[test]
+fn unsigned_metadata_does_not_verify() {
+    let expires = Utc::now().checked_add_signed(Duration::days(7)).unwrap();
+    let one = NonZeroU64::new(1).unwrap();
+    let root_path = test_data().join("simple-rsa").join("root.json");
+
+    let mut editor = RepositoryEditor::new(&root_path).unwrap();
+    editor
+        .targets_version(one)
+        .unwrap()
+        .targets_expires(expires)
+        .unwrap()
+        .snapshot_version(one)
+        .snapshot_expires(expires);
+    let metadata_dir = TempDir::new().unwrap();
+    editor
+        .unsigned()
+        .unwrap()
+        .write(metadata_dir.path())
+        .unwrap();
+
+    let targets: Signed<Targets> =
+        serde_json::from_reader(File::open(metadata_dir.path().join("1.targets.json")).unwrap())
+            .unwrap();
+    let root: Signed<Root> = serde_json::from_reader(File::open(&root_path).unwrap()).unwrap();
+    assert!(root.signed.verify_role(&targets).is_err());
+}
@@ -0,0 +1,74 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::File;
+use std::num::NonZeroU64;
+use tempfile::TempDir;
+use test_utils::{dir_url, test_data, RepoBuilder};
+use tough::{FilesystemTransport, RepositoryLoader};
+
+mod test_utils;
+
+fn version(version: u64) -> NonZeroU64 {
+    NonZeroU64::new(version).unwrap()
+}
+
+/// Test that an up-to-date repository reports no update, and a stale one reports the remote
+/// snapshot version without fetching the new snapshot.
+#[test]
+fn update_available() {
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_dir = repo_dir.path().join("metadata");
+    RepoBuilder::new().version(1).write(&metadata_dir);
+
+    let repo = RepositoryLoader::new(
+        File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        dir_url(&metadata_dir),
+        dir_url(repo_dir.path().join("targets")),
+    )
+    .load()
+    .unwrap();
+    assert_eq!(
+        repo.is_update_available(&FilesystemTransport).unwrap(),
+        (false, version(1))
+    );
+
+    // Only timestamp may be fetched, so the new snapshot and targets need not exist
+    RepoBuilder::new().version(2).write(&metadata_dir);
+    std::fs::remove_file(metadata_dir.join("2.snapshot.json")).unwrap();
+    std::fs::remove_file(metadata_dir.join("2.targets.json")).unwrap();
+    assert_eq!(
+        repo.is_update_available(&FilesystemTransport).unwrap(),
+        (true, version(2))
+    );
+    assert_eq!(repo.snapshot().signed.version, version(1));
+}
+
+/// Test that checking for an update doesn't save the remote timestamp to the datastore, so the
+/// trusted timestamp stays consistent with the trusted snapshot.
+#[test]
+fn update_available_not_saved() {
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_dir = repo_dir.path().join("metadata");
+    let datastore = TempDir::new().unwrap();
+    RepoBuilder::new().version(1).write(&metadata_dir);
+
+    let repo = RepositoryLoader::new(
+        File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        dir_url(&metadata_dir),
+        dir_url(repo_dir.path().join("targets")),
+    )
+    .datastore(datastore.path())
+    .load()
+    .unwrap();
+
+    RepoBuilder::new().version(2).write(&metadata_dir);
+    assert_eq!(
+        repo.is_update_available(&FilesystemTransport).unwrap(),
+        (true, version(2))
+    );
+    let saved: tough::schema::Signed<tough::schema::Timestamp> =
+        serde_json::from_reader(File::open(datastore.path().join("timestamp.json")).unwrap())
+            .unwrap();
+    assert_eq!(saved.signed.version, version(1));
+}
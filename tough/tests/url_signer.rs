@@ -0,0 +1,116 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::File;
+use std::io::Read;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use test_utils::{dir_url, read_to_end, test_data};
+use tough::{
+    ExpirationEnforcement, FilesystemTransport, Repository, RepositoryLoader, TargetName,
+    Transport, TransportError, UrlSigner,
+};
+use url::Url;
+
+mod test_utils;
+
+/// A `Transport` that records every URL it fetches. The filesystem transport ignores the query,
+/// so signed URLs still resolve to the file.
+#[derive(Debug, Clone, Default)]
+struct RecordingTransport {
+    urls: Arc<Mutex<Vec<Url>>>,
+}
+
+impl Transport for RecordingTransport {
+    fn fetch(&self, url: Url) -> Result<Box<dyn Read + Send>, TransportError> {
+        self.urls.lock().unwrap().push(url.clone());
+        FilesystemTransport.fetch(url)
+    }
+}
+
+/// A `UrlSigner` that appends a new token to each URL it signs.
+#[derive(Debug, Default)]
+struct TokenSigner {
+    count: AtomicUsize,
+}
+
+impl UrlSigner for TokenSigner {
+    fn sign_url(&self, mut url: Url) -> Result<Url, TransportError> {
+        let token = self.count.fetch_add(1, Ordering::SeqCst);
+        url.query_pairs_mut()
+            .append_pair("token", &format!("t{token}"));
+        Ok(url)
+    }
+}
+
+fn loader(transport: &RecordingTransport) -> RepositoryLoader<File> {
+    let base = test_data().join("tuf-reference-impl");
+    RepositoryLoader::new(
+        File::open(base.join("metadata").join("1.root.json")).unwrap(),
+        dir_url(base.join("metadata")),
+        dir_url(base.join("targets")),
+    )
+    .transport(transport.clone())
+    .expiration_enforcement(ExpirationEnforcement::Unsafe)
+}
+
+fn read_file1(repo: &Repository) {
+    let file1 = TargetName::new("file1.txt").unwrap();
+    assert_eq!(
+        read_to_end(repo.read_target(&file1).unwrap().unwrap()),
+        &b"This is an example target file."[..]
+    );
+}
+
+/// Test that only target URLs are signed, each with a fresh token, when a target signer is set.
+#[test]
+fn target_url_signer() {
+    let transport = RecordingTransport::default();
+    let repo = loader(&transport)
+        .target_url_signer(TokenSigner::default())
+        .load()
+        .unwrap();
+    assert!(transport
+        .urls
+        .lock()
+        .unwrap()
+        .iter()
+        .all(|url| url.query().is_none()));
+
+    read_file1(&repo);
+    read_file1(&repo);
+    let urls = transport.urls.lock().unwrap();
+    let queries = urls.iter().filter_map(Url::query).collect::<Vec<_>>();
+    assert_eq!(queries, ["token=t0", "token=t1"]);
+    assert!(urls
+        .iter()
+        .filter(|url| url.query().is_some())
+        .all(|url| url.path().ends_with("/targets/file1.txt")));
+}
+
+/// Test that metadata URLs are signed when a metadata signer is set.
+#[test]
+fn metadata_url_signer() {
+    let transport = RecordingTransport::default();
+    let repo = loader(&transport)
+        .metadata_url_signer(TokenSigner::default())
+        .load()
+        .unwrap();
+    assert!(transport
+        .urls
+        .lock()
+        .unwrap()
+        .iter()
+        .all(|url| url.query().is_some()));
+
+    // Targets are fetched unsigned
+    read_file1(&repo);
+    assert!(transport
+        .urls
+        .lock()
+        .unwrap()
+        .last()
+        .unwrap()
+        .query()
+        .is_none());
+}
@@ -0,0 +1,66 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs::File;
+use std::io::{Cursor, Read};
+use test_utils::{dir_url, test_data};
+use tough::{Repository, RepositoryLoader, TargetName};
+
+mod test_utils;
+
+fn load_reference_impl() -> Repository {
+    let base = test_data().join("tuf-reference-impl");
+    RepositoryLoader::new(
+        File::open(base.join("metadata").join("1.root.json")).unwrap(),
+        dir_url(base.join("metadata")),
+        dir_url(base.join("targets")),
+    )
+    .load()
+    .unwrap()
+}
+
+/// Test that target bytes held in memory are verified against the delegated role listing them.
+#[test]
+fn verify_delegated_target_from_memory() {
+    let repo = load_reference_impl();
+    let file3 = TargetName::new("file3.txt").unwrap();
+    let cached = std::fs::read(
+        test_data()
+            .join("tuf-reference-impl")
+            .join("targets")
+            .join("file3.txt"),
+    )
+    .unwrap();
+
+    let mut verified = Vec::new();
+    repo.verify_delegated_target("role1", &file3, Cursor::new(cached.clone()))
+        .unwrap()
+        .unwrap()
+        .read_to_end(&mut verified)
+        .unwrap();
+    assert_eq!(verified, cached);
+
+    let mut tampered = cached;
+    tampered[0] ^= 1;
+    assert!(repo
+        .verify_delegated_target("role1", &file3, Cursor::new(tampered))
+        .unwrap()
+        .unwrap()
+        .read_to_end(&mut Vec::new())
+        .is_err());
+}
+
+/// Test that only targets listed by the given delegated role are verified.
+#[test]
+fn verify_delegated_target_wrong_role() {
+    let repo = load_reference_impl();
+    let file1 = TargetName::new("file1.txt").unwrap();
+    // file1.txt is listed by the top-level targets role, not by role1
+    assert!(repo
+        .verify_delegated_target("role1", &file1, Cursor::new(Vec::new()))
+        .unwrap()
+        .is_none());
+    assert!(repo
+        .verify_delegated_target("no-such-role", &file1, Cursor::new(Vec::new()))
+        .is_err());
+}
@@ -1,18 +1,21 @@
 // Copyright 2019 Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use crate::common::load_metadata_repo;
+use crate::common::{load_metadata_repo, STDOUT_OUTDIR};
 use crate::datetime::parse_datetime;
 use crate::error::{self, Result};
 use crate::source::parse_key_source;
 use chrono::{DateTime, Utc};
 use clap::Parser;
-use snafu::ResultExt;
-use std::collections::HashMap;
+use snafu::{ensure, OptionExt, ResultExt};
+use std::collections::{BTreeMap, HashMap};
 use std::num::NonZeroU64;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tough::editor::targets::TargetsEditor;
 use tough::key_source::KeySource;
+use tough::schema::decoded::{Decoded, Hex};
+use tough::schema::key::Key;
+use tough::schema::Targets;
 use url::Url;
 
 #[derive(Debug, Parser)]
@@ -46,25 +49,66 @@ pub(crate) struct AddKeyArgs {
     #[clap(short = 'o', long = "outdir")]
     outdir: PathBuf,
 
-    /// The role for the keys to be added to
+    /// A role delegated by the signing role, or by a role below it, for the keys to be added to;
+    /// may be given more than once to add the keys to several roles. Each role that delegates one
+    /// of them is signed again.
     #[clap(long = "delegated-role")]
-    delegated_role: Option<String>,
+    delegated_roles: Vec<String>,
 }
 
 impl AddKeyArgs {
     pub(crate) fn run(&self, role: &str) -> Result<()> {
+        // Each role that delegates one of the keys' roles is written, so there isn't a single
+        // role to write to stdout
+        ensure!(
+            self.outdir != Path::new(STDOUT_OUTDIR),
+            error::StdoutAddKeySnafu
+        );
         // load the repo
         let repository = load_metadata_repo(&self.root, self.metadata_base_url.clone())?;
-        self.add_key(
-            role,
-            TargetsEditor::from_repo(repository, role)
-                .context(error::EditorFromRepoSnafu { path: &self.root })?,
-        )
+        let key_pairs = self.key_pairs()?;
+        if self.delegated_roles.is_empty() {
+            let editor = TargetsEditor::from_repo(repository, role)
+                .context(error::EditorFromRepoSnafu { path: &self.root })?;
+            return self.add_key(&key_pairs, vec![(role.to_string(), editor, Vec::new())]);
+        }
+
+        // Check every role before changing any, so a typo doesn't leave a partial update, and
+        // group them by the role that delegates them, which is the one that lists their keys
+        let targets = if role == "targets" {
+            &repository.targets().signed
+        } else {
+            &repository
+                .targets()
+                .signed
+                .delegated_targets(role)
+                .context(error::DelegateeNotFoundSnafu { role })?
+                .signed
+        };
+        let mut by_delegator = BTreeMap::<String, Vec<String>>::new();
+        for delegated_role in &self.delegated_roles {
+            let delegator = delegator_of(targets, role, delegated_role).context(
+                error::NotDelegatedBySnafu {
+                    role: delegated_role,
+                    delegator: role,
+                },
+            )?;
+            by_delegator
+                .entry(delegator)
+                .or_default()
+                .push(delegated_role.clone());
+        }
+        let mut editors = Vec::new();
+        for (delegator, delegated_roles) in by_delegator {
+            let editor = TargetsEditor::from_repo(repository.clone(), &delegator)
+                .context(error::EditorFromRepoSnafu { path: &self.root })?;
+            editors.push((delegator, editor, delegated_roles));
+        }
+        self.add_key(&key_pairs, editors)
     }
 
-    /// Adds keys to a role using targets Editor
-    fn add_key(&self, role: &str, mut editor: TargetsEditor) -> Result<()> {
-        // create the keypairs to add
+    /// Returns the keys to add, by key ID.
+    fn key_pairs(&self) -> Result<HashMap<Decoded<Hex>, Key>> {
         let mut key_pairs = HashMap::new();
         for source in &self.new_keys {
             let key_pair = source
@@ -79,20 +123,72 @@ impl AddKeyArgs {
                 key_pair,
             );
         }
-        let updated_role = editor
-            .add_key(key_pairs, self.delegated_role.as_deref())
-            .context(error::LoadMetadataSnafu)?
-            .version(self.version)
-            .expires(self.expires)
-            .sign(&self.keys)
-            .context(error::SignRepoSnafu)?;
+        Ok(key_pairs)
+    }
+
+    /// Adds keys to the roles each editor's role delegates, or to that role itself if there are
+    /// none, and signs each editor's role. Nothing is written until every role is signed.
+    fn add_key(
+        &self,
+        key_pairs: &HashMap<Decoded<Hex>, Key>,
+        editors: Vec<(String, TargetsEditor, Vec<String>)>,
+    ) -> Result<()> {
+        let mut key_ids = key_pairs.keys().map(hex::encode).collect::<Vec<_>>();
+        key_ids.sort();
+        let key_ids = key_ids.join(", ");
+        let mut updated_roles = Vec::new();
+        for (role, mut editor, delegated_roles) in editors {
+            if delegated_roles.is_empty() {
+                editor
+                    .add_key(key_pairs.clone(), None)
+                    .context(error::LoadMetadataSnafu)?;
+            } else {
+                for delegated_role in &delegated_roles {
+                    editor
+                        .add_key(key_pairs.clone(), Some(delegated_role))
+                        .context(error::LoadMetadataSnafu)?;
+                }
+            }
+            let updated_role = editor
+                .version(self.version)
+                .expires(self.expires)
+                .sign(&self.keys)
+                .context(error::SignRepoSnafu)?;
+            updated_roles.push((role, updated_role, delegated_roles));
+        }
+
         let metadata_destination_out = &self.outdir.join("metadata");
-        updated_role
-            .write(metadata_destination_out, false)
-            .context(error::WriteRolesSnafu {
-                roles: [role.to_string()].to_vec(),
-            })?;
+        for (role, updated_role, delegated_roles) in updated_roles {
+            updated_role
+                .write(metadata_destination_out, false)
+                .context(error::WriteRolesSnafu {
+                    roles: [role.clone()].to_vec(),
+                })?;
+            for delegated_role in delegated_roles {
+                println!("Added key {} to role '{}'", key_ids, delegated_role);
+            }
+            println!("Signed role '{}'", role);
+        }
 
         Ok(())
     }
 }
+
+/// Returns the name of the role that directly delegates `role`, looking in `targets`, the
+/// metadata of the role `name`, and in the roles below it.
+fn delegator_of(targets: &Targets, name: &str, role: &str) -> Option<String> {
+    let delegations = targets.delegations.as_ref()?;
+    if delegations
+        .roles
+        .iter()
+        .any(|delegated_role| delegated_role.name == role)
+    {
+        return Some(name.to_string());
+    }
+    delegations.roles.iter().find_map(|delegated_role| {
+        delegated_role
+            .targets
+            .as_ref()
+            .and_then(|targets| delegator_of(&targets.signed, &delegated_role.name, role))
+    })
+}
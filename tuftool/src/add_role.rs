@@ -1,17 +1,20 @@
 // Copyright 2019 Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use crate::common::load_metadata_repo;
+use crate::common::{load_metadata_repo, write_delegated_targets, STDOUT_OUTDIR};
 use crate::datetime::parse_datetime;
 use crate::error::{self, Result};
 use crate::source::parse_key_source;
 use chrono::{DateTime, Utc};
 use clap::Parser;
-use snafu::{OptionExt, ResultExt};
+use snafu::{ensure, OptionExt, ResultExt};
+use std::collections::HashMap;
 use std::num::NonZeroU64;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tough::editor::{targets::TargetsEditor, RepositoryEditor};
 use tough::key_source::KeySource;
+use tough::schema::decoded::{Decoded, Hex};
+use tough::schema::key::Key;
 use tough::schema::{PathHashPrefix, PathPattern, PathSet};
 use url::Url;
 
@@ -50,7 +53,13 @@ pub(crate) struct AddRoleArgs {
     #[clap(short = 't', long = "threshold")]
     threshold: NonZeroU64,
 
-    /// The directory where the repository will be written
+    /// Key files of the delegatee, any `threshold` of which must sign it; defaults to the keys
+    /// listed in the incoming role's own delegations
+    #[clap(long = "delegatee-key", parse(try_from_str = parse_key_source))]
+    delegatee_keys: Vec<Box<dyn KeySource>>,
+
+    /// The directory where the repository will be written; use '-' to write the signed role to
+    /// stdout (not supported with `--sign-all`)
     #[clap(short = 'o', long = "outdir")]
     outdir: PathBuf,
 
@@ -106,6 +115,29 @@ impl AddRoleArgs {
         }
     }
 
+    /// Returns the delegatee's public keys, if any were given, so they can be listed in the
+    /// delegation instead of the incoming role's own keys
+    fn delegatee_key_pairs(&self) -> Result<Option<HashMap<Decoded<Hex>, Key>>> {
+        if self.delegatee_keys.is_empty() {
+            return Ok(None);
+        }
+        let mut key_pairs = HashMap::new();
+        for source in &self.delegatee_keys {
+            let key_pair = source
+                .as_sign()
+                .context(error::KeyPairFromKeySourceSnafu)?
+                .tuf_key();
+            key_pairs.insert(
+                key_pair
+                    .key_id()
+                    .context(error::JsonSerializationSnafu {})?
+                    .clone(),
+                key_pair,
+            );
+        }
+        Ok(Some(key_pairs))
+    }
+
     #[allow(clippy::option_if_let_else)]
     /// Adds a role to metadata using targets Editor
     fn add_role(&self, role: &str, mut editor: TargetsEditor) -> Result<()> {
@@ -123,26 +155,29 @@ impl AddRoleArgs {
                 self.indir.as_str(),
                 paths,
                 self.threshold,
-                None,
+                self.delegatee_key_pairs()?,
             )
             .context(error::LoadMetadataSnafu)?
             .version(self.version)
             .expires(self.expires)
             .sign(&self.keys)
             .context(error::SignRepoSnafu)?;
-        let metadata_destination_out = &self.outdir.join("metadata");
-        updated_role
-            .write(metadata_destination_out, false)
-            .context(error::WriteRolesSnafu {
-                roles: [self.delegatee.clone(), role.to_string()].to_vec(),
-            })?;
-
-        Ok(())
+        write_delegated_targets(
+            updated_role,
+            &self.outdir,
+            role,
+            [self.delegatee.clone(), role.to_string()].to_vec(),
+        )
     }
 
     #[allow(clippy::option_if_let_else)]
     /// Adds a role to metadata using repo Editor
     fn with_repo_editor(&self, role: &str, mut editor: RepositoryEditor) -> Result<()> {
+        // The whole repository is signed, so there isn't a single role to write to stdout
+        ensure!(
+            self.outdir != Path::new(STDOUT_OUTDIR),
+            error::StdoutSignAllSnafu
+        );
         // Since we are using repo editor we will sign snapshot and timestamp
         // Check to make sure all versions and expirations are present
         let snapshot_version = self.snapshot_version.context(error::MissingSnafu {
@@ -188,7 +223,7 @@ impl AddRoleArgs {
                 self.indir.as_str(),
                 paths,
                 self.threshold,
-                None,
+                self.delegatee_key_pairs()?,
             )
             .context(error::LoadMetadataSnafu)?
             .targets_version(self.version)
@@ -0,0 +1,77 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::common::load_metadata_repo;
+use crate::error::{self, Result};
+use crate::source::parse_key_source;
+use clap::{Parser, ValueEnum};
+use snafu::ResultExt;
+use std::path::PathBuf;
+use tough::editor::RepositoryEditor;
+use tough::key_source::KeySource;
+use tough::schema::RoleType;
+use url::Url;
+
+/// The top-level roles whose version can be bumped.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub(crate) enum BumpRole {
+    /// targets.json, along with snapshot.json and timestamp.json
+    Targets,
+    /// snapshot.json, along with timestamp.json
+    Snapshot,
+    /// timestamp.json
+    Timestamp,
+}
+
+impl From<BumpRole> for RoleType {
+    fn from(role: BumpRole) -> Self {
+        match role {
+            BumpRole::Targets => RoleType::Targets,
+            BumpRole::Snapshot => RoleType::Snapshot,
+            BumpRole::Timestamp => RoleType::Timestamp,
+        }
+    }
+}
+
+#[derive(Debug, Parser)]
+pub(crate) struct BumpVersionArgs {
+    /// Key files to sign with
+    #[clap(short = 'k', long = "key", required = true, parse(try_from_str = parse_key_source))]
+    keys: Vec<Box<dyn KeySource>>,
+
+    /// Path to root.json file for the repository
+    #[clap(short = 'r', long = "root")]
+    root: PathBuf,
+
+    /// TUF repository metadata base URL
+    #[clap(short = 'm', long = "metadata-url")]
+    metadata_base_url: Url,
+
+    /// Role whose version is incremented; the roles that list it are incremented too
+    #[clap(long = "role", value_enum)]
+    role: BumpRole,
+
+    /// The directory where the updated repository will be written
+    #[clap(short = 'o', long = "outdir")]
+    outdir: PathBuf,
+}
+
+impl BumpVersionArgs {
+    pub(crate) fn run(&self) -> Result<()> {
+        let repository = load_metadata_repo(&self.root, self.metadata_base_url.clone())?;
+        let role = RoleType::from(self.role);
+        let mut editor = RepositoryEditor::from_repo(&self.root, repository)
+            .context(error::EditorFromRepoSnafu { path: &self.root })?;
+        editor
+            .bump_version(role)
+            .context(error::BumpVersionSnafu { role })?;
+        let signed_repo = editor.sign(&self.keys).context(error::SignRepoSnafu)?;
+
+        let metadata_dir = &self.outdir.join("metadata");
+        signed_repo
+            .write(metadata_dir)
+            .context(error::WriteRepoSnafu {
+                directory: metadata_dir,
+            })
+    }
+}
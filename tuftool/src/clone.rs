@@ -1,15 +1,22 @@
 // Copyright 2019 Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use crate::common::UNUSED_URL;
+use crate::common::{parse_base_url, UNUSED_URL};
 use crate::download_root::download_root;
 use crate::error::{self, Result};
+use crate::write_file;
 use clap::Parser;
-use snafu::ResultExt;
+use serde_json::json;
+use snafu::{ensure, OptionExt, ResultExt};
 use std::fs::File;
+use std::io::{Cursor, Read};
 use std::num::NonZeroU64;
-use std::path::PathBuf;
-use tough::{ExpirationEnforcement, RepositoryLoader};
+use std::path::{Path, PathBuf};
+use tough::schema::{Root, Signed};
+use tough::{
+    CachePlan, DefaultTransport, ExpirationEnforcement, Prefix, Repository, RepositoryLoader,
+    TargetName, Transport,
+};
 use url::Url;
 
 #[derive(Debug, Parser)]
@@ -22,16 +29,25 @@ pub(crate) struct CloneArgs {
     )]
     root: Option<PathBuf>,
 
-    /// Remote root.json version number
-    #[clap(short = 'v', long = "root-version", default_value = "1")]
-    root_version: NonZeroU64,
+    /// Remote root.json version number. With `--root`, the clone starts from this version of
+    /// root.json, fetched from the repository and verified by following the version chain from
+    /// the provided root.json. With `--allow-root-download`, this version is downloaded
+    /// [default: 1]
+    #[clap(short = 'v', long = "root-version")]
+    root_version: Option<NonZeroU64>,
 
-    /// TUF repository metadata base URL
-    #[clap(short = 'm', long = "metadata-url")]
+    /// TUF repository metadata base URL, from which all metadata is fetched
+    #[clap(short = 'm', long = "metadata-url", parse(try_from_str = parse_base_url))]
     metadata_base_url: Url,
 
-    /// TUF repository targets base URL
-    #[clap(short = 't', long = "targets-url", required_unless = "metadata-only")]
+    /// TUF repository targets base URL, from which all targets are fetched. Targets may be hosted
+    /// apart from metadata; this URL is never derived from the metadata URL
+    #[clap(
+        short = 't',
+        long = "targets-url",
+        parse(try_from_str = parse_base_url),
+        required_unless = "metadata-only"
+    )]
     targets_base_url: Option<Url>,
 
     /// Allow downloading the root.json file (unsafe)
@@ -57,6 +73,33 @@ pub(crate) struct CloneArgs {
     /// Only download the repository metadata, not the targets
     #[clap(long, conflicts_with_all(&["target-names", "targets-dir", "targets-base-url"]))]
     metadata_only: bool,
+
+    /// Print the changes the clone would make to the targets directory as JSON, without
+    /// downloading any targets or writing any metadata
+    #[clap(long, conflicts_with = "metadata-only")]
+    plan: bool,
+
+    /// Delete files in the targets directory that are not among the cloned targets
+    #[clap(long, conflicts_with = "metadata-only")]
+    prune: bool,
+
+    /// Also write a flattened JSON index of the cloned targets to this path, listing each
+    /// target's name, length, hashes, and path relative to the targets directory. The index is
+    /// derived from the verified metadata and rewritten on every clone; the signed metadata
+    /// remains authoritative
+    #[clap(long, conflicts_with_all(&["metadata-only", "plan"]))]
+    emit_index: Option<PathBuf>,
+
+    /// Stop at the first target that fails to download or verify, instead of trying every
+    /// target and reporting all of the failures
+    #[clap(long, conflicts_with = "metadata-only")]
+    fail_fast: bool,
+
+    /// Skip downloading targets whose length, as declared in the metadata, is greater than this
+    /// many bytes. The metadata is still cloned in full, and skipped targets are left out of the
+    /// index written by `--emit-index`
+    #[clap(long, conflicts_with = "metadata-only")]
+    max_target_size: Option<u64>,
 }
 
 #[rustfmt::skip]
@@ -74,7 +117,10 @@ impl CloneArgs {
             PathBuf::from(path)
         } else if self.allow_root_download {
             let outdir = std::env::current_dir().context(error::CurrentDirSnafu)?;
-            download_root(&self.metadata_base_url, self.root_version, outdir)?
+            let version = self
+                .root_version
+                .unwrap_or_else(|| NonZeroU64::new(1).unwrap());
+            download_root(&self.metadata_base_url, version, outdir)?
         } else {
             eprintln!("No root.json available");
             std::process::exit(1);
@@ -99,14 +145,23 @@ impl CloneArgs {
         } else {
             ExpirationEnforcement::Safe
         };
-        let repository = RepositoryLoader::new(
-            File::open(&root_path).context(error::OpenRootSnafu { path: &root_path })?,
-            self.metadata_base_url.clone(),
-            targets_base_url,
-        )
-        .expiration_enforcement(expiration_enforcement)
-        .load()
-        .context(error::RepoLoadSnafu)?;
+        // When a root.json version is pinned alongside a local root.json, start from that version
+        // of root.json instead, once the chain to it has been verified
+        let root: Box<dyn Read> = match (&self.root, self.root_version) {
+            (Some(_), Some(version)) => Box::new(Cursor::new(pinned_root(
+                &root_path,
+                &self.metadata_base_url,
+                version,
+            )?)),
+            _ => {
+                Box::new(File::open(&root_path).context(error::OpenRootSnafu { path: &root_path })?)
+            }
+        };
+        let repository =
+            RepositoryLoader::new(root, self.metadata_base_url.clone(), targets_base_url)
+                .expiration_enforcement(expiration_enforcement)
+                .load()
+                .context(error::RepoLoadSnafu)?;
 
         // Clone the repository, downloading none, all, or a subset of targets
         if self.metadata_only {
@@ -122,26 +177,226 @@ impl CloneArgs {
                 "Developer error: `targets_dir` is required unless downloading metadata only",
             );
 
+            let targets_subset = if self.target_names.is_empty() {
+                None
+            } else {
+                Some(self.target_names.as_slice())
+            };
+            // Planning compares every cached target to the repository, so only do it when asked
+            // to print the plan or prune the targets it doesn't include
+            let plan = if self.plan || self.prune {
+                repository
+                    .cache_plan(targets_dir, targets_subset, self.prune)
+                    .context(error::CloneRepositorySnafu)?
+            } else {
+                CachePlan::default()
+            };
+            if self.plan {
+                serde_json::to_writer_pretty(std::io::stdout(), &plan)
+                    .context(error::PlanWriteSnafu)?;
+                println!();
+                return Ok(());
+            }
+
             println!(
                 "Cloning repository:\n\tmetadata location: {:?}\n\ttargets location: {targets_dir:?}",
                 self.metadata_dir
             );
-            if self.target_names.is_empty() {
-                repository
-                    .cache(&self.metadata_dir, targets_dir, None::<&[&str]>, true)
-                    .context(error::CloneRepositorySnafu)?;
-            } else {
-                repository
-                    .cache(
-                        &self.metadata_dir,
-                        targets_dir,
-                        Some(self.target_names.as_slice()),
-                        true,
-                    )
-                    .context(error::CloneRepositorySnafu)?;
+            self.cache_targets(&repository, targets_dir, targets_subset)?;
+            repository
+                .cache_metadata(&self.metadata_dir, true)
+                .context(error::CloneRepositorySnafu)?;
+            for file in &plan.delete {
+                let path = targets_dir.join(file);
+                std::fs::remove_file(&path).context(error::PruneTargetSnafu { path })?;
+            }
+            if let Some(index_path) = &self.emit_index {
+                write_index(
+                    &repository,
+                    targets_subset,
+                    self.max_target_size,
+                    index_path,
+                )?;
             }
         };
 
         Ok(())
     }
+
+    /// Saves the targets to `targets_dir` in name order, skipping any larger than
+    /// `--max-target-size`. Each target that fails to download or verify is reported, and the
+    /// rest are still saved unless `--fail-fast` was given.
+    fn cache_targets(
+        &self,
+        repository: &Repository,
+        targets_dir: &Path,
+        targets_subset: Option<&[String]>,
+    ) -> Result<()> {
+        std::fs::create_dir_all(targets_dir)
+            .context(error::DirCreateSnafu { path: targets_dir })?;
+        let prefix = if repository.root().signed.consistent_snapshot {
+            Prefix::Digest
+        } else {
+            Prefix::None
+        };
+        let target_names = target_names(repository, targets_subset)?;
+        let mut failed = 0;
+        for target_name in &target_names {
+            if let Some(length) = oversized_length(repository, target_name, self.max_target_size) {
+                eprintln!(
+                    "Skipping target '{}': its length of {} bytes exceeds the maximum target size",
+                    target_name.raw(),
+                    length
+                );
+                continue;
+            }
+            if let Err(source) = repository.save_target(target_name, targets_dir, prefix) {
+                if self.fail_fast {
+                    return Err(source).context(error::CloneTargetSnafu {
+                        target: target_name.raw(),
+                    });
+                }
+                eprintln!("Failed to clone target '{}': {}", target_name.raw(), source);
+                failed += 1;
+            }
+        }
+        ensure!(
+            failed == 0,
+            error::CloneTargetsSnafu {
+                failed,
+                total: target_names.len(),
+            }
+        );
+        Ok(())
+    }
+}
+
+/// Returns the sorted names of the targets to clone: the given subset, or every target in the
+/// repository.
+fn target_names(
+    repository: &Repository,
+    targets_subset: Option<&[String]>,
+) -> Result<Vec<TargetName>> {
+    let mut target_names = match targets_subset {
+        Some(names) => names
+            .iter()
+            .map(|name| TargetName::new(name.as_str()).context(error::InvalidTargetNameSnafu))
+            .collect::<Result<Vec<_>>>()?,
+        None => repository
+            .all_targets()
+            .map(|(target_name, _)| target_name.clone())
+            .collect(),
+    };
+    target_names.sort();
+    target_names.dedup();
+    Ok(target_names)
+}
+
+/// Returns the declared length of the target if it is greater than `max_target_size`. Targets
+/// that aren't in the metadata aren't considered oversized; fetching them reports the error.
+fn oversized_length(
+    repository: &Repository,
+    target_name: &TargetName,
+    max_target_size: Option<u64>,
+) -> Option<u64> {
+    let max_target_size = max_target_size?;
+    let target = repository.targets().signed.find_target(target_name).ok()?;
+    (target.length > max_target_size).then_some(target.length)
+}
+
+/// Writes a flattened index of the cloned targets to `path`, for consumers that can't read TUF
+/// metadata. Targets larger than `max_target_size` weren't cloned and are left out.
+fn write_index(
+    repository: &Repository,
+    targets_subset: Option<&[String]>,
+    max_target_size: Option<u64>,
+    path: &Path,
+) -> Result<()> {
+    let target_names = target_names(repository, targets_subset)?;
+    let mut targets = Vec::new();
+    for target_name in &target_names {
+        if oversized_length(repository, target_name, max_target_size).is_some() {
+            continue;
+        }
+        let target = repository
+            .targets()
+            .signed
+            .find_target(target_name)
+            .ok()
+            .context(error::TargetNotFoundSnafu {
+                target: target_name.raw(),
+            })?;
+        let target_path = repository
+            .cached_target_filename(target_name)
+            .context(error::CloneRepositorySnafu)?;
+        targets.push(json!({
+            "name": target_name.raw(),
+            "length": target.length,
+            "hashes": target.hashes,
+            "path": target_path,
+        }));
+    }
+
+    write_file(
+        path,
+        &json!({
+            "note": "Derived from the repository's verified TUF metadata, which is authoritative",
+            "targets_version": repository.targets().signed.version,
+            "snapshot_version": repository.snapshot().signed.version,
+            "timestamp_version": repository.timestamp().signed.version,
+            "targets": targets,
+        }),
+    )
+}
+
+/// Fetches the given version of root.json from the repository and returns its contents. Each
+/// root.json after the trusted one at `root_path`, up to and including `version`, must be signed
+/// by a threshold of keys from both its predecessor and itself.
+fn pinned_root(root_path: &Path, metadata_base_url: &Url, version: NonZeroU64) -> Result<Vec<u8>> {
+    let mut buf = std::fs::read(root_path).context(error::OpenRootSnafu { path: root_path })?;
+    let mut trusted: Signed<Root> =
+        serde_json::from_slice(&buf).context(error::FileParseJsonSnafu { path: root_path })?;
+    ensure!(
+        trusted.signed.version <= version,
+        error::RootVersionOlderSnafu {
+            requested: version.get(),
+            provided: trusted.signed.version.get(),
+        }
+    );
+
+    let transport = DefaultTransport::new();
+    while trusted.signed.version < version {
+        let next = trusted.signed.version.get() + 1;
+        let name = format!("{next}.root.json");
+        let url = metadata_base_url
+            .join(&name)
+            .context(error::UrlParseSnafu {
+                url: format!("{}{name}", metadata_base_url.as_str()),
+            })?;
+        buf.clear();
+        transport
+            .fetch(url)
+            .context(error::RootVersionFetchSnafu { version: next })?
+            .read_to_end(&mut buf)
+            .context(error::RootVersionReadSnafu { version: next })?;
+        let new_root: Signed<Root> =
+            serde_json::from_slice(&buf).context(error::RootVersionParseSnafu { version: next })?;
+        trusted
+            .signed
+            .verify_role(&new_root)
+            .context(error::RootVersionVerifySnafu { version: next })?;
+        new_root
+            .signed
+            .verify_role(&new_root)
+            .context(error::RootVersionVerifySnafu { version: next })?;
+        ensure!(
+            new_root.signed.version.get() == next,
+            error::RootVersionMismatchSnafu {
+                expected: next,
+                fetched: new_root.signed.version.get(),
+            }
+        );
+        trusted = new_root;
+    }
+    Ok(buf)
 }
@@ -1,8 +1,11 @@
 /// This module is for code that is re-used by different `tuftool` subcommands.
 use crate::error::{self, Result};
-use snafu::ResultExt;
+use snafu::{ensure, OptionExt, ResultExt};
 use std::fs::File;
+use std::io::Write;
 use std::path::Path;
+use tough::editor::signed::SignedDelegatedTargets;
+use tough::schema::{RoleType, Targets};
 use tough::{Repository, RepositoryLoader};
 use url::Url;
 
@@ -11,8 +14,29 @@ use url::Url;
 /// the targets URL.
 pub(crate) const UNUSED_URL: &str = "file:///unused/url";
 
+/// Commands that sign a single role accept this as their `--outdir` to write the signed role to
+/// stdout instead of to a directory.
+pub(crate) const STDOUT_OUTDIR: &str = "-";
+
+/// Parses a repository base URL given on the command line, such as `--metadata-url` or
+/// `--targets-url`. Files are fetched by joining their names to the URL, so it must be a
+/// hierarchical `http`, `https`, or `file` URL, and may not have a query or fragment, which would
+/// be lost.
+pub(crate) fn parse_base_url(input: &str) -> Result<Url> {
+    let url = Url::parse(input).context(error::UrlParseSnafu { url: input })?;
+    ensure!(
+        matches!(url.scheme(), "http" | "https" | "file")
+            && !url.cannot_be_a_base()
+            && url.query().is_none()
+            && url.fragment().is_none(),
+        error::UrlNotBaseSnafu { url }
+    );
+    Ok(url)
+}
+
 /// Load a repo for metadata processing only. Such a repo will never use the
-/// targets directory, so a dummy path is passed.
+/// targets directory, so a dummy path is passed. Loading verifies all of the
+/// repository's metadata, so what the returned repo lists can be trusted.
 ///
 /// - `root` must be a path to a file that can be opened with `File::open`.
 /// - `metadata_url` can be local or remote.
@@ -33,3 +57,89 @@ where
     .load()
     .context(error::RepoLoadSnafu)
 }
+
+/// A key trusted to sign a role of a repository, for commands that describe the repository.
+pub(crate) struct RoleKey {
+    pub(crate) role: String,
+    pub(crate) key_id: String,
+    /// The key's signature scheme, as written in metadata, e.g. `ed25519`.
+    pub(crate) scheme: &'static str,
+}
+
+/// Returns the keys trusted to sign each role of `repository`: those of the top-level roles, as
+/// listed in root.json, then those of each delegated role, in the order they are delegated.
+pub(crate) fn role_keys(repository: &Repository) -> Vec<RoleKey> {
+    let root = &repository.root().signed;
+    let mut keys = Vec::new();
+    for role in [
+        RoleType::Root,
+        RoleType::Timestamp,
+        RoleType::Snapshot,
+        RoleType::Targets,
+    ] {
+        if let Some(role_keys) = root.roles.get(&role) {
+            for key_id in &role_keys.keyids {
+                if let Some(key) = root.keys.get(key_id) {
+                    keys.push(RoleKey {
+                        role: role.to_string(),
+                        key_id: hex::encode(key_id),
+                        scheme: key.scheme_name(),
+                    });
+                }
+            }
+        }
+    }
+    delegated_role_keys(&repository.targets().signed, &mut keys);
+    keys
+}
+
+/// Adds the keys of the roles `targets` delegates, and of the roles they delegate, to `keys`.
+fn delegated_role_keys(targets: &Targets, keys: &mut Vec<RoleKey>) {
+    let delegations = match &targets.delegations {
+        Some(delegations) => delegations,
+        None => return,
+    };
+    for role in &delegations.roles {
+        for key_id in &role.keyids {
+            if let Some(key) = delegations.keys.get(key_id) {
+                keys.push(RoleKey {
+                    role: role.name.clone(),
+                    key_id: hex::encode(key_id),
+                    scheme: key.scheme_name(),
+                });
+            }
+        }
+        if let Some(role_targets) = &role.targets {
+            delegated_role_keys(&role_targets.signed, keys);
+        }
+    }
+}
+
+/// Write signed delegated targets for commands that sign a single role.
+///
+/// The roles are written to the `metadata` directory in `outdir`, unless `outdir` is
+/// [`STDOUT_OUTDIR`], in which case only the signed JSON of `role` is written to stdout.
+pub(crate) fn write_delegated_targets(
+    signed: SignedDelegatedTargets,
+    outdir: &Path,
+    role: &str,
+    roles: Vec<String>,
+) -> Result<()> {
+    if outdir != Path::new(STDOUT_OUTDIR) {
+        return signed
+            .write(outdir.join("metadata"), false)
+            .context(error::WriteRolesSnafu { roles });
+    }
+    let signed_role = signed
+        .roles()
+        .into_iter()
+        .find(|signed_role| signed_role.signed().signed.name == role)
+        .context(error::MissingSnafu {
+            what: format!("signed metadata for role '{role}'"),
+        })?;
+    let mut stdout = std::io::stdout();
+    stdout
+        .write_all(signed_role.buffer())
+        .and_then(|()| stdout.flush())
+        .context(error::StdoutWriteSnafu)
+}
@@ -1,18 +1,21 @@
 // Copyright 2019 Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use crate::build_targets;
 use crate::datetime::parse_datetime;
 use crate::error::{self, Result};
+use crate::root;
 use crate::source::parse_key_source;
+use crate::{build_targets, load_file, write_file};
 use chrono::{DateTime, Utc};
 use clap::Parser;
 use snafu::ResultExt;
 use std::num::{NonZeroU64, NonZeroUsize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
 use tough::editor::signed::PathExists;
 use tough::editor::RepositoryEditor;
 use tough::key_source::KeySource;
+use tough::schema::{Root, Signed};
 
 #[derive(Debug, Parser)]
 pub(crate) struct CreateArgs {
@@ -70,6 +73,18 @@ pub(crate) struct CreateArgs {
     #[clap(short = 'j', long = "jobs")]
     jobs: Option<NonZeroUsize>,
 
+    /// Read each target in chunks of this many bytes on its own thread while hashing it, which
+    /// speeds up hashing very large targets
+    #[clap(long = "hash-chunk-size")]
+    hash_chunk_size: Option<NonZeroUsize>,
+
+    /// Whether the repository uses consistent snapshots, i.e. versioned metadata filenames and
+    /// hash-prefixed target filenames. If this differs from root.json, the repository is written
+    /// with a new version of root.json that has this setting, signed with the given keys; the
+    /// given root.json is left as is. If not given, the setting in root.json is used
+    #[clap(long = "consistent-snapshot", value_name = "true|false")]
+    consistent_snapshot: Option<bool>,
+
     /// The directory where the repository will be written
     #[clap(short = 'o', long = "outdir")]
     outdir: PathBuf,
@@ -86,9 +101,21 @@ impl CreateArgs {
                 .context(error::InitializeThreadPoolSnafu)?;
         }
 
-        let targets = build_targets(&self.targets_indir, self.follow)?;
-        let mut editor = RepositoryEditor::new(&self.root)
-            .context(error::EditorCreateSnafu { path: &self.root })?;
+        // Every write below follows the setting in root.json, so a new root.json with the
+        // requested setting must be signed first
+        let new_root = match self.consistent_snapshot {
+            Some(consistent_snapshot) => {
+                set_consistent_snapshot(&self.root, consistent_snapshot, &self.keys)?
+            }
+            None => None,
+        };
+        let root_path = new_root
+            .as_ref()
+            .map_or(self.root.clone(), |dir| dir.path().join("root.json"));
+
+        let targets = build_targets(&self.targets_indir, self.follow, self.hash_chunk_size)?;
+        let mut editor = RepositoryEditor::new(&root_path)
+            .context(error::EditorCreateSnafu { path: &root_path })?;
 
         editor
             .targets_version(self.targets_version)
@@ -125,3 +152,27 @@ impl CreateArgs {
         Ok(())
     }
 }
+
+/// If the root.json at `path` doesn't have the given consistent snapshot setting, writes the next
+/// version of it with the setting, signed with `keys`, to `root.json` in a temporary directory,
+/// which is returned. The root.json at `path` is never modified, so a failure to sign leaves
+/// nothing behind.
+fn set_consistent_snapshot(
+    path: &Path,
+    consistent_snapshot: bool,
+    keys: &[Box<dyn KeySource>],
+) -> Result<Option<TempDir>> {
+    let mut root: Signed<Root> = load_file(path)?;
+    if root.signed.consistent_snapshot == consistent_snapshot {
+        return Ok(None);
+    }
+    root.signed.consistent_snapshot = consistent_snapshot;
+    root::increment_version(&mut root.signed)?;
+    root.signatures.clear();
+
+    let dir = TempDir::new().context(error::TempDirSnafu)?;
+    let new_path = dir.path().join("root.json");
+    write_file(&new_path, &root)?;
+    root::Command::sign(&new_path, keys, None, false)?;
+    Ok(Some(dir))
+}
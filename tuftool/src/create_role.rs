@@ -1,6 +1,7 @@
 // Copyright 2019 Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+use crate::common::write_delegated_targets;
 use crate::datetime::parse_datetime;
 use crate::error::{self, Result};
 use crate::source::parse_key_source;
@@ -31,7 +32,8 @@ pub(crate) struct CreateRoleArgs {
     #[clap(short = 'v', long = "version")]
     version: NonZeroU64,
 
-    /// The directory where the repository will be written
+    /// The directory where the repository will be written; use '-' to write the signed role to
+    /// stdout
     #[clap(short = 'o', long = "outdir")]
     outdir: PathBuf,
 }
@@ -47,13 +49,7 @@ impl CreateRoleArgs {
             .sign(&self.keys)
             .context(error::SignRepoSnafu)?;
         // write the new role
-        let metadata_destination_out = &self.outdir.join("metadata");
-        new_role
-            .write(metadata_destination_out, false)
-            .context(error::WriteRolesSnafu {
-                roles: [role.to_string()].to_vec(),
-            })?;
-        Ok(())
+        write_delegated_targets(new_role, &self.outdir, role, [role.to_string()].to_vec())
     }
 }
 
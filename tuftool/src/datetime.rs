@@ -3,8 +3,41 @@
 
 use crate::error::{self, Result};
 
-use chrono::{DateTime, Duration, FixedOffset, Utc};
-use snafu::{ensure, ResultExt};
+use chrono::{DateTime, Duration, FixedOffset, TimeZone, Utc};
+use snafu::{ensure, OptionExt, ResultExt};
+use tough::Clock;
+
+/// The environment variable that fixes the current time, as seconds since the Unix epoch. This
+/// follows the reproducible builds convention.
+const SOURCE_DATE_EPOCH: &str = "SOURCE_DATE_EPOCH";
+
+/// A [`Clock`] that reads `SOURCE_DATE_EPOCH` when it is set, and the system clock otherwise.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SourceDateClock(Option<DateTime<Utc>>);
+
+impl Clock for SourceDateClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0.unwrap_or_else(Utc::now)
+    }
+}
+
+/// Returns the clock for times written into metadata, e.g. relative expiration dates. It must not
+/// be given to `RepositoryLoader`: a fixed `SOURCE_DATE_EPOCH` would let expired metadata pass the
+/// loader's expiration checks.
+pub(crate) fn clock() -> Result<SourceDateClock> {
+    let value = match std::env::var(SOURCE_DATE_EPOCH) {
+        Ok(value) => value,
+        Err(_) => return Ok(SourceDateClock(None)),
+    };
+    let seconds: i64 = value
+        .parse()
+        .context(error::SourceDateEpochSnafu { value: &value })?;
+    let time = Utc
+        .timestamp_opt(seconds, 0)
+        .single()
+        .context(error::SourceDateEpochRangeSnafu { value })?;
+    Ok(SourceDateClock(Some(time)))
+}
 
 /// Parses a user-specified datetime, either in full RFC 3339 format, or a shorthand like "in 7
 /// days"
@@ -55,7 +88,7 @@ pub(crate) fn parse_datetime(input: &str) -> Result<DateTime<Utc>> {
         }
     };
 
-    let now = Utc::now();
+    let now = clock()?.now();
     let then = now + duration;
     Ok(then)
 }
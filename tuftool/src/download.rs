@@ -1,13 +1,16 @@
 // Copyright 2019 Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+use crate::common::parse_base_url;
 use crate::download_root::download_root;
 use crate::error::{self, Result};
 use clap::Parser;
 use snafu::{ensure, ResultExt};
+use std::collections::BTreeSet;
 use std::fs::File;
 use std::num::NonZeroU64;
 use std::path::{Path, PathBuf};
+use tough::editor::signed::TargetLayout;
 use tough::{ExpirationEnforcement, Prefix, Repository, RepositoryLoader, TargetName};
 use url::Url;
 
@@ -21,12 +24,13 @@ pub(crate) struct DownloadArgs {
     #[clap(short = 'v', long = "root-version", default_value = "1")]
     root_version: NonZeroU64,
 
-    /// TUF repository metadata base URL
-    #[clap(short = 'm', long = "metadata-url")]
+    /// TUF repository metadata base URL, from which all metadata is fetched
+    #[clap(short = 'm', long = "metadata-url", parse(try_from_str = parse_base_url))]
     metadata_base_url: Url,
 
-    /// TUF repository targets base URL
-    #[clap(short = 't', long = "targets-url")]
+    /// TUF repository targets base URL, from which all targets are fetched. Targets may be hosted
+    /// apart from metadata; this URL is never derived from the metadata URL
+    #[clap(short = 't', long = "targets-url", parse(try_from_str = parse_base_url))]
     targets_base_url: Url,
 
     /// Allow downloading the root.json file (unsafe)
@@ -43,6 +47,19 @@ pub(crate) struct DownloadArgs {
     /// Allow repo download for expired metadata
     #[clap(long)]
     allow_expired_repo: bool,
+
+    /// Fail unless the repository's snapshot.json is at exactly this version
+    #[clap(long = "pin-snapshot-version", requires = "pin-timestamp-version")]
+    pin_snapshot_version: Option<NonZeroU64>,
+
+    /// Fail unless the repository's timestamp.json is at exactly this version
+    #[clap(long = "pin-timestamp-version", requires = "pin-snapshot-version")]
+    pin_timestamp_version: Option<NonZeroU64>,
+
+    /// Mirror the directories of '/'-separated target names in the output directory, refusing
+    /// target names that are absolute or contain '..' instead of resolving them
+    #[clap(long)]
+    nested: bool,
 }
 
 fn expired_repo_warning<P: AsRef<Path>>(path: P) {
@@ -81,21 +98,35 @@ impl DownloadArgs {
         } else {
             ExpirationEnforcement::Safe
         };
-        let repository = RepositoryLoader::new(
+        let mut loader = RepositoryLoader::new(
             File::open(&root_path).context(error::OpenRootSnafu { path: &root_path })?,
             self.metadata_base_url.clone(),
             self.targets_base_url.clone(),
         )
-        .expiration_enforcement(expiration_enforcement)
-        .load()
-        .context(error::RepoLoadSnafu)?;
+        .expiration_enforcement(expiration_enforcement);
+        if let (Some(snapshot), Some(timestamp)) =
+            (self.pin_snapshot_version, self.pin_timestamp_version)
+        {
+            loader = loader.pin_versions(snapshot, timestamp);
+        }
+        let repository = loader.load().context(error::RepoLoadSnafu)?;
 
         // download targets
-        handle_download(&repository, &self.outdir, &self.target_names)
+        let layout = if self.nested {
+            TargetLayout::Nested
+        } else {
+            TargetLayout::Flat
+        };
+        handle_download(&repository, &self.outdir, &self.target_names, layout)
     }
 }
 
-fn handle_download(repository: &Repository, outdir: &Path, raw_names: &[String]) -> Result<()> {
+fn handle_download(
+    repository: &Repository,
+    outdir: &Path,
+    raw_names: &[String],
+    layout: TargetLayout,
+) -> Result<()> {
     let target_names: Result<Vec<TargetName>> = raw_names
         .iter()
         .map(|s| TargetName::new(s).context(error::InvalidTargetNameSnafu))
@@ -104,19 +135,19 @@ fn handle_download(repository: &Repository, outdir: &Path, raw_names: &[String])
     let download_target = |name: &TargetName| -> Result<()> {
         println!("\t-> {}", name.raw());
         repository
-            .save_target(name, outdir, Prefix::None)
+            .save_target_with_layout(name, outdir, Prefix::None, layout)
             .context(error::MetadataSnafu)?;
         Ok(())
     };
 
-    // copy requested targets, or all available targets if not specified
+    // copy requested targets, or all available targets if not specified, including those of
+    // delegated roles; a name listed by more than one role is only downloaded once
     let targets: Vec<TargetName> = if target_names.is_empty() {
         repository
-            .targets()
-            .signed
-            .targets
-            .keys()
-            .cloned()
+            .all_targets()
+            .map(|(name, _)| name.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
             .collect()
     } else {
         target_names
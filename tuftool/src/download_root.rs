@@ -25,7 +25,7 @@ where
     let url = metadata_base_url
         .join(&name)
         .context(error::UrlParseSnafu {
-            url: format!("{name}/{}", metadata_base_url.as_str()),
+            url: format!("{}{name}", metadata_base_url.as_str()),
         })?;
     root_warning(&path);
 
@@ -13,12 +13,62 @@ pub(crate) type Result<T> = std::result::Result<T, Error>;
 #[derive(Debug, Snafu)]
 #[snafu(visibility(pub(crate)))]
 pub(crate) enum Error {
+    #[snafu(display("Failed to bump the version of role '{}': {}", role, source))]
+    BumpVersion {
+        role: tough::schema::RoleType,
+        source: tough::error::Error,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Metadata for role '{}' was given more than once", role))]
+    CeremonyDuplicateRole {
+        role: tough::schema::RoleType,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Signature threshold not met for: {}", roles))]
+    CeremonyIncomplete { roles: String, backtrace: Backtrace },
+
+    #[snafu(display(
+        "'{}' is not the metadata of a top-level role (root, timestamp, snapshot or targets)",
+        path.display()
+    ))]
+    CeremonyRole { path: PathBuf, backtrace: Backtrace },
+
+    #[snafu(display("Failed to sign {}: {}", role, source))]
+    CeremonySign {
+        role: String,
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Failed to verify signed {} metadata: {}", role, source))]
+    CeremonyVerify {
+        role: tough::schema::RoleType,
+        source: tough::schema::Error,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Failed to clone repository: {}", source))]
     CloneRepository {
         source: tough::error::Error,
         backtrace: Backtrace,
     },
 
+    #[snafu(display("Failed to clone target '{}': {}", target, source))]
+    CloneTarget {
+        target: String,
+        source: tough::error::Error,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Failed to clone {} of {} targets", failed, total))]
+    CloneTargets {
+        failed: usize,
+        total: usize,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Failed to run {}: {}", command_str, source))]
     CommandExec {
         command_str: String,
@@ -46,6 +96,9 @@ pub(crate) enum Error {
         backtrace: Backtrace,
     },
 
+    #[snafu(display("Cannot make a URL of directory '{}'", path.display()))]
+    DirectoryUrl { path: PathBuf, backtrace: Backtrace },
+
     #[snafu(display("Date argument '{}' is invalid: {}", input, msg))]
     DateArgInvalid { input: String, msg: &'static str },
 
@@ -66,6 +119,13 @@ pub(crate) enum Error {
         backtrace: Backtrace,
     },
 
+    #[snafu(display("Failed to read directory '{}': {}", path.display(), source))]
+    DirRead {
+        path: PathBuf,
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Invalid delegation structure: {}", source))]
     DelegationStructure {
         source: tough::error::Error,
@@ -79,9 +139,23 @@ pub(crate) enum Error {
         backtrace: Backtrace,
     },
 
+    #[snafu(display(
+        "Role '{}' is not delegated by '{}' or a role below it",
+        role,
+        delegator
+    ))]
+    NotDelegatedBy {
+        role: String,
+        delegator: String,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("A file or directory already exists at '{}'", path.display()))]
     DownloadOutdirExists { path: PathBuf, backtrace: Backtrace },
 
+    #[snafu(display("{} target name(s) are listed by more than one role", count))]
+    DuplicateTargets { count: usize, backtrace: Backtrace },
+
     #[snafu(display(
         "Failed to create a Repository Editor with root.json '{}': {}",
         path.display(),
@@ -106,6 +180,28 @@ pub(crate) enum Error {
         backtrace: Backtrace,
     },
 
+    #[snafu(display(
+        "Repository fingerprint {} doesn't match the expected {}",
+        fingerprint,
+        expected
+    ))]
+    FingerprintMismatch {
+        fingerprint: String,
+        expected: String,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display(
+        "Failed to serialize '{}' metadata for its fingerprint: {}",
+        role,
+        source
+    ))]
+    FingerprintSerialize {
+        role: String,
+        source: serde_json::Error,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Failed to parse {}: {}", path.display(), source))]
     FileParseJson {
         path: PathBuf,
@@ -120,6 +216,13 @@ pub(crate) enum Error {
         backtrace: Backtrace,
     },
 
+    #[snafu(display("Failed to read {}: {}", path.display(), source))]
+    FileRead {
+        path: PathBuf,
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Failed to create temporary file in {}: {}", path.display(), source))]
     FileTempCreate {
         path: PathBuf,
@@ -127,9 +230,6 @@ pub(crate) enum Error {
         backtrace: Backtrace,
     },
 
-    #[snafu(display("Can't build URL from path '{}'", path.display()))]
-    FileUrl { path: PathBuf, backtrace: Backtrace },
-
     #[snafu(display("Failed to write to {}: {}", path.display(), source))]
     FileWrite {
         path: PathBuf,
@@ -150,6 +250,24 @@ pub(crate) enum Error {
         backtrace: Backtrace,
     },
 
+    #[snafu(display("Failed to write {} metadata: {}", role, source))]
+    InspectWrite {
+        role: tough::schema::RoleType,
+        source: serde_json::Error,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display(
+        "{} target(s) differ from the inventory in '{}'",
+        count,
+        path.display()
+    ))]
+    InventoryMismatch {
+        count: usize,
+        path: PathBuf,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Invalid target name: {}", source))]
     InvalidTargetName { source: tough::error::Error },
 
@@ -171,6 +289,57 @@ pub(crate) enum Error {
         source: tough::schema::Error,
     },
 
+    #[snafu(display("Invalid key ID '{}': {}", key_id, source))]
+    KeyIdParse {
+        key_id: String,
+        source: tough::schema::Error,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display(
+        "Expected a key source and key ID as '<key source>=<key ID>', got '{}'",
+        input
+    ))]
+    KeyIdSourceFormat { input: String, backtrace: Backtrace },
+
+    #[snafu(display("Key ID {} is not listed by any role in root.json", key_id))]
+    KeyNotInRoot {
+        key_id: String,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Key ID {} is not listed by the root role", key_id))]
+    KeyNotInRootRole {
+        key_id: String,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display(
+        "Replacing the key would leave role '{}' with {} keys, below its threshold of {}",
+        role,
+        keys,
+        threshold
+    ))]
+    KeyReplaceThreshold {
+        role: tough::schema::RoleType,
+        keys: usize,
+        threshold: u64,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Key ID {} is already the key being replaced", key_id))]
+    KeyReplaceSame {
+        key_id: String,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Failed to parse key source '{}': {}", input, source))]
+    KeySourceParse {
+        input: String,
+        source: tough::error::Error,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Unable to parse keypair: {}", source))]
     KeyPairParse {
         source: tough::error::Error,
@@ -240,6 +409,19 @@ pub(crate) enum Error {
     #[snafu(display("Path {} is not valid UTF-8", path.display()))]
     PathUtf8 { path: PathBuf, backtrace: Backtrace },
 
+    #[snafu(display("Failed to write clone plan: {}", source))]
+    PlanWrite {
+        source: serde_json::Error,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Failed to prune '{}': {}", path.display(), source))]
+    PruneTarget {
+        path: PathBuf,
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Failed to load repository: {}", source))]
     RepoLoad {
         source: tough::error::Error,
@@ -265,6 +447,121 @@ pub(crate) enum Error {
         backtrace: Backtrace,
     },
 
+    #[snafu(display(
+        "Not removing roles {}: run interactively to confirm, or pass --yes",
+        roles
+    ))]
+    RemoveRoleUnconfirmed { roles: String, backtrace: Backtrace },
+
+    #[snafu(display(
+        "Changing the keys of the root role requires --confirm; the new root.json must then be \
+        signed by a threshold of keys from both the old and the new root role"
+    ))]
+    RootKeyUnconfirmed { backtrace: Backtrace },
+
+    #[snafu(display("Key ID {} is already listed by the root role", key_id))]
+    RootRoleKeyPresent {
+        key_id: String,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display(
+        "The root role would have {} keys, below its threshold of {}, and root.json could not be signed",
+        keys,
+        threshold
+    ))]
+    RootRoleThreshold {
+        keys: usize,
+        threshold: u64,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("root.json has {} problem(s)", count))]
+    RootInvalid { count: usize, backtrace: Backtrace },
+
+    #[snafu(display("Root transition is not valid: {}", problems))]
+    RootTransition {
+        problems: String,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display(
+        "Failed to fetch version {} of root.json from the repository: {}",
+        version,
+        source
+    ))]
+    RootVersionFetch {
+        version: u64,
+        source: tough::TransportError,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Repository has more than {} versions of root.json", max))]
+    RootVersionLimit { max: u64, backtrace: Backtrace },
+
+    #[snafu(display(
+        "Version {} of root.json was requested but version {} was fetched",
+        expected,
+        fetched
+    ))]
+    RootVersionMismatch {
+        expected: u64,
+        fetched: u64,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display(
+        "Requested root.json version {} is older than the provided root.json version {}",
+        requested,
+        provided
+    ))]
+    RootVersionOlder {
+        requested: u64,
+        provided: u64,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Failed to parse version {} of root.json: {}", version, source))]
+    RootVersionParse {
+        version: u64,
+        source: serde_json::Error,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Failed to read version {} of root.json: {}", version, source))]
+    RootVersionRead {
+        version: u64,
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Failed to verify version {} of root.json: {}", version, source))]
+    RootVersionVerify {
+        version: u64,
+        source: tough::schema::Error,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Failed to re-sign role '{}': {}", role, source))]
+    ResignRole {
+        role: tough::schema::RoleType,
+        source: tough::error::Error,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Failed to set paths for role '{}': {}", role, source))]
+    SetPaths {
+        role: String,
+        source: tough::error::Error,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display(
+        "Signature threshold not met for: {}; sign again with more of their keys",
+        roles
+    ))]
+    SignIncomplete { roles: String, backtrace: Backtrace },
+
     #[snafu(display("Failed to sign repository: {}", source))]
     SignRepo {
         source: tough::error::Error,
@@ -281,6 +578,13 @@ pub(crate) enum Error {
         signature_count: usize,
     },
 
+    #[snafu(display("Failed to sign role '{}': {}", role, source))]
+    SignRole {
+        role: tough::schema::RoleType,
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Failed to sign '{}': {}", path.display(), source))]
     SignRoot {
         path: PathBuf,
@@ -288,6 +592,19 @@ pub(crate) enum Error {
         backtrace: Backtrace,
     },
 
+    #[snafu(display(
+        "The given keys can make {} of the {} signatures required for role '{}'",
+        keys,
+        threshold,
+        role
+    ))]
+    SigningKeysThreshold {
+        role: tough::schema::RoleType,
+        keys: usize,
+        threshold: u64,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Unable to create Target from path '{}': {}", path.display(), source))]
     TargetFromPath {
         path: PathBuf,
@@ -302,21 +619,110 @@ pub(crate) enum Error {
         backtrace: Backtrace,
     },
 
+    #[snafu(display("Invalid SOURCE_DATE_EPOCH '{}': {}", value, source))]
+    SourceDateEpoch {
+        value: String,
+        source: std::num::ParseIntError,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("SOURCE_DATE_EPOCH '{}' is out of range", value))]
+    SourceDateEpochRange { value: String, backtrace: Backtrace },
+
+    #[snafu(display("Failed to set spec_version '{}': {}", spec_version, source))]
+    SpecVersion {
+        spec_version: String,
+        source: tough::error::Error,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Failed to update delegated role '{}': {}", role, source))]
+    StampDelegatedRole {
+        role: String,
+        source: tough::error::Error,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Cannot write to stdout when adding keys, which may sign several roles"))]
+    StdoutAddKey { backtrace: Backtrace },
+
+    #[snafu(display(
+        "Cannot write to stdout when adding targets, which are copied to the outdir"
+    ))]
+    StdoutAddTargets { backtrace: Backtrace },
+
+    #[snafu(display("Cannot write to stdout when signing the entire repository"))]
+    StdoutSignAll { backtrace: Backtrace },
+
+    #[snafu(display("{} warning(s) found and --strict-warnings was passed", count))]
+    StrictWarnings { count: usize, backtrace: Backtrace },
+
+    #[snafu(display("Failed to write to stdout: {}", source))]
+    StdoutWrite {
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Failed to check that target '{}' is available: {}", target, source))]
+    TargetCheck {
+        target: String,
+        source: tough::TransportError,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Target not found: {}", target))]
     TargetNotFound {
         target: String,
         backtrace: Backtrace,
     },
 
-    #[snafu(display("Failed to create temporary directory: {}", source))]
-    TempDir {
+    #[snafu(display(
+        "Targets not found at '{}': {}; upload them before publishing metadata that lists them",
+        url,
+        targets
+    ))]
+    TargetsUnavailable {
+        url: url::Url,
+        targets: String,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Failed to build the URL of target '{}': {}", target, source))]
+    TargetUrl {
+        target: String,
+        source: tough::error::Error,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Target '{}' is not delegated to role '{}'", target, role))]
+    TargetNotDelegated {
+        target: String,
+        role: String,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Failed to serialize the trusted root.json: {}", source))]
+    SimulateRootSerialize {
+        source: serde_json::Error,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Expected the update to be {}, but it was {}", expected, outcome))]
+    SimulateUpdateOutcome {
+        expected: crate::simulate_update::Outcome,
+        outcome: crate::simulate_update::Outcome,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Failed to read from stdin: {}", source))]
+    StdinRead {
         source: std::io::Error,
         backtrace: Backtrace,
     },
 
-    #[snafu(display("Unrecognized URL scheme \"{}\"", scheme))]
-    UnrecognizedScheme {
-        scheme: String,
+    #[snafu(display("Failed to create temporary directory: {}", source))]
+    TempDir {
+        source: std::io::Error,
         backtrace: Backtrace,
     },
 
@@ -333,6 +739,12 @@ pub(crate) enum Error {
         actual: usize,
     },
 
+    #[snafu(display(
+        "Cannot use \"{}\" as a base URL: it must be an http, https, or file URL with no query or fragment",
+        url
+    ))]
+    UrlNotBase { url: url::Url, backtrace: Backtrace },
+
     #[snafu(display("Failed to parse URL \"{}\": {}", url, source))]
     UrlParse {
         url: String,
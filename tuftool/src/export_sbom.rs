@@ -0,0 +1,145 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#![allow(clippy::doc_markdown)]
+
+use crate::common::load_metadata_repo;
+use crate::error::{self, Result};
+use clap::{Parser, ValueEnum};
+use serde::Serialize;
+use serde_json::Value;
+use snafu::ResultExt;
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::PathBuf;
+use tough::schema::{to_stable_json_pretty, Target};
+use tough::Repository;
+use url::Url;
+
+/// The CycloneDX specification version of the documents written by `export-sbom`.
+const CYCLONEDX_SPEC_VERSION: &str = "1.5";
+
+/// Formats that `export-sbom` can write.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub(crate) enum SbomFormat {
+    /// CycloneDX JSON
+    Cyclonedx,
+}
+
+#[derive(Debug, Parser)]
+pub(crate) struct ExportSbomArgs {
+    /// Path to root.json file for the repository
+    #[clap(short = 'r', long = "root")]
+    root: PathBuf,
+
+    /// TUF repository metadata base URL
+    #[clap(short = 'm', long = "metadata-url")]
+    metadata_base_url: Url,
+
+    /// Format of the document written to stdout
+    #[clap(long = "format", value_enum)]
+    format: SbomFormat,
+}
+
+impl ExportSbomArgs {
+    pub(crate) fn run(&self) -> Result<()> {
+        let repository = load_metadata_repo(&self.root, self.metadata_base_url.clone())?;
+        let document = match self.format {
+            SbomFormat::Cyclonedx => cyclonedx(&repository),
+        };
+        let mut buffer = to_stable_json_pretty(&document).context(error::JsonSerializationSnafu)?;
+        buffer.push(b'\n');
+        let mut stdout = std::io::stdout();
+        stdout
+            .write_all(&buffer)
+            .and_then(|()| stdout.flush())
+            .context(error::StdoutWriteSnafu)
+    }
+}
+
+/// A CycloneDX bill of materials with one component per target.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CycloneDxBom {
+    bom_format: &'static str,
+    spec_version: &'static str,
+    version: u32,
+    components: Vec<CycloneDxComponent>,
+}
+
+#[derive(Debug, Serialize)]
+struct CycloneDxComponent {
+    #[serde(rename = "type")]
+    component_type: &'static str,
+    #[serde(rename = "bom-ref")]
+    bom_ref: String,
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    hashes: Vec<CycloneDxHash>,
+}
+
+#[derive(Debug, Serialize)]
+struct CycloneDxHash {
+    alg: &'static str,
+    content: String,
+}
+
+/// Lists every target of the repository, including those of delegated roles, sorted by name. A
+/// target listed by more than one role is listed once, as the role clients trust for it lists it.
+fn cyclonedx(repository: &Repository) -> CycloneDxBom {
+    let names = repository
+        .all_targets()
+        .map(|(name, _)| name)
+        .collect::<BTreeSet<_>>();
+    let components = names
+        .into_iter()
+        .filter_map(|name| {
+            let target = repository.targets().signed.find_target(name).ok()?;
+            Some(CycloneDxComponent {
+                component_type: "file",
+                bom_ref: name.raw().to_owned(),
+                name: name.raw().to_owned(),
+                version: custom_version(target),
+                hashes: cyclonedx_hashes(target),
+            })
+        })
+        .collect();
+    CycloneDxBom {
+        bom_format: "CycloneDX",
+        spec_version: CYCLONEDX_SPEC_VERSION,
+        version: 1,
+        components,
+    }
+}
+
+/// The target's version, if its `custom` metadata has a string or number `version`.
+fn custom_version(target: &Target) -> Option<String> {
+    match target.custom.get("version")? {
+        Value::String(version) => Some(version.clone()),
+        Value::Number(version) => Some(version.to_string()),
+        _ => None,
+    }
+}
+
+/// The target's hashes, under their CycloneDX algorithm names. Hashes of algorithms CycloneDX
+/// doesn't name are left out.
+fn cyclonedx_hashes(target: &Target) -> Vec<CycloneDxHash> {
+    let mut hashes = vec![CycloneDxHash {
+        alg: "SHA-256",
+        content: hex::encode(&target.hashes.sha256),
+    }];
+    for (key, alg) in [
+        ("sha384", "SHA-384"),
+        ("sha512", "SHA-512"),
+        ("blake2b", "BLAKE2b-512"),
+    ] {
+        if let Some(Value::String(content)) = target.hashes._extra.get(key) {
+            hashes.push(CycloneDxHash {
+                alg,
+                content: content.clone(),
+            });
+        }
+    }
+    hashes
+}
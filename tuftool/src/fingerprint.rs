@@ -0,0 +1,108 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::common::load_metadata_repo;
+use crate::error::{self, Result};
+use clap::Parser;
+use olpc_cjson::CanonicalFormatter;
+use ring::digest::{digest, SHA256};
+use serde::Serialize;
+use serde_json::Value;
+use snafu::{ensure, OptionExt, ResultExt};
+use std::fmt::Write;
+use std::path::PathBuf;
+use tough::Repository;
+use url::Url;
+
+#[derive(Debug, Parser)]
+pub(crate) struct FingerprintArgs {
+    /// Path to root.json file for the repository
+    #[clap(short = 'r', long = "root")]
+    root: PathBuf,
+
+    /// TUF repository metadata base URL
+    #[clap(short = 'm', long = "metadata-url")]
+    metadata_base_url: Url,
+
+    /// Fail unless the repository has this fingerprint, e.g. one printed for the same repository
+    /// in another environment
+    #[clap(long = "expect")]
+    expect: Option<String>,
+}
+
+impl FingerprintArgs {
+    pub(crate) fn run(&self) -> Result<()> {
+        let repository = load_metadata_repo(&self.root, self.metadata_base_url.clone())?;
+        let fingerprint = fingerprint(&repository)?;
+        println!("{fingerprint}");
+        if let Some(expected) = &self.expect {
+            ensure!(
+                fingerprint.eq_ignore_ascii_case(expected.trim()),
+                error::FingerprintMismatchSnafu {
+                    expected,
+                    fingerprint,
+                }
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Computes the sha256 of one line per role, in the order of `Repository::metadata_summary`,
+/// holding the role's name, its version, and the sha256 of its signed metadata as canonical JSON.
+///
+/// Signatures are left out, so re-signing the same metadata doesn't change the fingerprint. For
+/// the same reason the hashes and lengths that timestamp and snapshot list for other metadata
+/// files are left out too: they cover those files' signatures, while the signed part of every file
+/// they refer to is already part of the fingerprint.
+fn fingerprint(repository: &Repository) -> Result<String> {
+    let summary = repository.metadata_summary();
+    let mut hashes = vec![
+        canonical_sha256("root", &repository.root().signed, false)?,
+        canonical_sha256("timestamp", &repository.timestamp().signed, true)?,
+        canonical_sha256("snapshot", &repository.snapshot().signed, true)?,
+        canonical_sha256("targets", &repository.targets().signed, false)?,
+    ];
+    // The rest of the summary lists the delegated roles
+    for role in summary.iter().skip(hashes.len()) {
+        let targets = repository
+            .delegated_role(&role.name)
+            .and_then(|delegated_role| delegated_role.targets.as_ref())
+            .context(error::MissingSnafu {
+                what: format!("metadata for delegated role '{}'", role.name),
+            })?;
+        hashes.push(canonical_sha256(&role.name, &targets.signed, false)?);
+    }
+
+    let mut lines = String::new();
+    for (role, hash) in summary.iter().zip(&hashes) {
+        // Writing to a String can't fail
+        let _ = writeln!(lines, "{} {} {}", role.name, role.version, hash);
+    }
+    Ok(hex::encode(digest(&SHA256, lines.as_bytes())))
+}
+
+/// Returns the hex sha256 of `signed` serialized as canonical JSON. If `strip_meta_hashes` is set,
+/// the `hashes` and `length` of each entry in its `meta` map are removed first.
+fn canonical_sha256<T: Serialize>(
+    role: &str,
+    signed: &T,
+    strip_meta_hashes: bool,
+) -> Result<String> {
+    let mut value =
+        serde_json::to_value(signed).context(error::FingerprintSerializeSnafu { role })?;
+    if strip_meta_hashes {
+        if let Some(meta) = value.get_mut("meta").and_then(Value::as_object_mut) {
+            for entry in meta.values_mut().filter_map(Value::as_object_mut) {
+                entry.remove("hashes");
+                entry.remove("length");
+            }
+        }
+    }
+    let mut data = Vec::new();
+    let mut ser = serde_json::Serializer::with_formatter(&mut data, CanonicalFormatter::new());
+    value
+        .serialize(&mut ser)
+        .context(error::FingerprintSerializeSnafu { role })?;
+    Ok(hex::encode(digest(&SHA256, &data)))
+}
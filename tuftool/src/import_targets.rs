@@ -0,0 +1,113 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::common::{load_metadata_repo, write_delegated_targets};
+use crate::datetime::parse_datetime;
+use crate::error::{self, Result};
+use crate::load_file;
+use crate::source::parse_key_source;
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use snafu::{ensure, OptionExt, ResultExt};
+use std::num::NonZeroU64;
+use std::path::PathBuf;
+use tough::editor::targets::TargetsEditor;
+use tough::key_source::KeySource;
+use tough::schema::{Signed, Targets};
+use url::Url;
+
+#[derive(Debug, Parser)]
+pub(crate) struct ImportTargetsArgs {
+    /// Key files to sign with
+    #[clap(short = 'k', long = "key", required = true, parse(try_from_str = parse_key_source))]
+    keys: Vec<Box<dyn KeySource>>,
+
+    /// Expiration of new role file; can be in full RFC 3339 format, or something like 'in
+    /// 7 days'
+    #[clap(short = 'e', long = "expires", parse(try_from_str = parse_datetime))]
+    expires: DateTime<Utc>,
+
+    /// Version of role file
+    #[clap(short = 'v', long = "version")]
+    version: NonZeroU64,
+
+    /// Path to root.json file for the repository
+    #[clap(short = 'r', long = "root")]
+    root: PathBuf,
+
+    /// TUF repository metadata base URL
+    #[clap(short = 'm', long = "metadata-url")]
+    metadata_base_url: Url,
+
+    /// The directory where the repository will be written; use '-' to write the signed role to
+    /// stdout
+    #[clap(short = 'o', long = "outdir")]
+    outdir: PathBuf,
+
+    /// Path to the external targets.json whose targets will replace the role's targets; its
+    /// signatures are discarded
+    #[clap(long = "targets-file")]
+    targets_file: PathBuf,
+}
+
+impl ImportTargetsArgs {
+    pub(crate) fn run(&self, role: &str) -> Result<()> {
+        let repository = load_metadata_repo(&self.root, self.metadata_base_url.clone())?;
+        let imported: Signed<Targets> = load_file(&self.targets_file)?;
+        let imported = imported.signed.targets;
+        let mut delegation_tree = repository.targets().signed.clone();
+        let mut editor = TargetsEditor::from_repo(repository, role)
+            .context(error::EditorFromRepoSnafu { path: &self.root })?;
+
+        // Every imported target has to be reachable through `role`, so check each one against a
+        // copy of the delegation tree in which `role` holds the only targets
+        if role != "targets" {
+            delegation_tree.targets.clear();
+            let role_names = delegation_tree
+                .role_names()
+                .into_iter()
+                .cloned()
+                .collect::<Vec<_>>();
+            for name in role_names {
+                if let Ok(targets) = delegation_tree.delegated_targets_mut(&name) {
+                    targets.signed.targets.clear();
+                }
+            }
+            delegation_tree
+                .delegated_targets_mut(role)
+                .ok()
+                .context(error::MissingSnafu {
+                    what: format!("metadata for role '{role}'"),
+                })?
+                .signed
+                .targets = imported.clone();
+            for target_name in imported.keys() {
+                ensure!(
+                    delegation_tree.find_target(target_name).is_ok(),
+                    error::TargetNotDelegatedSnafu {
+                        target: target_name.raw(),
+                        role,
+                    }
+                );
+            }
+        }
+
+        editor.clear_targets();
+        for (target_name, target) in imported {
+            editor
+                .add_target(target_name, target)
+                .context(error::InvalidTargetNameSnafu)?;
+        }
+        let updated_role = editor
+            .version(self.version)
+            .expires(self.expires)
+            .sign(&self.keys)
+            .context(error::SignRepoSnafu)?;
+        write_delegated_targets(
+            updated_role,
+            &self.outdir,
+            role,
+            [role.to_string()].to_vec(),
+        )
+    }
+}
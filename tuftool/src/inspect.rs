@@ -0,0 +1,97 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::common::{load_metadata_repo, role_keys};
+use crate::error::{self, Result};
+use clap::{Parser, ValueEnum};
+use snafu::{OptionExt, ResultExt};
+use std::io::Write;
+use std::path::PathBuf;
+use tough::schema::RoleType;
+use url::Url;
+
+/// The top-level roles whose metadata can be inspected.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub(crate) enum InspectRole {
+    /// root.json
+    Root,
+    /// timestamp.json
+    Timestamp,
+    /// snapshot.json
+    Snapshot,
+    /// targets.json
+    Targets,
+}
+
+impl From<InspectRole> for RoleType {
+    fn from(role: InspectRole) -> Self {
+        match role {
+            InspectRole::Root => RoleType::Root,
+            InspectRole::Timestamp => RoleType::Timestamp,
+            InspectRole::Snapshot => RoleType::Snapshot,
+            InspectRole::Targets => RoleType::Targets,
+        }
+    }
+}
+
+#[derive(Debug, Parser)]
+pub(crate) struct InspectArgs {
+    /// Path to root.json file for the repository
+    #[clap(short = 'r', long = "root")]
+    root: PathBuf,
+
+    /// TUF repository metadata base URL
+    #[clap(short = 'm', long = "metadata-url")]
+    metadata_base_url: Url,
+
+    /// Role whose metadata is written to stdout
+    #[clap(long = "role", value_enum)]
+    role: InspectRole,
+
+    /// Write the role's metadata exactly as the repository served it, once verified, instead of
+    /// re-serializing it
+    #[clap(long = "raw")]
+    raw: bool,
+}
+
+impl InspectArgs {
+    pub(crate) fn run(&self) -> Result<()> {
+        let repository = load_metadata_repo(&self.root, self.metadata_base_url.clone())?;
+        let role = RoleType::from(self.role);
+        let mut stdout = std::io::stdout();
+
+        // stdout only holds the metadata, so the keys of the role are described on stderr
+        for key in role_keys(&repository)
+            .iter()
+            .filter(|key| key.role == role.to_string())
+        {
+            eprintln!(
+                "Role '{}' key {} uses scheme {}",
+                key.role, key.key_id, key.scheme
+            );
+        }
+
+        if self.raw {
+            let raw = repository.raw_metadata(role).context(error::MissingSnafu {
+                what: format!("raw {role} metadata"),
+            })?;
+            return stdout
+                .write_all(raw)
+                .and_then(|()| stdout.flush())
+                .context(error::StdoutWriteSnafu);
+        }
+
+        match self.role {
+            InspectRole::Root => serde_json::to_writer_pretty(&mut stdout, repository.root()),
+            InspectRole::Timestamp => {
+                serde_json::to_writer_pretty(&mut stdout, repository.timestamp())
+            }
+            InspectRole::Snapshot => {
+                serde_json::to_writer_pretty(&mut stdout, repository.snapshot())
+            }
+            InspectRole::Targets => serde_json::to_writer_pretty(&mut stdout, repository.targets()),
+        }
+        .context(error::InspectWriteSnafu { role })?;
+        writeln!(stdout).context(error::StdoutWriteSnafu)
+    }
+}
@@ -0,0 +1,50 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+use serde_json::json;
+use std::io::Write;
+
+/// The crates whose log records are written; the same ones `TermLogger` is configured with.
+const ALLOWED_TARGETS: [&str; 2] = ["tuftool", "tough"];
+
+/// A logger that writes each record to stderr as a single line of JSON with `level`, `target`
+/// and `message` fields, for environments where logs are collected and parsed.
+pub(crate) struct JsonLogger {
+    level: LevelFilter,
+}
+
+impl JsonLogger {
+    /// Installs a `JsonLogger` as the global logger.
+    pub(crate) fn init(level: LevelFilter) -> Result<(), SetLoggerError> {
+        log::set_boxed_logger(Box::new(Self { level }))?;
+        log::set_max_level(level);
+        Ok(())
+    }
+}
+
+impl Log for JsonLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= self.level
+            && ALLOWED_TARGETS
+                .iter()
+                .any(|target| metadata.target().starts_with(target))
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = json!({
+            "level": record.level().as_str(),
+            "target": record.target(),
+            "message": record.args().to_string(),
+        });
+        // There is nowhere left to report a failure to write a log line
+        let _ = writeln!(std::io::stderr().lock(), "{line}");
+    }
+
+    fn flush(&self) {
+        let _ = std::io::stderr().flush();
+    }
+}
@@ -14,6 +14,7 @@
 
 mod add_key_role;
 mod add_role;
+mod bump_version;
 mod clone;
 mod common;
 mod create;
@@ -22,30 +23,44 @@ mod datetime;
 mod download;
 mod download_root;
 mod error;
+mod export_sbom;
+mod fingerprint;
+mod import_targets;
+mod inspect;
+mod json_logger;
 mod remove_key_role;
 mod remove_role;
+mod resign;
 mod root;
+mod scaffold;
+mod set_paths;
+mod sign;
+mod sign_ceremony;
+mod simulate_update;
 mod source;
+mod target_info;
 mod transfer_metadata;
 mod update;
 mod update_targets;
+mod verify;
+mod warnings;
 
 use crate::error::Result;
+use crate::json_logger::JsonLogger;
 use clap::Parser;
 use rayon::prelude::*;
 use simplelog::{ColorChoice, ConfigBuilder, LevelFilter, TermLogger, TerminalMode};
 use snafu::{ErrorCompat, OptionExt, ResultExt};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::Write;
+use std::num::NonZeroUsize;
 use std::path::Path;
 use tempfile::NamedTempFile;
-use tough::schema::Target;
+use tough::schema::{to_stable_json_pretty, Target};
 use tough::TargetName;
 use walkdir::WalkDir;
 
-static SPEC_VERSION: &str = "1.0.0";
-
 /// This wrapper enables global options and initializes the logger before running any subcommands.
 #[derive(Parser)]
 struct Program {
@@ -57,21 +72,37 @@ struct Program {
         default_value = "info"
     )]
     log_level: LevelFilter,
+    /// Only log errors; overrides `--log-level`
+    #[clap(short = 'q', long = "quiet")]
+    quiet: bool,
+    /// Write logs to stderr as JSON lines with `level`, `target` and `message` fields
+    #[clap(long = "json-logs")]
+    json_logs: bool,
     #[clap(subcommand)]
     cmd: Command,
 }
 
 impl Program {
     fn run(self) -> Result<()> {
-        TermLogger::init(
-            self.log_level,
-            ConfigBuilder::new()
-                .add_filter_allow_str("tuftool")
-                .add_filter_allow_str("tough")
-                .build(),
-            TerminalMode::Mixed,
-            ColorChoice::Auto,
-        )
+        let log_level = if self.quiet {
+            LevelFilter::Error
+        } else {
+            self.log_level
+        };
+        if self.json_logs {
+            JsonLogger::init(log_level)
+        } else {
+            TermLogger::init(
+                log_level,
+                ConfigBuilder::new()
+                    .add_filter_allow_str("tuftool")
+                    .add_filter_allow_str("tough")
+                    .build(),
+                // Keep stdout free for commands that write signed metadata to it
+                TerminalMode::Stderr,
+                ColorChoice::Auto,
+            )
+        }
         .context(error::LoggerSnafu)?;
         self.cmd.run()
     }
@@ -85,6 +116,14 @@ enum Command {
     Download(download::DownloadArgs),
     /// Update a TUF repository's metadata and optionally add targets
     Update(Box<update::UpdateArgs>),
+    /// Re-issue one role of a TUF repository at the next version, without changing its content
+    BumpVersion(bump_version::BumpVersionArgs),
+    /// Re-sign the top-level roles of a local TUF repository whose signatures don't verify,
+    /// leaving the others untouched
+    Resign(resign::ResignArgs),
+    /// Sign the top-level roles of an unsigned or partially signed TUF repository in place, e.g.
+    /// one assembled in a separate step without access to the keys
+    Sign(sign::SignArgs),
     /// Manipulate a root.json metadata file
     #[clap(subcommand)]
     Root(root::Command),
@@ -94,6 +133,24 @@ enum Command {
     Clone(clone::CloneArgs),
     /// Transfer a TUF repository's metadata from a previous root to a new root
     TransferMetadata(transfer_metadata::TransferMetadataArgs),
+    /// Load and verify a TUF repository's metadata
+    Verify(verify::VerifyArgs),
+    /// Write a verified metadata file of a TUF repository to stdout
+    Inspect(inspect::InspectArgs),
+    /// Print a fingerprint of a TUF repository's verified metadata, to compare it across
+    /// environments
+    Fingerprint(fingerprint::FingerprintArgs),
+    /// Collect detached signatures of metadata from several signers
+    #[clap(subcommand)]
+    SignCeremony(sign_ceremony::Command),
+    /// Simulate a client that trusts one state of a TUF repository updating to another, and
+    /// report whether it accepts the update
+    SimulateUpdate(simulate_update::SimulateUpdateArgs),
+    /// Write a bill of materials (SBOM) listing a TUF repository's verified targets to stdout
+    ExportSbom(export_sbom::ExportSbomArgs),
+    /// Show which roles are consulted, in order, to resolve a target name, and which one's target
+    /// is trusted
+    TargetInfo(target_info::TargetInfoArgs),
 }
 
 impl Command {
@@ -103,9 +160,19 @@ impl Command {
             Command::Root(root_subcommand) => root_subcommand.run(),
             Command::Download(args) => args.run(),
             Command::Update(args) => args.run(),
+            Command::BumpVersion(args) => args.run(),
+            Command::Resign(args) => args.run(),
+            Command::Sign(args) => args.run(),
             Command::Delegation(cmd) => cmd.run(),
             Command::Clone(cmd) => cmd.run(),
             Command::TransferMetadata(cmd) => cmd.run(),
+            Command::Verify(cmd) => cmd.run(),
+            Command::Inspect(cmd) => cmd.run(),
+            Command::Fingerprint(cmd) => cmd.run(),
+            Command::SignCeremony(cmd) => cmd.run(),
+            Command::SimulateUpdate(cmd) => cmd.run(),
+            Command::ExportSbom(cmd) => cmd.run(),
+            Command::TargetInfo(cmd) => cmd.run(),
         }
     }
 }
@@ -126,9 +193,10 @@ where
     let parent = path.parent().context(error::PathParentSnafu { path })?;
     let mut writer =
         NamedTempFile::new_in(parent).context(error::FileTempCreateSnafu { path: parent })?;
-    serde_json::to_writer_pretty(&mut writer, json).context(error::FileWriteJsonSnafu { path })?;
+    let mut buffer = to_stable_json_pretty(json).context(error::FileWriteJsonSnafu { path })?;
+    buffer.push(b'\n');
     writer
-        .write_all(b"\n")
+        .write_all(&buffer)
         .context(error::FileWriteSnafu { path })?;
     writer
         .persist(path)
@@ -136,31 +204,50 @@ where
     Ok(())
 }
 
-// Walk the directory specified, building a map of filename to Target structs.
-// Hashing of the targets is done in parallel
-fn build_targets<P>(indir: P, follow_links: bool) -> Result<HashMap<TargetName, Target>>
+/// How many chunks of a target may be read ahead of its hashing when `--hash-chunk-size` is given.
+const HASH_READ_AHEAD: usize = 4;
+
+// Walk the directory specified, building a map of filename to Target structs, sorted by name.
+// Hashing of the targets is done in parallel on rayon's global thread pool, whose size the `--jobs`
+// arguments set. If `hash_chunk_size` is given, each target is also read on its own thread while
+// it is hashed. The directory is walked in file name order and the hashed targets keep that order,
+// so the result doesn't depend on thread scheduling: if files in different subdirectories share a
+// name, the one walked last is always the one kept.
+fn build_targets<P>(
+    indir: P,
+    follow_links: bool,
+    hash_chunk_size: Option<NonZeroUsize>,
+) -> Result<BTreeMap<TargetName, Target>>
 where
     P: AsRef<Path>,
 {
     let indir = indir.as_ref();
-    WalkDir::new(indir)
+    let paths = WalkDir::new(indir)
         .follow_links(follow_links)
+        .sort_by_file_name()
         .into_iter()
-        .par_bridge()
         .filter_map(|entry| match entry {
             Ok(entry) => {
                 if entry.file_type().is_file() {
-                    Some(process_target(entry.path()))
+                    Some(Ok(entry.into_path()))
                 } else {
                     None
                 }
             }
             Err(err) => Some(Err(err).context(error::WalkDirSnafu { directory: indir })),
         })
-        .collect()
+        .collect::<Result<Vec<_>>>()?;
+    let targets = paths
+        .par_iter()
+        .map(|path| process_target(path, hash_chunk_size))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(targets.into_iter().collect())
 }
 
-fn process_target(path: &Path) -> Result<(TargetName, Target)> {
+fn process_target(
+    path: &Path,
+    hash_chunk_size: Option<NonZeroUsize>,
+) -> Result<(TargetName, Target)> {
     // Get the file name as a TargetName
     let target_name = TargetName::new(
         path.file_name()
@@ -171,7 +258,15 @@ fn process_target(path: &Path) -> Result<(TargetName, Target)> {
     .context(error::InvalidTargetNameSnafu)?;
 
     // Build a Target from the path given. If it is not a file, this will fail
-    let target = Target::from_path(path).context(error::TargetFromPathSnafu { path })?;
+    let target = match hash_chunk_size {
+        Some(chunk_size) => Target::from_path_pipelined(
+            path,
+            chunk_size,
+            NonZeroUsize::new(HASH_READ_AHEAD).unwrap(),
+        ),
+        None => Target::from_path(path),
+    }
+    .context(error::TargetFromPathSnafu { path })?;
 
     Ok((target_name, target))
 }
@@ -223,6 +318,12 @@ enum DelegationCommand {
     RemoveKey(Box<remove_key_role::RemoveKeyArgs>),
     /// Remove a role
     Remove(Box<remove_role::RemoveRoleArgs>),
+    /// Import an external targets.json into a delegated role
+    Import(Box<import_targets::ImportTargetsArgs>),
+    /// Set the paths delegated to a delegated role
+    SetPaths(Box<set_paths::SetPathsArgs>),
+    /// Write an unsigned skeleton of a role to delegate, for its owners to fill in and sign
+    Scaffold(Box<scaffold::ScaffoldArgs>),
 }
 
 impl DelegationCommand {
@@ -234,6 +335,9 @@ impl DelegationCommand {
             DelegationCommand::AddKey(args) => args.run(role),
             DelegationCommand::RemoveKey(args) => args.run(role),
             DelegationCommand::Remove(args) => args.run(role),
+            DelegationCommand::Import(args) => args.run(role),
+            DelegationCommand::SetPaths(args) => args.run(role),
+            DelegationCommand::Scaffold(args) => args.run(role),
         }
     }
 }
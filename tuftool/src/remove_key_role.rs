@@ -1,7 +1,7 @@
 // Copyright 2019 Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use crate::common::load_metadata_repo;
+use crate::common::{load_metadata_repo, write_delegated_targets};
 use crate::datetime::parse_datetime;
 use crate::error::{self, Result};
 use crate::source::parse_key_source;
@@ -42,7 +42,8 @@ pub(crate) struct RemoveKeyArgs {
     #[clap(short = 'm', long = "metadata-url")]
     metadata_base_url: Url,
 
-    /// The directory where the repository will be written
+    /// The directory where the repository will be written; use '-' to write the signed role to
+    /// stdout
     #[clap(short = 'o', long = "outdir")]
     outdir: PathBuf,
 
@@ -70,13 +71,11 @@ impl RemoveKeyArgs {
             .expires(self.expires)
             .sign(&self.keys)
             .context(error::SignRepoSnafu)?;
-        let metadata_destination_out = &self.outdir.join("metadata");
-        updated_role
-            .write(metadata_destination_out, false)
-            .context(error::WriteRolesSnafu {
-                roles: [role.to_string()].to_vec(),
-            })?;
-
-        Ok(())
+        write_delegated_targets(
+            updated_role,
+            &self.outdir,
+            role,
+            [role.to_string()].to_vec(),
+        )
     }
 }
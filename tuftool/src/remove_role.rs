@@ -1,17 +1,19 @@
 // Copyright 2019 Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use crate::common::load_metadata_repo;
+use crate::common::{load_metadata_repo, write_delegated_targets};
 use crate::datetime::parse_datetime;
 use crate::error::{self, Result};
 use crate::source::parse_key_source;
 use chrono::{DateTime, Utc};
 use clap::Parser;
-use snafu::ResultExt;
+use snafu::{ensure, ResultExt};
+use std::io::{BufRead, IsTerminal};
 use std::num::NonZeroU64;
 use std::path::PathBuf;
 use tough::editor::targets::TargetsEditor;
 use tough::key_source::KeySource;
+use tough::schema::Targets;
 use url::Url;
 
 #[derive(Debug, Parser)]
@@ -37,7 +39,8 @@ pub(crate) struct RemoveRoleArgs {
     #[clap(short = 'm', long = "metadata-url")]
     metadata_base_url: Url,
 
-    /// The directory where the repository will be written
+    /// The directory where the repository will be written; use '-' to write the signed role to
+    /// stdout
     #[clap(short = 'o', long = "outdir")]
     outdir: PathBuf,
 
@@ -48,11 +51,30 @@ pub(crate) struct RemoveRoleArgs {
     /// Determine if the role should be removed even if it's not a direct delegatee
     #[clap(long = "recursive")]
     recursive: bool,
+
+    /// Remove the roles without asking for confirmation; a recursive removal that isn't run
+    /// interactively is aborted unless this is passed
+    #[clap(long = "yes")]
+    yes: bool,
 }
 
 impl RemoveRoleArgs {
     pub(crate) fn run(&self, role: &str) -> Result<()> {
         let repository = load_metadata_repo(&self.root, self.metadata_base_url.clone())?;
+        if self.recursive && !self.yes {
+            // A role that isn't found is reported when the editor is created below
+            let targets = if role == "targets" {
+                Some(&repository.targets().signed)
+            } else {
+                repository
+                    .delegated_role(role)
+                    .and_then(|delegated_role| delegated_role.targets.as_ref())
+                    .map(|targets| &targets.signed)
+            };
+            if let Some(targets) = targets {
+                confirm_removal(&removed_roles(targets, &self.delegated_role))?;
+            }
+        }
         self.remove_delegated_role(
             role,
             TargetsEditor::from_repo(repository, role)
@@ -69,13 +91,61 @@ impl RemoveRoleArgs {
             .expires(self.expires)
             .sign(&self.keys)
             .context(error::SignRepoSnafu)?;
-        let metadata_destination_out = &self.outdir.join("metadata");
-        updated_role
-            .write(metadata_destination_out, false)
-            .context(error::WriteRolesSnafu {
-                roles: [role.to_string()].to_vec(),
-            })?;
+        write_delegated_targets(
+            updated_role,
+            &self.outdir,
+            role,
+            [role.to_string()].to_vec(),
+        )
+    }
+}
 
-        Ok(())
+/// Returns the roles that a recursive removal of `role` drops from `targets`: each role delegated
+/// by `targets` that is `role` or eventually delegates it, along with every role below it.
+fn removed_roles(targets: &Targets, role: &str) -> Vec<String> {
+    let mut removed = Vec::new();
+    for delegated_role in targets.delegations.iter().flat_map(|d| &d.roles) {
+        let subtree = delegated_role
+            .targets
+            .as_ref()
+            .map(|targets| &targets.signed);
+        if delegated_role.name == role
+            || subtree.map_or(false, |targets| targets.delegated_role(role).is_ok())
+        {
+            removed.push(delegated_role.name.clone());
+            if let Some(targets) = subtree {
+                removed.extend(targets.role_names().into_iter().cloned());
+            }
+        }
+    }
+    removed
+}
+
+/// Lists the roles that will be removed and asks for confirmation on stdin. Fails without asking
+/// if stdin isn't a terminal, since nobody is there to confirm.
+fn confirm_removal(roles: &[String]) -> Result<()> {
+    if roles.is_empty() {
+        return Ok(());
+    }
+    let roles_list = roles.join(", ");
+    eprintln!("The following roles will be removed:");
+    for role in roles {
+        eprintln!("  {role}");
     }
+    let stdin = std::io::stdin();
+    ensure!(
+        stdin.is_terminal(),
+        error::RemoveRoleUnconfirmedSnafu { roles: roles_list }
+    );
+    eprint!("Remove them? [y/N] ");
+    let mut answer = String::new();
+    stdin
+        .lock()
+        .read_line(&mut answer)
+        .context(error::StdinReadSnafu)?;
+    ensure!(
+        matches!(answer.trim(), "y" | "Y" | "yes"),
+        error::RemoveRoleUnconfirmedSnafu { roles: roles_list }
+    );
+    Ok(())
 }
@@ -0,0 +1,112 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::error::{self, Result};
+use crate::sign::{listed_path, update_snapshot_meta, update_targets_meta};
+use crate::source::parse_key_source;
+use crate::{load_file, write_file};
+use clap::Parser;
+use ring::rand::SystemRandom;
+use serde::Serialize;
+use snafu::ResultExt;
+use std::path::{Path, PathBuf};
+use tough::editor::signed::SignedRole;
+use tough::key_source::KeySource;
+use tough::schema::{KeyHolder, Role, Root, Signed, Snapshot, Targets, Timestamp};
+
+#[derive(Debug, Parser)]
+pub(crate) struct ResignArgs {
+    /// Key files to sign with
+    #[clap(short = 'k', long = "key", required = true, parse(try_from_str = parse_key_source))]
+    keys: Vec<Box<dyn KeySource>>,
+
+    /// Path to root.json file for the repository
+    #[clap(short = 'r', long = "root")]
+    root: PathBuf,
+
+    /// Directory of the repository's metadata. Roles are re-signed in place
+    #[clap(long = "metadata-dir")]
+    metadata_dir: PathBuf,
+}
+
+impl ResignArgs {
+    /// Checks the signatures of targets.json, snapshot.json, and timestamp.json against root.json
+    /// and re-signs each role whose signatures don't verify, leaving the others untouched.
+    ///
+    /// Re-signing targets.json changes the length and hashes that snapshot.json records for it,
+    /// and re-signing snapshot.json those that timestamp.json records for it. Where they are
+    /// recorded, they're updated, which invalidates the signatures of the recording role, so it
+    /// is re-signed in turn.
+    pub(crate) fn run(&self) -> Result<()> {
+        let root: Signed<Root> = load_file(&self.root)?;
+        let consistent_snapshot = root.signed.consistent_snapshot;
+
+        let timestamp_path = self.metadata_dir.join("timestamp.json");
+        let mut timestamp: Signed<Timestamp> = load_file(&timestamp_path)?;
+        let snapshot_path = listed_path(
+            &self.metadata_dir,
+            "snapshot.json",
+            timestamp
+                .signed
+                .meta
+                .get("snapshot.json")
+                .map(|meta| meta.version),
+            consistent_snapshot,
+        )?;
+        let mut snapshot: Signed<Snapshot> = load_file(&snapshot_path)?;
+        let targets_path = listed_path(
+            &self.metadata_dir,
+            "targets.json",
+            snapshot
+                .signed
+                .meta
+                .get("targets.json")
+                .map(|meta| meta.version),
+            consistent_snapshot,
+        )?;
+        let targets: Signed<Targets> = load_file(&targets_path)?;
+
+        let mut resigned = 0;
+        if self.resign_if_invalid(&root.signed, &targets, &targets_path)? {
+            resigned += 1;
+            update_targets_meta(&mut snapshot.signed, &targets_path)?;
+        }
+        if self.resign_if_invalid(&root.signed, &snapshot, &snapshot_path)? {
+            resigned += 1;
+            update_snapshot_meta(
+                &mut timestamp.signed,
+                &snapshot_path,
+                snapshot.signed.version,
+            )?;
+        }
+        if self.resign_if_invalid(&root.signed, &timestamp, &timestamp_path)? {
+            resigned += 1;
+        }
+
+        if resigned == 0 {
+            println!("All roles have valid signatures; nothing was re-signed");
+        }
+        Ok(())
+    }
+
+    /// Re-signs `role` with the given keys and writes it back to `path` if its signatures don't
+    /// verify against `root`. Returns whether the role was re-signed.
+    fn resign_if_invalid<T>(&self, root: &Root, role: &Signed<T>, path: &Path) -> Result<bool>
+    where
+        T: Role + Serialize + Clone,
+    {
+        if root.verify_role(role).is_ok() {
+            return Ok(false);
+        }
+        let signed_role = SignedRole::new(
+            role.signed.clone(),
+            &KeyHolder::Root(root.clone()),
+            &self.keys,
+            &SystemRandom::new(),
+        )
+        .context(error::ResignRoleSnafu { role: T::TYPE })?;
+        write_file(path, signed_role.signed())?;
+        println!("Re-signed role '{}'", T::TYPE);
+        Ok(true)
+    }
+}
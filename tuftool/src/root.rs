@@ -1,26 +1,31 @@
 // Copyright 2019 Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use crate::datetime::parse_datetime;
+use crate::common::parse_base_url;
+use crate::datetime::{clock, parse_datetime};
 use crate::error::{self, Result};
 use crate::source::parse_key_source;
 use crate::{load_file, write_file};
 use chrono::{DateTime, Timelike, Utc};
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use log::warn;
 use maplit::hashmap;
 use ring::rand::SystemRandom;
+use serde_json::json;
 use snafu::{ensure, OptionExt, ResultExt};
-use std::collections::HashMap;
-use std::io::Write;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
+use std::io::{Read, Write};
 use std::num::NonZeroU64;
 use std::path::{Path, PathBuf};
 use tempfile::NamedTempFile;
 use tough::editor::signed::SignedRole;
 use tough::key_source::KeySource;
 use tough::schema::decoded::{Decoded, Hex};
-use tough::schema::{key::Key, KeyHolder, RoleKeys, RoleType, Root, Signed};
+use tough::schema::{key::Key, KeyHolder, RoleKeys, RoleType, Root, Signed, SPEC_VERSION};
 use tough::sign::{parse_keypair, Sign};
+use tough::{Clock, DefaultTransport, Limits, Transport, TransportErrorKind};
+use url::Url;
 
 #[derive(Debug, Parser)]
 pub(crate) enum Command {
@@ -83,6 +88,40 @@ pub(crate) enum Command {
         /// file)
         role: Option<RoleType>,
     },
+    /// Replace a key ID with a new key in every role that lists it, and bump the version
+    ReplaceKey {
+        /// Path to root.json
+        path: PathBuf,
+        /// The key ID to replace
+        key_id: Decoded<Hex>,
+        /// The new key
+        #[clap(short = 'k', long = "key", parse(try_from_str = parse_key_source))]
+        key_source: Box<dyn KeySource>,
+    },
+    /// Add a key to the root role, which signs root.json itself, and bump the version. The new
+    /// root.json must then be signed by a threshold of keys from both the old and the new root role
+    AddRootKey {
+        /// Path to root.json
+        path: PathBuf,
+        /// The new key
+        #[clap(short = 'k', long = "key", parse(try_from_str = parse_key_source))]
+        key_source: Box<dyn KeySource>,
+        /// Confirm changing the keys of the root role
+        #[clap(long = "confirm")]
+        confirm: bool,
+    },
+    /// Remove a key ID from the root role, which signs root.json itself, and bump the version.
+    /// The new root.json must then be signed by a threshold of keys from both the old and the new
+    /// root role
+    RemoveRootKey {
+        /// Path to root.json
+        path: PathBuf,
+        /// The key ID to remove
+        key_id: Decoded<Hex>,
+        /// Confirm changing the keys of the root role
+        #[clap(long = "confirm")]
+        confirm: bool,
+    },
     /// Generate a new RSA key pair, saving it to a file, and add it to a role
     GenRsaKey {
         /// Path to root.json
@@ -114,6 +153,80 @@ pub(crate) enum Command {
         #[clap(short = 'i', long = "ignore-threshold")]
         ignore_threshold: bool,
     },
+    /// Check that a new root.json is signed by enough keys of both the old root.json and itself
+    VerifyTransition {
+        /// Path to the currently trusted root.json
+        old: PathBuf,
+        /// Path to the new root.json, whose version must follow the old one's
+        new: PathBuf,
+    },
+    /// Show the changes between two versions of root.json: keys added to or removed from each
+    /// role, threshold changes, and version and expiration bumps
+    Diff {
+        /// Path to the older root.json
+        old: PathBuf,
+        /// Path to the newer root.json
+        new: PathBuf,
+    },
+    /// Show each role's threshold and key IDs, and a set of keys that satisfies every threshold
+    Thresholds {
+        /// Path to root.json
+        #[clap(short = 'r', long = "root")]
+        root: PathBuf,
+        /// Output format
+        #[clap(long = "format", value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+    /// Check root.json for problems that make it unusable, such as roles without keys or
+    /// thresholds their keys can't meet
+    Validate {
+        /// Path to root.json
+        path: PathBuf,
+    },
+    /// List the key IDs in root.json that no role uses
+    ListUnusedKeys {
+        /// Path to root.json
+        #[clap(short = 'r', long = "root")]
+        root: PathBuf,
+    },
+    /// Remove the keys that no role uses from root.json and bump the version. The new root.json
+    /// is signed with the given keys, or left unsigned if none are given
+    PruneUnusedKeys {
+        /// Path to root.json
+        #[clap(short = 'r', long = "root")]
+        root: PathBuf,
+        /// Key source(s) to sign the new root.json with
+        #[clap(short = 'k', long = "key", parse(try_from_str = parse_key_source))]
+        key_sources: Vec<Box<dyn KeySource>>,
+    },
+    /// Write the next version of a repository's latest root.json, signed with the given keys.
+    /// Useful to recover a repository whose root.json files were lost locally but whose root
+    /// keys were not. The repository's root.json isn't verified, but the given keys must meet
+    /// its root threshold, so the new root.json is a valid transition from it
+    Recover {
+        /// TUF repository metadata base URL to read the latest root.json from
+        #[clap(long = "from-repo", parse(try_from_str = parse_base_url))]
+        from_repo: Url,
+        /// Key source(s) of the root role to sign the new root.json with
+        #[clap(short = 'k', long = "key", required = true, parse(try_from_str = parse_key_source))]
+        key_sources: Vec<Box<dyn KeySource>>,
+        /// Expiration of the new root.json; can be in full RFC 3339 format, or something like 'in
+        /// 7 days'
+        #[clap(short = 'e', long = "expires", parse(try_from_str = parse_datetime))]
+        expires: DateTime<Utc>,
+        /// Path to write the new root.json to
+        #[clap(short = 'o', long = "outfile")]
+        outfile: PathBuf,
+    },
+}
+
+/// Output formats for commands that report on root.json.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub(crate) enum OutputFormat {
+    /// Human-readable text
+    Text,
+    /// JSON
+    Json,
 }
 
 macro_rules! role_keys {
@@ -149,6 +262,21 @@ impl Command {
                 key_source,
             } => Command::add_key(&path, &roles, &key_source),
             Command::RemoveKey { path, key_id, role } => Command::remove_key(&path, &key_id, role),
+            Command::ReplaceKey {
+                path,
+                key_id,
+                key_source,
+            } => Command::replace_key(&path, &key_id, &key_source),
+            Command::AddRootKey {
+                path,
+                key_source,
+                confirm,
+            } => Command::add_root_key(&path, &key_source, confirm),
+            Command::RemoveRootKey {
+                path,
+                key_id,
+                confirm,
+            } => Command::remove_root_key(&path, &key_id, confirm),
             Command::GenRsaKey {
                 path,
                 roles,
@@ -162,6 +290,20 @@ impl Command {
                 cross_sign,
                 ignore_threshold,
             } => Command::sign(&path, &key_sources, cross_sign, ignore_threshold),
+            Command::VerifyTransition { old, new } => Command::verify_transition(&old, &new),
+            Command::Diff { old, new } => Command::diff(&old, &new),
+            Command::Thresholds { root, format } => Command::thresholds(&root, format),
+            Command::Validate { path } => Command::validate(&path),
+            Command::ListUnusedKeys { root } => Command::list_unused_keys(&root),
+            Command::PruneUnusedKeys { root, key_sources } => {
+                Command::prune_unused_keys(&root, &key_sources)
+            }
+            Command::Recover {
+                from_repo,
+                key_sources,
+                expires,
+                outfile,
+            } => Command::recover(&from_repo, &key_sources, expires, &outfile),
         }
     }
 
@@ -171,10 +313,10 @@ impl Command {
             path,
             &Signed {
                 signed: Root {
-                    spec_version: crate::SPEC_VERSION.to_owned(),
+                    spec_version: SPEC_VERSION.to_owned(),
                     consistent_snapshot: true,
                     version: NonZeroU64::new(init_version).unwrap(),
-                    expires: round_time(Utc::now()),
+                    expires: round_time(clock()?.now()),
                     keys: HashMap::new(),
                     roles: hashmap! {
                         RoleType::Root => role_keys!(),
@@ -191,14 +333,7 @@ impl Command {
 
     fn bump_version(path: &Path) -> Result<()> {
         let mut root: Signed<Root> = load_file(path)?;
-        root.signed.version = NonZeroU64::new(
-            root.signed
-                .version
-                .get()
-                .checked_add(1)
-                .context(error::VersionOverflowSnafu)?,
-        )
-        .context(error::VersionZeroSnafu)?;
+        increment_version(&mut root.signed)?;
         clear_sigs(&mut root);
         write_file(path, &root)
     }
@@ -273,6 +408,129 @@ impl Command {
         write_file(path, &root)
     }
 
+    #[allow(clippy::borrowed_box)]
+    fn replace_key(
+        path: &Path,
+        old_key_id: &Decoded<Hex>,
+        key_source: &Box<dyn KeySource>,
+    ) -> Result<()> {
+        let mut root: Signed<Root> = load_file(path)?;
+        let key = key_source
+            .as_sign()
+            .context(error::KeyPairFromKeySourceSnafu)?
+            .tuf_key();
+        let new_key_id = add_key(&mut root.signed, &[], key)?;
+        ensure!(
+            &new_key_id != old_key_id,
+            error::KeyReplaceSameSnafu {
+                key_id: hex::encode(old_key_id)
+            }
+        );
+
+        let mut affected = Vec::new();
+        for (role, role_keys) in &mut root.signed.roles {
+            if let Some(pos) = role_keys.keyids.iter().position(|k| k == old_key_id) {
+                if role_keys.keyids.contains(&new_key_id) {
+                    // The role already trusts the new key, so the old one can only be dropped
+                    role_keys.keyids.remove(pos);
+                    ensure!(
+                        role_keys.keyids.len() as u64 >= role_keys.threshold.get(),
+                        error::KeyReplaceThresholdSnafu {
+                            role: *role,
+                            keys: role_keys.keyids.len(),
+                            threshold: role_keys.threshold.get(),
+                        }
+                    );
+                } else {
+                    // Swapping in place keeps the role's key count, and so its threshold
+                    role_keys.keyids[pos] = new_key_id.clone();
+                }
+                affected.push(role.to_string());
+            }
+        }
+        ensure!(
+            !affected.is_empty(),
+            error::KeyNotInRootSnafu {
+                key_id: hex::encode(old_key_id)
+            }
+        );
+        root.signed.keys.remove(old_key_id);
+
+        increment_version(&mut root.signed)?;
+        clear_sigs(&mut root);
+
+        affected.sort();
+        println!(
+            "Replaced key {} with {} in roles: {}",
+            hex::encode(old_key_id),
+            hex::encode(&new_key_id),
+            affected.join(", ")
+        );
+        write_file(path, &root)
+    }
+
+    #[allow(clippy::borrowed_box)]
+    fn add_root_key(path: &Path, key_source: &Box<dyn KeySource>, confirm: bool) -> Result<()> {
+        ensure!(confirm, error::RootKeyUnconfirmedSnafu);
+        let mut root: Signed<Root> = load_file(path)?;
+        let key = key_source
+            .as_sign()
+            .context(error::KeyPairFromKeySourceSnafu)?
+            .tuf_key();
+        let key_id = add_key(&mut root.signed, &[], key)?;
+        let root_keys = root_role_keys(&mut root.signed)?;
+        ensure!(
+            !root_keys.keyids.contains(&key_id),
+            error::RootRoleKeyPresentSnafu {
+                key_id: hex::encode(&key_id)
+            }
+        );
+        root_keys.keyids.push(key_id.clone());
+        check_root_role_threshold(root_keys)?;
+
+        increment_version(&mut root.signed)?;
+        clear_sigs(&mut root);
+        println!(
+            "Added key {} to the root role; version {} must be signed by keys of both the old and \
+            the new root role",
+            hex::encode(&key_id),
+            root.signed.version
+        );
+        write_file(path, &root)
+    }
+
+    fn remove_root_key(path: &Path, key_id: &Decoded<Hex>, confirm: bool) -> Result<()> {
+        ensure!(confirm, error::RootKeyUnconfirmedSnafu);
+        let mut root: Signed<Root> = load_file(path)?;
+        let root_keys = root_role_keys(&mut root.signed)?;
+        let pos = root_keys.keyids.iter().position(|k| k == key_id).context(
+            error::KeyNotInRootRoleSnafu {
+                key_id: hex::encode(key_id),
+            },
+        )?;
+        root_keys.keyids.remove(pos);
+        check_root_role_threshold(root_keys)?;
+        // Keep the public key if other roles still list it
+        if !root
+            .signed
+            .roles
+            .values()
+            .any(|role_keys| role_keys.keyids.contains(key_id))
+        {
+            root.signed.keys.remove(key_id);
+        }
+
+        increment_version(&mut root.signed)?;
+        clear_sigs(&mut root);
+        println!(
+            "Removed key {} from the root role; version {} must be signed by keys of both the old \
+            and the new root role",
+            hex::encode(key_id),
+            root.signed.version
+        );
+        write_file(path, &root)
+    }
+
     #[allow(clippy::borrowed_box)]
     fn gen_rsa_key(
         path: &Path,
@@ -315,7 +573,7 @@ impl Command {
         write_file(path, &root)
     }
 
-    fn sign(
+    pub(crate) fn sign(
         path: &Path,
         key_source: &[Box<dyn KeySource>],
         cross_sign: Option<PathBuf>,
@@ -410,6 +668,277 @@ impl Command {
             .context(error::FilePersistSnafu { path })?;
         Ok(())
     }
+
+    fn verify_transition(old_path: &Path, new_path: &Path) -> Result<()> {
+        let old: Signed<Root> = load_file(old_path)?;
+        let new: Signed<Root> = load_file(new_path)?;
+        let mut problems = Vec::new();
+
+        // TUF v1.0.16, 5.3.5: the new root's version must be exactly one more than the old one's
+        let expected_version = old
+            .signed
+            .version
+            .get()
+            .checked_add(1)
+            .context(error::VersionOverflowSnafu)?;
+        if new.signed.version.get() != expected_version {
+            problems.push(format!(
+                "new root is version {}, expected {}",
+                new.signed.version, expected_version
+            ));
+        }
+
+        // TUF v1.0.16, 5.3.4: the new root must be signed by a threshold of keys from the old root,
+        // and by a threshold of keys from itself
+        for (label, keys) in [("old", &old.signed), ("new", &new.signed)] {
+            if !report_root_signatures(label, keys, &new)? {
+                problems.push(format!("{label} root's threshold is not met"));
+            }
+        }
+
+        ensure!(
+            problems.is_empty(),
+            error::RootTransitionSnafu {
+                problems: problems.join("; ")
+            }
+        );
+        println!("Root transition is valid");
+        Ok(())
+    }
+
+    fn diff(old_path: &Path, new_path: &Path) -> Result<()> {
+        let old: Signed<Root> = load_file(old_path)?;
+        let new: Signed<Root> = load_file(new_path)?;
+        let diff = old.signed.diff(&new.signed);
+        if diff.is_empty() {
+            println!("No changes");
+        } else {
+            print!("{diff}");
+        }
+        Ok(())
+    }
+
+    fn validate(path: &Path) -> Result<()> {
+        let root: Signed<Root> = load_file(path)?;
+        let mut problems = root
+            .signed
+            .validate()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        // A repeated key is silently collapsed by the parse above, so check the raw file too
+        let buf = std::fs::read(path).context(error::FileReadSnafu { path })?;
+        if let Err(err) = tough::schema::check_unique_keys(&buf) {
+            problems.push(err.to_string());
+        }
+        for problem in &problems {
+            println!("{problem}");
+        }
+        ensure!(
+            problems.is_empty(),
+            error::RootInvalidSnafu {
+                count: problems.len()
+            }
+        );
+        println!("root.json is valid");
+        Ok(())
+    }
+
+    fn list_unused_keys(path: &Path) -> Result<()> {
+        let root: Signed<Root> = load_file(path)?;
+        for key_id in unused_keys(&root.signed) {
+            println!("{}", hex::encode(key_id));
+        }
+        Ok(())
+    }
+
+    fn prune_unused_keys(path: &Path, key_sources: &[Box<dyn KeySource>]) -> Result<()> {
+        let mut root: Signed<Root> = load_file(path)?;
+        let unused = unused_keys(&root.signed);
+        if unused.is_empty() {
+            println!("No unused keys");
+            return Ok(());
+        }
+        for key_id in &unused {
+            root.signed.keys.remove(key_id);
+            println!("Removed key: {}", hex::encode(key_id));
+        }
+        increment_version(&mut root.signed)?;
+        clear_sigs(&mut root);
+        write_file(path, &root)?;
+        if key_sources.is_empty() {
+            Ok(())
+        } else {
+            Command::sign(path, key_sources, None, false)
+        }
+    }
+
+    fn recover(
+        from_repo: &Url,
+        key_sources: &[Box<dyn KeySource>],
+        expires: DateTime<Utc>,
+        outfile: &Path,
+    ) -> Result<()> {
+        let old = latest_root(from_repo)?;
+        println!(
+            "Latest root.json in the repository is version {}",
+            old.signed.version
+        );
+        let mut new = old.signed.clone();
+        increment_version(&mut new)?;
+        new.expires = round_time(expires);
+        let signed_root = SignedRole::new(
+            new,
+            &KeyHolder::Root(old.signed.clone()),
+            key_sources,
+            &SystemRandom::new(),
+        )
+        .context(error::SignRootSnafu { path: outfile })?;
+
+        // The keys are unchanged, so meeting the old root's threshold also meets the new one's
+        ensure!(
+            report_root_signatures("old", &old.signed, signed_root.signed())?,
+            error::RootTransitionSnafu {
+                problems: "old root's threshold is not met"
+            }
+        );
+        write_file(outfile, signed_root.signed())?;
+        println!(
+            "Wrote root.json version {} to {}",
+            signed_root.signed().signed.version,
+            outfile.display()
+        );
+        Ok(())
+    }
+
+    fn thresholds(path: &Path, format: OutputFormat) -> Result<()> {
+        let root: Signed<Root> = load_file(path)?;
+        let root = root.signed;
+        let roles = ROLE_ORDER
+            .iter()
+            .filter_map(|role| root.roles.get(role).map(|keys| (*role, keys)))
+            .collect::<Vec<_>>();
+        let signers = minimum_signers(&root);
+
+        match format {
+            OutputFormat::Text => {
+                for (role, keys) in &roles {
+                    println!(
+                        "{}: threshold {} of {} keys",
+                        role,
+                        keys.threshold,
+                        keys.keyids.len()
+                    );
+                    for key_id in &keys.keyids {
+                        println!("  {}", hex::encode(key_id));
+                    }
+                }
+                match signers {
+                    Some(signers) => {
+                        println!("Minimum signers: {}", signers.len());
+                        for key_id in signers {
+                            println!("  {}", hex::encode(key_id));
+                        }
+                    }
+                    None => println!("Minimum signers: thresholds cannot be met"),
+                }
+            }
+            OutputFormat::Json => {
+                let roles = roles
+                    .iter()
+                    .map(|(role, keys)| {
+                        (
+                            role.to_string(),
+                            json!({
+                                "threshold": keys.threshold,
+                                "total_keys": keys.keyids.len(),
+                                "key_ids": keys.keyids.iter().map(hex::encode).collect::<Vec<_>>(),
+                            }),
+                        )
+                    })
+                    .collect::<serde_json::Map<_, _>>();
+                let signers = signers.map(|signers| {
+                    json!({
+                        "count": signers.len(),
+                        "key_ids": signers.iter().map(hex::encode).collect::<Vec<_>>(),
+                    })
+                });
+                println!(
+                    "{:#}",
+                    json!({ "roles": roles, "minimum_signers": signers })
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The order in which `thresholds` lists roles.
+const ROLE_ORDER: [RoleType; 4] = [
+    RoleType::Root,
+    RoleType::Snapshot,
+    RoleType::Targets,
+    RoleType::Timestamp,
+];
+
+/// Returns the IDs of the keys in `root` that no role lists, sorted.
+fn unused_keys(root: &Root) -> Vec<Decoded<Hex>> {
+    let mut unused = root
+        .keys
+        .keys()
+        .filter(|key_id| {
+            !root
+                .roles
+                .values()
+                .any(|role_keys| role_keys.keyids.contains(key_id))
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    unused.sort();
+    unused
+}
+
+/// Picks a set of keys that together meet every role's threshold, or `None` if some role has too
+/// few keys. Keys are chosen greedily, each time taking the key that counts toward the most roles
+/// still short of their threshold, so keys shared between roles are preferred.
+fn minimum_signers(root: &Root) -> Option<Vec<&Decoded<Hex>>> {
+    let candidates = root
+        .roles
+        .values()
+        .flat_map(|keys| keys.keyids.iter())
+        .collect::<BTreeSet<_>>();
+    let mut signers = Vec::new();
+    loop {
+        let short = root
+            .roles
+            .values()
+            .filter(|keys| {
+                let signed = signers
+                    .iter()
+                    .filter(|key_id| keys.keyids.contains(**key_id))
+                    .count();
+                (signed as u64) < keys.threshold.get()
+            })
+            .collect::<Vec<_>>();
+        if short.is_empty() {
+            return Some(signers);
+        }
+        let (count, key_id) = candidates
+            .iter()
+            .filter(|key_id| !signers.contains(*key_id))
+            .map(|key_id| {
+                let count = short
+                    .iter()
+                    .filter(|keys| keys.keyids.contains(key_id))
+                    .count();
+                (count, *key_id)
+            })
+            .min_by_key(|(count, key_id)| (Reverse(*count), *key_id))?;
+        if count == 0 {
+            return None;
+        }
+        signers.push(key_id);
+    }
 }
 
 fn round_time(time: DateTime<Utc>) -> DateTime<Utc> {
@@ -417,11 +946,132 @@ fn round_time(time: DateTime<Utc>) -> DateTime<Utc> {
     time.with_nanosecond(0).unwrap()
 }
 
+/// Fetches each `N.root.json` from the repository, starting at 1, until one isn't found, and
+/// returns the last one. None of them are verified, since the caller may not trust any root.json.
+/// As when loading a repository, at most `max_root_updates` versions are fetched, so that a
+/// repository can't keep this fetching forever.
+fn latest_root(metadata_base_url: &Url) -> Result<Signed<Root>> {
+    let transport = DefaultTransport::new();
+    let max_versions = Limits::default().max_root_updates;
+    let mut latest = None;
+    for version in 1_u64.. {
+        let name = format!("{version}.root.json");
+        let url = metadata_base_url
+            .join(&name)
+            .context(error::UrlParseSnafu {
+                url: format!("{}{name}", metadata_base_url.as_str()),
+            })?;
+        let mut reader = match transport.fetch(url) {
+            Ok(reader) => reader,
+            Err(err) if err.kind() == TransportErrorKind::FileNotFound => break,
+            Err(err) => return Err(err).context(error::RootVersionFetchSnafu { version }),
+        };
+        ensure!(
+            version <= max_versions,
+            error::RootVersionLimitSnafu { max: max_versions }
+        );
+        let mut buf = Vec::new();
+        reader
+            .read_to_end(&mut buf)
+            .context(error::RootVersionReadSnafu { version })?;
+        let root: Signed<Root> =
+            serde_json::from_slice(&buf).context(error::RootVersionParseSnafu { version })?;
+        ensure!(
+            root.signed.version.get() == version,
+            error::RootVersionMismatchSnafu {
+                expected: version,
+                fetched: root.signed.version.get(),
+            }
+        );
+        latest = Some(root);
+    }
+    latest.context(error::MissingSnafu {
+        what: format!("1.root.json in {metadata_base_url}"),
+    })
+}
+
 /// Removes signatures from a role. Useful if the content is updated.
 fn clear_sigs<T>(role: &mut Signed<T>) {
     role.signatures.clear();
 }
 
+/// Increments the version of `root`.
+pub(crate) fn increment_version(root: &mut Root) -> Result<()> {
+    root.version = NonZeroU64::new(
+        root.version
+            .get()
+            .checked_add(1)
+            .context(error::VersionOverflowSnafu)?,
+    )
+    .context(error::VersionZeroSnafu)?;
+    Ok(())
+}
+
+/// Returns the keys of the root role, which signs root.json itself.
+fn root_role_keys(root: &mut Root) -> Result<&mut RoleKeys> {
+    root.roles
+        .get_mut(&RoleType::Root)
+        .context(error::MissingSnafu {
+            what: "root role in root.json",
+        })
+}
+
+/// Checks that the root role still lists enough keys to meet its threshold, so that the new
+/// root.json can be signed.
+fn check_root_role_threshold(root_keys: &RoleKeys) -> Result<()> {
+    ensure!(
+        root_keys.keyids.len() as u64 >= root_keys.threshold.get(),
+        error::RootRoleThresholdSnafu {
+            keys: root_keys.keyids.len(),
+            threshold: root_keys.threshold.get(),
+        }
+    );
+    Ok(())
+}
+
+/// Prints how many of the root role's keys in `keys` have validly signed `new`, and which keys are
+/// missing if the threshold isn't met. Returns whether the threshold is met.
+fn report_root_signatures(label: &str, keys: &Root, new: &Signed<Root>) -> Result<bool> {
+    let role_keys = keys
+        .roles
+        .get(&RoleType::Root)
+        .context(error::MissingSnafu {
+            what: format!("root role in the {label} root.json"),
+        })?;
+    let mut signed = BTreeSet::new();
+    for signature in &new.signatures {
+        if keys
+            .signature_is_valid(&new.signed, signature)
+            .context(error::JsonSerializationSnafu)?
+        {
+            signed.insert(hex::encode(&signature.keyid));
+        }
+    }
+
+    let threshold = role_keys.threshold.get();
+    let met = signed.len() as u64 >= threshold;
+    println!(
+        "{} root (version {}): {} of {} required signatures, threshold {}",
+        label,
+        keys.version,
+        signed.len(),
+        threshold,
+        if met { "met" } else { "not met" }
+    );
+    if !met {
+        println!(
+            "  {} more signatures needed from:",
+            threshold - signed.len() as u64
+        );
+        for key_id in role_keys.keyids.iter().map(hex::encode) {
+            if !signed.contains(&key_id) {
+                println!("    {key_id}");
+            }
+        }
+    }
+    Ok(met)
+}
+
 /// Adds a key to the root role if not already present, and adds its key ID to the specified role.
 fn add_key(root: &mut Root, role: &[RoleType], key: Key) -> Result<Decoded<Hex>> {
     let key_id = if let Some((key_id, _)) = root
@@ -0,0 +1,81 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::datetime::parse_datetime;
+use crate::error::{self, Result};
+use crate::write_file;
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use snafu::ResultExt;
+use std::num::NonZeroU64;
+use std::path::PathBuf;
+use tough::schema::{DelegatedTargets, PathPattern, Role, Signed, Targets, SPEC_VERSION};
+
+#[derive(Debug, Parser)]
+pub(crate) struct ScaffoldArgs {
+    /// The role to write a skeleton for, which the signing role will delegate to
+    #[clap(long = "role")]
+    delegatee: String,
+
+    /// The paths the signing role will delegate to the role
+    #[clap(short = 'p', long = "paths", required = true)]
+    paths: Vec<PathPattern>,
+
+    /// Placeholder expiration of the role, for its owners to replace; can be in full RFC 3339
+    /// format, or something like 'in 7 days'
+    #[clap(
+        short = 'e',
+        long = "expires",
+        default_value = "in 7 days",
+        parse(try_from_str = parse_datetime)
+    )]
+    expires: DateTime<Utc>,
+
+    /// Placeholder version of the role, for its owners to replace
+    #[clap(short = 'v', long = "version", default_value = "1")]
+    version: NonZeroU64,
+
+    /// The directory where the skeleton will be written
+    #[clap(short = 'o', long = "outdir")]
+    outdir: PathBuf,
+}
+
+impl ScaffoldArgs {
+    /// Writes an unsigned delegated targets file for `self.delegatee`, with no targets and no
+    /// keys, to the `metadata` directory in `outdir`. Its owners add their targets and sign it;
+    /// the signing role then adds it with `add-role`, listing the owners' keys in its own
+    /// delegations with `--delegatee-key`.
+    pub(crate) fn run(&self, role: &str) -> Result<()> {
+        let skeleton = DelegatedTargets {
+            name: self.delegatee.clone(),
+            targets: Targets::new(SPEC_VERSION.to_string(), self.version, self.expires),
+        };
+        let metadata_dir = self.outdir.join("metadata");
+        std::fs::create_dir_all(&metadata_dir).context(error::DirCreateSnafu {
+            path: &metadata_dir,
+        })?;
+        let path = metadata_dir.join(skeleton.filename(false));
+        write_file(
+            &path,
+            &Signed {
+                signed: skeleton.targets,
+                signatures: Vec::new(),
+            },
+        )?;
+
+        let paths = self
+            .paths
+            .iter()
+            .map(PathPattern::value)
+            .collect::<Vec<_>>();
+        println!(
+            "Wrote an unsigned skeleton of role '{}' to '{}'. Once its targets, keys, expiration \
+             and version are filled in and it is signed, role '{}' can add it for paths: {}",
+            self.delegatee,
+            path.display(),
+            role,
+            paths.join(", ")
+        );
+        Ok(())
+    }
+}
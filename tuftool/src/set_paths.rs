@@ -0,0 +1,101 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::common::{load_metadata_repo, write_delegated_targets};
+use crate::datetime::parse_datetime;
+use crate::error::{self, Result};
+use crate::source::parse_key_source;
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use snafu::ResultExt;
+use std::num::NonZeroU64;
+use std::path::PathBuf;
+use tough::editor::targets::TargetsEditor;
+use tough::key_source::KeySource;
+use tough::schema::{PathHashPrefix, PathPattern, PathSet};
+use url::Url;
+
+#[derive(Debug, Parser)]
+pub(crate) struct SetPathsArgs {
+    /// The delegated role whose paths are set
+    #[clap(short = 'd', long = "delegated-role")]
+    delegatee: String,
+
+    /// Key files to sign with
+    #[clap(short = 'k', long = "key", required = true, parse(try_from_str = parse_key_source))]
+    keys: Vec<Box<dyn KeySource>>,
+
+    /// Expiration of new role file; can be in full RFC 3339 format, or something like 'in
+    /// 7 days'
+    #[clap(short = 'e', long = "expires", parse(try_from_str = parse_datetime))]
+    expires: DateTime<Utc>,
+
+    /// Version of role file
+    #[clap(short = 'v', long = "version")]
+    version: NonZeroU64,
+
+    /// Path to root.json file for the repository
+    #[clap(short = 'r', long = "root")]
+    root: PathBuf,
+
+    /// TUF repository metadata base URL
+    #[clap(short = 'm', long = "metadata-url")]
+    metadata_base_url: Url,
+
+    /// The directory where the repository will be written; use '-' to write the signed role to
+    /// stdout
+    #[clap(short = 'o', long = "outdir")]
+    outdir: PathBuf,
+
+    /// The new delegated paths
+    #[clap(
+        short = 'p',
+        long = "paths",
+        conflicts_with = "path-hash-prefixes",
+        required_unless = "path-hash-prefixes"
+    )]
+    paths: Option<Vec<PathPattern>>,
+
+    /// The new delegated paths hash prefixes
+    #[clap(short = 'x', long = "path-hash-prefixes")]
+    path_hash_prefixes: Option<Vec<PathHashPrefix>>,
+}
+
+impl SetPathsArgs {
+    pub(crate) fn run(&self, role: &str) -> Result<()> {
+        let repository = load_metadata_repo(&self.root, self.metadata_base_url.clone())?;
+        self.set_paths(
+            role,
+            TargetsEditor::from_repo(repository, role)
+                .context(error::EditorFromRepoSnafu { path: &self.root })?,
+        )
+    }
+
+    #[allow(clippy::option_if_let_else)]
+    /// Replaces the paths `role` delegates to the delegated role using `TargetsEditor`
+    fn set_paths(&self, role: &str, mut editor: TargetsEditor) -> Result<()> {
+        let paths = if let Some(paths) = &self.paths {
+            PathSet::Paths(paths.clone())
+        } else if let Some(path_hash_prefixes) = &self.path_hash_prefixes {
+            PathSet::PathHashPrefixes(path_hash_prefixes.clone())
+        } else {
+            // Clap requires one of the two
+            PathSet::Paths(Vec::new())
+        };
+        let updated_role = editor
+            .set_role_paths(&self.delegatee, paths)
+            .context(error::SetPathsSnafu {
+                role: &self.delegatee,
+            })?
+            .version(self.version)
+            .expires(self.expires)
+            .sign(&self.keys)
+            .context(error::SignRepoSnafu)?;
+        write_delegated_targets(
+            updated_role,
+            &self.outdir,
+            role,
+            [role.to_string()].to_vec(),
+        )
+    }
+}
@@ -0,0 +1,313 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::datetime::parse_datetime;
+use crate::error::{self, Result};
+use crate::source::parse_key_source;
+use crate::{load_file, write_file};
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use ring::digest::{digest, SHA256};
+use ring::rand::SystemRandom;
+use serde::Serialize;
+use snafu::{ensure, OptionExt, ResultExt};
+use std::collections::HashMap;
+use std::fs;
+use std::num::NonZeroU64;
+use std::path::{Path, PathBuf};
+use tough::key_source::KeySource;
+use tough::schema::{
+    Hashes, Role, Root, Signature, Signed, Snapshot, Targets, Timestamp, TimestampMeta,
+    SPEC_VERSION,
+};
+
+#[derive(Debug, Parser)]
+pub(crate) struct SignArgs {
+    /// Key files to sign with
+    #[clap(short = 'k', long = "key", required = true, parse(try_from_str = parse_key_source))]
+    keys: Vec<Box<dyn KeySource>>,
+
+    /// Path to root.json file for the repository
+    #[clap(short = 'r', long = "root")]
+    root: PathBuf,
+
+    /// Directory of the repository's metadata. Roles are signed in place
+    #[clap(long = "metadata-dir")]
+    metadata_dir: PathBuf,
+
+    /// Version of a new timestamp.json to write once snapshot.json is signed. If not given, the
+    /// existing timestamp.json is updated to list the signed snapshot.json
+    #[clap(long = "timestamp-version", requires = "timestamp-expires")]
+    timestamp_version: Option<NonZeroU64>,
+
+    /// Expiration of a new timestamp.json; can be in full RFC 3339 format, or something like 'in
+    /// 7 days'
+    #[clap(
+        long = "timestamp-expires",
+        requires = "timestamp-version",
+        parse(try_from_str = parse_datetime)
+    )]
+    timestamp_expires: Option<DateTime<Utc>>,
+}
+
+impl SignArgs {
+    /// Signs the top-level targets, snapshot and timestamp roles of an unsigned or partially
+    /// signed repository, such as one written by `RepositoryEditor::unsigned`, and writes each
+    /// role it signs back in place. Delegated roles are left as they are.
+    ///
+    /// Each role keeps its existing valid signatures and is signed with every given key it trusts
+    /// that hasn't signed it yet. Roles are signed in order: snapshot is only signed once targets
+    /// meets its threshold, and timestamp once snapshot does, because each records the length and
+    /// hash of the one before it, which change with every signature. Roles short of their
+    /// threshold are written with the signatures they have, so other signers can add theirs in
+    /// later runs.
+    pub(crate) fn run(&self) -> Result<()> {
+        let root: Signed<Root> = load_file(&self.root)?;
+        let root = root.signed;
+
+        let timestamp_path = self.metadata_dir.join("timestamp.json");
+        let existing_timestamp: Option<Signed<Timestamp>> = if timestamp_path.exists() {
+            Some(load_file(&timestamp_path)?)
+        } else {
+            None
+        };
+        // The snapshot to sign is the one timestamp.json lists, unless timestamp.json is about
+        // to be replaced
+        let snapshot_path = match (&existing_timestamp, self.timestamp_version) {
+            (Some(timestamp), None) => listed_path(
+                &self.metadata_dir,
+                "snapshot.json",
+                timestamp
+                    .signed
+                    .meta
+                    .get("snapshot.json")
+                    .map(|meta| meta.version),
+                root.consistent_snapshot,
+            )?,
+            _ => newest_snapshot_path(&self.metadata_dir, root.consistent_snapshot)?,
+        };
+        let mut snapshot: Signed<Snapshot> = load_file(&snapshot_path)?;
+        let targets_path = listed_path(
+            &self.metadata_dir,
+            "targets.json",
+            snapshot
+                .signed
+                .meta
+                .get("targets.json")
+                .map(|meta| meta.version),
+            root.consistent_snapshot,
+        )?;
+        let mut targets: Signed<Targets> = load_file(&targets_path)?;
+
+        if !self.sign_and_write(&root, &mut targets, &targets_path)? {
+            return error::SignIncompleteSnafu { roles: "targets" }.fail();
+        }
+
+        // Snapshot may record the length and hashes of targets.json, which signing changes
+        update_targets_meta(&mut snapshot.signed, &targets_path)?;
+        if !self.sign_and_write(&root, &mut snapshot, &snapshot_path)? {
+            return error::SignIncompleteSnafu { roles: "snapshot" }.fail();
+        }
+
+        let mut timestamp = match (self.timestamp_version, self.timestamp_expires) {
+            (Some(version), Some(expires)) => Signed {
+                signed: Timestamp::new(SPEC_VERSION.to_string(), version, expires),
+                signatures: Vec::new(),
+            },
+            _ => existing_timestamp.context(error::MissingSnafu {
+                what: "--timestamp-version and --timestamp-expires to write timestamp.json",
+            })?,
+        };
+        update_snapshot_meta(
+            &mut timestamp.signed,
+            &snapshot_path,
+            snapshot.signed.version,
+        )?;
+        ensure!(
+            self.sign_and_write(&root, &mut timestamp, &timestamp_path)?,
+            error::SignIncompleteSnafu { roles: "timestamp" }
+        );
+        Ok(())
+    }
+
+    /// Signs `role` with the given keys and writes it to `path` if its signatures changed. Returns
+    /// whether the role's threshold is met.
+    fn sign_and_write<T>(&self, root: &Root, role: &mut Signed<T>, path: &Path) -> Result<bool>
+    where
+        T: Role + Serialize,
+    {
+        let original = role.signatures.clone();
+        let threshold_met = sign_role(root, role, &self.keys)?;
+        if role.signatures != original {
+            write_file(path, role)?;
+        }
+        Ok(threshold_met)
+    }
+}
+
+/// Drops the signatures of `role` that don't verify against `root`, such as those made before its
+/// content changed, and signs it with each of `keys` that the role trusts and that hasn't signed
+/// it yet. Prints how many signatures the role has, and returns whether its threshold is met.
+fn sign_role<T>(root: &Root, role: &mut Signed<T>, keys: &[Box<dyn KeySource>]) -> Result<bool>
+where
+    T: Role + Serialize,
+{
+    let role_keys = root.roles.get(&T::TYPE).context(error::MissingSnafu {
+        what: format!("{} role in root.json", T::TYPE),
+    })?;
+    let mut signatures = Vec::new();
+    for signature in role.signatures.drain(..) {
+        if root
+            .signature_is_valid(&role.signed, &signature)
+            .context(error::JsonSerializationSnafu)?
+        {
+            signatures.push(signature);
+        }
+    }
+    role.signatures = signatures;
+
+    let data = role
+        .signed
+        .canonical_form()
+        .context(error::JsonSerializationSnafu)?;
+    let rng = SystemRandom::new();
+    for source in keys {
+        let key_pair = source.as_sign().context(error::KeyPairFromKeySourceSnafu)?;
+        let keyid = key_pair
+            .tuf_key()
+            .key_id()
+            .context(error::JsonSerializationSnafu)?;
+        if !role_keys.keyids.contains(&keyid)
+            || role
+                .signatures
+                .iter()
+                .any(|signature| signature.keyid == keyid)
+        {
+            continue;
+        }
+        let sig = key_pair
+            .sign(&data, &rng)
+            .context(error::SignRoleSnafu { role: T::TYPE })?;
+        role.signatures.push(Signature {
+            keyid,
+            sig: sig.into(),
+        });
+    }
+
+    let threshold = role_keys.threshold.get();
+    let threshold_met = role.signatures.len() as u64 >= threshold;
+    println!(
+        "{}: {} of {} signatures, threshold {}",
+        T::TYPE,
+        role.signatures.len(),
+        threshold,
+        if threshold_met { "met" } else { "not met" }
+    );
+    Ok(threshold_met)
+}
+
+/// Returns the path in `metadata_dir` of the metadata file `name`, such as `targets.json`, at
+/// `version`, the version another role lists for it. The file name is prefixed with the version if
+/// the repository uses consistent snapshots.
+pub(crate) fn listed_path(
+    metadata_dir: &Path,
+    name: &str,
+    version: Option<NonZeroU64>,
+    consistent_snapshot: bool,
+) -> Result<PathBuf> {
+    let version = version.context(error::MissingSnafu {
+        what: format!("{name} meta"),
+    })?;
+    Ok(metadata_dir.join(if consistent_snapshot {
+        format!("{version}.{name}")
+    } else {
+        name.to_string()
+    }))
+}
+
+/// Returns the path of the newest snapshot metadata in `metadata_dir`: the highest-versioned
+/// `N.snapshot.json` if the repository uses consistent snapshots, or else `snapshot.json`.
+fn newest_snapshot_path(metadata_dir: &Path, consistent_snapshot: bool) -> Result<PathBuf> {
+    if !consistent_snapshot {
+        return Ok(metadata_dir.join("snapshot.json"));
+    }
+    let mut newest = None;
+    for entry in fs::read_dir(metadata_dir).context(error::DirReadSnafu { path: metadata_dir })? {
+        let entry = entry.context(error::DirReadSnafu { path: metadata_dir })?;
+        let version = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.strip_suffix(".snapshot.json"))
+            .and_then(|version| version.parse::<u64>().ok());
+        if let Some(version) = version {
+            if newest
+                .as_ref()
+                .map_or(true, |(newest, _)| version > *newest)
+            {
+                newest = Some((version, entry.path()));
+            }
+        }
+    }
+    newest.map(|(_, path)| path).context(error::MissingSnafu {
+        what: format!("snapshot metadata in '{}'", metadata_dir.display()),
+    })
+}
+
+/// Updates the length and hashes that `snapshot` records for targets.json, where it records them,
+/// to those of the targets metadata at `targets_path`. Signing targets.json changes both.
+pub(crate) fn update_targets_meta(snapshot: &mut Snapshot, targets_path: &Path) -> Result<()> {
+    let (length, sha256) = file_meta(targets_path)?;
+    if let Some(targets_meta) = snapshot.meta.get_mut("targets.json") {
+        if targets_meta.length.is_some() {
+            targets_meta.length = Some(length);
+        }
+        // Other hashes the meta lists would no longer match, so only sha256 is kept
+        if targets_meta.hashes.is_some() {
+            targets_meta.hashes = Some(Hashes {
+                sha256: sha256.into(),
+                _extra: HashMap::new(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Records the length, sha256 and version of the snapshot metadata at `snapshot_path` in
+/// `timestamp`, keeping any other fields of its existing meta.
+pub(crate) fn update_snapshot_meta(
+    timestamp: &mut Timestamp,
+    snapshot_path: &Path,
+    version: NonZeroU64,
+) -> Result<()> {
+    let (length, sha256) = file_meta(snapshot_path)?;
+    let hashes = Hashes {
+        sha256: sha256.into(),
+        _extra: HashMap::new(),
+    };
+    match timestamp.meta.get_mut("snapshot.json") {
+        Some(snapshot_meta) => {
+            snapshot_meta.length = length;
+            snapshot_meta.hashes = hashes;
+            snapshot_meta.version = version;
+        }
+        None => {
+            timestamp.meta.insert(
+                "snapshot.json".to_string(),
+                TimestampMeta {
+                    length,
+                    hashes,
+                    version,
+                    _extra: HashMap::new(),
+                },
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Returns the length and sha256 of the file at `path`, as listed in snapshot and timestamp
+/// metadata.
+fn file_meta(path: &Path) -> Result<(u64, Vec<u8>)> {
+    let buf = fs::read(path).context(error::FileReadSnafu { path })?;
+    Ok((buf.len() as u64, digest(&SHA256, &buf).as_ref().to_vec()))
+}
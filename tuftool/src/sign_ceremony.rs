@@ -0,0 +1,313 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! A signing ceremony collects detached signatures from several signers, each holding their own
+//! keys, without passing the metadata file itself around for every signature.
+//!
+//! `prepare` writes an unsigned copy of each metadata file to a ceremony directory, along with a
+//! `<role>.to-sign` file holding the exact bytes that must be signed. Each signer runs `sign` on
+//! the `.to-sign` file and hands back the `<role>.<key ID>.sig` files it writes. `finalize` checks
+//! every signature it is given, reports how far each role is from its threshold, and writes the
+//! signed metadata of each role whose threshold is met.
+
+use crate::error::{self, Result};
+use crate::source::parse_key_source;
+use crate::{load_file, write_file};
+use clap::Parser;
+use log::warn;
+use ring::digest::{digest, SHA256};
+use ring::rand::SystemRandom;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use snafu::{ensure, OptionExt, ResultExt};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tough::key_source::KeySource;
+use tough::schema::{Role, RoleType, Root, Signature, Signed, Snapshot, Targets, Timestamp};
+
+/// The extension of the files holding the bytes to sign.
+const TO_SIGN_EXTENSION: &str = "to-sign";
+
+/// The extension of detached signature files.
+const SIGNATURE_EXTENSION: &str = "sig";
+
+#[derive(Debug, Parser)]
+pub(crate) enum Command {
+    /// Write unsigned copies of metadata files, and the bytes each must be signed over, to a
+    /// ceremony directory
+    Prepare {
+        /// Metadata files of top-level roles to sign
+        #[clap(required = true)]
+        metadata: Vec<PathBuf>,
+        /// The ceremony directory to write to
+        #[clap(short = 'o', long = "outdir")]
+        outdir: PathBuf,
+    },
+    /// Write a detached signature of a `.to-sign` file for each key
+    Sign {
+        /// The `.to-sign` file written by `prepare`
+        to_sign: PathBuf,
+        /// Key files to sign with
+        #[clap(short = 'k', long = "key", required = true, parse(try_from_str = parse_key_source))]
+        keys: Vec<Box<dyn KeySource>>,
+        /// The directory to write signatures to
+        #[clap(short = 'o', long = "outdir")]
+        outdir: PathBuf,
+    },
+    /// Add the detached signatures to the prepared metadata, and write each role whose threshold
+    /// is met
+    Finalize {
+        /// The ceremony directory written by `prepare`
+        #[clap(long = "ceremony-dir")]
+        ceremony_dir: PathBuf,
+        /// The directory holding the detached signatures
+        #[clap(long = "signatures")]
+        signatures: PathBuf,
+        /// Path to the root.json file trusting the signing keys; required unless only root.json
+        /// is being signed, in which case its own keys are used
+        #[clap(long = "root")]
+        root: Option<PathBuf>,
+        /// The directory to write signed metadata to
+        #[clap(short = 'o', long = "outdir")]
+        outdir: PathBuf,
+    },
+}
+
+impl Command {
+    pub(crate) fn run(&self) -> Result<()> {
+        match self {
+            Command::Prepare { metadata, outdir } => prepare(metadata, outdir),
+            Command::Sign {
+                to_sign,
+                keys,
+                outdir,
+            } => sign(to_sign, keys, outdir),
+            Command::Finalize {
+                ceremony_dir,
+                signatures,
+                root,
+                outdir,
+            } => finalize(ceremony_dir, signatures, root.as_deref(), outdir),
+        }
+    }
+}
+
+/// Reads the role type declared by a metadata file.
+fn role_type(path: &Path) -> Result<RoleType> {
+    let metadata: Signed<serde_json::Value> = load_file(path)?;
+    metadata.signed["_type"]
+        .as_str()
+        .and_then(|role_type| role_type.parse::<RoleType>().ok())
+        .context(error::CeremonyRoleSnafu { path })
+}
+
+fn prepare(metadata: &[PathBuf], outdir: &Path) -> Result<()> {
+    fs::create_dir_all(outdir).context(error::DirCreateSnafu { path: outdir })?;
+    let mut prepared = HashSet::new();
+    for path in metadata {
+        let role_type = role_type(path)?;
+        ensure!(
+            prepared.insert(role_type),
+            error::CeremonyDuplicateRoleSnafu { role: role_type }
+        );
+        let to_sign = match role_type {
+            RoleType::Root => prepare_role::<Root>(path, outdir)?,
+            RoleType::Timestamp => prepare_role::<Timestamp>(path, outdir)?,
+            RoleType::Snapshot => prepare_role::<Snapshot>(path, outdir)?,
+            RoleType::Targets => prepare_role::<Targets>(path, outdir)?,
+            RoleType::DelegatedTargets => return error::CeremonyRoleSnafu { path }.fail(),
+        };
+        // Signers can compare this digest with the `.to-sign` file they are given
+        println!(
+            "{}: sha256 {}",
+            role_type,
+            hex::encode(digest(&SHA256, &to_sign))
+        );
+    }
+    Ok(())
+}
+
+/// Writes the unsigned metadata and the bytes to sign for one role, and returns those bytes.
+fn prepare_role<T>(path: &Path, outdir: &Path) -> Result<Vec<u8>>
+where
+    T: Role + Serialize + DeserializeOwned,
+{
+    let mut role: Signed<T> = load_file(path)?;
+    role.signatures.clear();
+    let to_sign = role
+        .signed
+        .canonical_form()
+        .context(error::JsonSerializationSnafu)?;
+    write_file(&outdir.join(format!("{}.json", T::TYPE)), &role)?;
+    let to_sign_path = outdir.join(format!("{}.{}", T::TYPE, TO_SIGN_EXTENSION));
+    fs::write(&to_sign_path, &to_sign).context(error::FileWriteSnafu {
+        path: &to_sign_path,
+    })?;
+    Ok(to_sign)
+}
+
+fn sign(to_sign: &Path, keys: &[Box<dyn KeySource>], outdir: &Path) -> Result<()> {
+    let role = to_sign
+        .file_stem()
+        .context(error::NoFileNameSnafu { path: to_sign })?
+        .to_str()
+        .context(error::PathUtf8Snafu { path: to_sign })?;
+    let data = fs::read(to_sign).context(error::FileReadSnafu { path: to_sign })?;
+    fs::create_dir_all(outdir).context(error::DirCreateSnafu { path: outdir })?;
+    let rng = SystemRandom::new();
+    for source in keys {
+        let key_pair = source.as_sign().context(error::KeyPairFromKeySourceSnafu)?;
+        let keyid = key_pair
+            .tuf_key()
+            .key_id()
+            .context(error::JsonSerializationSnafu)?;
+        let sig = key_pair
+            .sign(&data, &rng)
+            .context(error::CeremonySignSnafu { role })?;
+        let keyid_hex = hex::encode(&keyid);
+        write_file(
+            &outdir.join(format!("{role}.{keyid_hex}.{SIGNATURE_EXTENSION}")),
+            &Signature {
+                keyid,
+                sig: sig.into(),
+            },
+        )?;
+        println!("Signed {role} with key {keyid_hex}");
+    }
+    Ok(())
+}
+
+fn finalize(
+    ceremony_dir: &Path,
+    signatures: &Path,
+    root: Option<&Path>,
+    outdir: &Path,
+) -> Result<()> {
+    let keys_root = root.map(load_file::<Signed<Root>>).transpose()?;
+    fs::create_dir_all(outdir).context(error::DirCreateSnafu { path: outdir })?;
+
+    let mut incomplete = Vec::new();
+    for role_type in [
+        RoleType::Root,
+        RoleType::Timestamp,
+        RoleType::Snapshot,
+        RoleType::Targets,
+    ] {
+        let path = ceremony_dir.join(format!("{role_type}.json"));
+        if !path.exists() {
+            continue;
+        }
+        let complete = match (role_type, &keys_root) {
+            (RoleType::Root, None) => {
+                // A new root can only be trusted by its own keys
+                let root: Signed<Root> = load_file(&path)?;
+                finalize_role::<Root>(&path, &root.signed, signatures, outdir)?
+            }
+            (_, None) => {
+                return error::MissingSnafu {
+                    what: format!("--root to check the {role_type} signatures"),
+                }
+                .fail()
+            }
+            (RoleType::Root, Some(keys)) => {
+                finalize_role::<Root>(&path, &keys.signed, signatures, outdir)?
+            }
+            (RoleType::Timestamp, Some(keys)) => {
+                finalize_role::<Timestamp>(&path, &keys.signed, signatures, outdir)?
+            }
+            (RoleType::Snapshot, Some(keys)) => {
+                finalize_role::<Snapshot>(&path, &keys.signed, signatures, outdir)?
+            }
+            (_, Some(keys)) => finalize_role::<Targets>(&path, &keys.signed, signatures, outdir)?,
+        };
+        if !complete {
+            incomplete.push(role_type.to_string());
+        }
+    }
+
+    ensure!(
+        incomplete.is_empty(),
+        error::CeremonyIncompleteSnafu {
+            roles: incomplete.join(", ")
+        }
+    );
+    Ok(())
+}
+
+/// Adds every valid detached signature for one role, reports its threshold progress, and writes
+/// the signed metadata if the threshold is met. Returns whether the threshold is met.
+fn finalize_role<T>(path: &Path, keys: &Root, signatures: &Path, outdir: &Path) -> Result<bool>
+where
+    T: Role + Serialize + DeserializeOwned,
+{
+    let mut role: Signed<T> = load_file(path)?;
+    let threshold = keys
+        .roles
+        .get(&T::TYPE)
+        .context(error::MissingSnafu {
+            what: format!("{} role in root.json", T::TYPE),
+        })?
+        .threshold
+        .get();
+
+    let prefix = format!("{}.", T::TYPE);
+    let suffix = format!(".{SIGNATURE_EXTENSION}");
+    let mut entries = fs::read_dir(signatures)
+        .context(error::DirReadSnafu { path: signatures })?
+        .map(|entry| {
+            entry
+                .map(|entry| entry.path())
+                .context(error::DirReadSnafu { path: signatures })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    entries.sort();
+    for sig_path in entries {
+        let is_role_signature = sig_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map_or(false, |name| {
+                name.starts_with(&prefix) && name.ends_with(&suffix)
+            });
+        if !is_role_signature {
+            continue;
+        }
+        let signature: Signature = load_file(&sig_path)?;
+        if role
+            .signatures
+            .iter()
+            .any(|existing| existing.keyid == signature.keyid)
+        {
+            continue;
+        }
+        if keys
+            .signature_is_valid(&role.signed, &signature)
+            .context(error::JsonSerializationSnafu)?
+        {
+            role.signatures.push(signature);
+        } else {
+            warn!(
+                "Ignoring signature '{}': key {} is not trusted for {} or the signature doesn't match",
+                sig_path.display(),
+                hex::encode(&signature.keyid),
+                T::TYPE
+            );
+        }
+    }
+
+    let valid = role.signatures.len() as u64;
+    let complete = valid >= threshold;
+    println!(
+        "{}: {} of {} signatures, threshold {}",
+        T::TYPE,
+        valid,
+        threshold,
+        if complete { "met" } else { "not met" }
+    );
+    if complete {
+        keys.verify_role(&role)
+            .context(error::CeremonyVerifySnafu { role: T::TYPE })?;
+        write_file(&outdir.join(format!("{}.json", T::TYPE)), &role)?;
+    }
+    Ok(complete)
+}
@@ -0,0 +1,130 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::common::UNUSED_URL;
+use crate::error::{self, Result};
+use clap::{Parser, ValueEnum};
+use snafu::{ensure, OptionExt, ResultExt};
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use tough::{Repository, RepositoryLoader};
+use url::Url;
+
+#[derive(Debug, Parser)]
+pub(crate) struct SimulateUpdateArgs {
+    /// Path to the root.json file the client initially trusts
+    #[clap(short = 'r', long = "root")]
+    root: PathBuf,
+
+    /// Metadata directory of the repository state the client trusts before updating
+    #[clap(long = "from")]
+    from: PathBuf,
+
+    /// Metadata directory of the repository state the client then updates to
+    #[clap(long = "to")]
+    to: PathBuf,
+
+    /// Fail unless the update has this outcome
+    #[clap(long = "expect", value_enum)]
+    expect: Option<Outcome>,
+}
+
+/// What a client does when asked to update to a new repository state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Outcome {
+    /// The new state was verified and trusted
+    Accepted,
+    /// The new state was rejected because some metadata is older than what the client trusts
+    Rollback,
+    /// The new state was rejected because some metadata has expired
+    Expired,
+    /// The new state was rejected for another reason, e.g. a bad signature
+    Rejected,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Outcome::Accepted => "accepted",
+            Outcome::Rollback => "rollback",
+            Outcome::Expired => "expired",
+            Outcome::Rejected => "rejected",
+        })
+    }
+}
+
+impl Outcome {
+    fn of(error: &tough::error::Error) -> Self {
+        match error {
+            tough::error::Error::OlderMetadata { .. } => Outcome::Rollback,
+            tough::error::Error::ExpiredMetadata { .. } => Outcome::Expired,
+            _ => Outcome::Rejected,
+        }
+    }
+}
+
+impl SimulateUpdateArgs {
+    pub(crate) fn run(&self) -> Result<()> {
+        let from_url = dir_url(&self.from)?;
+        let to_url = dir_url(&self.to)?;
+        // Only metadata is loaded, so the targets URL is never used
+        let targets_url = Url::parse(UNUSED_URL).with_context(|_| error::UrlParseSnafu {
+            url: UNUSED_URL.to_owned(),
+        })?;
+        // The client's record of the metadata it trusts, which is how it detects rollbacks
+        let datastore = TempDir::new().context(error::TempDirSnafu)?;
+
+        let root = File::open(&self.root).context(error::OpenRootSnafu { path: &self.root })?;
+        let trusted = load(root, from_url, targets_url.clone(), datastore.path())
+            .context(error::RepoLoadSnafu)?;
+
+        // Like a real client, start the update from the latest root.json it came to trust
+        let root = serde_json::to_vec(trusted.root()).context(error::SimulateRootSerializeSnafu)?;
+        let outcome = match load(root.as_slice(), to_url, targets_url, datastore.path()) {
+            Ok(_) => {
+                println!("Update {}", Outcome::Accepted);
+                Outcome::Accepted
+            }
+            Err(err) => {
+                let outcome = Outcome::of(&err);
+                println!("Update rejected ({outcome}): {err}");
+                outcome
+            }
+        };
+
+        if let Some(expected) = self.expect {
+            ensure!(
+                outcome == expected,
+                error::SimulateUpdateOutcomeSnafu { expected, outcome }
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Loads the metadata at `metadata_url`, trusting `root` and keeping the client's record of the
+/// trusted metadata in `datastore`. The loader's error is returned as is, so that the reason for
+/// a rejected update can be told apart.
+fn load<R: Read>(
+    root: R,
+    metadata_url: Url,
+    targets_url: Url,
+    datastore: &Path,
+) -> tough::error::Result<Repository> {
+    RepositoryLoader::new(root, metadata_url, targets_url)
+        .datastore(datastore)
+        .load()
+}
+
+/// Returns the `file` URL of the directory `dir`, which may be relative to the current directory.
+fn dir_url(dir: &Path) -> Result<Url> {
+    let dir = std::env::current_dir()
+        .context(error::CurrentDirSnafu)?
+        .join(dir);
+    Url::from_directory_path(&dir)
+        .ok()
+        .context(error::DirectoryUrlSnafu { path: dir })
+}
@@ -9,7 +9,8 @@
 //! This module parses a key source command line parameter as a URL, relative to `file://$PWD`,
 //! then matches the URL scheme against ones we understand.
 //!
-//! Currently supported key sources are local files and AWS SSM.
+//! Currently supported key sources are local files, systemd credentials, AWS SSM, AWS KMS, and
+//! Azure Key Vault.
 //!
 //! Examples of currently supported formats:
 //!
@@ -17,6 +18,10 @@
 //! "./a/key/file/here"
 //! "file:///./a/key/file/here" (notice the 3 slashes after the colon)
 //!
+//! Keys passed to a systemd service as credentials are named by the credential, and read from
+//! the directory given by the CREDENTIALS_DIRECTORY environment variable:
+//! "cred:signing-key"
+//!
 //! Keys stored in AWS SSM use a special format:
 //! "aws-ssm://<aws profile>/key/path/in/SSM?kms-key-id=12345"
 //!
@@ -32,12 +37,18 @@
 //!
 //! You may also skip the profile bit and just use your local environment's default profile:
 //! "aws-ssm:///a/key" (notice the 3 slashes after the colon)
+//!
+//! When the `azure-kv` feature is enabled, keys stored in Azure Key Vault name the vault, the key,
+//! and the key version:
+//! "azurekv://<vault>/<key name>/<key version>"
+//!
+//! Requests to Key Vault are authenticated with the default Azure credential chain.
 
 use crate::error::{self, Result};
 use snafu::{OptionExt, ResultExt};
-use std::path::Path;
-use tough::key_source::{KeySource, LocalKeySource};
-use tough::SafeUrlPath;
+use tough::key_source::{KeyIdKeySource, KeySource, KeySourceRegistry};
+#[cfg(feature = "azure-kv")]
+use tough_azure_kv::AzureKeyVaultKeySource;
 use tough_kms::{KmsKeySource, KmsSigningAlgorithm};
 use tough_ssm::SsmKeySource;
 use url::Url;
@@ -48,32 +59,36 @@ use url::Url;
 /// doc comment for more info on the appropriate format.
 ///
 /// Users are welcome to add their own sources of keys by implementing
-/// the `KeySource` trait in the `tough` library. A user can then add
-/// to this parser to support them in `tuftool`.
+/// the `KeySource` trait in the `tough` library and registering a scheme
+/// for it with a `KeySourceRegistry`, as done in `key_source_registry`.
 pub(crate) fn parse_key_source(input: &str) -> Result<Box<dyn KeySource>> {
-    let input_as_path = Path::new(input);
-    let url = if input_as_path.exists() {
-        Url::from_file_path(input)
-            .ok() // dump unhelpful `()` error
-            .context(error::FileUrlSnafu {
-                path: input_as_path.to_owned(),
-            })?
-    } else {
-        Url::parse(input).context(error::UrlParseSnafu { url: input })?
-    };
-    match url.scheme() {
-        "file" => Ok(Box::new(LocalKeySource {
-            path: url.safe_url_filepath(),
-        })),
-        #[cfg(any(feature = "aws-sdk-rust-native-tls", feature = "aws-sdk-rust-rustls"))]
-        "aws-ssm" => Ok(Box::new(SsmKeySource {
-            profile: url.host_str().and_then(|s| {
-                if s.is_empty() {
-                    None
-                } else {
-                    Some(s.to_owned())
-                }
-            }),
+    key_source_registry()
+        .resolve(input)
+        .context(error::KeySourceParseSnafu { input })
+}
+
+/// Parses a key source that holds more than one key, and the ID of the key in it to sign with, as
+/// "<key source>=<key ID>", e.g. "./keys.pem=0a1b...". The key source is parsed as by
+/// `parse_key_source`.
+pub(crate) fn parse_key_id_source(input: &str) -> Result<Box<dyn KeySource>> {
+    // Key IDs are hex, so the last '=' is the one that separates them from the key source
+    let (source, key_id) = input
+        .rsplit_once('=')
+        .context(error::KeyIdSourceFormatSnafu { input })?;
+    Ok(Box::new(KeyIdKeySource {
+        source: parse_key_source(source)?,
+        key_id: key_id.parse().context(error::KeyIdParseSnafu { key_id })?,
+    }))
+}
+
+/// Returns a `KeySourceRegistry` with the AWS key sources, and the Azure one if its feature is
+/// enabled, registered alongside the built-in ones.
+fn key_source_registry() -> KeySourceRegistry {
+    let mut registry = KeySourceRegistry::new();
+    #[cfg(any(feature = "aws-sdk-rust-native-tls", feature = "aws-sdk-rust-rustls"))]
+    registry.register("aws-ssm", |url| {
+        Ok(Box::new(SsmKeySource {
+            profile: profile(url),
             parameter_name: url.path().to_owned(),
             // If a key ID isn't provided, the system uses the default key
             // associated with your AWS account.
@@ -84,15 +99,11 @@ pub(crate) fn parse_key_source(input: &str) -> Result<Box<dyn KeySource>> {
                     None
                 }
             }),
-        })),
-        "aws-kms" => Ok(Box::new(KmsKeySource {
-            profile: url.host_str().and_then(|s| {
-                if s.is_empty() {
-                    None
-                } else {
-                    Some(s.to_owned())
-                }
-            }),
+        }))
+    });
+    registry.register("aws-kms", |url| {
+        Ok(Box::new(KmsKeySource {
+            profile: profile(url),
             // remove first '/' from the path to get the key_id
             key_id: if url.path().is_empty() {
                 String::new()
@@ -101,10 +112,22 @@ pub(crate) fn parse_key_source(input: &str) -> Result<Box<dyn KeySource>> {
             },
             client: None,
             signing_algorithm: KmsSigningAlgorithm::RsassaPssSha256,
-        })),
-        _ => error::UnrecognizedSchemeSnafu {
-            scheme: url.scheme(),
+        }))
+    });
+    #[cfg(feature = "azure-kv")]
+    registry.register(tough_azure_kv::URL_SCHEME, |url| {
+        Ok(Box::new(AzureKeyVaultKeySource::from_url(url)?))
+    });
+    registry
+}
+
+/// The AWS profile named by the URL's host, if any.
+fn profile(url: &Url) -> Option<String> {
+    url.host_str().and_then(|s| {
+        if s.is_empty() {
+            None
+        } else {
+            Some(s.to_owned())
         }
-        .fail(),
-    }
+    })
 }
@@ -0,0 +1,84 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::common::load_metadata_repo;
+use crate::error::{self, Result};
+use clap::Parser;
+use snafu::{OptionExt, ResultExt};
+use std::path::PathBuf;
+use tough::schema::{ResolutionStep, Target, Targets};
+use tough::TargetName;
+use url::Url;
+
+#[derive(Debug, Parser)]
+pub(crate) struct TargetInfoArgs {
+    /// Path to root.json file for the repository
+    #[clap(short = 'r', long = "root")]
+    root: PathBuf,
+
+    /// TUF repository metadata base URL
+    #[clap(short = 'm', long = "metadata-url")]
+    metadata_base_url: Url,
+
+    /// Name of the target to resolve
+    target: String,
+}
+
+impl TargetInfoArgs {
+    pub(crate) fn run(&self) -> Result<()> {
+        let repository = load_metadata_repo(&self.root, self.metadata_base_url.clone())?;
+        let target_name = TargetName::new(&self.target).context(error::InvalidTargetNameSnafu)?;
+        let targets = &repository.targets().signed;
+        let steps = targets.resolution_order(&target_name);
+
+        // The first role that lists the target is the one whose target is trusted
+        let trusted = steps.iter().position(|step| step.lists_target);
+        println!("Resolution order for '{}':", target_name.raw());
+        for (i, step) in steps.iter().enumerate() {
+            println!(
+                "{:indent$}{}{}: {}{}",
+                "",
+                step.role,
+                if step.terminating {
+                    " (terminating)"
+                } else {
+                    ""
+                },
+                if step.lists_target {
+                    "listed"
+                } else {
+                    "not listed"
+                },
+                if trusted == Some(i) { " (trusted)" } else { "" },
+                indent = 2 * (step.depth + 1),
+            );
+        }
+
+        let target = trusted
+            .and_then(|i| listed_target(targets, &steps[i], &target_name))
+            .context(error::TargetNotFoundSnafu {
+                target: target_name.raw(),
+            })?;
+        println!("Length: {}", target.length);
+        println!("SHA-256: {}", hex::encode(&target.hashes.sha256));
+        Ok(())
+    }
+}
+
+/// Returns the target that the role of `step` lists under `target_name`.
+fn listed_target<'a>(
+    targets: &'a Targets,
+    step: &ResolutionStep,
+    target_name: &TargetName,
+) -> Option<&'a Target> {
+    if step.depth == 0 {
+        targets.targets.get(target_name)
+    } else {
+        targets
+            .delegated_targets(&step.role)
+            .ok()?
+            .signed
+            .targets
+            .get(target_name)
+    }
+}
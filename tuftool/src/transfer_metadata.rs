@@ -8,7 +8,7 @@ use chrono::{DateTime, Utc};
 use clap::Parser;
 use snafu::ResultExt;
 use std::fs::File;
-use std::num::NonZeroU64;
+use std::num::{NonZeroU64, NonZeroUsize};
 use std::path::{Path, PathBuf};
 use tough::editor::RepositoryEditor;
 use tough::key_source::KeySource;
@@ -68,6 +68,10 @@ pub(crate) struct TransferMetadataArgs {
     /// Allow repo update from expired metadata
     #[clap(long)]
     allow_expired_repo: bool,
+
+    /// Maximum number of delegated role metadata files to fetch at the same time
+    #[clap(long = "concurrency")]
+    concurrency: Option<NonZeroUsize>,
 }
 
 fn expired_repo_warning<P: AsRef<Path>>(from_path: P, to_path: P) {
@@ -93,16 +97,18 @@ impl TransferMetadataArgs {
         } else {
             ExpirationEnforcement::Safe
         };
-        let current_repo = RepositoryLoader::new(
+        let mut loader = RepositoryLoader::new(
             File::open(current_root).context(error::OpenRootSnafu {
                 path: &current_root,
             })?,
             self.metadata_base_url.clone(),
             self.targets_base_url.clone(),
         )
-        .expiration_enforcement(expiration_enforcement)
-        .load()
-        .context(error::RepoLoadSnafu)?;
+        .expiration_enforcement(expiration_enforcement);
+        if let Some(concurrency) = self.concurrency {
+            loader = loader.delegation_fetch_concurrency(concurrency);
+        }
+        let current_repo = loader.load().context(error::RepoLoadSnafu)?;
 
         let mut editor = RepositoryEditor::new(new_root)
             .context(error::EditorCreateSnafu { path: &new_root })?;
@@ -124,6 +130,8 @@ impl TransferMetadataArgs {
                 .context(error::DelegationStructureSnafu)?;
         }
 
+        // Nothing is written until every role has been loaded and signed, so a failure on any
+        // role leaves the output directory untouched.
         let signed_repo = editor.sign(&self.keys).context(error::SignRepoSnafu)?;
 
         let metadata_dir = &self.outdir.join("metadata");
@@ -133,6 +141,11 @@ impl TransferMetadataArgs {
                 directory: metadata_dir,
             })?;
 
+        println!(
+            "Transferred {} targets to {}",
+            targets.signed.targets.len(),
+            metadata_dir.display()
+        );
         Ok(())
     }
 }
@@ -2,28 +2,46 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use crate::build_targets;
-use crate::common::UNUSED_URL;
+use crate::common::{parse_base_url, UNUSED_URL};
 use crate::datetime::parse_datetime;
 use crate::error::{self, Result};
-use crate::source::parse_key_source;
+use crate::source::{parse_key_id_source, parse_key_source};
+use crate::warnings::{find_warnings, report};
 use chrono::{DateTime, Utc};
 use clap::Parser;
-use snafu::{OptionExt, ResultExt};
+use log::warn;
+use snafu::{ensure, OptionExt, ResultExt};
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::num::{NonZeroU64, NonZeroUsize};
 use std::path::{Path, PathBuf};
-use tough::editor::signed::PathExists;
+use tough::editor::signed::{PathExists, SignedRepository};
 use tough::editor::RepositoryEditor;
 use tough::key_source::KeySource;
-use tough::{ExpirationEnforcement, RepositoryLoader};
+use tough::schema::decoded::{Decoded, Hex};
+use tough::schema::{RoleType, Root};
+use tough::{
+    target_fetch_url, DefaultTransport, ExpirationEnforcement, Repository, RepositoryLoader,
+    TargetName, Transport, TransportErrorKind,
+};
 use url::Url;
 
 #[derive(Debug, Parser)]
 pub(crate) struct UpdateArgs {
     /// Key files to sign with
-    #[clap(short = 'k', long = "key", required = true, parse(try_from_str = parse_key_source))]
+    #[clap(
+        short = 'k',
+        long = "key",
+        required_unless_present = "sign-with-key-id",
+        parse(try_from_str = parse_key_source)
+    )]
     keys: Vec<Box<dyn KeySource>>,
 
+    /// Key source that holds more than one key, and the ID of the key in it to sign with, as
+    /// '<key source>=<key ID>'; it's an error if the key source doesn't hold the key
+    #[clap(long = "sign-with-key-id", parse(try_from_str = parse_key_id_source))]
+    sign_with_key_id: Vec<Box<dyn KeySource>>,
+
     /// Version of snapshot.json file
     #[clap(long = "snapshot-version")]
     snapshot_version: NonZeroU64,
@@ -60,6 +78,30 @@ pub(crate) struct UpdateArgs {
     #[clap(short = 't', long = "add-targets")]
     targets_indir: Option<PathBuf>,
 
+    /// Remove the targets listed in targets.json that aren't in the `--add-targets` directory, so
+    /// that targets.json lists exactly the directory's targets
+    #[clap(long = "prune-targets", requires = "targets-indir")]
+    prune_targets: bool,
+
+    /// Metadata base URL of another repository whose targets, including those of its delegated
+    /// roles, replace the targets listed by this repository's targets.json; the target files
+    /// themselves are not copied
+    #[clap(
+        long = "from-repo",
+        requires = "from-root",
+        conflicts_with = "targets-indir"
+    )]
+    from_repo: Option<Url>,
+
+    /// Path to root.json file for the repository given by `--from-repo`
+    #[clap(long = "from-root", requires = "from-repo")]
+    from_root: Option<PathBuf>,
+
+    /// TUF spec version to write to the updated metadata; by default the repository's existing
+    /// spec version is kept
+    #[clap(long = "spec-version")]
+    spec_version: Option<String>,
+
     /// Behavior when a target exists with the same name and hash in the desired repository
     /// directory, for example from another repository when you're sharing target directories.
     /// Options are "replace", "fail", and "skip"
@@ -78,6 +120,11 @@ pub(crate) struct UpdateArgs {
     #[clap(short = 'j', long = "jobs")]
     jobs: Option<NonZeroUsize>,
 
+    /// Read each target in chunks of this many bytes on its own thread while hashing it, which
+    /// speeds up hashing very large targets
+    #[clap(long = "hash-chunk-size")]
+    hash_chunk_size: Option<NonZeroUsize>,
+
     /// The directory where the updated repository will be written
     #[clap(short = 'o', long = "outdir")]
     outdir: PathBuf,
@@ -90,9 +137,40 @@ pub(crate) struct UpdateArgs {
     #[clap(long = "role")]
     role: Option<String>,
 
+    /// Expiration to set for delegated roles, whose versions are also incremented. Roles that
+    /// can't be signed with the given keys are skipped
+    #[clap(long = "delegated-expires", parse(try_from_str = parse_datetime))]
+    delegated_expires: Option<DateTime<Utc>>,
+
+    /// Delegated role to update with `--delegated-expires`; all delegated roles if not given
+    #[clap(long = "delegated-role", requires = "delegated-expires")]
+    delegated_roles: Vec<String>,
+
     /// Allow repo download for expired metadata
     #[clap(long)]
     allow_expired_repo: bool,
+
+    /// Fail without writing the updated repository if any warning is found in it: a role
+    /// expiring before `--warn-expiring-before`, a delegated role trusted for no paths, or a
+    /// target name listed by more than one role
+    #[clap(long = "strict-warnings")]
+    strict_warnings: bool,
+
+    /// Warn about roles that expire before this time; can be in full RFC 3339 format, or
+    /// something like 'in 7 days'
+    #[clap(
+        long = "warn-expiring-before",
+        default_value = "in 7 days",
+        parse(try_from_str = parse_datetime)
+    )]
+    warn_expiring_before: DateTime<Utc>,
+
+    /// Before writing the updated metadata, check that each target it newly references, i.e. each
+    /// target that was added or whose hash changed, can be fetched from this targets base URL,
+    /// and fail if any can't. This keeps metadata from being published with references to
+    /// targets that failed to upload
+    #[clap(long = "check-targets-url", parse(try_from_str = parse_base_url))]
+    check_targets_url: Option<Url>,
 }
 
 fn expired_repo_warning<P: AsRef<Path>>(path: P) {
@@ -106,28 +184,75 @@ WARNING: `--allow-expired-repo` was passed; this is unsafe and will not establis
 }
 
 impl UpdateArgs {
-    pub(crate) fn run(&self) -> Result<()> {
+    pub(crate) fn run(mut self) -> Result<()> {
+        self.keys.append(&mut self.sign_with_key_id);
         let expiration_enforcement = if self.allow_expired_repo {
             expired_repo_warning(&self.outdir);
             ExpirationEnforcement::Unsafe
         } else {
             ExpirationEnforcement::Safe
         };
-        let repository = RepositoryLoader::new(
-            File::open(&self.root).context(error::OpenRootSnafu { path: &self.root })?,
+        let repository = load_repo(
+            &self.root,
             self.metadata_base_url.clone(),
-            Url::parse(UNUSED_URL).context(error::UrlParseSnafu { url: UNUSED_URL })?,
-        )
-        .expiration_enforcement(expiration_enforcement)
-        .load()
-        .context(error::RepoLoadSnafu)?;
+            expiration_enforcement,
+        )?;
+        // Fail before any targets are hashed if signing would fail anyway
+        check_signing_keys(&repository.root().signed, &self.keys)?;
+        let source = match (&self.from_root, &self.from_repo) {
+            (Some(from_root), Some(from_repo)) => Some(load_repo(
+                from_root,
+                from_repo.clone(),
+                expiration_enforcement,
+            )?),
+            _ => None,
+        };
+        let delegated_roles = if self.delegated_roles.is_empty() {
+            repository
+                .targets()
+                .signed
+                .role_names()
+                .into_iter()
+                .cloned()
+                .collect()
+        } else {
+            self.delegated_roles.clone()
+        };
+        let mut existing_targets = repository
+            .targets()
+            .signed
+            .targets
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        existing_targets.sort();
+        let previous_hashes = repository
+            .all_targets()
+            .map(|(target_name, target)| (target_name.clone(), target.hashes.sha256.clone()))
+            .collect::<HashMap<_, _>>();
         self.update_metadata(
             RepositoryEditor::from_repo(&self.root, repository)
                 .context(error::EditorFromRepoSnafu { path: &self.root })?,
+            source.as_ref(),
+            &delegated_roles,
+            &existing_targets,
+            &previous_hashes,
         )
     }
 
-    fn update_metadata(&self, mut editor: RepositoryEditor) -> Result<()> {
+    fn update_metadata(
+        &self,
+        mut editor: RepositoryEditor,
+        source: Option<&Repository>,
+        delegated_roles: &[String],
+        existing_targets: &[TargetName],
+        previous_hashes: &HashMap<TargetName, Decoded<Hex>>,
+    ) -> Result<()> {
+        if let Some(spec_version) = &self.spec_version {
+            editor
+                .spec_version(spec_version)
+                .context(error::SpecVersionSnafu { spec_version })?;
+        }
         editor
             .targets_version(self.targets_version)
             .context(error::DelegationStructureSnafu)?
@@ -149,7 +274,20 @@ impl UpdateArgs {
                     .context(error::InitializeThreadPoolSnafu)?;
             }
 
-            let new_targets = build_targets(targets_indir, self.follow)?;
+            let new_targets = build_targets(targets_indir, self.follow, self.hash_chunk_size)?;
+
+            // If "prune-targets" was passed, the directory is the whole set of targets
+            if self.prune_targets {
+                for target_name in existing_targets
+                    .iter()
+                    .filter(|target_name| !new_targets.contains_key(*target_name))
+                {
+                    editor
+                        .remove_target(target_name)
+                        .context(error::DelegationStructureSnafu)?;
+                    println!("Removed target '{}'", target_name.raw());
+                }
+            }
 
             for (target_name, target) in new_targets {
                 editor
@@ -158,6 +296,23 @@ impl UpdateArgs {
             }
         };
 
+        // If the "from-repo" argument was passed, replace the targets with every target the other
+        // repository lists, including those listed by its delegated roles. A target listed by
+        // more than one role is taken from the first one a client would search.
+        if let Some(source) = source {
+            editor
+                .clear_targets()
+                .context(error::DelegationStructureSnafu)?;
+            let mut added = HashSet::new();
+            for (target_name, target) in source.all_targets() {
+                if added.insert(target_name) {
+                    editor
+                        .add_target(target_name.clone(), target.clone())
+                        .context(error::DelegationStructureSnafu)?;
+                }
+            }
+        }
+
         // If a `Targets` metadata needs to be updated
         if self.role.is_some() && self.indir.is_some() {
             editor
@@ -179,9 +334,36 @@ impl UpdateArgs {
                 })?;
         }
 
+        // If "delegated-expires" was passed, bump the delegated roles we have keys for
+        if let Some(delegated_expires) = self.delegated_expires {
+            editor
+                .sign_targets_editor(&self.keys)
+                .context(error::DelegationStructureSnafu)?;
+            for role in delegated_roles {
+                match editor.stamp_delegated_role(role, delegated_expires, &self.keys) {
+                    Ok(_) => println!("Updated delegated role '{}'", role),
+                    Err(tough::error::Error::SigningKeysNotFound { .. }) => {
+                        warn!("Skipping delegated role '{}': no signing keys given", role);
+                    }
+                    Err(source) => {
+                        return Err(source).context(error::StampDelegatedRoleSnafu { role });
+                    }
+                }
+            }
+        }
+
         // Sign the repo
         let signed_repo = editor.sign(&self.keys).context(error::SignRepoSnafu)?;
 
+        let warnings = find_warnings(
+            signed_repo.root().signed(),
+            signed_repo.timestamp().signed(),
+            signed_repo.snapshot().signed(),
+            signed_repo.targets().signed(),
+            self.warn_expiring_before,
+        );
+        report(&warnings, self.strict_warnings)?;
+
         // Symlink any targets that were added
         if let Some(ref targets_indir) = self.targets_indir {
             let targets_outdir = &self.outdir.join("targets");
@@ -193,6 +375,10 @@ impl UpdateArgs {
                 })?;
         };
 
+        if let Some(check_targets_url) = &self.check_targets_url {
+            check_targets_available(&signed_repo, previous_hashes, check_targets_url)?;
+        }
+
         // Write the metadata to the outdir
         let metadata_dir = &self.outdir.join("metadata");
         signed_repo
@@ -204,3 +390,103 @@ impl UpdateArgs {
         Ok(())
     }
 }
+
+/// Checks that each target `signed_repo` lists that isn't in `previous_hashes` with the same
+/// sha256 can be fetched from `targets_base_url`, named as the repository names its target files.
+/// Fails with the names of the targets that aren't found.
+fn check_targets_available(
+    signed_repo: &SignedRepository,
+    previous_hashes: &HashMap<TargetName, Decoded<Hex>>,
+    targets_base_url: &Url,
+) -> Result<()> {
+    let consistent_snapshot = signed_repo.root().signed().signed.consistent_snapshot;
+    let mut new_targets = signed_repo
+        .targets()
+        .signed()
+        .signed
+        .targets_iter()
+        .filter(|(target_name, target)| {
+            previous_hashes.get(*target_name) != Some(&target.hashes.sha256)
+        })
+        .collect::<Vec<_>>();
+    new_targets.sort_by_key(|(target_name, _)| *target_name);
+    new_targets.dedup_by_key(|(target_name, _)| *target_name);
+
+    let transport = DefaultTransport::new();
+    let mut missing = Vec::new();
+    for (target_name, target) in new_targets {
+        let url = target_fetch_url(targets_base_url, target_name, target, consistent_snapshot)
+            .context(error::TargetUrlSnafu {
+                target: target_name.raw(),
+            })?;
+        match transport.fetch(url) {
+            Ok(_) => {}
+            Err(err) if err.kind() == TransportErrorKind::FileNotFound => {
+                missing.push(target_name.raw().to_owned());
+            }
+            Err(err) => {
+                return Err(err).context(error::TargetCheckSnafu {
+                    target: target_name.raw(),
+                })
+            }
+        }
+    }
+    ensure!(
+        missing.is_empty(),
+        error::TargetsUnavailableSnafu {
+            url: targets_base_url.clone(),
+            targets: missing.join(", "),
+        }
+    );
+    Ok(())
+}
+
+/// Checks that `keys` can make enough signatures to meet the threshold of every top-level role
+/// signed by `update`, and names the first role whose threshold can't be met.
+fn check_signing_keys(root: &Root, keys: &[Box<dyn KeySource>]) -> Result<()> {
+    let mut key_ids = HashSet::new();
+    for source in keys {
+        let key_pair = source.as_sign().context(error::KeyPairFromKeySourceSnafu)?;
+        key_ids.insert(
+            key_pair
+                .tuf_key()
+                .key_id()
+                .context(error::JsonSerializationSnafu)?,
+        );
+    }
+    for role in [RoleType::Targets, RoleType::Snapshot, RoleType::Timestamp] {
+        let role_keys = root.roles.get(&role).context(error::MissingSnafu {
+            what: format!("{role} role in root.json"),
+        })?;
+        let signing_keys = role_keys
+            .keyids
+            .iter()
+            .filter(|keyid| key_ids.contains(*keyid))
+            .count();
+        ensure!(
+            signing_keys as u64 >= role_keys.threshold.get(),
+            error::SigningKeysThresholdSnafu {
+                role,
+                keys: signing_keys,
+                threshold: role_keys.threshold.get(),
+            }
+        );
+    }
+    Ok(())
+}
+
+/// Loads the repository metadata at `metadata_base_url`, trusting the root.json at `root`.
+fn load_repo(
+    root: &Path,
+    metadata_base_url: Url,
+    expiration_enforcement: ExpirationEnforcement,
+) -> Result<Repository> {
+    RepositoryLoader::new(
+        File::open(root).context(error::OpenRootSnafu { path: root })?,
+        metadata_base_url,
+        Url::parse(UNUSED_URL).context(error::UrlParseSnafu { url: UNUSED_URL })?,
+    )
+    .expiration_enforcement(expiration_enforcement)
+    .load()
+    .context(error::RepoLoadSnafu)
+}
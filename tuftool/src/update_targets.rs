@@ -2,16 +2,16 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use crate::build_targets;
-use crate::common::load_metadata_repo;
+use crate::common::{load_metadata_repo, write_delegated_targets, STDOUT_OUTDIR};
 use crate::datetime::parse_datetime;
 use crate::error::{self, Result};
 use crate::source::parse_key_source;
 use chrono::{DateTime, Utc};
 use clap::Parser;
-use snafu::ResultExt;
+use snafu::{ensure, ResultExt};
 use std::num::NonZeroU64;
 use std::num::NonZeroUsize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tough::editor::signed::PathExists;
 use tough::editor::targets::TargetsEditor;
 use tough::key_source::KeySource;
@@ -44,7 +44,8 @@ pub(crate) struct UpdateTargetsArgs {
     #[clap(short = 't', long = "add-targets")]
     targets_indir: Option<PathBuf>,
 
-    /// The directory where the repository will be written
+    /// The directory where the repository will be written; use '-' to write the signed role to
+    /// stdout, unless targets are being added
     #[clap(short = 'o', long = "outdir")]
     outdir: PathBuf,
 
@@ -60,24 +61,42 @@ pub(crate) struct UpdateTargetsArgs {
     #[clap(short = 'j', long = "jobs")]
     jobs: Option<NonZeroUsize>,
 
+    /// Read each target in chunks of this many bytes on its own thread while hashing it, which
+    /// speeds up hashing very large targets
+    #[clap(long = "hash-chunk-size")]
+    hash_chunk_size: Option<NonZeroUsize>,
+
     /// Behavior when a target exists with the same name and hash in the desired repository
     /// directory, for example from another repository when you're sharing target directories.
     /// Options are "replace", "fail", and "skip"
     #[clap(long = "target-path-exists", default_value = "skip")]
     target_path_exists: PathExists,
+
+    /// Sign the role even if some of its targets aren't covered by the paths delegated to it, for
+    /// example while staging a change to the delegation
+    #[clap(long = "force")]
+    force: bool,
 }
 
 impl UpdateTargetsArgs {
     pub(crate) fn run(&self, role: &str) -> Result<()> {
+        ensure!(
+            self.targets_indir.is_none() || self.outdir != Path::new(STDOUT_OUTDIR),
+            error::StdoutAddTargetsSnafu
+        );
         let repository = load_metadata_repo(&self.root, self.metadata_base_url.clone())?;
         self.update_targets(
+            role,
             TargetsEditor::from_repo(repository, role)
                 .context(error::EditorFromRepoSnafu { path: &self.root })?,
         )
     }
 
-    fn update_targets(&self, mut editor: TargetsEditor) -> Result<()> {
-        editor.version(self.version).expires(self.expires);
+    fn update_targets(&self, role: &str, mut editor: TargetsEditor) -> Result<()> {
+        editor
+            .version(self.version)
+            .expires(self.expires)
+            .allow_undelegated_targets(self.force);
 
         // If the "add-targets" argument was passed, build a list of targets
         // and add them to the repository. If a user specifies job count we
@@ -90,7 +109,7 @@ impl UpdateTargetsArgs {
                     .context(error::InitializeThreadPoolSnafu)?;
             }
 
-            let new_targets = build_targets(targets_indir, self.follow)?;
+            let new_targets = build_targets(targets_indir, self.follow, self.hash_chunk_size)?;
 
             for (target_name, target) in new_targets {
                 editor
@@ -114,13 +133,6 @@ impl UpdateTargetsArgs {
         };
 
         // Write the metadata to the outdir
-        let metadata_dir = &self.outdir.join("metadata");
-        signed_role
-            .write(metadata_dir, false)
-            .context(error::WriteRepoSnafu {
-                directory: metadata_dir,
-            })?;
-
-        Ok(())
+        write_delegated_targets(signed_role, &self.outdir, role, [role.to_string()].to_vec())
     }
 }
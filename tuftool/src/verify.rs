@@ -0,0 +1,160 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::common::{load_metadata_repo, role_keys};
+use crate::datetime::parse_datetime;
+use crate::error::{self, Result};
+use crate::load_file;
+use crate::warnings::{find_warnings, report};
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use serde::Deserialize;
+use snafu::ensure;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tough::schema::Hashes;
+use tough::Repository;
+use url::Url;
+
+#[derive(Debug, Parser)]
+pub(crate) struct VerifyArgs {
+    /// Path to root.json file for the repository
+    #[clap(short = 'r', long = "root")]
+    root: PathBuf,
+
+    /// TUF repository metadata base URL
+    #[clap(short = 'm', long = "metadata-url")]
+    metadata_base_url: Url,
+
+    /// Also fail if any target name is listed by more than one role, which usually points to
+    /// misconfigured delegations
+    #[clap(long = "strict")]
+    strict: bool,
+
+    /// Fail if any warning is found: a role expiring before `--warn-expiring-before`, a delegated
+    /// role trusted for no paths, or a target name listed by more than one role
+    #[clap(long = "strict-warnings")]
+    strict_warnings: bool,
+
+    /// Warn about roles that expire before this time; can be in full RFC 3339 format, or
+    /// something like 'in 7 days'
+    #[clap(
+        long = "warn-expiring-before",
+        default_value = "in 7 days",
+        parse(try_from_str = parse_datetime)
+    )]
+    warn_expiring_before: DateTime<Utc>,
+
+    /// Fail unless the repository's targets match this inventory exactly: a JSON file with a
+    /// `targets` list, each entry giving a target's `name` and `hashes`. The index written by
+    /// `tuftool clone --emit-index` is an inventory
+    #[clap(long = "expect-inventory")]
+    expect_inventory: Option<PathBuf>,
+}
+
+/// The targets a repository is expected to hold.
+#[derive(Debug, Deserialize)]
+struct Inventory {
+    targets: Vec<InventoryTarget>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InventoryTarget {
+    name: String,
+    hashes: Hashes,
+}
+
+impl VerifyArgs {
+    pub(crate) fn run(&self) -> Result<()> {
+        let repository = load_metadata_repo(&self.root, self.metadata_base_url.clone())?;
+
+        if self.strict {
+            let duplicates = repository.targets().signed.duplicate_targets();
+            for (target_name, roles) in &duplicates {
+                eprintln!(
+                    "Target '{}' is listed by roles: {}",
+                    target_name.raw(),
+                    roles.join(", ")
+                );
+            }
+            ensure!(
+                duplicates.is_empty(),
+                error::DuplicateTargetsSnafu {
+                    count: duplicates.len()
+                }
+            );
+        }
+
+        if let Some(path) = &self.expect_inventory {
+            check_inventory(&repository, path)?;
+        }
+
+        let warnings = find_warnings(
+            repository.root(),
+            repository.timestamp(),
+            repository.snapshot(),
+            repository.targets(),
+            self.warn_expiring_before,
+        );
+        report(&warnings, self.strict_warnings)?;
+
+        for key in role_keys(&repository) {
+            println!(
+                "Role '{}' key {} uses scheme {}",
+                key.role, key.key_id, key.scheme
+            );
+        }
+        println!("Repository verified");
+        Ok(())
+    }
+}
+
+/// Compares the targets `repository` resolves, including delegated targets, against the inventory
+/// in `path`, reporting each target that is missing, extra, or has a different sha256 hash.
+fn check_inventory(repository: &Repository, path: &Path) -> Result<()> {
+    let inventory: Inventory = load_file(path)?;
+    let mut expected = BTreeMap::new();
+    for target in inventory.targets {
+        expected.insert(target.name, target.hashes.sha256);
+    }
+
+    // A target listed by more than one role resolves to the first, as it does for clients
+    let mut actual = BTreeMap::new();
+    for (target_name, _) in repository.all_targets() {
+        if let Ok(target) = repository.targets().signed.find_target(target_name) {
+            actual.insert(target_name.raw().to_owned(), &target.hashes.sha256);
+        }
+    }
+
+    let mut count = 0;
+    for (name, sha256) in &expected {
+        match actual.get(name) {
+            None => {
+                eprintln!(
+                    "Target '{}' is in the inventory but not the repository",
+                    name
+                );
+                count += 1;
+            }
+            Some(actual_sha256) if *actual_sha256 != sha256 => {
+                eprintln!(
+                    "Target '{}' has sha256 {} but the inventory expects {}",
+                    name,
+                    hex::encode(actual_sha256),
+                    hex::encode(sha256)
+                );
+                count += 1;
+            }
+            Some(_) => {}
+        }
+    }
+    for name in actual.keys().filter(|name| !expected.contains_key(*name)) {
+        eprintln!(
+            "Target '{}' is in the repository but not the inventory",
+            name
+        );
+        count += 1;
+    }
+    ensure!(count == 0, error::InventoryMismatchSnafu { count, path });
+    Ok(())
+}
@@ -0,0 +1,128 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Conditions that don't make a repository invalid, but that usually point to a mistake. Commands
+//! print these as warnings, and fail because of them when `--strict-warnings` is passed.
+
+use crate::error::{self, Result};
+use chrono::{DateTime, Utc};
+use snafu::ensure;
+use std::fmt;
+use tough::schema::{PathSet, Root, Signed, Snapshot, Targets, Timestamp};
+
+/// A condition found in a repository's metadata that is reported as a warning.
+#[derive(Debug)]
+pub(crate) enum Warning {
+    /// A role's metadata expires before the time given with `--warn-expiring-before`.
+    NearExpiry {
+        role: String,
+        expires: DateTime<Utc>,
+    },
+    /// A delegated role is trusted for no paths, so none of its targets can be used.
+    EmptyPaths { role: String },
+    /// A target name is listed by more than one role.
+    DuplicateTarget { target: String, roles: Vec<String> },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::NearExpiry { role, expires } => {
+                write!(
+                    f,
+                    "Role '{}' expires soon, at {}",
+                    role,
+                    expires.to_rfc3339()
+                )
+            }
+            Warning::EmptyPaths { role } => {
+                write!(f, "Delegated role '{}' is trusted for no paths", role)
+            }
+            Warning::DuplicateTarget { target, roles } => write!(
+                f,
+                "Target '{}' is listed by roles: {}",
+                target,
+                roles.join(", ")
+            ),
+        }
+    }
+}
+
+/// Finds the warnings for a repository's metadata. Roles expiring before `expiring_before` are
+/// reported as near expiry.
+pub(crate) fn find_warnings(
+    root: &Signed<Root>,
+    timestamp: &Signed<Timestamp>,
+    snapshot: &Signed<Snapshot>,
+    targets: &Signed<Targets>,
+    expiring_before: DateTime<Utc>,
+) -> Vec<Warning> {
+    let mut expirations = vec![
+        ("root".to_owned(), root.signed.expires),
+        ("timestamp".to_owned(), timestamp.signed.expires),
+        ("snapshot".to_owned(), snapshot.signed.expires),
+        ("targets".to_owned(), targets.signed.expires),
+    ];
+    let mut empty_paths = Vec::new();
+    walk_delegations(&targets.signed, &mut expirations, &mut empty_paths);
+
+    let mut warnings = expirations
+        .into_iter()
+        .filter(|(_, expires)| *expires < expiring_before)
+        .map(|(role, expires)| Warning::NearExpiry { role, expires })
+        .collect::<Vec<_>>();
+    warnings.append(&mut empty_paths);
+    warnings.extend(
+        targets
+            .signed
+            .duplicate_targets()
+            .into_iter()
+            .map(|(target_name, roles)| Warning::DuplicateTarget {
+                target: target_name.raw().to_owned(),
+                roles,
+            }),
+    );
+    warnings
+}
+
+/// Collects the expiration of every delegated role reachable from `targets`, depth first, and
+/// warns about delegated roles with empty path sets.
+fn walk_delegations(
+    targets: &Targets,
+    expirations: &mut Vec<(String, DateTime<Utc>)>,
+    warnings: &mut Vec<Warning>,
+) {
+    let roles = match &targets.delegations {
+        Some(delegations) => &delegations.roles,
+        None => return,
+    };
+    for role in roles {
+        let empty = match &role.paths {
+            PathSet::Paths(paths) => paths.is_empty(),
+            PathSet::PathHashPrefixes(prefixes) => prefixes.is_empty(),
+        };
+        if empty {
+            warnings.push(Warning::EmptyPaths {
+                role: role.name.clone(),
+            });
+        }
+        if let Some(delegated_targets) = &role.targets {
+            expirations.push((role.name.clone(), delegated_targets.signed.expires));
+            walk_delegations(&delegated_targets.signed, expirations, warnings);
+        }
+    }
+}
+
+/// Prints each warning to stderr, and fails if there are any and `strict` is set.
+pub(crate) fn report(warnings: &[Warning], strict: bool) -> Result<()> {
+    for warning in warnings {
+        eprintln!("WARNING: {}", warning);
+    }
+    ensure!(
+        !strict || warnings.is_empty(),
+        error::StrictWarningsSnafu {
+            count: warnings.len()
+        }
+    );
+    Ok(())
+}
@@ -196,3 +196,235 @@ fn clone_full_repo() {
         assert_target_match(&repo_paths.targets_outdir, f)
     }
 }
+
+/// Given a `Command`, attach the args to clone the `rotated-root` metadata from its given
+/// `root.json` version, pinning the starting root to `root_version`
+fn clone_rotated_root_command<'a>(
+    cmd: &'a mut Command,
+    root_file: &str,
+    root_version: &str,
+    metadata_outdir: &TempDir,
+) -> &'a mut Command {
+    let base = test_data().join("rotated-root");
+    cmd.args([
+        "clone",
+        "--root",
+        base.join(root_file).to_str().unwrap(),
+        "--root-version",
+        root_version,
+        "--metadata-url",
+        dir_url(&base).as_str(),
+        "--metadata-dir",
+        metadata_outdir.path().to_str().unwrap(),
+        "--metadata-only",
+    ])
+}
+
+#[test]
+// Ensure we can start a clone from a pinned root version that chains to the provided root
+fn clone_pinned_root_version() {
+    let metadata_outdir = TempDir::new().unwrap();
+    let mut cmd = Command::cargo_bin("tuftool").unwrap();
+    clone_rotated_root_command(&mut cmd, "1.root.json", "2", &metadata_outdir)
+        .assert()
+        .success();
+
+    for f in &["1.root.json", "2.root.json", "timestamp.json"] {
+        assert!(metadata_outdir.path().join(f).is_file());
+    }
+}
+
+#[test]
+// Ensure a pinned root version the repository can't provide, or one older than the provided root,
+// fails the clone
+fn clone_pinned_root_version_failure() {
+    let metadata_outdir = TempDir::new().unwrap();
+    let mut cmd = Command::cargo_bin("tuftool").unwrap();
+    clone_rotated_root_command(&mut cmd, "1.root.json", "3", &metadata_outdir)
+        .assert()
+        .failure();
+
+    let mut cmd = Command::cargo_bin("tuftool").unwrap();
+    clone_rotated_root_command(&mut cmd, "2.root.json", "1", &metadata_outdir)
+        .assert()
+        .failure();
+}
+
+#[test]
+// Ensure `--plan` reports the changes a clone would make, without making them, and that `--prune`
+// then removes the files it lists for deletion
+fn clone_plan_and_prune() {
+    let repo_paths = RepoPaths::new();
+    let targets_dir = repo_paths.targets_outdir.path();
+    std::fs::write(targets_dir.join("file1.txt"), "outdated").unwrap();
+    std::fs::write(targets_dir.join("old.txt"), "old").unwrap();
+    let targets_args = [
+        "--targets-url",
+        repo_paths.targets_base_url.as_str(),
+        "--targets-dir",
+        targets_dir.to_str().unwrap(),
+        "--prune",
+    ];
+
+    let mut cmd = Command::cargo_bin("tuftool").unwrap();
+    let output = clone_base_command(&mut cmd, &repo_paths)
+        .args(targets_args)
+        .arg("--plan")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let plan: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        plan,
+        serde_json::json!({
+            "add": ["file2.txt", "file3.txt"],
+            "update": ["file1.txt"],
+            "delete": ["old.txt"],
+        })
+    );
+    // Nothing was downloaded or removed
+    assert!(!targets_dir.join("file2.txt").exists());
+    assert!(targets_dir.join("old.txt").exists());
+
+    let mut cmd = Command::cargo_bin("tuftool").unwrap();
+    clone_base_command(&mut cmd, &repo_paths)
+        .args(targets_args)
+        .assert()
+        .success();
+    for f in &["file1.txt", "file2.txt", "file3.txt"] {
+        assert_target_match(&repo_paths.targets_outdir, f)
+    }
+    assert!(!targets_dir.join("old.txt").exists());
+}
+
+#[test]
+// Ensure `--emit-index` writes a flattened index that matches the cloned targets
+fn clone_emit_index() {
+    let repo_paths = RepoPaths::new();
+    let index_dir = TempDir::new().unwrap();
+    let index_path = index_dir.path().join("index.json");
+    let mut cmd = Command::cargo_bin("tuftool").unwrap();
+    clone_base_command(&mut cmd, &repo_paths)
+        .args([
+            "--targets-url",
+            repo_paths.targets_base_url.as_str(),
+            "--targets-dir",
+            repo_paths.targets_outdir.path().to_str().unwrap(),
+            "--emit-index",
+            index_path.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    let index: serde_json::Value =
+        serde_json::from_str(&read_to_string(&index_path).unwrap()).unwrap();
+    assert_eq!(index["targets_version"], 1);
+    let targets = index["targets"].as_array().unwrap();
+    let names = targets
+        .iter()
+        .map(|target| target["name"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["file1.txt", "file2.txt", "file3.txt"]);
+
+    // Each entry describes the file cloned at its path
+    for target in targets {
+        let contents = std::fs::read(
+            repo_paths
+                .targets_outdir
+                .path()
+                .join(target["path"].as_str().unwrap()),
+        )
+        .unwrap();
+        assert_eq!(target["length"], contents.len());
+        assert_eq!(
+            target["hashes"]["sha256"],
+            hex::encode(ring::digest::digest(&ring::digest::SHA256, &contents))
+        );
+    }
+}
+
+/// Copies the reference targets to a new directory, corrupting `file1.txt` and `file3.txt`.
+fn corrupted_targets() -> TempDir {
+    let targets_dir = TempDir::new().unwrap();
+    for name in ["file1.txt", "file2.txt", "file3.txt"] {
+        let mut contents = std::fs::read(
+            test_data()
+                .join("tuf-reference-impl")
+                .join("targets")
+                .join(name),
+        )
+        .unwrap();
+        if name != "file2.txt" {
+            contents[0] ^= 1;
+        }
+        std::fs::write(targets_dir.path().join(name), contents).unwrap();
+    }
+    targets_dir
+}
+
+#[test]
+// Ensure every bad target is reported by default, and `--fail-fast` stops at the first one
+fn clone_fail_fast() {
+    let targets_dir = corrupted_targets();
+    for (fail_fast, reported) in [(false, 2), (true, 1)] {
+        let repo_paths = RepoPaths::new();
+        let mut cmd = Command::cargo_bin("tuftool").unwrap();
+        clone_base_command(&mut cmd, &repo_paths).args([
+            "--targets-url",
+            dir_url(targets_dir.path()).as_str(),
+            "--targets-dir",
+            repo_paths.targets_outdir.path().to_str().unwrap(),
+        ]);
+        if fail_fast {
+            cmd.arg("--fail-fast");
+        }
+        let output = cmd.output().unwrap();
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert_eq!(
+            stderr.matches("Failed to clone target 'file").count(),
+            reported,
+            "{stderr}"
+        );
+        assert!(stderr.contains("file1.txt"));
+        assert_eq!(stderr.contains("file3.txt"), !fail_fast);
+        // Targets after the first bad one are only cloned when collecting every failure
+        assert_eq!(
+            repo_paths.targets_outdir.path().join("file2.txt").is_file(),
+            !fail_fast
+        );
+        assert!(!repo_paths
+            .metadata_outdir
+            .path()
+            .join("targets.json")
+            .exists());
+    }
+}
+
+#[test]
+// Ensure targets larger than `--max-target-size` are skipped while the metadata is cloned in full
+fn clone_max_target_size() {
+    // file1.txt is 31 bytes, file2.txt is 39 bytes, and file3.txt is 28 bytes
+    let repo_paths = RepoPaths::new();
+    let mut cmd = Command::cargo_bin("tuftool").unwrap();
+    let output = clone_base_command(&mut cmd, &repo_paths)
+        .args([
+            "--targets-url",
+            repo_paths.targets_base_url.as_str(),
+            "--targets-dir",
+            repo_paths.targets_outdir.path().to_str().unwrap(),
+            "--max-target-size",
+            "31",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Skipping target 'file2.txt'"), "{stderr}");
+    assert_eq!(stderr.matches("Skipping target").count(), 1, "{stderr}");
+
+    assert_all_metadata(&repo_paths.metadata_outdir);
+    assert_target_match(&repo_paths.targets_outdir, "file1.txt");
+    assert_target_match(&repo_paths.targets_outdir, "file3.txt");
+    assert!(!repo_paths.targets_outdir.path().join("file2.txt").exists());
+}
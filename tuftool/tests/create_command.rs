@@ -4,11 +4,11 @@
 mod test_utils;
 
 use assert_cmd::Command;
-use chrono::{Duration, Utc};
+use chrono::{Duration, TimeZone, Utc};
 use std::fs::File;
 use tempfile::TempDir;
 use test_utils::dir_url;
-use tough::{RepositoryLoader, TargetName};
+use tough::{FixedClock, RepositoryLoader, TargetName};
 
 #[test]
 // Ensure we can read a repo created by the `tuftool` binary using the `tough` library
@@ -179,3 +179,271 @@ fn create_with_no_key() {
         .assert()
         .failure();
 }
+
+#[test]
+// Ensure relative expiration dates are resolved against SOURCE_DATE_EPOCH when it is set
+fn create_with_source_date_epoch() {
+    let epoch = Utc.with_ymd_and_hms(2030, 1, 1, 0, 0, 0).unwrap();
+    let targets_input_dir = test_utils::test_data()
+        .join("tuf-reference-impl")
+        .join("targets");
+    let root_json = test_utils::test_data().join("simple-rsa").join("root.json");
+    let root_key = test_utils::test_data().join("snakeoil.pem");
+    let repo_dir = TempDir::new().unwrap();
+
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .env("SOURCE_DATE_EPOCH", epoch.timestamp().to_string())
+        .args([
+            "create",
+            "-t",
+            targets_input_dir.to_str().unwrap(),
+            "-o",
+            repo_dir.path().to_str().unwrap(),
+            "-k",
+            root_key.to_str().unwrap(),
+            "--root",
+            root_json.to_str().unwrap(),
+            "--targets-expires",
+            "in 7 days",
+            "--targets-version",
+            "1",
+            "--snapshot-expires",
+            "in 2 weeks",
+            "--snapshot-version",
+            "1",
+            "--timestamp-expires",
+            "in 3 hours",
+            "--timestamp-version",
+            "1",
+        ])
+        .assert()
+        .success();
+
+    // Load the repo as of the fixed time
+    let repo = RepositoryLoader::new(
+        File::open(root_json).unwrap(),
+        dir_url(repo_dir.path().join("metadata")),
+        dir_url(repo_dir.path().join("targets")),
+    )
+    .clock(FixedClock(epoch))
+    .load()
+    .unwrap();
+
+    assert_eq!(repo.targets().signed.expires, epoch + Duration::days(7));
+    assert_eq!(repo.snapshot().signed.expires, epoch + Duration::weeks(2));
+    assert_eq!(repo.timestamp().signed.expires, epoch + Duration::hours(3));
+}
+
+/// Creates a repo in `repo_dir` with the reference implementation targets, passing
+/// `--consistent-snapshot` with the given setting, and loads it.
+fn create_consistent_snapshot(
+    root_json: &std::path::Path,
+    repo_dir: &std::path::Path,
+    consistent_snapshot: bool,
+) -> tough::Repository {
+    let targets_input_dir = test_utils::test_data()
+        .join("tuf-reference-impl")
+        .join("targets");
+    let root_key = test_utils::test_data().join("snakeoil.pem");
+    let expires = Utc::now().checked_add_signed(Duration::days(7)).unwrap();
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "create",
+            "-t",
+            targets_input_dir.to_str().unwrap(),
+            "-o",
+            repo_dir.to_str().unwrap(),
+            "-k",
+            root_key.to_str().unwrap(),
+            "--root",
+            root_json.to_str().unwrap(),
+            "--targets-expires",
+            expires.to_rfc3339().as_str(),
+            "--targets-version",
+            "3",
+            "--snapshot-expires",
+            expires.to_rfc3339().as_str(),
+            "--snapshot-version",
+            "2",
+            "--timestamp-expires",
+            expires.to_rfc3339().as_str(),
+            "--timestamp-version",
+            "1",
+            "--consistent-snapshot",
+            &consistent_snapshot.to_string(),
+        ])
+        .assert()
+        .success();
+
+    RepositoryLoader::new(
+        File::open(root_json).unwrap(),
+        dir_url(repo_dir.join("metadata")),
+        dir_url(repo_dir.join("targets")),
+    )
+    .load()
+    .unwrap()
+}
+
+#[test]
+// Ensure `--consistent-snapshot` writes a new version of root.json with the mode, leaving the
+// given root.json as is, and the written repo follows it
+fn create_consistent_snapshot_mode() {
+    let work_dir = TempDir::new().unwrap();
+    let root_json = work_dir.path().join("root.json");
+    // This root.json uses consistent snapshots
+    std::fs::copy(
+        test_utils::test_data().join("simple-rsa").join("root.json"),
+        &root_json,
+    )
+    .unwrap();
+    let original_root = std::fs::read(&root_json).unwrap();
+    let file1 = TargetName::new("file1.txt").unwrap();
+
+    let repo_dir = TempDir::new().unwrap();
+    let repo = create_consistent_snapshot(&root_json, repo_dir.path(), false);
+    assert!(!repo.root().signed.consistent_snapshot);
+    assert_eq!(repo.root().signed.version.get(), 2);
+    assert_eq!(std::fs::read(&root_json).unwrap(), original_root);
+    let metadata_dir = repo_dir.path().join("metadata");
+    assert!(metadata_dir.join("2.root.json").is_file());
+    assert!(metadata_dir.join("targets.json").is_file());
+    assert!(metadata_dir.join("snapshot.json").is_file());
+    assert!(!metadata_dir.join("3.targets.json").exists());
+    assert!(repo_dir.path().join("targets").join("file1.txt").is_file());
+    assert_eq!(
+        test_utils::read_to_end(repo.read_target(&file1).unwrap().unwrap()),
+        &b"This is an example target file."[..]
+    );
+
+    let repo_dir = TempDir::new().unwrap();
+    let repo = create_consistent_snapshot(&root_json, repo_dir.path(), true);
+    assert!(repo.root().signed.consistent_snapshot);
+    assert_eq!(repo.root().signed.version.get(), 1);
+    let metadata_dir = repo_dir.path().join("metadata");
+    assert!(metadata_dir.join("3.targets.json").is_file());
+    assert!(metadata_dir.join("2.snapshot.json").is_file());
+    assert!(!metadata_dir.join("targets.json").exists());
+    let sha256 = hex::encode(repo.targets().signed.targets[&file1].hashes.sha256.as_ref());
+    assert!(repo_dir
+        .path()
+        .join("targets")
+        .join(format!("{sha256}.file1.txt"))
+        .is_file());
+    assert_eq!(
+        test_utils::read_to_end(repo.read_target(&file1).unwrap().unwrap()),
+        &b"This is an example target file."[..]
+    );
+}
+
+#[test]
+// Ensure a `--consistent-snapshot` change that can't be signed leaves the given root.json as is
+fn create_consistent_snapshot_sign_failure() {
+    let work_dir = TempDir::new().unwrap();
+    let root_json = work_dir.path().join("root.json");
+    std::fs::copy(
+        test_utils::test_data().join("simple-rsa").join("root.json"),
+        &root_json,
+    )
+    .unwrap();
+    let original_root = std::fs::read(&root_json).unwrap();
+    let targets_input_dir = test_utils::test_data()
+        .join("tuf-reference-impl")
+        .join("targets");
+    // This key isn't listed in root.json
+    let key = test_utils::test_data().join("snakeoil_2.pem");
+    let expires = Utc::now().checked_add_signed(Duration::days(7)).unwrap();
+    let repo_dir = TempDir::new().unwrap();
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "create",
+            "-t",
+            targets_input_dir.to_str().unwrap(),
+            "-o",
+            repo_dir.path().to_str().unwrap(),
+            "-k",
+            key.to_str().unwrap(),
+            "--root",
+            root_json.to_str().unwrap(),
+            "--targets-expires",
+            expires.to_rfc3339().as_str(),
+            "--targets-version",
+            "1",
+            "--snapshot-expires",
+            expires.to_rfc3339().as_str(),
+            "--snapshot-version",
+            "1",
+            "--timestamp-expires",
+            expires.to_rfc3339().as_str(),
+            "--timestamp-version",
+            "1",
+            "--consistent-snapshot",
+            "false",
+        ])
+        .assert()
+        .failure();
+
+    assert_eq!(std::fs::read(&root_json).unwrap(), original_root);
+}
+
+#[test]
+// Ensure the targets metadata doesn't depend on how many threads hash the targets
+fn create_deterministic_across_jobs() {
+    let targets_input_dir = TempDir::new().unwrap();
+    for dir in ["a", "b", "c"] {
+        let dir_path = targets_input_dir.path().join(dir);
+        std::fs::create_dir(&dir_path).unwrap();
+        for i in 0..16 {
+            std::fs::write(
+                dir_path.join(format!("{dir}-{i}.txt")),
+                format!("target {dir} {i}\n").repeat(i + 1),
+            )
+            .unwrap();
+        }
+    }
+    let root_json = test_utils::test_data().join("simple-rsa").join("root.json");
+    let root_key = test_utils::test_data().join("snakeoil.pem");
+    let expires = "2030-01-01T00:00:00Z";
+    let create = |jobs: &str| {
+        let repo_dir = TempDir::new().unwrap();
+        Command::cargo_bin("tuftool")
+            .unwrap()
+            .args([
+                "create",
+                "-t",
+                targets_input_dir.path().to_str().unwrap(),
+                "-o",
+                repo_dir.path().to_str().unwrap(),
+                "-k",
+                root_key.to_str().unwrap(),
+                "--root",
+                root_json.to_str().unwrap(),
+                "--targets-expires",
+                expires,
+                "--targets-version",
+                "1",
+                "--snapshot-expires",
+                expires,
+                "--snapshot-version",
+                "1",
+                "--timestamp-expires",
+                expires,
+                "--timestamp-version",
+                "1",
+                "--jobs",
+                jobs,
+            ])
+            .assert()
+            .success();
+        // RSA-PSS signatures are randomized, so only compare the signed contents
+        let targets = std::fs::read(repo_dir.path().join("metadata").join("1.targets.json"));
+        let targets: serde_json::Value = serde_json::from_slice(&targets.unwrap()).unwrap();
+        targets["signed"].to_string()
+    };
+
+    let targets_json = create("1");
+    assert_eq!(create("8"), targets_json);
+    assert_eq!(create("8"), targets_json);
+}
@@ -6,9 +6,16 @@ mod test_utils;
 use assert_cmd::Command;
 use chrono::{Duration, Utc};
 use std::fs::File;
+use std::num::NonZeroU64;
 use std::path::Path;
 use tempfile::TempDir;
 use test_utils::dir_url;
+use tough::editor::targets::TargetsEditor;
+use tough::editor::RepositoryEditor;
+use tough::key_source::{KeySource, LocalKeySource};
+use tough::schema::{
+    DelegatedRole, Delegations, KeyHolder, PathPattern, PathSet, Signed, Target, Targets,
+};
 use tough::{RepositoryLoader, TargetName};
 
 fn create_repo<P: AsRef<Path>>(repo_dir: P) {
@@ -319,6 +326,8 @@ fn update_target_command() {
             expiration.to_rfc3339().as_str(),
             "--delegated-role",
             "A",
+            "-p",
+            "file?.txt",
             "-t",
             "1",
             "-v",
@@ -1371,6 +1380,7 @@ fn remove_role_recursive_command() {
             "-v",
             "325",
             "--recursive",
+            "--yes",
         ])
         .assert()
         .success();
@@ -1433,18 +1443,108 @@ fn remove_role_recursive_command() {
 }
 
 #[test]
-/// Ensure we that we percent encode path traversal characters when adding a role name such as
-/// `../../strange/role/../name` and that we don't write files in unexpected places.
+/// Ensure role names with path traversal characters, such as `../../strange/role/../name`, are
+/// rejected by `create-role` and `add-role` and that nothing is written for them.
 fn dubious_role_name() {
     let dubious_role_name = "../../strange/role/../name";
-    let dubious_name_encoded = "..%2F..%2Fstrange%2Frole%2F..%2Fname";
     let funny_role_name = "../🍺/( ͡° ͜ʖ ͡°)";
-    let funny_name_encoded =
-        "..%2F%F0%9F%8D%BA%2F%28%20%CD%A1%C2%B0%20%CD%9C%CA%96%20%CD%A1%C2%B0%29";
     let root_json = test_utils::test_data().join("simple-rsa").join("root.json");
     let root_key = test_utils::test_data().join("snakeoil.pem");
     let targets_key = test_utils::test_data().join("targetskey");
-    let targets_key1 = test_utils::test_data().join("targetskey-1");
+    let repo_dir = TempDir::new().unwrap();
+
+    // Create a repo using tuftool and the reference tuf implementation data
+    create_repo(repo_dir.path());
+
+    // Set new expiration date for the new role
+    let expiration = Utc::now().checked_add_signed(Duration::days(4)).unwrap();
+    let metadata_base_url = &dir_url(repo_dir.path().join("metadata"));
+
+    for role_name in [dubious_role_name, funny_role_name] {
+        // create-role refuses the name
+        let meta_out = TempDir::new().unwrap();
+        Command::cargo_bin("tuftool")
+            .unwrap()
+            .args([
+                "delegation",
+                "--signing-role",
+                role_name,
+                "create-role",
+                "-o",
+                meta_out.path().to_str().unwrap(),
+                "-k",
+                targets_key.to_str().unwrap(),
+                "-e",
+                expiration.to_rfc3339().as_str(),
+                "-v",
+                "1",
+            ])
+            .assert()
+            .failure();
+        assert!(!meta_out.path().join("metadata").exists());
+
+        // add-role refuses the name before looking for its metadata
+        let new_repo_dir = TempDir::new().unwrap();
+        Command::cargo_bin("tuftool")
+            .unwrap()
+            .args([
+                "delegation",
+                "--signing-role",
+                "targets",
+                "add-role",
+                "-o",
+                new_repo_dir.path().to_str().unwrap(),
+                "-i",
+                metadata_base_url.as_str(),
+                "-k",
+                root_key.to_str().unwrap(),
+                "--root",
+                root_json.to_str().unwrap(),
+                "--metadata-url",
+                metadata_base_url.as_str(),
+                "-e",
+                expiration.to_rfc3339().as_str(),
+                "--delegated-role",
+                role_name,
+                "-t",
+                "1",
+                "-v",
+                "2",
+            ])
+            .assert()
+            .failure();
+        assert!(!new_repo_dir.path().join("metadata").exists());
+    }
+}
+
+/// Writes a `targets.json` holding the given test data targets, as if produced by another
+/// repository.
+fn write_external_targets(path: &Path, names: &[&str]) {
+    let mut targets = Targets::new(
+        "1.0.0".to_string(),
+        NonZeroU64::new(1).unwrap(),
+        Utc::now().checked_add_signed(Duration::days(4)).unwrap(),
+    );
+    for name in names {
+        targets.targets.insert(
+            TargetName::new(*name).unwrap(),
+            Target::from_path(test_utils::test_data().join("targets").join(name)).unwrap(),
+        );
+    }
+    let signed = Signed {
+        signed: targets,
+        signatures: Vec::new(),
+    };
+    std::fs::write(path, serde_json::to_vec_pretty(&signed).unwrap()).unwrap();
+}
+
+#[test]
+// Ensure we can import an external targets.json into a delegated role, and that targets outside
+// of the role's delegated paths are rejected
+fn import_targets_command() {
+    let root_json = test_utils::test_data().join("simple-rsa").join("root.json");
+    let root_key = test_utils::test_data().join("snakeoil.pem");
+    let targets_key = test_utils::test_data().join("targetskey");
     let repo_dir = TempDir::new().unwrap();
 
     // Set new expiration dates and version numbers for the update command
@@ -1452,8 +1552,6 @@ fn dubious_role_name() {
     let new_timestamp_version: u64 = 310;
     let new_snapshot_expiration = Utc::now().checked_add_signed(Duration::days(5)).unwrap();
     let new_snapshot_version: u64 = 250;
-    let new_targets_expiration = Utc::now().checked_add_signed(Duration::days(6)).unwrap();
-    let new_targets_version: u64 = 170;
 
     // Create a repo using tuftool and the reference tuf implementation data
     create_repo(repo_dir.path());
@@ -1469,7 +1567,7 @@ fn dubious_role_name() {
         .args([
             "delegation",
             "--signing-role",
-            dubious_role_name,
+            "A",
             "create-role",
             "-o",
             meta_out.path().to_str().unwrap(),
@@ -1484,7 +1582,7 @@ fn dubious_role_name() {
         .success();
 
     let new_repo_dir = TempDir::new().unwrap();
-    // add role to targets metadata and sign entire repo
+    // add role to targets metadata, delegating only file4.txt and file5.txt, and sign entire repo
     Command::cargo_bin("tuftool")
         .unwrap()
         .args([
@@ -1505,7 +1603,11 @@ fn dubious_role_name() {
             "-e",
             expiration.to_rfc3339().as_str(),
             "--delegated-role",
-            dubious_role_name,
+            "A",
+            "-p",
+            "file4.txt",
+            "-p",
+            "file5.txt",
             "-t",
             "1",
             "-v",
@@ -1523,88 +1625,70 @@ fn dubious_role_name() {
         .assert()
         .success();
 
-    // Load the updated repo
+    let external_dir = TempDir::new().unwrap();
+    let valid_targets = external_dir.path().join("valid.targets.json");
+    write_external_targets(&valid_targets, &["file4.txt", "file5.txt"]);
+    let invalid_targets = external_dir.path().join("invalid.targets.json");
+    write_external_targets(&invalid_targets, &["file4.txt", "file6.txt"]);
     let updated_metadata_base_url = &dir_url(new_repo_dir.path().join("metadata"));
-    let updated_targets_base_url = &dir_url(new_repo_dir.path().join("targets"));
-    let repo = RepositoryLoader::new(
-        File::open(&root_json).unwrap(),
-        updated_metadata_base_url.clone(),
-        updated_targets_base_url.clone(),
-    )
-    .load()
-    .unwrap();
-    // Make sure `A` is added as a role
-    assert!(repo.delegated_role(dubious_role_name).is_some());
 
-    let create_out = TempDir::new().unwrap();
-    // create role B
+    // Importing a target that isn't delegated to A fails
+    let import_out = TempDir::new().unwrap();
     Command::cargo_bin("tuftool")
         .unwrap()
         .args([
             "delegation",
             "--signing-role",
-            funny_role_name,
-            "create-role",
+            "A",
+            "import",
             "-o",
-            create_out.path().to_str().unwrap(),
+            import_out.path().to_str().unwrap(),
             "-k",
-            targets_key1.to_str().unwrap(),
+            targets_key.to_str().unwrap(),
+            "--root",
+            root_json.to_str().unwrap(),
+            "--metadata-url",
+            updated_metadata_base_url.as_str(),
+            "--targets-file",
+            invalid_targets.to_str().unwrap(),
             "-e",
-            expiration.to_rfc3339().as_str(),
+            "in 5 days",
             "-v",
-            "1",
+            "2",
         ])
         .assert()
-        .success();
+        .failure();
 
-    let add_b_out = TempDir::new().unwrap();
-    // add role B to A metadata and sign A meta
+    // Import A's targets
     Command::cargo_bin("tuftool")
         .unwrap()
         .args([
             "delegation",
             "--signing-role",
-            dubious_role_name,
-            "add-role",
+            "A",
+            "import",
             "-o",
-            add_b_out.path().to_str().unwrap(),
-            "-i",
-            dir_url(create_out.path().join("metadata")).as_str(),
+            import_out.path().to_str().unwrap(),
             "-k",
             targets_key.to_str().unwrap(),
             "--root",
             root_json.to_str().unwrap(),
             "--metadata-url",
             updated_metadata_base_url.as_str(),
+            "--targets-file",
+            valid_targets.to_str().unwrap(),
             "-e",
-            expiration.to_rfc3339().as_str(),
-            "--delegated-role",
-            funny_role_name,
-            "-t",
-            "1",
+            "in 5 days",
             "-v",
             "2",
         ])
         .assert()
         .success();
 
-    // Make sure the metadata files are in the right directory
-    assert!(add_b_out
-        .path()
-        .join("metadata")
-        .join(format!("{}.json", dubious_name_encoded))
-        .is_file());
-    assert!(add_b_out
-        .path()
-        .join("metadata")
-        .join(format!("{}.json", funny_name_encoded))
-        .is_file());
-
     // update repo with new metadata
-
+    let new_targets_expiration = Utc::now().checked_add_signed(Duration::days(6)).unwrap();
+    let new_targets_version: u64 = 170;
     let update_out = TempDir::new().unwrap();
-
-    // Update the repo we just created
     Command::cargo_bin("tuftool")
         .unwrap()
         .args([
@@ -1630,14 +1714,14 @@ fn dubious_role_name() {
             "--timestamp-version",
             format!("{}", new_timestamp_version).as_str(),
             "--role",
-            dubious_role_name,
+            "A",
             "-i",
-            dir_url(add_b_out.path().join("metadata")).as_str(),
+            dir_url(import_out.path().join("metadata")).as_str(),
         ])
         .assert()
         .success();
 
-    // Load the updated repo
+    // Load the updated repo and make sure A now signs for the imported targets
     let repo = RepositoryLoader::new(
         File::open(root_json).unwrap(),
         dir_url(update_out.path().join("metadata")),
@@ -1645,19 +1729,884 @@ fn dubious_role_name() {
     )
     .load()
     .unwrap();
+    let a_targets = &repo
+        .delegated_role("A")
+        .unwrap()
+        .targets
+        .as_ref()
+        .unwrap()
+        .signed
+        .targets;
+    assert_eq!(a_targets.len(), 2);
+    assert!(a_targets.contains_key(&TargetName::new("file4.txt").unwrap()));
+    assert!(a_targets.contains_key(&TargetName::new("file5.txt").unwrap()));
+}
 
-    // Make sure `B` is added as a role
-    assert!(repo.delegated_role(funny_role_name).is_some());
-
-    // Make sure the metadata files are in the right directory
-    assert!(update_out
-        .path()
-        .join("metadata")
-        .join(format!("{}.{}.json", 2, dubious_name_encoded))
-        .is_file());
-    assert!(update_out
-        .path()
-        .join("metadata")
-        .join(format!("{}.{}.json", 1, funny_name_encoded))
-        .is_file());
+#[test]
+// Ensure a new role can be written to stdout instead of an output directory
+fn create_role_stdout() {
+    let targets_key = test_utils::test_data().join("targetskey");
+    let expiration = Utc::now().checked_add_signed(Duration::days(4)).unwrap();
+    let working_dir = TempDir::new().unwrap();
+
+    let output = Command::cargo_bin("tuftool")
+        .unwrap()
+        .current_dir(working_dir.path())
+        .args([
+            "delegation",
+            "--signing-role",
+            "A",
+            "create-role",
+            "-o",
+            "-",
+            "-k",
+            targets_key.to_str().unwrap(),
+            "-e",
+            expiration.to_rfc3339().as_str(),
+            "-v",
+            "3",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    // The signed role was written to stdout and nothing was written to disk
+    let role: Signed<Targets> = serde_json::from_slice(&output).unwrap();
+    assert_eq!(role.signed.version, NonZeroU64::new(3).unwrap());
+    assert_eq!(role.signatures.len(), 1);
+    assert!(std::fs::read_dir(working_dir.path())
+        .unwrap()
+        .next()
+        .is_none());
+}
+
+#[test]
+// Ensure we can change the paths delegated to a role, and that targets then resolve to the role
+// through the new paths only
+fn set_paths_command() {
+    let root_json = test_utils::test_data().join("simple-rsa").join("root.json");
+    let root_key = test_utils::test_data().join("snakeoil.pem");
+    let targets_key = test_utils::test_data().join("targetskey");
+    let repo_dir = TempDir::new().unwrap();
+    create_repo(repo_dir.path());
+    let expiration = Utc::now().checked_add_signed(Duration::days(4)).unwrap();
+    let metadata_base_url = &dir_url(repo_dir.path().join("metadata"));
+
+    // create role A and delegate file1.txt to it
+    let meta_out = TempDir::new().unwrap();
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "delegation",
+            "--signing-role",
+            "A",
+            "create-role",
+            "-o",
+            meta_out.path().to_str().unwrap(),
+            "-k",
+            targets_key.to_str().unwrap(),
+            "-e",
+            expiration.to_rfc3339().as_str(),
+            "-v",
+            "1",
+        ])
+        .assert()
+        .success();
+    let new_repo_dir = TempDir::new().unwrap();
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "delegation",
+            "--signing-role",
+            "targets",
+            "add-role",
+            "-o",
+            new_repo_dir.path().to_str().unwrap(),
+            "-i",
+            dir_url(meta_out.path().join("metadata")).as_str(),
+            "-k",
+            root_key.to_str().unwrap(),
+            "--root",
+            root_json.to_str().unwrap(),
+            "--metadata-url",
+            metadata_base_url.as_str(),
+            "-e",
+            expiration.to_rfc3339().as_str(),
+            "--delegated-role",
+            "A",
+            "-p",
+            "file1.txt",
+            "-t",
+            "1",
+            "-v",
+            "2",
+            "--sign-all",
+            "--snapshot-expires",
+            expiration.to_rfc3339().as_str(),
+            "--snapshot-version",
+            "2",
+            "--timestamp-expires",
+            expiration.to_rfc3339().as_str(),
+            "--timestamp-version",
+            "2",
+        ])
+        .assert()
+        .success();
+
+    // Delegate file2.txt and subdirectories to A instead
+    let output = Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "delegation",
+            "--signing-role",
+            "targets",
+            "set-paths",
+            "-o",
+            "-",
+            "-k",
+            root_key.to_str().unwrap(),
+            "--root",
+            root_json.to_str().unwrap(),
+            "--metadata-url",
+            dir_url(new_repo_dir.path().join("metadata")).as_str(),
+            "-e",
+            expiration.to_rfc3339().as_str(),
+            "--delegated-role",
+            "A",
+            "-p",
+            "file2.txt",
+            "-p",
+            "sub/*",
+            "-v",
+            "3",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let targets: Signed<Targets> = serde_json::from_slice(&output).unwrap();
+    assert_eq!(targets.signed.version, NonZeroU64::new(3).unwrap());
+    let delegations = targets.signed.delegations.unwrap();
+    for (name, delegated) in [
+        ("file1.txt", false),
+        ("file2.txt", true),
+        ("sub/file3.txt", true),
+    ] {
+        assert_eq!(
+            delegations.target_is_delegated(&TargetName::new(name).unwrap()),
+            delegated,
+            "{name}"
+        );
+    }
+
+    // Paths and path hash prefixes can't both be set
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "delegation",
+            "--signing-role",
+            "targets",
+            "set-paths",
+            "-o",
+            "-",
+            "-k",
+            root_key.to_str().unwrap(),
+            "--root",
+            root_json.to_str().unwrap(),
+            "--metadata-url",
+            dir_url(new_repo_dir.path().join("metadata")).as_str(),
+            "-e",
+            expiration.to_rfc3339().as_str(),
+            "--delegated-role",
+            "A",
+            "-p",
+            "file2.txt",
+            "-x",
+            "ab",
+            "-v",
+            "3",
+        ])
+        .assert()
+        .failure();
+}
+
+/// Creates role `A` signed by `signing_keys`, then delegates it from `targets` to all three test
+/// keys with a threshold of `threshold`.
+fn delegate_threshold_role(
+    repo_dir: &Path,
+    signing_keys: &[&Path],
+    threshold: u64,
+) -> assert_cmd::assert::Assert {
+    let root_json = test_utils::test_data().join("simple-rsa").join("root.json");
+    let root_key = test_utils::test_data().join("snakeoil.pem");
+    let delegatee_keys = [
+        test_utils::test_data().join("targetskey"),
+        test_utils::test_data().join("targetskey-1"),
+        test_utils::test_data().join("snakeoil_2.pem"),
+    ];
+    let expiration = Utc::now().checked_add_signed(Duration::days(4)).unwrap();
+    create_repo(repo_dir);
+
+    // create role A, signed by only some of its keys
+    let meta_out = TempDir::new().unwrap();
+    let mut create_role = Command::cargo_bin("tuftool").unwrap();
+    create_role.args([
+        "delegation",
+        "--signing-role",
+        "A",
+        "create-role",
+        "-o",
+        meta_out.path().to_str().unwrap(),
+        "-e",
+        expiration.to_rfc3339().as_str(),
+        "-v",
+        "1",
+    ]);
+    for key in signing_keys {
+        create_role.args(["-k", key.to_str().unwrap()]);
+    }
+    create_role.assert().success();
+
+    // delegate A to all three keys
+    let mut add_role = Command::cargo_bin("tuftool").unwrap();
+    add_role.args([
+        "delegation",
+        "--signing-role",
+        "targets",
+        "add-role",
+        "-o",
+        repo_dir.join("out").to_str().unwrap(),
+        "-i",
+        dir_url(meta_out.path().join("metadata")).as_str(),
+        "-k",
+        root_key.to_str().unwrap(),
+        "--root",
+        root_json.to_str().unwrap(),
+        "--metadata-url",
+        dir_url(repo_dir.join("metadata")).as_str(),
+        "-e",
+        expiration.to_rfc3339().as_str(),
+        "--delegated-role",
+        "A",
+        "-t",
+        format!("{}", threshold).as_str(),
+        "-v",
+        "2",
+        "--sign-all",
+        "--snapshot-expires",
+        expiration.to_rfc3339().as_str(),
+        "--snapshot-version",
+        "2",
+        "--timestamp-expires",
+        expiration.to_rfc3339().as_str(),
+        "--timestamp-version",
+        "2",
+    ]);
+    for key in &delegatee_keys {
+        add_role.args(["--delegatee-key", key.to_str().unwrap()]);
+    }
+    add_role.assert()
+}
+
+fn load_threshold_repo(repo_dir: &Path) -> Result<tough::Repository, tough::error::Error> {
+    let root_json = test_utils::test_data().join("simple-rsa").join("root.json");
+    RepositoryLoader::new(
+        File::open(root_json).unwrap(),
+        dir_url(repo_dir.join("out").join("metadata")),
+        dir_url(repo_dir.join("out").join("targets")),
+    )
+    .load()
+}
+
+#[test]
+// Ensure a role can be delegated to several keys with a threshold above one, and that loading
+// enforces the threshold
+fn add_role_threshold() {
+    let targets_key = test_utils::test_data().join("targetskey");
+    let targets_key1 = test_utils::test_data().join("targetskey-1");
+
+    // A 2-of-3 role signed by two of its keys loads
+    let repo_dir = TempDir::new().unwrap();
+    delegate_threshold_role(repo_dir.path(), &[&targets_key, &targets_key1], 2).success();
+    let repo = load_threshold_repo(repo_dir.path()).unwrap();
+    let delegated = repo.delegated_role("A").unwrap();
+    assert_eq!(delegated.keyids.len(), 3);
+    assert_eq!(delegated.threshold, NonZeroU64::new(2).unwrap());
+
+    // A 2-of-3 role signed by one of its keys does not
+    let repo_dir = TempDir::new().unwrap();
+    delegate_threshold_role(repo_dir.path(), &[&targets_key], 2).success();
+    assert!(load_threshold_repo(repo_dir.path()).is_err());
+
+    // A threshold that the delegatee keys can never meet is rejected
+    let repo_dir = TempDir::new().unwrap();
+    delegate_threshold_role(repo_dir.path(), &[&targets_key, &targets_key1], 4).failure();
+}
+
+fn remove_role_recursive(repo_dir: &Path, outdir: &Path) -> Command {
+    let root_json = test_utils::test_data().join("simple-rsa").join("root.json");
+    let root_key = test_utils::test_data().join("snakeoil.pem");
+    let mut cmd = Command::cargo_bin("tuftool").unwrap();
+    cmd.args([
+        "delegation",
+        "--signing-role",
+        "targets",
+        "remove",
+        "-o",
+        outdir.to_str().unwrap(),
+        "-k",
+        root_key.to_str().unwrap(),
+        "--root",
+        root_json.to_str().unwrap(),
+        "--metadata-url",
+        dir_url(repo_dir.join("out").join("metadata")).as_str(),
+        "--delegated-role",
+        "A",
+        "-e",
+        "in 4 days",
+        "-v",
+        "3",
+        "--recursive",
+    ]);
+    cmd
+}
+
+#[test]
+// Ensure a recursive removal lists the roles it would remove and is aborted when it can't be
+// confirmed, unless `--yes` is passed
+fn remove_role_recursive_confirmation() {
+    let targets_key = test_utils::test_data().join("targetskey");
+    let targets_key1 = test_utils::test_data().join("targetskey-1");
+    let repo_dir = TempDir::new().unwrap();
+    delegate_threshold_role(repo_dir.path(), &[&targets_key, &targets_key1], 2).success();
+
+    // stdin isn't a terminal, so nobody can confirm
+    let abort_out = TempDir::new().unwrap();
+    let output = remove_role_recursive(repo_dir.path(), abort_out.path())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("The following roles will be removed:\n  A\n"),
+        "unexpected stderr: {}",
+        stderr
+    );
+    assert!(!abort_out.path().join("metadata").exists());
+
+    let yes_out = TempDir::new().unwrap();
+    remove_role_recursive(repo_dir.path(), yes_out.path())
+        .arg("--yes")
+        .assert()
+        .success();
+    let targets: Signed<Targets> = serde_json::from_reader(
+        File::open(yes_out.path().join("metadata").join("targets.json")).unwrap(),
+    )
+    .unwrap();
+    assert!(targets.signed.delegations.unwrap().roles.is_empty());
+}
+
+/// Creates role `name`, signed by `targetskey`, and delegates it from `targets`, reading the
+/// repository at `metadata_url` and writing the updated repository to `outdir`.
+fn create_and_delegate_role(metadata_url: &url::Url, outdir: &Path, name: &str, version: u64) {
+    let root_json = test_utils::test_data().join("simple-rsa").join("root.json");
+    let root_key = test_utils::test_data().join("snakeoil.pem");
+    let targets_key = test_utils::test_data().join("targetskey");
+    let expiration = Utc::now().checked_add_signed(Duration::days(4)).unwrap();
+    let version = format!("{}", version);
+
+    let meta_out = TempDir::new().unwrap();
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "delegation",
+            "--signing-role",
+            name,
+            "create-role",
+            "-o",
+            meta_out.path().to_str().unwrap(),
+            "-k",
+            targets_key.to_str().unwrap(),
+            "-e",
+            expiration.to_rfc3339().as_str(),
+            "-v",
+            "1",
+        ])
+        .assert()
+        .success();
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "delegation",
+            "--signing-role",
+            "targets",
+            "add-role",
+            "-o",
+            outdir.to_str().unwrap(),
+            "-i",
+            dir_url(meta_out.path().join("metadata")).as_str(),
+            "-k",
+            root_key.to_str().unwrap(),
+            "--root",
+            root_json.to_str().unwrap(),
+            "--metadata-url",
+            metadata_url.as_str(),
+            "-e",
+            expiration.to_rfc3339().as_str(),
+            "--delegated-role",
+            name,
+            "-t",
+            "1",
+            "-v",
+            version.as_str(),
+            "--sign-all",
+            "--snapshot-expires",
+            expiration.to_rfc3339().as_str(),
+            "--snapshot-version",
+            version.as_str(),
+            "--timestamp-expires",
+            expiration.to_rfc3339().as_str(),
+            "--timestamp-version",
+            version.as_str(),
+        ])
+        .assert()
+        .success();
+}
+
+#[test]
+// Ensure one key can be added to several delegated roles in one invocation, and that an unknown
+// role fails the whole invocation
+fn add_key_to_several_roles() {
+    let root_json = test_utils::test_data().join("simple-rsa").join("root.json");
+    let root_key = test_utils::test_data().join("snakeoil.pem");
+    let new_key = test_utils::test_data().join("snakeoil_2.pem");
+    let expiration = Utc::now().checked_add_signed(Duration::days(4)).unwrap();
+
+    // targets delegates A, B, and C
+    let repo_dir = TempDir::new().unwrap();
+    create_repo(repo_dir.path().join("0"));
+    for (version, name) in ["A", "B", "C"].iter().enumerate() {
+        create_and_delegate_role(
+            &dir_url(repo_dir.path().join(version.to_string()).join("metadata")),
+            &repo_dir.path().join((version + 1).to_string()),
+            name,
+            version as u64 + 2,
+        );
+    }
+    let metadata_url = dir_url(repo_dir.path().join("3").join("metadata"));
+
+    let add_key = |roles: &[&str], outdir: &Path| {
+        let mut command = Command::cargo_bin("tuftool").unwrap();
+        command.args([
+            "delegation",
+            "--signing-role",
+            "targets",
+            "add-key",
+            "-o",
+            outdir.to_str().unwrap(),
+            "--new-key",
+            new_key.to_str().unwrap(),
+            "-k",
+            root_key.to_str().unwrap(),
+            "--root",
+            root_json.to_str().unwrap(),
+            "--metadata-url",
+            metadata_url.as_str(),
+            "-e",
+            expiration.to_rfc3339().as_str(),
+            "-v",
+            "5",
+        ]);
+        for role in roles {
+            command.args(["--delegated-role", role]);
+        }
+        command.assert()
+    };
+
+    // An unknown role fails before anything is written
+    let key_out = TempDir::new().unwrap();
+    add_key(&["A", "D"], key_out.path()).failure();
+    assert!(!key_out.path().join("metadata").exists());
+
+    let key_out = TempDir::new().unwrap();
+    let output = add_key(&["A", "B", "C"], key_out.path()).success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    for role in ["A", "B", "C"] {
+        assert!(stdout.contains(&format!("to role '{}'", role)));
+    }
+
+    let targets: Signed<Targets> = serde_json::from_reader(
+        File::open(key_out.path().join("metadata").join("targets.json")).unwrap(),
+    )
+    .unwrap();
+    let delegations = targets.signed.delegations.unwrap();
+    let new_key_ids = delegations
+        .roles
+        .iter()
+        .map(|role| {
+            assert_eq!(role.keyids.len(), 2, "role {}", role.name);
+            role.keyids[1].clone()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(new_key_ids.len(), 3);
+    assert!(new_key_ids.iter().all(|key_id| *key_id == new_key_ids[0]));
+    assert!(delegations.keys.contains_key(&new_key_ids[0]));
+}
+
+#[test]
+// Ensure commands that sign a single role write it to stdout for `--outdir -`, and commands that
+// write more than that role reject it rather than creating a directory named '-'
+fn single_role_commands_stdout() {
+    let root_json = test_utils::test_data().join("simple-rsa").join("root.json");
+    let root_key = test_utils::test_data().join("snakeoil.pem");
+    let targets_key = test_utils::test_data().join("targetskey");
+    let expiration = Utc::now().checked_add_signed(Duration::days(4)).unwrap();
+    let repo_dir = TempDir::new().unwrap();
+    create_repo(repo_dir.path().join("0"));
+    create_and_delegate_role(
+        &dir_url(repo_dir.path().join("0").join("metadata")),
+        &repo_dir.path().join("1"),
+        "A",
+        2,
+    );
+    let metadata_url = dir_url(repo_dir.path().join("1").join("metadata"));
+    let working_dir = TempDir::new().unwrap();
+
+    let output = Command::cargo_bin("tuftool")
+        .unwrap()
+        .current_dir(working_dir.path())
+        .args([
+            "delegation",
+            "--signing-role",
+            "A",
+            "update-targets",
+            "-o",
+            "-",
+            "-k",
+            targets_key.to_str().unwrap(),
+            "--root",
+            root_json.to_str().unwrap(),
+            "--metadata-url",
+            metadata_url.as_str(),
+            "-e",
+            expiration.to_rfc3339().as_str(),
+            "-v",
+            "2",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let role: Signed<Targets> = serde_json::from_slice(&output).unwrap();
+    assert_eq!(role.signed.version, NonZeroU64::new(2).unwrap());
+
+    // Targets have to be copied somewhere, and adding keys may sign several roles
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .current_dir(working_dir.path())
+        .args([
+            "delegation",
+            "--signing-role",
+            "A",
+            "update-targets",
+            "-o",
+            "-",
+            "-t",
+            test_utils::test_data().join("targets").to_str().unwrap(),
+            "-k",
+            targets_key.to_str().unwrap(),
+            "--root",
+            root_json.to_str().unwrap(),
+            "--metadata-url",
+            metadata_url.as_str(),
+            "-e",
+            expiration.to_rfc3339().as_str(),
+            "-v",
+            "2",
+        ])
+        .assert()
+        .failure();
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .current_dir(working_dir.path())
+        .args([
+            "delegation",
+            "--signing-role",
+            "targets",
+            "add-key",
+            "-o",
+            "-",
+            "--new-key",
+            targets_key.to_str().unwrap(),
+            "-k",
+            root_key.to_str().unwrap(),
+            "--root",
+            root_json.to_str().unwrap(),
+            "--metadata-url",
+            metadata_url.as_str(),
+            "-e",
+            expiration.to_rfc3339().as_str(),
+            "-v",
+            "3",
+        ])
+        .assert()
+        .failure();
+    assert!(std::fs::read_dir(working_dir.path())
+        .unwrap()
+        .next()
+        .is_none());
+}
+
+#[test]
+// Ensure adding a key to roles with different delegators signs each delegator again
+fn add_key_to_roles_of_several_delegators() {
+    let root_json = test_utils::test_data().join("simple-rsa").join("root.json");
+    let root_key = test_utils::test_data().join("snakeoil.pem");
+    let targets_key = test_utils::test_data().join("targetskey");
+    let new_key = test_utils::test_data().join("snakeoil_2.pem");
+    let expiration = Utc::now().checked_add_signed(Duration::days(4)).unwrap();
+    let one = NonZeroU64::new(1).unwrap();
+    let root_keys: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource {
+        path: root_key.clone(),
+    })];
+    let role_keys: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource {
+        path: targets_key.clone(),
+    })];
+    let paths = |pattern: &str| PathSet::Paths(vec![PathPattern::new(pattern).unwrap()]);
+
+    // targets delegates A and B, and A delegates C
+    let repo_dir = TempDir::new().unwrap();
+    let mut editor = RepositoryEditor::new(&root_json).unwrap();
+    editor
+        .snapshot_version(one)
+        .snapshot_expires(expiration)
+        .timestamp_version(one)
+        .timestamp_expires(expiration)
+        .targets_version(one)
+        .unwrap()
+        .targets_expires(expiration)
+        .unwrap()
+        .delegate_role("A", role_keys, paths("a/*"), one, expiration, one)
+        .unwrap()
+        .delegate_role("B", role_keys, paths("b/*"), one, expiration, one)
+        .unwrap()
+        .sign_targets_editor(root_keys)
+        .unwrap()
+        .change_delegated_targets("A")
+        .unwrap()
+        .delegate_role("C", role_keys, paths("a/c/*"), one, expiration, one)
+        .unwrap()
+        .sign_targets_editor(role_keys)
+        .unwrap()
+        .change_delegated_targets("targets")
+        .unwrap();
+    editor
+        .sign(root_keys)
+        .unwrap()
+        .write(repo_dir.path().join("metadata"))
+        .unwrap();
+
+    let key_out = TempDir::new().unwrap();
+    let output = Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "delegation",
+            "--signing-role",
+            "targets",
+            "add-key",
+            "-o",
+            key_out.path().to_str().unwrap(),
+            "--new-key",
+            new_key.to_str().unwrap(),
+            "-k",
+            root_key.to_str().unwrap(),
+            "-k",
+            targets_key.to_str().unwrap(),
+            "--root",
+            root_json.to_str().unwrap(),
+            "--metadata-url",
+            dir_url(repo_dir.path().join("metadata")).as_str(),
+            "-e",
+            expiration.to_rfc3339().as_str(),
+            "-v",
+            "2",
+            "--delegated-role",
+            "B",
+            "--delegated-role",
+            "C",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Signed role 'targets'"));
+    assert!(stdout.contains("Signed role 'A'"));
+
+    // Each delegator lists the new key for its own role, and only for that role
+    let new_key_id = LocalKeySource { path: new_key }
+        .as_sign()
+        .unwrap()
+        .tuf_key()
+        .key_id()
+        .unwrap();
+    for (delegator, role, other) in [("targets", "B", "A"), ("A", "C", "")] {
+        let targets: Signed<Targets> = serde_json::from_reader(
+            File::open(
+                key_out
+                    .path()
+                    .join("metadata")
+                    .join(format!("{delegator}.json")),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(targets.signed.version.get(), 2);
+        let delegations = targets.signed.delegations.unwrap();
+        assert!(delegations.keys.contains_key(&new_key_id));
+        for delegated_role in &delegations.roles {
+            let listed = delegated_role.keyids.contains(&new_key_id);
+            assert_eq!(
+                listed,
+                delegated_role.name == role,
+                "{}",
+                delegated_role.name
+            );
+            assert!(delegated_role.name == role || delegated_role.name == other);
+        }
+    }
+}
+
+#[test]
+// Ensure a scaffolded role, once its owners add targets and keys and sign it, can be added to its
+// parent
+fn scaffold_command() {
+    let root_json = test_utils::test_data().join("simple-rsa").join("root.json");
+    let root_key = test_utils::test_data().join("snakeoil.pem");
+    let targets_key = test_utils::test_data().join("targetskey");
+    let repo_dir = TempDir::new().unwrap();
+    create_repo(repo_dir.path());
+    let expiration = Utc::now().checked_add_signed(Duration::days(4)).unwrap();
+
+    let scaffold_out = TempDir::new().unwrap();
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "delegation",
+            "--signing-role",
+            "targets",
+            "scaffold",
+            "--role",
+            "A",
+            "-p",
+            "file1.txt",
+            "-o",
+            scaffold_out.path().to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+    let skeleton: Signed<Targets> = serde_json::from_reader(
+        File::open(scaffold_out.path().join("metadata").join("A.json")).unwrap(),
+    )
+    .unwrap();
+    assert!(skeleton.signatures.is_empty());
+    assert!(skeleton.signed.targets.is_empty());
+
+    // The role's owners list their key, add their target, and sign
+    let keys: Vec<Box<dyn KeySource>> = vec![Box::new(LocalKeySource {
+        path: targets_key.clone(),
+    })];
+    let key = keys[0].as_sign().unwrap().tuf_key();
+    let keyid = key.key_id().unwrap();
+    let mut targets = skeleton.signed;
+    let delegations = targets.delegations.as_mut().unwrap();
+    delegations.keys.insert(keyid.clone(), key);
+    let key_holder = KeyHolder::Delegations(Delegations {
+        keys: delegations.keys.clone(),
+        roles: vec![DelegatedRole {
+            name: "A".to_string(),
+            keyids: vec![keyid],
+            threshold: NonZeroU64::new(1).unwrap(),
+            paths: PathSet::Paths(vec![PathPattern::new("file1.txt").unwrap()]),
+            terminating: false,
+            targets: None,
+        }],
+    });
+    let signed_out = TempDir::new().unwrap();
+    TargetsEditor::from_targets("A", targets, key_holder)
+        .add_target_path(
+            test_utils::test_data()
+                .join("tuf-reference-impl")
+                .join("targets")
+                .join("file1.txt"),
+        )
+        .unwrap()
+        .version(NonZeroU64::new(1).unwrap())
+        .expires(expiration)
+        .sign(&keys)
+        .unwrap()
+        .write(signed_out.path().join("metadata"), false)
+        .unwrap();
+
+    // The parent adds the signed role
+    let new_repo_dir = TempDir::new().unwrap();
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "delegation",
+            "--signing-role",
+            "targets",
+            "add-role",
+            "-o",
+            new_repo_dir.path().to_str().unwrap(),
+            "-i",
+            dir_url(signed_out.path().join("metadata")).as_str(),
+            "-k",
+            root_key.to_str().unwrap(),
+            "--root",
+            root_json.to_str().unwrap(),
+            "--metadata-url",
+            dir_url(repo_dir.path().join("metadata")).as_str(),
+            "-e",
+            expiration.to_rfc3339().as_str(),
+            "--delegated-role",
+            "A",
+            "-p",
+            "file1.txt",
+            "-t",
+            "1",
+            "-v",
+            "2",
+            "--sign-all",
+            "--snapshot-expires",
+            expiration.to_rfc3339().as_str(),
+            "--snapshot-version",
+            "2",
+            "--timestamp-expires",
+            expiration.to_rfc3339().as_str(),
+            "--timestamp-version",
+            "2",
+        ])
+        .assert()
+        .success();
+
+    let repo = RepositoryLoader::new(
+        File::open(&root_json).unwrap(),
+        dir_url(new_repo_dir.path().join("metadata")),
+        dir_url(new_repo_dir.path().join("targets")),
+    )
+    .load()
+    .unwrap();
+    let role = repo.delegated_role("A").unwrap();
+    assert!(role
+        .targets
+        .as_ref()
+        .unwrap()
+        .signed
+        .targets
+        .contains_key(&TargetName::new("file1.txt").unwrap()));
 }
@@ -72,6 +72,8 @@ fn download_command(metadata_base_url: Url, targets_base_url: Url) {
     // Assert the files are exactly correct
     assert_file_match(&outdir, "file1.txt");
     assert_file_match(&outdir, "file2.txt");
+    // file3.txt is listed by the delegated role `role1`
+    assert_file_match(&outdir, "file3.txt");
 
     // Download again into the same outdir, this will fail because the directory exists.
     Command::cargo_bin("tuftool")
@@ -102,12 +104,66 @@ fn download_http_transport() {
     server.expect(create_successful_get("metadata/timestamp.json"));
     server.expect(create_successful_get("targets/file1.txt"));
     server.expect(create_successful_get("targets/file2.txt"));
+    server.expect(create_successful_get("targets/file3.txt"));
     server.expect(create_unsuccessful_get("metadata/2.root.json"));
     let metadata_base_url = Url::from_str(server.url_str("/metadata").as_str()).unwrap();
     let targets_base_url = Url::from_str(server.url_str("/targets").as_str()).unwrap();
     download_command(metadata_base_url, targets_base_url);
 }
 
+#[test]
+// Ensure that when metadata and targets are hosted apart, metadata is only fetched from the
+// metadata URL and targets only from the targets URL.
+fn download_split_hosts() {
+    let metadata_server = Server::run();
+    metadata_server.expect(create_successful_get("metadata/role1.json"));
+    metadata_server.expect(create_successful_get("metadata/role2.json"));
+    metadata_server.expect(create_successful_get("metadata/snapshot.json"));
+    metadata_server.expect(create_successful_get("metadata/targets.json"));
+    metadata_server.expect(create_successful_get("metadata/timestamp.json"));
+    metadata_server.expect(create_unsuccessful_get("metadata/2.root.json"));
+    let targets_server = Server::run();
+    targets_server.expect(create_successful_get("targets/file1.txt"));
+    targets_server.expect(create_successful_get("targets/file2.txt"));
+    targets_server.expect(create_successful_get("targets/file3.txt"));
+    let metadata_base_url = Url::from_str(metadata_server.url_str("/metadata").as_str()).unwrap();
+    let targets_base_url = Url::from_str(targets_server.url_str("/targets").as_str()).unwrap();
+    download_command(metadata_base_url, targets_base_url);
+}
+
+#[test]
+// Ensure that a targets URL that can't be used as a base URL is rejected.
+fn download_invalid_targets_url() {
+    let repo_dir = test_utils::test_data().join("tuf-reference-impl");
+    let tempdir = TempDir::new().unwrap();
+    for targets_url in [
+        "mailto:targets@example.com",
+        "https://example.com/targets?v=1",
+    ] {
+        let output = Command::cargo_bin("tuftool")
+            .unwrap()
+            .args([
+                "download",
+                "-r",
+                repo_dir
+                    .join("metadata")
+                    .join("root.json")
+                    .to_str()
+                    .unwrap(),
+                "--metadata-url",
+                test_utils::dir_url(repo_dir.join("metadata")).as_str(),
+                "--targets-url",
+                targets_url,
+                tempdir.path().join("outdir").to_str().unwrap(),
+            ])
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("as a base URL"), "{stderr}");
+    }
+}
+
 #[test]
 // Ensure that the download command works with file transport, and that we require outdir to
 // not-exist.
@@ -190,3 +246,83 @@ fn download_safe_target_paths() {
     assert!(outdir.join("data1.txt").is_file());
     assert!(outdir.join("foo/bar/data2.txt").is_file())
 }
+
+#[test]
+// Ensure `--nested` refuses target names that would need resolving, and mirrors the others
+fn download_nested_target_paths() {
+    let download = |repo_dir: &Path, outdir: &Path| {
+        let mut cmd = Command::cargo_bin("tuftool").unwrap();
+        cmd.args([
+            "download",
+            "-r",
+            repo_dir
+                .join("metadata")
+                .join("1.root.json")
+                .to_str()
+                .unwrap(),
+            "--metadata-url",
+            test_utils::dir_url(repo_dir.join("metadata")).as_str(),
+            "--targets-url",
+            test_utils::dir_url(repo_dir.join("targets")).as_str(),
+            "--nested",
+            outdir.to_str().unwrap(),
+        ]);
+        cmd.output().unwrap()
+    };
+    let tempdir = TempDir::new().unwrap();
+
+    // Each of these target names has a '..' component
+    let unsafe_out = tempdir.path().join("unsafe");
+    let output = download(
+        &test_utils::test_data().join("safe-target-paths"),
+        &unsafe_out,
+    );
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Refusing to mirror target"), "{}", stderr);
+    assert!(!tempdir.path().join("delegated").exists());
+
+    let outdir = tempdir.path().join("outdir");
+    let output = download(&test_utils::test_data().join("tuf-reference-impl"), &outdir);
+    assert!(output.status.success());
+    assert_file_match(&outdir, "file1.txt");
+    assert_file_match(&outdir, "file2.txt");
+}
+
+#[test]
+// Ensure the download command fails unless the repository is at the pinned versions
+fn download_pinned_versions() {
+    let repo_dir = test_utils::test_data().join("tuf-reference-impl");
+    let root = repo_dir.join("metadata").join("1.root.json");
+    let metadata_base_url = test_utils::dir_url(repo_dir.join("metadata"));
+    let targets_base_url = test_utils::dir_url(repo_dir.join("targets"));
+    let download = |outdir: &Path, snapshot: &str, timestamp: &str| {
+        Command::cargo_bin("tuftool")
+            .unwrap()
+            .args([
+                "download",
+                "-r",
+                root.to_str().unwrap(),
+                "--metadata-url",
+                metadata_base_url.as_str(),
+                "--targets-url",
+                targets_base_url.as_str(),
+                "--pin-snapshot-version",
+                snapshot,
+                "--pin-timestamp-version",
+                timestamp,
+                outdir.to_str().unwrap(),
+            ])
+            .assert()
+    };
+    let tempdir = TempDir::new().unwrap();
+
+    // The repository is at snapshot version 1 and timestamp version 1
+    download(&tempdir.path().join("timestamp"), "1", "2").failure();
+    download(&tempdir.path().join("snapshot"), "2", "1").failure();
+    assert!(!tempdir.path().join("snapshot").exists());
+
+    let outdir = tempdir.path().join("pinned");
+    download(&outdir, "1", "1").success();
+    assert_file_match(&outdir, "file1.txt");
+}
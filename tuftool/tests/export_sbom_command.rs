@@ -0,0 +1,86 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+mod test_utils;
+
+use assert_cmd::Command;
+use serde_json::{json, Value};
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::{dir_url, test_data, RepoBuilder};
+use tough::schema::Target;
+
+/// Signs a repository listing file1.txt, with a version in its custom metadata, and file2.txt,
+/// without one.
+fn create_repo(metadata_dir: &Path) {
+    let targets_dir = test_data().join("tuf-reference-impl").join("targets");
+    let mut file1 = Target::from_path(targets_dir.join("file1.txt")).unwrap();
+    file1.custom.insert("version".to_owned(), json!("1.2.3"));
+
+    RepoBuilder::new()
+        .target("file1.txt", file1)
+        .target_path(targets_dir.join("file2.txt"))
+        .write(metadata_dir);
+}
+
+#[test]
+// Ensure export-sbom lists each target as a CycloneDX component with its version and hashes
+fn export_sbom_cyclonedx() {
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_dir = repo_dir.path().join("metadata");
+    create_repo(&metadata_dir);
+
+    let output = Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "export-sbom",
+            "--root",
+            test_data()
+                .join("simple-rsa")
+                .join("root.json")
+                .to_str()
+                .unwrap(),
+            "--metadata-url",
+            dir_url(&metadata_dir).as_str(),
+            "--format",
+            "cyclonedx",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let document: Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    let sha256 = |name: &str| {
+        let target = Target::from_path(
+            test_data()
+                .join("tuf-reference-impl")
+                .join("targets")
+                .join(name),
+        )
+        .unwrap();
+        hex::encode(target.hashes.sha256)
+    };
+    assert_eq!(
+        document,
+        json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.5",
+            "version": 1,
+            "components": [
+                {
+                    "type": "file",
+                    "bom-ref": "file1.txt",
+                    "name": "file1.txt",
+                    "version": "1.2.3",
+                    "hashes": [{"alg": "SHA-256", "content": sha256("file1.txt")}],
+                },
+                {
+                    "type": "file",
+                    "bom-ref": "file2.txt",
+                    "name": "file2.txt",
+                    "hashes": [{"alg": "SHA-256", "content": sha256("file2.txt")}],
+                },
+            ],
+        })
+    );
+}
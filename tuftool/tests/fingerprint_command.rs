@@ -0,0 +1,76 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+mod test_utils;
+
+use assert_cmd::Command;
+use chrono::{DateTime, Duration, Utc};
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::{dir_url, RepoBuilder};
+
+/// Creates a repo whose roles all expire at `expires` and are at `version`.
+fn create_repo(repo_dir: &Path, expires: DateTime<Utc>, version: u64) {
+    RepoBuilder::new()
+        .expires(expires)
+        .version(version)
+        .write(repo_dir.join("metadata"));
+}
+
+fn fingerprint_command(repo_dir: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("tuftool").unwrap();
+    cmd.args([
+        "fingerprint",
+        "--root",
+        test_utils::test_data()
+            .join("simple-rsa")
+            .join("root.json")
+            .to_str()
+            .unwrap(),
+        "--metadata-url",
+        dir_url(repo_dir.join("metadata")).as_str(),
+    ]);
+    cmd
+}
+
+fn fingerprint(repo_dir: &Path) -> String {
+    let output = fingerprint_command(repo_dir).output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap().trim().to_owned()
+}
+
+#[test]
+// Ensure repos with the same metadata have the same fingerprint, even though their signatures
+// differ, and that a change to the metadata changes the fingerprint
+fn fingerprint_identical_and_changed_repos() {
+    let expires = Utc::now().checked_add_signed(Duration::days(7)).unwrap();
+    let repo_a = TempDir::new().unwrap();
+    let repo_b = TempDir::new().unwrap();
+    let repo_c = TempDir::new().unwrap();
+    create_repo(repo_a.path(), expires, 1);
+    create_repo(repo_b.path(), expires, 1);
+    create_repo(repo_c.path(), expires, 2);
+
+    let fingerprint_a = fingerprint(repo_a.path());
+    assert_eq!(fingerprint_a.len(), 64);
+    assert_eq!(fingerprint_a, fingerprint(repo_b.path()));
+    assert_ne!(fingerprint_a, fingerprint(repo_c.path()));
+}
+
+#[test]
+// Ensure `--expect` fails only when the fingerprint doesn't match
+fn fingerprint_expect() {
+    let expires = Utc::now().checked_add_signed(Duration::days(7)).unwrap();
+    let repo_dir = TempDir::new().unwrap();
+    create_repo(repo_dir.path(), expires, 1);
+    let expected = fingerprint(repo_dir.path());
+
+    fingerprint_command(repo_dir.path())
+        .args(["--expect", &expected])
+        .assert()
+        .success();
+    fingerprint_command(repo_dir.path())
+        .args(["--expect", &"0".repeat(64)])
+        .assert()
+        .failure();
+}
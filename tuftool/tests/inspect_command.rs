@@ -0,0 +1,72 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+mod test_utils;
+
+use assert_cmd::Command;
+use test_utils::dir_url;
+
+#[test]
+// Ensure `--raw` writes each role's metadata byte for byte as the repository served it
+fn inspect_raw_matches_served_bytes() {
+    let metadata_dir = test_utils::test_data()
+        .join("tuf-reference-impl")
+        .join("metadata");
+    let root_json = metadata_dir.join("1.root.json");
+
+    for role in ["timestamp", "snapshot", "targets"] {
+        let output = Command::cargo_bin("tuftool")
+            .unwrap()
+            .args([
+                "inspect",
+                "--root",
+                root_json.to_str().unwrap(),
+                "--metadata-url",
+                dir_url(&metadata_dir).as_str(),
+                "--role",
+                role,
+                "--raw",
+            ])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let served = std::fs::read(metadata_dir.join(format!("{role}.json"))).unwrap();
+        assert_eq!(output.stdout, served);
+    }
+}
+
+#[test]
+// Ensure the scheme of each key trusted for the role is shown on stderr, leaving stdout to the
+// metadata
+fn inspect_shows_key_schemes() {
+    let metadata_dir = test_utils::test_data()
+        .join("tuf-reference-impl")
+        .join("metadata");
+    let root_json = metadata_dir.join("1.root.json");
+
+    let output = Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "inspect",
+            "--root",
+            root_json.to_str().unwrap(),
+            "--metadata-url",
+            dir_url(&metadata_dir).as_str(),
+            "--role",
+            "timestamp",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let lines: Vec<_> = stderr
+        .lines()
+        .filter(|line| line.starts_with("Role '"))
+        .collect();
+    assert!(!lines.is_empty());
+    for line in lines {
+        assert!(line.starts_with("Role 'timestamp' key "), "{}", line);
+        assert!(line.ends_with(" uses scheme ed25519"), "{}", line);
+    }
+    serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap();
+}
@@ -0,0 +1,113 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+mod test_utils;
+
+use assert_cmd::Command;
+use chrono::{Duration, Utc};
+use std::fs::{self, File};
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::{dir_url, test_data, RepoBuilder};
+use tough::RepositoryLoader;
+
+fn resign(metadata_dir: &Path) -> String {
+    let output = Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "resign",
+            "-k",
+            test_data().join("snakeoil.pem").to_str().unwrap(),
+            "--root",
+            test_data()
+                .join("simple-rsa")
+                .join("root.json")
+                .to_str()
+                .unwrap(),
+            "--metadata-dir",
+            metadata_dir.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+// Ensure only the role whose signature no longer verifies is re-signed
+fn resign_tampered_role() {
+    let metadata_dir = TempDir::new().unwrap();
+    let dir = metadata_dir.path();
+    RepoBuilder::new().write(dir);
+
+    // Edit timestamp.json by hand, which leaves its signature stale
+    let timestamp_path = dir.join("timestamp.json");
+    let mut timestamp: serde_json::Value =
+        serde_json::from_reader(File::open(&timestamp_path).unwrap()).unwrap();
+    let expires = Utc::now().checked_add_signed(Duration::days(3)).unwrap();
+    timestamp["signed"]["expires"] = serde_json::Value::from(expires.to_rfc3339());
+    fs::write(
+        &timestamp_path,
+        serde_json::to_vec_pretty(&timestamp).unwrap(),
+    )
+    .unwrap();
+
+    let snapshot = fs::read(dir.join("1.snapshot.json")).unwrap();
+    let targets = fs::read(dir.join("1.targets.json")).unwrap();
+
+    assert_eq!(resign(dir), "Re-signed role 'timestamp'\n");
+    assert_eq!(fs::read(dir.join("1.snapshot.json")).unwrap(), snapshot);
+    assert_eq!(fs::read(dir.join("1.targets.json")).unwrap(), targets);
+
+    let repo = RepositoryLoader::new(
+        File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        dir_url(dir),
+        dir_url(dir),
+    )
+    .load()
+    .unwrap();
+    assert_eq!(
+        repo.timestamp().signed.expires.timestamp(),
+        expires.timestamp()
+    );
+
+    // Every role is valid now, so nothing more is re-signed
+    assert_eq!(
+        resign(dir),
+        "All roles have valid signatures; nothing was re-signed\n"
+    );
+}
+
+#[test]
+// Ensure re-signing targets.json updates and re-signs the snapshot and timestamp that record it,
+// so the repository still loads
+fn resign_tampered_targets() {
+    let metadata_dir = TempDir::new().unwrap();
+    let dir = metadata_dir.path();
+    RepoBuilder::new().write(dir);
+
+    // Edit targets.json by hand, which leaves its signature stale
+    let targets_path = dir.join("1.targets.json");
+    let mut targets: serde_json::Value =
+        serde_json::from_reader(File::open(&targets_path).unwrap()).unwrap();
+    let expires = Utc::now().checked_add_signed(Duration::days(3)).unwrap();
+    targets["signed"]["expires"] = serde_json::Value::from(expires.to_rfc3339());
+    fs::write(&targets_path, serde_json::to_vec_pretty(&targets).unwrap()).unwrap();
+
+    assert_eq!(
+        resign(dir),
+        "Re-signed role 'targets'\nRe-signed role 'snapshot'\nRe-signed role 'timestamp'\n"
+    );
+
+    let repo = RepositoryLoader::new(
+        File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        dir_url(dir),
+        dir_url(dir),
+    )
+    .load()
+    .unwrap();
+    assert_eq!(
+        repo.targets().signed.expires.timestamp(),
+        expires.timestamp()
+    );
+}
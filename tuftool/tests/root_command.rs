@@ -436,3 +436,672 @@ fn set_version_root() {
     // validate version number
     assert_eq!(get_version(root_json.to_str().unwrap()), version);
 }
+
+#[test]
+fn thresholds_root() {
+    let root_json = test_utils::test_data()
+        .join("tuf-reference-impl")
+        .join("metadata")
+        .join("1.root.json");
+    let root: Signed<Root> = serde_json::from_reader(File::open(&root_json).unwrap()).unwrap();
+
+    let output = Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "root",
+            "thresholds",
+            "--root",
+            root_json.to_str().unwrap(),
+            "--format",
+            "json",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let thresholds: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    // Every role matches the root's own configuration
+    for (role, keys) in &root.signed.roles {
+        let key_ids = keys.keyids.iter().map(hex::encode).collect::<Vec<_>>();
+        assert_eq!(
+            thresholds["roles"][role.to_string()],
+            serde_json::json!({
+                "threshold": keys.threshold.get(),
+                "total_keys": key_ids.len(),
+                "key_ids": key_ids,
+            })
+        );
+    }
+    // Each role has its own key, so signing every role takes all four
+    assert_eq!(thresholds["minimum_signers"]["count"], 4);
+
+    // A root that uses one key for every role needs only that key
+    let output = Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "root",
+            "thresholds",
+            "--root",
+            test_utils::test_data()
+                .join("simple-rsa")
+                .join("root.json")
+                .to_str()
+                .unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("root: threshold 1 of 1 keys"));
+    assert!(stdout.contains(
+        "Minimum signers: 1\n  8ec3a843a0f9328c863cac4046ab1cacbbc67888476ac7acf73d9bcd9a223ada\n"
+    ));
+}
+
+#[test]
+// Ensure `--json-logs` writes each log record as a line of JSON, and `--quiet` drops warnings
+fn json_logs_and_quiet() {
+    let out_dir = TempDir::new().unwrap();
+    let root_json = out_dir.path().join("root.json");
+    let key = test_utils::test_data().join("snakeoil.pem");
+    initialize_root_json(root_json.to_str().unwrap());
+    add_key_root(&vec![key.to_str().unwrap()], root_json.to_str().unwrap());
+
+    // Signing below the threshold with `-i` logs warnings
+    let output = Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "--json-logs",
+            "root",
+            "sign",
+            root_json.to_str().unwrap(),
+            "-i",
+            "-k",
+            key.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let lines = stderr.lines().collect::<Vec<_>>();
+    assert!(!lines.is_empty());
+    for line in lines {
+        let record: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert_eq!(record["level"], "WARN");
+        assert!(record["target"].as_str().unwrap().starts_with("tuftool"));
+        assert!(record["message"].is_string());
+    }
+
+    let output = Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "--quiet",
+            "--json-logs",
+            "root",
+            "sign",
+            root_json.to_str().unwrap(),
+            "-i",
+            "-k",
+            key.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+// Ensure `replace-key` swaps a key in every role that lists it and bumps the version
+fn replace_key_root() {
+    let out_dir = TempDir::new().unwrap();
+    let root_json = out_dir.path().join("root.json");
+    let key_1 = test_utils::test_data().join("snakeoil.pem");
+    let key_2 = test_utils::test_data().join("snakeoil_2.pem");
+    let targets_key = test_utils::test_data().join("targetskey");
+    initialize_root_json(root_json.to_str().unwrap());
+    add_key_root(
+        &vec![key_1.to_str().unwrap(), key_2.to_str().unwrap()],
+        root_json.to_str().unwrap(),
+    );
+    add_key_timestamp(key_2.to_str().unwrap(), root_json.to_str().unwrap());
+    add_key_snapshot(key_1.to_str().unwrap(), root_json.to_str().unwrap());
+    add_key_targets(key_1.to_str().unwrap(), root_json.to_str().unwrap());
+
+    let key_id = |path: &std::path::Path| {
+        LocalKeySource {
+            path: path.to_path_buf(),
+        }
+        .as_sign()
+        .unwrap()
+        .tuf_key()
+        .key_id()
+        .unwrap()
+    };
+    let old_key_id = key_id(&key_2);
+    let new_key_id = key_id(&targets_key);
+    let version = get_version(root_json.to_str().unwrap());
+
+    let output = Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "root",
+            "replace-key",
+            root_json.to_str().unwrap(),
+            &hex::encode(&old_key_id),
+            "-k",
+            targets_key.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("in roles: root, timestamp"));
+
+    let root = get_signed_root(root_json.to_str().unwrap()).signed;
+    assert_eq!(root.version.get(), version.get() + 1);
+    assert!(!root.keys.contains_key(&old_key_id));
+    assert!(root.keys.contains_key(&new_key_id));
+    for keys in root.roles.values() {
+        assert!(!keys.keyids.contains(&old_key_id));
+    }
+    let root_keys = &root.roles[&tough::schema::RoleType::Root];
+    assert_eq!(root_keys.keyids, vec![key_id(&key_1), new_key_id.clone()]);
+    assert_eq!(
+        root.roles[&tough::schema::RoleType::Timestamp].keyids,
+        vec![new_key_id]
+    );
+
+    // The old key is gone, so replacing it again fails
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "root",
+            "replace-key",
+            root_json.to_str().unwrap(),
+            &hex::encode(&old_key_id),
+            "-k",
+            key_1.to_str().unwrap(),
+        ])
+        .assert()
+        .failure();
+}
+
+/// Creates version 2 of the root in `tests/data/cross-sign-root`, rotating the root role to
+/// `snakeoil_2.pem` and `targetskey` with a threshold of 2, and signs it with the new keys.
+fn rotated_root_json(new_root_json: &str) {
+    let key_2 = test_utils::test_data().join("snakeoil_2.pem");
+    let key_3 = test_utils::test_data().join("targetskey");
+    initialize_root_json(new_root_json);
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .args(["root", "set-version", new_root_json, "2"])
+        .assert()
+        .success();
+    add_keys_all_roles(
+        vec![key_2.to_str().unwrap(), key_3.to_str().unwrap()],
+        new_root_json,
+    );
+    sign_root_json(key_2.to_str().unwrap(), new_root_json);
+    sign_root_json(key_3.to_str().unwrap(), new_root_json);
+}
+
+#[test]
+// Ensure a new root signed by both the old and new root keys is a valid transition
+fn verify_transition_valid() {
+    let out_dir = TempDir::new().unwrap();
+    let old_root_json = test_utils::test_data()
+        .join("cross-sign-root")
+        .join("1.root.json");
+    let new_root_json = out_dir.path().join("2.root.json");
+    rotated_root_json(new_root_json.to_str().unwrap());
+    cross_sign(
+        old_root_json.to_str().unwrap(),
+        new_root_json.to_str().unwrap(),
+        test_utils::test_data()
+            .join("snakeoil.pem")
+            .to_str()
+            .unwrap(),
+    );
+
+    let output = Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "root",
+            "verify-transition",
+            old_root_json.to_str().unwrap(),
+            new_root_json.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("old root (version 1): 1 of 1 required signatures, threshold met"));
+    assert!(stdout.contains("new root (version 2): 2 of 2 required signatures, threshold met"));
+}
+
+#[test]
+// Ensure a new root that isn't signed by the old root's keys is reported, with the missing key
+fn verify_transition_not_cross_signed() {
+    let out_dir = TempDir::new().unwrap();
+    let old_root_json = test_utils::test_data()
+        .join("cross-sign-root")
+        .join("1.root.json");
+    let new_root_json = out_dir.path().join("2.root.json");
+    rotated_root_json(new_root_json.to_str().unwrap());
+
+    let output = Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "root",
+            "verify-transition",
+            old_root_json.to_str().unwrap(),
+            new_root_json.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("old root (version 1): 0 of 1 required signatures, threshold not met"));
+    assert!(stdout.contains("69f069cf595e3f09cbe99a9f0f82127f7c7d2fde859e30fd5c0f2b4fc9c4a507"));
+    assert!(stdout.contains("new root (version 2): 2 of 2 required signatures, threshold met"));
+}
+
+/// Returns the key ID of the key in the file at `path`.
+fn key_id(path: &std::path::Path) -> Decoded<Hex> {
+    LocalKeySource {
+        path: path.to_path_buf(),
+    }
+    .as_sign()
+    .unwrap()
+    .tuf_key()
+    .key_id()
+    .unwrap()
+}
+
+fn verify_transition(old_root_json: &str, new_root_json: &str) -> bool {
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .args(["root", "verify-transition", old_root_json, new_root_json])
+        .output()
+        .unwrap()
+        .status
+        .success()
+}
+
+#[test]
+// Ensure `add-root-key` needs confirmation, bumps the version, and produces a root that must be
+// cross-signed by the old root role before the transition is valid
+fn add_root_key() {
+    let out_dir = TempDir::new().unwrap();
+    let old_root_json = test_utils::test_data()
+        .join("cross-sign-root")
+        .join("1.root.json");
+    let new_root_json = out_dir.path().join("2.root.json");
+    std::fs::copy(&old_root_json, &new_root_json).unwrap();
+    let key_1 = test_utils::test_data().join("snakeoil.pem");
+    let key_2 = test_utils::test_data().join("snakeoil_2.pem");
+    let add_root_key = |confirm: bool| {
+        let mut cmd = Command::cargo_bin("tuftool").unwrap();
+        cmd.args([
+            "root",
+            "add-root-key",
+            new_root_json.to_str().unwrap(),
+            "-k",
+            key_2.to_str().unwrap(),
+        ]);
+        if confirm {
+            cmd.arg("--confirm");
+        }
+        cmd.assert()
+    };
+
+    add_root_key(false).failure();
+    assert_eq!(get_version(new_root_json.to_str().unwrap()).get(), 1);
+
+    add_root_key(true).success();
+    let root = get_signed_root(new_root_json.to_str().unwrap());
+    assert_eq!(root.signed.version.get(), 2);
+    assert!(root.signatures.is_empty());
+    assert_eq!(
+        root.signed.roles[&tough::schema::RoleType::Root].keyids,
+        vec![key_id(&key_1), key_id(&key_2)]
+    );
+    // The key is only added to the root role
+    assert_eq!(
+        root.signed.roles[&tough::schema::RoleType::Targets].keyids,
+        vec![key_id(&key_1)]
+    );
+
+    // Adding the same key again fails
+    add_root_key(true).failure();
+
+    // Signed only by the new key, the old root role's threshold isn't met
+    sign_root_json(key_2.to_str().unwrap(), new_root_json.to_str().unwrap());
+    assert!(!verify_transition(
+        old_root_json.to_str().unwrap(),
+        new_root_json.to_str().unwrap()
+    ));
+    cross_sign(
+        old_root_json.to_str().unwrap(),
+        new_root_json.to_str().unwrap(),
+        key_1.to_str().unwrap(),
+    );
+    assert!(verify_transition(
+        old_root_json.to_str().unwrap(),
+        new_root_json.to_str().unwrap()
+    ));
+}
+
+#[test]
+// Ensure `remove-root-key` needs confirmation, bumps the version, and refuses to leave the root
+// role below its threshold
+fn remove_root_key() {
+    let out_dir = TempDir::new().unwrap();
+    let root_json = out_dir.path().join("root.json");
+    std::fs::copy(
+        test_utils::test_data()
+            .join("cross-sign-root")
+            .join("1.root.json"),
+        &root_json,
+    )
+    .unwrap();
+    let key_1 = test_utils::test_data().join("snakeoil.pem");
+    let key_2 = test_utils::test_data().join("snakeoil_2.pem");
+    let remove_root_key = |key_id: &Decoded<Hex>, confirm: bool| {
+        let mut cmd = Command::cargo_bin("tuftool").unwrap();
+        cmd.args([
+            "root",
+            "remove-root-key",
+            root_json.to_str().unwrap(),
+            &hex::encode(key_id),
+        ]);
+        if confirm {
+            cmd.arg("--confirm");
+        }
+        cmd.assert()
+    };
+
+    // Removing the only root key would leave root.json unsignable
+    remove_root_key(&key_id(&key_1), true).failure();
+
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "root",
+            "add-root-key",
+            root_json.to_str().unwrap(),
+            "-k",
+            key_2.to_str().unwrap(),
+            "--confirm",
+        ])
+        .assert()
+        .success();
+    remove_root_key(&key_id(&key_1), false).failure();
+    remove_root_key(&key_id(&key_1), true).success();
+
+    let root = get_signed_root(root_json.to_str().unwrap()).signed;
+    assert_eq!(root.version.get(), 3);
+    assert_eq!(
+        root.roles[&tough::schema::RoleType::Root].keyids,
+        vec![key_id(&key_2)]
+    );
+    // The other roles still list the removed key, so it stays in the file
+    assert!(root.keys.contains_key(&key_id(&key_1)));
+
+    // The key is no longer in the root role
+    remove_root_key(&key_id(&key_1), true).failure();
+}
+
+#[test]
+fn validate_root() {
+    let out_dir = TempDir::new().unwrap();
+    let root_json = out_dir.path().join("root.json");
+    let key = test_utils::test_data().join("snakeoil.pem");
+    let validate = || {
+        Command::cargo_bin("tuftool")
+            .unwrap()
+            .args(["root", "validate", root_json.to_str().unwrap()])
+            .output()
+            .unwrap()
+    };
+
+    // A freshly initialized root.json lists no keys
+    initialize_root_json(root_json.to_str().unwrap());
+    let output = validate();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    for role in ["root", "snapshot", "targets", "timestamp"] {
+        assert!(stdout.contains(&format!("Role '{role}' lists no key IDs")));
+    }
+
+    // One key can't meet the root role's threshold of 2
+    add_keys_all_roles(vec![key.to_str().unwrap()], root_json.to_str().unwrap());
+    let output = validate();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        stdout,
+        "Role 'root' has threshold 2 but only 1 usable keys\n"
+    );
+
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "root",
+            "set-threshold",
+            root_json.to_str().unwrap(),
+            "root",
+            "1",
+        ])
+        .assert()
+        .success();
+    let output = validate();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "root.json is valid\n"
+    );
+
+    // A repeated key is collapsed when parsing, but still reported
+    let json = std::fs::read_to_string(&root_json).unwrap();
+    std::fs::write(
+        &root_json,
+        json.replacen(
+            "\"consistent_snapshot\"",
+            "\"consistent_snapshot\": false, \"consistent_snapshot\"",
+            1,
+        ),
+    )
+    .unwrap();
+    let output = validate();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Duplicate key 'consistent_snapshot' in JSON object at '/signed'\n"
+    );
+}
+
+#[test]
+fn prune_unused_keys() {
+    let out_dir = TempDir::new().unwrap();
+    let root_json = out_dir.path().join("root.json");
+    let key_1 = test_utils::test_data().join("snakeoil.pem");
+    let key_2 = test_utils::test_data().join("snakeoil_2.pem");
+    let list_unused_keys = || {
+        let output = Command::cargo_bin("tuftool")
+            .unwrap()
+            .args([
+                "root",
+                "list-unused-keys",
+                "--root",
+                root_json.to_str().unwrap(),
+            ])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    initialize_root_json(root_json.to_str().unwrap());
+    add_keys_all_roles(
+        vec![key_1.to_str().unwrap(), key_2.to_str().unwrap()],
+        root_json.to_str().unwrap(),
+    );
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "root",
+            "set-threshold",
+            root_json.to_str().unwrap(),
+            "root",
+            "1",
+        ])
+        .assert()
+        .success();
+    assert_eq!(list_unused_keys(), "");
+
+    // Removing the key from its only role leaves it in the keys map
+    let unused = hex::encode(key_id(&key_2));
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "root",
+            "remove-key",
+            root_json.to_str().unwrap(),
+            &unused,
+            "root",
+        ])
+        .assert()
+        .success();
+    assert_eq!(list_unused_keys(), format!("{unused}\n"));
+
+    let version = get_version(root_json.to_str().unwrap());
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "root",
+            "prune-unused-keys",
+            "--root",
+            root_json.to_str().unwrap(),
+            "-k",
+            key_1.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+    assert_eq!(list_unused_keys(), "");
+    let root = get_signed_root(root_json.to_str().unwrap());
+    assert!(!root.signed.keys.contains_key(&key_id(&key_2)));
+    assert!(root.signed.keys.contains_key(&key_id(&key_1)));
+    assert_eq!(root.signed.version.get(), version.get() + 1);
+    assert!(check_signature_exists(
+        root_json.to_str().unwrap(),
+        key_id(&key_1)
+    ));
+}
+
+#[test]
+fn diff_root() {
+    let out_dir = TempDir::new().unwrap();
+    let old_root_json = out_dir.path().join("old.json");
+    let new_root_json = out_dir.path().join("new.json");
+    let key_1 = test_utils::test_data().join("snakeoil.pem");
+    let key_2 = test_utils::test_data().join("snakeoil_2.pem");
+    let diff = || {
+        let output = Command::cargo_bin("tuftool")
+            .unwrap()
+            .args([
+                "root",
+                "diff",
+                old_root_json.to_str().unwrap(),
+                new_root_json.to_str().unwrap(),
+            ])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    initialize_root_json(old_root_json.to_str().unwrap());
+    add_keys_all_roles(
+        vec![key_1.to_str().unwrap()],
+        old_root_json.to_str().unwrap(),
+    );
+    std::fs::copy(&old_root_json, &new_root_json).unwrap();
+    assert_eq!(diff(), "No changes\n");
+
+    // Rotate the snapshot key
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .args(["root", "bump-version", new_root_json.to_str().unwrap()])
+        .assert()
+        .success();
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "root",
+            "remove-key",
+            new_root_json.to_str().unwrap(),
+            &hex::encode(key_id(&key_1)),
+            "snapshot",
+        ])
+        .assert()
+        .success();
+    add_key_snapshot(key_2.to_str().unwrap(), new_root_json.to_str().unwrap());
+
+    assert_eq!(
+        diff(),
+        format!(
+            "version: 1 -> 2\nsnapshot: added key {}\nsnapshot: removed key {}\n",
+            hex::encode(key_id(&key_2)),
+            hex::encode(key_id(&key_1))
+        )
+    );
+}
+
+#[test]
+// Ensure `recover` writes the next version of the repository's latest root.json, signed with the
+// root's original key, and refuses keys that don't meet the root threshold
+fn recover_root() {
+    let out_dir = TempDir::new().unwrap();
+    let metadata_dir = out_dir.path().join("metadata");
+    std::fs::create_dir(&metadata_dir).unwrap();
+    let old_root_json = metadata_dir.join("1.root.json");
+    std::fs::copy(
+        test_utils::test_data().join("simple-rsa").join("root.json"),
+        &old_root_json,
+    )
+    .unwrap();
+    let new_root_json = out_dir.path().join("root.json");
+    let recover = |key: &str| {
+        Command::cargo_bin("tuftool")
+            .unwrap()
+            .args([
+                "root",
+                "recover",
+                "--from-repo",
+                test_utils::dir_url(&metadata_dir).as_str(),
+                "-k",
+                test_utils::test_data().join(key).to_str().unwrap(),
+                "-e",
+                "in 7 days",
+                "-o",
+                new_root_json.to_str().unwrap(),
+            ])
+            .output()
+            .unwrap()
+    };
+
+    assert!(!recover("snakeoil_2.pem").status.success());
+    assert!(!new_root_json.exists());
+
+    assert!(recover("snakeoil.pem").status.success());
+    let old = get_signed_root(old_root_json.to_str().unwrap());
+    let new = get_signed_root(new_root_json.to_str().unwrap());
+    assert_eq!(new.signed.version.get(), 2);
+    assert_eq!(new.signed.keys, old.signed.keys);
+    assert_eq!(new.signed.roles, old.signed.roles);
+    assert!(verify_transition(
+        old_root_json.to_str().unwrap(),
+        new_root_json.to_str().unwrap()
+    ));
+}
@@ -0,0 +1,123 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+mod test_utils;
+
+use assert_cmd::Command;
+use std::fs::File;
+use std::path::Path;
+use tempfile::TempDir;
+use tough::schema::{Root, Signed};
+
+fn tuftool(args: &[&str]) -> Command {
+    let mut cmd = Command::cargo_bin("tuftool").unwrap();
+    cmd.args(args);
+    cmd
+}
+
+/// Creates an unsigned root.json whose root role needs 2 of 3 keys.
+fn create_root(root_json: &str) {
+    tuftool(&["root", "init", root_json]).assert().success();
+    tuftool(&["root", "expire", root_json, "2030-09-22T00:00:00Z"])
+        .assert()
+        .success();
+    tuftool(&["root", "set-threshold", root_json, "root", "2"])
+        .assert()
+        .success();
+    for key in ["snakeoil.pem", "snakeoil_2.pem", "targetskey"] {
+        let key = test_utils::test_data().join(key);
+        tuftool(&[
+            "root",
+            "add-key",
+            root_json,
+            "-k",
+            key.to_str().unwrap(),
+            "--role",
+            "root",
+        ])
+        .assert()
+        .success();
+    }
+}
+
+/// Signs the prepared root with `key`, as one of the ceremony's signers would.
+fn sign(ceremony_dir: &Path, key: &str, signatures: &Path) {
+    let key = test_utils::test_data().join(key);
+    tuftool(&[
+        "sign-ceremony",
+        "sign",
+        ceremony_dir.join("root.to-sign").to_str().unwrap(),
+        "-k",
+        key.to_str().unwrap(),
+        "-o",
+        signatures.to_str().unwrap(),
+    ])
+    .assert()
+    .success();
+}
+
+fn finalize(ceremony_dir: &Path, signatures: &Path, outdir: &Path) -> std::process::Output {
+    tuftool(&[
+        "sign-ceremony",
+        "finalize",
+        "--ceremony-dir",
+        ceremony_dir.to_str().unwrap(),
+        "--signatures",
+        signatures.to_str().unwrap(),
+        "-o",
+        outdir.to_str().unwrap(),
+    ])
+    .output()
+    .unwrap()
+}
+
+#[test]
+// Ensure a 2-of-3 root can be signed by collecting detached signatures from separate signers, and
+// that threshold progress is reported until enough signatures are in
+fn sign_ceremony_two_of_three() {
+    let dir = TempDir::new().unwrap();
+    let root_json = dir.path().join("root.json");
+    let ceremony_dir = dir.path().join("ceremony");
+    let signatures = dir.path().join("signatures");
+    let outdir = dir.path().join("out");
+    create_root(root_json.to_str().unwrap());
+
+    let output = tuftool(&[
+        "sign-ceremony",
+        "prepare",
+        root_json.to_str().unwrap(),
+        "-o",
+        ceremony_dir.to_str().unwrap(),
+    ])
+    .assert()
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .starts_with("root: sha256 "));
+
+    // The first signer signs, and so does a key that the root doesn't trust
+    sign(&ceremony_dir, "snakeoil.pem", &signatures);
+    sign(&ceremony_dir, "targetskey-1", &signatures);
+    let output = finalize(&ceremony_dir, &signatures, &outdir);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("root: 1 of 2 signatures, threshold not met"));
+    assert!(!outdir.join("root.json").exists());
+
+    // The second signer signs
+    sign(&ceremony_dir, "snakeoil_2.pem", &signatures);
+    let output = finalize(&ceremony_dir, &signatures, &outdir);
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("root: 2 of 2 signatures, threshold met"));
+
+    let root: Signed<Root> =
+        serde_json::from_reader(File::open(outdir.join("root.json")).unwrap()).unwrap();
+    assert_eq!(root.signatures.len(), 2);
+    root.signed.verify_role(&root).unwrap();
+}
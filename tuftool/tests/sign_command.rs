@@ -0,0 +1,135 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+mod test_utils;
+
+use assert_cmd::Command;
+use chrono::{Duration, Utc};
+use std::fs::File;
+use std::num::NonZeroU64;
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::{dir_url, test_data};
+use tough::editor::RepositoryEditor;
+use tough::{RepositoryLoader, TargetName};
+
+/// Assembles a repository listing file1.txt without signing it, as a build step without access to
+/// the keys would.
+fn assemble_unsigned(metadata_dir: &Path) {
+    let expires = Utc::now().checked_add_signed(Duration::days(7)).unwrap();
+    let one = NonZeroU64::new(1).unwrap();
+    let mut editor =
+        RepositoryEditor::new(test_data().join("simple-rsa").join("root.json")).unwrap();
+    editor
+        .targets_version(one)
+        .unwrap()
+        .targets_expires(expires)
+        .unwrap()
+        .snapshot_version(one)
+        .snapshot_expires(expires)
+        .add_target_path(
+            test_data()
+                .join("tuf-reference-impl")
+                .join("targets")
+                .join("file1.txt"),
+        )
+        .unwrap();
+    editor.unsigned().unwrap().write(metadata_dir).unwrap();
+}
+
+fn sign_command(metadata_dir: &Path, key: &str) -> Command {
+    let mut cmd = Command::cargo_bin("tuftool").unwrap();
+    cmd.args([
+        "sign",
+        "-k",
+        test_data().join(key).to_str().unwrap(),
+        "--root",
+        test_data()
+            .join("simple-rsa")
+            .join("root.json")
+            .to_str()
+            .unwrap(),
+        "--metadata-dir",
+        metadata_dir.to_str().unwrap(),
+    ]);
+    cmd
+}
+
+#[test]
+// Ensure a repository assembled unsigned can be signed in a separate invocation, and then loads
+fn sign_unsigned_repo() {
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_dir = repo_dir.path().join("metadata");
+    assemble_unsigned(&metadata_dir);
+
+    // A key the roles don't trust adds no signatures
+    let output = sign_command(&metadata_dir, "snakeoil_2.pem")
+        .args([
+            "--timestamp-version",
+            "1",
+            "--timestamp-expires",
+            "in 7 days",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "targets: 0 of 1 signatures, threshold not met\n"
+    );
+
+    // timestamp.json doesn't exist yet, so its version and expiration are needed
+    sign_command(&metadata_dir, "snakeoil.pem")
+        .assert()
+        .failure();
+
+    let output = sign_command(&metadata_dir, "snakeoil.pem")
+        .args([
+            "--timestamp-version",
+            "1",
+            "--timestamp-expires",
+            "in 7 days",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "targets: 1 of 1 signatures, threshold met\n\
+         snapshot: 1 of 1 signatures, threshold met\n\
+         timestamp: 1 of 1 signatures, threshold met\n"
+    );
+
+    let repo = RepositoryLoader::new(
+        File::open(test_data().join("simple-rsa").join("root.json")).unwrap(),
+        dir_url(&metadata_dir),
+        dir_url(repo_dir.path().join("targets")),
+    )
+    .load()
+    .unwrap();
+    assert!(repo
+        .targets()
+        .signed
+        .targets
+        .contains_key(&TargetName::new("file1.txt").unwrap()));
+
+    // Signing again leaves the signed repository as it was
+    let timestamp = std::fs::read(metadata_dir.join("timestamp.json")).unwrap();
+    sign_command(&metadata_dir, "snakeoil.pem")
+        .assert()
+        .success();
+    assert_eq!(
+        std::fs::read(metadata_dir.join("timestamp.json")).unwrap(),
+        timestamp
+    );
+
+    // The snapshot timestamp.json lists is signed, not a newer one it doesn't list
+    std::fs::write(metadata_dir.join("2.snapshot.json"), "{}").unwrap();
+    sign_command(&metadata_dir, "snakeoil.pem")
+        .assert()
+        .success();
+    assert_eq!(
+        std::fs::read(metadata_dir.join("timestamp.json")).unwrap(),
+        timestamp
+    );
+}
@@ -0,0 +1,119 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+mod test_utils;
+
+use assert_cmd::Command;
+use chrono::{DateTime, Duration, Utc};
+use std::num::NonZeroU64;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use tough::editor::RepositoryEditor;
+use tough::key_source::{KeySource, LocalKeySource};
+
+/// Writes the metadata of a repository whose non-root roles are all at `version` to `outdir`, and
+/// returns the metadata directory.
+fn create_state(outdir: &Path, version: u64, expires: DateTime<Utc>) -> PathBuf {
+    let key: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource {
+        path: test_utils::test_data().join("snakeoil.pem"),
+    })];
+    let version = NonZeroU64::new(version).unwrap();
+
+    let mut editor =
+        RepositoryEditor::new(test_utils::test_data().join("simple-rsa").join("root.json"))
+            .unwrap();
+    editor
+        .snapshot_version(version)
+        .snapshot_expires(expires)
+        .timestamp_version(version)
+        .timestamp_expires(expires)
+        .targets_version(version)
+        .unwrap()
+        .targets_expires(expires)
+        .unwrap();
+    let metadata_dir = outdir.join("metadata");
+    editor.sign(key).unwrap().write(&metadata_dir).unwrap();
+    metadata_dir
+}
+
+fn simulate_update(from: &Path, to: &Path) -> Command {
+    let root_json = test_utils::test_data().join("simple-rsa").join("root.json");
+    let mut cmd = Command::cargo_bin("tuftool").unwrap();
+    cmd.args([
+        "simulate-update",
+        "--root",
+        root_json.to_str().unwrap(),
+        "--from",
+        from.to_str().unwrap(),
+        "--to",
+        to.to_str().unwrap(),
+    ]);
+    cmd
+}
+
+#[test]
+// Ensure moving to newer metadata is accepted
+fn simulate_good_update() {
+    let expires = Utc::now() + Duration::days(7);
+    let v1_dir = TempDir::new().unwrap();
+    let v1 = create_state(v1_dir.path(), 1, expires);
+    let v2_dir = TempDir::new().unwrap();
+    let v2 = create_state(v2_dir.path(), 2, expires);
+
+    let output = simulate_update(&v1, &v2).output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Update accepted\n"
+    );
+    simulate_update(&v1, &v2)
+        .args(["--expect", "accepted"])
+        .assert()
+        .success();
+    simulate_update(&v1, &v2)
+        .args(["--expect", "rollback"])
+        .assert()
+        .failure();
+}
+
+#[test]
+// Ensure moving back to older metadata is rejected as a rollback
+fn simulate_rollback_update() {
+    let expires = Utc::now() + Duration::days(7);
+    let v1_dir = TempDir::new().unwrap();
+    let v1 = create_state(v1_dir.path(), 1, expires);
+    let v2_dir = TempDir::new().unwrap();
+    let v2 = create_state(v2_dir.path(), 2, expires);
+
+    // A rejected update is still reported successfully unless another outcome is expected
+    let output = simulate_update(&v2, &v1).output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.starts_with("Update rejected (rollback): "),
+        "unexpected output: {}",
+        stdout
+    );
+    simulate_update(&v2, &v1)
+        .args(["--expect", "rollback"])
+        .assert()
+        .success();
+    simulate_update(&v2, &v1)
+        .args(["--expect", "accepted"])
+        .assert()
+        .failure();
+}
+
+#[test]
+// Ensure moving to newer but expired metadata is rejected as expired
+fn simulate_expired_update() {
+    let v1_dir = TempDir::new().unwrap();
+    let v1 = create_state(v1_dir.path(), 1, Utc::now() + Duration::days(7));
+    let v2_dir = TempDir::new().unwrap();
+    let v2 = create_state(v2_dir.path(), 2, Utc::now() - Duration::days(1));
+
+    simulate_update(&v1, &v2)
+        .args(["--expect", "expired"])
+        .assert()
+        .success();
+}
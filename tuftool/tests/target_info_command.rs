@@ -0,0 +1,138 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+mod test_utils;
+
+use assert_cmd::Command;
+use chrono::{Duration, Utc};
+use std::num::NonZeroU64;
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::dir_url;
+use tough::editor::RepositoryEditor;
+use tough::key_source::{KeySource, LocalKeySource};
+use tough::schema::{PathPattern, PathSet, Target};
+use tough::TargetName;
+
+/// Creates a repository in which the top-level targets role delegates overlapping paths to `role1`
+/// and `role2`, which both list `file1.txt`.
+fn create_overlapping_repo(metadata_dir: &Path) {
+    let root_key: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource {
+        path: test_utils::test_data().join("snakeoil.pem"),
+    })];
+    let role_key: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource {
+        path: test_utils::test_data().join("targetskey"),
+    })];
+    let expires = Utc::now().checked_add_signed(Duration::days(7)).unwrap();
+    let version = NonZeroU64::new(1).unwrap();
+    let file1 = TargetName::new("file1.txt").unwrap();
+    let target = Target::from_path(
+        test_utils::test_data()
+            .join("tuf-reference-impl")
+            .join("targets")
+            .join("file1.txt"),
+    )
+    .unwrap();
+
+    let mut editor =
+        RepositoryEditor::new(test_utils::test_data().join("simple-rsa").join("root.json"))
+            .unwrap();
+    editor
+        .snapshot_version(version)
+        .snapshot_expires(expires)
+        .timestamp_version(version)
+        .timestamp_expires(expires)
+        .targets_version(version)
+        .unwrap()
+        .targets_expires(expires)
+        .unwrap();
+    for (role, path) in [("role1", "*"), ("role2", "file*")] {
+        editor
+            .delegate_role(
+                role,
+                role_key,
+                PathSet::Paths(vec![PathPattern::new(path).unwrap()]),
+                version,
+                expires,
+                version,
+            )
+            .unwrap();
+    }
+    editor.sign_targets_editor(root_key).unwrap();
+    for role in ["role1", "role2"] {
+        editor
+            .change_delegated_targets(role)
+            .unwrap()
+            .add_target(file1.clone(), target.clone())
+            .unwrap()
+            .targets_version(version)
+            .unwrap()
+            .targets_expires(expires)
+            .unwrap()
+            .sign_targets_editor(role_key)
+            .unwrap();
+    }
+    editor
+        .change_delegated_targets("targets")
+        .unwrap()
+        .targets_version(version)
+        .unwrap()
+        .targets_expires(expires)
+        .unwrap();
+    editor.sign(root_key).unwrap().write(metadata_dir).unwrap();
+}
+
+fn target_info(metadata_dir: &Path, target: &str) -> std::process::Output {
+    let root_json = test_utils::test_data().join("simple-rsa").join("root.json");
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "target-info",
+            "--root",
+            root_json.to_str().unwrap(),
+            "--metadata-url",
+            dir_url(metadata_dir).as_str(),
+            target,
+        ])
+        .output()
+        .unwrap()
+}
+
+#[test]
+// Ensure target-info shows every role consulted for a target, and that the first one listing it is
+// trusted
+fn target_info_overlapping_roles() {
+    let repo_dir = TempDir::new().unwrap();
+    let metadata_dir = repo_dir.path().join("metadata");
+    create_overlapping_repo(&metadata_dir);
+
+    let output = target_info(&metadata_dir, "file1.txt");
+    assert!(output.status.success());
+    let target = Target::from_path(
+        test_utils::test_data()
+            .join("tuf-reference-impl")
+            .join("targets")
+            .join("file1.txt"),
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        format!(
+            "Resolution order for 'file1.txt':\n  \
+             targets: not listed\n    \
+             role1: listed (trusted)\n    \
+             role2: listed\n\
+             Length: 31\n\
+             SHA-256: {}\n",
+            hex::encode(target.hashes.sha256)
+        )
+    );
+
+    // No role lists the target, so it isn't found after showing the roles consulted
+    let output = target_info(&metadata_dir, "other.txt");
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Resolution order for 'other.txt':\n  targets: not listed\n    role1: not listed\n"
+    );
+}
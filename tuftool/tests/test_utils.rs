@@ -2,9 +2,15 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use assert_cmd::Command;
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use std::io::Read;
+use std::num::NonZeroU64;
 use std::path::{Path, PathBuf};
+use tough::editor::signed::SignedRepository;
+use tough::editor::RepositoryEditor;
+use tough::key_source::{KeySource, LocalKeySource};
+use tough::schema::{PathSet, Target};
+use tough::TargetName;
 use url::Url;
 
 /// Utilities for tests. Not every test module uses every function, so we suppress unused warnings.
@@ -74,3 +80,115 @@ pub fn create_expired_repo<P: AsRef<Path>>(repo_dir: P) {
         .assert()
         .success();
 }
+
+/// Returns the key that signs the top-level roles of the simple-rsa root
+#[allow(unused)]
+pub fn root_key() -> Vec<Box<dyn KeySource>> {
+    vec![Box::new(LocalKeySource {
+        path: test_data().join("snakeoil.pem"),
+    })]
+}
+
+/// Returns the key that signs the roles delegated by a `RepoBuilder`
+#[allow(unused)]
+pub fn role_key() -> Vec<Box<dyn KeySource>> {
+    vec![Box::new(LocalKeySource {
+        path: test_data().join("targetskey"),
+    })]
+}
+
+/// Builds a repository on the simple-rsa root in which every role has the same version and
+/// expiration, by default 1 and a week from now. Top-level roles are signed with `root_key()` and
+/// delegated roles with `role_key()`.
+#[allow(unused)]
+pub struct RepoBuilder {
+    editor: RepositoryEditor,
+    version: NonZeroU64,
+    expires: DateTime<Utc>,
+}
+
+impl Default for RepoBuilder {
+    fn default() -> Self {
+        Self {
+            editor: RepositoryEditor::new(test_data().join("simple-rsa").join("root.json"))
+                .unwrap(),
+            version: NonZeroU64::new(1).unwrap(),
+            expires: Utc::now().checked_add_signed(Duration::days(7)).unwrap(),
+        }
+    }
+}
+
+#[allow(unused)]
+impl RepoBuilder {
+    /// Starts a repository with no targets or delegated roles
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the version of every role, including roles delegated after this call
+    pub fn version(mut self, version: u64) -> Self {
+        self.version = NonZeroU64::new(version).unwrap();
+        self
+    }
+
+    /// Sets the expiration of every role, including roles delegated after this call
+    pub fn expires(mut self, expires: DateTime<Utc>) -> Self {
+        self.expires = expires;
+        self
+    }
+
+    /// Lists `target` under `name` in the top-level targets role
+    pub fn target(mut self, name: &str, target: Target) -> Self {
+        self.editor
+            .add_target(TargetName::new(name).unwrap(), target)
+            .unwrap();
+        self
+    }
+
+    /// Lists the file at `path` under its file name in the top-level targets role
+    pub fn target_path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.editor.add_target_path(path).unwrap();
+        self
+    }
+
+    /// Delegates `paths` from the top-level targets role to a new, empty role `name`
+    pub fn delegate(mut self, name: &str, paths: PathSet) -> Self {
+        self.editor
+            .delegate_role(
+                name,
+                &role_key(),
+                paths,
+                NonZeroU64::new(1).unwrap(),
+                self.expires,
+                self.version,
+            )
+            .unwrap();
+        self
+    }
+
+    /// Gives access to the editor, for changes the builder doesn't cover
+    pub fn editor(&mut self) -> &mut RepositoryEditor {
+        &mut self.editor
+    }
+
+    /// Sets the versions and expirations of the top-level roles and signs the repository
+    pub fn sign(mut self) -> SignedRepository {
+        self.editor
+            .targets_version(self.version)
+            .unwrap()
+            .targets_expires(self.expires)
+            .unwrap()
+            .snapshot_version(self.version)
+            .snapshot_expires(self.expires)
+            .timestamp_version(self.version)
+            .timestamp_expires(self.expires);
+        self.editor.sign(&root_key()).unwrap()
+    }
+
+    /// Signs the repository and writes its metadata to `metadata_dir`
+    pub fn write<P: AsRef<Path>>(self, metadata_dir: P) -> SignedRepository {
+        let signed = self.sign();
+        signed.write(metadata_dir).unwrap();
+        signed
+    }
+}
@@ -0,0 +1,121 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+mod test_utils;
+
+use assert_cmd::Command;
+use chrono::{Duration, Utc};
+use std::fs::File;
+use tempfile::TempDir;
+use test_utils::dir_url;
+use tough::RepositoryLoader;
+
+#[test]
+// Ensure the targets of a repository with delegated roles can be moved to a new root
+fn transfer_metadata_command() {
+    let expires = Utc::now().checked_add_signed(Duration::days(7)).unwrap();
+    let base = test_utils::test_data().join("tuf-reference-impl");
+    let current_root = base.join("metadata").join("1.root.json");
+    let new_root = test_utils::test_data().join("simple-rsa").join("root.json");
+    let key = test_utils::test_data().join("snakeoil.pem");
+    let outdir = TempDir::new().unwrap();
+
+    let output = Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "transfer-metadata",
+            "-k",
+            key.to_str().unwrap(),
+            "-m",
+            dir_url(base.join("metadata")).as_str(),
+            "-t",
+            dir_url(base.join("targets")).as_str(),
+            "-r",
+            current_root.to_str().unwrap(),
+            "-n",
+            new_root.to_str().unwrap(),
+            "-o",
+            outdir.path().to_str().unwrap(),
+            "--concurrency",
+            "2",
+            "--targets-version",
+            "2",
+            "--targets-expires",
+            expires.to_rfc3339().as_str(),
+            "--snapshot-version",
+            "2",
+            "--snapshot-expires",
+            expires.to_rfc3339().as_str(),
+            "--timestamp-version",
+            "2",
+            "--timestamp-expires",
+            expires.to_rfc3339().as_str(),
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let summary = format!(
+        "Transferred 2 targets to {}\n",
+        outdir.path().join("metadata").display()
+    );
+    assert_eq!(String::from_utf8(output).unwrap(), summary);
+
+    // The transferred metadata is trusted by the new root, and keeps the top-level targets
+    let repo = RepositoryLoader::new(
+        File::open(&new_root).unwrap(),
+        dir_url(outdir.path().join("metadata")),
+        dir_url(base.join("targets")),
+    )
+    .load()
+    .unwrap();
+    assert_eq!(repo.targets().signed.version.get(), 2);
+    let mut names: Vec<_> = repo.all_targets().map(|(name, _)| name.raw()).collect();
+    names.sort_unstable();
+    assert_eq!(names, ["file1.txt", "file2.txt"]);
+}
+
+#[test]
+// Ensure nothing is written when the current repository can't be loaded
+fn transfer_metadata_command_fails_cleanly() {
+    let expires = Utc::now().checked_add_signed(Duration::days(7)).unwrap();
+    let base = test_utils::test_data().join("tuf-reference-impl");
+    let new_root = test_utils::test_data().join("simple-rsa").join("root.json");
+    let key = test_utils::test_data().join("snakeoil.pem");
+    let outdir = TempDir::new().unwrap();
+
+    // The simple-rsa root doesn't trust the reference repository's metadata
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "transfer-metadata",
+            "-k",
+            key.to_str().unwrap(),
+            "-m",
+            dir_url(base.join("metadata")).as_str(),
+            "-t",
+            dir_url(base.join("targets")).as_str(),
+            "-r",
+            new_root.to_str().unwrap(),
+            "-n",
+            new_root.to_str().unwrap(),
+            "-o",
+            outdir.path().to_str().unwrap(),
+            "--targets-version",
+            "2",
+            "--targets-expires",
+            expires.to_rfc3339().as_str(),
+            "--snapshot-version",
+            "2",
+            "--snapshot-expires",
+            expires.to_rfc3339().as_str(),
+            "--timestamp-version",
+            "2",
+            "--timestamp-expires",
+            expires.to_rfc3339().as_str(),
+        ])
+        .assert()
+        .failure();
+    assert!(!outdir.path().join("metadata").exists());
+}
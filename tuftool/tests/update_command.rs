@@ -7,21 +7,31 @@ use assert_cmd::assert::Assert;
 use assert_cmd::Command;
 use chrono::{DateTime, Duration, Utc};
 use std::fs::File;
-use std::path::Path;
+use std::num::NonZeroU64;
+use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 use test_utils::dir_url;
-use tough::{RepositoryLoader, TargetName};
+use tough::editor::RepositoryEditor;
+use tough::key_source::{KeySource, LocalKeySource};
+use tough::schema::{PathPattern, PathSet};
+use tough::{Repository, RepositoryLoader, TargetName};
 
 fn create_repo<P: AsRef<Path>>(repo_dir: P) {
+    create_repo_with_targets(
+        repo_dir,
+        test_utils::test_data()
+            .join("tuf-reference-impl")
+            .join("targets"),
+    );
+}
+
+fn create_repo_with_targets<P: AsRef<Path>>(repo_dir: P, targets_input_dir: PathBuf) {
     let timestamp_expiration = Utc::now().checked_add_signed(Duration::days(1)).unwrap();
     let timestamp_version: u64 = 31;
     let snapshot_expiration = Utc::now().checked_add_signed(Duration::days(2)).unwrap();
     let snapshot_version: u64 = 25;
     let targets_expiration = Utc::now().checked_add_signed(Duration::days(3)).unwrap();
     let targets_version: u64 = 17;
-    let targets_input_dir = test_utils::test_data()
-        .join("tuf-reference-impl")
-        .join("targets");
     let root_json = test_utils::test_data().join("simple-rsa").join("root.json");
     let root_key = test_utils::test_data().join("snakeoil.pem");
 
@@ -252,6 +262,52 @@ fn update_with_incorrect_key() {
         .failure();
 }
 
+#[test]
+// Ensure the update command checks that its keys can meet each role's threshold before it starts
+// adding targets
+fn update_with_insufficient_keys() {
+    let base = test_utils::test_data().join("tuf-reference-impl");
+    let root_json = base.join("metadata").join("1.root.json");
+    let bad_key = test_utils::test_data().join("snakeoil.pem");
+    let outdir = TempDir::new().unwrap();
+
+    let output = Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "update",
+            "--outdir",
+            outdir.path().to_str().unwrap(),
+            "-k",
+            bad_key.to_str().unwrap(),
+            "--root",
+            root_json.to_str().unwrap(),
+            "--metadata-url",
+            dir_url(base.join("metadata")).as_str(),
+            // Hashing these targets would fail, so the keys must be checked first
+            "--add-targets",
+            "/targets/do/not/exist",
+            "--targets-expires",
+            "in 7 days",
+            "--targets-version",
+            "1234",
+            "--snapshot-expires",
+            "in 7 days",
+            "--snapshot-version",
+            "1234",
+            "--timestamp-expires",
+            "in 7 days",
+            "--timestamp-version",
+            "1234",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr
+        .contains("The given keys can make 0 of the 1 signatures required for role 'targets'"));
+    assert!(!outdir.path().join("metadata").exists());
+}
+
 #[test]
 // Ensure we fail if no key is provided
 fn update_with_no_key() {
@@ -386,3 +442,562 @@ fn update_command_expired_repo_allow() {
     assert_eq!(repo.targets().signed.expires, update_expected.5);
     assert_eq!(repo.targets().signed.version.get(), update_expected.6);
 }
+
+/// Runs `tuftool update` on the repo in `repo_dir`, with the extra `args`
+fn update_with_args(repo_dir: &Path, update_out: &Path, args: &[&str]) -> Assert {
+    let root_json = test_utils::test_data().join("simple-rsa").join("root.json");
+    let root_key = test_utils::test_data().join("snakeoil.pem");
+    let expiration = Utc::now().checked_add_signed(Duration::days(4)).unwrap();
+    Command::cargo_bin("tuftool")
+        .unwrap()
+        .args([
+            "update",
+            "-o",
+            update_out.to_str().unwrap(),
+            "-k",
+            root_key.to_str().unwrap(),
+            "--root",
+            root_json.to_str().unwrap(),
+            "--metadata-url",
+            dir_url(repo_dir.join("metadata")).as_str(),
+            "--targets-expires",
+            expiration.to_rfc3339().as_str(),
+            "--targets-version",
+            "170",
+            "--snapshot-expires",
+            expiration.to_rfc3339().as_str(),
+            "--snapshot-version",
+            "250",
+            "--timestamp-expires",
+            expiration.to_rfc3339().as_str(),
+            "--timestamp-version",
+            "310",
+        ])
+        .args(args)
+        .assert()
+}
+
+#[test]
+// Ensure a repo's targets can be replaced with the targets of another repo
+fn update_command_from_repo() {
+    let root_json = test_utils::test_data().join("simple-rsa").join("root.json");
+    let staging_dir = TempDir::new().unwrap();
+    let production_dir = TempDir::new().unwrap();
+    create_repo(staging_dir.path());
+    create_repo_with_targets(
+        production_dir.path(),
+        test_utils::test_data().join("targets"),
+    );
+    let update_out = TempDir::new().unwrap();
+
+    // Promote the staging targets into the production repo
+    update_with_args(
+        production_dir.path(),
+        update_out.path(),
+        &[
+            "--from-repo",
+            dir_url(staging_dir.path().join("metadata")).as_str(),
+            "--from-root",
+            root_json.to_str().unwrap(),
+        ],
+    )
+    .success();
+
+    let load = |metadata_dir: &Path| {
+        RepositoryLoader::new(
+            File::open(&root_json).unwrap(),
+            dir_url(metadata_dir),
+            dir_url(metadata_dir.join("targets")),
+        )
+        .load()
+        .unwrap()
+    };
+    let staging = load(&staging_dir.path().join("metadata"));
+    let promoted = load(&update_out.path().join("metadata"));
+
+    // The production repo now lists exactly the staging targets
+    assert_eq!(
+        promoted.targets().signed.targets,
+        staging.targets().signed.targets
+    );
+    assert!(promoted
+        .targets()
+        .signed
+        .targets
+        .contains_key(&TargetName::new("file1.txt").unwrap()));
+    assert_eq!(promoted.targets().signed.version.get(), 170);
+}
+
+#[test]
+// Ensure `--from-repo` takes the targets listed by the other repo's delegated roles too
+fn update_command_from_repo_with_delegations() {
+    let root_json = test_utils::test_data().join("simple-rsa").join("root.json");
+    let root_key: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource {
+        path: test_utils::test_data().join("snakeoil.pem"),
+    })];
+    let role_key: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource {
+        path: test_utils::test_data().join("targetskey"),
+    })];
+    let targets_dir = test_utils::test_data()
+        .join("tuf-reference-impl")
+        .join("targets");
+    let expires = Utc::now().checked_add_signed(Duration::days(1)).unwrap();
+    let one = NonZeroU64::new(1).unwrap();
+
+    // The staging repo lists file1.txt itself, and delegates file2.txt to role1
+    let staging_dir = TempDir::new().unwrap();
+    let mut editor = RepositoryEditor::new(&root_json).unwrap();
+    editor
+        .snapshot_version(one)
+        .snapshot_expires(expires)
+        .timestamp_version(one)
+        .timestamp_expires(expires)
+        .targets_version(one)
+        .unwrap()
+        .targets_expires(expires)
+        .unwrap()
+        .add_target_path(targets_dir.join("file1.txt"))
+        .unwrap()
+        .delegate_role(
+            "role1",
+            role_key,
+            PathSet::Paths(vec![PathPattern::new("file2.txt").unwrap()]),
+            one,
+            expires,
+            one,
+        )
+        .unwrap()
+        .sign_targets_editor(root_key)
+        .unwrap()
+        .change_delegated_targets("role1")
+        .unwrap()
+        .add_target_path(targets_dir.join("file2.txt"))
+        .unwrap()
+        .targets_version(one)
+        .unwrap()
+        .targets_expires(expires)
+        .unwrap()
+        .sign_targets_editor(role_key)
+        .unwrap()
+        .change_delegated_targets("targets")
+        .unwrap();
+    editor
+        .sign(root_key)
+        .unwrap()
+        .write(staging_dir.path().join("metadata"))
+        .unwrap();
+
+    let production_dir = TempDir::new().unwrap();
+    create_repo_with_targets(
+        production_dir.path(),
+        test_utils::test_data().join("targets"),
+    );
+    let update_out = TempDir::new().unwrap();
+    update_with_args(
+        production_dir.path(),
+        update_out.path(),
+        &[
+            "--from-repo",
+            dir_url(staging_dir.path().join("metadata")).as_str(),
+            "--from-root",
+            root_json.to_str().unwrap(),
+        ],
+    )
+    .success();
+
+    let promoted = load_updated(update_out.path());
+    let mut names: Vec<_> = promoted
+        .targets()
+        .signed
+        .targets
+        .keys()
+        .map(TargetName::raw)
+        .collect();
+    names.sort_unstable();
+    assert_eq!(names, ["file1.txt", "file2.txt"]);
+}
+
+#[test]
+// Ensure targets can't be both added from a directory and taken from another repo
+fn update_command_from_repo_conflicts_with_add_targets() {
+    let root_json = test_utils::test_data().join("simple-rsa").join("root.json");
+    let repo_dir = TempDir::new().unwrap();
+    create_repo(repo_dir.path());
+    let update_out = TempDir::new().unwrap();
+
+    update_with_args(
+        repo_dir.path(),
+        update_out.path(),
+        &[
+            "--from-repo",
+            dir_url(repo_dir.path().join("metadata")).as_str(),
+            "--from-root",
+            root_json.to_str().unwrap(),
+            "--add-targets",
+            test_utils::test_data().join("targets").to_str().unwrap(),
+        ],
+    )
+    .failure();
+}
+
+#[test]
+// Ensure `--prune-targets` removes the targets that aren't in the `--add-targets` directory, and
+// that without it, targets are only added
+fn update_command_prune_targets() {
+    let repo_dir = TempDir::new().unwrap();
+    create_repo(repo_dir.path());
+    let new_targets = test_utils::test_data().join("targets");
+    let target_names = |repo: &Repository| {
+        let mut names = repo
+            .targets()
+            .signed
+            .targets
+            .keys()
+            .map(|name| name.raw().to_owned())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    };
+    let mut expected = std::fs::read_dir(&new_targets)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    expected.sort();
+
+    let added_out = TempDir::new().unwrap();
+    update_with_args(
+        repo_dir.path(),
+        added_out.path(),
+        &["--add-targets", new_targets.to_str().unwrap()],
+    )
+    .success();
+    let added = load_updated(added_out.path());
+    assert!(added
+        .targets()
+        .signed
+        .targets
+        .contains_key(&TargetName::new("file1.txt").unwrap()));
+    assert!(target_names(&added).len() > expected.len());
+
+    let pruned_out = TempDir::new().unwrap();
+    let assert = update_with_args(
+        repo_dir.path(),
+        pruned_out.path(),
+        &[
+            "--add-targets",
+            new_targets.to_str().unwrap(),
+            "--prune-targets",
+        ],
+    )
+    .success();
+    assert_eq!(
+        String::from_utf8(assert.get_output().stdout.clone()).unwrap(),
+        "Removed target 'file1.txt'\nRemoved target 'file2.txt'\nRemoved target 'file3.txt'\n"
+    );
+    assert_eq!(target_names(&load_updated(pruned_out.path())), expected);
+
+    // Pruning needs a directory to prune to
+    update_with_args(repo_dir.path(), pruned_out.path(), &["--prune-targets"]).failure();
+}
+
+#[test]
+// Ensure `--spec-version` bumps the spec version and later updates preserve it
+fn update_command_spec_version() {
+    let root_json = test_utils::test_data().join("simple-rsa").join("root.json");
+    let repo_dir = TempDir::new().unwrap();
+    create_repo(repo_dir.path());
+    let bumped_out = TempDir::new().unwrap();
+    let preserved_out = TempDir::new().unwrap();
+
+    update_with_args(
+        repo_dir.path(),
+        bumped_out.path(),
+        &["--spec-version", "1.0.31"],
+    )
+    .success();
+    update_with_args(bumped_out.path(), preserved_out.path(), &[]).success();
+
+    let metadata_dir = preserved_out.path().join("metadata");
+    let repo = RepositoryLoader::new(
+        File::open(&root_json).unwrap(),
+        dir_url(&metadata_dir),
+        dir_url(metadata_dir.join("targets")),
+    )
+    .load()
+    .unwrap();
+    assert_eq!(repo.targets().signed.spec_version, "1.0.31");
+    assert_eq!(repo.snapshot().signed.spec_version, "1.0.31");
+    assert_eq!(repo.timestamp().signed.spec_version, "1.0.31");
+
+    // A spec version with a different major version is rejected
+    let rejected_out = TempDir::new().unwrap();
+    update_with_args(
+        repo_dir.path(),
+        rejected_out.path(),
+        &["--spec-version", "2.0.0"],
+    )
+    .failure();
+}
+
+/// Creates a repository whose top-level targets role delegates to `role1`, which is signed with
+/// `targetskey` and expires in one day.
+fn create_delegated_repo(repo_dir: &Path) {
+    let root_key: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource {
+        path: test_utils::test_data().join("snakeoil.pem"),
+    })];
+    let role_key: &[Box<dyn KeySource>] = &[Box::new(LocalKeySource {
+        path: test_utils::test_data().join("targetskey"),
+    })];
+    let expires = Utc::now().checked_add_signed(Duration::days(1)).unwrap();
+    let version = NonZeroU64::new(1).unwrap();
+
+    let mut editor =
+        RepositoryEditor::new(test_utils::test_data().join("simple-rsa").join("root.json"))
+            .unwrap();
+    editor
+        .snapshot_version(version)
+        .snapshot_expires(expires)
+        .timestamp_version(version)
+        .timestamp_expires(expires)
+        .targets_version(version)
+        .unwrap()
+        .targets_expires(expires)
+        .unwrap()
+        .delegate_role(
+            "role1",
+            role_key,
+            PathSet::Paths(vec![PathPattern::new("*").unwrap()]),
+            NonZeroU64::new(1).unwrap(),
+            expires,
+            version,
+        )
+        .unwrap();
+    editor
+        .sign(root_key)
+        .unwrap()
+        .write(repo_dir.join("metadata"))
+        .unwrap();
+}
+
+fn load_updated(update_out: &Path) -> Repository {
+    let root_json = test_utils::test_data().join("simple-rsa").join("root.json");
+    RepositoryLoader::new(
+        File::open(root_json).unwrap(),
+        dir_url(update_out.join("metadata")),
+        dir_url(update_out.join("targets")),
+    )
+    .load()
+    .unwrap()
+}
+
+#[test]
+// Ensure `--delegated-expires` bumps delegated roles we have keys for and skips the others
+fn update_command_delegated_expires() {
+    let repo_dir = TempDir::new().unwrap();
+    create_delegated_repo(repo_dir.path());
+    let delegated_expires = Utc::now().checked_add_signed(Duration::days(30)).unwrap();
+    let role_key = test_utils::test_data().join("targetskey");
+
+    // With the role's key, role1 gets the new expiration and a new version
+    let stamped_out = TempDir::new().unwrap();
+    let assert = update_with_args(
+        repo_dir.path(),
+        stamped_out.path(),
+        &[
+            "-k",
+            role_key.to_str().unwrap(),
+            "--delegated-expires",
+            delegated_expires.to_rfc3339().as_str(),
+        ],
+    )
+    .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("Updated delegated role 'role1'"));
+    let repo = load_updated(stamped_out.path());
+    let role1 = &repo
+        .delegated_role("role1")
+        .unwrap()
+        .targets
+        .as_ref()
+        .unwrap()
+        .signed;
+    assert_eq!(role1.expires, delegated_expires);
+    assert_eq!(role1.version.get(), 2);
+
+    // Without it, role1 is skipped and left as it was
+    let skipped_out = TempDir::new().unwrap();
+    update_with_args(
+        repo_dir.path(),
+        skipped_out.path(),
+        &[
+            "--delegated-role",
+            "role1",
+            "--delegated-expires",
+            delegated_expires.to_rfc3339().as_str(),
+        ],
+    )
+    .success();
+    let repo = load_updated(skipped_out.path());
+    let role1 = &repo
+        .delegated_role("role1")
+        .unwrap()
+        .targets
+        .as_ref()
+        .unwrap()
+        .signed;
+    assert_ne!(role1.expires, delegated_expires);
+    assert_eq!(role1.version.get(), 1);
+}
+
+#[test]
+// Ensure `--strict-warnings` fails the update, without writing it, when the updated metadata
+// expires within the warning window
+fn update_command_strict_warnings() {
+    let repo_dir = TempDir::new().unwrap();
+    create_repo(repo_dir.path());
+
+    // `update_with_args` sets expirations 4 days out, inside the default 7 day window
+    let strict_out = TempDir::new().unwrap();
+    let assert =
+        update_with_args(repo_dir.path(), strict_out.path(), &["--strict-warnings"]).failure();
+    let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+    assert!(stderr.contains("WARNING: Role 'targets' expires soon"));
+    assert!(!strict_out.path().join("metadata").exists());
+
+    let update_out = TempDir::new().unwrap();
+    update_with_args(
+        repo_dir.path(),
+        update_out.path(),
+        &["--strict-warnings", "--warn-expiring-before", "in 1 days"],
+    )
+    .success();
+    load_updated(update_out.path());
+}
+
+#[test]
+// Ensure `--check-targets-url` fails the update, before any metadata is written, if a newly
+// listed target isn't available there, and doesn't check targets that were already listed
+fn update_command_check_targets_url() {
+    let repo_dir = TempDir::new().unwrap();
+    create_repo(repo_dir.path());
+    let new_targets = test_utils::test_data().join("targets");
+    let empty_dir = TempDir::new().unwrap();
+
+    let missing_out = TempDir::new().unwrap();
+    let assert = update_with_args(
+        repo_dir.path(),
+        missing_out.path(),
+        &[
+            "--add-targets",
+            new_targets.to_str().unwrap(),
+            "--check-targets-url",
+            dir_url(empty_dir.path()).as_str(),
+        ],
+    )
+    .failure();
+    let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+    assert!(stderr.contains("file4.txt, file5.txt, file6.txt"));
+    assert!(!missing_out.path().join("metadata").exists());
+
+    // The added targets are linked into the output directory before the check
+    let available_out = TempDir::new().unwrap();
+    update_with_args(
+        repo_dir.path(),
+        available_out.path(),
+        &[
+            "--add-targets",
+            new_targets.to_str().unwrap(),
+            "--check-targets-url",
+            dir_url(available_out.path().join("targets")).as_str(),
+        ],
+    )
+    .success();
+
+    let unchanged_out = TempDir::new().unwrap();
+    update_with_args(
+        repo_dir.path(),
+        unchanged_out.path(),
+        &["--check-targets-url", dir_url(empty_dir.path()).as_str()],
+    )
+    .success();
+}
+
+#[test]
+// Ensure `--check-targets-url` finds targets whose names must be percent-encoded in their URLs
+fn update_command_check_targets_url_encoded_names() {
+    let repo_dir = TempDir::new().unwrap();
+    create_repo(repo_dir.path());
+    let new_targets = TempDir::new().unwrap();
+    std::fs::write(new_targets.path().join("a file #1.txt"), "hello").unwrap();
+
+    let update_out = TempDir::new().unwrap();
+    update_with_args(
+        repo_dir.path(),
+        update_out.path(),
+        &[
+            "--add-targets",
+            new_targets.path().to_str().unwrap(),
+            "--check-targets-url",
+            dir_url(update_out.path().join("targets")).as_str(),
+        ],
+    )
+    .success();
+    let repo = load_updated(update_out.path());
+    assert!(repo
+        .targets()
+        .signed
+        .targets
+        .contains_key(&TargetName::new("a file #1.txt").unwrap()));
+}
+
+#[test]
+// Ensure `--sign-with-key-id` picks the key out of the key source it names, and leaves the other
+// `--key` sources alone
+fn update_command_sign_with_key_id() {
+    let repo_dir = TempDir::new().unwrap();
+    create_repo(repo_dir.path());
+    let key_id = |path: PathBuf| {
+        LocalKeySource { path }
+            .as_sign()
+            .unwrap()
+            .tuf_key()
+            .key_id()
+            .unwrap()
+    };
+
+    // A bundle holding both snakeoil keys; `update_with_args` also signs with `snakeoil.pem`
+    let bundle_dir = TempDir::new().unwrap();
+    let bundle = bundle_dir.path().join("bundle.pem");
+    let mut keys = std::fs::read(test_utils::test_data().join("snakeoil.pem")).unwrap();
+    keys.extend(std::fs::read(test_utils::test_data().join("snakeoil_2.pem")).unwrap());
+    std::fs::write(&bundle, keys).unwrap();
+
+    let snakeoil_2_id = key_id(test_utils::test_data().join("snakeoil_2.pem"));
+    let update_out = TempDir::new().unwrap();
+    update_with_args(
+        repo_dir.path(),
+        update_out.path(),
+        &[
+            "--sign-with-key-id",
+            &format!("{}={}", bundle.display(), hex::encode(&snakeoil_2_id)),
+        ],
+    )
+    .success();
+    load_updated(update_out.path());
+
+    // The bundle doesn't hold the targets key
+    let targets_key_id = key_id(test_utils::test_data().join("targetskey"));
+    let missing_out = TempDir::new().unwrap();
+    let assert = update_with_args(
+        repo_dir.path(),
+        missing_out.path(),
+        &[
+            "--sign-with-key-id",
+            &format!("{}={}", bundle.display(), hex::encode(&targets_key_id)),
+        ],
+    )
+    .failure();
+    let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+    assert!(stderr.contains("does not hold a key with key ID"));
+    assert!(!missing_out.path().join("metadata").exists());
+}
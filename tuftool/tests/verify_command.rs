@@ -0,0 +1,253 @@
+// Copyright 2023 Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+mod test_utils;
+
+use assert_cmd::Command;
+use chrono::{DateTime, Duration, Utc};
+use std::num::NonZeroU64;
+use std::path::Path;
+use tempfile::TempDir;
+use test_utils::{dir_url, role_key, root_key, RepoBuilder};
+use tough::schema::{PathPattern, PathSet, Target};
+use tough::TargetName;
+
+/// Creates a repository in which both the top-level targets role and its delegated role `role1`
+/// list `file1.txt`.
+fn create_duplicate_repo(outdir: &Path) {
+    let expires = Utc::now().checked_add_signed(Duration::days(7)).unwrap();
+    let version = NonZeroU64::new(1).unwrap();
+    let target = Target::from_path(
+        test_utils::test_data()
+            .join("tuf-reference-impl")
+            .join("targets")
+            .join("file1.txt"),
+    )
+    .unwrap();
+
+    let mut builder = RepoBuilder::new()
+        .expires(expires)
+        .target("file1.txt", target.clone())
+        .delegate(
+            "role1",
+            PathSet::Paths(vec![PathPattern::new("*").unwrap()]),
+        );
+    builder
+        .editor()
+        .targets_version(version)
+        .unwrap()
+        .targets_expires(expires)
+        .unwrap()
+        .sign_targets_editor(&root_key())
+        .unwrap()
+        .change_delegated_targets("role1")
+        .unwrap()
+        .add_target(TargetName::new("file1.txt").unwrap(), target)
+        .unwrap()
+        .targets_version(version)
+        .unwrap()
+        .targets_expires(expires)
+        .unwrap()
+        .sign_targets_editor(&role_key())
+        .unwrap()
+        .change_delegated_targets("targets")
+        .unwrap();
+    builder.write(outdir.join("metadata"));
+}
+
+/// Creates a repository whose metadata all expires at `expires`, in which the top-level targets
+/// role delegates `role1_paths` to `role1`.
+fn create_repo(outdir: &Path, expires: DateTime<Utc>, role1_paths: PathSet) {
+    RepoBuilder::new()
+        .expires(expires)
+        .delegate("role1", role1_paths)
+        .write(outdir.join("metadata"));
+}
+
+fn verify_command(repo_dir: &Path) -> Command {
+    let root_json = test_utils::test_data().join("simple-rsa").join("root.json");
+    let mut cmd = Command::cargo_bin("tuftool").unwrap();
+    cmd.args([
+        "verify",
+        "--root",
+        root_json.to_str().unwrap(),
+        "--metadata-url",
+        dir_url(repo_dir.join("metadata")).as_str(),
+    ]);
+    cmd
+}
+
+#[test]
+// Ensure a target listed by more than one role is only an error with `--strict`
+fn verify_strict_duplicate_targets() {
+    let repo_dir = TempDir::new().unwrap();
+    create_duplicate_repo(repo_dir.path());
+
+    verify_command(repo_dir.path()).assert().success();
+
+    let output = verify_command(repo_dir.path())
+        .arg("--strict")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Target 'file1.txt' is listed by roles: targets, role1"));
+}
+
+#[test]
+// Ensure the scheme of each key trusted for a role is shown, including delegated roles' keys
+fn verify_shows_key_schemes() {
+    let repo_dir = TempDir::new().unwrap();
+    let expires = Utc::now().checked_add_signed(Duration::days(30)).unwrap();
+    create_repo(
+        repo_dir.path(),
+        expires,
+        PathSet::Paths(vec![PathPattern::new("*").unwrap()]),
+    );
+
+    let output = verify_command(repo_dir.path()).output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let scheme_of = |role: &str| {
+        stdout
+            .lines()
+            .find(|line| line.starts_with(&format!("Role '{role}' key ")))
+            .and_then(|line| line.rsplit(' ').next())
+            .map(str::to_owned)
+    };
+    assert_eq!(scheme_of("root").as_deref(), Some("rsassa-pss-sha256"));
+    assert_eq!(scheme_of("timestamp").as_deref(), Some("rsassa-pss-sha256"));
+    assert_eq!(scheme_of("role1").as_deref(), Some("ed25519"));
+}
+
+#[test]
+// Ensure roles expiring within the warning window are a warning, and only an error with
+// `--strict-warnings`
+fn verify_strict_warnings_near_expiry() {
+    let repo_dir = TempDir::new().unwrap();
+    let expires = Utc::now().checked_add_signed(Duration::days(1)).unwrap();
+    create_repo(
+        repo_dir.path(),
+        expires,
+        PathSet::Paths(vec![PathPattern::new("*").unwrap()]),
+    );
+
+    let output = verify_command(repo_dir.path()).output().unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("WARNING: Role 'timestamp' expires soon"));
+    assert!(stderr.contains("WARNING: Role 'role1' expires soon"));
+
+    let output = verify_command(repo_dir.path())
+        .arg("--strict-warnings")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("WARNING: Role 'snapshot' expires soon"));
+    assert!(stderr.contains("--strict-warnings"));
+
+    verify_command(repo_dir.path())
+        .args(["--strict-warnings", "--warn-expiring-before", "in 1 hours"])
+        .assert()
+        .success();
+}
+
+#[test]
+// Ensure a delegated role trusted for no paths is an error with `--strict-warnings`
+fn verify_strict_warnings_empty_paths() {
+    let repo_dir = TempDir::new().unwrap();
+    let expires = Utc::now().checked_add_signed(Duration::days(30)).unwrap();
+    create_repo(repo_dir.path(), expires, PathSet::Paths(Vec::new()));
+
+    verify_command(repo_dir.path()).assert().success();
+
+    let output = verify_command(repo_dir.path())
+        .arg("--strict-warnings")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("WARNING: Delegated role 'role1' is trusted for no paths"));
+}
+
+#[test]
+// Ensure a target listed by more than one role is an error with `--strict-warnings`
+fn verify_strict_warnings_duplicate_targets() {
+    let repo_dir = TempDir::new().unwrap();
+    create_duplicate_repo(repo_dir.path());
+
+    let output = verify_command(repo_dir.path())
+        .args(["--strict-warnings", "--warn-expiring-before", "in 1 hours"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("WARNING: Target 'file1.txt' is listed by roles: targets, role1"));
+    assert!(!stderr.contains("expires soon"));
+}
+
+/// Writes an inventory listing each of `targets`, given as a name and its sha256 in hex, to
+/// `path`.
+fn write_inventory(path: &Path, targets: &[(&str, &str)]) {
+    let targets: Vec<_> = targets
+        .iter()
+        .map(|(name, sha256)| serde_json::json!({ "name": name, "hashes": { "sha256": sha256 } }))
+        .collect();
+    std::fs::write(
+        path,
+        serde_json::to_vec(&serde_json::json!({ "targets": targets })).unwrap(),
+    )
+    .unwrap();
+}
+
+#[test]
+// Ensure `--expect-inventory` passes for a matching inventory and reports each drifted target
+fn verify_expect_inventory() {
+    let repo_dir = TempDir::new().unwrap();
+    create_duplicate_repo(repo_dir.path());
+    let file1 = std::fs::read(
+        test_utils::test_data()
+            .join("tuf-reference-impl")
+            .join("targets")
+            .join("file1.txt"),
+    )
+    .unwrap();
+    let file1_sha256 = hex::encode(ring::digest::digest(&ring::digest::SHA256, &file1));
+    let inventory = repo_dir.path().join("inventory.json");
+
+    write_inventory(&inventory, &[("file1.txt", &file1_sha256)]);
+    verify_command(repo_dir.path())
+        .arg("--expect-inventory")
+        .arg(&inventory)
+        .assert()
+        .success();
+
+    let wrong_sha256 = "0".repeat(64);
+    write_inventory(
+        &inventory,
+        &[("file1.txt", &wrong_sha256), ("file2.txt", &file1_sha256)],
+    );
+    let output = verify_command(repo_dir.path())
+        .arg("--expect-inventory")
+        .arg(&inventory)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(&format!(
+        "Target 'file1.txt' has sha256 {} but the inventory expects {}",
+        file1_sha256, wrong_sha256
+    )));
+    assert!(stderr.contains("Target 'file2.txt' is in the inventory but not the repository"));
+
+    write_inventory(&inventory, &[]);
+    let output = verify_command(repo_dir.path())
+        .arg("--expect-inventory")
+        .arg(&inventory)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Target 'file1.txt' is in the repository but not the inventory"));
+}